@@ -1088,26 +1088,41 @@ async function loadAccountBalancesAsOf(year, month) {
         return;
     }
 
-    if (!balances || balances.length === 0) {
+    // Filter out the synthetic NONE account — it carries no real balance and
+    // would only confuse the reconciliation view.
+    const assets = (balances?.assets || []).filter((b) => b.account_code !== 'NONE');
+    const liabilities = balances?.liabilities || [];
+
+    if (assets.length === 0 && liabilities.length === 0) {
         tbody.innerHTML = `<tr><td colspan="2" class="account-balances-empty">${
             i18n.t('dashboard.balances_empty') || 'No accounts'
         }</td></tr>`;
         return;
     }
 
-    // Filter out the synthetic NONE account — it carries no real balance and
-    // would only confuse the reconciliation view.
-    const visible = balances.filter((b) => b.account_code !== 'NONE');
-
-    tbody.innerHTML = visible
-        .map((b) => {
-            const cls = b.balance < 0 ? 'balance-negative' : 'balance-positive';
-            return `<tr>
-                <td>${escapeHtml(b.account_name)}</td>
-                <td class="balance-col ${cls}">¥${b.balance.toLocaleString()}</td>
+    // Liability balances are the amount owed, so they are shown negated.
+    // Account balances are in the account's currency, net worth in the base one.
+    const row = (name, amount, currency) => {
+        const cls = amount < 0 ? 'balance-negative' : 'balance-positive';
+        return `<tr>
+                <td>${escapeHtml(name)}</td>
+                <td class="balance-col ${cls}">${escapeHtml(formatMinorAmount(amount, currency))}</td>
             </tr>`;
-        })
-        .join('');
+    };
+
+    tbody.innerHTML = [
+        ...assets.map((b) => row(b.account_name, b.balance, b.currency_code)),
+        ...liabilities.map((b) => row(b.account_name, -b.balance, b.currency_code)),
+        row(i18n.t('dashboard.net_worth') || 'Net worth', balances.net_worth, balances.base_currency),
+    ].join('');
+}
+
+// Amounts arrive in minor units (cents for USD), so scale by the currency's
+// fraction digits before formatting.
+function formatMinorAmount(amount, currency) {
+    const format = new Intl.NumberFormat(undefined, { style: 'currency', currency: currency || 'JPY' });
+    const digits = format.resolvedOptions().maximumFractionDigits;
+    return format.format(amount / 10 ** digits);
 }
 
 // Tiny HTML escaper used by the balances table. Names come from user-typed
@@ -38,6 +38,7 @@ export const API_ERROR_CODES = Object.freeze({
     ADMIN_PROTECTED: 'admin_protected',
     VALIDATION: 'validation',
     DATABASE: 'database',
+    JOURNAL_CONFLICT: 'journal_conflict',
 });
 
 /**
@@ -16,6 +16,7 @@ console.log('=== RECURRING-RULE.JS LOADED ===');
 let currentUserId = null;
 let categoryTree = [];
 let pendingDeleteRule = null; // { rule_id, occurrence_count, name } awaiting modal choice
+let exceptionsRule = null; // rule whose skip/move exceptions are open in the modal
 
 document.addEventListener('DOMContentLoaded', async () => {
     createMenuBar('management');
@@ -48,12 +49,14 @@ document.addEventListener('DOMContentLoaded', async () => {
         await loadShops();
 
         setupCycleKindToggle();
+        setupNoEndDateToggle();
         setupCategoryChainHandlers();
         setupDetailTaxCalculation();
         setupBoundedFieldCounters();
         setupFormSubmit();
         setupResetButton();
         setupDeleteModal();
+        setupExceptionsModal();
         await loadRules();
 
         // Default start_date to today, end_date to one year out
@@ -158,6 +161,21 @@ function updateCycleVisibility() {
     document.getElementById('day-of-week-group').classList.toggle('visible', isMonthNth);
 }
 
+// Open-ended rules have no end date; the backend keeps a rolling window
+// of occurrences generated instead.
+function setupNoEndDateToggle() {
+    const noEndDate = document.getElementById('no-end-date');
+    noEndDate.addEventListener('change', updateEndDateState);
+    updateEndDateState();
+}
+
+function updateEndDateState() {
+    const noEndDate = document.getElementById('no-end-date').checked;
+    const endDate = document.getElementById('end-date');
+    endDate.disabled = noEndDate;
+    endDate.required = !noEndDate;
+}
+
 // ----- Category 1 → 2 → 3 dependent dropdowns -----
 
 function setupCategoryChainHandlers() {
@@ -274,7 +292,9 @@ function setupFormSubmit() {
             holiday_shift_type: parseInt(document.getElementById('holiday-shift-type').value, 10),
 
             start_date: document.getElementById('start-date').value,
-            end_date: document.getElementById('end-date').value,
+            end_date: document.getElementById('no-end-date').checked
+                ? null
+                : document.getElementById('end-date').value,
 
             shop_id: intOrNull(document.getElementById('shop').value),
             category1_code: document.getElementById('category1').value,
@@ -284,8 +304,10 @@ function setupFormSubmit() {
             tax_rounding_type: parseInt(document.getElementById('tax-rounding-type').value, 10),
             tax_included_type: parseInt(document.getElementById('tax-included-type').value, 10),
             header_memo: stringOrNull(document.getElementById('header-memo').value),
+            is_estimated: document.getElementById('is-estimated').checked,
 
-            detail: {
+            // The form edits a single DETAIL line; the backend accepts several.
+            details: [{
                 category1_code: document.getElementById('category1').value,
                 category2_code: stringOrNull(document.getElementById('category2').value),
                 category3_code: stringOrNull(document.getElementById('category3').value),
@@ -297,7 +319,7 @@ function setupFormSubmit() {
                     document.getElementById('amount-including-tax').value
                 ),
                 detail_memo: stringOrNull(document.getElementById('detail-memo').value),
-            },
+            }],
         };
 
         // Client-side guards (server validates anyway; this is just UX)
@@ -305,7 +327,7 @@ function setupFormSubmit() {
             showResult('error', i18n.t('recurring_rule.err_category1_required') || 'Category 1 is required.');
             return;
         }
-        if (!request.detail.item_name.trim()) {
+        if (!request.details[0].item_name.trim()) {
             showResult('error', i18n.t('recurring_rule.err_item_name_required') || 'Item name is required.');
             return;
         }
@@ -333,8 +355,8 @@ function setupFormSubmit() {
 
         const ruleName = request.rule_name || '';
         const headerMemo = request.header_memo || '';
-        const itemName = request.detail.item_name;
-        const detailMemo = request.detail.detail_memo || '';
+        const itemName = request.details[0].item_name;
+        const detailMemo = request.details[0].detail_memo || '';
 
         if ([...ruleName].length > MAX_RULE_NAME_LEN) {
             showMaxLengthError(ruleNameInput, i18n.t('recurring_rule.rule_name'), MAX_RULE_NAME_LEN);
@@ -403,6 +425,7 @@ function setupResetButton() {
     document.getElementById('reset-btn').addEventListener('click', () => {
         document.getElementById('recurring-rule-form').reset();
         hideResult();
+        updateEndDateState();
         // form.reset() does not fire 'input', so refresh counters manually.
         ['rule-name', 'header-memo', 'item-name', 'detail-memo'].forEach((id) => {
             const el = document.getElementById(id);
@@ -475,12 +498,16 @@ function renderRules(rules) {
         tr.appendChild(tdCycle);
 
         const tdRange = document.createElement('td');
-        tdRange.textContent = `${r.start_date} 〜 ${r.end_date}`;
+        const endLabel = r.end_date || i18n.t('recurring_rule.no_end_date') || 'No end date';
+        tdRange.textContent = `${r.start_date} 〜 ${endLabel}`;
         tr.appendChild(tdRange);
 
         const tdAmount = document.createElement('td');
         tdAmount.className = 'col-amount';
         tdAmount.textContent = r.total_amount.toLocaleString();
+        if (r.is_estimated) {
+            tdAmount.textContent += ` (${i18n.t('recurring_rule.estimated_badge') || 'est.'})`;
+        }
         tr.appendChild(tdAmount);
 
         const tdCount = document.createElement('td');
@@ -489,6 +516,20 @@ function renderRules(rules) {
         tr.appendChild(tdCount);
 
         const tdActions = document.createElement('td');
+        if (r.is_estimated) {
+            const suggestBtn = document.createElement('button');
+            suggestBtn.type = 'button';
+            suggestBtn.className = 'btn-secondary';
+            suggestBtn.textContent = i18n.t('recurring_rule.suggest_estimate') || 'Suggest estimate';
+            suggestBtn.addEventListener('click', () => suggestEstimate(r));
+            tdActions.appendChild(suggestBtn);
+        }
+        const exceptionsBtn = document.createElement('button');
+        exceptionsBtn.type = 'button';
+        exceptionsBtn.className = 'btn-secondary';
+        exceptionsBtn.textContent = i18n.t('recurring_rule.exceptions') || 'Exceptions';
+        exceptionsBtn.addEventListener('click', () => openExceptionsModal(r));
+        tdActions.appendChild(exceptionsBtn);
         const delBtn = document.createElement('button');
         delBtn.type = 'button';
         delBtn.className = 'btn-danger';
@@ -501,6 +542,25 @@ function renderRules(rules) {
     });
 }
 
+async function suggestEstimate(rule) {
+    try {
+        const suggestion = await invoke('suggest_recurring_rule_estimate', { ruleId: rule.rule_id });
+        if (suggestion.estimated_amount === null) {
+            showResult('error', i18n.t('recurring_rule.estimate_no_history')
+                || 'No confirmed occurrences to estimate from yet.');
+            return;
+        }
+        const tmpl = i18n.t('recurring_rule.estimate_suggested')
+            || 'Suggested estimate: {0} (average of the last {1} confirmed)';
+        showResult('success', tmpl
+            .replace('{0}', suggestion.estimated_amount.toLocaleString())
+            .replace('{1}', suggestion.sample_count));
+    } catch (err) {
+        console.error('suggest_recurring_rule_estimate failed:', err);
+        showResult('error', formatApiError(err));
+    }
+}
+
 function formatCycle(unit, interval, shiftType) {
     const unitLabel = {
         DAY: i18n.t('recurring_rule.cycle_daily') || 'Daily',
@@ -576,6 +636,148 @@ async function deleteRule(cascade) {
     }
 }
 
+// ----- Exceptions modal -----
+
+function setupExceptionsModal() {
+    document.getElementById('exceptions-modal-close').addEventListener('click', closeExceptionsModal);
+    document.getElementById('exception-type').addEventListener('change', updateMovedToState);
+    document.getElementById('exception-save-btn').addEventListener('click', saveException);
+}
+
+function updateMovedToState() {
+    const isMove = document.getElementById('exception-type').value === 'MOVE';
+    const movedTo = document.getElementById('exception-moved-to');
+    movedTo.disabled = !isMove;
+    if (!isMove) movedTo.value = '';
+}
+
+async function openExceptionsModal(rule) {
+    exceptionsRule = rule;
+    const name = rule.rule_name || `#${rule.rule_id}`;
+    const title = i18n.t('recurring_rule.exceptions_title') || 'Skipped / moved occurrences';
+    document.getElementById('exceptions-modal-title').textContent = `${title}: ${name}`;
+    document.getElementById('exception-date').value = '';
+    document.getElementById('exception-type').value = 'SKIP';
+    updateMovedToState();
+    hideExceptionsError();
+    document.getElementById('exceptions-modal').classList.remove('hidden');
+    await loadExceptions();
+}
+
+function closeExceptionsModal() {
+    exceptionsRule = null;
+    document.getElementById('exceptions-modal').classList.add('hidden');
+}
+
+async function loadExceptions() {
+    if (!exceptionsRule) return;
+    try {
+        const exceptions = await invoke('list_recurring_rule_exceptions', { ruleId: exceptionsRule.rule_id });
+        renderExceptions(exceptions);
+    } catch (err) {
+        console.error('list_recurring_rule_exceptions failed:', err);
+        showExceptionsError(formatApiError(err));
+    }
+}
+
+function renderExceptions(exceptions) {
+    const tbody = document.getElementById('exceptions-tbody');
+    const table = document.getElementById('exceptions-table');
+    const emptyMsg = document.getElementById('no-exceptions-message');
+    tbody.innerHTML = '';
+
+    if (!exceptions || exceptions.length === 0) {
+        table.classList.add('hidden');
+        emptyMsg.classList.remove('hidden');
+        return;
+    }
+    table.classList.remove('hidden');
+    emptyMsg.classList.add('hidden');
+
+    exceptions.forEach((ex) => {
+        const tr = document.createElement('tr');
+
+        const tdDate = document.createElement('td');
+        tdDate.textContent = ex.occurrence_date;
+        tr.appendChild(tdDate);
+
+        const tdChange = document.createElement('td');
+        if (ex.exception_type === 'MOVE') {
+            const tmpl = i18n.t('recurring_rule.exception_moved_to_display') || 'Moved to {0}';
+            tdChange.textContent = tmpl.replace('{0}', ex.moved_to_date);
+        } else {
+            tdChange.textContent = i18n.t('recurring_rule.exception_skip') || 'Skip';
+        }
+        tr.appendChild(tdChange);
+
+        const tdActions = document.createElement('td');
+        const undoBtn = document.createElement('button');
+        undoBtn.type = 'button';
+        undoBtn.className = 'btn-secondary';
+        undoBtn.textContent = i18n.t('recurring_rule.exception_undo') || 'Undo';
+        undoBtn.addEventListener('click', () => deleteException(ex.occurrence_date));
+        tdActions.appendChild(undoBtn);
+        tr.appendChild(tdActions);
+
+        tbody.appendChild(tr);
+    });
+}
+
+async function saveException() {
+    if (!exceptionsRule) return;
+    hideExceptionsError();
+    const occurrenceDate = document.getElementById('exception-date').value;
+    if (!occurrenceDate) {
+        showExceptionsError(i18n.t('recurring_rule.exception_date_required') || 'Enter the occurrence date.');
+        return;
+    }
+    const exceptionType = document.getElementById('exception-type').value;
+    const request = {
+        occurrence_date: occurrenceDate,
+        exception_type: exceptionType,
+        moved_to_date: exceptionType === 'MOVE'
+            ? stringOrNull(document.getElementById('exception-moved-to').value)
+            : null,
+    };
+    try {
+        await invoke('save_recurring_rule_exception', { ruleId: exceptionsRule.rule_id, request });
+        document.getElementById('exception-date').value = '';
+        await loadExceptions();
+        await loadRules();
+    } catch (err) {
+        console.error('save_recurring_rule_exception failed:', err);
+        showExceptionsError(formatApiError(err));
+    }
+}
+
+async function deleteException(occurrenceDate) {
+    if (!exceptionsRule) return;
+    hideExceptionsError();
+    try {
+        await invoke('delete_recurring_rule_exception', {
+            ruleId: exceptionsRule.rule_id,
+            occurrenceDate,
+        });
+        await loadExceptions();
+        await loadRules();
+    } catch (err) {
+        console.error('delete_recurring_rule_exception failed:', err);
+        showExceptionsError(formatApiError(err));
+    }
+}
+
+function showExceptionsError(message) {
+    const box = document.getElementById('exceptions-error');
+    box.textContent = message;
+    box.classList.remove('hidden');
+}
+
+function hideExceptionsError() {
+    const box = document.getElementById('exceptions-error');
+    box.classList.add('hidden');
+    box.textContent = '';
+}
+
 // ----- Result helpers -----
 
 function showResult(kind, message) {
@@ -55,6 +55,8 @@ export function buildDraftFromForm() {
         amount_including_tax: document.getElementById('amount-including-tax')?.value || '',
         tax_amount: document.getElementById('tax-amount')?.value || '',
         memo: document.getElementById('memo')?.value || '',
+        from_account_code: document.getElementById('split-from-account')?.value || '',
+        to_account_code: document.getElementById('split-to-account')?.value || '',
         selected_product_id: autocompleteState.selectedProductId,
     };
 }
@@ -155,6 +157,9 @@ document.addEventListener('DOMContentLoaded', async function() {
         // Load transaction details
         await loadDetails();
 
+        // Accounts for split lines
+        await loadSplitAccountOptions();
+
         // If we just came back from product-management via the "Back to detail
         // entry" button, restore the half-filled modal. Only consume the draft
         // on a successful restore; otherwise leave it for the user to retry.
@@ -416,6 +421,24 @@ function setupEventListeners() {
     }
 }
 
+/**
+ * Fill the split-line account selects. The empty option keeps a line on the
+ * header's accounts; picking both moves that line between its own accounts.
+ */
+async function loadSplitAccountOptions() {
+    const accounts = await invoke('get_accounts', {});
+    for (const id of ['split-from-account', 'split-to-account']) {
+        const select = document.getElementById(id);
+        if (!select) continue;
+        accounts.forEach((acc) => {
+            const opt = document.createElement('option');
+            opt.value = acc.account_code;
+            opt.textContent = acc.account_name;
+            select.appendChild(opt);
+        });
+    }
+}
+
 /**
  * Load category dropdowns based on CATEGORY1_CODE from header
  */
@@ -864,6 +887,8 @@ async function openDetailModal(detail = null) {
         document.getElementById('tax-rate').value = detail.tax_rate;
         document.getElementById('tax-amount').value = detail.tax_amount;
         document.getElementById('amount-including-tax').value = detail.amount_including_tax;
+        document.getElementById('split-from-account').value = detail.from_account_code || '';
+        document.getElementById('split-to-account').value = detail.to_account_code || '';
         if (detail.memo_text) {
             document.getElementById('memo').value = detail.memo_text;
         }
@@ -931,6 +956,8 @@ export async function restoreDraftIntoModal(draft) {
     document.getElementById('amount-including-tax').value = draft.amount_including_tax || '';
     document.getElementById('tax-amount').value = draft.tax_amount || '';
     document.getElementById('memo').value = draft.memo || '';
+    document.getElementById('split-from-account').value = draft.from_account_code || '';
+    document.getElementById('split-to-account').value = draft.to_account_code || '';
 }
 
 async function handleDetailFormSubmit(event) {
@@ -947,6 +974,8 @@ async function handleDetailFormSubmit(event) {
     const amountIncludingTax = parseInt(document.getElementById('amount-including-tax').value) || 0;
     const taxRate = parseInt(document.getElementById('tax-rate').value) || 0;
     const taxAmount = parseInt(document.getElementById('tax-amount').value) || 0;
+    const fromAccountCode = document.getElementById('split-from-account').value || null;
+    const toAccountCode = document.getElementById('split-to-account').value || null;
     const memo = memoInput.value.trim();
 
     clearValidationError(itemNameInput);
@@ -993,7 +1022,9 @@ async function handleDetailFormSubmit(event) {
                 taxRate: taxRate,
                 taxAmount: taxAmount,
                 productId: productId,
-                memo: memo || null
+                memo: memo || null,
+                fromAccountCode: fromAccountCode,
+                toAccountCode: toAccountCode
             });
         } else {
             // Add new detail
@@ -1008,7 +1039,9 @@ async function handleDetailFormSubmit(event) {
                 taxRate: taxRate,
                 taxAmount: taxAmount,
                 productId: productId,
-                memo: memo || null
+                memo: memo || null,
+                fromAccountCode: fromAccountCode,
+                toAccountCode: toAccountCode
             });
         }
 
@@ -23,6 +23,9 @@ let currentUserRole = null;
 let currentPage = 1;
 const perPage = 50;
 
+// Rows ticked for a bulk edit on the current page
+const selectedTransactionIds = new Set();
+
 // Filter state
 let currentFilters = {
     startDate: null,
@@ -33,9 +36,18 @@ let currentFilters = {
     minAmount: null,
     maxAmount: null,
     keyword: null,
-    includeScheduled: false
+    tagId: null,
+    shopId: null,
+    accountCode: null,
+    itemName: null,
+    includeScheduled: false,
+    sort: 'date_desc'
 };
 
+// Saved filter currently driving the list (null = the live filter above)
+let currentSavedFilterId = null;
+let savedFilters = [];
+
 document.addEventListener('DOMContentLoaded', async function() {
     
     // Create menu bar
@@ -95,6 +107,9 @@ document.addEventListener('DOMContentLoaded', async function() {
         
         // Load category data for filters
         await loadCategoriesForFilter();
+        await loadTagsForFilter();
+        await loadShopsAndAccountsForFilter();
+        await loadSavedFilters();
         
         // Load transactions
         await loadTransactions();
@@ -175,6 +190,45 @@ function setupEventListeners() {
     const clearFilterBtn = document.getElementById('clear-filter-btn');
     clearFilterBtn.addEventListener('click', clearFilters);
 
+    // Bulk edit
+    document.getElementById('select-all-transactions').addEventListener('change', (event) => {
+        document.querySelectorAll('#transaction-list .transaction-select input').forEach((checkbox) => {
+            checkbox.checked = event.target.checked;
+            toggleTransactionSelection(parseInt(checkbox.value), checkbox.checked);
+        });
+    });
+    document.getElementById('bulk-action').addEventListener('change', updateBulkBar);
+    document.getElementById('bulk-apply-btn').addEventListener('click', applyBulkChange);
+
+    // Full-text search
+    document.getElementById('fulltext-search-btn').addEventListener('click', runFulltextSearch);
+    document.getElementById('fulltext-clear-btn').addEventListener('click', closeFulltextResults);
+    document.getElementById('fulltext-query').addEventListener('keydown', (event) => {
+        if (event.key === 'Enter' && !event.isComposing) {
+            event.preventDefault();
+            runFulltextSearch();
+        }
+    });
+
+    // Duplicate-entry report
+    document.getElementById('find-duplicates-btn').addEventListener('click', loadDuplicateGroups);
+    document.getElementById('close-duplicates-btn').addEventListener('click', () => {
+        document.getElementById('duplicate-panel').classList.add('hidden');
+    });
+
+    // Undo / redo
+    document.getElementById('undo-btn').addEventListener('click', () => replayOperations('undo_operations', null));
+    document.getElementById('redo-btn').addEventListener('click', () => replayOperations('redo_operations', null));
+    document.getElementById('undo-history-btn').addEventListener('click', loadUndoHistory);
+    document.getElementById('close-undo-history-btn').addEventListener('click', () => {
+        document.getElementById('undo-history-panel').classList.add('hidden');
+    });
+
+    // Saved filters
+    document.getElementById('filter-saved').addEventListener('change', selectSavedFilter);
+    document.getElementById('save-filter-btn').addEventListener('click', saveCurrentFilter);
+    document.getElementById('delete-saved-filter-btn').addEventListener('click', deleteSelectedSavedFilter);
+
     // Pagination
     const prevPageBtn = document.getElementById('prev-page-btn');
     const nextPageBtn = document.getElementById('next-page-btn');
@@ -262,6 +316,7 @@ async function loadCategoriesForFilter() {
         }
         
         category1Select.innerHTML = '<option value="">' + i18n.t('common.all') + '</option>';
+        populateBulkCategorySelect(categoryTree);
         
         categoryTree.forEach((cat1) => {
             const option = document.createElement('option');
@@ -328,22 +383,477 @@ async function loadCategoriesForFilter() {
     }
 }
 
+/**
+ * One bulk-edit option per category2 and category3, labelled with the full
+ * path. The value carries both codes since the backend sets them together.
+ */
+function populateBulkCategorySelect(categoryTree) {
+    const select = document.getElementById('bulk-category');
+    select.innerHTML = '<option value="">' + i18n.t('transaction_mgmt.bulk_clear_value') + '</option>';
+    const addOption = (label, category2Code, category3Code) => {
+        const option = document.createElement('option');
+        option.value = JSON.stringify({ category2_code: category2Code, category3_code: category3Code });
+        option.textContent = label;
+        select.appendChild(option);
+    };
+    categoryTree.forEach((cat1) => {
+        (cat1.children || []).forEach((cat2) => {
+            const cat2Label = `${cat1.category1.category1_name_i18n} › ${cat2.category2.category2_name_i18n}`;
+            addOption(cat2Label, cat2.category2.category2_code, null);
+            (cat2.children || []).forEach((cat3) => {
+                addOption(`${cat2Label} › ${cat3.category3_name_i18n}`, cat2.category2.category2_code, cat3.category3_code);
+            });
+        });
+    });
+}
+
+async function loadTagsForFilter() {
+    try {
+        const tags = await invoke('list_tags');
+        const tagSelect = document.getElementById('filter-tag');
+        tagSelect.innerHTML = '<option value="">' + i18n.t('common.all') + '</option>';
+        tags.forEach((tag) => {
+            const option = document.createElement('option');
+            option.value = tag.tag_id;
+            option.textContent = tag.tag_name;
+            tagSelect.appendChild(option);
+        });
+    } catch (error) {
+        console.error('Failed to load tags:', error);
+    }
+}
+
+async function loadShopsAndAccountsForFilter() {
+    try {
+        const [shops, accounts] = await Promise.all([
+            invoke('get_shops', {}),
+            invoke('get_accounts', {})
+        ]);
+        const shopSelect = document.getElementById('filter-shop');
+        shops.forEach((shop) => {
+            const option = document.createElement('option');
+            option.value = shop.shop_id;
+            option.textContent = shop.shop_name;
+            shopSelect.appendChild(option);
+        });
+        const accountSelect = document.getElementById('filter-account');
+        accounts.forEach((account) => {
+            if (account.account_code === 'NONE') return;
+            const option = document.createElement('option');
+            option.value = account.account_code;
+            option.textContent = account.account_name;
+            accountSelect.appendChild(option);
+        });
+        // Bulk edit: shops as in the filter, accounts including NONE
+        const bulkShopSelect = document.getElementById('bulk-shop');
+        shops.forEach((shop) => {
+            const option = document.createElement('option');
+            option.value = shop.shop_id;
+            option.textContent = shop.shop_name;
+            bulkShopSelect.appendChild(option);
+        });
+        const bulkAccountSelect = document.getElementById('bulk-account');
+        accounts.forEach((account) => {
+            const option = document.createElement('option');
+            option.value = account.account_code;
+            option.textContent = account.account_name;
+            bulkAccountSelect.appendChild(option);
+        });
+    } catch (error) {
+        console.error('Failed to load shops/accounts for filter:', error);
+    }
+}
+
+async function loadSavedFilters() {
+    try {
+        savedFilters = await invoke('list_saved_filters');
+        const savedSelect = document.getElementById('filter-saved');
+        savedSelect.innerHTML = '<option value="">' + i18n.t('common.select') + '</option>';
+        savedFilters.forEach((saved) => {
+            const option = document.createElement('option');
+            option.value = saved.saved_filter_id;
+            option.textContent = saved.filter_name;
+            savedSelect.appendChild(option);
+        });
+        savedSelect.value = currentSavedFilterId ? String(currentSavedFilterId) : '';
+    } catch (error) {
+        console.error('Failed to load saved filters:', error);
+    }
+}
+
+// Both get_transactions and saved filters take the backend's snake_case
+// TransactionQuery fields.
+function toFilterSpec(filters) {
+    return {
+        start_date: filters.startDate,
+        end_date: filters.endDate,
+        category1_code: filters.category1Code,
+        category2_code: filters.category2Code,
+        category3_code: filters.category3Code,
+        min_amount: filters.minAmount,
+        max_amount: filters.maxAmount,
+        keyword: filters.keyword,
+        tag_ids: filters.tagId ? [filters.tagId] : [],
+        shop_id: filters.shopId,
+        account_code: filters.accountCode,
+        item_name: filters.itemName,
+        status: filters.includeScheduled ? 'all' : 'confirmed',
+        sort: filters.sort
+    };
+}
+
+function fillFilterForm(spec) {
+    document.getElementById('filter-start-date').value = spec.start_date || '';
+    document.getElementById('filter-end-date').value = spec.end_date || '';
+    document.getElementById('filter-category1').value = spec.category1_code || '';
+    document.getElementById('filter-category2').value = spec.category2_code || '';
+    document.getElementById('filter-category3').value = spec.category3_code || '';
+    document.getElementById('filter-min-amount').value = spec.min_amount ?? '';
+    document.getElementById('filter-max-amount').value = spec.max_amount ?? '';
+    document.getElementById('filter-keyword').value = spec.keyword || '';
+    document.getElementById('filter-tag').value = spec.tag_ids && spec.tag_ids.length > 0 ? String(spec.tag_ids[0]) : '';
+    document.getElementById('filter-shop').value = spec.shop_id ? String(spec.shop_id) : '';
+    document.getElementById('filter-account').value = spec.account_code || '';
+    document.getElementById('filter-item-name').value = spec.item_name || '';
+    document.getElementById('filter-include-scheduled').checked = spec.status !== undefined && spec.status !== 'confirmed';
+    document.getElementById('filter-sort').value = spec.sort || 'date_desc';
+}
+
+async function selectSavedFilter() {
+    const value = document.getElementById('filter-saved').value;
+    currentSavedFilterId = value ? parseInt(value) : null;
+    const saved = savedFilters.find((f) => f.saved_filter_id === currentSavedFilterId);
+    if (saved) {
+        fillFilterForm(saved.filter);
+        document.getElementById('saved-filter-name').value = saved.filter_name;
+    }
+    currentPage = 1;
+    await loadTransactions();
+}
+
+async function saveCurrentFilter() {
+    const nameInput = document.getElementById('saved-filter-name');
+    const filterName = nameInput.value.trim();
+    if (!filterName) {
+        showToast(i18n.t('transaction_mgmt.saved_filter_name_required'), { variant: 'warning' });
+        return;
+    }
+    try {
+        const saved = await invoke('save_filter', {
+            filterName: filterName,
+            filter: toFilterSpec(readFilterForm())
+        });
+        currentSavedFilterId = saved.saved_filter_id;
+        await loadSavedFilters();
+        showToast(i18n.t('transaction_mgmt.saved_filter_saved'), { variant: 'success' });
+    } catch (error) {
+        console.error('Failed to save filter:', error);
+        showToast(i18n.t('transaction_mgmt.saved_filter_save_error') + ': ' + formatApiError(error), { variant: 'error' });
+    }
+}
+
+async function deleteSelectedSavedFilter() {
+    if (!currentSavedFilterId) return;
+    if (!confirm(i18n.t('transaction_mgmt.confirm_delete_saved_filter'))) return;
+    try {
+        await invoke('delete_saved_filter', { savedFilterId: currentSavedFilterId });
+        currentSavedFilterId = null;
+        document.getElementById('saved-filter-name').value = '';
+        await loadSavedFilters();
+    } catch (error) {
+        console.error('Failed to delete saved filter:', error);
+        showToast(i18n.t('transaction_mgmt.delete_error') + ': ' + formatApiError(error), { variant: 'error' });
+    }
+}
+
+async function runFulltextSearch() {
+    const query = document.getElementById('fulltext-query').value.trim();
+    if (!query) {
+        closeFulltextResults();
+        return;
+    }
+    const resultsContainer = document.getElementById('fulltext-results');
+    resultsContainer.classList.remove('hidden');
+    document.getElementById('fulltext-clear-btn').classList.remove('hidden');
+    try {
+        const hits = await invoke('search_transactions', { query: query, limit: null });
+        renderFulltextResults(hits);
+    } catch (error) {
+        console.error('Failed to search transactions:', error);
+        resultsContainer.innerHTML = `<div class="error">${escapeHtml(i18n.t('transaction_mgmt.fulltext_error'))}: ${escapeHtml(formatApiError(error))}</div>`;
+    }
+}
+
+function renderFulltextResults(hits) {
+    const resultsContainer = document.getElementById('fulltext-results');
+    if (hits.length === 0) {
+        resultsContainer.innerHTML = `<div class="empty-state">${escapeHtml(i18n.t('transaction_mgmt.fulltext_no_results'))}</div>`;
+        return;
+    }
+    resultsContainer.innerHTML = '';
+    hits.forEach((hit) => {
+        const row = document.createElement('div');
+        row.className = 'search-hit';
+        // Snippet text comes from user data: escape every segment and only
+        // add the <mark> tags ourselves
+        const snippet = hit.snippet
+            .map((s) => s.highlighted ? `<mark>${escapeHtml(s.text)}</mark>` : escapeHtml(s.text))
+            .join('');
+        row.innerHTML = `
+            <div class="search-hit-date">${escapeHtml(hit.transaction_date.substring(0, 16))}</div>
+            <div class="search-hit-amount">${escapeHtml(formatAmount(hit.total_amount))}</div>
+            <div class="search-hit-snippet">${snippet}</div>
+        `;
+        row.addEventListener('click', () => editTransaction(hit.transaction_id));
+        resultsContainer.appendChild(row);
+    });
+}
+
+function closeFulltextResults() {
+    const resultsContainer = document.getElementById('fulltext-results');
+    resultsContainer.innerHTML = '';
+    resultsContainer.classList.add('hidden');
+    document.getElementById('fulltext-clear-btn').classList.add('hidden');
+    document.getElementById('fulltext-query').value = '';
+}
+
+function toggleTransactionSelection(transactionId, selected) {
+    if (selected) {
+        selectedTransactionIds.add(transactionId);
+    } else {
+        selectedTransactionIds.delete(transactionId);
+    }
+    updateBulkBar();
+}
+
+function updateBulkBar() {
+    const bar = document.getElementById('bulk-bar');
+    bar.classList.toggle('hidden', selectedTransactionIds.size === 0);
+    document.getElementById('bulk-selected-count').textContent =
+        i18n.t('transaction_mgmt.bulk_selected_count', { count: selectedTransactionIds.size });
+    const action = document.getElementById('bulk-action').value;
+    bar.querySelectorAll('[data-bulk-action]').forEach((field) => {
+        field.classList.toggle('hidden', field.dataset.bulkAction !== action);
+    });
+}
+
+function readBulkChange() {
+    const action = document.getElementById('bulk-action').value;
+    switch (action) {
+        case 'set_shop': {
+            const shopValue = document.getElementById('bulk-shop').value;
+            return { action, shop_id: shopValue ? parseInt(shopValue) : null };
+        }
+        case 'set_categories': {
+            const value = document.getElementById('bulk-category').value;
+            return { action, ...(value ? JSON.parse(value) : { category2_code: null, category3_code: null }) };
+        }
+        case 'move_account': {
+            const code = document.getElementById('bulk-account').value;
+            const side = document.getElementById('bulk-account-side').value;
+            return {
+                action,
+                from_account_code: side === 'from' ? code : null,
+                to_account_code: side === 'to' ? code : null
+            };
+        }
+        case 'set_memo':
+            return { action, memo: document.getElementById('bulk-memo').value || null };
+        default:
+            return { action };
+    }
+}
+
+async function applyBulkChange() {
+    const change = readBulkChange();
+    const transactionIds = Array.from(selectedTransactionIds);
+    const confirmKey = change.action === 'delete'
+        ? 'transaction_mgmt.bulk_confirm_delete'
+        : 'transaction_mgmt.bulk_confirm_apply';
+    if (!confirm(i18n.t(confirmKey, { count: transactionIds.length }))) return;
+
+    const resultDiv = document.getElementById('bulk-result');
+    resultDiv.classList.add('hidden');
+    try {
+        const results = await invoke('bulk_update_transactions', { transactionIds, change });
+        const failed = results.filter((r) => r.error);
+        const succeeded = results.length - failed.length;
+        showToast(i18n.t('transaction_mgmt.bulk_done', { count: succeeded }), {
+            variant: failed.length ? 'warning' : 'success'
+        });
+        if (failed.length) {
+            // Keep the refused rows listed (and selected) so they can be fixed
+            const items = failed
+                .map((r) => `<li>#${escapeHtml(String(r.transaction_id))}: ${escapeHtml(formatApiError(r.error))}</li>`)
+                .join('');
+            resultDiv.innerHTML = `${escapeHtml(i18n.t('transaction_mgmt.bulk_failed', { count: failed.length }))}<ul>${items}</ul>`;
+            resultDiv.classList.remove('hidden');
+        }
+        await loadTransactions();
+        failed.forEach((r) => {
+            const checkbox = document.querySelector(`#transaction-list .transaction-select input[value="${r.transaction_id}"]`);
+            if (checkbox) {
+                checkbox.checked = true;
+                toggleTransactionSelection(r.transaction_id, true);
+            }
+        });
+    } catch (error) {
+        console.error('Failed to apply bulk change:', error);
+        showToast(i18n.t('transaction_mgmt.bulk_error') + ': ' + formatApiError(error), { variant: 'error' });
+    }
+}
+
+async function loadDuplicateGroups() {
+    const panel = document.getElementById('duplicate-panel');
+    const container = document.getElementById('duplicate-groups');
+    panel.classList.remove('hidden');
+    try {
+        const groups = await invoke('find_duplicate_transactions', { options: null });
+        renderDuplicateGroups(groups);
+    } catch (error) {
+        console.error('Failed to find duplicates:', error);
+        container.innerHTML = `<div class="error">${escapeHtml(i18n.t('transaction_mgmt.duplicates_error'))}: ${escapeHtml(formatApiError(error))}</div>`;
+    }
+}
+
+function renderDuplicateGroups(groups) {
+    const container = document.getElementById('duplicate-groups');
+    if (groups.length === 0) {
+        container.innerHTML = `<div class="empty-state">${escapeHtml(i18n.t('transaction_mgmt.duplicates_none'))}</div>`;
+        return;
+    }
+    container.innerHTML = '';
+    groups.forEach((group) => {
+        const groupDiv = document.createElement('div');
+        groupDiv.className = 'duplicate-group';
+
+        const header = document.createElement('div');
+        header.className = 'duplicate-group-header';
+        const scoreSpan = document.createElement('span');
+        scoreSpan.textContent = `${i18n.t('transaction_mgmt.duplicates_similarity')}: ${Math.round(group.score * 100)}%`;
+        const dismissBtn = document.createElement('button');
+        dismissBtn.className = 'btn btn-secondary';
+        dismissBtn.textContent = i18n.t('transaction_mgmt.duplicates_dismiss');
+        dismissBtn.addEventListener('click', () => dismissDuplicateGroup(group));
+        header.appendChild(scoreSpan);
+        header.appendChild(dismissBtn);
+        groupDiv.appendChild(header);
+
+        group.transactions.forEach((t) => {
+            const row = document.createElement('div');
+            row.className = 'duplicate-row';
+            const label = [t.shop_name, t.item_names.join(', ')].filter(Boolean).join(' / ');
+            row.innerHTML = `
+                <div class="search-hit-date">${escapeHtml(t.transaction_date.substring(0, 16))}</div>
+                <div class="search-hit-amount">${escapeHtml(formatAmount(t.total_amount))}</div>
+                <div class="search-hit-snippet">${escapeHtml(label)}</div>
+            `;
+            row.addEventListener('click', () => editTransaction(t.transaction_id));
+            groupDiv.appendChild(row);
+        });
+        container.appendChild(groupDiv);
+    });
+}
+
+async function dismissDuplicateGroup(group) {
+    if (!confirm(i18n.t('transaction_mgmt.duplicates_confirm_dismiss'))) return;
+    try {
+        await invoke('dismiss_duplicate_group', {
+            transactionIds: group.transactions.map((t) => t.transaction_id)
+        });
+        await loadDuplicateGroups();
+    } catch (error) {
+        console.error('Failed to dismiss duplicate group:', error);
+        showToast(i18n.t('transaction_mgmt.duplicates_error') + ': ' + formatApiError(error), { variant: 'error' });
+    }
+}
+
+function operationLabel(label) {
+    const [entity, action] = label.split('.');
+    return `${i18n.t('transaction_mgmt.undo_entity_' + entity)}: ${i18n.t('transaction_mgmt.undo_action_' + action)}`;
+}
+
+async function loadUndoHistory() {
+    const panel = document.getElementById('undo-history-panel');
+    const container = document.getElementById('undo-history-list');
+    panel.classList.remove('hidden');
+    try {
+        const operations = await invoke('list_change_operations');
+        renderUndoHistory(operations);
+    } catch (error) {
+        console.error('Failed to load undo history:', error);
+        container.innerHTML = `<div class="error">${escapeHtml(i18n.t('transaction_mgmt.undo_error'))}: ${escapeHtml(formatApiError(error))}</div>`;
+    }
+}
+
+function renderUndoHistory(operations) {
+    const container = document.getElementById('undo-history-list');
+    if (operations.length === 0) {
+        container.innerHTML = `<div class="empty-state">${escapeHtml(i18n.t('transaction_mgmt.undo_history_empty'))}</div>`;
+        return;
+    }
+    container.innerHTML = '';
+    operations.forEach((op) => {
+        const undone = op.status === 'undone';
+        const row = document.createElement('div');
+        row.className = 'duplicate-row undo-history-row' + (undone ? ' undone' : '');
+        const label = operationLabel(op.label) + (undone ? ' ' + i18n.t('transaction_mgmt.undo_status_undone') : '');
+        row.innerHTML = `
+            <div class="search-hit-date">${escapeHtml(op.entry_dt.substring(0, 16))}</div>
+            <div class="search-hit-snippet">${escapeHtml(label)}</div>
+            <div class="search-hit-amount">${escapeHtml(i18n.t('transaction_mgmt.undo_rows', { count: op.change_count }))}</div>
+        `;
+        const button = document.createElement('button');
+        button.className = 'btn btn-secondary';
+        button.textContent = i18n.t(undone ? 'transaction_mgmt.redo' : 'transaction_mgmt.undo');
+        button.addEventListener('click', () => {
+            replayOperations(undone ? 'redo_operations' : 'undo_operations', op.operation_id);
+        });
+        row.appendChild(button);
+        container.appendChild(row);
+    });
+}
+
+/**
+ * Undo or redo one operation, or the latest one when `operationId` is null.
+ * @param {'undo_operations'|'redo_operations'} command
+ * @param {number|null} operationId
+ */
+async function replayOperations(command, operationId) {
+    const isUndo = command === 'undo_operations';
+    try {
+        const ids = await invoke(command, { count: null, operationId: operationId });
+        if (ids.length === 0) {
+            showToast(i18n.t(isUndo ? 'transaction_mgmt.undo_nothing' : 'transaction_mgmt.redo_nothing'));
+            return;
+        }
+        showToast(i18n.t(isUndo ? 'transaction_mgmt.undo_done' : 'transaction_mgmt.redo_done'), { variant: 'success' });
+        await loadTransactions();
+    } catch (error) {
+        console.error(`Failed to ${isUndo ? 'undo' : 'redo'}:`, error);
+        const message = error && typeof error === 'object' && error.code === API_ERROR_CODES.JOURNAL_CONFLICT
+            ? i18n.t('transaction_mgmt.undo_conflict')
+            : i18n.t('transaction_mgmt.undo_error') + ': ' + formatApiError(error);
+        showToast(message, { variant: 'error' });
+    }
+    if (!document.getElementById('undo-history-panel').classList.contains('hidden')) {
+        await loadUndoHistory();
+    }
+}
+
 async function loadTransactions() {
     try {
         const listContainer = document.getElementById('transaction-list');
         listContainer.innerHTML = '<div class="loading" data-i18n="common.loading">Loading...</div>';
         i18n.updateUI();
 
-        const response = await invoke('get_transactions', {
-            startDate: currentFilters.startDate,
-            endDate: currentFilters.endDate,
-            category1Code: currentFilters.category1Code,
-            category2Code: currentFilters.category2Code,
-            category3Code: currentFilters.category3Code,
-            minAmount: currentFilters.minAmount,
-            maxAmount: currentFilters.maxAmount,
-            keyword: currentFilters.keyword,
-            includeScheduled: currentFilters.includeScheduled,
+        // A selected saved filter runs server-side from its stored spec
+        const response = currentSavedFilterId ? await invoke('execute_saved_filter', {
+            savedFilterId: currentSavedFilterId,
+            page: currentPage,
+            perPage: perPage
+        }) : await invoke('get_transactions', {
+            query: toFilterSpec(currentFilters),
             page: currentPage,
             perPage: perPage
         });
@@ -367,6 +877,9 @@ async function loadTransactions() {
 
 function renderTransactions(transactions) {
     const listContainer = document.getElementById('transaction-list');
+    selectedTransactionIds.clear();
+    document.getElementById('select-all-transactions').checked = false;
+    updateBulkBar();
     
     if (!transactions || transactions.length === 0) {
         listContainer.innerHTML = '<div class="empty-state" data-i18n="transaction_mgmt.no_transactions">No transactions found</div>';
@@ -391,6 +904,16 @@ function createTransactionItem(transaction) {
         item.classList.add('scheduled');
     }
 
+    // Bulk-edit selection
+    const selectDiv = document.createElement('div');
+    selectDiv.className = 'transaction-select';
+    const checkbox = document.createElement('input');
+    checkbox.type = 'checkbox';
+    checkbox.value = transaction.transaction_id;
+    checkbox.addEventListener('change', () => toggleTransactionSelection(transaction.transaction_id, checkbox.checked));
+    selectDiv.appendChild(checkbox);
+    item.appendChild(selectDiv);
+
     // Create content wrapper (for non-button content)
     const contentWrapper = document.createElement('div');
     contentWrapper.className = 'transaction-content';
@@ -459,7 +982,7 @@ function createTransactionItem(transaction) {
         confirmBtn.className = 'btn-confirm';
         confirmBtn.textContent = i18n.t('transaction_mgmt.confirm_btn') || 'Confirm';
         confirmBtn.setAttribute('data-i18n', 'transaction_mgmt.confirm_btn');
-        confirmBtn.addEventListener('click', () => confirmScheduledTransaction(transaction.transaction_id));
+        confirmBtn.addEventListener('click', () => confirmScheduledTransaction(transaction));
         actionsDiv.appendChild(confirmBtn);
     }
 
@@ -507,20 +1030,34 @@ function updatePagination(response) {
     nextBtn.disabled = response.page >= response.total_pages;
 }
 
-async function applyFilters() {
-    currentFilters.startDate = document.getElementById('filter-start-date').value || null;
-    currentFilters.endDate = document.getElementById('filter-end-date').value || null;
-    currentFilters.category1Code = document.getElementById('filter-category1').value || null;
-    currentFilters.category2Code = document.getElementById('filter-category2').value || null;
-    currentFilters.category3Code = document.getElementById('filter-category3').value || null;
-    
+function readFilterForm() {
     const minAmountInput = document.getElementById('filter-min-amount').value;
     const maxAmountInput = document.getElementById('filter-max-amount').value;
-    
-    currentFilters.minAmount = minAmountInput ? parseInt(minAmountInput) : null;
-    currentFilters.maxAmount = maxAmountInput ? parseInt(maxAmountInput) : null;
-    currentFilters.keyword = document.getElementById('filter-keyword').value || null;
-    currentFilters.includeScheduled = document.getElementById('filter-include-scheduled').checked;
+    const tagValue = document.getElementById('filter-tag').value;
+    const shopValue = document.getElementById('filter-shop').value;
+
+    return {
+        startDate: document.getElementById('filter-start-date').value || null,
+        endDate: document.getElementById('filter-end-date').value || null,
+        category1Code: document.getElementById('filter-category1').value || null,
+        category2Code: document.getElementById('filter-category2').value || null,
+        category3Code: document.getElementById('filter-category3').value || null,
+        minAmount: minAmountInput ? parseInt(minAmountInput) : null,
+        maxAmount: maxAmountInput ? parseInt(maxAmountInput) : null,
+        keyword: document.getElementById('filter-keyword').value || null,
+        tagId: tagValue ? parseInt(tagValue) : null,
+        shopId: shopValue ? parseInt(shopValue) : null,
+        accountCode: document.getElementById('filter-account').value || null,
+        itemName: document.getElementById('filter-item-name').value || null,
+        includeScheduled: document.getElementById('filter-include-scheduled').checked,
+        sort: document.getElementById('filter-sort').value
+    };
+}
+
+async function applyFilters() {
+    currentFilters = readFilterForm();
+    currentSavedFilterId = null;
+    document.getElementById('filter-saved').value = '';
 
     currentPage = 1; // Reset to first page
     
@@ -540,7 +1077,15 @@ async function clearFilters() {
     document.getElementById('filter-min-amount').value = '';
     document.getElementById('filter-max-amount').value = '';
     document.getElementById('filter-keyword').value = '';
+    document.getElementById('filter-tag').value = '';
+    document.getElementById('filter-shop').value = '';
+    document.getElementById('filter-account').value = '';
+    document.getElementById('filter-item-name').value = '';
     document.getElementById('filter-include-scheduled').checked = false;
+    document.getElementById('filter-sort').value = 'date_desc';
+    document.getElementById('filter-saved').value = '';
+    document.getElementById('saved-filter-name').value = '';
+    currentSavedFilterId = null;
 
     currentFilters = {
         startDate: null,
@@ -551,7 +1096,12 @@ async function clearFilters() {
         minAmount: null,
         maxAmount: null,
         keyword: null,
-        includeScheduled: false
+        tagId: null,
+        shopId: null,
+        accountCode: null,
+        itemName: null,
+        includeScheduled: false,
+        sort: 'date_desc'
     };
     
     currentPage = 1;
@@ -562,11 +1112,25 @@ async function editTransaction(transactionId) {
     await openTransactionModal(transactionId);
 }
 
-async function confirmScheduledTransaction(transactionId) {
-    const confirmMessage = i18n.t('transaction_mgmt.confirm_scheduled') || 'Mark this scheduled transaction as actual?';
-    if (!confirm(confirmMessage)) return;
+async function confirmScheduledTransaction(transaction) {
+    let adjustment = null;
+    if (transaction.is_estimated === 1) {
+        // Estimated amount: ask for the actual one, prefilled with the estimate
+        const promptMessage = i18n.t('transaction_mgmt.confirm_actual_amount') || 'Enter the actual amount:';
+        const input = prompt(promptMessage, String(transaction.total_amount));
+        if (input === null) return;
+        const actual = parseInt(input, 10);
+        if (Number.isNaN(actual) || actual < 0) {
+            showToast(i18n.t('transaction_mgmt.confirm_actual_amount_invalid') || 'Enter a valid amount.', { variant: 'error' });
+            return;
+        }
+        adjustment = { total_amount: actual, details: null };
+    } else {
+        const confirmMessage = i18n.t('transaction_mgmt.confirm_scheduled') || 'Mark this scheduled transaction as actual?';
+        if (!confirm(confirmMessage)) return;
+    }
     try {
-        await invoke('confirm_scheduled_transaction', { transactionId });
+        await invoke('confirm_scheduled_transaction', { transactionId: transaction.transaction_id, adjustment });
         await loadTransactions();
     } catch (error) {
         console.error('Failed to confirm scheduled transaction:', error);
@@ -18,6 +18,7 @@ CREATE TABLE IF NOT EXISTS USERS (
     YEAR_PERIOD_START_MONTH INTEGER DEFAULT 1,
     YEAR_PERIOD_START_DAY INTEGER DEFAULT 1,
     ENCRYPTION_SALT BLOB,
+    BASE_CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY',  -- currency totals are reported in
     ENTRY_DT DATETIME NOT NULL,
     UPDATE_DT DATETIME,
     PRIMARY KEY(USER_ID)
@@ -627,6 +628,7 @@ CREATE TABLE IF NOT EXISTS ACCOUNT_TEMPLATES (
     TEMPLATE_NAME_JA TEXT NOT NULL,
     TEMPLATE_NAME_EN TEXT NOT NULL,
     DISPLAY_ORDER INTEGER,
+    ACCOUNT_CLASS INTEGER NOT NULL DEFAULT 0,  -- 0: asset, 1: liability
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now'))
 );
 
@@ -640,6 +642,7 @@ CREATE TABLE IF NOT EXISTS ACCOUNTS (
     INITIAL_BALANCE INTEGER DEFAULT 0,
     DISPLAY_ORDER INTEGER,
     IS_DISABLED INTEGER DEFAULT 0,
+    CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY',  -- amounts are in its minor unit
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
     FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
@@ -828,6 +831,9 @@ CREATE INDEX IF NOT EXISTS idx_transactions_detail_categories ON TRANSACTIONS_DE
 -- SQL_30000010: Create RECURRING_RULES table (cycle definition + HEADER template)
 -- Group membership of generated occurrences is established by the RULE_ID
 -- foreign key on each TRANSACTIONS_HEADER row (no linked-list bookkeeping).
+-- END_DATE NULL = open-ended rule; MATERIALIZED_UNTIL is the last date
+-- occurrences have been generated for. IS_ESTIMATED = 1: TOTAL_AMOUNT is an
+-- estimate, replaced by the actual amount when an occurrence is confirmed.
 CREATE TABLE IF NOT EXISTS RECURRING_RULES (
     RULE_ID INTEGER PRIMARY KEY AUTOINCREMENT,
     USER_ID INTEGER NOT NULL,
@@ -842,7 +848,8 @@ CREATE TABLE IF NOT EXISTS RECURRING_RULES (
     MONTH_OF_YEAR INTEGER,
     HOLIDAY_SHIFT_TYPE INTEGER DEFAULT 0,
     START_DATE DATE NOT NULL,
-    END_DATE DATE NOT NULL,
+    END_DATE DATE,
+    MATERIALIZED_UNTIL DATE,
     SHOP_ID INTEGER,
     CATEGORY1_CODE VARCHAR(50) NOT NULL,
     FROM_ACCOUNT_CODE VARCHAR(50) NOT NULL,
@@ -851,6 +858,7 @@ CREATE TABLE IF NOT EXISTS RECURRING_RULES (
     TAX_ROUNDING_TYPE INTEGER DEFAULT 0,
     TAX_INCLUDED_TYPE INTEGER DEFAULT 1 NOT NULL,
     MEMO_ID INTEGER,
+    IS_ESTIMATED INTEGER NOT NULL DEFAULT 0,
     IS_DISABLED INTEGER DEFAULT 0,
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now', 'localtime')),
     UPDATE_DT DATETIME,
@@ -860,10 +868,10 @@ CREATE TABLE IF NOT EXISTS RECURRING_RULES (
     FOREIGN KEY (USER_ID, TO_ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE)
 );
 
--- SQL_30000011: Create RECURRING_RULE_DETAILS table (DETAIL template, 1:1 via UNIQUE)
+-- SQL_30000011: Create RECURRING_RULE_DETAILS table (DETAIL templates, N per rule)
 CREATE TABLE IF NOT EXISTS RECURRING_RULE_DETAILS (
     RULE_DETAIL_ID INTEGER PRIMARY KEY AUTOINCREMENT,
-    RULE_ID INTEGER NOT NULL UNIQUE,
+    RULE_ID INTEGER NOT NULL,
     USER_ID INTEGER NOT NULL,
     CATEGORY1_CODE VARCHAR(50) NOT NULL,
     CATEGORY2_CODE VARCHAR(50),
@@ -873,10 +881,12 @@ CREATE TABLE IF NOT EXISTS RECURRING_RULE_DETAILS (
     TAX_AMOUNT INTEGER DEFAULT 0,
     TAX_RATE INTEGER DEFAULT 8,
     AMOUNT_INCLUDING_TAX INTEGER,
+    PRODUCT_ID INTEGER,
     MEMO_ID INTEGER,
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
     FOREIGN KEY (RULE_ID) REFERENCES RECURRING_RULES(RULE_ID) ON DELETE CASCADE,
+    FOREIGN KEY (PRODUCT_ID) REFERENCES PRODUCTS(PRODUCT_ID) ON DELETE SET NULL,
     FOREIGN KEY (MEMO_ID) REFERENCES MEMOS(MEMO_ID),
     CHECK (ITEM_NAME != '')
 );
@@ -900,8 +910,25 @@ CREATE TABLE IF NOT EXISTS HOLIDAYS_USER_CUSTOM (
     UNIQUE (USER_ID, HOLIDAY_DATE)
 );
 
+-- SQL_30000014: Create RECURRING_RULE_EXCEPTIONS table (skip / move of one occurrence)
+CREATE TABLE IF NOT EXISTS RECURRING_RULE_EXCEPTIONS (
+    EXCEPTION_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    RULE_ID INTEGER NOT NULL,
+    USER_ID INTEGER NOT NULL,
+    OCCURRENCE_DATE DATE NOT NULL,
+    EXCEPTION_TYPE TEXT NOT NULL,
+    MOVED_TO_DATE DATE,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (RULE_ID) REFERENCES RECURRING_RULES(RULE_ID) ON DELETE CASCADE,
+    UNIQUE (RULE_ID, OCCURRENCE_DATE),
+    CHECK ((EXCEPTION_TYPE = 'SKIP' AND MOVED_TO_DATE IS NULL)
+        OR (EXCEPTION_TYPE = 'MOVE' AND MOVED_TO_DATE IS NOT NULL))
+);
+
 -- Create indexes for recurring rules
 CREATE INDEX IF NOT EXISTS idx_recurring_rules_user ON RECURRING_RULES(USER_ID, IS_DISABLED);
+CREATE INDEX IF NOT EXISTS idx_recurring_rule_details_rule ON RECURRING_RULE_DETAILS(RULE_ID);
 CREATE INDEX IF NOT EXISTS idx_holidays_standard_locale_date ON HOLIDAYS_STANDARD(LOCALE, HOLIDAY_DATE);
 CREATE INDEX IF NOT EXISTS idx_holidays_user_custom_user_date ON HOLIDAYS_USER_CUSTOM(USER_ID, HOLIDAY_DATE);
 
@@ -1884,3 +1911,237 @@ INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESO
 INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2432, 'error.invalid_credentials', 'ja', 'ユーザー名またはパスワードが正しくありません。', 'error', 'ログイン失敗: 認証情報不正 (PR14)', datetime('now'));
 INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2433, 'error.setup_completed', 'en', 'Setup has already been completed.', 'error', 'Registration refused: setup done (PR14)', datetime('now'));
 INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2434, 'error.setup_completed', 'ja', 'セットアップは既に完了しています。', 'error', '登録拒否: セットアップ済み (PR14)', datetime('now'));
+
+-- Split transactions: per-line accounts in the detail modal
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2435, 'detail_mgmt.split_accounts', 'en', 'Line Accounts (From → To)', 'detail_mgmt', 'Per-line accounts for split transactions', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2436, 'detail_mgmt.split_accounts', 'ja', '明細の口座 (出金元 → 入金先)', 'detail_mgmt', '分割取引の明細ごとの口座', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2437, 'detail_mgmt.split_same_as_header', 'en', 'Same as transaction', 'detail_mgmt', 'Split account option: follow the header', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2438, 'detail_mgmt.split_same_as_header', 'ja', '取引と同じ', 'detail_mgmt', '分割口座の選択肢: ヘッダーに従う', datetime('now'));
+
+-- Tags: transaction list filter and aggregation axis
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2439, 'transaction_mgmt.tag', 'en', 'Tag:', 'transaction_mgmt', 'Tag filter label', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2440, 'transaction_mgmt.tag', 'ja', 'タグ:', 'transaction_mgmt', 'タグフィルタラベル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2441, 'aggregation.tag', 'en', 'Tag', 'aggregation', 'Tag option', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2442, 'aggregation.tag', 'ja', 'タグ', 'aggregation', 'タグオプション', datetime('now'));
+
+-- Saved filters on the transaction list
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2443, 'transaction_mgmt.saved_filter', 'en', 'Saved Filter:', 'transaction_mgmt', 'Saved filter label', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2444, 'transaction_mgmt.saved_filter', 'ja', '保存した検索:', 'transaction_mgmt', '保存した検索ラベル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2445, 'transaction_mgmt.saved_filter_name_placeholder', 'en', 'Filter name', 'transaction_mgmt', 'Saved filter name placeholder', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2446, 'transaction_mgmt.saved_filter_name_placeholder', 'ja', '検索名', 'transaction_mgmt', '保存する検索名のプレースホルダー', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2447, 'transaction_mgmt.save_filter', 'en', 'Save Filter', 'transaction_mgmt', 'Save filter button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2448, 'transaction_mgmt.save_filter', 'ja', '検索を保存', 'transaction_mgmt', '検索保存ボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2449, 'transaction_mgmt.delete_saved_filter', 'en', 'Delete', 'transaction_mgmt', 'Delete saved filter button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2450, 'transaction_mgmt.delete_saved_filter', 'ja', '削除', 'transaction_mgmt', '保存した検索の削除ボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2451, 'transaction_mgmt.saved_filter_name_required', 'en', 'Enter a filter name', 'transaction_mgmt', 'Saved filter name missing', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2452, 'transaction_mgmt.saved_filter_name_required', 'ja', '検索名を入力してください', 'transaction_mgmt', '検索名未入力', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2453, 'transaction_mgmt.saved_filter_saved', 'en', 'Filter saved', 'transaction_mgmt', 'Saved filter success', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2454, 'transaction_mgmt.saved_filter_saved', 'ja', '検索条件を保存しました', 'transaction_mgmt', '検索保存成功', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2455, 'transaction_mgmt.saved_filter_save_error', 'en', 'Failed to save filter', 'transaction_mgmt', 'Saved filter failure', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2456, 'transaction_mgmt.saved_filter_save_error', 'ja', '検索条件の保存に失敗しました', 'transaction_mgmt', '検索保存失敗', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2457, 'transaction_mgmt.confirm_delete_saved_filter', 'en', 'Delete this saved filter?', 'transaction_mgmt', 'Delete saved filter confirmation', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2458, 'transaction_mgmt.confirm_delete_saved_filter', 'ja', 'この保存した検索を削除しますか？', 'transaction_mgmt', '保存した検索の削除確認', datetime('now'));
+
+-- Transaction list: shop / account / item filters and sort order
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2459, 'transaction_mgmt.shop_filter', 'en', 'Shop:', 'transaction_mgmt', 'Shop filter label', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2460, 'transaction_mgmt.shop_filter', 'ja', '店舗:', 'transaction_mgmt', '店舗フィルタラベル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2461, 'transaction_mgmt.account_filter', 'en', 'Account:', 'transaction_mgmt', 'Account filter label', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2462, 'transaction_mgmt.account_filter', 'ja', '口座:', 'transaction_mgmt', '口座フィルタラベル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2463, 'transaction_mgmt.item_name_filter', 'en', 'Item:', 'transaction_mgmt', 'Item name filter label', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2464, 'transaction_mgmt.item_name_filter', 'ja', '品名:', 'transaction_mgmt', '品名フィルタラベル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2465, 'transaction_mgmt.item_name_placeholder', 'en', 'Search in item names', 'transaction_mgmt', 'Item name filter placeholder', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2466, 'transaction_mgmt.item_name_placeholder', 'ja', '品名で検索', 'transaction_mgmt', '品名フィルタのプレースホルダー', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2467, 'transaction_mgmt.sort', 'en', 'Sort:', 'transaction_mgmt', 'Sort order label', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2468, 'transaction_mgmt.sort', 'ja', '並び順:', 'transaction_mgmt', '並び順ラベル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2469, 'transaction_mgmt.sort_date_desc', 'en', 'Newest first', 'transaction_mgmt', 'Sort: date descending', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2470, 'transaction_mgmt.sort_date_desc', 'ja', '日付の新しい順', 'transaction_mgmt', '並び順: 日付降順', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2471, 'transaction_mgmt.sort_date_asc', 'en', 'Oldest first', 'transaction_mgmt', 'Sort: date ascending', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2472, 'transaction_mgmt.sort_date_asc', 'ja', '日付の古い順', 'transaction_mgmt', '並び順: 日付昇順', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2473, 'transaction_mgmt.sort_amount_desc', 'en', 'Largest amount first', 'transaction_mgmt', 'Sort: amount descending', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2474, 'transaction_mgmt.sort_amount_desc', 'ja', '金額の大きい順', 'transaction_mgmt', '並び順: 金額降順', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2475, 'transaction_mgmt.sort_amount_asc', 'en', 'Smallest amount first', 'transaction_mgmt', 'Sort: amount ascending', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2476, 'transaction_mgmt.sort_amount_asc', 'ja', '金額の小さい順', 'transaction_mgmt', '並び順: 金額昇順', datetime('now'));
+
+-- Transaction list: full-text search
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2477, 'transaction_mgmt.fulltext_placeholder', 'en', 'Search memos, items, shops and products', 'transaction_mgmt', 'Full-text search placeholder', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2478, 'transaction_mgmt.fulltext_placeholder', 'ja', 'メモ・品名・店舗・商品を検索', 'transaction_mgmt', '全文検索のプレースホルダー', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2479, 'transaction_mgmt.fulltext_search', 'en', 'Search', 'transaction_mgmt', 'Full-text search button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2480, 'transaction_mgmt.fulltext_search', 'ja', '検索', 'transaction_mgmt', '全文検索ボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2481, 'transaction_mgmt.fulltext_clear', 'en', 'Close', 'transaction_mgmt', 'Close full-text results button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2482, 'transaction_mgmt.fulltext_clear', 'ja', '閉じる', 'transaction_mgmt', '全文検索結果を閉じるボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2483, 'transaction_mgmt.fulltext_no_results', 'en', 'No matching transactions', 'transaction_mgmt', 'Full-text search empty result', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2484, 'transaction_mgmt.fulltext_no_results', 'ja', '該当する取引はありません', 'transaction_mgmt', '全文検索の該当なし', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2485, 'transaction_mgmt.fulltext_error', 'en', 'Search failed', 'transaction_mgmt', 'Full-text search failure', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2486, 'transaction_mgmt.fulltext_error', 'ja', '検索に失敗しました', 'transaction_mgmt', '全文検索失敗', datetime('now'));
+
+-- Transaction list: duplicate-entry report
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2487, 'transaction_mgmt.find_duplicates', 'en', 'Find Duplicates', 'transaction_mgmt', 'Duplicate report button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2488, 'transaction_mgmt.find_duplicates', 'ja', '重複を検出', 'transaction_mgmt', '重複検出ボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2489, 'transaction_mgmt.duplicates_title', 'en', 'Possible Duplicates', 'transaction_mgmt', 'Duplicate report title', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2490, 'transaction_mgmt.duplicates_title', 'ja', '重複の可能性がある取引', 'transaction_mgmt', '重複レポートの見出し', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2491, 'transaction_mgmt.duplicates_none', 'en', 'No possible duplicates found', 'transaction_mgmt', 'Duplicate report empty', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2492, 'transaction_mgmt.duplicates_none', 'ja', '重複の可能性がある取引はありません', 'transaction_mgmt', '重複レポートの該当なし', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2493, 'transaction_mgmt.duplicates_similarity', 'en', 'Similarity', 'transaction_mgmt', 'Duplicate group score label', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2494, 'transaction_mgmt.duplicates_similarity', 'ja', '類似度', 'transaction_mgmt', '重複グループの類似度ラベル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2495, 'transaction_mgmt.duplicates_dismiss', 'en', 'Not duplicates', 'transaction_mgmt', 'Dismiss duplicate group button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2496, 'transaction_mgmt.duplicates_dismiss', 'ja', '重複ではない', 'transaction_mgmt', '重複グループを除外するボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2497, 'transaction_mgmt.duplicates_confirm_dismiss', 'en', 'Hide this group from the duplicate report?', 'transaction_mgmt', 'Dismiss duplicate group confirmation', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2498, 'transaction_mgmt.duplicates_confirm_dismiss', 'ja', 'このグループを重複レポートに表示しないようにしますか？', 'transaction_mgmt', '重複グループ除外の確認', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2499, 'transaction_mgmt.duplicates_error', 'en', 'Duplicate check failed', 'transaction_mgmt', 'Duplicate report failure', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2500, 'transaction_mgmt.duplicates_error', 'ja', '重複の検出に失敗しました', 'transaction_mgmt', '重複検出失敗', datetime('now'));
+
+-- Transaction list: bulk edit
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2501, 'transaction_mgmt.bulk_selected_count', 'en', '{count} selected', 'transaction_mgmt', 'Bulk edit selection count', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2502, 'transaction_mgmt.bulk_selected_count', 'ja', '{count}件選択中', 'transaction_mgmt', '一括編集の選択件数', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2503, 'transaction_mgmt.bulk_select_all', 'en', 'Select all on this page', 'transaction_mgmt', 'Bulk edit select-all checkbox', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2504, 'transaction_mgmt.bulk_select_all', 'ja', 'このページをすべて選択', 'transaction_mgmt', '一括編集の全選択', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2505, 'transaction_mgmt.bulk_set_shop', 'en', 'Set shop', 'transaction_mgmt', 'Bulk action: set shop', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2506, 'transaction_mgmt.bulk_set_shop', 'ja', '店舗を設定', 'transaction_mgmt', '一括操作: 店舗設定', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2507, 'transaction_mgmt.bulk_set_categories', 'en', 'Set category', 'transaction_mgmt', 'Bulk action: set category2/3', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2508, 'transaction_mgmt.bulk_set_categories', 'ja', '分類を設定', 'transaction_mgmt', '一括操作: 分類設定', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2509, 'transaction_mgmt.bulk_move_account', 'en', 'Move account', 'transaction_mgmt', 'Bulk action: move account', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2510, 'transaction_mgmt.bulk_move_account', 'ja', '口座を変更', 'transaction_mgmt', '一括操作: 口座変更', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2511, 'transaction_mgmt.bulk_set_memo', 'en', 'Set memo', 'transaction_mgmt', 'Bulk action: set memo', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2512, 'transaction_mgmt.bulk_set_memo', 'ja', 'メモを設定', 'transaction_mgmt', '一括操作: メモ設定', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2513, 'transaction_mgmt.bulk_confirm_scheduled', 'en', 'Confirm scheduled', 'transaction_mgmt', 'Bulk action: confirm scheduled', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2514, 'transaction_mgmt.bulk_confirm_scheduled', 'ja', '予定を確定', 'transaction_mgmt', '一括操作: 予定確定', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2515, 'transaction_mgmt.bulk_delete', 'en', 'Delete', 'transaction_mgmt', 'Bulk action: delete', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2516, 'transaction_mgmt.bulk_delete', 'ja', '削除', 'transaction_mgmt', '一括操作: 削除', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2517, 'transaction_mgmt.bulk_clear_value', 'en', '(Clear)', 'transaction_mgmt', 'Bulk edit clear option', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2518, 'transaction_mgmt.bulk_clear_value', 'ja', '(クリア)', 'transaction_mgmt', '一括編集のクリア選択肢', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2519, 'transaction_mgmt.bulk_memo_placeholder', 'en', 'Memo (blank to clear)', 'transaction_mgmt', 'Bulk memo placeholder', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2520, 'transaction_mgmt.bulk_memo_placeholder', 'ja', 'メモ (空欄でクリア)', 'transaction_mgmt', '一括メモのプレースホルダー', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2521, 'transaction_mgmt.bulk_apply', 'en', 'Apply to Selected', 'transaction_mgmt', 'Bulk apply button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2522, 'transaction_mgmt.bulk_apply', 'ja', '選択した取引に適用', 'transaction_mgmt', '一括適用ボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2523, 'transaction_mgmt.bulk_confirm_apply', 'en', 'Apply this change to {count} transactions?', 'transaction_mgmt', 'Bulk apply confirmation', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2524, 'transaction_mgmt.bulk_confirm_apply', 'ja', '{count}件の取引に適用しますか？', 'transaction_mgmt', '一括適用の確認', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2525, 'transaction_mgmt.bulk_confirm_delete', 'en', 'Delete {count} transactions?', 'transaction_mgmt', 'Bulk delete confirmation', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2526, 'transaction_mgmt.bulk_confirm_delete', 'ja', '{count}件の取引を削除しますか？', 'transaction_mgmt', '一括削除の確認', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2527, 'transaction_mgmt.bulk_done', 'en', '{count} transactions updated', 'transaction_mgmt', 'Bulk edit success', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2528, 'transaction_mgmt.bulk_done', 'ja', '{count}件の取引を更新しました', 'transaction_mgmt', '一括編集成功', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2529, 'transaction_mgmt.bulk_failed', 'en', '{count} transactions could not be changed:', 'transaction_mgmt', 'Bulk edit partial failure', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2530, 'transaction_mgmt.bulk_failed', 'ja', '{count}件の取引は変更できませんでした:', 'transaction_mgmt', '一括編集の一部失敗', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2531, 'transaction_mgmt.bulk_error', 'en', 'Bulk edit failed', 'transaction_mgmt', 'Bulk edit failure', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2532, 'transaction_mgmt.bulk_error', 'ja', '一括編集に失敗しました', 'transaction_mgmt', '一括編集失敗', datetime('now'));
+
+-- Transaction list: undo / redo history
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2533, 'transaction_mgmt.undo', 'en', 'Undo', 'transaction_mgmt', 'Undo last change button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2534, 'transaction_mgmt.undo', 'ja', '元に戻す', 'transaction_mgmt', '直前の変更を元に戻すボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2535, 'transaction_mgmt.redo', 'en', 'Redo', 'transaction_mgmt', 'Redo button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2536, 'transaction_mgmt.redo', 'ja', 'やり直す', 'transaction_mgmt', 'やり直しボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2537, 'transaction_mgmt.undo_history', 'en', 'History', 'transaction_mgmt', 'Undo history button', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2538, 'transaction_mgmt.undo_history', 'ja', '操作履歴', 'transaction_mgmt', '操作履歴ボタン', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2539, 'transaction_mgmt.undo_history_title', 'en', 'Recent Changes', 'transaction_mgmt', 'Undo history panel title', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2540, 'transaction_mgmt.undo_history_title', 'ja', '最近の変更', 'transaction_mgmt', '操作履歴パネルのタイトル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2541, 'transaction_mgmt.undo_history_empty', 'en', 'No changes in this session', 'transaction_mgmt', 'Undo history empty state', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2542, 'transaction_mgmt.undo_history_empty', 'ja', 'このセッションでの変更はありません', 'transaction_mgmt', '操作履歴が空', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2543, 'transaction_mgmt.undo_rows', 'en', '{count} rows', 'transaction_mgmt', 'Rows changed by an operation', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2544, 'transaction_mgmt.undo_rows', 'ja', '{count}行', 'transaction_mgmt', '操作で変更された行数', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2545, 'transaction_mgmt.undo_status_undone', 'en', '(undone)', 'transaction_mgmt', 'Undone operation marker', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2546, 'transaction_mgmt.undo_status_undone', 'ja', '(取り消し済み)', 'transaction_mgmt', '取り消し済みの表示', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2547, 'transaction_mgmt.undo_done', 'en', 'Change undone', 'transaction_mgmt', 'Undo success', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2548, 'transaction_mgmt.undo_done', 'ja', '変更を元に戻しました', 'transaction_mgmt', '元に戻す成功', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2549, 'transaction_mgmt.redo_done', 'en', 'Change redone', 'transaction_mgmt', 'Redo success', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2550, 'transaction_mgmt.redo_done', 'ja', '変更をやり直しました', 'transaction_mgmt', 'やり直し成功', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2551, 'transaction_mgmt.undo_nothing', 'en', 'Nothing to undo', 'transaction_mgmt', 'Undo with empty history', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2552, 'transaction_mgmt.undo_nothing', 'ja', '元に戻す変更はありません', 'transaction_mgmt', '元に戻す対象なし', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2553, 'transaction_mgmt.redo_nothing', 'en', 'Nothing to redo', 'transaction_mgmt', 'Redo with nothing undone', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2554, 'transaction_mgmt.redo_nothing', 'ja', 'やり直す変更はありません', 'transaction_mgmt', 'やり直す対象なし', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2555, 'transaction_mgmt.undo_conflict', 'en', 'The data was edited afterwards, so nothing was changed', 'transaction_mgmt', 'Undo/redo refused by a later edit', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2556, 'transaction_mgmt.undo_conflict', 'ja', '後から編集されたデータがあるため、変更しませんでした', 'transaction_mgmt', '後の編集により元に戻せない', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2557, 'transaction_mgmt.undo_error', 'en', 'Undo failed', 'transaction_mgmt', 'Undo/redo failure', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2558, 'transaction_mgmt.undo_error', 'ja', '元に戻せませんでした', 'transaction_mgmt', '元に戻す失敗', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2559, 'transaction_mgmt.undo_entity_transaction', 'en', 'Transaction', 'transaction_mgmt', 'Undo label: transaction', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2560, 'transaction_mgmt.undo_entity_transaction', 'ja', '入出金', 'transaction_mgmt', '操作対象: 入出金', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2561, 'transaction_mgmt.undo_entity_category', 'en', 'Category', 'transaction_mgmt', 'Undo label: category', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2562, 'transaction_mgmt.undo_entity_category', 'ja', '費目', 'transaction_mgmt', '操作対象: 費目', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2563, 'transaction_mgmt.undo_entity_account', 'en', 'Account', 'transaction_mgmt', 'Undo label: account', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2564, 'transaction_mgmt.undo_entity_account', 'ja', '口座', 'transaction_mgmt', '操作対象: 口座', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2565, 'transaction_mgmt.undo_entity_shop', 'en', 'Shop', 'transaction_mgmt', 'Undo label: shop', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2566, 'transaction_mgmt.undo_entity_shop', 'ja', '店舗', 'transaction_mgmt', '操作対象: 店舗', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2567, 'transaction_mgmt.undo_entity_manufacturer', 'en', 'Manufacturer', 'transaction_mgmt', 'Undo label: manufacturer', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2568, 'transaction_mgmt.undo_entity_manufacturer', 'ja', 'メーカー', 'transaction_mgmt', '操作対象: メーカー', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2569, 'transaction_mgmt.undo_entity_product', 'en', 'Product', 'transaction_mgmt', 'Undo label: product', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2570, 'transaction_mgmt.undo_entity_product', 'ja', '商品', 'transaction_mgmt', '操作対象: 商品', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2571, 'transaction_mgmt.undo_action_add', 'en', 'add', 'transaction_mgmt', 'Undo label: add', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2572, 'transaction_mgmt.undo_action_add', 'ja', '追加', 'transaction_mgmt', '操作: 追加', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2573, 'transaction_mgmt.undo_action_update', 'en', 'edit', 'transaction_mgmt', 'Undo label: update', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2574, 'transaction_mgmt.undo_action_update', 'ja', '編集', 'transaction_mgmt', '操作: 編集', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2575, 'transaction_mgmt.undo_action_delete', 'en', 'delete', 'transaction_mgmt', 'Undo label: delete', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2576, 'transaction_mgmt.undo_action_delete', 'ja', '削除', 'transaction_mgmt', '操作: 削除', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2577, 'transaction_mgmt.undo_action_move', 'en', 'reorder', 'transaction_mgmt', 'Undo label: move', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2578, 'transaction_mgmt.undo_action_move', 'ja', '並べ替え', 'transaction_mgmt', '操作: 並べ替え', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2579, 'transaction_mgmt.undo_action_enable', 'en', 'enable', 'transaction_mgmt', 'Undo label: enable', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2580, 'transaction_mgmt.undo_action_enable', 'ja', '有効化', 'transaction_mgmt', '操作: 有効化', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2581, 'transaction_mgmt.undo_action_disable', 'en', 'disable', 'transaction_mgmt', 'Undo label: disable', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2582, 'transaction_mgmt.undo_action_disable', 'ja', '無効化', 'transaction_mgmt', '操作: 無効化', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2583, 'transaction_mgmt.undo_action_confirm', 'en', 'confirm scheduled', 'transaction_mgmt', 'Undo label: confirm scheduled', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2584, 'transaction_mgmt.undo_action_confirm', 'ja', '予定確定', 'transaction_mgmt', '操作: 予定確定', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2585, 'transaction_mgmt.undo_action_bulk_edit', 'en', 'bulk edit', 'transaction_mgmt', 'Undo label: bulk edit', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2586, 'transaction_mgmt.undo_action_bulk_edit', 'ja', '一括編集', 'transaction_mgmt', '操作: 一括編集', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2587, 'recurring_rule.no_end_date', 'en', 'No end date', 'recurring_rule', 'Open-ended rule checkbox and list label', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2588, 'recurring_rule.no_end_date', 'ja', '終了日なし', 'recurring_rule', '終了日なしチェックボックス・一覧表示', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2589, 'recurring_rule.is_estimated', 'en', 'Amount is an estimate', 'recurring_rule', 'Variable-amount rule checkbox', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2590, 'recurring_rule.is_estimated', 'ja', '金額は見込み', 'recurring_rule', '変動額ルールのチェックボックス', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2591, 'recurring_rule.estimated_badge', 'en', 'est.', 'recurring_rule', 'List label for estimated amounts', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2592, 'recurring_rule.estimated_badge', 'ja', '見込み', 'recurring_rule', '一覧の見込み額表示', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2593, 'recurring_rule.suggest_estimate', 'en', 'Suggest estimate', 'recurring_rule', 'Button: suggest estimate from history', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2594, 'recurring_rule.suggest_estimate', 'ja', '見込み額を提案', 'recurring_rule', 'ボタン: 実績から見込み額を提案', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2595, 'recurring_rule.estimate_suggested', 'en', 'Suggested estimate: {0} (average of the last {1} confirmed)', 'recurring_rule', 'Suggested estimate message', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2596, 'recurring_rule.estimate_suggested', 'ja', '見込み額の提案: {0}（直近{1}件の確定実績の平均）', 'recurring_rule', '見込み額の提案メッセージ', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2597, 'recurring_rule.estimate_no_history', 'en', 'No confirmed occurrences to estimate from yet.', 'recurring_rule', 'No history for estimate suggestion', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2598, 'recurring_rule.estimate_no_history', 'ja', '見込み額の算出に使える確定済みの実績がまだありません。', 'recurring_rule', '見込み額算出の実績なし', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2599, 'transaction_mgmt.confirm_actual_amount', 'en', 'Enter the actual amount:', 'transaction_mgmt', 'Prompt for actual amount on confirm', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2600, 'transaction_mgmt.confirm_actual_amount', 'ja', '実際の金額を入力してください:', 'transaction_mgmt', '確定時の実績金額入力', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2601, 'transaction_mgmt.confirm_actual_amount_invalid', 'en', 'Enter a valid amount.', 'transaction_mgmt', 'Invalid actual amount', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2602, 'transaction_mgmt.confirm_actual_amount_invalid', 'ja', '正しい金額を入力してください。', 'transaction_mgmt', '実績金額が不正', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2603, 'recurring_rule.exceptions', 'en', 'Exceptions', 'recurring_rule', 'Button: open occurrence exceptions', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2604, 'recurring_rule.exceptions', 'ja', '例外', 'recurring_rule', 'ボタン: 個別の回の例外を開く', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2605, 'recurring_rule.exceptions_title', 'en', 'Skipped / moved occurrences', 'recurring_rule', 'Exceptions modal title', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2606, 'recurring_rule.exceptions_title', 'ja', 'スキップ・日付変更した回', 'recurring_rule', '例外ダイアログのタイトル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2607, 'recurring_rule.col_occurrence_date', 'en', 'Occurrence', 'recurring_rule', 'Column: generated occurrence date', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2608, 'recurring_rule.col_occurrence_date', 'ja', '対象日', 'recurring_rule', '列: ルールが生成する日付', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2609, 'recurring_rule.col_exception', 'en', 'Change', 'recurring_rule', 'Column: exception type', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2610, 'recurring_rule.col_exception', 'ja', '変更内容', 'recurring_rule', '列: 例外の種類', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2611, 'recurring_rule.no_exceptions', 'en', 'Every occurrence follows the rule.', 'recurring_rule', 'No exceptions message', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2612, 'recurring_rule.no_exceptions', 'ja', 'すべての回がルールどおりです。', 'recurring_rule', '例外なしのメッセージ', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2613, 'recurring_rule.exception_skip', 'en', 'Skip', 'recurring_rule', 'Exception type: skip', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2614, 'recurring_rule.exception_skip', 'ja', 'スキップ', 'recurring_rule', '例外の種類: スキップ', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2615, 'recurring_rule.exception_move', 'en', 'Move to', 'recurring_rule', 'Exception type: move', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2616, 'recurring_rule.exception_move', 'ja', '日付を変更', 'recurring_rule', '例外の種類: 日付変更', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2617, 'recurring_rule.exception_moved_to', 'en', 'New date', 'recurring_rule', 'Label: moved-to date', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2618, 'recurring_rule.exception_moved_to', 'ja', '変更後の日付', 'recurring_rule', 'ラベル: 変更後の日付', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2619, 'recurring_rule.exception_moved_to_display', 'en', 'Moved to {0}', 'recurring_rule', 'Moved exception display', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2620, 'recurring_rule.exception_moved_to_display', 'ja', '{0} に変更', 'recurring_rule', '日付変更の表示', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2621, 'recurring_rule.exception_undo', 'en', 'Undo', 'recurring_rule', 'Button: remove exception', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2622, 'recurring_rule.exception_undo', 'ja', '元に戻す', 'recurring_rule', 'ボタン: 例外を取り消す', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2623, 'recurring_rule.exception_date_required', 'en', 'Enter the occurrence date.', 'recurring_rule', 'Occurrence date missing', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2624, 'recurring_rule.exception_date_required', 'ja', '対象日を入力してください。', 'recurring_rule', '対象日が未入力', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2625, 'transaction_mgmt.undo_entity_tag', 'en', 'Tag', 'transaction_mgmt', 'Undo label: tag', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2626, 'transaction_mgmt.undo_entity_tag', 'ja', 'タグ', 'transaction_mgmt', '操作対象: タグ', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2627, 'transaction_mgmt.undo_entity_attachment', 'en', 'Attachment', 'transaction_mgmt', 'Undo label: attachment', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2628, 'transaction_mgmt.undo_entity_attachment', 'ja', '添付ファイル', 'transaction_mgmt', '操作対象: 添付ファイル', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2629, 'transaction_mgmt.undo_entity_card_statement', 'en', 'Card statement', 'transaction_mgmt', 'Undo label: card statement', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2630, 'transaction_mgmt.undo_entity_card_statement', 'ja', 'カード明細', 'transaction_mgmt', '操作対象: カード明細', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2631, 'transaction_mgmt.undo_entity_recurring_rule', 'en', 'Recurring rule', 'transaction_mgmt', 'Undo label: recurring rule', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2632, 'transaction_mgmt.undo_entity_recurring_rule', 'ja', '定期予定', 'transaction_mgmt', '操作対象: 定期予定', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2633, 'transaction_mgmt.undo_action_import', 'en', 'CSV import', 'transaction_mgmt', 'Undo label: CSV import', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2634, 'transaction_mgmt.undo_action_import', 'ja', 'CSV取込', 'transaction_mgmt', '操作: CSV取込', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2635, 'transaction_mgmt.undo_action_issue', 'en', 'issue', 'transaction_mgmt', 'Undo label: issue statement', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2636, 'transaction_mgmt.undo_action_issue', 'ja', '発行', 'transaction_mgmt', '操作: 明細発行', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2637, 'transaction_mgmt.undo_action_set_tags', 'en', 'set tags', 'transaction_mgmt', 'Undo label: set tags', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2638, 'transaction_mgmt.undo_action_set_tags', 'ja', 'タグ設定', 'transaction_mgmt', '操作: タグ設定', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2639, 'transaction_mgmt.undo_action_save_exception', 'en', 'skip / move', 'transaction_mgmt', 'Undo label: save exception', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2640, 'transaction_mgmt.undo_action_save_exception', 'ja', 'スキップ・日付変更', 'transaction_mgmt', '操作: 例外登録', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2641, 'transaction_mgmt.undo_action_delete_exception', 'en', 'restore occurrence', 'transaction_mgmt', 'Undo label: delete exception', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2642, 'transaction_mgmt.undo_action_delete_exception', 'ja', '予定を元に戻す', 'transaction_mgmt', '操作: 例外削除', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2643, 'transaction_mgmt.undo_action_clear', 'en', 'cleared mark', 'transaction_mgmt', 'Undo label: set cleared', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2644, 'transaction_mgmt.undo_action_clear', 'ja', '照合済みマーク', 'transaction_mgmt', '操作: 照合マーク', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2645, 'transaction_mgmt.undo_action_reconcile', 'en', 'reconcile', 'transaction_mgmt', 'Undo label: reconcile', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2646, 'transaction_mgmt.undo_action_reconcile', 'ja', '残高照合', 'transaction_mgmt', '操作: 残高照合', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2647, 'transaction_mgmt.undo_action_unlock', 'en', 'unlock', 'transaction_mgmt', 'Undo label: unlock reconciled', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2648, 'transaction_mgmt.undo_action_unlock', 'ja', 'ロック解除', 'transaction_mgmt', '操作: ロック解除', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2649, 'transaction_mgmt.undo_action_dismiss_duplicates', 'en', 'not duplicates', 'transaction_mgmt', 'Undo label: dismiss duplicates', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2650, 'transaction_mgmt.undo_action_dismiss_duplicates', 'ja', '重複ではない', 'transaction_mgmt', '操作: 重複を除外', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2651, 'transaction_mgmt.undo_action_set_currency', 'en', 'change currency', 'transaction_mgmt', 'Undo label: set currency', datetime('now'));
+INSERT OR IGNORE INTO I18N_RESOURCES (RESOURCE_ID, RESOURCE_KEY, LANG_CODE, RESOURCE_VALUE, CATEGORY, DESCRIPTION, ENTRY_DT) VALUES (2652, 'transaction_mgmt.undo_action_set_currency', 'ja', '通貨変更', 'transaction_mgmt', '操作: 通貨変更', datetime('now'));
@@ -218,6 +218,7 @@ describe('mapMasterErrorCode — ApiError code → i18n key', () => {
         expect(API_ERROR_CODES.ADMIN_PROTECTED).toBe('admin_protected');
         expect(API_ERROR_CODES.VALIDATION).toBe('validation');
         expect(API_ERROR_CODES.DATABASE).toBe('database');
+        expect(API_ERROR_CODES.JOURNAL_CONFLICT).toBe('journal_conflict');
     });
 });
 
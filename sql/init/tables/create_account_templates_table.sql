@@ -8,5 +8,6 @@ CREATE TABLE ACCOUNT_TEMPLATES (
     TEMPLATE_NAME_JA TEXT NOT NULL,
     TEMPLATE_NAME_EN TEXT NOT NULL,
     DISPLAY_ORDER INTEGER,
+    ACCOUNT_CLASS INTEGER NOT NULL DEFAULT 0,
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now'))
 );
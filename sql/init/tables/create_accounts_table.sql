@@ -30,6 +30,7 @@ CREATE TABLE ACCOUNTS (
     INITIAL_BALANCE INTEGER DEFAULT 0,
     DISPLAY_ORDER INTEGER,
     IS_DISABLED INTEGER DEFAULT 0,
+    CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY',
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
     FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
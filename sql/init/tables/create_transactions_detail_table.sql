@@ -3,6 +3,7 @@
 -- Created: 2025-01-07
 -- Updated: 2025-11-17 - Added USER_ID, CATEGORY1_CODE, AMOUNT_INCLUDING_TAX
 -- Updated: 2025-11-18 - Removed MEMO_ID foreign key constraint (MEMO_ID is now a simple reference)
+-- Updated: 2026-10-18 - Added FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE for split lines
 
 CREATE TABLE IF NOT EXISTS TRANSACTIONS_DETAIL (
     DETAIL_ID INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -17,6 +18,8 @@ CREATE TABLE IF NOT EXISTS TRANSACTIONS_DETAIL (
     TAX_RATE INTEGER DEFAULT 8,  -- Default 8% for food items
     AMOUNT_INCLUDING_TAX INTEGER,
     MEMO_ID INTEGER,  -- Simple reference, no foreign key constraint
+    FROM_ACCOUNT_CODE VARCHAR(50),  -- Split line only; NULL follows the header
+    TO_ACCOUNT_CODE VARCHAR(50),    -- Split line only; NULL follows the header
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now', 'localtime')),
     UPDATE_DT DATETIME,
     FOREIGN KEY (TRANSACTION_ID) REFERENCES TRANSACTIONS_HEADER(TRANSACTION_ID) ON DELETE CASCADE,
@@ -49,6 +49,8 @@ impl ApiError {
     pub const CODE_ADMIN_PROTECTED: &'static str = "admin_protected";
     pub const CODE_VALIDATION: &'static str = "validation";
     pub const CODE_DATABASE: &'static str = "database";
+    pub const CODE_RECONCILED_LOCKED: &'static str = "reconciled_locked";
+    pub const CODE_JOURNAL_CONFLICT: &'static str = "journal_conflict";
     // PR14 (Fable-5 #21) — auth-specific codes so the login / setup
     // screens can map to their own i18n messages instead of dumping the
     // raw English `Err` string next to a Japanese label.
@@ -126,6 +128,28 @@ impl ApiError {
         }
     }
 
+    /// Edit refused because the transaction was reconciled against a bank
+    /// statement. The UI offers an explicit "unlock" instead of a generic
+    /// validation toast, so it gets its own code.
+    pub fn reconciled_locked() -> Self {
+        Self {
+            code: Self::CODE_RECONCILED_LOCKED.to_string(),
+            message: "Transaction is reconciled; unlock it before editing".to_string(),
+            entity: Some("transaction".to_string()),
+        }
+    }
+
+    /// Undo / redo refused because a row the operation touched has been
+    /// changed since. Separate from validation so the UI can explain that
+    /// nothing was rolled back rather than show a generic failure.
+    pub fn journal_conflict(table: &str) -> Self {
+        Self {
+            code: Self::CODE_JOURNAL_CONFLICT.to_string(),
+            message: format!("{} was changed after this operation; nothing was restored", table),
+            entity: None,
+        }
+    }
+
     /// Wraps an unexpected database error. The frontend does not classify
     /// on the message contents — it just shows a generic "save failed"
     /// toast — so any specific text is safe to keep for logs.
@@ -220,6 +244,13 @@ mod tests {
         assert_eq!(err.entity.as_deref(), Some("manufacturer"));
     }
 
+    #[test]
+    fn reconciled_locked_has_its_own_code() {
+        let err = ApiError::reconciled_locked();
+        assert_eq!(err.code, "reconciled_locked");
+        assert_eq!(err.entity.as_deref(), Some("transaction"));
+    }
+
     #[test]
     fn validation_carries_message_through_and_omits_entity() {
         let err = ApiError::validation("Shop name cannot be empty");
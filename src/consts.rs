@@ -36,6 +36,26 @@ pub const TAX_INCLUDED: i64 = 0;  // 内税 - tax is included in prices
 #[allow(dead_code)]
 pub const TAX_EXCLUDED: i64 = 1;  // 外税 - tax is calculated separately
 
+// CATEGORY1_CODE values the ledger sign rules key on (see
+// sql_queries::ACCOUNT_BALANCES_AS_OF)
+pub const CATEGORY1_INCOME: &str = "INCOME";
+pub const CATEGORY1_EXPENSE: &str = "EXPENSE";
+pub const CATEGORY1_TRANSFER: &str = "TRANSFER";
+
+// ACCOUNT_TEMPLATES.ACCOUNT_CLASS value for liabilities; anything else (the
+// column default is 0) is an asset. Liability balances are reported as the
+// amount owed (see sql_queries::ACCOUNT_BALANCES_AS_OF).
+pub const ACCOUNT_CLASS_LIABILITY: i64 = 1;
+
+// TRANSACTIONS_HEADER.RECONCILE_STATUS values. RECONCILED rows are locked
+// against header edits until explicitly unlocked back to CLEARED.
+pub const RECONCILE_STATUS_UNCLEARED: i64 = 0;
+pub const RECONCILE_STATUS_CLEARED: i64 = 1;
+pub const RECONCILE_STATUS_RECONCILED: i64 = 2;
+
+// Placeholder account created for every user by initialize_none_account
+pub const ACCOUNT_CODE_NONE: &str = "NONE";
+
 // Recurring scheduled transactions (v2.1.0) — HOLIDAY_SHIFT_TYPE column values
 pub const HOLIDAY_SHIFT_NONE: i32 = 0;
 pub const HOLIDAY_SHIFT_PREV: i32 = 1;
@@ -53,6 +73,18 @@ pub const MONTH_DAY_RULE_TYPE_DAY_OR_END: &str = "DAY_OR_END";
 pub const MONTH_DAY_RULE_TYPE_END: &str = "END";
 pub const MONTH_DAY_RULE_TYPE_NTH_WEEKDAY: &str = "NTH_WEEKDAY";
 
+// Recurring scheduled transactions — RECURRING_RULE_EXCEPTIONS.EXCEPTION_TYPE values
+pub const RECURRING_EXCEPTION_SKIP: &str = "SKIP";
+pub const RECURRING_EXCEPTION_MOVE: &str = "MOVE";
+
+// Open-ended recurring rules (no END_DATE) keep occurrences generated this
+// many months ahead of today
+pub const RECURRING_OPEN_ENDED_HORIZON_MONTHS: u32 = 13;
+
+// Confirmed occurrences averaged when suggesting the estimate of a
+// variable-amount recurring rule, unless the caller asks for another count
+pub const RECURRING_ESTIMATE_SAMPLE_SIZE: u32 = 3;
+
 // Bounded-field length limits (in characters, not bytes).
 // Paired with `validation.max_length` i18n key for the user-facing message.
 pub const MAX_NAME_LEN: usize = 128;          // USERS.NAME, CATEGORY*_NAME, ACCOUNTS.ACCOUNT_NAME, SHOPS/MANUFACTURERS/PRODUCTS names
@@ -50,6 +50,16 @@ impl Crypto {
     /// * `Ok(String)` - Base64-encoded encrypted data
     /// * `Err(CryptoError)` - If encryption fails
     pub fn encrypt(&self, plaintext: &str) -> Result<String, CryptoError> {
+        let combined = self.encrypt_bytes(plaintext.as_bytes())?;
+
+        // Encode to Base64
+        Ok(general_purpose::STANDARD.encode(&combined))
+    }
+
+    /// Encrypt raw bytes (e.g. an attachment) without Base64 encoding
+    ///
+    /// Format: nonce || ciphertext
+    pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
         // Generate random 96-bit nonce
         let nonce_bytes: [u8; 12] = rand::random();
         let nonce = nonce_bytes.into();
@@ -57,16 +67,14 @@ impl Crypto {
         // Encrypt
         let ciphertext = self
             .cipher
-            .encrypt(&nonce, plaintext.as_bytes())
+            .encrypt(&nonce, plaintext)
             .map_err(|e| CryptoError::EncryptionError(e.to_string()))?;
 
         // Combine nonce + ciphertext
         let mut combined = Vec::with_capacity(12 + ciphertext.len());
         combined.extend_from_slice(&nonce_bytes);
         combined.extend_from_slice(&ciphertext);
-
-        // Encode to Base64
-        Ok(general_purpose::STANDARD.encode(&combined))
+        Ok(combined)
     }
 
     /// Decrypt Base64-encoded ciphertext
@@ -83,6 +91,15 @@ impl Crypto {
             .decode(encoded)
             .map_err(|e| CryptoError::EncodingError(e.to_string()))?;
 
+        let plaintext_bytes = self.decrypt_bytes(&combined)?;
+
+        // Convert to UTF-8 string
+        String::from_utf8(plaintext_bytes)
+            .map_err(|e| CryptoError::DecryptionError(format!("Invalid UTF-8: {}", e)))
+    }
+
+    /// Decrypt raw `nonce || ciphertext` bytes produced by `encrypt_bytes`
+    pub fn decrypt_bytes(&self, combined: &[u8]) -> Result<Vec<u8>, CryptoError> {
         if combined.len() < 12 {
             return Err(CryptoError::DecryptionError(
                 "Invalid ciphertext: too short".to_string(),
@@ -96,14 +113,9 @@ impl Crypto {
         let nonce = nonce.into();
 
         // Decrypt
-        let plaintext_bytes = self
-            .cipher
+        self.cipher
             .decrypt(&nonce, ciphertext)
-            .map_err(|e| CryptoError::DecryptionError(e.to_string()))?;
-
-        // Convert to UTF-8 string
-        String::from_utf8(plaintext_bytes)
-            .map_err(|e| CryptoError::DecryptionError(format!("Invalid UTF-8: {}", e)))
+            .map_err(|e| CryptoError::DecryptionError(e.to_string()))
     }
 }
 
@@ -197,6 +209,20 @@ mod tests {
         assert_eq!(plaintext, decrypted);
     }
 
+    #[test]
+    fn test_encrypt_decrypt_bytes_roundtrip_binary() {
+        let crypto = Crypto::new(get_test_key());
+        // Not valid UTF-8: the byte API must not care
+        let plaintext: Vec<u8> = vec![0x25, 0x50, 0x44, 0x46, 0xff, 0x00, 0xfe, 0x80];
+
+        let encrypted = crypto.encrypt_bytes(&plaintext).expect("Failed to encrypt");
+        assert_eq!(encrypted.len(), 12 + plaintext.len() + 16);
+        let decrypted = crypto.decrypt_bytes(&encrypted).expect("Failed to decrypt");
+        assert_eq!(plaintext, decrypted);
+
+        assert!(Crypto::new([7u8; 32]).decrypt_bytes(&encrypted).is_err());
+    }
+
     #[test]
     fn test_different_keys_produce_different_results() {
         let key1 = [1u8; 32];
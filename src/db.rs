@@ -58,6 +58,13 @@ impl Database {
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
+
+    /// Wrap an already-connected pool. Lets service tests run the real
+    /// `initialize` + `migrate_*` sequence against an in-memory database.
+    #[cfg(test)]
+    pub fn from_pool(pool: SqlitePool) -> Self {
+        Database { pool }
+    }
     
     /// Run every statement in `res/sql/dbaccess.sql` in a single
     /// transaction. Fable-5 review #30: the previous shape autocommitted
@@ -327,8 +334,14 @@ impl Database {
     ///   from TRANSACTIONS_HEADER and FIRST_TRANSACTION_ID from RECURRING_RULES
     ///   if a previous unreleased build of this branch added them
     /// - Adds HOLIDAY_LOCALE/WEEK_START_DAY to USERS
-    /// - Creates RECURRING_RULES, RECURRING_RULE_DETAILS, HOLIDAYS_STANDARD,
-    ///   HOLIDAYS_USER_CUSTOM tables
+    /// - Creates RECURRING_RULES, RECURRING_RULE_DETAILS,
+    ///   RECURRING_RULE_EXCEPTIONS, HOLIDAYS_STANDARD, HOLIDAYS_USER_CUSTOM
+    ///   tables
+    /// - Rebuilds RECURRING_RULES with a nullable END_DATE and the
+    ///   MATERIALIZED_UNTIL column used by open-ended rules
+    /// - Rebuilds RECURRING_RULE_DETAILS so a rule can hold several DETAIL
+    ///   templates, each with an optional PRODUCT_ID
+    /// - Adds IS_ESTIMATED to RECURRING_RULES
     /// - Seeds HOLIDAYS_STANDARD with Japanese statutory holidays generated by
     ///   the jpholiday crate for a sliding window around the current year
     pub async fn migrate_recurring(&self) -> Result<(), sqlx::Error> {
@@ -336,6 +349,9 @@ impl Database {
         self.ensure_users_recurring_columns().await?;
         self.create_recurring_tables().await?;
         self.drop_obsolete_linked_list_columns().await?;
+        self.ensure_recurring_rules_open_ended().await?;
+        self.ensure_recurring_rule_details_multi_line().await?;
+        self.ensure_recurring_rules_estimated_column().await?;
         self.seed_japanese_holidays().await?;
         Ok(())
     }
@@ -421,6 +437,105 @@ impl Database {
         Ok(())
     }
 
+    /// Let RECURRING_RULES hold open-ended rules: END_DATE becomes nullable
+    /// and MATERIALIZED_UNTIL records how far occurrences were generated.
+    /// SQLite cannot drop a NOT NULL in place, so the table is rebuilt; the
+    /// PRAGMA handling is the same as `migrate_transactions_detail_table`,
+    /// which also keeps the DROP from cascading into RECURRING_RULE_DETAILS
+    /// or clearing TRANSACTIONS_HEADER.RULE_ID. Skipped once the column
+    /// exists, including on fresh installs.
+    async fn ensure_recurring_rules_open_ended(&self) -> Result<(), sqlx::Error> {
+        let has_column: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('RECURRING_RULES') WHERE name = 'MATERIALIZED_UNTIL'"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if has_column == 1 {
+            return Ok(());
+        }
+
+        self.rebuild_with_foreign_keys_off(&[
+            sql_queries::MIGRATE_RECURRING_RULES_CREATE_NEW,
+            sql_queries::MIGRATE_RECURRING_RULES_COPY_DATA,
+            sql_queries::MIGRATE_RECURRING_RULES_DROP_OLD,
+            sql_queries::MIGRATE_RECURRING_RULES_RENAME_NEW,
+            sql_queries::CREATE_RECURRING_RULES_USER_INDEX,
+        ])
+        .await
+    }
+
+    /// Let a recurring rule carry several DETAIL templates: the UNIQUE on
+    /// RECURRING_RULE_DETAILS.RULE_ID goes and PRODUCT_ID is added. The
+    /// constraint lives in the table definition, so the table is rebuilt.
+    /// Skipped once PRODUCT_ID exists, including on fresh installs.
+    async fn ensure_recurring_rule_details_multi_line(&self) -> Result<(), sqlx::Error> {
+        let has_column: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('RECURRING_RULE_DETAILS') WHERE name = 'PRODUCT_ID'"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if has_column == 1 {
+            return Ok(());
+        }
+
+        self.rebuild_with_foreign_keys_off(&[
+            sql_queries::MIGRATE_RECURRING_RULE_DETAILS_CREATE_NEW,
+            sql_queries::MIGRATE_RECURRING_RULE_DETAILS_COPY_DATA,
+            sql_queries::MIGRATE_RECURRING_RULE_DETAILS_DROP_OLD,
+            sql_queries::MIGRATE_RECURRING_RULE_DETAILS_RENAME_NEW,
+            sql_queries::CREATE_RECURRING_RULE_DETAILS_INDEX,
+        ])
+        .await
+    }
+
+    /// Add IS_ESTIMATED to RECURRING_RULES if absent. Runs after the
+    /// open-ended rebuild, whose table definition predates the column.
+    async fn ensure_recurring_rules_estimated_column(&self) -> Result<(), sqlx::Error> {
+        let has_column: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('RECURRING_RULES') WHERE name = 'IS_ESTIMATED'"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if has_column == 0 {
+            sqlx::query(sql_queries::MIGRATE_RECURRING_RULES_ADD_IS_ESTIMATED)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Run a CREATE/COPY/DROP/RENAME table rebuild in one transaction with
+    /// FK enforcement off on the connection, so dropping the old table does
+    /// not cascade into the rows that reference it. Enforcement is switched
+    /// back on even when the rebuild fails.
+    async fn rebuild_with_foreign_keys_off(&self, statements: &[&str]) -> Result<(), sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await?;
+
+        let migration = async {
+            let mut tx = conn.begin().await?;
+            for sql in statements {
+                sqlx::query(sql).execute(&mut *tx).await?;
+            }
+            tx.commit().await
+        }
+        .await;
+
+        let restore = sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await;
+
+        migration?;
+        restore?;
+        Ok(())
+    }
+
     /// Add HOLIDAY_LOCALE, WEEK_START_DAY to USERS if absent.
     async fn ensure_users_recurring_columns(&self) -> Result<(), sqlx::Error> {
         for (name, ddl) in [
@@ -594,6 +709,266 @@ impl Database {
         Ok(())
     }
 
+    /// Create IMPORT_PROFILES, the per-account column mapping used by the
+    /// CSV statement import in `TransactionService`. New table only, so the
+    /// IF NOT EXISTS DDL is the whole migration.
+    pub async fn migrate_csv_import(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(sql_queries::CREATE_IMPORT_PROFILES_TABLE)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Create BUDGETS and its expression unique index (see
+    /// `CREATE_BUDGETS_KEY_INDEX` for why the key is not a table constraint).
+    pub async fn migrate_budgets(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(sql_queries::CREATE_BUDGETS_TABLE)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(sql_queries::CREATE_BUDGETS_KEY_INDEX)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Create the budget rollover rule table and the carry history that
+    /// freezes each closed period's carry-forward.
+    pub async fn migrate_budget_rollover(&self) -> Result<(), sqlx::Error> {
+        for sql in [
+            sql_queries::CREATE_BUDGET_ROLLOVER_RULES_TABLE,
+            sql_queries::CREATE_BUDGET_ROLLOVER_RULES_KEY_INDEX,
+            sql_queries::CREATE_BUDGET_CARRY_HISTORY_TABLE,
+            sql_queries::CREATE_BUDGET_CARRY_HISTORY_KEY_INDEX,
+        ] {
+            sqlx::query(sql).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Add TRANSACTIONS_HEADER.RECONCILE_STATUS (0 uncleared, 1 cleared,
+    /// 2 reconciled) and the statement checkpoint table. Existing rows
+    /// start uncleared.
+    pub async fn migrate_reconciliation(&self) -> Result<(), sqlx::Error> {
+        let has_column: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('TRANSACTIONS_HEADER') WHERE name = 'RECONCILE_STATUS'"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if has_column == 0 {
+            sqlx::query(
+                "ALTER TABLE TRANSACTIONS_HEADER ADD COLUMN RECONCILE_STATUS INTEGER NOT NULL DEFAULT 0"
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        sqlx::query(sql_queries::CREATE_RECONCILIATION_CHECKPOINTS_TABLE)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Add ACCOUNT_TEMPLATES.ACCOUNT_CLASS (0 asset, 1 liability) on
+    /// databases created before it was in dbaccess.sql, add a LOAN template
+    /// and mark CREDIT/LOAN as liabilities.
+    pub async fn migrate_account_class(&self) -> Result<(), sqlx::Error> {
+        let has_column: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('ACCOUNT_TEMPLATES') WHERE name = 'ACCOUNT_CLASS'"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if has_column == 0 {
+            sqlx::query(
+                "ALTER TABLE ACCOUNT_TEMPLATES ADD COLUMN ACCOUNT_CLASS INTEGER NOT NULL DEFAULT 0"
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        for sql in [
+            sql_queries::ACCOUNT_TEMPLATE_INSERT_LOAN,
+            sql_queries::ACCOUNT_TEMPLATE_MARK_LIABILITIES,
+        ] {
+            sqlx::query(sql).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Create the credit-card billing cycle and issued statement tables.
+    pub async fn migrate_card_billing(&self) -> Result<(), sqlx::Error> {
+        for sql in [
+            sql_queries::CREATE_CARD_BILLING_SETTINGS_TABLE,
+            sql_queries::CREATE_CARD_STATEMENTS_TABLE,
+        ] {
+            sqlx::query(sql).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Add currency codes to USERS (base currency), ACCOUNTS and
+    /// TRANSACTIONS_HEADER, all defaulting to JPY so existing yen amounts
+    /// keep their meaning, and create EXCHANGE_RATES.
+    pub async fn migrate_currency(&self) -> Result<(), sqlx::Error> {
+        for (table, column, ddl) in [
+            ("USERS", "BASE_CURRENCY_CODE",
+             "ALTER TABLE USERS ADD COLUMN BASE_CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY'"),
+            ("ACCOUNTS", "CURRENCY_CODE",
+             "ALTER TABLE ACCOUNTS ADD COLUMN CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY'"),
+            ("TRANSACTIONS_HEADER", "CURRENCY_CODE",
+             "ALTER TABLE TRANSACTIONS_HEADER ADD COLUMN CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY'"),
+        ] {
+            let has_column: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?"
+            )
+            .bind(table)
+            .bind(column)
+            .fetch_one(&self.pool)
+            .await?;
+
+            if has_column == 0 {
+                sqlx::query(ddl).execute(&self.pool).await?;
+            }
+        }
+
+        sqlx::query(sql_queries::CREATE_EXCHANGE_RATES_TABLE)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Give detail lines their own optional FROM/TO accounts so one header
+    /// can be split across payment methods and categories, and create the
+    /// TRANSACTION_MOVEMENTS view that balance queries read legs from.
+    pub async fn migrate_split_transactions(&self) -> Result<(), sqlx::Error> {
+        for column in ["FROM_ACCOUNT_CODE", "TO_ACCOUNT_CODE"] {
+            let has_column: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM pragma_table_info('TRANSACTIONS_DETAIL') WHERE name = ?"
+            )
+            .bind(column)
+            .fetch_one(&self.pool)
+            .await?;
+
+            if has_column == 0 {
+                sqlx::query(&format!(
+                    "ALTER TABLE TRANSACTIONS_DETAIL ADD COLUMN {} VARCHAR(50)",
+                    column
+                ))
+                .execute(&self.pool)
+                .await?;
+            }
+        }
+
+        sqlx::query(sql_queries::CREATE_TRANSACTION_MOVEMENTS_VIEW)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Create the ATTACHMENTS table for receipts and documents linked to
+    /// transaction headers and detail lines.
+    pub async fn migrate_attachments(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(sql_queries::CREATE_ATTACHMENTS_TABLE)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(sql_queries::CREATE_ATTACHMENTS_INDEX)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Create TAGS and the TRANSACTION_TAGS link table for free-form tags
+    /// on headers and detail lines.
+    pub async fn migrate_tags(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(sql_queries::CREATE_TAGS_TABLE)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(sql_queries::CREATE_TRANSACTION_TAGS_TABLE)
+            .execute(&self.pool)
+            .await?;
+        for ddl in sql_queries::CREATE_TRANSACTION_TAGS_INDEXES {
+            sqlx::query(ddl).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Create the SAVED_FILTERS table for named transaction-list filters.
+    pub async fn migrate_saved_filters(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(sql_queries::CREATE_SAVED_FILTERS_TABLE)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Create the DUPLICATE_DISMISSALS table used by the duplicate report.
+    pub async fn migrate_duplicate_dismissals(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(sql_queries::CREATE_DUPLICATE_DISMISSALS_TABLE)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Create the AUDIT_LOG table written alongside master and transaction
+    /// changes.
+    pub async fn migrate_audit_log(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(sql_queries::CREATE_AUDIT_LOG_TABLE)
+            .execute(&self.pool)
+            .await?;
+        for ddl in sql_queries::CREATE_AUDIT_LOG_INDEXES {
+            sqlx::query(ddl).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Create the TRANSACTION_SEARCH full-text index and the triggers that
+    /// keep it current. Existing transactions are indexed the first time
+    /// the table is created. Must run after every migration that rebuilds
+    /// a table the triggers sit on.
+    pub async fn migrate_transaction_search(&self) -> Result<(), sqlx::Error> {
+        let existed: i64 = sqlx::query_scalar(sql_queries::TRANSACTION_SEARCH_TABLE_EXISTS)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(sql_queries::CREATE_TRANSACTION_SEARCH_SOURCE_VIEW)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(sql_queries::CREATE_TRANSACTION_SEARCH_TABLE)
+            .execute(&mut *tx)
+            .await?;
+        for ddl in sql_queries::CREATE_TRANSACTION_SEARCH_TRIGGERS {
+            sqlx::query(ddl).execute(&mut *tx).await?;
+        }
+        if existed == 0 {
+            for sql in sql_queries::TRANSACTION_SEARCH_REBUILD {
+                sqlx::query(sql).execute(&mut *tx).await?;
+            }
+        }
+        tx.commit().await
+    }
+
+    /// Create the undo journal tables, drop whatever the previous run left in
+    /// them, and regenerate the TRG_JOURNAL_* triggers from the current
+    /// columns of the journaled tables.
+    pub async fn migrate_change_journal(&self) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for ddl in [
+            sql_queries::CREATE_CHANGE_OPERATIONS_TABLE,
+            sql_queries::CREATE_CHANGE_JOURNAL_TABLE,
+            sql_queries::CREATE_CHANGE_JOURNAL_INDEX,
+            sql_queries::CREATE_CHANGE_JOURNAL_CONTROL_TABLE,
+            sql_queries::CHANGE_JOURNAL_CONTROL_INIT,
+        ] {
+            sqlx::query(ddl).execute(&mut *tx).await?;
+        }
+        for sql in sql_queries::CHANGE_JOURNAL_RESET {
+            sqlx::query(sql).execute(&mut *tx).await?;
+        }
+        crate::services::journal::install_triggers(&mut tx).await?;
+        tx.commit().await
+    }
+
     /// Create new tables for v2.1.0 (idempotent via IF NOT EXISTS).
     async fn create_recurring_tables(&self) -> Result<(), sqlx::Error> {
         sqlx::query(sql_queries::CREATE_RECURRING_RULES_TABLE)
@@ -602,6 +977,9 @@ impl Database {
         sqlx::query(sql_queries::CREATE_RECURRING_RULE_DETAILS_TABLE)
             .execute(&self.pool)
             .await?;
+        sqlx::query(sql_queries::CREATE_RECURRING_RULE_EXCEPTIONS_TABLE)
+            .execute(&self.pool)
+            .await?;
         sqlx::query(sql_queries::CREATE_HOLIDAYS_STANDARD_TABLE)
             .execute(&self.pool)
             .await?;
@@ -1040,6 +1418,7 @@ mod tests {
         for table in [
             "RECURRING_RULES",
             "RECURRING_RULE_DETAILS",
+            "RECURRING_RULE_EXCEPTIONS",
             "HOLIDAYS_STANDARD",
             "HOLIDAYS_USER_CUSTOM",
         ] {
@@ -1071,6 +1450,115 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_migrate_recurring_makes_end_date_optional_without_losing_links() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        // Swap in the RECURRING_RULES table earlier releases created
+        sqlx::query("DROP TABLE RECURRING_RULES").execute(db.pool()).await.unwrap();
+        sqlx::query(sql_queries::TEST_DB_CREATE_LEGACY_RECURRING_RULES_TABLE)
+            .execute(db.pool())
+            .await
+            .expect("legacy RECURRING_RULES");
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'rr', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO RECURRING_RULES (RULE_ID, USER_ID, PERIOD_UNIT, PERIOD_INTERVAL, START_DATE, END_DATE, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TOTAL_AMOUNT) VALUES (5, 2, 'MONTH', 1, '2026-01-01', '2026-12-31', 'EXPENSE', 'NONE', 'NONE', 1000)",
+            "INSERT INTO RECURRING_RULE_DETAILS (RULE_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (5, 2, 'EXPENSE', 'Rent', 1000)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, RULE_ID) VALUES (1, 2, 'EXPENSE', 'NONE', 'NONE', '2026-01-25 00:00:00', 1000, 5)",
+        ] {
+            sqlx::query(sql).execute(db.pool()).await.unwrap();
+        }
+
+        db.migrate_recurring().await.expect("first run");
+        db.migrate_recurring().await.expect("second run");
+
+        let end_date_not_null: i64 = sqlx::query_scalar(
+            "SELECT \"notnull\" FROM pragma_table_info('RECURRING_RULES') WHERE name = 'END_DATE'",
+        )
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(end_date_not_null, 0);
+
+        let materialized: String =
+            sqlx::query_scalar("SELECT MATERIALIZED_UNTIL FROM RECURRING_RULES WHERE RULE_ID = 5")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert_eq!(materialized, "2026-12-31");
+
+        // Neither the DETAIL template nor the occurrence's RULE_ID went with
+        // the dropped table
+        let links: (i64, Option<i64>) = sqlx::query_as(
+            "SELECT (SELECT COUNT(*) FROM RECURRING_RULE_DETAILS WHERE RULE_ID = 5),
+                    (SELECT RULE_ID FROM TRANSACTIONS_HEADER WHERE TRANSACTION_ID = 1)",
+        )
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(links, (1, Some(5)));
+        assert_eq!(column_count(&db, "RECURRING_RULES", "IS_ESTIMATED").await, 1);
+
+        let has_index: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_recurring_rules_user'",
+        )
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(has_index, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_recurring_allows_multiple_detail_templates() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        // Swap in the single-template RECURRING_RULE_DETAILS of earlier releases
+        sqlx::query("DROP TABLE RECURRING_RULE_DETAILS").execute(db.pool()).await.unwrap();
+        sqlx::query(sql_queries::TEST_DB_CREATE_LEGACY_RECURRING_RULE_DETAILS_TABLE)
+            .execute(db.pool())
+            .await
+            .expect("legacy RECURRING_RULE_DETAILS");
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'rr', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO RECURRING_RULES (RULE_ID, USER_ID, PERIOD_UNIT, PERIOD_INTERVAL, START_DATE, END_DATE, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TOTAL_AMOUNT) VALUES (5, 2, 'MONTH', 1, '2026-01-01', '2026-12-31', 'EXPENSE', 'NONE', 'NONE', 1000)",
+            "INSERT INTO RECURRING_RULE_DETAILS (RULE_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (5, 2, 'EXPENSE', 'Rent', 1000)",
+        ] {
+            sqlx::query(sql).execute(db.pool()).await.unwrap();
+        }
+
+        db.migrate_recurring().await.expect("first run");
+        db.migrate_recurring().await.expect("second run");
+
+        assert_eq!(column_count(&db, "RECURRING_RULE_DETAILS", "PRODUCT_ID").await, 1);
+
+        // The existing template survived and a second line is now accepted
+        sqlx::query(
+            "INSERT INTO RECURRING_RULE_DETAILS (RULE_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (5, 2, 'EXPENSE', 'Fee', 100)",
+        )
+        .execute(db.pool())
+        .await
+        .expect("second DETAIL template");
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT ITEM_NAME FROM RECURRING_RULE_DETAILS WHERE RULE_ID = 5 ORDER BY RULE_DETAIL_ID",
+        )
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(names, vec!["Rent".to_string(), "Fee".to_string()]);
+
+        let has_index: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_recurring_rule_details_rule'",
+        )
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(has_index, 1);
+    }
+
     #[tokio::test]
     async fn test_migrate_period_customization_adds_columns_idempotently() {
         let db = memory_db().await;
@@ -1108,6 +1596,272 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_migrate_csv_import_creates_profiles_table_idempotently() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+
+        db.migrate_csv_import().await.expect("first run");
+        db.migrate_csv_import().await.expect("second run");
+
+        assert_eq!(table_count(&db, "IMPORT_PROFILES").await, 1);
+        assert_eq!(
+            column_count(&db, "IMPORT_PROFILES", "COUNTER_ACCOUNT_CODE").await,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_budgets_creates_table_and_key_index() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+
+        db.migrate_budgets().await.expect("first run");
+        db.migrate_budgets().await.expect("second run");
+
+        assert_eq!(table_count(&db, "BUDGETS").await, 1);
+        assert_eq!(column_count(&db, "BUDGETS", "CATEGORY3_CODE").await, 1);
+
+        sqlx::query("INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'u', 'x', 1, datetime('now'))")
+            .execute(db.pool())
+            .await
+            .expect("user");
+        // NULL sub-category codes still collide through the IFNULL key.
+        let insert = "INSERT INTO BUDGETS (USER_ID, PERIOD_YEAR, PERIOD_MONTH, CATEGORY1_CODE, AMOUNT) \
+                      VALUES (2, 2024, 4, 'EXPENSE', 1000)";
+        sqlx::query(insert).execute(db.pool()).await.expect("first budget");
+        assert!(sqlx::query(insert).execute(db.pool()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_budget_rollover_creates_tables_idempotently() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+
+        db.migrate_budget_rollover().await.expect("first run");
+        db.migrate_budget_rollover().await.expect("second run");
+
+        assert_eq!(table_count(&db, "BUDGET_ROLLOVER_RULES").await, 1);
+        assert_eq!(table_count(&db, "BUDGET_CARRY_HISTORY").await, 1);
+        assert_eq!(
+            column_count(&db, "BUDGET_CARRY_HISTORY", "CARRIED_OUT").await,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_reconciliation_adds_status_column_and_checkpoints() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+
+        db.migrate_reconciliation().await.expect("first run");
+        db.migrate_reconciliation().await.expect("second run");
+
+        assert_eq!(
+            column_count(&db, "TRANSACTIONS_HEADER", "RECONCILE_STATUS").await,
+            1
+        );
+        assert_eq!(table_count(&db, "RECONCILIATION_CHECKPOINTS").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_account_class_marks_liability_templates() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+
+        db.migrate_account_class().await.expect("first run");
+        db.migrate_account_class().await.expect("second run");
+
+        let classes: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT TEMPLATE_CODE, ACCOUNT_CLASS FROM ACCOUNT_TEMPLATES \
+             WHERE TEMPLATE_CODE IN ('BANK', 'CREDIT', 'LOAN') ORDER BY TEMPLATE_CODE"
+        )
+        .fetch_all(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(
+            classes,
+            vec![
+                ("BANK".to_string(), 0),
+                ("CREDIT".to_string(), 1),
+                ("LOAN".to_string(), 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_card_billing_creates_tables() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+
+        db.migrate_card_billing().await.expect("first run");
+        db.migrate_card_billing().await.expect("second run");
+
+        assert_eq!(table_count(&db, "CARD_BILLING_SETTINGS").await, 1);
+        assert_eq!(table_count(&db, "CARD_STATEMENTS").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_currency_adds_columns_and_rates_table() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+
+        db.migrate_currency().await.expect("first run");
+        db.migrate_currency().await.expect("second run");
+
+        assert_eq!(column_count(&db, "USERS", "BASE_CURRENCY_CODE").await, 1);
+        assert_eq!(column_count(&db, "ACCOUNTS", "CURRENCY_CODE").await, 1);
+        assert_eq!(column_count(&db, "TRANSACTIONS_HEADER", "CURRENCY_CODE").await, 1);
+        assert_eq!(table_count(&db, "EXCHANGE_RATES").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_split_transactions_adds_line_accounts_and_view() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+        db.migrate_currency().await.expect("migrate_currency");
+
+        db.migrate_split_transactions().await.expect("first run");
+        db.migrate_split_transactions().await.expect("second run");
+
+        assert_eq!(column_count(&db, "TRANSACTIONS_DETAIL", "FROM_ACCOUNT_CODE").await, 1);
+        assert_eq!(column_count(&db, "TRANSACTIONS_DETAIL", "TO_ACCOUNT_CODE").await, 1);
+        let views: i64 = sqlx::query_scalar(sql_queries::TEST_DB_COUNT_VIEW)
+            .bind("TRANSACTION_MOVEMENTS")
+            .fetch_one(db.pool())
+            .await
+            .expect("Failed to query sqlite_master");
+        assert_eq!(views, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_attachments_is_idempotent() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+
+        db.migrate_attachments().await.expect("first run");
+        db.migrate_attachments().await.expect("second run");
+
+        assert_eq!(table_count(&db, "ATTACHMENTS").await, 1);
+        assert_eq!(column_count(&db, "ATTACHMENTS", "IS_ENCRYPTED").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_tags_is_idempotent() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+
+        db.migrate_tags().await.expect("first run");
+        db.migrate_tags().await.expect("second run");
+
+        assert_eq!(table_count(&db, "TAGS").await, 1);
+        assert_eq!(table_count(&db, "TRANSACTION_TAGS").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_duplicate_dismissals_is_idempotent() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+        db.migrate_duplicate_dismissals().await.expect("first run");
+        db.migrate_duplicate_dismissals().await.expect("second run");
+        assert_eq!(table_count(&db, "DUPLICATE_DISMISSALS").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_audit_log_is_idempotent() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_audit_log().await.expect("first run");
+        db.migrate_audit_log().await.expect("second run");
+        assert_eq!(table_count(&db, "AUDIT_LOG").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_transaction_search_backfills_once() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'fts', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO MEMOS (MEMO_ID, USER_ID, MEMO_TEXT) VALUES (1, 2, '北海道旅行のお土産')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, MEMO_ID) VALUES (1, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-01 10:00:00', 1000, 1)",
+        ] {
+            sqlx::query(sql).execute(db.pool()).await.unwrap();
+        }
+
+        db.migrate_transaction_search().await.expect("first run");
+        db.migrate_transaction_search().await.expect("second run");
+
+        let rows: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM TRANSACTION_SEARCH WHERE TRANSACTION_SEARCH MATCH '\"北海道\"'",
+        )
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+        assert_eq!(rows, 1, "pre-existing transaction must be indexed exactly once");
+    }
+
+    #[tokio::test]
+    async fn test_migrate_saved_filters_is_idempotent() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+
+        db.migrate_saved_filters().await.expect("first run");
+        db.migrate_saved_filters().await.expect("second run");
+
+        assert_eq!(table_count(&db, "SAVED_FILTERS").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_change_journal_resets_and_regenerates_triggers() {
+        let db = memory_db().await;
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+
+        db.migrate_change_journal().await.expect("first run");
+        let triggers = "SELECT COUNT(*) FROM sqlite_master WHERE type = 'trigger' AND name LIKE 'TRG_JOURNAL_%'";
+        let first: i64 = sqlx::query_scalar(triggers).fetch_one(db.pool()).await.unwrap();
+        assert!(first > 0);
+        sqlx::query("INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'undo', 'x', 1, datetime('now'))")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO CHANGE_OPERATIONS (USER_ID, LABEL) VALUES (2, 'shop.add')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query("UPDATE CHANGE_JOURNAL_CONTROL SET ACTIVE_OPERATION_ID = 1")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        // The next startup drops the previous run's journal and any
+        // operation left active by a crash
+        db.migrate_change_journal().await.expect("second run");
+        let second: i64 = sqlx::query_scalar(triggers).fetch_one(db.pool()).await.unwrap();
+        assert_eq!(second, first);
+        assert_eq!(table_count(&db, "CHANGE_OPERATIONS").await, 1);
+        let operations: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM CHANGE_OPERATIONS")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(operations, 0);
+        let active: Option<i64> = sqlx::query_scalar(sql_queries::CHANGE_JOURNAL_GET_ACTIVE)
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(active, None);
+    }
+
     #[test]
     fn test_get_db_path_points_at_app_directory() {
         let path = get_db_path();
@@ -23,6 +23,20 @@ mod services {
     pub mod period;
     pub mod holiday;
     pub mod master_data;
+    pub mod export;
+    pub mod budget;
+    pub mod rollover;
+    pub mod reconciliation;
+    pub mod balance_history;
+    pub mod card_billing;
+    pub mod currency;
+    pub mod attachment;
+    pub mod tag;
+    pub mod saved_filter;
+    pub mod search;
+    pub mod duplicate;
+    pub mod journal;
+    pub mod audit;
 }
 
 #[cfg(test)]
@@ -61,6 +75,11 @@ pub struct AppState {
     pub transaction: Arc<Mutex<TransactionService>>,
     pub recurring: Arc<Mutex<RecurringService>>,
     pub session: Arc<SessionState>,
+    /// Held while a journaled command or an undo / redo runs, and by every
+    /// other command that writes a table in `CHANGE_JOURNAL_TABLES`, so no
+    /// row can be recorded into another command's operation. Take it after
+    /// any service lock; see `journaled`.
+    pub journal: Arc<Mutex<()>>,
 }
 
 /// Helper function to get user_id from session
@@ -82,6 +101,26 @@ fn get_session_user(
         .ok_or_else(|| "User not authenticated. Please login first.".to_string())
 }
 
+/// Run a mutating command as one undoable operation in the change journal.
+/// The journal lock is held throughout so rows written by another command
+/// cannot end up in this operation.
+async fn journaled<T, F>(
+    state: &tauri::State<'_, AppState>,
+    user_id: i64,
+    label: &str,
+    command: F,
+) -> Result<T, api_error::ApiError>
+where
+    F: std::future::Future<Output = Result<T, api_error::ApiError>>,
+{
+    let journal = state.journal.clone().lock_owned().await;
+    let operation =
+        services::journal::ActiveOperation::begin(state.db.pool(), user_id, label, journal).await?;
+    let result = command.await;
+    operation.finish().await?;
+    result
+}
+
 /// Helper function requiring an authenticated administrator session
 fn require_admin_session(state: &tauri::State<'_, AppState>) -> Result<(), String> {
     let user = get_session_user(state)?;
@@ -116,6 +155,15 @@ async fn login_user(
                 name: user.name.clone(),
                 role: user.role,
             };
+            let recurring = state.recurring.lock().await;
+            let _journal = state.journal.lock().await;
+            // Undo history does not carry over from a previous login
+            services::journal::clear_journal(state.db.pool(), user.user_id).await?;
+            // Keep open-ended recurring rules materialized a full window
+            // ahead. Best effort: a failure here must not lock the user out.
+            if let Err(e) = recurring.extend_open_ended_rules(user.user_id).await {
+                log::error!("Failed to extend recurring rules at login: {}", e);
+            }
             state.session.set_user(session_user.clone());
             Ok(session_user)
         }
@@ -140,6 +188,7 @@ async fn register_admin(
         ));
     }
 
+    let _journal = state.journal.lock().await;
     auth.register_admin_user(&username, &password).await?;
     Ok("Admin user registered successfully".to_string())
 }
@@ -165,6 +214,7 @@ async fn register_user(
         ));
     }
 
+    let _journal = state.journal.lock().await;
     auth.register_user(&username, &password).await?;
     Ok("User registered successfully".to_string())
 }
@@ -357,6 +407,7 @@ async fn create_general_user(
 
     let user_mgmt = state.user_mgmt.lock().await;
     let category = state.category.lock().await;
+    let _journal = state.journal.lock().await;
 
     let user_id = user_mgmt.register_general_user(&username, &password).await?;
     // Populate default categories for the new user
@@ -400,6 +451,7 @@ async fn update_general_user_with_reencryption(
     }
 
     let user_mgmt = state.user_mgmt.lock().await;
+    let _journal = state.journal.lock().await;
     user_mgmt.update_general_user_with_password(
         user_id,
         &old_password,
@@ -442,6 +494,7 @@ async fn update_admin_user_with_reencryption(
     }
 
     let user_mgmt = state.user_mgmt.lock().await;
+    let _journal = state.journal.lock().await;
     user_mgmt.update_admin_user_with_password(
         user_id,
         &old_password,
@@ -458,6 +511,7 @@ async fn delete_general_user_info(
 ) -> Result<(), api_error::ApiError> {
     require_admin_session(&state).map_err(api_error::ApiError::validation)?;
     let user_mgmt = state.user_mgmt.lock().await;
+    let _journal = state.journal.lock().await;
     user_mgmt.delete_general_user(user_id).await?;
     Ok(())
 }
@@ -1230,7 +1284,9 @@ async fn enable_category2(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.enable_category2(user_id, &category1_code, &category2_code).await?)
+    journaled(&state, user_id, "category.enable", async {
+        Ok(category.enable_category2(user_id, &category1_code, &category2_code).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1242,7 +1298,9 @@ async fn enable_category3(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.enable_category3(user_id, &category1_code, &category2_code, &category3_code).await?)
+    journaled(&state, user_id, "category.enable", async {
+        Ok(category.enable_category3(user_id, &category1_code, &category2_code, &category3_code).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1254,7 +1312,9 @@ async fn add_category2(
 ) -> Result<String, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.add_category2(user_id, &category1_code, &name_ja, &name_en).await?)
+    journaled(&state, user_id, "category.add", async {
+        Ok(category.add_category2(user_id, &category1_code, &name_ja, &name_en).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1267,7 +1327,9 @@ async fn add_category3(
 ) -> Result<String, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.add_category3(user_id, &category1_code, &category2_code, &name_ja, &name_en).await?)
+    journaled(&state, user_id, "category.add", async {
+        Ok(category.add_category3(user_id, &category1_code, &category2_code, &name_ja, &name_en).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1303,7 +1365,9 @@ async fn update_category2(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.update_category2_i18n(user_id, &category1_code, &category2_code, &name_ja, &name_en).await?)
+    journaled(&state, user_id, "category.update", async {
+        Ok(category.update_category2_i18n(user_id, &category1_code, &category2_code, &name_ja, &name_en).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1317,7 +1381,9 @@ async fn update_category3(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.update_category3_i18n(user_id, &category1_code, &category2_code, &category3_code, &name_ja, &name_en).await?)
+    journaled(&state, user_id, "category.update", async {
+        Ok(category.update_category3_i18n(user_id, &category1_code, &category2_code, &category3_code, &name_ja, &name_en).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1328,7 +1394,9 @@ async fn move_category2_up(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.move_category2_up(user_id, &category1_code, &category2_code).await?)
+    journaled(&state, user_id, "category.move", async {
+        Ok(category.move_category2_up(user_id, &category1_code, &category2_code).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1339,7 +1407,9 @@ async fn move_category2_down(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.move_category2_down(user_id, &category1_code, &category2_code).await?)
+    journaled(&state, user_id, "category.move", async {
+        Ok(category.move_category2_down(user_id, &category1_code, &category2_code).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1351,7 +1421,9 @@ async fn move_category3_up(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.move_category3_up(user_id, &category1_code, &category2_code, &category3_code).await?)
+    journaled(&state, user_id, "category.move", async {
+        Ok(category.move_category3_up(user_id, &category1_code, &category2_code, &category3_code).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1363,7 +1435,9 @@ async fn move_category3_down(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.move_category3_down(user_id, &category1_code, &category2_code, &category3_code).await?)
+    journaled(&state, user_id, "category.move", async {
+        Ok(category.move_category3_down(user_id, &category1_code, &category2_code, &category3_code).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1374,7 +1448,9 @@ async fn disable_category2(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.disable_category2(user_id, &category1_code, &category2_code).await?)
+    journaled(&state, user_id, "category.disable", async {
+        Ok(category.disable_category2(user_id, &category1_code, &category2_code).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1386,45 +1462,29 @@ async fn disable_category3(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let category = state.category.lock().await;
-    Ok(category.disable_category3(user_id, &category1_code, &category2_code, &category3_code).await?)
+    journaled(&state, user_id, "category.disable", async {
+        Ok(category.disable_category3(user_id, &category1_code, &category2_code, &category3_code).await?)
+    }).await
 }
 
 // ============================================================================
 // Transaction Management Commands
 // ============================================================================
 
+/// `query` uses the snake_case field names of `TransactionQuery`; omitted
+/// fields (or a missing `query`) mean "no condition".
 #[tauri::command]
 async fn get_transactions(
-    start_date: Option<String>,
-    end_date: Option<String>,
-    category1_code: Option<String>,
-    category2_code: Option<String>,
-    category3_code: Option<String>,
-    min_amount: Option<i64>,
-    max_amount: Option<i64>,
-    keyword: Option<String>,
-    include_scheduled: Option<bool>,
+    query: Option<services::transaction::TransactionQuery>,
     page: i64,
     per_page: i64,
     state: tauri::State<'_, AppState>
 ) -> Result<services::transaction::TransactionListResponse, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let transaction = state.transaction.lock().await;
-    Ok(transaction.get_transactions(
-        user_id,
-        start_date.as_deref(),
-        end_date.as_deref(),
-        category1_code.as_deref(),
-        category2_code.as_deref(),
-        category3_code.as_deref(),
-        min_amount,
-        max_amount,
-        keyword.as_deref(),
-        include_scheduled.unwrap_or(false),
-        page,
-        per_page,
-    )
-    .await?)
+    Ok(transaction
+        .get_transactions(user_id, &query.unwrap_or_default(), page, per_page)
+        .await?)
 }
 
 #[tauri::command]
@@ -1434,7 +1494,9 @@ async fn delete_transaction(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let transaction = state.transaction.lock().await;
-    Ok(transaction.delete_transaction(user_id, transaction_id).await?)
+    journaled(&state, user_id, "transaction.delete", async {
+        Ok(transaction.delete_transaction(user_id, transaction_id).await?)
+    }).await
 }
 
 // ============================================================================
@@ -1458,12 +1520,94 @@ async fn get_account_templates(
 async fn get_account_balances_as_of(
     as_of_date: String,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<services::account::AccountBalance>, api_error::ApiError> {
+) -> Result<services::account::AccountBalancesAsOf, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let db = &state.db;
     services::account::get_account_balances_as_of(db.pool(), user_id, &as_of_date).await
 }
 
+/// End-of-period balances of every account plus net worth between two
+/// dates (`YYYY-MM-DD`), for the net-worth chart. Week/month/year edges
+/// follow the user's period settings; `week_start` defaults to Monday.
+#[tauri::command]
+async fn get_balance_history(
+    start_date: String,
+    end_date: String,
+    interval: services::balance_history::BalanceInterval,
+    week_start: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<services::balance_history::BalanceHistory, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+
+    let start = chrono::NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| api_error::ApiError::validation(format!("Invalid start date format: {}", e)))?;
+    let end = chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+        .map_err(|e| api_error::ApiError::validation(format!("Invalid end date format: {}", e)))?;
+    let week_start = match week_start.as_deref().unwrap_or("monday") {
+        "sunday" => services::aggregation::WeekStart::Sunday,
+        "monday" => services::aggregation::WeekStart::Monday,
+        other => {
+            return Err(api_error::ApiError::validation(format!(
+                "Invalid week_start value: {}",
+                other
+            )))
+        }
+    };
+
+    let (month_start_day, year_start_month, year_start_day, month_shift) =
+        fetch_period_settings(db.pool(), user_id)
+            .await
+            .map_err(api_error::ApiError::database)?;
+    // Shifted month edges can move a few days either side of the range.
+    let holidays = if interval == services::balance_history::BalanceInterval::Month
+        && !matches!(month_shift, services::holiday::HolidayShift::None)
+    {
+        services::holiday::fetch_holidays(
+            db.pool(),
+            user_id,
+            start - chrono::Duration::days(45),
+            end + chrono::Duration::days(45),
+        )
+        .await?
+    } else {
+        std::collections::HashSet::new()
+    };
+    let calendar = services::balance_history::PeriodCalendar {
+        week_start,
+        month_start_day,
+        month_shift,
+        holidays,
+        year_start_month,
+        year_start_day,
+    };
+
+    services::balance_history::get_balance_history(db.pool(), user_id, start, end, interval, &calendar)
+        .await
+}
+
+/// Per-account ledger: every actualised transaction touching the account
+/// with its signed amount, counterpart and running balance. Dates are
+/// optional inclusive `YYYY-MM-DD` bounds.
+#[tauri::command]
+async fn get_account_ledger(
+    account_code: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<services::account::AccountLedger, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::account::get_account_ledger(
+        db.pool(),
+        user_id,
+        &account_code,
+        start_date.as_deref(),
+        end_date.as_deref(),
+    )
+    .await
+}
+
 #[tauri::command]
 async fn get_accounts(
     state: tauri::State<'_, AppState>
@@ -1502,7 +1646,7 @@ async fn add_account(
         initial_balance,
     };
 
-    services::account::add_account(db.pool(), user_id, request).await
+    journaled(&state, user_id, "account.add", services::account::add_account(db.pool(), user_id, request)).await
 }
 
 #[tauri::command]
@@ -1525,7 +1669,7 @@ async fn update_account(
         display_order,
     };
 
-    services::account::update_account(db.pool(), user_id, request).await
+    journaled(&state, user_id, "account.update", services::account::update_account(db.pool(), user_id, request)).await
 }
 
 #[tauri::command]
@@ -1536,7 +1680,7 @@ async fn delete_account(
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let db = &state.db;
 
-    services::account::delete_account(db.pool(), user_id, &account_code).await
+    journaled(&state, user_id, "account.delete", services::account::delete_account(db.pool(), user_id, &account_code)).await
 }
 
 // ============================================================================
@@ -1566,7 +1710,7 @@ async fn add_shop(
         memo,
     };
 
-    services::shop::add_shop(db.pool(), user_id, request).await
+    journaled(&state, user_id, "shop.add", services::shop::add_shop(db.pool(), user_id, request)).await
 }
 
 #[tauri::command]
@@ -1586,7 +1730,7 @@ async fn update_shop(
         display_order,
     };
 
-    services::shop::update_shop(db.pool(), user_id, shop_id, request).await
+    journaled(&state, user_id, "shop.update", services::shop::update_shop(db.pool(), user_id, shop_id, request)).await
 }
 
 #[tauri::command]
@@ -1596,7 +1740,7 @@ async fn delete_shop(
 ) -> Result<String, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let db = &state.db;
-    services::shop::delete_shop(db.pool(), user_id, shop_id).await
+    journaled(&state, user_id, "shop.delete", services::shop::delete_shop(db.pool(), user_id, shop_id)).await
 }
 
 // ============================================================================
@@ -1629,7 +1773,7 @@ async fn add_manufacturer(
         is_disabled,
     };
 
-    services::manufacturer::add_manufacturer(db.pool(), user_id, request).await
+    journaled(&state, user_id, "manufacturer.add", services::manufacturer::add_manufacturer(db.pool(), user_id, request)).await
 }
 
 #[tauri::command]
@@ -1651,7 +1795,7 @@ async fn update_manufacturer(
         is_disabled,
     };
 
-    services::manufacturer::update_manufacturer(db.pool(), user_id, manufacturer_id, request).await
+    journaled(&state, user_id, "manufacturer.update", services::manufacturer::update_manufacturer(db.pool(), user_id, manufacturer_id, request)).await
 }
 
 #[tauri::command]
@@ -1661,7 +1805,7 @@ async fn delete_manufacturer(
 ) -> Result<String, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let db = &state.db;
-    services::manufacturer::delete_manufacturer(db.pool(), user_id, manufacturer_id).await
+    journaled(&state, user_id, "manufacturer.delete", services::manufacturer::delete_manufacturer(db.pool(), user_id, manufacturer_id)).await
 }
 
 // ============================================================================
@@ -1696,7 +1840,7 @@ async fn add_product(
         is_disabled,
     };
 
-    services::product::add_product(db.pool(), user_id, request).await
+    journaled(&state, user_id, "product.add", services::product::add_product(db.pool(), user_id, request)).await
 }
 
 #[tauri::command]
@@ -1720,7 +1864,7 @@ async fn update_product(
         is_disabled,
     };
 
-    services::product::update_product(db.pool(), user_id, product_id, request).await
+    journaled(&state, user_id, "product.update", services::product::update_product(db.pool(), user_id, product_id, request)).await
 }
 
 #[tauri::command]
@@ -1730,7 +1874,7 @@ async fn delete_product(
 ) -> Result<String, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let db = &state.db;
-    services::product::delete_product(db.pool(), user_id, product_id).await
+    journaled(&state, user_id, "product.delete", services::product::delete_product(db.pool(), user_id, product_id)).await
 }
 
 #[tauri::command]
@@ -1777,7 +1921,9 @@ async fn save_transaction_header(
         is_scheduled,
     };
 
-    Ok(transaction.save_transaction_header(user_id, request).await?)
+    journaled(&state, user_id, "transaction.add", async {
+        Ok(transaction.save_transaction_header(user_id, request).await?)
+    }).await
 }
 
 #[tauri::command]
@@ -1862,203 +2008,1017 @@ async fn update_transaction_header(
         is_scheduled,
     };
 
-    Ok(transaction.update_transaction_header(user_id, transaction_id, request).await?)
+    journaled(&state, user_id, "transaction.update", async {
+        Ok(transaction.update_transaction_header(user_id, transaction_id, request).await?)
+    }).await
 }
 
+/// `adjustment` carries the actual amount when the occurrence's amount was
+/// an estimate.
 #[tauri::command]
 async fn confirm_scheduled_transaction(
     transaction_id: i64,
+    adjustment: Option<services::transaction::ConfirmScheduledAdjustment>,
     state: tauri::State<'_, AppState>
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let transaction = state.transaction.lock().await;
-    Ok(transaction.confirm_scheduled_transaction(user_id, transaction_id).await?)
+    journaled(&state, user_id, "transaction.confirm", async {
+        Ok(transaction.confirm_scheduled_transaction(user_id, transaction_id, adjustment).await?)
+    }).await
+}
+
+/// Apply one change to many transactions; returns one result per ID so the
+/// screen can show which rows were refused and why.
+#[tauri::command]
+async fn bulk_update_transactions(
+    transaction_ids: Vec<i64>,
+    change: services::transaction::BulkTransactionChange,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::transaction::BulkTransactionResult>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let transaction = state.transaction.lock().await;
+    journaled(&state, user_id, "transaction.bulk_edit", async {
+        Ok(transaction.bulk_update_transactions(user_id, &transaction_ids, &change).await?)
+    }).await
 }
 
 // ============================================================================
-// Transaction Detail Management Commands
+// CSV Statement Import Commands
 // ============================================================================
 
 #[tauri::command]
-async fn get_transaction_header_with_info(
-    transaction_id: i64,
+async fn save_import_profile(
+    request: services::transaction::SaveImportProfileRequest,
     state: tauri::State<'_, AppState>
-) -> Result<services::transaction::TransactionHeaderWithInfo, api_error::ApiError> {
-    let transaction = state.transaction.lock().await;
+) -> Result<services::transaction::ImportProfile, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
-
-    Ok(transaction.get_transaction_header_with_info(user_id, transaction_id).await?)
+    let transaction = state.transaction.lock().await;
+    Ok(transaction.save_import_profile(user_id, request).await?)
 }
 
 #[tauri::command]
-async fn get_transaction_details(
-    transaction_id: i64,
+async fn get_import_profile(
+    account_code: String,
     state: tauri::State<'_, AppState>
-) -> Result<Vec<services::transaction::TransactionDetailWithInfo>, api_error::ApiError> {
-    let transaction = state.transaction.lock().await;
+) -> Result<services::transaction::ImportProfile, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
-
-    Ok(transaction.get_transaction_details(user_id, transaction_id).await?)
+    let transaction = state.transaction.lock().await;
+    Ok(transaction.get_import_profile(user_id, &account_code).await?)
 }
 
 #[tauri::command]
-async fn add_transaction_detail(
-    transaction_id: i64,
-    category1_code: String,
-    category2_code: Option<String>,
-    category3_code: Option<String>,
-    item_name: String,
-    amount: i64,
-    tax_rate: i32,
-    tax_amount: i64,
-    amount_including_tax: Option<i64>,
-    product_id: Option<i64>,
-    memo: Option<String>,
+async fn list_import_profiles(
     state: tauri::State<'_, AppState>
-) -> Result<i64, api_error::ApiError> {
-    let transaction = state.transaction.lock().await;
+) -> Result<Vec<services::transaction::ImportProfile>, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
-
-    let request = services::transaction::SaveTransactionDetailRequest {
-        detail_id: None,
-        category1_code,
-        category2_code,
-        category3_code,
-        item_name,
-        amount,
-        tax_rate,
-        tax_amount,
-        amount_including_tax,
-        product_id,
-        memo,
-    };
-
-    Ok(transaction.add_transaction_detail(user_id, transaction_id, request).await?)
+    let transaction = state.transaction.lock().await;
+    Ok(transaction.list_import_profiles(user_id).await?)
 }
 
 #[tauri::command]
-async fn update_transaction_detail(
-    detail_id: i64,
-    category1_code: String,
-    category2_code: Option<String>,
-    category3_code: Option<String>,
-    item_name: String,
-    amount: i64,
-    tax_rate: i32,
-    tax_amount: i64,
-    amount_including_tax: Option<i64>,
-    product_id: Option<i64>,
-    memo: Option<String>,
+async fn delete_import_profile(
+    account_code: String,
     state: tauri::State<'_, AppState>
 ) -> Result<(), api_error::ApiError> {
-    let transaction = state.transaction.lock().await;
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
-
-    let request = services::transaction::SaveTransactionDetailRequest {
-        detail_id: Some(detail_id),
-        category1_code,
-        category2_code,
-        category3_code,
-        item_name,
-        amount,
-        tax_rate,
-        tax_amount,
-        amount_including_tax,
-        product_id,
-        memo,
-    };
-
-    Ok(transaction.update_transaction_detail(user_id, detail_id, request).await?)
+    let transaction = state.transaction.lock().await;
+    Ok(transaction.delete_import_profile(user_id, &account_code).await?)
 }
 
+/// Parse a statement with the account's import profile and return the
+/// rows it would create, flagging ledger duplicates and unreadable lines.
+/// Writes nothing; the import screen shows this before `import_csv_statement`.
 #[tauri::command]
-async fn delete_transaction_detail(
-    detail_id: i64,
+async fn preview_csv_import(
+    account_code: String,
+    csv_text: String,
     state: tauri::State<'_, AppState>
-) -> Result<(), api_error::ApiError> {
+) -> Result<services::transaction::ImportPreview, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let transaction = state.transaction.lock().await;
+    Ok(transaction.preview_csv_import(user_id, &account_code, &csv_text).await?)
+}
+
+#[tauri::command]
+async fn import_csv_statement(
+    account_code: String,
+    csv_text: String,
+    include_duplicates: Option<bool>,
+    state: tauri::State<'_, AppState>
+) -> Result<services::transaction::ImportSummary, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let transaction = state.transaction.lock().await;
+    journaled(&state, user_id, "transaction.import", async {
+        Ok(transaction
+            .import_csv_statement(user_id, &account_code, &csv_text, include_duplicates.unwrap_or(false))
+            .await?)
+    }).await
+}
+
+// ============================================================================
+// Budget Commands
+// ============================================================================
 
-    Ok(transaction.delete_transaction_detail(user_id, detail_id).await?)
+#[tauri::command]
+async fn list_budgets(
+    period_year: i32,
+    period_month: u32,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::budget::Budget>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let lang = state.settings.lock().await
+        .get_string("language")
+        .unwrap_or_else(|_| LANG_DEFAULT.to_string());
+    let db = &state.db;
+    services::budget::list_budgets(db.pool(), user_id, period_year, period_month, &lang).await
 }
 
-/// Compute the TOTAL_AMOUNT a transaction header *should* have based on its
-/// current details and saved tax rounding setting. The frontend uses this
-/// after a detail edit (or a tax-setting change) to decide whether to prompt
-/// the user before overwriting the cached header total.
 #[tauri::command]
-async fn compute_recommended_transaction_total(
-    transaction_id: i64,
-    state: tauri::State<'_, AppState>,
+async fn add_budget(
+    request: services::budget::AddBudgetRequest,
+    state: tauri::State<'_, AppState>
 ) -> Result<i64, api_error::ApiError> {
-    let transaction = state.transaction.lock().await;
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
-
-    Ok(transaction
-        .compute_recommended_total(user_id, transaction_id)
-        .await?)
+    let db = &state.db;
+    services::budget::add_budget(db.pool(), user_id, request).await
 }
 
-/// Overwrite a transaction header's TOTAL_AMOUNT without touching other
-/// fields. The frontend calls this once the user confirms a recalculation
-/// prompt; passing the user-decided value (rather than recomputing on the
-/// backend) keeps the prompt's displayed value as the source of truth.
 #[tauri::command]
-async fn update_transaction_header_total(
-    transaction_id: i64,
-    total_amount: i64,
-    state: tauri::State<'_, AppState>,
+async fn update_budget(
+    budget_id: i64,
+    request: services::budget::UpdateBudgetRequest,
+    state: tauri::State<'_, AppState>
 ) -> Result<(), api_error::ApiError> {
-    let transaction = state.transaction.lock().await;
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::budget::update_budget(db.pool(), user_id, budget_id, request).await
+}
 
-    Ok(transaction
-        .update_transaction_header_total(user_id, transaction_id, total_amount)
-        .await?)
+#[tauri::command]
+async fn delete_budget(
+    budget_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::budget::delete_budget(db.pool(), user_id, budget_id).await
 }
 
-/// Walk every transaction header for the current user, recompute its
-/// `TOTAL_AMOUNT` from the latest details, and persist the result. A file
-/// backup of the live DB is taken first so the frontend can offer a one-
-/// click rollback via `restore_totals_from_backup` without the user having
-/// to find the backup in the filesystem themselves.
+/// Budget-vs-actual for one month period. Bounds follow the user's month
+/// start day and holiday shift, same as `get_monthly_aggregation`.
 #[tauri::command]
-async fn recalculate_all_transaction_totals(
-    state: tauri::State<'_, AppState>,
-) -> Result<services::transaction::RecalcSummary, api_error::ApiError> {
-    let transaction = state.transaction.lock().await;
+async fn get_budget_report(
+    period_year: i32,
+    period_month: u32,
+    include_scheduled: Option<bool>,
+    state: tauri::State<'_, AppState>
+) -> Result<services::budget::BudgetReport, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let lang = state.settings.lock().await
+        .get_string("language")
+        .unwrap_or_else(|_| LANG_DEFAULT.to_string());
+    let db = &state.db;
 
-    Ok(transaction
-        .recalculate_all_transaction_totals(user_id)
-        .await?)
+    let (month_start_day, _, _, month_shift) = fetch_period_settings(db.pool(), user_id)
+        .await
+        .map_err(api_error::ApiError::database)?;
+    let (start, end) = monthly_bounds_with_shift_for(
+        db.pool(),
+        user_id,
+        period_year,
+        period_month,
+        month_start_day,
+        month_shift,
+    )
+    .await
+    .map_err(api_error::ApiError::validation)?;
+
+    services::budget::get_budget_report(
+        db.pool(),
+        user_id,
+        period_year,
+        period_month,
+        start,
+        end,
+        &lang,
+        include_scheduled.unwrap_or(false),
+    )
+    .await
 }
 
-/// Roll back the `TOTAL_AMOUNT` column of every header for the current user
-/// to the values stored in `backup_path`. Used immediately after
-/// `recalculate_all_transaction_totals` if the user changes their mind, or
-/// later if anomalies surface. The command only ever touches `TOTAL_AMOUNT`
-/// — details, memos, audit fields and the rest of the schema are
-/// untouched, so a rollback cannot delete data the user has typed in
-/// since the recalculation.
 #[tauri::command]
-async fn restore_totals_from_backup(
-    backup_path: String,
-    state: tauri::State<'_, AppState>,
-) -> Result<services::transaction::RestoreSummary, api_error::ApiError> {
-    let transaction = state.transaction.lock().await;
+async fn list_budget_rollover_rules(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::budget::RolloverRule>, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::budget::list_rollover_rules(db.pool(), user_id).await
+}
 
-    Ok(transaction
-        .restore_totals_from_backup(user_id, &backup_path)
-        .await?)
+#[tauri::command]
+async fn set_budget_rollover_rule(
+    request: services::budget::SetRolloverRuleRequest,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::budget::set_rollover_rule(db.pool(), user_id, request).await
 }
 
-// =============================================================================
-// Aggregation Commands
-// =============================================================================
+#[tauri::command]
+async fn list_budget_carry_history(
+    period_year: i32,
+    period_month: u32,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::budget::CarryRecord>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::budget::list_carry_history(db.pool(), user_id, period_year, period_month).await
+}
 
-// =============================================================================
+/// Store each budget's carry out of a period into the next one.
+/// `recompute = true` replaces carries that were already stored.
+#[tauri::command]
+async fn close_budget_period(
+    period_year: i32,
+    period_month: u32,
+    recompute: Option<bool>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::budget::CarryRecord>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let lang = state.settings.lock().await
+        .get_string("language")
+        .unwrap_or_else(|_| LANG_DEFAULT.to_string());
+    let db = &state.db;
+
+    let (month_start_day, _, _, month_shift) = fetch_period_settings(db.pool(), user_id)
+        .await
+        .map_err(api_error::ApiError::database)?;
+    let (start, end) = monthly_bounds_with_shift_for(
+        db.pool(),
+        user_id,
+        period_year,
+        period_month,
+        month_start_day,
+        month_shift,
+    )
+    .await
+    .map_err(api_error::ApiError::validation)?;
+
+    services::budget::close_budget_period(
+        db.pool(),
+        user_id,
+        period_year,
+        period_month,
+        start,
+        end,
+        &lang,
+        recompute.unwrap_or(false),
+    )
+    .await
+}
+
+// ============================================================================
+// Reconciliation Commands
+// ============================================================================
+
+#[tauri::command]
+async fn add_reconciliation_checkpoint(
+    request: services::reconciliation::AddCheckpointRequest,
+    state: tauri::State<'_, AppState>
+) -> Result<services::reconciliation::CheckpointStatus, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::reconciliation::add_checkpoint(db.pool(), user_id, request).await
+}
+
+/// Statement checkpoints of an account with the difference from the
+/// ledger balance recomputed on every call.
+#[tauri::command]
+async fn list_reconciliation_checkpoints(
+    account_code: String,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::reconciliation::CheckpointStatus>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::reconciliation::list_checkpoints(db.pool(), user_id, &account_code).await
+}
+
+#[tauri::command]
+async fn delete_reconciliation_checkpoint(
+    checkpoint_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::reconciliation::delete_checkpoint(db.pool(), user_id, checkpoint_id).await
+}
+
+#[tauri::command]
+async fn set_transactions_cleared(
+    transaction_ids: Vec<i64>,
+    cleared: bool,
+    state: tauri::State<'_, AppState>
+) -> Result<u64, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "transaction.clear", services::reconciliation::set_cleared(db.pool(), user_id, &transaction_ids, cleared)).await
+}
+
+#[tauri::command]
+async fn reconcile_checkpoint(
+    checkpoint_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<services::reconciliation::ReconcileSummary, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "transaction.reconcile", services::reconciliation::reconcile_checkpoint(db.pool(), user_id, checkpoint_id)).await
+}
+
+/// Release reconciled transactions so `update_transaction_header` accepts
+/// edits to them again.
+#[tauri::command]
+async fn unlock_reconciled_transactions(
+    transaction_ids: Vec<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<u64, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "transaction.unlock", services::reconciliation::unlock_transactions(db.pool(), user_id, &transaction_ids)).await
+}
+
+// ============================================================================
+// Credit Card Billing Commands
+// ============================================================================
+
+#[tauri::command]
+async fn list_card_billing_settings(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::card_billing::CardBillingSettings>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::card_billing::list_billing_settings(db.pool(), user_id).await
+}
+
+#[tauri::command]
+async fn set_card_billing_settings(
+    request: services::card_billing::SetCardBillingRequest,
+    state: tauri::State<'_, AppState>
+) -> Result<services::card_billing::CardBillingSettings, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::card_billing::set_billing_settings(db.pool(), user_id, request).await
+}
+
+#[tauri::command]
+async fn delete_card_billing_settings(
+    account_code: String,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::card_billing::delete_billing_settings(db.pool(), user_id, &account_code).await
+}
+
+/// Statement closing in `year`/`month`, computed without saving anything
+#[tauri::command]
+async fn preview_card_statement(
+    account_code: String,
+    year: i32,
+    month: u32,
+    state: tauri::State<'_, AppState>
+) -> Result<services::card_billing::CardStatement, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::card_billing::preview_statement(db.pool(), user_id, &account_code, year, month).await
+}
+
+/// Issue the statement and schedule its bank → card payment, which the user
+/// confirms later with `confirm_scheduled_transaction`.
+#[tauri::command]
+async fn issue_card_statement(
+    account_code: String,
+    year: i32,
+    month: u32,
+    state: tauri::State<'_, AppState>
+) -> Result<services::card_billing::CardStatement, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "card_statement.issue", services::card_billing::issue_statement(db.pool(), user_id, &account_code, year, month)).await
+}
+
+#[tauri::command]
+async fn list_card_statements(
+    account_code: String,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::card_billing::CardStatement>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::card_billing::list_statements(db.pool(), user_id, &account_code).await
+}
+
+// ============================================================================
+// Currency Commands
+// ============================================================================
+
+#[tauri::command]
+fn list_currencies() -> Vec<services::currency::CurrencyInfo> {
+    services::currency::SUPPORTED_CURRENCIES.to_vec()
+}
+
+#[tauri::command]
+async fn get_base_currency(
+    state: tauri::State<'_, AppState>
+) -> Result<String, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::currency::get_base_currency(db.pool(), user_id).await
+}
+
+#[tauri::command]
+async fn set_base_currency(
+    currency_code: String,
+    state: tauri::State<'_, AppState>
+) -> Result<String, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::currency::set_base_currency(db.pool(), user_id, &currency_code).await
+}
+
+/// Only allowed while the account has no transactions
+#[tauri::command]
+async fn set_account_currency(
+    account_code: String,
+    currency_code: String,
+    state: tauri::State<'_, AppState>
+) -> Result<String, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "account.set_currency", services::account::set_account_currency(db.pool(), user_id, &account_code, &currency_code)).await
+}
+
+#[tauri::command]
+async fn list_exchange_rates(
+    currency_code: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::currency::ExchangeRate>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::currency::list_exchange_rates(db.pool(), user_id, currency_code.as_deref()).await
+}
+
+#[tauri::command]
+async fn set_exchange_rate(
+    request: services::currency::SetExchangeRateRequest,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::currency::set_exchange_rate(db.pool(), user_id, request).await
+}
+
+#[tauri::command]
+async fn delete_exchange_rate(
+    rate_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::currency::delete_exchange_rate(db.pool(), user_id, rate_id).await
+}
+
+/// Import `date,currency,rate` lines; all-or-nothing
+#[tauri::command]
+async fn import_exchange_rates_csv(
+    csv_text: String,
+    state: tauri::State<'_, AppState>
+) -> Result<services::currency::ExchangeRateImportSummary, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::currency::import_exchange_rates_csv(db.pool(), user_id, &csv_text).await
+}
+
+// ============================================================================
+// Attachment Commands
+// ============================================================================
+
+/// Attach an image or PDF to a transaction (or one of its detail lines).
+/// Passing `password` stores the file encrypted with the user's key.
+#[tauri::command]
+async fn add_attachment(
+    request: services::attachment::AddAttachmentRequest,
+    password: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<services::attachment::Attachment, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "attachment.add", services::attachment::add_attachment(db.pool(), user_id, request, password.as_deref())).await
+}
+
+#[tauri::command]
+async fn list_attachments(
+    transaction_id: i64,
+    detail_id: Option<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::attachment::Attachment>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::attachment::list_attachments(db.pool(), user_id, transaction_id, detail_id).await
+}
+
+#[tauri::command]
+async fn get_attachment(
+    attachment_id: i64,
+    password: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<services::attachment::AttachmentContent, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::attachment::get_attachment(db.pool(), user_id, attachment_id, password.as_deref()).await
+}
+
+#[tauri::command]
+async fn delete_attachment(
+    attachment_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "attachment.delete", services::attachment::delete_attachment(db.pool(), user_id, attachment_id)).await
+}
+
+// ============================================================================
+// Tag Commands
+// ============================================================================
+
+#[tauri::command]
+async fn list_tags(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::tag::Tag>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::tag::list_tags(db.pool(), user_id).await
+}
+
+#[tauri::command]
+async fn add_tag(
+    tag_name: String,
+    state: tauri::State<'_, AppState>
+) -> Result<services::tag::Tag, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "tag.add", services::tag::add_tag(db.pool(), user_id, &tag_name)).await
+}
+
+#[tauri::command]
+async fn rename_tag(
+    tag_id: i64,
+    tag_name: String,
+    state: tauri::State<'_, AppState>
+) -> Result<services::tag::Tag, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "tag.update", services::tag::rename_tag(db.pool(), user_id, tag_id, &tag_name)).await
+}
+
+#[tauri::command]
+async fn delete_tag(
+    tag_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "tag.delete", services::tag::delete_tag(db.pool(), user_id, tag_id)).await
+}
+
+#[tauri::command]
+async fn get_transaction_tags(
+    transaction_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::tag::TransactionTag>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::tag::get_transaction_tags(db.pool(), user_id, transaction_id).await
+}
+
+/// Replace the tags on a transaction header (`detail_id` = None) or on one
+/// of its detail lines.
+#[tauri::command]
+async fn set_transaction_tags(
+    transaction_id: i64,
+    detail_id: Option<i64>,
+    tag_ids: Vec<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::tag::TransactionTag>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "transaction.set_tags", services::tag::set_transaction_tags(db.pool(), user_id, transaction_id, detail_id, &tag_ids)).await
+}
+
+// ============================================================================
+// Saved Filter Commands
+// ============================================================================
+
+#[tauri::command]
+async fn list_saved_filters(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::saved_filter::SavedFilter>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::saved_filter::list_saved_filters(db.pool(), user_id).await
+}
+
+#[tauri::command]
+async fn save_filter(
+    filter_name: String,
+    filter: services::transaction::TransactionQuery,
+    state: tauri::State<'_, AppState>
+) -> Result<services::saved_filter::SavedFilter, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::saved_filter::save_filter(db.pool(), user_id, &filter_name, &filter).await
+}
+
+#[tauri::command]
+async fn rename_saved_filter(
+    saved_filter_id: i64,
+    filter_name: String,
+    state: tauri::State<'_, AppState>
+) -> Result<services::saved_filter::SavedFilter, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::saved_filter::rename_saved_filter(db.pool(), user_id, saved_filter_id, &filter_name).await
+}
+
+#[tauri::command]
+async fn delete_saved_filter(
+    saved_filter_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::saved_filter::delete_saved_filter(db.pool(), user_id, saved_filter_id).await
+}
+
+#[tauri::command]
+async fn execute_saved_filter(
+    saved_filter_id: i64,
+    page: i64,
+    per_page: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<services::transaction::TransactionListResponse, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::saved_filter::execute_saved_filter(db.pool(), user_id, saved_filter_id, page, per_page).await
+}
+
+// ============================================================================
+// Search Commands
+// ============================================================================
+
+/// Full-text search over memos, item names, shops and products, returning
+/// ranked hits with highlighted snippets.
+#[tauri::command]
+async fn search_transactions(
+    query: String,
+    limit: Option<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::search::TransactionSearchHit>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::search::search_transactions(db.pool(), user_id, &query, limit).await
+}
+
+// ============================================================================
+// Duplicate Detection Commands
+// ============================================================================
+
+/// Groups of transactions that look like the same receipt entered twice.
+#[tauri::command]
+async fn find_duplicate_transactions(
+    options: Option<services::duplicate::DuplicateScanOptions>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::duplicate::DuplicateGroup>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::duplicate::find_duplicate_groups(db.pool(), user_id, &options.unwrap_or_default()).await
+}
+
+/// Mark a reported group as not duplicates so it is not reported again.
+#[tauri::command]
+async fn dismiss_duplicate_group(
+    transaction_ids: Vec<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    journaled(&state, user_id, "transaction.dismiss_duplicates", services::duplicate::dismiss_duplicate_group(db.pool(), user_id, &transaction_ids)).await
+}
+
+// ============================================================================
+// Undo / Redo Commands
+// ============================================================================
+
+/// Journaled operations of this login, newest first.
+#[tauri::command]
+async fn list_change_operations(
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::journal::ChangeOperation>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::journal::list_operations(db.pool(), user_id).await
+}
+
+/// Undo `operation_id`, or the last `count` operations (default 1) when no
+/// ID is given. Returns the IDs that were undone.
+#[tauri::command]
+async fn undo_operations(
+    count: Option<i64>,
+    operation_id: Option<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<i64>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let _journal = state.journal.lock().await;
+    let db = &state.db;
+    match operation_id {
+        Some(id) => {
+            services::journal::undo_operation(db.pool(), user_id, id).await?;
+            Ok(vec![id])
+        }
+        None => services::journal::undo_last(db.pool(), user_id, count.unwrap_or(1)).await,
+    }
+}
+
+/// Redo `operation_id`, or the next `count` undone operations (default 1).
+/// Returns the IDs that were redone.
+#[tauri::command]
+async fn redo_operations(
+    count: Option<i64>,
+    operation_id: Option<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<i64>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let _journal = state.journal.lock().await;
+    let db = &state.db;
+    match operation_id {
+        Some(id) => {
+            services::journal::redo_operation(db.pool(), user_id, id).await?;
+            Ok(vec![id])
+        }
+        None => services::journal::redo_last(db.pool(), user_id, count.unwrap_or(1)).await,
+    }
+}
+
+// ============================================================================
+// Audit Log Commands
+// ============================================================================
+
+/// Who changed which row, across every user of the household. Admin only.
+#[tauri::command]
+async fn get_audit_log(
+    query: Option<services::audit::AuditLogQuery>,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::audit::AuditLogEntry>, api_error::ApiError> {
+    require_admin_session(&state).map_err(api_error::ApiError::validation)?;
+    let db = &state.db;
+    services::audit::list_audit_log(db.pool(), &query.unwrap_or_default()).await
+}
+
+// ============================================================================
+// Ledger Export Commands
+// ============================================================================
+
+/// Render the session user's ledger for a date range as CSV or JSON with
+/// category, account, shop, product and memo names resolved. The frontend
+/// saves `content` under the suggested `file_name`.
+#[tauri::command]
+async fn export_ledger(
+    start_date: Option<String>,
+    end_date: Option<String>,
+    include_scheduled: Option<bool>,
+    format: services::export::ExportFormat,
+    state: tauri::State<'_, AppState>
+) -> Result<services::export::LedgerExport, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let lang = state.settings.lock().await
+        .get_string("language")
+        .unwrap_or_else(|_| LANG_DEFAULT.to_string());
+    let db = &state.db;
+    services::export::export_ledger(
+        db.pool(),
+        user_id,
+        &lang,
+        start_date.as_deref(),
+        end_date.as_deref(),
+        include_scheduled.unwrap_or(false),
+        format,
+    )
+    .await
+}
+
+// ============================================================================
+// Transaction Detail Management Commands
+// ============================================================================
+
+#[tauri::command]
+async fn get_transaction_header_with_info(
+    transaction_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<services::transaction::TransactionHeaderWithInfo, api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+
+    Ok(transaction.get_transaction_header_with_info(user_id, transaction_id).await?)
+}
+
+#[tauri::command]
+async fn get_transaction_details(
+    transaction_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<Vec<services::transaction::TransactionDetailWithInfo>, api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+
+    Ok(transaction.get_transaction_details(user_id, transaction_id).await?)
+}
+
+#[tauri::command]
+async fn add_transaction_detail(
+    transaction_id: i64,
+    category1_code: String,
+    category2_code: Option<String>,
+    category3_code: Option<String>,
+    item_name: String,
+    amount: i64,
+    tax_rate: i32,
+    tax_amount: i64,
+    amount_including_tax: Option<i64>,
+    product_id: Option<i64>,
+    memo: Option<String>,
+    from_account_code: Option<String>,
+    to_account_code: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<i64, api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+
+    let request = services::transaction::SaveTransactionDetailRequest {
+        detail_id: None,
+        category1_code,
+        category2_code,
+        category3_code,
+        item_name,
+        amount,
+        tax_rate,
+        tax_amount,
+        amount_including_tax,
+        product_id,
+        memo,
+        from_account_code,
+        to_account_code,
+    };
+
+    journaled(&state, user_id, "transaction.update", async {
+        Ok(transaction.add_transaction_detail(user_id, transaction_id, request).await?)
+    }).await
+}
+
+#[tauri::command]
+async fn update_transaction_detail(
+    detail_id: i64,
+    category1_code: String,
+    category2_code: Option<String>,
+    category3_code: Option<String>,
+    item_name: String,
+    amount: i64,
+    tax_rate: i32,
+    tax_amount: i64,
+    amount_including_tax: Option<i64>,
+    product_id: Option<i64>,
+    memo: Option<String>,
+    from_account_code: Option<String>,
+    to_account_code: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+
+    let request = services::transaction::SaveTransactionDetailRequest {
+        detail_id: Some(detail_id),
+        category1_code,
+        category2_code,
+        category3_code,
+        item_name,
+        amount,
+        tax_rate,
+        tax_amount,
+        amount_including_tax,
+        product_id,
+        memo,
+        from_account_code,
+        to_account_code,
+    };
+
+    journaled(&state, user_id, "transaction.update", async {
+        Ok(transaction.update_transaction_detail(user_id, detail_id, request).await?)
+    }).await
+}
+
+#[tauri::command]
+async fn delete_transaction_detail(
+    detail_id: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<(), api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+
+    journaled(&state, user_id, "transaction.update", async {
+        Ok(transaction.delete_transaction_detail(user_id, detail_id).await?)
+    }).await
+}
+
+/// Compute the TOTAL_AMOUNT a transaction header *should* have based on its
+/// current details and saved tax rounding setting. The frontend uses this
+/// after a detail edit (or a tax-setting change) to decide whether to prompt
+/// the user before overwriting the cached header total.
+#[tauri::command]
+async fn compute_recommended_transaction_total(
+    transaction_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> Result<i64, api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+
+    Ok(transaction
+        .compute_recommended_total(user_id, transaction_id)
+        .await?)
+}
+
+/// Overwrite a transaction header's TOTAL_AMOUNT without touching other
+/// fields. The frontend calls this once the user confirms a recalculation
+/// prompt; passing the user-decided value (rather than recomputing on the
+/// backend) keeps the prompt's displayed value as the source of truth.
+#[tauri::command]
+async fn update_transaction_header_total(
+    transaction_id: i64,
+    total_amount: i64,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+
+    journaled(&state, user_id, "transaction.update", async {
+        Ok(transaction
+            .update_transaction_header_total(user_id, transaction_id, total_amount)
+            .await?)
+    }).await
+}
+
+/// Walk every transaction header for the current user, recompute its
+/// `TOTAL_AMOUNT` from the latest details, and persist the result. A file
+/// backup of the live DB is taken first so the frontend can offer a one-
+/// click rollback via `restore_totals_from_backup` without the user having
+/// to find the backup in the filesystem themselves.
+#[tauri::command]
+async fn recalculate_all_transaction_totals(
+    state: tauri::State<'_, AppState>,
+) -> Result<services::transaction::RecalcSummary, api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let _journal = state.journal.lock().await;
+
+    Ok(transaction
+        .recalculate_all_transaction_totals(user_id)
+        .await?)
+}
+
+/// Roll back the `TOTAL_AMOUNT` column of every header for the current user
+/// to the values stored in `backup_path`. Used immediately after
+/// `recalculate_all_transaction_totals` if the user changes their mind, or
+/// later if anomalies surface. The command only ever touches `TOTAL_AMOUNT`
+/// — details, memos, audit fields and the rest of the schema are
+/// untouched, so a rollback cannot delete data the user has typed in
+/// since the recalculation.
+#[tauri::command]
+async fn restore_totals_from_backup(
+    backup_path: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<services::transaction::RestoreSummary, api_error::ApiError> {
+    let transaction = state.transaction.lock().await;
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let _journal = state.journal.lock().await;
+
+    Ok(transaction
+        .restore_totals_from_backup(user_id, &backup_path)
+        .await?)
+}
+
+// =============================================================================
+// Aggregation Commands
+// =============================================================================
+
+// =============================================================================
 // Aggregation Commands
 // =============================================================================
 
@@ -2072,6 +3032,7 @@ fn parse_group_by(group_by: &str) -> Result<services::aggregation::GroupBy, Stri
         "shop" => Ok(services::aggregation::GroupBy::Shop),
         "product" => Ok(services::aggregation::GroupBy::Product),
         "date" => Ok(services::aggregation::GroupBy::Date),
+        "tag" => Ok(services::aggregation::GroupBy::Tag),
         _ => Err(format!("Invalid group_by value: {}", group_by)),
     }
 }
@@ -2308,6 +3269,7 @@ async fn get_monthly_aggregation_by_category(
         "shop" => services::aggregation::GroupBy::Shop,
         "product" => services::aggregation::GroupBy::Product,
         "date" => services::aggregation::GroupBy::Date,
+        "tag" => services::aggregation::GroupBy::Tag,
         _ => return Err(format!("Invalid group_by value: {}", group_by)),
     };
 
@@ -2350,12 +3312,31 @@ async fn create_recurring_rule(
 ) -> Result<services::recurring::CreateRecurringRuleResult, api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let recurring = state.recurring.lock().await;
-    Ok(recurring.create_rule_with_instances(user_id, request).await?)
+    journaled(&state, user_id, "recurring_rule.add", async {
+        Ok(recurring.create_rule_with_instances(user_id, request).await?)
+    }).await
+}
+
+/// Edit a recurring rule. Scheduled occurrences dated today or later are
+/// rewritten, removed or added to match the new cycle and template;
+/// confirmed occurrences keep their values.
+#[tauri::command]
+async fn update_recurring_rule(
+    rule_id: i64,
+    request: services::recurring::SaveRecurringRuleRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<services::recurring::UpdateRecurringRuleResult, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let recurring = state.recurring.lock().await;
+    journaled(&state, user_id, "recurring_rule.update", async {
+        Ok(recurring.update_rule(user_id, rule_id, request).await?)
+    }).await
 }
 
 /// Delete a recurring rule. `cascade = true` also removes every
 /// IS_SCHEDULED=1 occurrence the rule had generated; `cascade = false`
-/// keeps those occurrences but clears their `RULE_ID` reference.
+/// keeps those occurrences but clears their `RULE_ID` reference. A cascade
+/// is refused while any occurrence is reconciled.
 #[tauri::command]
 async fn delete_recurring_rule(
     rule_id: i64,
@@ -2364,7 +3345,9 @@ async fn delete_recurring_rule(
 ) -> Result<(), api_error::ApiError> {
     let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
     let recurring = state.recurring.lock().await;
-    Ok(recurring.delete_rule(user_id, rule_id, cascade).await?)
+    journaled(&state, user_id, "recurring_rule.delete", async {
+        Ok(recurring.delete_rule(user_id, rule_id, cascade).await?)
+    }).await
 }
 
 /// List the recurring rules the current user has registered.
@@ -2379,6 +3362,60 @@ async fn list_recurring_rules(
     Ok(recurring.list_rules(user_id).await?)
 }
 
+/// Suggest the estimate of a variable-amount rule from its last
+/// `sample_size` (default `RECURRING_ESTIMATE_SAMPLE_SIZE`) confirmed
+/// occurrences.
+#[tauri::command]
+async fn suggest_recurring_rule_estimate(
+    rule_id: i64,
+    sample_size: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> Result<services::recurring::RecurringEstimateSuggestion, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let recurring = state.recurring.lock().await;
+    let sample_size = sample_size.unwrap_or(consts::RECURRING_ESTIMATE_SAMPLE_SIZE);
+    Ok(recurring.suggest_estimate(user_id, rule_id, sample_size).await?)
+}
+
+/// List the occurrences of a rule that were skipped or moved.
+#[tauri::command]
+async fn list_recurring_rule_exceptions(
+    rule_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<services::recurring::RecurringExceptionSummary>, api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let recurring = state.recurring.lock().await;
+    Ok(recurring.list_exceptions(user_id, rule_id).await?)
+}
+
+/// Skip one occurrence of a rule or move it to another date.
+#[tauri::command]
+async fn save_recurring_rule_exception(
+    rule_id: i64,
+    request: services::recurring::SaveRecurringExceptionRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let recurring = state.recurring.lock().await;
+    journaled(&state, user_id, "recurring_rule.save_exception", async {
+        Ok(recurring.save_exception(user_id, rule_id, request).await?)
+    }).await
+}
+
+/// Remove a skip/move exception, putting the occurrence back on its date.
+#[tauri::command]
+async fn delete_recurring_rule_exception(
+    rule_id: i64,
+    occurrence_date: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), api_error::ApiError> {
+    let user_id = get_session_user_id(&state).map_err(api_error::ApiError::validation)?;
+    let recurring = state.recurring.lock().await;
+    journaled(&state, user_id, "recurring_rule.delete_exception", async {
+        Ok(recurring.delete_exception(user_id, rule_id, &occurrence_date).await?)
+    }).await
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -2461,6 +3498,8 @@ pub fn run() {
             get_account_templates,
             get_accounts,
             get_account_balances_as_of,
+            get_account_ledger,
+            get_balance_history,
             add_account,
             update_account,
             delete_account,
@@ -2482,6 +3521,7 @@ pub fn run() {
             select_transaction_headers,
             update_transaction_header,
             confirm_scheduled_transaction,
+            bulk_update_transactions,
             get_transaction_header_with_info,
             get_transaction_details,
             add_transaction_detail,
@@ -2499,8 +3539,71 @@ pub fn run() {
             get_yearly_aggregation,
             get_monthly_aggregation_by_category,
             create_recurring_rule,
+            update_recurring_rule,
             delete_recurring_rule,
-            list_recurring_rules
+            list_recurring_rules,
+            suggest_recurring_rule_estimate,
+            list_recurring_rule_exceptions,
+            save_recurring_rule_exception,
+            delete_recurring_rule_exception,
+            save_import_profile,
+            get_import_profile,
+            list_import_profiles,
+            delete_import_profile,
+            preview_csv_import,
+            import_csv_statement,
+            export_ledger,
+            list_budgets,
+            add_budget,
+            update_budget,
+            delete_budget,
+            get_budget_report,
+            list_budget_rollover_rules,
+            set_budget_rollover_rule,
+            list_budget_carry_history,
+            close_budget_period,
+            add_reconciliation_checkpoint,
+            list_reconciliation_checkpoints,
+            delete_reconciliation_checkpoint,
+            set_transactions_cleared,
+            reconcile_checkpoint,
+            unlock_reconciled_transactions,
+            list_card_billing_settings,
+            set_card_billing_settings,
+            delete_card_billing_settings,
+            preview_card_statement,
+            issue_card_statement,
+            list_card_statements,
+            list_currencies,
+            get_base_currency,
+            set_base_currency,
+            set_account_currency,
+            list_exchange_rates,
+            set_exchange_rate,
+            delete_exchange_rate,
+            import_exchange_rates_csv,
+            add_attachment,
+            list_attachments,
+            get_attachment,
+            delete_attachment,
+            list_tags,
+            add_tag,
+            rename_tag,
+            delete_tag,
+            get_transaction_tags,
+            set_transaction_tags,
+            list_saved_filters,
+            save_filter,
+            rename_saved_filter,
+            delete_saved_filter,
+            execute_saved_filter,
+            search_transactions,
+            find_duplicate_transactions,
+            dismiss_duplicate_group,
+            list_change_operations,
+            undo_operations,
+            redo_operations,
+            get_audit_log
         ])
         .setup(|app| {
             if cfg!(debug_assertions) {
@@ -2547,6 +3650,66 @@ pub fn run() {
                 database.migrate_shops_unique().await
                     .map_err(|e| format!("Failed to migrate SHOPS unique constraint: {}", e))?;
 
+                // CSV statement import column-mapping profiles
+                database.migrate_csv_import().await
+                    .map_err(|e| format!("Failed to migrate import profile table: {}", e))?;
+
+                // Monthly budgets per category
+                database.migrate_budgets().await
+                    .map_err(|e| format!("Failed to migrate budget table: {}", e))?;
+                database.migrate_budget_rollover().await
+                    .map_err(|e| format!("Failed to migrate budget rollover tables: {}", e))?;
+
+                // Statement checkpoints + cleared/reconciled flags
+                database.migrate_reconciliation().await
+                    .map_err(|e| format!("Failed to migrate reconciliation: {}", e))?;
+
+                // Asset/liability class on account templates
+                database.migrate_account_class().await
+                    .map_err(|e| format!("Failed to migrate account class: {}", e))?;
+
+                // Credit-card billing cycles and statements
+                database.migrate_card_billing().await
+                    .map_err(|e| format!("Failed to migrate card billing tables: {}", e))?;
+
+                // Currency codes on users/accounts/headers + exchange rates
+                database.migrate_currency().await
+                    .map_err(|e| format!("Failed to migrate currency columns: {}", e))?;
+
+                // Per-line accounts on details + TRANSACTION_MOVEMENTS view
+                database.migrate_split_transactions().await
+                    .map_err(|e| format!("Failed to migrate split transactions: {}", e))?;
+
+                // Receipt/document attachments on headers and details
+                database.migrate_attachments().await
+                    .map_err(|e| format!("Failed to migrate attachments table: {}", e))?;
+
+                // Free-form tags on headers and details
+                database.migrate_tags().await
+                    .map_err(|e| format!("Failed to migrate tag tables: {}", e))?;
+
+                // Named transaction-list filters
+                database.migrate_saved_filters().await
+                    .map_err(|e| format!("Failed to migrate saved filters table: {}", e))?;
+
+                // Pairs dismissed from the duplicate-transaction report
+                database.migrate_duplicate_dismissals().await
+                    .map_err(|e| format!("Failed to migrate duplicate dismissals table: {}", e))?;
+
+                // Who-changed-what log for administrators
+                database.migrate_audit_log().await
+                    .map_err(|e| format!("Failed to migrate audit log table: {}", e))?;
+
+                // Full-text search index; keep last so its triggers survive
+                // any table rebuild above
+                database.migrate_transaction_search().await
+                    .map_err(|e| format!("Failed to migrate transaction search index: {}", e))?;
+
+                // Undo journal; its triggers are generated from the final
+                // column lists, so this runs after every other migration
+                database.migrate_change_journal().await
+                    .map_err(|e| format!("Failed to migrate change journal: {}", e))?;
+
                 let auth_service = AuthService::new(database.pool().clone());
                 let user_mgmt_service = UserManagementService::new(database.pool().clone());
                 let encryption_service = EncryptionService::new(database.pool().clone());
@@ -2571,6 +3734,7 @@ pub fn run() {
                 transaction: Arc::new(Mutex::new(transaction)),
                 recurring: Arc::new(Mutex::new(recurring)),
                 session: Arc::new(SessionState::new()),
+                journal: Arc::new(Mutex::new(())),
             });
 
             Ok(())
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, FromRow};
 use crate::api_error::ApiError;
+use crate::consts;
+use crate::services::audit::{self, AuditKey};
+use crate::services::currency::{self, RateTable};
 use crate::services::master_data;
 use crate::sql_queries;
 use crate::validation;
@@ -8,6 +11,11 @@ use crate::validation;
 const NAME_LABEL: &str = "Account name";
 const ENTITY_LABEL: &str = "Account";
 
+/// Accounts are audited by their per-user code, the key the commands use.
+fn audit_key(user_id: i64, account_code: &str) -> AuditKey {
+    AuditKey::new("ACCOUNTS", "USER_ID", user_id).and("ACCOUNT_CODE", account_code)
+}
+
 /// Normalize account code to uppercase
 fn normalize_account_code(code: &str) -> String {
     code.trim().to_uppercase()
@@ -21,6 +29,8 @@ pub struct AccountTemplate {
     pub template_name_ja: String,
     pub template_name_en: String,
     pub display_order: i64,
+    /// 0 for assets or `consts::ACCOUNT_CLASS_LIABILITY`
+    pub account_class: i64,
     pub entry_dt: String,
 }
 
@@ -37,21 +47,123 @@ pub struct Account {
     pub is_disabled: i64,
     pub entry_dt: String,
     pub update_dt: Option<String>,
+    /// Inherited from the template
+    pub account_class: i64,
+    /// Currency of `initial_balance` and every amount booked on the account
+    pub currency_code: String,
+}
+
+impl Account {
+    pub fn is_liability(&self) -> bool {
+        self.account_class == consts::ACCOUNT_CLASS_LIABILITY
+    }
+}
+
+/// Multiplier turning an asset-side amount (as `signed_amount` returns it)
+/// into the account's own terms: unchanged for assets, negated for
+/// liabilities, whose balance is the amount owed.
+pub fn class_direction(account_class: i64) -> i64 {
+    if account_class == consts::ACCOUNT_CLASS_LIABILITY {
+        -1
+    } else {
+        1
+    }
 }
 
 /// Result row for `get_account_balances_as_of`. One row per active account,
 /// representing the running balance after every actualised transaction up to
 /// and including the given as-of date. Used by the dashboard so the user can
-/// reconcile the chart totals against per-account ledgers.
+/// reconcile the chart totals against per-account ledgers. For liabilities
+/// `balance` is the amount owed. `balance` is in the account's currency;
+/// `base_balance` is the same amount in the user's base currency, `None`
+/// when no exchange rate is on file for the as-of date.
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 #[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
 pub struct AccountBalance {
     pub account_code: String,
     pub account_name: String,
+    pub account_class: i64,
+    pub currency_code: String,
     pub balance: i64,
+    #[sqlx(skip)]
+    pub base_balance: Option<i64>,
     pub display_order: i64,
 }
 
+/// Balances as of one date, split by account class. Totals are in
+/// `base_currency` and leave out accounts without a `base_balance`.
+/// `net_worth = total_assets - total_liabilities`.
+#[derive(Debug, Serialize, Clone)]
+pub struct AccountBalancesAsOf {
+    pub base_currency: String,
+    pub assets: Vec<AccountBalance>,
+    pub liabilities: Vec<AccountBalance>,
+    pub total_assets: i64,
+    pub total_liabilities: i64,
+    pub net_worth: i64,
+}
+
+impl AccountBalancesAsOf {
+    /// Find one account's row in either class.
+    pub fn find(&self, account_code: &str) -> Option<&AccountBalance> {
+        self.assets
+            .iter()
+            .chain(self.liabilities.iter())
+            .find(|b| b.account_code == account_code)
+    }
+}
+
+/// Raw row of `ACCOUNT_LEDGER_ROWS`, before the sign and running balance
+/// are applied.
+#[derive(Debug, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+struct LedgerRow {
+    transaction_id: i64,
+    transaction_date: String,
+    category1_code: String,
+    from_account_code: String,
+    to_account_code: String,
+    counterpart_account_code: String,
+    counterpart_account_name: Option<String>,
+    total_amount: i64,
+    reconcile_status: i64,
+    shop_name: Option<String>,
+    memo_text: Option<String>,
+}
+
+/// One line of an account ledger. `amount` is signed from the account's
+/// point of view (for a liability, positive means more owed) and `balance`
+/// is the running balance after this line.
+#[derive(Debug, Serialize, Clone)]
+pub struct AccountLedgerEntry {
+    pub transaction_id: i64,
+    pub transaction_date: String,
+    pub category1_code: String,
+    pub counterpart_account_code: String,
+    pub counterpart_account_name: Option<String>,
+    pub amount: i64,
+    pub balance: i64,
+    pub reconcile_status: i64,
+    pub shop_name: Option<String>,
+    pub memo_text: Option<String>,
+}
+
+/// Ledger of a single account over an optional date range.
+/// `opening_balance` is the balance just before the first listed entry
+/// (INITIAL_BALANCE plus everything dated before `start_date`), and
+/// `closing_balance` matches `get_account_balances_as_of` on `end_date`.
+#[derive(Debug, Serialize, Clone)]
+pub struct AccountLedger {
+    pub account_code: String,
+    pub account_name: String,
+    pub account_class: i64,
+    pub currency_code: String,
+    pub initial_balance: i64,
+    pub opening_balance: i64,
+    pub closing_balance: i64,
+    pub entries: Vec<AccountLedgerEntry>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct AddAccountRequest {
     pub account_code: String,
@@ -100,19 +212,133 @@ pub async fn get_accounts(pool: &SqlitePool, user_id: i64) -> Result<Vec<Account
 ///
 /// The single-pass CASE keeps each transaction visible to both the source
 /// and destination accounts of a TRANSFER, with the sign flipped per side.
-/// Disabled accounts are excluded.
+/// Liability accounts are reported as the amount owed, so paying a card
+/// bill from a bank lowers both the bank balance and the card debt, and
+/// net worth is unchanged. Disabled accounts are excluded. Each balance is
+/// also converted to the base currency at the rate in force on
+/// `as_of_date`.
 pub async fn get_account_balances_as_of(
     pool: &SqlitePool,
     user_id: i64,
     as_of_date: &str,
-) -> Result<Vec<AccountBalance>, ApiError> {
-    let balances = sqlx::query_as::<_, AccountBalance>(sql_queries::ACCOUNT_BALANCES_AS_OF)
+) -> Result<AccountBalancesAsOf, ApiError> {
+    let mut balances = sqlx::query_as::<_, AccountBalance>(sql_queries::ACCOUNT_BALANCES_AS_OF)
         .bind(as_of_date)
         .bind(user_id)
         .fetch_all(pool)
         .await?;
 
-    Ok(balances)
+    let rates = RateTable::load(pool, user_id).await?;
+    for balance in &mut balances {
+        balance.base_balance = rates.to_base(balance.balance, &balance.currency_code, as_of_date);
+    }
+
+    let (liabilities, assets): (Vec<_>, Vec<_>) = balances
+        .into_iter()
+        .partition(|b| b.account_class == consts::ACCOUNT_CLASS_LIABILITY);
+    let total_assets = assets.iter().filter_map(|b| b.base_balance).sum();
+    let total_liabilities = liabilities.iter().filter_map(|b| b.base_balance).sum();
+
+    Ok(AccountBalancesAsOf {
+        base_currency: rates.base_currency().to_string(),
+        assets,
+        liabilities,
+        total_assets,
+        total_liabilities,
+        net_worth: total_assets - total_liabilities,
+    })
+}
+
+/// Effect of one actualised header on `account_code`, using the same rules
+/// as ACCOUNT_BALANCES_AS_OF. The branches are checked in the same order as
+/// the SQL CASE, so a TRANSFER from an account to itself counts as incoming.
+pub fn signed_amount(
+    category1_code: &str,
+    from_account_code: &str,
+    to_account_code: &str,
+    account_code: &str,
+    amount: i64,
+) -> i64 {
+    match category1_code {
+        consts::CATEGORY1_INCOME if to_account_code == account_code => amount,
+        consts::CATEGORY1_EXPENSE if from_account_code == account_code => -amount,
+        consts::CATEGORY1_TRANSFER if to_account_code == account_code => amount,
+        consts::CATEGORY1_TRANSFER if from_account_code == account_code => -amount,
+        _ => 0,
+    }
+}
+
+/// List every actualised transaction touching `account_code` in date order,
+/// with a signed amount, the counterpart account and the running balance.
+/// Both dates are inclusive `YYYY-MM-DD`; transactions before `start_date`
+/// are folded into `opening_balance` instead of being listed.
+pub async fn get_account_ledger(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<AccountLedger, ApiError> {
+    let account_code = normalize_account_code(account_code);
+    let account = get_account_by_code(pool, user_id, &account_code)
+        .await?
+        .ok_or_else(|| ApiError::not_found("account"))?;
+
+    let rows = sqlx::query_as::<_, LedgerRow>(sql_queries::ACCOUNT_LEDGER_ROWS)
+        .bind(&account_code)
+        .bind(&account_code)
+        .bind(user_id)
+        .bind(&account_code)
+        .bind(&account_code)
+        .bind(end_date)
+        .bind(end_date)
+        .fetch_all(pool)
+        .await?;
+
+    let direction = class_direction(account.account_class);
+    let mut balance = account.initial_balance;
+    let mut opening_balance = balance;
+    let mut entries = Vec::new();
+    for row in rows {
+        let amount = direction
+            * signed_amount(
+                &row.category1_code,
+                &row.from_account_code,
+                &row.to_account_code,
+                &account_code,
+                row.total_amount,
+            );
+        balance += amount;
+        // Dates are stored as `YYYY-MM-DD[ HH:MM:SS]`, so a prefix compare
+        // on the day is enough.
+        if start_date.is_some_and(|start| row.transaction_date.get(..10).unwrap_or("") < start) {
+            opening_balance = balance;
+            continue;
+        }
+        entries.push(AccountLedgerEntry {
+            transaction_id: row.transaction_id,
+            transaction_date: row.transaction_date,
+            category1_code: row.category1_code,
+            counterpart_account_code: row.counterpart_account_code,
+            counterpart_account_name: row.counterpart_account_name,
+            amount,
+            balance,
+            reconcile_status: row.reconcile_status,
+            shop_name: row.shop_name,
+            memo_text: row.memo_text,
+        });
+    }
+
+    Ok(AccountLedger {
+        account_code: account.account_code,
+        account_name: account.account_name,
+        account_class: account.account_class,
+        currency_code: account.currency_code,
+        initial_balance: account.initial_balance,
+        opening_balance,
+        closing_balance: balance,
+        entries,
+    })
 }
 
 /// Get all accounts (for admin users)
@@ -124,10 +350,9 @@ pub async fn get_all_accounts(pool: &SqlitePool) -> Result<Vec<Account>, ApiErro
     Ok(accounts)
 }
 
-/// Get a single account by code. Kept for module tests only after the
-/// PR3/PR4 Fable-5 #26 refactor removed the delete/update pre-checks;
-/// still useful as a post-write "did the row actually land" probe.
-#[allow(dead_code)]
+/// Get a single account by code. No longer used by the account delete/update
+/// paths after the PR3/PR4 Fable-5 #26 refactor; reconciliation uses it to
+/// check the checkpoint's account, and the module tests as a post-write probe.
 pub async fn get_account_by_code(
     pool: &SqlitePool,
     user_id: i64,
@@ -193,6 +418,9 @@ pub async fn add_account(
     .await?;
 
     // Upsert account (insert or reactivate if deleted)
+    let mut tx = pool.begin().await?;
+    let key = audit_key(user_id, &request.account_code);
+    let before = audit::snapshot(&mut tx, &key).await?;
     sqlx::query(sql_queries::ACCOUNT_UPSERT)
         .bind(user_id)
         .bind(&request.account_code)
@@ -200,8 +428,10 @@ pub async fn add_account(
         .bind(&request.template_code)
         .bind(request.initial_balance)
         .bind(display_order)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
 
     Ok("Account added successfully".to_string())
 }
@@ -229,6 +459,9 @@ pub async fn update_account(
     // TOCTOU window where the row could vanish between the pre-check
     // and the update. shop/manufacturer/product got the same treatment
     // in PR3 via master_data::ensure_update_affected_one.
+    let mut tx = pool.begin().await?;
+    let key = audit_key(user_id, &request.account_code);
+    let before = audit::snapshot(&mut tx, &key).await?;
     let affected = sqlx::query(sql_queries::ACCOUNT_UPDATE)
         .bind(&request.account_name)
         .bind(&request.template_code)
@@ -236,16 +469,64 @@ pub async fn update_account(
         .bind(request.display_order)
         .bind(user_id)
         .bind(&request.account_code)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?
         .rows_affected();
     if affected == 0 {
         return Err(ApiError::not_found(ENTITY_LABEL));
     }
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
 
     Ok("Account updated successfully".to_string())
 }
 
+/// Change the currency an account is kept in. Only allowed while nothing is
+/// booked on it, since stored amounts would otherwise change meaning.
+pub async fn set_account_currency(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+    currency_code: &str,
+) -> Result<String, ApiError> {
+    let account_code = normalize_account_code(account_code);
+    let currency_code = currency::normalize_currency_code(currency_code)?;
+    if account_code == "NONE" {
+        return Err(ApiError::validation("The NONE account has no currency"));
+    }
+
+    let booked: i64 = sqlx::query_scalar(sql_queries::ACCOUNT_COUNT_TRANSACTIONS)
+        .bind(user_id)
+        .bind(&account_code)
+        .bind(&account_code)
+        .fetch_one(pool)
+        .await?;
+    if booked > 0 {
+        return Err(ApiError::validation(format!(
+            "Account {} already has transactions; its currency cannot change",
+            account_code
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    let key = audit_key(user_id, &account_code);
+    let before = audit::snapshot(&mut tx, &key).await?;
+    let affected = sqlx::query(sql_queries::ACCOUNT_UPDATE_CURRENCY)
+        .bind(&currency_code)
+        .bind(user_id)
+        .bind(&account_code)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
+
+    Ok(currency_code)
+}
+
 /// Delete an account (logical deletion)
 pub async fn delete_account(
     pool: &SqlitePool,
@@ -259,15 +540,20 @@ pub async fn delete_account(
     // delete_product (PR3, Fable-5 #26): a single logical-delete UPDATE
     // that maps 0-rows → NotFound eliminates the earlier pre-check +
     // execute pair and its TOCTOU window.
+    let mut tx = pool.begin().await?;
+    let key = audit_key(user_id, &account_code);
+    let before = audit::snapshot(&mut tx, &key).await?;
     let affected = sqlx::query(sql_queries::ACCOUNT_DELETE_LOGICAL)
         .bind(user_id)
         .bind(&account_code)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?
         .rows_affected();
     if affected == 0 {
         return Err(ApiError::not_found(ENTITY_LABEL));
     }
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
 
     Ok("Account deleted successfully".to_string())
 }
@@ -306,12 +592,19 @@ pub async fn initialize_none_account(pool: &SqlitePool, user_id: i64) -> Result<
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::consts;
     use crate::test_helpers::database::{init_db, TEST_DB_URL};
 
+    /// ACCOUNT_TEMPLATES.ACCOUNT_CLASS default
+    const ACCOUNT_CLASS_ASSET: i64 = 0;
+
     async fn setup_test_db() -> SqlitePool {
         let pool = init_db(TEST_DB_URL).await.unwrap();
 
+        sqlx::query(sql_queries::CREATE_AUDIT_LOG_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
+
         // Create USERS table
         sqlx::query(sql_queries::TEST_CREATE_USERS_TABLE)
             .execute(&pool)
@@ -330,6 +623,11 @@ mod tests {
             .await
             .unwrap();
 
+        sqlx::query(sql_queries::CREATE_EXCHANGE_RATES_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
+
         // Insert test users
         sqlx::query(sql_queries::TEST_INSERT_USER_ADMIN)
             .execute(&pool)
@@ -583,6 +881,18 @@ mod tests {
         assert!(err.message.contains("cannot be empty"), "unexpected error: {}", err.message);
     }
 
+    /// Header and detail tables plus the TRANSACTION_MOVEMENTS view the
+    /// balance queries read.
+    async fn create_transaction_tables(pool: &SqlitePool) {
+        for ddl in [
+            sql_queries::TEST_TRANSACTION_CREATE_HEADER_TABLE,
+            sql_queries::TEST_TRANSACTION_CREATE_DETAIL_TABLE,
+            sql_queries::CREATE_TRANSACTION_MOVEMENTS_VIEW,
+        ] {
+            sqlx::query(ddl).execute(pool).await.unwrap();
+        }
+    }
+
     async fn add_test_account(pool: &SqlitePool, code: &str, initial_balance: i64) {
         let request = AddAccountRequest {
             account_code: code.to_string(),
@@ -815,10 +1125,7 @@ mod tests {
     #[tokio::test]
     async fn test_get_account_balances_as_of_applies_transaction_signs() {
         let pool = setup_test_db().await;
-        sqlx::query(sql_queries::TEST_TRANSACTION_CREATE_HEADER_TABLE)
-            .execute(&pool)
-            .await
-            .unwrap();
+        create_transaction_tables(&pool).await;
         add_test_account(&pool, "CASH", 1000).await;
         add_test_account(&pool, "BANK", 5000).await;
 
@@ -830,8 +1137,8 @@ mod tests {
             .await
             .unwrap();
 
-        let cash = balances.iter().find(|b| b.account_code == "CASH").unwrap();
-        let bank = balances.iter().find(|b| b.account_code == "BANK").unwrap();
+        let cash = balances.find("CASH").unwrap();
+        let bank = balances.find("BANK").unwrap();
         assert_eq!(cash.balance, 1000 + 300 - 100 + 2000);
         assert_eq!(bank.balance, 5000 - 2000);
     }
@@ -839,10 +1146,7 @@ mod tests {
     #[tokio::test]
     async fn test_get_account_balances_as_of_ignores_future_and_scheduled() {
         let pool = setup_test_db().await;
-        sqlx::query(sql_queries::TEST_TRANSACTION_CREATE_HEADER_TABLE)
-            .execute(&pool)
-            .await
-            .unwrap();
+        create_transaction_tables(&pool).await;
         add_test_account(&pool, "CASH", 1000).await;
 
         insert_header(&pool, "INCOME", "NONE", "CASH", "2026-01-05", 300, 0).await;
@@ -855,17 +1159,14 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(balances.len(), 1);
-        assert_eq!(balances[0].balance, 1300);
+        assert_eq!(balances.assets.len(), 1);
+        assert_eq!(balances.assets[0].balance, 1300);
     }
 
     #[tokio::test]
     async fn test_get_account_balances_as_of_excludes_disabled_accounts() {
         let pool = setup_test_db().await;
-        sqlx::query(sql_queries::TEST_TRANSACTION_CREATE_HEADER_TABLE)
-            .execute(&pool)
-            .await
-            .unwrap();
+        create_transaction_tables(&pool).await;
         add_test_account(&pool, "CASH", 1000).await;
         delete_account(&pool, 2, "CASH").await.unwrap();
 
@@ -873,7 +1174,126 @@ mod tests {
             .await
             .unwrap();
 
-        assert!(balances.is_empty());
+        assert!(balances.assets.is_empty());
+        assert!(balances.liabilities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_account_balances_as_of_reports_liabilities_as_owed() {
+        let pool = setup_ledger_db().await;
+        sqlx::query(sql_queries::TEST_ACCOUNT_INSERT_CREDIT_TEMPLATE)
+            .execute(&pool)
+            .await
+            .unwrap();
+        add_test_account(&pool, "BANK", 5000).await;
+        add_account(
+            &pool,
+            2,
+            AddAccountRequest {
+                account_code: "CARD".to_string(),
+                account_name: "Card".to_string(),
+                template_code: "CREDIT".to_string(),
+                initial_balance: 500,
+            },
+        )
+        .await
+        .unwrap();
+
+        insert_header(&pool, "EXPENSE", "CARD", "NONE", "2026-01-05", 3000, 0).await;
+        // Paying the bill: money leaves the bank and the debt shrinks
+        insert_header(&pool, "TRANSFER", "BANK", "CARD", "2026-01-27", 2000, 0).await;
+
+        let balances = get_account_balances_as_of(&pool, 2, "2026-01-31").await.unwrap();
+
+        assert_eq!(balances.liabilities.len(), 1);
+        assert_eq!(balances.liabilities[0].account_code, "CARD");
+        assert_eq!(balances.assets[0].account_class, ACCOUNT_CLASS_ASSET);
+        assert_eq!(balances.liabilities[0].balance, 500 + 3000 - 2000);
+        assert_eq!(balances.total_assets, 3000);
+        assert_eq!(balances.total_liabilities, 1500);
+        assert_eq!(balances.net_worth, 1500);
+
+        let ledger = get_account_ledger(&pool, 2, "CARD", None, None).await.unwrap();
+        let amounts: Vec<i64> = ledger.entries.iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, vec![3000, -2000]);
+        assert_eq!(ledger.closing_balance, balances.liabilities[0].balance);
+    }
+
+    #[tokio::test]
+    async fn test_get_account_balances_as_of_converts_totals_to_base_currency() {
+        let pool = setup_test_db().await;
+        create_transaction_tables(&pool).await;
+        add_test_account(&pool, "BANK", 5000).await;
+        add_test_account(&pool, "USBANK", 10_000).await;
+        add_test_account(&pool, "EUBANK", 100).await;
+        set_account_currency(&pool, 2, "usbank", "usd").await.unwrap();
+        set_account_currency(&pool, 2, "EUBANK", "EUR").await.unwrap();
+        // $12.34 spent from the USD account
+        insert_header(&pool, "EXPENSE", "USBANK", "NONE", "2026-01-05", 1234, 0).await;
+        currency::set_exchange_rate(
+            &pool,
+            2,
+            currency::SetExchangeRateRequest {
+                currency_code: "USD".to_string(),
+                rate_date: "2026-01-01".to_string(),
+                rate: 150.0,
+            },
+        )
+        .await
+        .unwrap();
+
+        let balances = get_account_balances_as_of(&pool, 2, "2026-01-31").await.unwrap();
+
+        let usd = balances.find("USBANK").unwrap();
+        assert_eq!(usd.currency_code, "USD");
+        assert_eq!(usd.balance, 10_000 - 1234);
+        // 87.66 USD at 150 yen
+        assert_eq!(usd.base_balance, Some(13_149));
+        // No EUR rate on file: shown natively, left out of the totals
+        assert_eq!(balances.find("EUBANK").unwrap().base_balance, None);
+        assert_eq!(balances.base_currency, "JPY");
+        assert_eq!(balances.total_assets, 5000 + 13_149);
+
+        // Booked accounts keep their currency
+        let err = set_account_currency(&pool, 2, "USBANK", "JPY").await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+    }
+
+    #[tokio::test]
+    async fn test_split_header_books_each_leg_on_its_own_accounts() {
+        let pool = setup_ledger_db().await;
+        add_test_account(&pool, "CASH", 10_000).await;
+        add_test_account(&pool, "CARD", 0).await;
+        add_test_account(&pool, "SAVINGS", 0).await;
+
+        // One receipt paid 1,000 in cash and 2,500 by card, plus 3,000 of
+        // the same trip moved from the wallet into savings.
+        insert_header(&pool, "EXPENSE", "CASH", "NONE", "2026-01-05", 6500, 0).await;
+        for (category1, item, amount, from, to) in [
+            ("EXPENSE", "Groceries", 1000, None, None),
+            ("EXPENSE", "Household", 2500, Some("CARD"), Some("NONE")),
+            ("TRANSFER", "Savings", 3000, Some("CASH"), Some("SAVINGS")),
+        ] {
+            sqlx::query(sql_queries::TEST_ACCOUNT_INSERT_DETAIL_LINE)
+                .bind(category1)
+                .bind(item)
+                .bind(amount)
+                .bind(from)
+                .bind(to)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let balances = get_account_balances_as_of(&pool, 2, "2026-01-31").await.unwrap();
+        assert_eq!(balances.find("CASH").unwrap().balance, 10_000 - 1000 - 3000);
+        assert_eq!(balances.find("CARD").unwrap().balance, -2500);
+        assert_eq!(balances.find("SAVINGS").unwrap().balance, 3000);
+
+        let ledger = get_account_ledger(&pool, 2, "CASH", None, None).await.unwrap();
+        let amounts: Vec<i64> = ledger.entries.iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, vec![-1000, -3000]);
+        assert_eq!(ledger.closing_balance, 6000);
     }
 
     #[tokio::test]
@@ -886,4 +1306,82 @@ mod tests {
         assert!(err.contains("NONE template"), "unexpected error: {}", err);
         assert!(get_accounts(&pool, 2).await.unwrap().is_empty());
     }
+
+    async fn setup_ledger_db() -> SqlitePool {
+        let pool = setup_test_db().await;
+        create_transaction_tables(&pool).await;
+        for ddl in [
+            sql_queries::TEST_TRANSACTION_CREATE_SHOPS_TABLE,
+            sql_queries::TEST_TRANSACTION_CREATE_MEMOS_TABLE,
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[test]
+    fn test_signed_amount_follows_balance_rules() {
+        assert_eq!(signed_amount("INCOME", "NONE", "CASH", "CASH", 300), 300);
+        assert_eq!(signed_amount("EXPENSE", "CASH", "NONE", "CASH", 100), -100);
+        assert_eq!(signed_amount("TRANSFER", "BANK", "CASH", "CASH", 50), 50);
+        assert_eq!(signed_amount("TRANSFER", "BANK", "CASH", "BANK", 50), -50);
+        // INCOME paid out of the account does not reduce it
+        assert_eq!(signed_amount("INCOME", "CASH", "NONE", "CASH", 300), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_account_ledger_running_balance_and_counterpart() {
+        let pool = setup_ledger_db().await;
+        add_test_account(&pool, "CASH", 1000).await;
+        add_test_account(&pool, "BANK", 5000).await;
+
+        insert_header(&pool, "TRANSFER", "BANK", "CASH", "2026-01-07", 2000, 0).await;
+        insert_header(&pool, "INCOME", "NONE", "CASH", "2026-01-05", 300, 0).await;
+        insert_header(&pool, "EXPENSE", "CASH", "NONE", "2026-01-06", 100, 0).await;
+        insert_header(&pool, "EXPENSE", "BANK", "NONE", "2026-01-06", 999, 0).await;
+        insert_header(&pool, "EXPENSE", "CASH", "NONE", "2026-01-08", 400, 1).await;
+
+        let ledger = get_account_ledger(&pool, 2, "cash", None, None).await.unwrap();
+
+        let amounts: Vec<i64> = ledger.entries.iter().map(|e| e.amount).collect();
+        let balances: Vec<i64> = ledger.entries.iter().map(|e| e.balance).collect();
+        assert_eq!(amounts, vec![300, -100, 2000]);
+        assert_eq!(balances, vec![1300, 1200, 3200]);
+        assert_eq!(ledger.opening_balance, 1000);
+        assert_eq!(ledger.closing_balance, 3200);
+        assert_eq!(ledger.entries[2].counterpart_account_code, "BANK");
+        assert_eq!(ledger.entries[2].counterpart_account_name.as_deref(), Some("BANK account"));
+
+        let as_of = get_account_balances_as_of(&pool, 2, "2026-12-31").await.unwrap();
+        let cash = as_of.find("CASH").unwrap();
+        assert_eq!(cash.balance, ledger.closing_balance);
+    }
+
+    #[tokio::test]
+    async fn test_get_account_ledger_date_range_folds_opening_balance() {
+        let pool = setup_ledger_db().await;
+        add_test_account(&pool, "CASH", 1000).await;
+
+        insert_header(&pool, "INCOME", "NONE", "CASH", "2026-01-05", 300, 0).await;
+        insert_header(&pool, "EXPENSE", "CASH", "NONE", "2026-02-03", 100, 0).await;
+        insert_header(&pool, "EXPENSE", "CASH", "NONE", "2026-03-01", 50, 0).await;
+
+        let ledger = get_account_ledger(&pool, 2, "CASH", Some("2026-02-01"), Some("2026-02-28"))
+            .await
+            .unwrap();
+
+        assert_eq!(ledger.opening_balance, 1300);
+        assert_eq!(ledger.entries.len(), 1);
+        assert_eq!(ledger.entries[0].balance, 1200);
+        assert_eq!(ledger.closing_balance, 1200);
+    }
+
+    #[tokio::test]
+    async fn test_get_account_ledger_rejects_unknown_account() {
+        let pool = setup_ledger_db().await;
+
+        let err = get_account_ledger(&pool, 2, "MISSING", None, None).await.unwrap_err();
+
+        assert_eq!(err.code, ApiError::CODE_NOT_FOUND);
+    }
 }
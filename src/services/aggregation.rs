@@ -7,6 +7,7 @@ use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 
+use crate::services::currency;
 use crate::services::period::{monthly_period_bounds, yearly_period_bounds};
 
 // =============================================================================
@@ -260,6 +261,8 @@ pub enum GroupBy {
     Product,
     /// Group by date
     Date,
+    /// Group by tag
+    Tag,
 }
 
 impl GroupBy {
@@ -292,11 +295,13 @@ impl GroupBy {
             GroupBy::Category2
             | GroupBy::Category3
             | GroupBy::Product
-            | GroupBy::Account => unreachable!(
+            | GroupBy::Account
+            | GroupBy::Tag => unreachable!(
                 "to_select_clause is only called from build_header_query \
                  (Category1/Shop/Date); {:?} must go through its dedicated \
                  builder — build_detail_query for Category2/3/Product, \
-                 build_account_aggregation_query for Account.",
+                 build_account_aggregation_query for Account, \
+                 build_tag_query for Tag.",
                 self
             ),
         }
@@ -313,7 +318,8 @@ impl GroupBy {
             GroupBy::Category2
             | GroupBy::Category3
             | GroupBy::Product
-            | GroupBy::Account => unreachable!(
+            | GroupBy::Account
+            | GroupBy::Tag => unreachable!(
                 "to_group_by_clause is only called from build_header_query \
                  (Category1/Shop/Date); {:?} must go through its dedicated \
                  builder.",
@@ -735,11 +741,14 @@ pub fn build_where_clause(user_id: i64, filter: &AggregationFilter) -> (String,
 ///   `TRANSACTIONS_DETAIL`, so the query has to walk the detail rows. These go
 ///   through `build_detail_query`, which protects against the row-multiplication
 ///   that comes from joining detail and naively summing `th.TOTAL_AMOUNT`.
+/// - `Tag` can attach to a header or to individual detail lines, so it needs
+///   both shapes at once. Handled by `build_tag_query`.
 /// - Everything else (`Category1`, `Shop`, `Date`) groups on header columns and
 ///   never joins detail, so the simpler `build_header_query` shape is correct.
 pub fn build_query(request: &AggregationRequest, lang: &str) -> (String, Vec<BindValue>) {
     match request.group_by {
         GroupBy::Account => build_account_aggregation_query(request),
+        GroupBy::Tag => build_tag_query(request),
         GroupBy::Category2 | GroupBy::Category3 | GroupBy::Product => {
             build_detail_query(request, lang)
         }
@@ -750,7 +759,11 @@ pub fn build_query(request: &AggregationRequest, lang: &str) -> (String, Vec<Bin
 }
 
 /// Build the aggregation query for header-level groupings (`Category1`, `Shop`,
-/// `Date`). Sums `th.TOTAL_AMOUNT` directly because no detail join takes place.
+/// `Date`). Sums `th.TOTAL_AMOUNT` directly because no detail join takes place,
+/// converted into the base currency (headers without a rate drop out of the
+/// sum but are still counted). Reads `TRANSACTION_MOVEMENTS`, so each leg of a
+/// split header counts under its own category; `count` and `avg_amount` are
+/// still per transaction.
 /// Bind order: joins-binds (lang) → where-binds (user_id, date, [amount], [category], [shop_id]) → [limit].
 fn build_header_query(request: &AggregationRequest, lang: &str) -> (String, Vec<BindValue>) {
     let select_clause = request.group_by.to_select_clause();
@@ -760,29 +773,30 @@ fn build_header_query(request: &AggregationRequest, lang: &str) -> (String, Vec<
     let sort_order = request.sort_order.to_sql();
 
     let (joins, join_binds) = build_join_clauses(&request.group_by, request.user_id, lang);
+    let amount = currency::base_amount_sql("th.TOTAL_AMOUNT", "th");
 
     let mut sql = format!(
         r#"
 SELECT
     {},
-    SUM(
+    COALESCE(SUM(
         CASE
-            WHEN th.CATEGORY1_CODE = 'EXPENSE' THEN -th.TOTAL_AMOUNT
-            WHEN th.CATEGORY1_CODE = 'INCOME' THEN th.TOTAL_AMOUNT
+            WHEN th.CATEGORY1_CODE = 'EXPENSE' THEN -{amount}
+            WHEN th.CATEGORY1_CODE = 'INCOME' THEN {amount}
             WHEN th.CATEGORY1_CODE = 'TRANSFER' THEN 0
-            ELSE th.TOTAL_AMOUNT
+            ELSE {amount}
         END
-    ) as total_amount,
-    COUNT(*) as count,
-    CAST(AVG(
+    ), 0) as total_amount,
+    COUNT(DISTINCT th.TRANSACTION_ID) as count,
+    COALESCE(CAST(SUM(
         CASE
-            WHEN th.CATEGORY1_CODE = 'EXPENSE' THEN -th.TOTAL_AMOUNT
-            WHEN th.CATEGORY1_CODE = 'INCOME' THEN th.TOTAL_AMOUNT
+            WHEN th.CATEGORY1_CODE = 'EXPENSE' THEN -{amount}
+            WHEN th.CATEGORY1_CODE = 'INCOME' THEN {amount}
             WHEN th.CATEGORY1_CODE = 'TRANSFER' THEN 0
-            ELSE th.TOTAL_AMOUNT
+            ELSE {amount}
         END
-    ) AS INTEGER) as avg_amount
-FROM TRANSACTIONS_HEADER th
+    ) * 1.0 / COUNT(DISTINCT th.TRANSACTION_ID) AS INTEGER), 0) as avg_amount
+FROM TRANSACTION_MOVEMENTS th
 {}
 WHERE {}
 GROUP BY {}
@@ -816,8 +830,9 @@ ORDER BY {} {}
 /// 2. **Middle** — applies the transaction's `TAX_ROUNDING_TYPE` exactly once
 ///    per `(transaction, group, tax_rate)` slice: the pre-tax sum is grossed
 ///    up by `(100 + tax_rate)/100` and rounded according to the chosen mode,
-///    then added to the already-included sum, then signed by `CATEGORY1_CODE`.
-///    The output is an integer `signed_amount` per slice.
+///    then added to the already-included sum, then signed by `CATEGORY1_CODE`
+///    and converted into the base currency at the header's date. The output
+///    is an integer `signed_amount` per slice (NULL when no rate exists).
 /// 3. **Outer** — groups the integer slices by `(group_key, group_name)` and
 ///    sums them. No further rounding happens here, so cross-transaction
 ///    aggregation is exact.
@@ -838,13 +853,14 @@ fn build_detail_query(request: &AggregationRequest, lang: &str) -> (String, Vec<
     let sort_order = request.sort_order.to_sql();
     let (group_key_expr, group_name_expr, joins, join_binds) =
         build_detail_group_pieces(&request.group_by, lang);
+    let signed_amount = currency::base_amount_sql(DETAIL_SLICE_AMOUNT_SQL, "agg");
 
     let mut sql = format!(
         r#"
 SELECT
     sub.group_key,
     sub.group_name,
-    SUM(sub.signed_amount) AS total_amount,
+    COALESCE(SUM(sub.signed_amount), 0) AS total_amount,
     COUNT(DISTINCT sub.txn_id) AS count,
     -- Derive avg_amount from total / txn_count so `avg × count == total`
     -- always holds. `AVG(sub.signed_amount)` averages over `sub` rows, which
@@ -854,45 +870,23 @@ SELECT
     -- The multiplication by 1.0 promotes the numerator to REAL before the
     -- division, preserving the truncate-toward-zero semantics of the prior
     -- `CAST(AVG(...) AS INTEGER)` shape.
-    CAST(SUM(sub.signed_amount) * 1.0 / COUNT(DISTINCT sub.txn_id) AS INTEGER) AS avg_amount
+    COALESCE(CAST(SUM(sub.signed_amount) * 1.0 / COUNT(DISTINCT sub.txn_id) AS INTEGER), 0) AS avg_amount
 FROM (
     SELECT
         agg.txn_id,
         agg.group_key,
         agg.group_name,
-        CASE agg.cat1
-            WHEN 'EXPENSE' THEN -1
-            WHEN 'TRANSFER' THEN 0
-            ELSE 1
-        END
-        * (
-            agg.already_included_sum
-            + CASE agg.rounding_type
-                -- floor: integer division on positive integers truncates
-                -- towards zero, which equals floor when the operands are
-                -- positive (which they are: pretax_sum and (100 + rate)
-                -- are both non-negative).
-                WHEN 0 THEN agg.pretax_sum * (100 + agg.tax_rate) / 100
-                -- half-away-from-zero: lift to REAL via 100.0 / 100.0 so
-                -- ROUND() can see the fractional part.
-                WHEN 1 THEN CAST(ROUND(agg.pretax_sum * (100.0 + agg.tax_rate) / 100.0) AS INTEGER)
-                -- ceil for positive integers: (n + 99) / 100 with integer
-                -- division. The historical `-CAST(-n / 100 AS INTEGER)`
-                -- idiom looks plausible but is wrong here — SQLite's
-                -- integer division truncates towards zero, which on the
-                -- negated operand acts as ceil, so the double-negation
-                -- collapses back to floor and shaves off the very 1-yen
-                -- that ceil was supposed to add. Mirrors the Rust port.
-                WHEN 2 THEN (agg.pretax_sum * (100 + agg.tax_rate) + 99) / 100
-                ELSE agg.pretax_sum * (100 + agg.tax_rate) / 100
-            END
-        ) AS signed_amount
+        {signed_amount} AS signed_amount
     FROM (
         SELECT
             th.TRANSACTION_ID AS txn_id,
+            th.USER_ID,
+            th.CURRENCY_CODE,
+            th.TRANSACTION_DATE,
             {gk} AS group_key,
             {gn} AS group_name,
-            th.CATEGORY1_CODE AS cat1,
+            CASE WHEN td.FROM_ACCOUNT_CODE IS NULL THEN th.CATEGORY1_CODE
+                 ELSE td.CATEGORY1_CODE END AS cat1,
             td.TAX_RATE AS tax_rate,
             th.TAX_ROUNDING_TYPE AS rounding_type,
             SUM(CASE
@@ -912,7 +906,7 @@ FROM (
             ON th.USER_ID = td.USER_ID AND th.TRANSACTION_ID = td.TRANSACTION_ID
         {joins}
         WHERE {where_clause}
-        GROUP BY th.TRANSACTION_ID, {gk}, {gn}, td.TAX_RATE, th.TAX_ROUNDING_TYPE, th.CATEGORY1_CODE
+        GROUP BY th.TRANSACTION_ID, {gk}, {gn}, td.TAX_RATE, th.TAX_ROUNDING_TYPE, cat1
     ) agg
 ) sub
 GROUP BY sub.group_key, sub.group_name
@@ -920,6 +914,7 @@ ORDER BY {order_field} {sort_order}
 "#,
         gk = group_key_expr,
         gn = group_name_expr,
+        signed_amount = signed_amount,
         joins = joins,
         where_clause = where_clause,
         order_field = order_field,
@@ -939,6 +934,36 @@ ORDER BY {order_field} {sort_order}
     (sql, binds)
 }
 
+/// Signed, tax-applied amount of one detail slice (`agg` row) in the
+/// header's currency; the middle layer of `build_detail_query`.
+const DETAIL_SLICE_AMOUNT_SQL: &str = r#"(CASE agg.cat1
+            WHEN 'EXPENSE' THEN -1
+            WHEN 'TRANSFER' THEN 0
+            ELSE 1
+        END
+        * (
+            agg.already_included_sum
+            + CASE agg.rounding_type
+                -- floor: integer division on positive integers truncates
+                -- towards zero, which equals floor when the operands are
+                -- positive (which they are: pretax_sum and (100 + rate)
+                -- are both non-negative).
+                WHEN 0 THEN agg.pretax_sum * (100 + agg.tax_rate) / 100
+                -- half-away-from-zero: lift to REAL via 100.0 / 100.0 so
+                -- ROUND() can see the fractional part.
+                WHEN 1 THEN CAST(ROUND(agg.pretax_sum * (100.0 + agg.tax_rate) / 100.0) AS INTEGER)
+                -- ceil for positive integers: (n + 99) / 100 with integer
+                -- division. The historical `-CAST(-n / 100 AS INTEGER)`
+                -- idiom looks plausible but is wrong here — SQLite's
+                -- integer division truncates towards zero, which on the
+                -- negated operand acts as ceil, so the double-negation
+                -- collapses back to floor and shaves off the very 1-yen
+                -- that ceil was supposed to add. Mirrors the Rust port.
+                WHEN 2 THEN (agg.pretax_sum * (100 + agg.tax_rate) + 99) / 100
+                ELSE agg.pretax_sum * (100 + agg.tax_rate) / 100
+            END
+        ))"#;
+
 /// Resolve the per-grouping pieces that the detail-level subquery needs:
 /// the SQL expression that produces the group key, the expression for the
 /// human-readable name, and the JOIN clauses for the ancillary tables that
@@ -994,12 +1019,118 @@ fn build_detail_group_pieces(
     }
 }
 
+/// Build the aggregation query for `GroupBy::Tag`.
+///
+/// A tag on a header covers the whole transaction, so that branch sums
+/// `TRANSACTION_MOVEMENTS` like `build_header_query`. A tag on a detail line
+/// covers only that line, so the second branch reuses the per-slice tax
+/// rounding of `build_detail_query`. A detail line whose header already
+/// carries the same tag is skipped there, so tagging both never counts the
+/// line twice. Amounts are converted into the base currency in both branches.
+/// Bind order: where-binds (header branch) → where-binds (detail branch) → [limit].
+fn build_tag_query(request: &AggregationRequest) -> (String, Vec<BindValue>) {
+    let (where_clause, where_binds) = build_where_clause(request.user_id, &request.filter);
+    let order_field = request.order_by.to_order_by_field();
+    let sort_order = request.sort_order.to_sql();
+    let header_amount = currency::base_amount_sql("th.TOTAL_AMOUNT", "th");
+    let slice_amount = currency::base_amount_sql(DETAIL_SLICE_AMOUNT_SQL, "agg");
+
+    let mut sql = format!(
+        r#"
+SELECT
+    CAST(tg.TAG_ID AS TEXT) AS group_key,
+    tg.TAG_NAME AS group_name,
+    COALESCE(SUM(sub.signed_amount), 0) AS total_amount,
+    COUNT(DISTINCT sub.txn_id) AS count,
+    COALESCE(CAST(SUM(sub.signed_amount) * 1.0 / COUNT(DISTINCT sub.txn_id) AS INTEGER), 0) AS avg_amount
+FROM (
+    -- Header-level tags: the whole transaction (each split leg signed on its own)
+    SELECT
+        tt.TAG_ID AS tag_id,
+        th.TRANSACTION_ID AS txn_id,
+        CASE
+            WHEN th.CATEGORY1_CODE = 'EXPENSE' THEN -{header_amount}
+            WHEN th.CATEGORY1_CODE = 'TRANSFER' THEN 0
+            ELSE {header_amount}
+        END AS signed_amount
+    FROM TRANSACTION_MOVEMENTS th
+    INNER JOIN TRANSACTION_TAGS tt
+        ON tt.USER_ID = th.USER_ID AND tt.TRANSACTION_ID = th.TRANSACTION_ID
+       AND tt.DETAIL_ID IS NULL
+    WHERE {where_clause}
+
+    UNION ALL
+
+    -- Detail-level tags: only the tagged lines
+    SELECT
+        agg.tag_id,
+        agg.txn_id,
+        {slice_amount} AS signed_amount
+    FROM (
+        SELECT
+            tt.TAG_ID AS tag_id,
+            th.TRANSACTION_ID AS txn_id,
+            th.USER_ID,
+            th.CURRENCY_CODE,
+            th.TRANSACTION_DATE,
+            CASE WHEN td.FROM_ACCOUNT_CODE IS NULL THEN th.CATEGORY1_CODE
+                 ELSE td.CATEGORY1_CODE END AS cat1,
+            td.TAX_RATE AS tax_rate,
+            th.TAX_ROUNDING_TYPE AS rounding_type,
+            SUM(CASE
+                WHEN td.TAX_RATE = 0
+                  OR (td.AMOUNT_INCLUDING_TAX IS NOT NULL
+                      AND td.AMOUNT = td.AMOUNT_INCLUDING_TAX)
+                THEN td.AMOUNT ELSE 0
+            END) AS already_included_sum,
+            SUM(CASE
+                WHEN td.TAX_RATE > 0
+                  AND (td.AMOUNT_INCLUDING_TAX IS NULL
+                       OR td.AMOUNT != td.AMOUNT_INCLUDING_TAX)
+                THEN td.AMOUNT ELSE 0
+            END) AS pretax_sum
+        FROM TRANSACTIONS_HEADER th
+        INNER JOIN TRANSACTIONS_DETAIL td
+            ON th.USER_ID = td.USER_ID AND th.TRANSACTION_ID = td.TRANSACTION_ID
+        INNER JOIN TRANSACTION_TAGS tt
+            ON tt.USER_ID = td.USER_ID AND tt.TRANSACTION_ID = td.TRANSACTION_ID
+           AND tt.DETAIL_ID = td.DETAIL_ID
+        WHERE {where_clause}
+          AND NOT EXISTS (
+              SELECT 1 FROM TRANSACTION_TAGS tth
+              WHERE tth.USER_ID = th.USER_ID AND tth.TRANSACTION_ID = th.TRANSACTION_ID
+                AND tth.DETAIL_ID IS NULL AND tth.TAG_ID = tt.TAG_ID
+          )
+        GROUP BY tt.TAG_ID, th.TRANSACTION_ID, td.TAX_RATE, th.TAX_ROUNDING_TYPE, cat1
+    ) agg
+) sub
+INNER JOIN TAGS tg ON tg.TAG_ID = sub.tag_id
+GROUP BY tg.TAG_ID, tg.TAG_NAME
+ORDER BY {order_field} {sort_order}
+"#
+    );
+
+    let mut binds = where_binds.clone();
+    binds.extend(where_binds);
+
+    if let Some(limit) = request.limit {
+        sql.push_str("LIMIT ?");
+        binds.push(BindValue::I64(limit as i64));
+    }
+
+    (sql, binds)
+}
+
 /// Build account aggregation query using UNION ALL approach
 ///
 /// Account aggregation requires special handling because:
 /// - EXPENSE: uses FROM_ACCOUNT_CODE (money flows out)
 /// - INCOME: uses TO_ACCOUNT_CODE (money flows in)
 /// - TRANSFER: creates two records - FROM_ACCOUNT (outflow) and TO_ACCOUNT (inflow)
+///
+/// Amounts are converted into the base currency like the other shapes. The
+/// branches read `TRANSACTION_MOVEMENTS`, so a split header is attributed to
+/// the accounts each of its legs actually moved.
 fn build_account_aggregation_query(request: &AggregationRequest) -> (String, Vec<BindValue>) {
     let user_id = request.user_id;
     let (date_sql, date_binds) = request.filter.date.to_sql_parts();
@@ -1051,39 +1182,41 @@ fn build_account_aggregation_query(request: &AggregationRequest) -> (String, Vec
         format!(" AND {}", additional_conditions.join(" AND "))
     };
 
+    let amount = currency::base_amount_sql("th.TOTAL_AMOUNT", "th");
+
     let mut sql = format!(
         r#"
 SELECT
     account_data.account_code as group_key,
     COALESCE(a.ACCOUNT_NAME, '指定なし') as group_name,
-    SUM(account_data.amount) as total_amount,
+    COALESCE(SUM(account_data.amount), 0) as total_amount,
     COUNT(*) as count,
-    CAST(AVG(account_data.amount) AS INTEGER) as avg_amount
+    COALESCE(CAST(AVG(account_data.amount) AS INTEGER), 0) as avg_amount
 FROM (
     -- EXPENSE: FROM_ACCOUNT loses money (negative amount)
-    SELECT th.FROM_ACCOUNT_CODE as account_code, -th.TOTAL_AMOUNT as amount
-    FROM TRANSACTIONS_HEADER th
+    SELECT th.FROM_ACCOUNT_CODE as account_code, -{amount} as amount
+    FROM TRANSACTION_MOVEMENTS th
     WHERE th.USER_ID = ? AND th.CATEGORY1_CODE = 'EXPENSE' AND {date_sql}{additional_where}
 
     UNION ALL
 
     -- INCOME: TO_ACCOUNT gains money (positive amount)
-    SELECT th.TO_ACCOUNT_CODE as account_code, th.TOTAL_AMOUNT as amount
-    FROM TRANSACTIONS_HEADER th
+    SELECT th.TO_ACCOUNT_CODE as account_code, {amount} as amount
+    FROM TRANSACTION_MOVEMENTS th
     WHERE th.USER_ID = ? AND th.CATEGORY1_CODE = 'INCOME' AND {date_sql}{additional_where}
 
     UNION ALL
 
     -- TRANSFER FROM: FROM_ACCOUNT loses money (negative amount)
-    SELECT th.FROM_ACCOUNT_CODE as account_code, -th.TOTAL_AMOUNT as amount
-    FROM TRANSACTIONS_HEADER th
+    SELECT th.FROM_ACCOUNT_CODE as account_code, -{amount} as amount
+    FROM TRANSACTION_MOVEMENTS th
     WHERE th.USER_ID = ? AND th.CATEGORY1_CODE = 'TRANSFER' AND {date_sql}{additional_where}
 
     UNION ALL
 
     -- TRANSFER TO: TO_ACCOUNT gains money (positive amount)
-    SELECT th.TO_ACCOUNT_CODE as account_code, th.TOTAL_AMOUNT as amount
-    FROM TRANSACTIONS_HEADER th
+    SELECT th.TO_ACCOUNT_CODE as account_code, {amount} as amount
+    FROM TRANSACTION_MOVEMENTS th
     WHERE th.USER_ID = ? AND th.CATEGORY1_CODE = 'TRANSFER' AND {date_sql}{additional_where}
 ) AS account_data
 LEFT JOIN ACCOUNTS a ON a.USER_ID = ? AND a.ACCOUNT_CODE = account_data.account_code
@@ -1140,11 +1273,13 @@ LEFT JOIN SHOPS s ON th.USER_ID = s.USER_ID AND th.SHOP_ID = s.SHOP_ID
         GroupBy::Category2
         | GroupBy::Category3
         | GroupBy::Product
-        | GroupBy::Account => unreachable!(
+        | GroupBy::Account
+        | GroupBy::Tag => unreachable!(
             "build_join_clauses is only called from build_header_query \
              (Category1/Shop/Date); {:?} must go through its dedicated \
              builder — build_detail_query for Category2/3/Product, \
-             build_account_aggregation_query for Account.",
+             build_account_aggregation_query for Account, \
+             build_tag_query for Tag.",
             group_by
         ),
     }
@@ -1422,6 +1557,25 @@ fn calculate_week_range(
     Ok((start_date, end_date))
 }
 
+/// Start and end (inclusive) of the week containing `reference_date`.
+/// Shared with the balance history series so both agree on week edges.
+pub fn week_bounds_containing(reference_date: NaiveDate, week_start: WeekStart) -> (NaiveDate, NaiveDate) {
+    use chrono::Weekday;
+
+    let target_weekday = match week_start {
+        WeekStart::Sunday => Weekday::Sun,
+        WeekStart::Monday => Weekday::Mon,
+    };
+
+    let current_weekday = reference_date.weekday();
+    let days_from_start = (7 + current_weekday.num_days_from_monday() as i32
+        - target_weekday.num_days_from_monday() as i32) % 7;
+
+    let start_date = reference_date - chrono::Duration::days(days_from_start as i64);
+    let end_date = start_date + chrono::Duration::days(6);
+    (start_date, end_date)
+}
+
 /// Weekly aggregation by reference date
 ///
 /// Creates an aggregation request for the week containing the reference date.
@@ -1451,21 +1605,8 @@ pub fn weekly_aggregation_by_date(
     week_start: WeekStart,
     group_by: GroupBy,
 ) -> Result<AggregationRequest, AggregationError> {
-    use chrono::Weekday;
-    
-    // Calculate the start of the week containing reference_date
-    let target_weekday = match week_start {
-        WeekStart::Sunday => Weekday::Sun,
-        WeekStart::Monday => Weekday::Mon,
-    };
-    
-    let current_weekday = reference_date.weekday();
-    let days_from_start = (7 + current_weekday.num_days_from_monday() as i32
-        - target_weekday.num_days_from_monday() as i32) % 7;
-    
-    let start_date = reference_date - chrono::Duration::days(days_from_start as i64);
-    let end_date = start_date + chrono::Duration::days(6);
-    
+    let (start_date, end_date) = week_bounds_containing(reference_date, week_start);
+
     // Create filter for week range
     let filter = AggregationFilter::new(DateFilter::Between(start_date, end_date));
     
@@ -1811,7 +1952,7 @@ mod tests {
 
         let (sql, binds) = build_query(&request, "ja");
         assert!(sql.contains("SELECT"));
-        assert!(sql.contains("FROM TRANSACTIONS_HEADER"));
+        assert!(sql.contains("FROM TRANSACTION_MOVEMENTS"));
         assert!(sql.contains("GROUP BY"));
         assert!(sql.contains("ORDER BY"));
         assert!(sql.contains("LIMIT ?"), "LIMIT must now be a placeholder: {}", sql);
@@ -2258,7 +2399,8 @@ mod tests {
                 NAME TEXT NOT NULL UNIQUE,
                 PAW TEXT NOT NULL,
                 ROLE INTEGER NOT NULL,
-                ENTRY_DT TEXT NOT NULL
+                ENTRY_DT TEXT NOT NULL,
+                BASE_CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY'
             )",
             "CREATE TABLE CATEGORY1 (
                 USER_ID INTEGER NOT NULL,
@@ -2294,7 +2436,8 @@ mod tests {
                 TOTAL_AMOUNT INTEGER NOT NULL,
                 TAX_ROUNDING_TYPE INTEGER,
                 TAX_INCLUDED_TYPE INTEGER NOT NULL DEFAULT 1,
-                IS_SCHEDULED INTEGER NOT NULL DEFAULT 0
+                IS_SCHEDULED INTEGER NOT NULL DEFAULT 0,
+                CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY'
             )",
             "CREATE TABLE TRANSACTIONS_DETAIL (
                 USER_ID INTEGER NOT NULL,
@@ -2310,6 +2453,8 @@ mod tests {
                 TAX_AMOUNT INTEGER DEFAULT 0,
                 AMOUNT_INCLUDING_TAX INTEGER,
                 MEMO TEXT,
+                FROM_ACCOUNT_CODE TEXT,
+                TO_ACCOUNT_CODE TEXT,
                 PRIMARY KEY (USER_ID, TRANSACTION_ID, DETAIL_ID)
             )",
         ];
@@ -2317,6 +2462,12 @@ mod tests {
         for stmt in create_stmts {
             sqlx::query(stmt).execute(&pool).await.unwrap();
         }
+        for stmt in [
+            crate::sql_queries::CREATE_EXCHANGE_RATES_TABLE,
+            crate::sql_queries::CREATE_TRANSACTION_MOVEMENTS_VIEW,
+        ] {
+            sqlx::query(stmt).execute(&pool).await.unwrap();
+        }
 
         sqlx::query(
             "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) \
@@ -2740,6 +2891,131 @@ mod tests {
         );
     }
 
+    /// A USD header is summed at the latest rate on or before its date; once
+    /// the rate is removed the header still counts but adds nothing.
+    #[tokio::test]
+    async fn test_queries_convert_foreign_headers_to_base_currency() {
+        let pool = setup_aggregation_test_db().await;
+
+        insert_test_header(&pool, 1, 0, 1, 1000).await;
+        let usd = insert_test_header(&pool, 1, 0, 1, 1250).await;
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET CURRENCY_CODE = 'USD' WHERE TRANSACTION_ID = ?")
+            .bind(usd)
+            .execute(&pool)
+            .await
+            .unwrap();
+        insert_detail(&pool, 1, usd, 1, "FOOD", 1250, 0, Some(1250)).await;
+        // $12.50 at 150 yen/dollar (1.5 yen per cent); the later rate is ignored
+        for (date, minor_rate) in [("2024-06-01", 1.5), ("2024-07-01", 2.0)] {
+            sqlx::query(
+                "INSERT INTO EXCHANGE_RATES \
+                 (USER_ID, CURRENCY_CODE, BASE_CURRENCY_CODE, RATE_DATE, RATE, MINOR_RATE) \
+                 VALUES (1, 'USD', 'JPY', ?, ?, ?)",
+            )
+            .bind(date)
+            .bind(minor_rate * 100.0)
+            .bind(minor_rate)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let run = |group_by: GroupBy| {
+            let pool = pool.clone();
+            async move {
+                let (sql, binds) = build_query(&june_2024_request(group_by), "ja");
+                bind_all(sqlx::query_as::<_, AggregationResult>(&sql), binds)
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let by_date = run(GroupBy::Date).await;
+        assert_eq!(by_date[0].total_amount, -(1000 + 1875));
+        sqlx::query("CREATE TABLE ACCOUNTS (USER_ID INTEGER, ACCOUNT_CODE TEXT, ACCOUNT_NAME TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let by_account = run(GroupBy::Account).await;
+        let cash = by_account.iter().find(|r| r.group_key == "CASH").unwrap();
+        assert_eq!(cash.total_amount, -(1000 + 1875));
+        let by_category2 = run(GroupBy::Category2).await;
+        assert_eq!(by_category2[0].total_amount, -1875);
+
+        sqlx::query("DELETE FROM EXCHANGE_RATES").execute(&pool).await.unwrap();
+        let by_date = run(GroupBy::Date).await;
+        assert_eq!(by_date[0].total_amount, -1000);
+        assert_eq!(by_date[0].count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_queries_follow_split_line_accounts_and_categories() {
+        let pool = setup_aggregation_test_db().await;
+        sqlx::query("CREATE TABLE ACCOUNTS (USER_ID INTEGER, ACCOUNT_CODE TEXT, ACCOUNT_NAME TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // EXPENSE from CASH: 1,000 on the header's accounts, 2,000 of it
+        // paid by CARD, and 500 moved from CASH into SAVINGS.
+        let txn = insert_test_header(&pool, 1, 0, 0, 3500).await;
+        insert_detail(&pool, 1, txn, 1, "FOOD", 1000, 0, None).await;
+        for (detail_id, category1, category2, amount, from, to) in [
+            (2, "EXPENSE", "FOOD", 2000, "CARD", "NONE"),
+            (3, "TRANSFER", "SAVE", 500, "CASH", "SAVINGS"),
+        ] {
+            sqlx::query(
+                "INSERT INTO TRANSACTIONS_DETAIL \
+                 (USER_ID, TRANSACTION_ID, DETAIL_ID, CATEGORY1_CODE, CATEGORY2_CODE, \
+                  ITEM_NAME, AMOUNT, TAX_RATE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE) \
+                 VALUES (1, ?, ?, ?, ?, 'item', ?, 0, ?, ?)",
+            )
+            .bind(txn)
+            .bind(detail_id)
+            .bind(category1)
+            .bind(category2)
+            .bind(amount)
+            .bind(from)
+            .bind(to)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let run = |group_by: GroupBy| {
+            let pool = pool.clone();
+            async move {
+                let (sql, binds) = build_query(&june_2024_request(group_by), "ja");
+                bind_all(sqlx::query_as::<_, AggregationResult>(&sql), binds)
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let by_account = run(GroupBy::Account).await;
+        let total_for = |code: &str| {
+            by_account.iter().find(|r| r.group_key == code).unwrap().total_amount
+        };
+        assert_eq!(total_for("CASH"), -1000 - 500);
+        assert_eq!(total_for("CARD"), -2000);
+        assert_eq!(total_for("SAVINGS"), 500);
+        assert!(by_account.iter().all(|r| r.group_key != "BANK"));
+
+        let by_date = run(GroupBy::Date).await;
+        assert_eq!(by_date[0].total_amount, -3000);
+        assert_eq!(by_date[0].count, 1);
+
+        // The transfer line keeps its own sign rule inside the detail query
+        let by_category2 = run(GroupBy::Category2).await;
+        let total_for = |key: &str| {
+            by_category2.iter().find(|r| r.group_key == key).unwrap().total_amount
+        };
+        assert_eq!(total_for("EXPENSE/FOOD"), -3000);
+        assert_eq!(total_for("TRANSFER/SAVE"), 0);
+    }
+
     // Regression pin for PR6 / Fable-5 #17. Category2 and Category3
     // filters used to reference `th.CATEGORY2_CODE` / `th.CATEGORY3_CODE`
     // — columns that never existed on TRANSACTIONS_HEADER. The corrected
@@ -2814,4 +3090,80 @@ mod tests {
             binds
         );
     }
+
+    /// A detail line tagged with the same tag as its header must not be
+    /// counted on top of the header; a tag present only on a line covers
+    /// only that line.
+    #[tokio::test]
+    async fn test_tag_aggregation_counts_each_line_once() {
+        let pool = setup_aggregation_test_db().await;
+        // The fixture keys TRANSACTIONS_DETAIL on (USER_ID, TRANSACTION_ID,
+        // DETAIL_ID), which the production DETAIL_ID foreign key cannot
+        // target, so the link table is declared without foreign keys here.
+        for stmt in [
+            crate::sql_queries::CREATE_TAGS_TABLE,
+            "CREATE TABLE TRANSACTION_TAGS (
+                TAG_LINK_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+                USER_ID INTEGER NOT NULL,
+                TAG_ID INTEGER NOT NULL,
+                TRANSACTION_ID INTEGER NOT NULL,
+                DETAIL_ID INTEGER
+            )",
+        ] {
+            sqlx::query(stmt).execute(&pool).await.unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO TAGS (TAG_ID, USER_ID, TAG_NAME, ENTRY_DT) \
+             VALUES (1, 1, 'trip', '2024-01-01'), (2, 1, 'gift', '2024-01-01')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // txn1: 1,080 total; header tagged `trip`, its line tagged `trip` + `gift`.
+        let txn1 = insert_test_header(&pool, 1, 0, 1, 1080).await;
+        insert_detail(&pool, 1, txn1, 1, "FOOD", 1000, 8, Some(1080)).await;
+        insert_detail(&pool, 1, txn1, 2, "FOOD", 0, 8, Some(0)).await;
+        // txn2: 1,620 total, but only the 540-yen line is tagged `gift`.
+        let txn2 = insert_test_header(&pool, 1, 0, 1, 1620).await;
+        insert_detail(&pool, 1, txn2, 1, "FOOD", 500, 8, Some(540)).await;
+        insert_detail(&pool, 1, txn2, 2, "FOOD", 1000, 8, Some(1080)).await;
+
+        for (tag_id, txn, detail) in [
+            (1, txn1, None),
+            (1, txn1, Some(1)),
+            (2, txn1, Some(1)),
+            (2, txn2, Some(1)),
+        ] {
+            sqlx::query(
+                "INSERT INTO TRANSACTION_TAGS (USER_ID, TAG_ID, TRANSACTION_ID, DETAIL_ID) \
+                 VALUES (1, ?, ?, ?)",
+            )
+            .bind(tag_id)
+            .bind(txn)
+            .bind(detail)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let request = june_2024_request(GroupBy::Tag);
+        let (sql, binds) = build_query(&request, "ja");
+        let mut results: Vec<AggregationResult> = bind_all(
+            sqlx::query_as::<_, AggregationResult>(&sql),
+            binds,
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        results.sort_by(|a, b| a.group_key.cmp(&b.group_key));
+
+        assert_eq!(results.len(), 2, "{:?}", results);
+        assert_eq!(results[0].group_name, "trip");
+        assert_eq!(results[0].total_amount, -1080, "header and line tag must not double-count");
+        assert_eq!(results[0].count, 1);
+        assert_eq!(results[1].group_name, "gift");
+        assert_eq!(results[1].total_amount, -1620);
+        assert_eq!(results[1].count, 2);
+    }
 }
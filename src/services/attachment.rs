@@ -0,0 +1,518 @@
+//! Receipts and documents attached to transactions.
+//!
+//! Files live in the `ATTACHMENTS` table next to the ledger, so a backup of
+//! the database file carries them along. An attachment belongs to a header
+//! and optionally to one of its detail lines; the foreign keys cascade, so
+//! deleting either removes its files. Content can be encrypted with the key
+//! `derive_encryption_key` derives from the user's password and salt, for
+//! documents such as medical receipts kept for tax filing. `SHA256` is the
+//! digest of the plain file in both cases and is checked again on fetch.
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::crypto::Crypto;
+use crate::security::{derive_encryption_key, verify_password};
+use crate::services::audit::{self, AuditKey};
+use crate::sql_queries;
+
+const ENTITY_LABEL: &str = "attachment";
+
+fn attachment_audit_key(attachment_id: i64) -> AuditKey {
+    AuditKey::new("ATTACHMENTS", "ATTACHMENT_ID", attachment_id)
+}
+
+/// Largest file accepted, before Base64 encoding
+pub const MAX_ATTACHMENT_BYTES: usize = 20 * 1024 * 1024;
+
+const MAX_FILE_NAME_CHARS: usize = 255;
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct Attachment {
+    pub attachment_id: i64,
+    pub transaction_id: i64,
+    pub detail_id: Option<i64>,
+    pub file_name: String,
+    pub mime_type: String,
+    pub byte_size: i64,
+    /// Hex SHA-256 of the plain file
+    pub sha256: String,
+    pub is_encrypted: bool,
+    pub entry_dt: String,
+}
+
+/// An attachment with its (decrypted) file content
+#[derive(Debug, Serialize, Clone)]
+pub struct AttachmentContent {
+    #[serde(flatten)]
+    pub attachment: Attachment,
+    pub content_base64: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AddAttachmentRequest {
+    pub transaction_id: i64,
+    pub detail_id: Option<i64>,
+    pub file_name: String,
+    pub content_base64: String,
+}
+
+/// MIME type of an image or PDF, recognised from the file's leading bytes
+/// rather than its name or what the caller claims it is.
+pub fn detect_mime_type(content: &[u8]) -> Option<&'static str> {
+    if content.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if content.starts_with(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]) {
+        Some("image/png")
+    } else if content.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if content.starts_with(b"GIF87a") || content.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if content.len() >= 12 && &content[..4] == b"RIFF" && &content[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if content.len() >= 12
+        && &content[4..8] == b"ftyp"
+        && matches!(&content[8..12], b"heic" | b"heix" | b"mif1" | b"msf1")
+    {
+        Some("image/heic")
+    } else {
+        None
+    }
+}
+
+pub fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Keep only the last path component of `file_name` so a name picked from a
+/// full path never records the user's directory layout.
+fn normalize_file_name(file_name: &str) -> Result<String, ApiError> {
+    let name = file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if name.is_empty() {
+        return Err(ApiError::validation("Attachment file name cannot be empty"));
+    }
+    if name.chars().count() > MAX_FILE_NAME_CHARS {
+        return Err(ApiError::validation(format!(
+            "Attachment file name must be {} characters or less",
+            MAX_FILE_NAME_CHARS
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Cipher keyed from the user's password. The password is checked against
+/// the stored hash first: encrypting under a mistyped password would lock
+/// the file away for good.
+async fn user_crypto(pool: &SqlitePool, user_id: i64, password: &str) -> Result<Crypto, ApiError> {
+    let hash: String = sqlx::query_scalar(sql_queries::USER_GET_PASSWORD_BY_ID)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found("User"))?;
+    if !verify_password(password, &hash).map_err(|e| ApiError::validation(e.to_string()))? {
+        return Err(ApiError::validation("Password is incorrect"));
+    }
+
+    let salt: Vec<u8> = sqlx::query_scalar::<_, Option<Vec<u8>>>(sql_queries::USER_GET_ENCRYPTION_SALT)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .flatten()
+        .ok_or_else(|| ApiError::validation("Encryption salt is not set for this user"))?;
+    let key = derive_encryption_key(password, &salt)
+        .map_err(|e| ApiError::validation(e.to_string()))?;
+    Ok(Crypto::new(key))
+}
+
+/// Store a file against a header (and optionally one of its lines). With a
+/// `password` the content is encrypted with the user's key.
+pub async fn add_attachment(
+    pool: &SqlitePool,
+    user_id: i64,
+    request: AddAttachmentRequest,
+    password: Option<&str>,
+) -> Result<Attachment, ApiError> {
+    let file_name = normalize_file_name(&request.file_name)?;
+    let content = general_purpose::STANDARD
+        .decode(request.content_base64.trim())
+        .map_err(|e| ApiError::validation(format!("Attachment content is not valid Base64: {}", e)))?;
+    if content.is_empty() {
+        return Err(ApiError::validation("Attachment file is empty"));
+    }
+    if content.len() > MAX_ATTACHMENT_BYTES {
+        return Err(ApiError::validation(format!(
+            "Attachment must be {} MB or smaller",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+    let mime_type = detect_mime_type(&content)
+        .ok_or_else(|| ApiError::validation("Only image and PDF files can be attached"))?;
+
+    let header: Option<i64> = sqlx::query_scalar(sql_queries::TRANSACTION_HEADER_EXISTS_FOR_USER)
+        .bind(request.transaction_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+    if header.is_none() {
+        return Err(ApiError::not_found("Transaction"));
+    }
+    if let Some(detail_id) = request.detail_id {
+        let belongs: i64 = sqlx::query_scalar(sql_queries::TRANSACTION_DETAIL_BELONGS_TO_HEADER)
+            .bind(detail_id)
+            .bind(request.transaction_id)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+        if belongs == 0 {
+            return Err(ApiError::validation(format!(
+                "Detail {} is not part of transaction {}",
+                detail_id, request.transaction_id
+            )));
+        }
+    }
+
+    let sha256 = sha256_hex(&content);
+    let byte_size = content.len() as i64;
+    let (stored, is_encrypted) = match password {
+        Some(password) => {
+            let crypto = user_crypto(pool, user_id, password).await?;
+            let encrypted = crypto
+                .encrypt_bytes(&content)
+                .map_err(|e| ApiError::validation(e.to_string()))?;
+            (encrypted, true)
+        }
+        None => (content, false),
+    };
+
+    let mut tx = pool.begin().await?;
+    let attachment_id = sqlx::query(sql_queries::ATTACHMENT_INSERT)
+        .bind(user_id)
+        .bind(request.transaction_id)
+        .bind(request.detail_id)
+        .bind(&file_name)
+        .bind(mime_type)
+        .bind(byte_size)
+        .bind(&sha256)
+        .bind(is_encrypted)
+        .bind(stored)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+    audit::record(&mut tx, user_id, &attachment_audit_key(attachment_id), None).await?;
+    tx.commit().await?;
+
+    get_attachment_info(pool, user_id, attachment_id).await
+}
+
+/// A header's attachments, or only those of `detail_id`. File content is
+/// left out; fetch it with `get_attachment`.
+pub async fn list_attachments(
+    pool: &SqlitePool,
+    user_id: i64,
+    transaction_id: i64,
+    detail_id: Option<i64>,
+) -> Result<Vec<Attachment>, ApiError> {
+    let attachments = sqlx::query_as::<_, Attachment>(sql_queries::ATTACHMENT_LIST)
+        .bind(user_id)
+        .bind(transaction_id)
+        .bind(detail_id)
+        .bind(detail_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(attachments)
+}
+
+async fn get_attachment_info(
+    pool: &SqlitePool,
+    user_id: i64,
+    attachment_id: i64,
+) -> Result<Attachment, ApiError> {
+    sqlx::query_as::<_, Attachment>(sql_queries::ATTACHMENT_GET_BY_ID)
+        .bind(attachment_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(ENTITY_LABEL))
+}
+
+/// Fetch an attachment with its file content. Encrypted attachments need
+/// the user's password.
+pub async fn get_attachment(
+    pool: &SqlitePool,
+    user_id: i64,
+    attachment_id: i64,
+    password: Option<&str>,
+) -> Result<AttachmentContent, ApiError> {
+    let attachment = get_attachment_info(pool, user_id, attachment_id).await?;
+    let stored: Vec<u8> = sqlx::query_scalar(sql_queries::ATTACHMENT_GET_CONTENT)
+        .bind(attachment_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+    let content = if attachment.is_encrypted {
+        let password = password
+            .ok_or_else(|| ApiError::validation("This attachment is encrypted; enter your password to open it"))?;
+        let crypto = user_crypto(pool, user_id, password).await?;
+        crypto
+            .decrypt_bytes(&stored)
+            .map_err(|e| ApiError::validation(format!("Attachment could not be decrypted: {}", e)))?
+    } else {
+        stored
+    };
+
+    if sha256_hex(&content) != attachment.sha256 {
+        return Err(ApiError::validation(format!(
+            "Attachment {} is corrupted (SHA-256 mismatch)",
+            attachment_id
+        )));
+    }
+
+    Ok(AttachmentContent {
+        attachment,
+        content_base64: general_purpose::STANDARD.encode(&content),
+    })
+}
+
+pub async fn delete_attachment(
+    pool: &SqlitePool,
+    user_id: i64,
+    attachment_id: i64,
+) -> Result<(), ApiError> {
+    let mut tx = pool.begin().await?;
+    let key = attachment_audit_key(attachment_id);
+    let before = audit::snapshot(&mut tx, &key).await?;
+    let result = sqlx::query(sql_queries::ATTACHMENT_DELETE)
+        .bind(attachment_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::encryption::EncryptionService;
+    use crate::test_helpers::database::{create_test_user, setup_migrated_test_db};
+
+    const PASSWORD: &str = "receipt_pw_123";
+    const PDF: &[u8] = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog >>\nendobj\n%%EOF\n";
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0, 0, 0, 13];
+
+    /// User with a salt, one header (id 1) with two lines (ids 1, 2) and a
+    /// second header (id 2) with no lines. Foreign keys are enforced, as in
+    /// the app.
+    async fn setup() -> (SqlitePool, i64) {
+        let pool = setup_migrated_test_db().await;
+        let user_id = create_test_user(&pool, "receipts", PASSWORD).await;
+        sqlx::query("UPDATE USERS SET ENCRYPTION_SALT = ? WHERE USER_ID = ?")
+            .bind(crate::security::generate_encryption_salt().as_slice())
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for sql in [
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (?, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (?, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (1, ?, 'EXPENSE', 'NONE', 'NONE', '2024-03-01 10:00:00', 5000)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, ?, 'EXPENSE', 'NONE', 'NONE', '2024-03-02 10:00:00', 800)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (1, 1, ?, 'EXPENSE', 'Clinic', 3000)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (2, 1, ?, 'EXPENSE', 'Pharmacy', 2000)",
+        ] {
+            sqlx::query(sql).bind(user_id).execute(&pool).await.unwrap();
+        }
+        (pool, user_id)
+    }
+
+    fn request(transaction_id: i64, detail_id: Option<i64>, file_name: &str, content: &[u8]) -> AddAttachmentRequest {
+        AddAttachmentRequest {
+            transaction_id,
+            detail_id,
+            file_name: file_name.to_string(),
+            content_base64: general_purpose::STANDARD.encode(content),
+        }
+    }
+
+    fn decoded(content: &AttachmentContent) -> Vec<u8> {
+        general_purpose::STANDARD.decode(&content.content_base64).unwrap()
+    }
+
+    #[test]
+    fn detect_mime_type_reads_magic_bytes() {
+        assert_eq!(detect_mime_type(PDF), Some("application/pdf"));
+        assert_eq!(detect_mime_type(PNG), Some("image/png"));
+        assert_eq!(detect_mime_type(&[0xff, 0xd8, 0xff, 0xe0]), Some("image/jpeg"));
+        assert_eq!(detect_mime_type(b"RIFF\x10\x00\x00\x00WEBPVP8 "), Some("image/webp"));
+        assert_eq!(detect_mime_type(b"\x00\x00\x00\x18ftypheic"), Some("image/heic"));
+        assert_eq!(detect_mime_type(b"PK\x03\x04 not a receipt"), None);
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[tokio::test]
+    async fn add_list_and_fetch_plain_attachments() {
+        let (pool, user_id) = setup().await;
+
+        let receipt = add_attachment(&pool, user_id, request(1, None, "C:\\scans\\receipt.pdf", PDF), None)
+            .await
+            .unwrap();
+        assert_eq!(receipt.file_name, "receipt.pdf");
+        assert_eq!(receipt.mime_type, "application/pdf");
+        assert_eq!(receipt.byte_size, PDF.len() as i64);
+        assert_eq!(receipt.sha256, sha256_hex(PDF));
+        assert!(!receipt.is_encrypted);
+        let photo = add_attachment(&pool, user_id, request(1, Some(2), "/tmp/photo.png", PNG), None)
+            .await
+            .unwrap();
+
+        let all = list_attachments(&pool, user_id, 1, None).await.unwrap();
+        assert_eq!(all.iter().map(|a| a.attachment_id).collect::<Vec<_>>(),
+                   vec![receipt.attachment_id, photo.attachment_id]);
+        let line = list_attachments(&pool, user_id, 1, Some(2)).await.unwrap();
+        assert_eq!(line.len(), 1);
+        assert_eq!(line[0].file_name, "photo.png");
+
+        let fetched = get_attachment(&pool, user_id, receipt.attachment_id, None).await.unwrap();
+        assert_eq!(decoded(&fetched), PDF);
+
+        delete_attachment(&pool, user_id, photo.attachment_id).await.unwrap();
+        let err = delete_attachment(&pool, user_id, photo.attachment_id).await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_NOT_FOUND);
+
+        let actions: Vec<String> = sqlx::query_scalar(
+            "SELECT ACTION FROM AUDIT_LOG WHERE ENTITY = 'ATTACHMENTS' ORDER BY AUDIT_ID",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(actions, ["INSERT", "INSERT", "DELETE"]);
+    }
+
+    #[tokio::test]
+    async fn add_rejects_bad_files_and_foreign_links() {
+        let (pool, user_id) = setup().await;
+
+        let err = add_attachment(&pool, user_id, request(1, None, "notes.zip", b"PK\x03\x04"), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+        let err = add_attachment(&pool, user_id, request(1, None, "   ", PDF), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+        // Detail 1 belongs to header 1, not header 2
+        let err = add_attachment(&pool, user_id, request(2, Some(1), "r.pdf", PDF), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+        // Another user's header is invisible
+        let err = add_attachment(&pool, user_id + 1, request(1, None, "r.pdf", PDF), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_NOT_FOUND);
+        assert!(list_attachments(&pool, user_id, 1, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn encrypted_attachment_needs_the_right_password() {
+        let (pool, user_id) = setup().await;
+
+        let err = add_attachment(&pool, user_id, request(1, Some(1), "clinic.pdf", PDF), Some("wrong"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+
+        let attachment = add_attachment(&pool, user_id, request(1, Some(1), "clinic.pdf", PDF), Some(PASSWORD))
+            .await
+            .unwrap();
+        assert!(attachment.is_encrypted);
+        assert_eq!(attachment.byte_size, PDF.len() as i64);
+
+        let stored: Vec<u8> = sqlx::query_scalar(sql_queries::ATTACHMENT_GET_CONTENT)
+            .bind(attachment.attachment_id)
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!stored.windows(5).any(|w| w == b"%PDF-"));
+
+        assert!(get_attachment(&pool, user_id, attachment.attachment_id, None).await.is_err());
+        assert!(get_attachment(&pool, user_id, attachment.attachment_id, Some("wrong")).await.is_err());
+        let fetched = get_attachment(&pool, user_id, attachment.attachment_id, Some(PASSWORD))
+            .await
+            .unwrap();
+        assert_eq!(decoded(&fetched), PDF);
+    }
+
+    #[tokio::test]
+    async fn password_change_re_keys_encrypted_attachments() {
+        let (pool, user_id) = setup().await;
+        let attachment = add_attachment(&pool, user_id, request(1, None, "clinic.pdf", PDF), Some(PASSWORD))
+            .await
+            .unwrap();
+
+        let new_password = "receipt_pw_456";
+        EncryptionService::new(pool.clone())
+            .re_encrypt_user_data(user_id, PASSWORD, new_password)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE USERS SET PAW = ? WHERE USER_ID = ?")
+            .bind(crate::security::hash_password(new_password).unwrap())
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let fetched = get_attachment(&pool, user_id, attachment.attachment_id, Some(new_password))
+            .await
+            .unwrap();
+        assert_eq!(decoded(&fetched), PDF);
+    }
+
+    #[tokio::test]
+    async fn deleting_header_or_detail_cascades_to_attachments() {
+        let (pool, user_id) = setup().await;
+
+        add_attachment(&pool, user_id, request(1, None, "receipt.pdf", PDF), None).await.unwrap();
+        add_attachment(&pool, user_id, request(1, Some(2), "photo.png", PNG), None).await.unwrap();
+        add_attachment(&pool, user_id, request(2, None, "other.pdf", PDF), None).await.unwrap();
+
+        sqlx::query(sql_queries::TRANSACTION_DETAIL_DELETE_BY_ID)
+            .bind(2)
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let left = list_attachments(&pool, user_id, 1, None).await.unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].file_name, "receipt.pdf");
+
+        sqlx::query(sql_queries::TRANSACTION_DELETE)
+            .bind(user_id)
+            .bind(1)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(list_attachments(&pool, user_id, 1, None).await.unwrap().is_empty());
+        assert_eq!(list_attachments(&pool, user_id, 2, None).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,368 @@
+//! Audit log: which user created, changed or deleted which row.
+//!
+//! Services take a [`snapshot`] of the row before writing it and call
+//! [`record`] afterwards, both on the connection of the transaction that
+//! does the write. The audit row therefore commits or rolls back together
+//! with the change it describes. The changed columns are derived by
+//! comparing the two snapshots, so call sites never list columns.
+//!
+//! Entries cover the rows a service addresses by key. Rows changed as a
+//! side effect (details removed by an FK cascade, child categories disabled
+//! with their parent) are not listed separately, except when an undo or
+//! redo puts them back one by one.
+//!
+//! Unlike the change journal this log is permanent and only records column
+//! names, not values, so it can be shown to an administrator without
+//! exposing another user's memos or amounts.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::services::journal;
+use crate::sql_queries;
+
+/// Default and maximum page size of [`list_audit_log`].
+pub const DEFAULT_AUDIT_LOG_LIMIT: i64 = 100;
+pub const MAX_AUDIT_LOG_LIMIT: i64 = 1000;
+
+/// Bookkeeping columns every table sets on write; a change to them alone is
+/// not worth an entry.
+const IGNORED_COLUMNS: [&str; 2] = ["ENTRY_DT", "UPDATE_DT"];
+
+const ACTIONS: [&str; 3] = ["INSERT", "UPDATE", "DELETE"];
+
+/// Primary key of one audited row.
+pub(crate) struct AuditKey {
+    table: String,
+    columns: Vec<(String, Value)>,
+}
+
+impl AuditKey {
+    pub(crate) fn new(table: &str, column: &str, value: impl Into<Value>) -> Self {
+        AuditKey {
+            table: table.to_string(),
+            columns: vec![(column.to_string(), value.into())],
+        }
+    }
+
+    /// Add the next column of a composite key.
+    pub(crate) fn and(mut self, column: &str, value: impl Into<Value>) -> Self {
+        self.columns.push((column.to_string(), value.into()));
+        self
+    }
+
+    fn to_json(&self) -> String {
+        let map: Map<String, Value> = self.columns.iter().cloned().collect();
+        Value::Object(map).to_string()
+    }
+}
+
+/// Column values of one row, in table order.
+pub(crate) type RowSnapshot = Vec<(String, Value)>;
+
+/// Current values of the row behind `key`, or `None` if it does not exist.
+pub(crate) async fn snapshot(
+    conn: &mut SqliteConnection,
+    key: &AuditKey,
+) -> Result<Option<RowSnapshot>, sqlx::Error> {
+    let columns = journal::table_columns(conn, &key.table).await?;
+    let key_clause = key
+        .columns
+        .iter()
+        .map(|(name, _)| format!("{} = ?", name))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {}",
+        journal::row_image_sql(&columns, ""),
+        key.table,
+        key_clause
+    );
+    let mut query = sqlx::query(&sql);
+    for (_, value) in &key.columns {
+        query = journal::bind_json(query, Some(value));
+    }
+    let image: Option<String> = query
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|row| row.get(0));
+
+    Ok(image.map(|image| {
+        let mut values = match serde_json::from_str::<Value>(&image) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        columns
+            .iter()
+            .map(|c| {
+                let value = values.remove(&c.name).unwrap_or(Value::Null);
+                (c.name.clone(), value)
+            })
+            .collect()
+    }))
+}
+
+/// Write the audit entry for the row behind `key`, given its snapshot from
+/// before the write. Writes nothing when neither snapshot has the row or
+/// when an update changed no audited column.
+pub(crate) async fn record(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    key: &AuditKey,
+    before: Option<RowSnapshot>,
+) -> Result<(), sqlx::Error> {
+    let after = snapshot(conn, key).await?;
+    let (action, changed) = match (&before, &after) {
+        (None, None) => return Ok(()),
+        (None, Some(row)) => ("INSERT", set_columns(row)),
+        (Some(row), None) => ("DELETE", set_columns(row)),
+        (Some(old), Some(new)) => ("UPDATE", changed_columns(old, new)),
+    };
+    if changed.is_empty() && action == "UPDATE" {
+        return Ok(());
+    }
+
+    sqlx::query(sql_queries::AUDIT_LOG_INSERT)
+        .bind(user_id)
+        .bind(&key.table)
+        .bind(key.to_json())
+        .bind(action)
+        .bind(Value::from(changed).to_string())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+fn is_audited(column: &str) -> bool {
+    !IGNORED_COLUMNS.contains(&column)
+}
+
+fn set_columns(row: &RowSnapshot) -> Vec<String> {
+    row.iter()
+        .filter(|(name, value)| is_audited(name) && !value.is_null())
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+fn changed_columns(old: &RowSnapshot, new: &RowSnapshot) -> Vec<String> {
+    new.iter()
+        .filter(|(name, value)| {
+            is_audited(name)
+                && old
+                    .iter()
+                    .find(|(old_name, _)| old_name == name)
+                    .map_or(true, |(_, old_value)| old_value != value)
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Filters of the admin audit log view. Every field is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditLogQuery {
+    pub user_id: Option<i64>,
+    /// Table name, e.g. `TRANSACTIONS_HEADER`
+    pub entity: Option<String>,
+    /// `INSERT`, `UPDATE` or `DELETE`
+    pub action: Option<String>,
+    /// Inclusive `YYYY-MM-DD` range on the entry date
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AuditLogEntry {
+    pub audit_id: i64,
+    pub user_id: i64,
+    /// `None` once the user has been deleted
+    pub user_name: Option<String>,
+    pub entity: String,
+    /// Primary-key values by column name
+    pub entity_key: Value,
+    pub action: String,
+    pub changed_columns: Vec<String>,
+    pub entry_dt: String,
+}
+
+/// Audit entries matching `query`, newest first.
+pub async fn list_audit_log(
+    pool: &SqlitePool,
+    query: &AuditLogQuery,
+) -> Result<Vec<AuditLogEntry>, ApiError> {
+    if let Some(action) = &query.action {
+        if !ACTIONS.contains(&action.as_str()) {
+            return Err(ApiError::validation(format!("Unknown audit action: {}", action)));
+        }
+    }
+    for date in [&query.start_date, &query.end_date].into_iter().flatten() {
+        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+            return Err(ApiError::validation(format!("Invalid date: {}", date)));
+        }
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LOG_LIMIT);
+    if !(1..=MAX_AUDIT_LOG_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "Limit must be between 1 and {}",
+            MAX_AUDIT_LOG_LIMIT
+        )));
+    }
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let rows = sqlx::query(sql_queries::AUDIT_LOG_LIST)
+        .bind(query.user_id)
+        .bind(query.user_id)
+        .bind(&query.entity)
+        .bind(&query.entity)
+        .bind(&query.action)
+        .bind(&query.action)
+        .bind(&query.start_date)
+        .bind(&query.start_date)
+        .bind(&query.end_date)
+        .bind(&query.end_date)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let entity_key: String = row.get("ENTITY_KEY");
+            let changed: String = row.get("CHANGED_COLUMNS");
+            AuditLogEntry {
+                audit_id: row.get("AUDIT_ID"),
+                user_id: row.get("USER_ID"),
+                user_name: row.get("USER_NAME"),
+                entity: row.get("ENTITY"),
+                entity_key: serde_json::from_str(&entity_key).unwrap_or(Value::Null),
+                action: row.get("ACTION"),
+                changed_columns: serde_json::from_str(&changed).unwrap_or_default(),
+                entry_dt: row.get("ENTRY_DT"),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::shop::{self, AddShopRequest, UpdateShopRequest};
+    use crate::services::transaction::TransactionService;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'alice', 'x', 1, datetime('now'))",
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (3, 'bob', 'x', 1, datetime('now'))",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    async fn entries(pool: &SqlitePool, query: AuditLogQuery) -> Vec<AuditLogEntry> {
+        list_audit_log(pool, &query).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn shop_writes_are_logged_with_their_changed_columns() {
+        let pool = setup().await;
+        let add = AddShopRequest { shop_name: "Corner store".to_string(), memo: None };
+        shop::add_shop(&pool, 2, add).await.unwrap();
+        let shop_id: i64 = sqlx::query_scalar("SELECT SHOP_ID FROM SHOPS")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let rename = |name: &str| UpdateShopRequest {
+            shop_name: name.to_string(),
+            memo: None,
+            display_order: 1,
+        };
+        shop::update_shop(&pool, 2, shop_id, rename("Station store")).await.unwrap();
+        // Saving the form unchanged only bumps UPDATE_DT: no entry
+        shop::update_shop(&pool, 2, shop_id, rename("Station store")).await.unwrap();
+        shop::delete_shop(&pool, 2, shop_id).await.unwrap();
+
+        let log = entries(&pool, AuditLogQuery::default()).await;
+        let actions: Vec<(&str, Vec<&str>)> = log
+            .iter()
+            .rev()
+            .map(|e| {
+                (
+                    e.action.as_str(),
+                    e.changed_columns.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(actions.len(), 3);
+        assert_eq!(actions[0].0, "INSERT");
+        assert!(actions[0].1.contains(&"SHOP_NAME"));
+        assert!(!actions[0].1.contains(&"MEMO"));
+        assert_eq!(actions[1], ("UPDATE", vec!["SHOP_NAME"]));
+        assert_eq!(actions[2], ("UPDATE", vec!["IS_DISABLED"]));
+        assert!(log.iter().all(|e| e.entity == "SHOPS"
+            && e.user_name.as_deref() == Some("alice")
+            && e.entity_key == serde_json::json!({ "SHOP_ID": shop_id })));
+    }
+
+    #[tokio::test]
+    async fn rejected_write_leaves_no_entry() {
+        let pool = setup().await;
+        let add = AddShopRequest { shop_name: "Corner store".to_string(), memo: None };
+        shop::add_shop(&pool, 2, add).await.unwrap();
+        let shop_id: i64 = sqlx::query_scalar("SELECT SHOP_ID FROM SHOPS")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // The row exists, but not for user 3: the delete touches nothing
+        assert!(shop::delete_shop(&pool, 3, shop_id).await.is_err());
+        let query = AuditLogQuery { user_id: Some(3), ..Default::default() };
+        assert!(entries(&pool, query).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn transaction_delete_is_logged_under_its_key() {
+        let pool = setup().await;
+        for sql in [
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (7, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-01 10:00:00', 1000)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        TransactionService::new(pool.clone())
+            .delete_transaction(2, 7)
+            .await
+            .unwrap();
+
+        let query = AuditLogQuery {
+            entity: Some("TRANSACTIONS_HEADER".to_string()),
+            action: Some("DELETE".to_string()),
+            ..Default::default()
+        };
+        let log = entries(&pool, query).await;
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].entity_key, serde_json::json!({ "TRANSACTION_ID": 7 }));
+        assert!(log[0].changed_columns.contains(&"TOTAL_AMOUNT".to_string()));
+    }
+
+    #[tokio::test]
+    async fn list_rejects_bad_filters() {
+        let pool = setup().await;
+        for query in [
+            AuditLogQuery { action: Some("TRUNCATE".to_string()), ..Default::default() },
+            AuditLogQuery { start_date: Some("2026/01/01".to_string()), ..Default::default() },
+            AuditLogQuery { limit: Some(MAX_AUDIT_LOG_LIMIT + 1), ..Default::default() },
+        ] {
+            let err = list_audit_log(&pool, &query).await.unwrap_err();
+            assert_eq!(err.code, ApiError::CODE_VALIDATION);
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! End-of-period account balances over a date range (net-worth chart).
+//!
+//! Uses the same sign rules as `ACCOUNT_BALANCES_AS_OF`, but loads the
+//! per-day deltas once and accumulates them while walking the period ends,
+//! so the number of queries does not grow with the number of points.
+//! Liability balances are the amount owed and are subtracted from net worth.
+//! Per-account balances stay in each account's currency; the totals of a
+//! point are in the base currency at the rates in force on its as-of date.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::services::account;
+use crate::services::currency::RateTable;
+use crate::services::aggregation::{week_bounds_containing, WeekStart};
+use crate::services::holiday::HolidayShift;
+use crate::services::period::{monthly_period_bounds_with_shift, yearly_period_bounds};
+use crate::sql_queries;
+
+/// Upper bound on points per request; ten years of daily points fit.
+const MAX_POINTS: usize = 4000;
+
+/// Spacing of the points in a balance history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BalanceInterval {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// The user's period settings, used to place week/month/year edges the same
+/// way the aggregation screens do.
+#[derive(Debug, Clone)]
+pub struct PeriodCalendar {
+    pub week_start: WeekStart,
+    pub month_start_day: u32,
+    pub month_shift: HolidayShift,
+    /// Holidays covering the requested range; only read for shifted months
+    pub holidays: HashSet<NaiveDate>,
+    pub year_start_month: u32,
+    pub year_start_day: u32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BalanceHistoryAccount {
+    pub account_code: String,
+    pub account_name: String,
+    pub account_class: i64,
+    pub currency_code: String,
+}
+
+/// Balances at the end of one period. `as_of_date` is `period_end` clipped
+/// to the requested range, and `balances` follows the order of
+/// `BalanceHistory::accounts`.
+#[derive(Debug, Serialize, Clone)]
+pub struct BalancePoint {
+    pub period_start: String,
+    pub period_end: String,
+    pub as_of_date: String,
+    pub balances: Vec<i64>,
+    pub total_assets: i64,
+    pub total_liabilities: i64,
+    pub net_worth: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BalanceHistory {
+    pub interval: BalanceInterval,
+    /// Currency of the point totals
+    pub base_currency: String,
+    pub accounts: Vec<BalanceHistoryAccount>,
+    pub points: Vec<BalancePoint>,
+}
+
+#[derive(Debug, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+struct DailyDelta {
+    account_code: String,
+    day: String,
+    delta: i64,
+}
+
+fn next_month(year: i32, month: u32) -> (i32, u32) {
+    if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    }
+}
+
+fn prev_month(year: i32, month: u32) -> (i32, u32) {
+    if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    }
+}
+
+/// Bounds (inclusive) of the period of `interval` that contains `date`.
+pub fn period_containing(
+    date: NaiveDate,
+    interval: BalanceInterval,
+    calendar: &PeriodCalendar,
+) -> (NaiveDate, NaiveDate) {
+    match interval {
+        BalanceInterval::Day => (date, date),
+        BalanceInterval::Week => week_bounds_containing(date, calendar.week_start),
+        BalanceInterval::Month => {
+            let month_bounds = |(year, month): (i32, u32)| {
+                monthly_period_bounds_with_shift(
+                    year,
+                    month,
+                    calendar.month_start_day,
+                    calendar.month_shift,
+                    &calendar.holidays,
+                )
+            };
+            // A period is named after the month it starts in, so the one
+            // holding `date` is that month's, the previous or the next one.
+            let (start, end) = month_bounds((date.year(), date.month()));
+            if date < start {
+                month_bounds(prev_month(date.year(), date.month()))
+            } else if date > end {
+                month_bounds(next_month(date.year(), date.month()))
+            } else {
+                (start, end)
+            }
+        }
+        BalanceInterval::Year => {
+            let (start, end) =
+                yearly_period_bounds(date.year(), calendar.year_start_month, calendar.year_start_day);
+            if date < start {
+                yearly_period_bounds(date.year() - 1, calendar.year_start_month, calendar.year_start_day)
+            } else {
+                (start, end)
+            }
+        }
+    }
+}
+
+/// Consecutive periods covering `start..=end`. The first and last may extend
+/// past the range.
+pub fn periods_in_range(
+    start: NaiveDate,
+    end: NaiveDate,
+    interval: BalanceInterval,
+    calendar: &PeriodCalendar,
+) -> Result<Vec<(NaiveDate, NaiveDate)>, String> {
+    if start > end {
+        return Err(format!("Start date {} is after end date {}", start, end));
+    }
+    let mut periods = Vec::new();
+    let mut cursor = start;
+    loop {
+        let (period_start, period_end) = period_containing(cursor, interval, calendar);
+        periods.push((period_start, period_end));
+        if periods.len() > MAX_POINTS {
+            return Err(format!(
+                "Range produces more than {} points; use a longer interval",
+                MAX_POINTS
+            ));
+        }
+        if period_end >= end {
+            break;
+        }
+        cursor = period_end
+            .succ_opt()
+            .ok_or_else(|| "Date out of range".to_string())?;
+    }
+    Ok(periods)
+}
+
+/// End-of-period balance of every active account and their sum, for each
+/// period of `interval` between `start` and `end`.
+pub async fn get_balance_history(
+    pool: &SqlitePool,
+    user_id: i64,
+    start: NaiveDate,
+    end: NaiveDate,
+    interval: BalanceInterval,
+    calendar: &PeriodCalendar,
+) -> Result<BalanceHistory, ApiError> {
+    let periods = periods_in_range(start, end, interval, calendar).map_err(ApiError::validation)?;
+
+    let accounts = account::get_accounts(pool, user_id).await?;
+    let index: HashMap<&str, usize> = accounts
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (a.account_code.as_str(), i))
+        .collect();
+
+    let deltas = sqlx::query_as::<_, DailyDelta>(sql_queries::ACCOUNT_BALANCE_DAILY_DELTAS)
+        .bind(end.format("%Y-%m-%d").to_string())
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+    let rates = RateTable::load(pool, user_id).await?;
+
+    let mut running: Vec<i64> = accounts.iter().map(|a| a.initial_balance).collect();
+    let mut pending = deltas.iter().peekable();
+    let mut points = Vec::with_capacity(periods.len());
+    for (period_start, period_end) in periods {
+        let as_of = period_end.min(end).format("%Y-%m-%d").to_string();
+        // DAY is `YYYY-MM-DD`, so string order is date order.
+        while let Some(delta) = pending.next_if(|d| d.day <= as_of) {
+            if let Some(&i) = index.get(delta.account_code.as_str()) {
+                running[i] += account::class_direction(accounts[i].account_class) * delta.delta;
+            }
+        }
+        // Accounts without a rate for this date drop out of the totals,
+        // as in `get_account_balances_as_of`.
+        let (mut total_assets, mut total_liabilities) = (0, 0);
+        for (a, &balance) in accounts.iter().zip(&running) {
+            let Some(base) = rates.to_base(balance, &a.currency_code, &as_of) else {
+                continue;
+            };
+            if a.is_liability() {
+                total_liabilities += base;
+            } else {
+                total_assets += base;
+            }
+        }
+        points.push(BalancePoint {
+            period_start: period_start.format("%Y-%m-%d").to_string(),
+            period_end: period_end.format("%Y-%m-%d").to_string(),
+            as_of_date: as_of,
+            balances: running.clone(),
+            total_assets,
+            total_liabilities,
+            net_worth: total_assets - total_liabilities,
+        });
+    }
+
+    Ok(BalanceHistory {
+        interval,
+        base_currency: rates.base_currency().to_string(),
+        accounts: accounts
+            .into_iter()
+            .map(|a| BalanceHistoryAccount {
+                account_code: a.account_code,
+                account_name: a.account_name,
+                account_class: a.account_class,
+                currency_code: a.currency_code,
+            })
+            .collect(),
+        points,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    fn d(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn calendar(month_start_day: u32) -> PeriodCalendar {
+        PeriodCalendar {
+            week_start: WeekStart::Monday,
+            month_start_day,
+            month_shift: HolidayShift::None,
+            holidays: HashSet::new(),
+            year_start_month: 4,
+            year_start_day: 1,
+        }
+    }
+
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'bh', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'INCOME', 2, '収入', datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'TRANSFER', 3, '振替', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, INITIAL_BALANCE, DISPLAY_ORDER) VALUES (2, 'NONE', '指定なし', 'NONE', 0, 0)",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, INITIAL_BALANCE, DISPLAY_ORDER) VALUES (2, 'CASH', 'Cash', 'CASH', 1000, 1)",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, INITIAL_BALANCE, DISPLAY_ORDER) VALUES (2, 'BANK', 'Bank', 'BANK', 5000, 2)",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, INITIAL_BALANCE, DISPLAY_ORDER) VALUES (2, 'CARD', 'Card', 'CREDIT', 0, 3)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    async fn insert_header(pool: &SqlitePool, cat1: &str, from: &str, to: &str, date: &str, amount: i64) {
+        sqlx::query(
+            "INSERT INTO TRANSACTIONS_HEADER \
+             (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, IS_SCHEDULED) \
+             VALUES (2, ?, ?, ?, ?, ?, 0)",
+        )
+        .bind(cat1)
+        .bind(from)
+        .bind(to)
+        .bind(date)
+        .bind(amount)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn month_periods_follow_start_day() {
+        let periods = periods_in_range(d(2026, 1, 10), d(2026, 3, 20), BalanceInterval::Month, &calendar(25)).unwrap();
+        assert_eq!(
+            periods,
+            vec![
+                (d(2025, 12, 25), d(2026, 1, 24)),
+                (d(2026, 1, 25), d(2026, 2, 24)),
+                (d(2026, 2, 25), d(2026, 3, 24)),
+            ]
+        );
+    }
+
+    #[test]
+    fn year_periods_follow_fiscal_start() {
+        let periods = periods_in_range(d(2026, 2, 1), d(2026, 5, 1), BalanceInterval::Year, &calendar(1)).unwrap();
+        assert_eq!(
+            periods,
+            vec![(d(2025, 4, 1), d(2026, 3, 31)), (d(2026, 4, 1), d(2027, 3, 31))]
+        );
+    }
+
+    #[test]
+    fn week_periods_are_contiguous() {
+        // 2026-01-07 is a Wednesday
+        let periods = periods_in_range(d(2026, 1, 7), d(2026, 1, 19), BalanceInterval::Week, &calendar(1)).unwrap();
+        assert_eq!(
+            periods,
+            vec![
+                (d(2026, 1, 5), d(2026, 1, 11)),
+                (d(2026, 1, 12), d(2026, 1, 18)),
+                (d(2026, 1, 19), d(2026, 1, 25)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reversed_or_oversized_range_is_rejected() {
+        assert!(periods_in_range(d(2026, 2, 1), d(2026, 1, 1), BalanceInterval::Day, &calendar(1)).is_err());
+        assert!(periods_in_range(d(2000, 1, 1), d(2026, 1, 1), BalanceInterval::Day, &calendar(1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn history_matches_balances_as_of_each_point() {
+        let pool = setup().await;
+        insert_header(&pool, "INCOME", "NONE", "CASH", "2025-12-20", 100).await;
+        insert_header(&pool, "EXPENSE", "CASH", "NONE", "2026-01-15", 300).await;
+        insert_header(&pool, "TRANSFER", "BANK", "CASH", "2026-02-01 10:00:00", 2000).await;
+        insert_header(&pool, "EXPENSE", "BANK", "NONE", "2026-03-31", 500).await;
+        insert_header(&pool, "EXPENSE", "CARD", "NONE", "2026-01-20", 800).await;
+        insert_header(&pool, "TRANSFER", "BANK", "CARD", "2026-02-27", 800).await;
+
+        let history = get_balance_history(
+            &pool,
+            2,
+            d(2026, 1, 1),
+            d(2026, 3, 15),
+            BalanceInterval::Month,
+            &calendar(1),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(history.points.len(), 3);
+        let codes: Vec<&str> = history.accounts.iter().map(|a| a.account_code.as_str()).collect();
+        for point in &history.points {
+            let as_of = account::get_account_balances_as_of(&pool, 2, &point.as_of_date).await.unwrap();
+            for (i, code) in codes.iter().enumerate() {
+                let expected = as_of.find(code).unwrap().balance;
+                assert_eq!(point.balances[i], expected, "{} on {}", code, point.as_of_date);
+            }
+            assert_eq!(point.total_liabilities, as_of.total_liabilities);
+            assert_eq!(point.net_worth, as_of.net_worth);
+        }
+        // January ends with the card bill still owed
+        assert_eq!(history.points[0].total_liabilities, 800);
+        // The last point is clipped to the range end, before the March expense
+        assert_eq!(history.points[2].as_of_date, "2026-03-15");
+        assert_eq!(history.points[2].total_liabilities, 0);
+        assert_eq!(history.points[2].net_worth, 1000 + 5000 + 100 - 300 - 800);
+    }
+}
@@ -0,0 +1,828 @@
+//! Monthly budgets per category and the budget-vs-actual report.
+//!
+//! A budget belongs to a (year, month) *period*, not a calendar month: the
+//! report measures actuals between the bounds the caller derives from the
+//! user's month start day and holiday shift, so a budget for "2024-04" with
+//! a 25th start day covers 2024-04-25..2024-05-24.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::consts::CATEGORY1_TRANSFER;
+use crate::services::aggregation::{self, GroupBy};
+use crate::services::rollover::{self, RolloverMode};
+use crate::sql_queries;
+use crate::validation;
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct Budget {
+    pub budget_id: i64,
+    pub user_id: i64,
+    pub period_year: i64,
+    pub period_month: i64,
+    pub category1_code: String,
+    pub category2_code: Option<String>,
+    pub category3_code: Option<String>,
+    /// Localized name of the deepest category level the budget is set on
+    pub category_name: Option<String>,
+    pub amount: i64,
+    pub memo: Option<String>,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AddBudgetRequest {
+    pub period_year: i32,
+    pub period_month: u32,
+    pub category1_code: String,
+    pub category2_code: Option<String>,
+    pub category3_code: Option<String>,
+    pub amount: i64,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBudgetRequest {
+    pub amount: i64,
+    pub memo: Option<String>,
+}
+
+/// Carry-forward mode for one category node. The rule applies to budgets
+/// on exactly that node; a rule on `EXPENSE` does not cover `EXPENSE/FOOD`.
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct RolloverRule {
+    pub rule_id: i64,
+    pub user_id: i64,
+    pub category1_code: String,
+    pub category2_code: Option<String>,
+    pub category3_code: Option<String>,
+    /// `RolloverMode` DB value (0 reset, 1 surplus, 2 deficit, 3 both)
+    pub rollover_mode: i64,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRolloverRuleRequest {
+    pub category1_code: String,
+    pub category2_code: Option<String>,
+    pub category3_code: Option<String>,
+    pub rollover_mode: i64,
+}
+
+/// Stored carry of one budget out of a closed period, with the inputs it
+/// was computed from.
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct CarryRecord {
+    pub carry_id: i64,
+    pub user_id: i64,
+    pub period_year: i64,
+    pub period_month: i64,
+    pub category1_code: String,
+    pub category2_code: Option<String>,
+    pub category3_code: Option<String>,
+    pub rollover_mode: i64,
+    pub planned: i64,
+    pub carried_in: i64,
+    pub actual: i64,
+    pub carried_out: i64,
+    pub period_start: String,
+    pub period_end: String,
+    pub entry_dt: String,
+}
+
+/// One report line per budget
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BudgetReportRow {
+    pub budget_id: i64,
+    pub category1_code: String,
+    pub category2_code: Option<String>,
+    pub category3_code: Option<String>,
+    pub category_name: Option<String>,
+    pub planned: i64,
+    /// Carried out of the previous period when it was closed; 0 if it
+    /// was not closed or the category resets.
+    pub carried_in: i64,
+    /// `planned + carried_in`
+    pub available: i64,
+    pub actual: i64,
+    /// `available - actual`; negative once the budget is overspent
+    pub remaining: i64,
+    /// Actual as a percentage of available, one decimal place. `None` when
+    /// nothing is available, where any spending is unbounded.
+    pub percent_used: Option<f64>,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BudgetReport {
+    pub period_year: i32,
+    pub period_month: u32,
+    /// First day of the period (`YYYY-MM-DD`)
+    pub start_date: String,
+    /// Last day of the period (`YYYY-MM-DD`)
+    pub end_date: String,
+    pub rows: Vec<BudgetReportRow>,
+}
+
+/// Normalise optional sub-category codes: blank strings mean "not set".
+fn normalize_code(code: Option<String>) -> Option<String> {
+    code.map(|c| c.trim().to_string()).filter(|c| !c.is_empty())
+}
+
+fn validate_period(year: i32, month: u32) -> Result<(), ApiError> {
+    if !(1900..=2100).contains(&year) {
+        return Err(ApiError::validation(format!("Invalid year: {}", year)));
+    }
+    if !(1..=12).contains(&month) {
+        return Err(ApiError::validation(format!(
+            "Invalid month: {} (expected 1..=12)",
+            month
+        )));
+    }
+    Ok(())
+}
+
+fn validate_amount(amount: i64) -> Result<(), ApiError> {
+    if amount < 0 {
+        return Err(ApiError::validation("Budget amount must not be negative"));
+    }
+    Ok(())
+}
+
+/// Key matching `AggregationResult::group_key` for a category node
+/// (`CAT1`, `CAT1/CAT2` or `CAT1/CAT2/CAT3`).
+fn category_key(category1: &str, category2: Option<&str>, category3: Option<&str>) -> String {
+    match (category2, category3) {
+        (Some(c2), Some(c3)) => format!("{}/{}/{}", category1, c2, c3),
+        (Some(c2), None) => format!("{}/{}", category1, c2),
+        _ => category1.to_string(),
+    }
+}
+
+fn budget_group_key(budget: &Budget) -> String {
+    category_key(
+        &budget.category1_code,
+        budget.category2_code.as_deref(),
+        budget.category3_code.as_deref(),
+    )
+}
+
+fn budget_group_by(budget: &Budget) -> GroupBy {
+    match (&budget.category2_code, &budget.category3_code) {
+        (Some(_), Some(_)) => GroupBy::Category3,
+        (Some(_), None) => GroupBy::Category2,
+        _ => GroupBy::Category1,
+    }
+}
+
+fn percent_used(available: i64, actual: i64) -> Option<f64> {
+    if available <= 0 {
+        return None;
+    }
+    Some((actual as f64 * 1000.0 / available as f64).round() / 10.0)
+}
+
+/// Join budgets with the actual totals and carried-in amounts, both keyed
+/// by aggregation group key.
+fn build_report_rows(
+    budgets: Vec<Budget>,
+    actuals: &HashMap<String, i64>,
+    carried_in: &HashMap<String, i64>,
+) -> Vec<BudgetReportRow> {
+    budgets
+        .into_iter()
+        .map(|b| {
+            let key = budget_group_key(&b);
+            let actual = actuals.get(&key).copied().unwrap_or(0);
+            let carried_in = carried_in.get(&key).copied().unwrap_or(0);
+            let available = b.amount + carried_in;
+            BudgetReportRow {
+                budget_id: b.budget_id,
+                category1_code: b.category1_code,
+                category2_code: b.category2_code,
+                category3_code: b.category3_code,
+                category_name: b.category_name,
+                planned: b.amount,
+                carried_in,
+                available,
+                actual,
+                remaining: available - actual,
+                percent_used: percent_used(available, actual),
+                memo: b.memo,
+            }
+        })
+        .collect()
+}
+
+/// Check that the category node exists for the user. TRANSFER is rejected
+/// up front: aggregation leaves it out, so a budget on it could never show
+/// any actual spending.
+async fn ensure_budget_category(
+    pool: &SqlitePool,
+    user_id: i64,
+    category1: &str,
+    category2: &Option<String>,
+    category3: &Option<String>,
+) -> Result<(), ApiError> {
+    if category3.is_some() && category2.is_none() {
+        return Err(ApiError::validation(
+            "Category3 budget requires a category2 code",
+        ));
+    }
+    if category1 == CATEGORY1_TRANSFER {
+        return Err(ApiError::validation("Budgets cannot be set on transfers"));
+    }
+
+    let exists: i64 = sqlx::query_scalar(sql_queries::BUDGET_CATEGORY_EXISTS)
+        .bind(category2)
+        .bind(category3)
+        .bind(user_id)
+        .bind(category1)
+        .bind(category2)
+        .bind(category3)
+        .fetch_one(pool)
+        .await?;
+    if exists == 0 {
+        return Err(ApiError::not_found("Category"));
+    }
+    Ok(())
+}
+
+/// List the budgets of one period
+pub async fn list_budgets(
+    pool: &SqlitePool,
+    user_id: i64,
+    period_year: i32,
+    period_month: u32,
+    lang: &str,
+) -> Result<Vec<Budget>, ApiError> {
+    validate_period(period_year, period_month)?;
+    let budgets = sqlx::query_as::<_, Budget>(sql_queries::BUDGET_LIST_BY_PERIOD)
+        .bind(lang)
+        .bind(lang)
+        .bind(lang)
+        .bind(user_id)
+        .bind(period_year)
+        .bind(period_month)
+        .fetch_all(pool)
+        .await?;
+    Ok(budgets)
+}
+
+/// Add a budget and return its BUDGET_ID
+pub async fn add_budget(
+    pool: &SqlitePool,
+    user_id: i64,
+    request: AddBudgetRequest,
+) -> Result<i64, ApiError> {
+    validate_period(request.period_year, request.period_month)?;
+    validate_amount(request.amount)?;
+    validation::validate_memo("Memo", request.memo.as_ref()).map_err(ApiError::validation)?;
+
+    let category1 = request.category1_code.trim().to_string();
+    let category2 = normalize_code(request.category2_code);
+    let category3 = normalize_code(request.category3_code);
+    ensure_budget_category(pool, user_id, &category1, &category2, &category3).await?;
+
+    let duplicates: i64 = sqlx::query_scalar(sql_queries::BUDGET_CHECK_DUPLICATE)
+        .bind(user_id)
+        .bind(request.period_year)
+        .bind(request.period_month)
+        .bind(&category1)
+        .bind(&category2)
+        .bind(&category3)
+        .fetch_one(pool)
+        .await?;
+    if duplicates > 0 {
+        return Err(ApiError::duplicate_code("Budget"));
+    }
+
+    let budget_id = sqlx::query(sql_queries::BUDGET_INSERT)
+        .bind(user_id)
+        .bind(request.period_year)
+        .bind(request.period_month)
+        .bind(&category1)
+        .bind(&category2)
+        .bind(&category3)
+        .bind(request.amount)
+        .bind(&request.memo)
+        .execute(pool)
+        .await?
+        .last_insert_rowid();
+
+    Ok(budget_id)
+}
+
+/// Change a budget's planned amount and memo. Period and category are the
+/// budget's identity; moving one means delete + add.
+pub async fn update_budget(
+    pool: &SqlitePool,
+    user_id: i64,
+    budget_id: i64,
+    request: UpdateBudgetRequest,
+) -> Result<(), ApiError> {
+    validate_amount(request.amount)?;
+    validation::validate_memo("Memo", request.memo.as_ref()).map_err(ApiError::validation)?;
+
+    let affected = sqlx::query(sql_queries::BUDGET_UPDATE)
+        .bind(request.amount)
+        .bind(&request.memo)
+        .bind(user_id)
+        .bind(budget_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        return Err(ApiError::not_found("Budget"));
+    }
+    Ok(())
+}
+
+/// Delete a budget
+pub async fn delete_budget(pool: &SqlitePool, user_id: i64, budget_id: i64) -> Result<(), ApiError> {
+    let affected = sqlx::query(sql_queries::BUDGET_DELETE)
+        .bind(user_id)
+        .bind(budget_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        return Err(ApiError::not_found("Budget"));
+    }
+    Ok(())
+}
+
+/// Actual totals for every category level that has a budget, keyed by
+/// aggregation group key. Uses `execute_period_aggregation` so the figures
+/// match the monthly aggregation screen exactly.
+async fn fetch_actuals(
+    pool: &SqlitePool,
+    user_id: i64,
+    budgets: &[Budget],
+    start: NaiveDate,
+    end: NaiveDate,
+    lang: &str,
+    include_scheduled: bool,
+) -> Result<HashMap<String, i64>, ApiError> {
+    let mut actuals: HashMap<String, i64> = HashMap::new();
+    for group_by in [GroupBy::Category1, GroupBy::Category2, GroupBy::Category3] {
+        let level = std::mem::discriminant(&group_by);
+        if !budgets
+            .iter()
+            .any(|b| std::mem::discriminant(&budget_group_by(b)) == level)
+        {
+            continue;
+        }
+        let results = aggregation::execute_period_aggregation(
+            pool,
+            user_id,
+            start,
+            end,
+            group_by,
+            lang,
+            include_scheduled,
+        )
+        .await
+        .map_err(ApiError::database)?;
+        // Aggregation totals are signed (EXPENSE negative); a budget is a
+        // planned magnitude for either direction.
+        actuals.extend(results.into_iter().map(|r| (r.group_key, r.total_amount.abs())));
+    }
+    Ok(actuals)
+}
+
+/// Amounts carried into `period_year`/`period_month`, i.e. the stored
+/// carry-out of the previous period, keyed by aggregation group key.
+async fn fetch_carried_in(
+    pool: &SqlitePool,
+    user_id: i64,
+    period_year: i32,
+    period_month: u32,
+) -> Result<HashMap<String, i64>, ApiError> {
+    let (prev_year, prev_month) = rollover::previous_period(period_year, period_month);
+    let history = list_carry_history(pool, user_id, prev_year, prev_month).await?;
+    Ok(history
+        .into_iter()
+        .map(|c| {
+            let key = category_key(
+                &c.category1_code,
+                c.category2_code.as_deref(),
+                c.category3_code.as_deref(),
+            );
+            (key, c.carried_out)
+        })
+        .collect())
+}
+
+/// Budget-vs-actual for one period. `start` / `end` are the period bounds
+/// (from `monthly_period_bounds_with_shift`). Each budget is topped up (or
+/// reduced) by what the previous period carried out when it was closed.
+#[allow(clippy::too_many_arguments)]
+pub async fn get_budget_report(
+    pool: &SqlitePool,
+    user_id: i64,
+    period_year: i32,
+    period_month: u32,
+    start: NaiveDate,
+    end: NaiveDate,
+    lang: &str,
+    include_scheduled: bool,
+) -> Result<BudgetReport, ApiError> {
+    let budgets = list_budgets(pool, user_id, period_year, period_month, lang).await?;
+    let actuals =
+        fetch_actuals(pool, user_id, &budgets, start, end, lang, include_scheduled).await?;
+    let carried_in = fetch_carried_in(pool, user_id, period_year, period_month).await?;
+
+    Ok(BudgetReport {
+        period_year,
+        period_month,
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        rows: build_report_rows(budgets, &actuals, &carried_in),
+    })
+}
+
+/// List the user's carry-forward rules
+pub async fn list_rollover_rules(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<RolloverRule>, ApiError> {
+    let rules = sqlx::query_as::<_, RolloverRule>(sql_queries::BUDGET_ROLLOVER_RULE_LIST)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(rules)
+}
+
+/// Create or change the carry-forward rule of one category node. Only
+/// periods closed afterwards use the new mode; stored carries stay as they
+/// were computed.
+pub async fn set_rollover_rule(
+    pool: &SqlitePool,
+    user_id: i64,
+    request: SetRolloverRuleRequest,
+) -> Result<(), ApiError> {
+    let mode = RolloverMode::from_db_value(request.rollover_mode).ok_or_else(|| {
+        ApiError::validation(format!(
+            "Rollover mode must be 0 (reset), 1 (surplus), 2 (deficit), or 3 (both) (got {})",
+            request.rollover_mode
+        ))
+    })?;
+    let category1 = request.category1_code.trim().to_string();
+    let category2 = normalize_code(request.category2_code);
+    let category3 = normalize_code(request.category3_code);
+    ensure_budget_category(pool, user_id, &category1, &category2, &category3).await?;
+
+    let affected = sqlx::query(sql_queries::BUDGET_ROLLOVER_RULE_UPDATE)
+        .bind(mode.to_db_value())
+        .bind(user_id)
+        .bind(&category1)
+        .bind(&category2)
+        .bind(&category3)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        sqlx::query(sql_queries::BUDGET_ROLLOVER_RULE_INSERT)
+            .bind(user_id)
+            .bind(&category1)
+            .bind(&category2)
+            .bind(&category3)
+            .bind(mode.to_db_value())
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Carries stored when `period_year`/`period_month` was closed
+pub async fn list_carry_history(
+    pool: &SqlitePool,
+    user_id: i64,
+    period_year: i32,
+    period_month: u32,
+) -> Result<Vec<CarryRecord>, ApiError> {
+    validate_period(period_year, period_month)?;
+    let records = sqlx::query_as::<_, CarryRecord>(sql_queries::BUDGET_CARRY_HISTORY_LIST_BY_PERIOD)
+        .bind(user_id)
+        .bind(period_year)
+        .bind(period_month)
+        .fetch_all(pool)
+        .await?;
+    Ok(records)
+}
+
+/// Close a period: compute each budget's carry into the next period with
+/// `rollover::carry_forward` and store it. A period that is already closed
+/// is returned unchanged unless `recompute` is set, so later rule changes
+/// or late entries never silently rewrite past carries. Scheduled rows do
+/// not count as actuals.
+#[allow(clippy::too_many_arguments)]
+pub async fn close_budget_period(
+    pool: &SqlitePool,
+    user_id: i64,
+    period_year: i32,
+    period_month: u32,
+    start: NaiveDate,
+    end: NaiveDate,
+    lang: &str,
+    recompute: bool,
+) -> Result<Vec<CarryRecord>, ApiError> {
+    let existing = list_carry_history(pool, user_id, period_year, period_month).await?;
+    if !existing.is_empty() && !recompute {
+        return Ok(existing);
+    }
+
+    let budgets = list_budgets(pool, user_id, period_year, period_month, lang).await?;
+    let actuals = fetch_actuals(pool, user_id, &budgets, start, end, lang, false).await?;
+    let carried_in = fetch_carried_in(pool, user_id, period_year, period_month).await?;
+    let modes: HashMap<String, RolloverMode> = list_rollover_rules(pool, user_id)
+        .await?
+        .into_iter()
+        .map(|r| {
+            let key = category_key(
+                &r.category1_code,
+                r.category2_code.as_deref(),
+                r.category3_code.as_deref(),
+            );
+            let mode = RolloverMode::from_db_value(r.rollover_mode).unwrap_or(RolloverMode::Reset);
+            (key, mode)
+        })
+        .collect();
+
+    let period_start = start.format("%Y-%m-%d").to_string();
+    let period_end = end.format("%Y-%m-%d").to_string();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(sql_queries::BUDGET_CARRY_HISTORY_DELETE_BY_PERIOD)
+        .bind(user_id)
+        .bind(period_year)
+        .bind(period_month)
+        .execute(&mut *tx)
+        .await?;
+    for b in &budgets {
+        let key = budget_group_key(b);
+        let mode = modes.get(&key).copied().unwrap_or(RolloverMode::Reset);
+        let actual = actuals.get(&key).copied().unwrap_or(0);
+        let carried_in = carried_in.get(&key).copied().unwrap_or(0);
+        let carried_out = rollover::carry_forward(b.amount, carried_in, actual, mode);
+
+        sqlx::query(sql_queries::BUDGET_CARRY_HISTORY_INSERT)
+            .bind(user_id)
+            .bind(period_year)
+            .bind(period_month)
+            .bind(&b.category1_code)
+            .bind(&b.category2_code)
+            .bind(&b.category3_code)
+            .bind(mode.to_db_value())
+            .bind(b.amount)
+            .bind(carried_in)
+            .bind(actual)
+            .bind(carried_out)
+            .bind(&period_start)
+            .bind(&period_end)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    list_carry_history(pool, user_id, period_year, period_month).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::period::monthly_period_bounds;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    async fn setup_budget_db() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'budget', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'TRANSFER', 3, '振替', datetime('now'))",
+            "INSERT INTO CATEGORY2 (USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, DISPLAY_ORDER, CATEGORY2_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 'FOOD', 1, '食費', datetime('now'))",
+            "INSERT INTO CATEGORY2_I18N (USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, LANG_CODE, CATEGORY2_NAME_I18N, ENTRY_DT) VALUES (2, 'EXPENSE', 'FOOD', 'en', 'Food', datetime('now'))",
+            "INSERT INTO CATEGORY2 (USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, DISPLAY_ORDER, CATEGORY2_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 'HOUSING', 2, '住居費', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'CASH', 'Wallet', 'CASH')",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    async fn insert_expense(pool: &SqlitePool, id: i64, date: &str, category2: &str, amount: i64) {
+        sqlx::query(
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, TAX_INCLUDED_TYPE, IS_SCHEDULED) \
+             VALUES (?, 2, 'EXPENSE', 'CASH', 'NONE', ?, ?, 1, 0)",
+        )
+        .bind(id)
+        .bind(date)
+        .bind(amount)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO TRANSACTIONS_DETAIL (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX) \
+             VALUES (?, 2, 'EXPENSE', ?, 'item', ?, 0, 0, ?)",
+        )
+        .bind(id)
+        .bind(category2)
+        .bind(amount)
+        .bind(amount)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    fn request(category2: Option<&str>, amount: i64) -> AddBudgetRequest {
+        AddBudgetRequest {
+            period_year: 2024,
+            period_month: 4,
+            category1_code: "EXPENSE".to_string(),
+            category2_code: category2.map(str::to_string),
+            category3_code: None,
+            amount,
+            memo: None,
+        }
+    }
+
+    #[test]
+    fn test_percent_used_rounds_and_skips_zero_budget() {
+        assert_eq!(percent_used(3000, 1000), Some(33.3));
+        assert_eq!(percent_used(1000, 1500), Some(150.0));
+        assert_eq!(percent_used(0, 500), None);
+    }
+
+    #[tokio::test]
+    async fn test_budget_crud_and_validation() {
+        let pool = setup_budget_db().await;
+
+        let id = add_budget(&pool, 2, request(Some("FOOD"), 30000)).await.unwrap();
+        add_budget(&pool, 2, request(None, 100000)).await.unwrap();
+
+        let dup = add_budget(&pool, 2, request(Some("FOOD"), 1)).await.unwrap_err();
+        assert_eq!(dup.code, ApiError::CODE_DUPLICATE_CODE);
+        let missing = add_budget(&pool, 2, request(Some("NOPE"), 1)).await.unwrap_err();
+        assert_eq!(missing.code, ApiError::CODE_NOT_FOUND);
+        let negative = add_budget(&pool, 2, request(Some("HOUSING"), -1)).await.unwrap_err();
+        assert_eq!(negative.code, ApiError::CODE_VALIDATION);
+        let mut transfer = request(None, 1);
+        transfer.category1_code = "TRANSFER".to_string();
+        assert_eq!(
+            add_budget(&pool, 2, transfer).await.unwrap_err().code,
+            ApiError::CODE_VALIDATION
+        );
+
+        update_budget(&pool, 2, id, UpdateBudgetRequest { amount: 25000, memo: Some("tighter".into()) })
+            .await
+            .unwrap();
+        let budgets = list_budgets(&pool, 2, 2024, 4, "en").await.unwrap();
+        assert_eq!(budgets.len(), 2);
+        assert_eq!(budgets[0].category2_code, None);
+        assert_eq!(budgets[0].category_name.as_deref(), Some("支出"));
+        assert_eq!(budgets[1].amount, 25000);
+        assert_eq!(budgets[1].category_name.as_deref(), Some("Food"));
+
+        delete_budget(&pool, 2, id).await.unwrap();
+        assert_eq!(
+            delete_budget(&pool, 2, id).await.unwrap_err().code,
+            ApiError::CODE_NOT_FOUND
+        );
+        // Another user cannot touch the remaining budget.
+        let other = budgets[0].budget_id;
+        assert_eq!(
+            update_budget(&pool, 3, other, UpdateBudgetRequest { amount: 1, memo: None })
+                .await
+                .unwrap_err()
+                .code,
+            ApiError::CODE_NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_budget_report_uses_period_bounds() {
+        let pool = setup_budget_db().await;
+        add_budget(&pool, 2, request(None, 50000)).await.unwrap();
+        add_budget(&pool, 2, request(Some("FOOD"), 20000)).await.unwrap();
+        add_budget(&pool, 2, request(Some("HOUSING"), 0)).await.unwrap();
+
+        // Month starts on the 25th: the April period is 04-25..05-24.
+        insert_expense(&pool, 1, "2024-04-20 10:00:00", "FOOD", 9999).await;
+        insert_expense(&pool, 2, "2024-04-25 10:00:00", "FOOD", 15000).await;
+        insert_expense(&pool, 3, "2024-05-24 21:00:00", "FOOD", 10000).await;
+        insert_expense(&pool, 4, "2024-05-01 09:00:00", "HOUSING", 80000).await;
+        insert_expense(&pool, 5, "2024-05-25 09:00:00", "FOOD", 7777).await;
+
+        let (start, end) = monthly_period_bounds(2024, 4, 25);
+        let report = get_budget_report(&pool, 2, 2024, 4, start, end, "en", false)
+            .await
+            .unwrap();
+        assert_eq!(report.start_date, "2024-04-25");
+        assert_eq!(report.end_date, "2024-05-24");
+
+        let expense = &report.rows[0];
+        assert_eq!((expense.planned, expense.actual, expense.remaining), (50000, 105000, -55000));
+        assert_eq!(expense.percent_used, Some(210.0));
+
+        let food = &report.rows[1];
+        assert_eq!(food.category2_code.as_deref(), Some("FOOD"));
+        assert_eq!((food.planned, food.actual, food.remaining), (20000, 25000, -5000));
+        assert_eq!(food.percent_used, Some(125.0));
+
+        let housing = &report.rows[2];
+        assert_eq!((housing.planned, housing.actual), (0, 80000));
+        assert_eq!(housing.percent_used, None);
+    }
+
+    #[tokio::test]
+    async fn test_close_period_carries_into_next_report_and_is_frozen() {
+        let pool = setup_budget_db().await;
+        add_budget(&pool, 2, request(Some("FOOD"), 20000)).await.unwrap();
+        let mut may = request(Some("FOOD"), 20000);
+        may.period_month = 5;
+        add_budget(&pool, 2, may).await.unwrap();
+        set_rollover_rule(
+            &pool,
+            2,
+            SetRolloverRuleRequest {
+                category1_code: "EXPENSE".into(),
+                category2_code: Some("FOOD".into()),
+                category3_code: None,
+                rollover_mode: RolloverMode::CarrySurplus.to_db_value(),
+            },
+        )
+        .await
+        .unwrap();
+        insert_expense(&pool, 1, "2024-04-10 10:00:00", "FOOD", 15000).await;
+
+        let (start, end) = monthly_period_bounds(2024, 4, 1);
+        let closed = close_budget_period(&pool, 2, 2024, 4, start, end, "en", false)
+            .await
+            .unwrap();
+        assert_eq!(closed.len(), 1);
+        assert_eq!((closed[0].planned, closed[0].actual, closed[0].carried_out), (20000, 15000, 5000));
+        assert_eq!(closed[0].period_end, "2024-04-30");
+
+        let (may_start, may_end) = monthly_period_bounds(2024, 5, 1);
+        let report = get_budget_report(&pool, 2, 2024, 5, may_start, may_end, "en", false)
+            .await
+            .unwrap();
+        assert_eq!((report.rows[0].carried_in, report.rows[0].available), (5000, 25000));
+        assert_eq!(report.rows[0].remaining, 25000);
+
+        // Switching the rule afterwards does not rewrite the closed period...
+        set_rollover_rule(
+            &pool,
+            2,
+            SetRolloverRuleRequest {
+                category1_code: "EXPENSE".into(),
+                category2_code: Some("FOOD".into()),
+                category3_code: None,
+                rollover_mode: RolloverMode::Reset.to_db_value(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(list_rollover_rules(&pool, 2).await.unwrap().len(), 1);
+        let again = close_budget_period(&pool, 2, 2024, 4, start, end, "en", false)
+            .await
+            .unwrap();
+        assert_eq!(again[0].carried_out, 5000);
+
+        // ...until it is explicitly recomputed.
+        let recomputed = close_budget_period(&pool, 2, 2024, 4, start, end, "en", true)
+            .await
+            .unwrap();
+        assert_eq!(recomputed[0].carried_out, 0);
+        assert_eq!(recomputed[0].rollover_mode, RolloverMode::Reset.to_db_value());
+    }
+
+    #[tokio::test]
+    async fn test_set_rollover_rule_rejects_unknown_mode() {
+        let pool = setup_budget_db().await;
+        let err = set_rollover_rule(
+            &pool,
+            2,
+            SetRolloverRuleRequest {
+                category1_code: "EXPENSE".into(),
+                category2_code: None,
+                category3_code: None,
+                rollover_mode: 4,
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+    }
+}
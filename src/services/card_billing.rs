@@ -0,0 +1,662 @@
+//! Credit-card statement cycles.
+//!
+//! Each card account can carry a closing day, a payment day and the bank
+//! account that pays it. A statement collects the card's actualised charges
+//! between the previous closing date (exclusive) and this one (inclusive);
+//! issuing it creates the payment as a scheduled TRANSFER bank → card, which
+//! the user then confirms through the normal `IS_SCHEDULED` flow. Payment
+//! dates use `HolidayShift::Next`, the same as withdrawals in recurring rules.
+
+use std::collections::HashSet;
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::services::audit::{self, AuditKey};
+use crate::services::transaction::header_audit_key;
+use crate::services::{account, currency};
+use crate::services::holiday::{self, shift_for_holidays, HolidayShift};
+use crate::services::period::resolve_day_or_end;
+use crate::sql_queries;
+
+const ENTITY_LABEL: &str = "card billing settings";
+
+fn statement_audit_key(statement_id: i64) -> AuditKey {
+    AuditKey::new("CARD_STATEMENTS", "STATEMENT_ID", statement_id)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct CardBillingSettings {
+    pub account_code: String,
+    pub payment_account_code: String,
+    pub closing_day: i64,
+    pub payment_day: i64,
+    /// Months between the closing month and the payment month (1 or 2)
+    pub payment_month_offset: i64,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SetCardBillingRequest {
+    pub account_code: String,
+    pub payment_account_code: String,
+    pub closing_day: i64,
+    pub payment_day: i64,
+    pub payment_month_offset: i64,
+}
+
+/// Dates of the statement that closes in a given month
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatementCycle {
+    pub period_start: NaiveDate,
+    pub closing_date: NaiveDate,
+    pub payment_date: NaiveDate,
+}
+
+/// A statement, either previewed or issued. `statement_id` is `None` for a
+/// preview; `transaction_id` is the scheduled payment, `None` when nothing
+/// is owed or the payment row was deleted. `is_confirmed` is true once the
+/// user has confirmed that payment.
+#[derive(Debug, Serialize, Clone)]
+pub struct CardStatement {
+    pub statement_id: Option<i64>,
+    pub account_code: String,
+    pub payment_account_code: Option<String>,
+    pub period_start: String,
+    pub closing_date: String,
+    pub payment_date: String,
+    pub total_amount: i64,
+    pub charge_count: i64,
+    pub transaction_id: Option<i64>,
+    pub is_confirmed: bool,
+}
+
+#[derive(Debug, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+struct StatementRow {
+    statement_id: i64,
+    account_code: String,
+    period_start: String,
+    closing_date: String,
+    payment_date: String,
+    total_amount: i64,
+    charge_count: i64,
+    transaction_id: Option<i64>,
+    payment_account_code: Option<String>,
+    is_scheduled: Option<i64>,
+}
+
+impl From<StatementRow> for CardStatement {
+    fn from(row: StatementRow) -> Self {
+        CardStatement {
+            statement_id: Some(row.statement_id),
+            account_code: row.account_code,
+            payment_account_code: row.payment_account_code,
+            period_start: row.period_start,
+            closing_date: row.closing_date,
+            payment_date: row.payment_date,
+            total_amount: row.total_amount,
+            charge_count: row.charge_count,
+            transaction_id: row.transaction_id,
+            is_confirmed: row.is_scheduled == Some(0),
+        }
+    }
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn month_start(year: i32, month: u32) -> Result<NaiveDate, ApiError> {
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| ApiError::validation(format!("Invalid year/month: {}/{}", year, month)))
+}
+
+/// Closing day `closing_day` of (year, month) and the payment date
+/// `offset` months later, before holiday shift.
+fn raw_cycle_dates(
+    year: i32,
+    month: u32,
+    settings: &CardBillingSettings,
+) -> Result<(NaiveDate, NaiveDate, NaiveDate), ApiError> {
+    let first = month_start(year, month)?;
+    let resolve = |d: NaiveDate, day: i64| resolve_day_or_end(d.year(), d.month(), day as u32);
+    let previous = first
+        .checked_sub_months(Months::new(1))
+        .ok_or_else(|| ApiError::validation("Date out of range"))?;
+    let payment_month = first
+        .checked_add_months(Months::new(settings.payment_month_offset as u32))
+        .ok_or_else(|| ApiError::validation("Date out of range"))?;
+    Ok((
+        resolve(previous, settings.closing_day),
+        resolve(first, settings.closing_day),
+        resolve(payment_month, settings.payment_day),
+    ))
+}
+
+/// Cycle of the statement closing in (year, month). The closing date itself
+/// is never shifted; only the payment date moves to the next business day.
+pub fn statement_cycle(
+    year: i32,
+    month: u32,
+    settings: &CardBillingSettings,
+    holidays: &HashSet<NaiveDate>,
+) -> Result<StatementCycle, ApiError> {
+    let (previous_closing, closing_date, payment_candidate) = raw_cycle_dates(year, month, settings)?;
+    let period_start = previous_closing
+        .succ_opt()
+        .ok_or_else(|| ApiError::validation("Date out of range"))?;
+    Ok(StatementCycle {
+        period_start,
+        closing_date,
+        payment_date: shift_for_holidays(payment_candidate, HolidayShift::Next, holidays),
+    })
+}
+
+fn validate_day(label: &str, day: i64) -> Result<(), ApiError> {
+    if !(1..=31).contains(&day) {
+        return Err(ApiError::validation(format!(
+            "{} must be between 1 and 31 (got {})",
+            label, day
+        )));
+    }
+    Ok(())
+}
+
+pub async fn list_billing_settings(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<CardBillingSettings>, ApiError> {
+    let settings = sqlx::query_as::<_, CardBillingSettings>(sql_queries::CARD_BILLING_SETTINGS_LIST)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(settings)
+}
+
+async fn get_billing_settings(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+) -> Result<CardBillingSettings, ApiError> {
+    sqlx::query_as::<_, CardBillingSettings>(sql_queries::CARD_BILLING_SETTINGS_GET)
+        .bind(user_id)
+        .bind(account_code)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(ENTITY_LABEL))
+}
+
+/// Create or replace the billing cycle of a card. The card must be a
+/// liability account and the payment account an asset one in the same
+/// currency.
+pub async fn set_billing_settings(
+    pool: &SqlitePool,
+    user_id: i64,
+    request: SetCardBillingRequest,
+) -> Result<CardBillingSettings, ApiError> {
+    let account_code = request.account_code.trim().to_uppercase();
+    let payment_account_code = request.payment_account_code.trim().to_uppercase();
+    validate_day("Closing day", request.closing_day)?;
+    validate_day("Payment day", request.payment_day)?;
+    if !(1..=2).contains(&request.payment_month_offset) {
+        return Err(ApiError::validation(format!(
+            "Payment month offset must be 1 or 2 (got {})",
+            request.payment_month_offset
+        )));
+    }
+
+    let card = account::get_account_by_code(pool, user_id, &account_code)
+        .await?
+        .ok_or_else(|| ApiError::not_found("account"))?;
+    if !card.is_liability() {
+        return Err(ApiError::validation(format!(
+            "Account {} is not a liability account",
+            account_code
+        )));
+    }
+    let payer = account::get_account_by_code(pool, user_id, &payment_account_code)
+        .await?
+        .ok_or_else(|| ApiError::not_found("account"))?;
+    if payer.is_liability() {
+        return Err(ApiError::validation(format!(
+            "Payment account {} must be an asset account",
+            payment_account_code
+        )));
+    }
+    if payer.currency_code != card.currency_code {
+        return Err(ApiError::validation(format!(
+            "Payment account {} ({}) and card {} ({}) use different currencies",
+            payment_account_code, payer.currency_code, account_code, card.currency_code
+        )));
+    }
+
+    let affected = sqlx::query(sql_queries::CARD_BILLING_SETTINGS_UPDATE)
+        .bind(&payment_account_code)
+        .bind(request.closing_day)
+        .bind(request.payment_day)
+        .bind(request.payment_month_offset)
+        .bind(user_id)
+        .bind(&account_code)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        sqlx::query(sql_queries::CARD_BILLING_SETTINGS_INSERT)
+            .bind(user_id)
+            .bind(&account_code)
+            .bind(&payment_account_code)
+            .bind(request.closing_day)
+            .bind(request.payment_day)
+            .bind(request.payment_month_offset)
+            .execute(pool)
+            .await?;
+    }
+
+    get_billing_settings(pool, user_id, &account_code).await
+}
+
+/// Remove a card's billing cycle. Issued statements and their payments stay.
+pub async fn delete_billing_settings(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+) -> Result<(), ApiError> {
+    let affected = sqlx::query(sql_queries::CARD_BILLING_SETTINGS_DELETE)
+        .bind(user_id)
+        .bind(account_code.trim().to_uppercase())
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    Ok(())
+}
+
+async fn load_cycle(
+    pool: &SqlitePool,
+    user_id: i64,
+    settings: &CardBillingSettings,
+    year: i32,
+    month: u32,
+) -> Result<StatementCycle, ApiError> {
+    let (_, _, payment_candidate) = raw_cycle_dates(year, month, settings)?;
+    let holidays = holiday::fetch_holidays(pool, user_id, payment_candidate, payment_candidate).await?;
+    statement_cycle(year, month, settings, &holidays)
+}
+
+async fn statement_total(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+    cycle: &StatementCycle,
+) -> Result<(i64, i64), ApiError> {
+    let totals: (i64, i64) = sqlx::query_as(sql_queries::CARD_STATEMENT_TOTAL)
+        .bind(account_code)
+        .bind(account_code)
+        .bind(account_code)
+        .bind(user_id)
+        .bind(account_code)
+        .bind(account_code)
+        .bind(format_date(cycle.period_start))
+        .bind(format_date(cycle.closing_date))
+        .fetch_one(pool)
+        .await?;
+    Ok(totals)
+}
+
+async fn get_statement(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+    closing_date: &str,
+) -> Result<Option<CardStatement>, ApiError> {
+    let row = sqlx::query_as::<_, StatementRow>(sql_queries::CARD_STATEMENT_GET)
+        .bind(user_id)
+        .bind(account_code)
+        .bind(closing_date)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(CardStatement::from))
+}
+
+/// Statement closing in (year, month) as it would be issued now, without
+/// writing anything. Returns the stored statement's ids if it was issued.
+pub async fn preview_statement(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+    year: i32,
+    month: u32,
+) -> Result<CardStatement, ApiError> {
+    let account_code = account_code.trim().to_uppercase();
+    let settings = get_billing_settings(pool, user_id, &account_code).await?;
+    let cycle = load_cycle(pool, user_id, &settings, year, month).await?;
+    let (total_amount, charge_count) = statement_total(pool, user_id, &account_code, &cycle).await?;
+    let issued = get_statement(pool, user_id, &account_code, &format_date(cycle.closing_date)).await?;
+
+    Ok(CardStatement {
+        statement_id: issued.as_ref().and_then(|s| s.statement_id),
+        account_code,
+        payment_account_code: Some(settings.payment_account_code),
+        period_start: format_date(cycle.period_start),
+        closing_date: format_date(cycle.closing_date),
+        payment_date: format_date(cycle.payment_date),
+        total_amount,
+        charge_count,
+        transaction_id: issued.as_ref().and_then(|s| s.transaction_id),
+        is_confirmed: issued.is_some_and(|s| s.is_confirmed),
+    })
+}
+
+/// Issue (or re-issue) the statement closing in (year, month) and schedule
+/// its payment. Re-issuing updates the still-scheduled payment to the
+/// current total; once the payment is confirmed the statement is frozen.
+/// A zero or negative total removes the scheduled payment instead.
+pub async fn issue_statement(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+    year: i32,
+    month: u32,
+) -> Result<CardStatement, ApiError> {
+    let account_code = account_code.trim().to_uppercase();
+    let settings = get_billing_settings(pool, user_id, &account_code).await?;
+    let cycle = load_cycle(pool, user_id, &settings, year, month).await?;
+    let closing_date = format_date(cycle.closing_date);
+    let payment_datetime = format!("{} 00:00:00", format_date(cycle.payment_date));
+
+    let existing = get_statement(pool, user_id, &account_code, &closing_date).await?;
+    if existing.as_ref().is_some_and(|s| s.is_confirmed) {
+        return Err(ApiError::validation(format!(
+            "The statement closing on {} has already been paid",
+            closing_date
+        )));
+    }
+    let (total_amount, charge_count) = statement_total(pool, user_id, &account_code, &cycle).await?;
+    let currency_code =
+        currency::transaction_currency(pool, user_id, &settings.payment_account_code, &account_code).await?;
+
+    let mut tx = pool.begin().await?;
+    let mut transaction_id = existing.as_ref().and_then(|s| s.transaction_id);
+    if total_amount > 0 {
+        let updated = match transaction_id {
+            Some(id) => {
+                let key = header_audit_key(id);
+                let before = audit::snapshot(&mut tx, &key).await?;
+                let updated = sqlx::query(sql_queries::CARD_PAYMENT_UPDATE)
+                    .bind(&payment_datetime)
+                    .bind(&settings.payment_account_code)
+                    .bind(total_amount)
+                    .bind(id)
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+                audit::record(&mut tx, user_id, &key, before).await?;
+                updated
+            }
+            None => 0,
+        };
+        if updated == 0 {
+            let result = sqlx::query(sql_queries::CARD_PAYMENT_INSERT)
+                .bind(user_id)
+                .bind(&payment_datetime)
+                .bind(&settings.payment_account_code)
+                .bind(&account_code)
+                .bind(total_amount)
+                .bind(&currency_code)
+                .execute(&mut *tx)
+                .await?;
+            let id = result.last_insert_rowid();
+            audit::record(&mut tx, user_id, &header_audit_key(id), None).await?;
+            transaction_id = Some(id);
+        }
+    } else if let Some(id) = transaction_id.take() {
+        let key = header_audit_key(id);
+        let before = audit::snapshot(&mut tx, &key).await?;
+        sqlx::query(sql_queries::CARD_PAYMENT_DELETE)
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        audit::record(&mut tx, user_id, &key, before).await?;
+    }
+
+    match existing.and_then(|s| s.statement_id) {
+        Some(statement_id) => {
+            let key = statement_audit_key(statement_id);
+            let before = audit::snapshot(&mut tx, &key).await?;
+            sqlx::query(sql_queries::CARD_STATEMENT_UPDATE)
+                .bind(format_date(cycle.period_start))
+                .bind(format_date(cycle.payment_date))
+                .bind(total_amount)
+                .bind(charge_count)
+                .bind(transaction_id)
+                .bind(statement_id)
+                .execute(&mut *tx)
+                .await?;
+            audit::record(&mut tx, user_id, &key, before).await?;
+        }
+        None => {
+            let result = sqlx::query(sql_queries::CARD_STATEMENT_INSERT)
+                .bind(user_id)
+                .bind(&account_code)
+                .bind(format_date(cycle.period_start))
+                .bind(&closing_date)
+                .bind(format_date(cycle.payment_date))
+                .bind(total_amount)
+                .bind(charge_count)
+                .bind(transaction_id)
+                .execute(&mut *tx)
+                .await?;
+            let key = statement_audit_key(result.last_insert_rowid());
+            audit::record(&mut tx, user_id, &key, None).await?;
+        }
+    }
+    tx.commit().await?;
+
+    get_statement(pool, user_id, &account_code, &closing_date)
+        .await?
+        .ok_or_else(|| ApiError::not_found("card statement"))
+}
+
+/// Issued statements of one card, newest first
+pub async fn list_statements(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+) -> Result<Vec<CardStatement>, ApiError> {
+    let rows = sqlx::query_as::<_, StatementRow>(sql_queries::CARD_STATEMENT_LIST_BY_ACCOUNT)
+        .bind(user_id)
+        .bind(account_code.trim().to_uppercase())
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(CardStatement::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    fn d(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn settings(closing_day: i64, payment_day: i64, offset: i64) -> CardBillingSettings {
+        CardBillingSettings {
+            account_code: "CARD".to_string(),
+            payment_account_code: "BANK".to_string(),
+            closing_day,
+            payment_day,
+            payment_month_offset: offset,
+            entry_dt: String::new(),
+            update_dt: None,
+        }
+    }
+
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'card', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'INCOME', 2, '収入', datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'TRANSFER', 3, '振替', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, INITIAL_BALANCE) VALUES (2, 'BANK', 'Bank', 'BANK', 100000)",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'CARD', 'Card', 'CREDIT')",
+            // Closes on the 15th, paid on the 10th of the next month
+            "INSERT INTO CARD_BILLING_SETTINGS (USER_ID, ACCOUNT_CODE, PAYMENT_ACCOUNT_CODE, CLOSING_DAY, PAYMENT_DAY, PAYMENT_MONTH_OFFSET) VALUES (2, 'CARD', 'BANK', 15, 10, 1)",
+            "INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 'EXPENSE', 'CARD', 'NONE', '2024-06-15 23:00:00', 999)",
+            "INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 'EXPENSE', 'CARD', 'NONE', '2024-06-16 09:00:00', 3000)",
+            "INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 'EXPENSE', 'CARD', 'NONE', '2024-07-15 09:00:00', 2000)",
+            "INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 'INCOME', 'NONE', 'CARD', '2024-07-01 09:00:00', 500)",
+            // Bank purchase and a scheduled card charge stay out of the statement
+            "INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 'EXPENSE', 'BANK', 'NONE', '2024-07-01 09:00:00', 700)",
+            "INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, IS_SCHEDULED) VALUES (2, 'EXPENSE', 'CARD', 'NONE', '2024-07-02 09:00:00', 800, 1)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[test]
+    fn cycle_runs_from_day_after_previous_closing() {
+        let cycle = statement_cycle(2024, 7, &settings(15, 10, 1), &HashSet::new()).unwrap();
+        assert_eq!(cycle.period_start, d(2024, 6, 16));
+        assert_eq!(cycle.closing_date, d(2024, 7, 15));
+        // 2024-08-10 is a Saturday
+        assert_eq!(cycle.payment_date, d(2024, 8, 12));
+    }
+
+    #[test]
+    fn end_of_month_closing_and_holiday_shift() {
+        let holidays: HashSet<NaiveDate> = [d(2024, 4, 29)].into_iter().collect();
+        let cycle = statement_cycle(2024, 2, &settings(31, 27, 2), &holidays).unwrap();
+        assert_eq!(cycle.period_start, d(2024, 2, 1));
+        assert_eq!(cycle.closing_date, d(2024, 2, 29));
+        // 04-27 Sat, 04-28 Sun, 04-29 holiday
+        assert_eq!(cycle.payment_date, d(2024, 4, 30));
+    }
+
+    #[test]
+    fn cycle_wraps_year() {
+        let cycle = statement_cycle(2025, 1, &settings(10, 5, 1), &HashSet::new()).unwrap();
+        assert_eq!(cycle.period_start, d(2024, 12, 11));
+        assert_eq!(cycle.closing_date, d(2025, 1, 10));
+        assert_eq!(cycle.payment_date, d(2025, 2, 5));
+    }
+
+    #[tokio::test]
+    async fn issue_schedules_transfer_and_reissue_updates_it() {
+        let pool = setup().await;
+
+        let preview = preview_statement(&pool, 2, "card", 2024, 7).await.unwrap();
+        assert_eq!(preview.statement_id, None);
+        assert_eq!(preview.total_amount, 3000 + 2000 - 500);
+        assert_eq!(preview.charge_count, 2);
+
+        let issued = issue_statement(&pool, 2, "CARD", 2024, 7).await.unwrap();
+        assert_eq!(issued.total_amount, 4500);
+        // 08-10 Sat, 08-11 Sun, 08-12 substitute holiday in the seeded calendar
+        assert_eq!(issued.payment_date, "2024-08-13");
+        assert!(!issued.is_confirmed);
+        let payment_id = issued.transaction_id.unwrap();
+        let payment: (String, String, String, i64, i64) = sqlx::query_as(
+            "SELECT CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TOTAL_AMOUNT, IS_SCHEDULED \
+             FROM TRANSACTIONS_HEADER WHERE TRANSACTION_ID = ?",
+        )
+        .bind(payment_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(payment, ("TRANSFER".into(), "BANK".into(), "CARD".into(), 4500, 1));
+
+        // A late entry in the cycle: re-issuing reuses the scheduled row
+        sqlx::query(
+            "INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) \
+             VALUES (2, 'EXPENSE', 'CARD', 'NONE', '2024-07-10 09:00:00', 100)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let reissued = issue_statement(&pool, 2, "CARD", 2024, 7).await.unwrap();
+        assert_eq!(reissued.statement_id, issued.statement_id);
+        assert_eq!(reissued.transaction_id, Some(payment_id));
+        assert_eq!(reissued.total_amount, 4600);
+        assert_eq!(list_statements(&pool, 2, "CARD").await.unwrap().len(), 1);
+
+        // Payment and statement are audited on both issues
+        let audited: Vec<(String, String)> =
+            sqlx::query_as("SELECT ENTITY, ACTION FROM AUDIT_LOG WHERE USER_ID = 2 ORDER BY AUDIT_ID")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            audited,
+            [
+                ("TRANSACTIONS_HEADER".into(), "INSERT".into()),
+                ("CARD_STATEMENTS".into(), "INSERT".into()),
+                ("TRANSACTIONS_HEADER".into(), "UPDATE".into()),
+                ("CARD_STATEMENTS".into(), "UPDATE".into()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn confirmed_statement_cannot_be_reissued() {
+        let pool = setup().await;
+        let issued = issue_statement(&pool, 2, "CARD", 2024, 7).await.unwrap();
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET IS_SCHEDULED = 0 WHERE TRANSACTION_ID = ?")
+            .bind(issued.transaction_id.unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(list_statements(&pool, 2, "CARD").await.unwrap()[0].is_confirmed);
+        let err = issue_statement(&pool, 2, "CARD", 2024, 7).await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+    }
+
+    #[tokio::test]
+    async fn set_billing_settings_requires_liability_card() {
+        let pool = setup().await;
+
+        let err = set_billing_settings(
+            &pool,
+            2,
+            SetCardBillingRequest {
+                account_code: "BANK".to_string(),
+                payment_account_code: "BANK".to_string(),
+                closing_day: 15,
+                payment_day: 10,
+                payment_month_offset: 1,
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+
+        let saved = set_billing_settings(
+            &pool,
+            2,
+            SetCardBillingRequest {
+                account_code: "card".to_string(),
+                payment_account_code: "bank".to_string(),
+                closing_day: 31,
+                payment_day: 27,
+                payment_month_offset: 2,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(saved.closing_day, 31);
+        assert_eq!(list_billing_settings(&pool, 2).await.unwrap().len(), 1);
+    }
+}
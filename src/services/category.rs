@@ -1,10 +1,48 @@
 use sqlx::sqlite::SqlitePool;
 use sqlx::Row;
 use crate::api_error::ApiError;
+use crate::services::audit::{self, AuditKey};
 use crate::{sql_queries, consts};
 
 const ENTITY_LABEL: &str = "Category";
 
+/// Audit key of a CATEGORY2 row, or of one of its CATEGORY2_I18N rows when
+/// `lang_code` is given.
+fn category2_audit_key(
+    user_id: i64,
+    category1_code: &str,
+    category2_code: &str,
+    lang_code: Option<&str>,
+) -> AuditKey {
+    let table = if lang_code.is_some() { "CATEGORY2_I18N" } else { "CATEGORY2" };
+    let key = AuditKey::new(table, "USER_ID", user_id)
+        .and("CATEGORY1_CODE", category1_code)
+        .and("CATEGORY2_CODE", category2_code);
+    match lang_code {
+        Some(lang) => key.and("LANG_CODE", lang),
+        None => key,
+    }
+}
+
+/// Same as [`category2_audit_key`] for CATEGORY3 / CATEGORY3_I18N.
+fn category3_audit_key(
+    user_id: i64,
+    category1_code: &str,
+    category2_code: &str,
+    category3_code: &str,
+    lang_code: Option<&str>,
+) -> AuditKey {
+    let table = if lang_code.is_some() { "CATEGORY3_I18N" } else { "CATEGORY3" };
+    let key = AuditKey::new(table, "USER_ID", user_id)
+        .and("CATEGORY1_CODE", category1_code)
+        .and("CATEGORY2_CODE", category2_code)
+        .and("CATEGORY3_CODE", category3_code);
+    match lang_code {
+        Some(lang) => key.and("LANG_CODE", lang),
+        None => key,
+    }
+}
+
 #[derive(Debug)]
 pub enum CategoryError {
     DatabaseError(sqlx::Error),
@@ -226,6 +264,14 @@ impl CategoryService {
 
         if let Some(sibling_code) = sibling_code {
             let mut tx = self.pool.begin().await?;
+            let keys = [
+                category2_audit_key(user_id, category1_code, category2_code, None),
+                category2_audit_key(user_id, category1_code, &sibling_code, None),
+            ];
+            let mut befores = Vec::with_capacity(keys.len());
+            for key in &keys {
+                befores.push(audit::snapshot(&mut tx, key).await?);
+            }
             // Move current to target.
             sqlx::query(sql_queries::CATEGORY2_UPDATE_ORDER)
                 .bind(target_order)
@@ -242,6 +288,9 @@ impl CategoryService {
                 .bind(&sibling_code)
                 .execute(&mut *tx)
                 .await?;
+            for (key, before) in keys.iter().zip(befores) {
+                audit::record(&mut tx, user_id, key, before).await?;
+            }
             tx.commit().await?;
         }
 
@@ -277,6 +326,14 @@ impl CategoryService {
 
         if let Some(sibling_code) = sibling_code {
             let mut tx = self.pool.begin().await?;
+            let keys = [
+                category3_audit_key(user_id, category1_code, category2_code, category3_code, None),
+                category3_audit_key(user_id, category1_code, category2_code, &sibling_code, None),
+            ];
+            let mut befores = Vec::with_capacity(keys.len());
+            for key in &keys {
+                befores.push(audit::snapshot(&mut tx, key).await?);
+            }
             sqlx::query(sql_queries::CATEGORY3_UPDATE_ORDER)
                 .bind(target_order)
                 .bind(user_id)
@@ -293,6 +350,9 @@ impl CategoryService {
                 .bind(&sibling_code)
                 .execute(&mut *tx)
                 .await?;
+            for (key, before) in keys.iter().zip(befores) {
+                audit::record(&mut tx, user_id, key, before).await?;
+            }
             tx.commit().await?;
         }
 
@@ -586,12 +646,17 @@ impl CategoryService {
         category1_code: &str,
         category2_code: &str,
     ) -> Result<(), CategoryError> {
+        let mut tx = self.pool.begin().await?;
+        let key = category2_audit_key(user_id, category1_code, category2_code, None);
+        let before = audit::snapshot(&mut tx, &key).await?;
         sqlx::query(sql_queries::CATEGORY2_ENABLE)
             .bind(user_id)
             .bind(category1_code)
             .bind(category2_code)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -603,13 +668,18 @@ impl CategoryService {
         category2_code: &str,
         category3_code: &str,
     ) -> Result<(), CategoryError> {
+        let mut tx = self.pool.begin().await?;
+        let key = category3_audit_key(user_id, category1_code, category2_code, category3_code, None);
+        let before = audit::snapshot(&mut tx, &key).await?;
         sqlx::query(sql_queries::CATEGORY3_ENABLE)
             .bind(user_id)
             .bind(category1_code)
             .bind(category2_code)
             .bind(category3_code)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -655,6 +725,8 @@ impl CategoryService {
         let max_order: i64 = max_order_row.get("max_order");
         let new_order = max_order + 1;
         
+        let mut tx = self.pool.begin().await?;
+
         // Insert category2
         sqlx::query(sql_queries::CATEGORY2_INSERT)
             .bind(user_id)
@@ -662,7 +734,7 @@ impl CategoryService {
             .bind(&category2_code)
             .bind(new_order)
             .bind(category2_name_en) // Default name (English)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         
         // Insert i18n records
@@ -672,7 +744,7 @@ impl CategoryService {
             .bind(&category2_code)
             .bind("ja")
             .bind(category2_name_ja)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         
         sqlx::query(sql_queries::CATEGORY2_I18N_INSERT)
@@ -681,8 +753,14 @@ impl CategoryService {
             .bind(&category2_code)
             .bind("en")
             .bind(category2_name_en)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+
+        for lang_code in [None, Some("ja"), Some("en")] {
+            let key = category2_audit_key(user_id, category1_code, &category2_code, lang_code);
+            audit::record(&mut tx, user_id, &key, None).await?;
+        }
+        tx.commit().await?;
         
         Ok(category2_code)
     }
@@ -735,6 +813,8 @@ impl CategoryService {
         let max_order: i64 = max_order_row.get("max_order");
         let new_order = max_order + 1;
         
+        let mut tx = self.pool.begin().await?;
+
         // Insert category3
         sqlx::query(sql_queries::CATEGORY3_INSERT)
             .bind(user_id)
@@ -743,7 +823,7 @@ impl CategoryService {
             .bind(&category3_code)
             .bind(new_order)
             .bind(category3_name_en) // Default name (English)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         
         // Insert i18n records
@@ -754,7 +834,7 @@ impl CategoryService {
             .bind(&category3_code)
             .bind("ja")
             .bind(category3_name_ja)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         
         sqlx::query(sql_queries::CATEGORY3_I18N_INSERT)
@@ -764,8 +844,20 @@ impl CategoryService {
             .bind(&category3_code)
             .bind("en")
             .bind(category3_name_en)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+
+        for lang_code in [None, Some("ja"), Some("en")] {
+            let key = category3_audit_key(
+                user_id,
+                category1_code,
+                category2_code,
+                &category3_code,
+                lang_code,
+            );
+            audit::record(&mut tx, user_id, &key, None).await?;
+        }
+        tx.commit().await?;
         
         Ok(category3_code)
     }
@@ -847,6 +939,16 @@ impl CategoryService {
         )
         .await?;
 
+        let mut tx = self.pool.begin().await?;
+        let keys = [
+            category2_audit_key(user_id, category1_code, category2_code, Some("ja")),
+            category2_audit_key(user_id, category1_code, category2_code, Some("en")),
+        ];
+        let mut befores = Vec::with_capacity(keys.len());
+        for key in &keys {
+            befores.push(audit::snapshot(&mut tx, key).await?);
+        }
+
         // Update Japanese name
         sqlx::query(sql_queries::CATEGORY2_I18N_UPDATE)
             .bind(name_ja)
@@ -854,7 +956,7 @@ impl CategoryService {
             .bind(category1_code)
             .bind(category2_code)
             .bind("ja")
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         
         // Update English name
@@ -864,8 +966,13 @@ impl CategoryService {
             .bind(category1_code)
             .bind(category2_code)
             .bind("en")
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+
+        for (key, before) in keys.iter().zip(befores) {
+            audit::record(&mut tx, user_id, key, before).await?;
+        }
+        tx.commit().await?;
         
         Ok(())
     }
@@ -895,6 +1002,16 @@ impl CategoryService {
         )
         .await?;
 
+        let mut tx = self.pool.begin().await?;
+        let keys = [
+            category3_audit_key(user_id, category1_code, category2_code, category3_code, Some("ja")),
+            category3_audit_key(user_id, category1_code, category2_code, category3_code, Some("en")),
+        ];
+        let mut befores = Vec::with_capacity(keys.len());
+        for key in &keys {
+            befores.push(audit::snapshot(&mut tx, key).await?);
+        }
+
         // Update Japanese name
         sqlx::query(sql_queries::CATEGORY3_I18N_UPDATE)
             .bind(name_ja)
@@ -903,7 +1020,7 @@ impl CategoryService {
             .bind(category2_code)
             .bind(category3_code)
             .bind("ja")
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         
         // Update English name
@@ -914,8 +1031,13 @@ impl CategoryService {
             .bind(category2_code)
             .bind(category3_code)
             .bind("en")
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+
+        for (key, before) in keys.iter().zip(befores) {
+            audit::record(&mut tx, user_id, key, before).await?;
+        }
+        tx.commit().await?;
         
         Ok(())
     }
@@ -996,6 +1118,8 @@ impl CategoryService {
         category2_code: &str,
     ) -> Result<(), CategoryError> {
         let mut tx = self.pool.begin().await?;
+        let key = category2_audit_key(user_id, category1_code, category2_code, None);
+        let before = audit::snapshot(&mut tx, &key).await?;
 
         // Disable all child CATEGORY3 entries (may be zero — that is fine)
         sqlx::query(sql_queries::CATEGORY3_DISABLE_BY_CATEGORY2)
@@ -1017,6 +1141,7 @@ impl CategoryService {
             return Err(CategoryError::NotFound);
         }
 
+        audit::record(&mut tx, user_id, &key, before).await?;
         tx.commit().await?;
         Ok(())
     }
@@ -1033,18 +1158,23 @@ impl CategoryService {
         category2_code: &str,
         category3_code: &str,
     ) -> Result<(), CategoryError> {
+        let mut tx = self.pool.begin().await?;
+        let key = category3_audit_key(user_id, category1_code, category2_code, category3_code, None);
+        let before = audit::snapshot(&mut tx, &key).await?;
         let result = sqlx::query(sql_queries::CATEGORY3_DELETE_LOGICAL)
             .bind(user_id)
             .bind(category1_code)
             .bind(category2_code)
             .bind(category3_code)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(CategoryError::NotFound);
         }
 
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
         Ok(())
     }
 }
@@ -1078,6 +1208,11 @@ mod tests {
     async fn setup_test_db() -> SqlitePool {
         let pool = init_db(TEST_DB_URL).await.unwrap();
 
+        sqlx::query(sql_queries::CREATE_AUDIT_LOG_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
+
         // Read and execute DDL from dbaccess.sql
         let sql_content = std::fs::read_to_string("res/sql/dbaccess.sql")
             .expect("Failed to read dbaccess.sql");
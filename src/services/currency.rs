@@ -0,0 +1,483 @@
+//! Currencies and exchange rates.
+//!
+//! Every amount column stays an `INTEGER` in the minor unit of its currency
+//! (yen for JPY, cents for USD/EUR). An account has one currency, and a
+//! transaction is booked in the currency of the real accounts it touches, so
+//! per-account balances never mix currencies. Totals across accounts and the
+//! aggregation screens are converted into the user's base currency
+//! (`USERS.BASE_CURRENCY_CODE`) with the latest `EXCHANGE_RATES` row dated on
+//! or before the day being converted. Without such a row an amount has no
+//! base value: SQL sums skip it and `RateTable::to_base` returns `None`.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::services::transaction::parse_csv_records;
+use crate::sql_queries;
+
+const ENTITY_LABEL: &str = "exchange rate";
+
+/// ISO 4217 code with its number of minor-unit digits
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct CurrencyInfo {
+    pub code: &'static str,
+    pub minor_units: u32,
+}
+
+/// Currencies accounts can be opened in
+pub const SUPPORTED_CURRENCIES: &[CurrencyInfo] = &[
+    CurrencyInfo { code: "JPY", minor_units: 0 },
+    CurrencyInfo { code: "USD", minor_units: 2 },
+    CurrencyInfo { code: "EUR", minor_units: 2 },
+    CurrencyInfo { code: "GBP", minor_units: 2 },
+    CurrencyInfo { code: "CHF", minor_units: 2 },
+    CurrencyInfo { code: "CAD", minor_units: 2 },
+    CurrencyInfo { code: "AUD", minor_units: 2 },
+    CurrencyInfo { code: "CNY", minor_units: 2 },
+    CurrencyInfo { code: "HKD", minor_units: 2 },
+    CurrencyInfo { code: "TWD", minor_units: 2 },
+    CurrencyInfo { code: "SGD", minor_units: 2 },
+    CurrencyInfo { code: "THB", minor_units: 2 },
+    CurrencyInfo { code: "KRW", minor_units: 0 },
+];
+
+pub fn currency_info(code: &str) -> Option<CurrencyInfo> {
+    SUPPORTED_CURRENCIES.iter().copied().find(|c| c.code == code)
+}
+
+/// Trim and uppercase `code`, rejecting currencies outside
+/// `SUPPORTED_CURRENCIES`.
+pub fn normalize_currency_code(code: &str) -> Result<String, ApiError> {
+    let code = code.trim().to_uppercase();
+    if currency_info(&code).is_none() {
+        return Err(ApiError::validation(format!("Unsupported currency: {}", code)));
+    }
+    Ok(code)
+}
+
+/// Minor-unit digits of `code`; unknown codes are treated like JPY.
+pub fn minor_units(code: &str) -> u32 {
+    currency_info(code).map_or(0, |c| c.minor_units)
+}
+
+/// Scale a major-unit decimal string ("12.34") to minor units of a
+/// currency with `minor_units` digits. Digits past the minor unit are only
+/// accepted when they are zeros ("1200.00" is 1200 yen).
+pub fn parse_minor_amount(digits: &str, minor_units: u32) -> Option<i64> {
+    let (integer_part, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer_part.is_empty()
+        || !integer_part.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let kept = fraction.len().min(minor_units as usize);
+    if !fraction[kept..].chars().all(|c| c == '0') {
+        return None;
+    }
+    let scale = 10_i64.pow(minor_units);
+    let whole: i64 = integer_part.parse().ok()?;
+    let cents: i64 = if kept == 0 {
+        0
+    } else {
+        fraction[..kept].parse::<i64>().ok()? * 10_i64.pow(minor_units - kept as u32)
+    };
+    whole.checked_mul(scale)?.checked_add(cents)
+}
+
+/// `rate` (base units per one unit of `currency`) expressed as base minor
+/// units per one minor unit of `currency`. 150 JPY per USD becomes 1.5
+/// yen per cent.
+pub fn minor_rate(rate: f64, currency: &str, base_currency: &str) -> f64 {
+    let shift = minor_units(base_currency) as i32 - minor_units(currency) as i32;
+    rate * 10_f64.powi(shift)
+}
+
+/// Round half away from zero, the same as SQLite's `ROUND()`.
+pub fn apply_minor_rate(amount: i64, minor_rate: f64) -> i64 {
+    (amount as f64 * minor_rate).round() as i64
+}
+
+/// SQL expression converting `amount` (minor units of header `alias`'s
+/// currency) into the user's base currency on the header's date. Evaluates
+/// to NULL when no rate is on file, so `SUM()` leaves the row out.
+pub fn base_amount_sql(amount: &str, alias: &str) -> String {
+    format!(
+        "(CASE WHEN {a}.CURRENCY_CODE = (SELECT bu.BASE_CURRENCY_CODE FROM USERS bu WHERE bu.USER_ID = {a}.USER_ID) \
+         THEN {amount} \
+         ELSE CAST(ROUND({amount} * (\
+             SELECT er.MINOR_RATE FROM EXCHANGE_RATES er \
+             JOIN USERS eu ON eu.USER_ID = er.USER_ID AND eu.BASE_CURRENCY_CODE = er.BASE_CURRENCY_CODE \
+             WHERE er.USER_ID = {a}.USER_ID AND er.CURRENCY_CODE = {a}.CURRENCY_CODE \
+               AND er.RATE_DATE <= DATE({a}.TRANSACTION_DATE) \
+             ORDER BY er.RATE_DATE DESC LIMIT 1)) AS INTEGER) END)",
+        a = alias,
+        amount = amount,
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ExchangeRate {
+    pub rate_id: i64,
+    pub currency_code: String,
+    pub base_currency_code: String,
+    /// `YYYY-MM-DD`; the rate applies from this day until the next row
+    pub rate_date: String,
+    /// Base-currency units per one unit of `currency_code`
+    pub rate: f64,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SetExchangeRateRequest {
+    pub currency_code: String,
+    pub rate_date: String,
+    pub rate: f64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExchangeRateImportSummary {
+    pub imported_count: usize,
+}
+
+/// Rates against the user's base currency, loaded once for conversions done
+/// in Rust (account totals, balance history).
+#[derive(Debug, Clone)]
+pub struct RateTable {
+    base_currency: String,
+    /// Per currency, `(rate_date, minor_rate)` in ascending date order
+    rates: HashMap<String, Vec<(String, f64)>>,
+}
+
+impl RateTable {
+    pub async fn load(pool: &SqlitePool, user_id: i64) -> Result<Self, ApiError> {
+        let base_currency = get_base_currency(pool, user_id).await?;
+        let rows: Vec<(String, String, f64)> =
+            sqlx::query_as(sql_queries::EXCHANGE_RATE_LIST_FOR_CONVERSION)
+                .bind(user_id)
+                .bind(&base_currency)
+                .fetch_all(pool)
+                .await?;
+
+        let mut rates: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+        for (currency, date, rate) in rows {
+            rates.entry(currency).or_default().push((date, rate));
+        }
+        Ok(RateTable { base_currency, rates })
+    }
+
+    pub fn base_currency(&self) -> &str {
+        &self.base_currency
+    }
+
+    /// Convert `amount` in `currency` to the base currency with the latest
+    /// rate dated on or before `date` (`YYYY-MM-DD`).
+    pub fn to_base(&self, amount: i64, currency: &str, date: &str) -> Option<i64> {
+        if currency == self.base_currency {
+            return Some(amount);
+        }
+        let series = self.rates.get(currency)?;
+        let index = series.partition_point(|(rate_date, _)| rate_date.as_str() <= date);
+        let (_, rate) = series.get(index.checked_sub(1)?)?;
+        Some(apply_minor_rate(amount, *rate))
+    }
+}
+
+pub async fn get_base_currency(pool: &SqlitePool, user_id: i64) -> Result<String, ApiError> {
+    let code: String = sqlx::query_scalar(sql_queries::USER_GET_BASE_CURRENCY)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(code)
+}
+
+/// Change the currency totals are reported in. Rates stored against the
+/// previous base stay on file and apply again if it is switched back.
+pub async fn set_base_currency(
+    pool: &SqlitePool,
+    user_id: i64,
+    currency_code: &str,
+) -> Result<String, ApiError> {
+    let code = normalize_currency_code(currency_code)?;
+    sqlx::query(sql_queries::USER_SET_BASE_CURRENCY)
+        .bind(&code)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(code)
+}
+
+/// Currency a header moving money between `from_account_code` and
+/// `to_account_code` must be booked in: the currency of the real (non-NONE)
+/// accounts involved, or the base currency when both sides are NONE. A
+/// transfer between accounts of different currencies is refused; record the
+/// exchange as two transfers through NONE instead.
+pub async fn transaction_currency(
+    pool: &SqlitePool,
+    user_id: i64,
+    from_account_code: &str,
+    to_account_code: &str,
+) -> Result<String, ApiError> {
+    let rows: Vec<(String, String)> = sqlx::query_as(sql_queries::ACCOUNT_CURRENCIES_FOR_CODES)
+        .bind(user_id)
+        .bind(from_account_code)
+        .bind(to_account_code)
+        .fetch_all(pool)
+        .await?;
+
+    match rows.as_slice() {
+        [] => get_base_currency(pool, user_id).await,
+        [(_, currency)] => Ok(currency.clone()),
+        [(code_a, currency_a), (code_b, currency_b), ..] => {
+            if currency_a != currency_b {
+                return Err(ApiError::validation(format!(
+                    "Accounts {} ({}) and {} ({}) use different currencies",
+                    code_a, currency_a, code_b, currency_b
+                )));
+            }
+            Ok(currency_a.clone())
+        }
+    }
+}
+
+pub async fn list_exchange_rates(
+    pool: &SqlitePool,
+    user_id: i64,
+    currency_code: Option<&str>,
+) -> Result<Vec<ExchangeRate>, ApiError> {
+    let currency_code = currency_code.map(|c| c.trim().to_uppercase());
+    let rates = sqlx::query_as::<_, ExchangeRate>(sql_queries::EXCHANGE_RATE_LIST)
+        .bind(user_id)
+        .bind(&currency_code)
+        .bind(&currency_code)
+        .fetch_all(pool)
+        .await?;
+    Ok(rates)
+}
+
+fn parse_rate_date(text: &str) -> Result<NaiveDate, String> {
+    let text = text.trim();
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(text, "%Y/%m/%d"))
+        .map_err(|_| format!("Invalid date: {}", text))
+}
+
+/// Validate one rate against `base_currency`, returning the values to store
+/// as `(currency_code, rate_date, rate, minor_rate)`.
+fn validate_rate(
+    base_currency: &str,
+    currency_code: &str,
+    rate_date: &str,
+    rate: f64,
+) -> Result<(String, String, f64, f64), String> {
+    let currency_code = normalize_currency_code(currency_code).map_err(|e| e.message)?;
+    if currency_code == base_currency {
+        return Err(format!("{} is the base currency", currency_code));
+    }
+    let date = parse_rate_date(rate_date)?;
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err(format!("Rate must be a positive number (got {})", rate));
+    }
+    let minor = minor_rate(rate, &currency_code, base_currency);
+    Ok((currency_code, date.format("%Y-%m-%d").to_string(), rate, minor))
+}
+
+/// Store the rate of one currency for one day against the current base
+/// currency, replacing an existing rate for the same day.
+pub async fn set_exchange_rate(
+    pool: &SqlitePool,
+    user_id: i64,
+    request: SetExchangeRateRequest,
+) -> Result<(), ApiError> {
+    let base_currency = get_base_currency(pool, user_id).await?;
+    let (currency_code, rate_date, rate, minor) =
+        validate_rate(&base_currency, &request.currency_code, &request.rate_date, request.rate)
+            .map_err(ApiError::validation)?;
+
+    sqlx::query(sql_queries::EXCHANGE_RATE_UPSERT)
+        .bind(user_id)
+        .bind(&currency_code)
+        .bind(&base_currency)
+        .bind(&rate_date)
+        .bind(rate)
+        .bind(minor)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_exchange_rate(
+    pool: &SqlitePool,
+    user_id: i64,
+    rate_id: i64,
+) -> Result<(), ApiError> {
+    let affected = sqlx::query(sql_queries::EXCHANGE_RATE_DELETE)
+        .bind(rate_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    Ok(())
+}
+
+/// Import rates from CSV text with one `date,currency,rate` record per line
+/// (dates as `YYYY-MM-DD` or `YYYY/MM/DD`). A first line that does not start
+/// with a date is taken as a header. Any bad line fails the whole import,
+/// which otherwise runs in one SQL transaction.
+pub async fn import_exchange_rates_csv(
+    pool: &SqlitePool,
+    user_id: i64,
+    csv_text: &str,
+) -> Result<ExchangeRateImportSummary, ApiError> {
+    let base_currency = get_base_currency(pool, user_id).await?;
+    let records = parse_csv_records(csv_text, ',').map_err(ApiError::validation)?;
+
+    let mut rows = Vec::new();
+    for (index, (line_number, fields)) in records.into_iter().enumerate() {
+        if fields.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        let first = fields.first().map(String::as_str).unwrap_or("");
+        if index == 0 && parse_rate_date(first).is_err() {
+            continue;
+        }
+        let [date, currency, rate] = fields.as_slice() else {
+            return Err(ApiError::validation(format!(
+                "Line {}: expected date,currency,rate",
+                line_number
+            )));
+        };
+        let rate: f64 = rate
+            .trim()
+            .parse()
+            .map_err(|_| ApiError::validation(format!("Line {}: invalid rate: {}", line_number, rate.trim())))?;
+        let row = validate_rate(&base_currency, currency, date, rate)
+            .map_err(|e| ApiError::validation(format!("Line {}: {}", line_number, e)))?;
+        rows.push(row);
+    }
+
+    let mut tx = pool.begin().await?;
+    for (currency_code, rate_date, rate, minor) in &rows {
+        sqlx::query(sql_queries::EXCHANGE_RATE_UPSERT)
+            .bind(user_id)
+            .bind(currency_code)
+            .bind(&base_currency)
+            .bind(rate_date)
+            .bind(rate)
+            .bind(minor)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(ExchangeRateImportSummary { imported_count: rows.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'fx', 'x', 1, datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'BANK', 'Bank', 'BANK')",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, CURRENCY_CODE) VALUES (2, 'USBANK', 'US Bank', 'BANK', 'USD')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[test]
+    fn parse_minor_amount_scales_by_currency() {
+        assert_eq!(parse_minor_amount("12.34", 2), Some(1234));
+        assert_eq!(parse_minor_amount("12.3", 2), Some(1230));
+        assert_eq!(parse_minor_amount("12", 2), Some(1200));
+        assert_eq!(parse_minor_amount("1200.00", 0), Some(1200));
+        assert_eq!(parse_minor_amount("12.345", 2), None);
+        assert_eq!(parse_minor_amount("12.5", 0), None);
+        assert_eq!(parse_minor_amount(".5", 2), None);
+    }
+
+    #[test]
+    fn minor_rate_accounts_for_minor_units() {
+        // 150.25 JPY per USD is 1.5025 yen per cent
+        assert!((minor_rate(150.25, "USD", "JPY") - 1.5025).abs() < 1e-12);
+        // 0.0065 USD per JPY is 0.65 cents per yen
+        assert!((minor_rate(0.0065, "JPY", "USD") - 0.65).abs() < 1e-12);
+        // Half a yen rounds away from zero
+        assert_eq!(apply_minor_rate(1999, 1.5), 2999);
+        assert_eq!(apply_minor_rate(-1999, 1.5), -2999);
+    }
+
+    #[tokio::test]
+    async fn rate_table_uses_latest_rate_on_or_before_date() {
+        let pool = setup().await;
+        for (date, rate) in [("2024-01-01", 140.0), ("2024-02-01", 150.0)] {
+            set_exchange_rate(
+                &pool,
+                2,
+                SetExchangeRateRequest { currency_code: "usd".into(), rate_date: date.into(), rate },
+            )
+            .await
+            .unwrap();
+        }
+
+        let table = RateTable::load(&pool, 2).await.unwrap();
+        assert_eq!(table.base_currency(), "JPY");
+        assert_eq!(table.to_base(1000, "JPY", "2023-01-01"), Some(1000));
+        assert_eq!(table.to_base(10_000, "USD", "2023-12-31"), None);
+        assert_eq!(table.to_base(10_000, "USD", "2024-01-31"), Some(14_000));
+        assert_eq!(table.to_base(10_000, "USD", "2024-02-01"), Some(15_000));
+        assert_eq!(table.to_base(100, "EUR", "2024-02-01"), None);
+    }
+
+    #[tokio::test]
+    async fn csv_import_is_all_or_nothing() {
+        let pool = setup().await;
+
+        let err = import_exchange_rates_csv(&pool, 2, "2024-01-01,USD,148.5\n2024-01-02,XXX,1\n")
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("Line 2:"), "{}", err.message);
+        assert!(list_exchange_rates(&pool, 2, None).await.unwrap().is_empty());
+
+        let summary = import_exchange_rates_csv(
+            &pool,
+            2,
+            "date,currency,rate\r\n2024/01/01,USD,148.5\r\n2024-01-01,EUR,160.1\r\n\r\n2024-01-01,USD,149\r\n",
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.imported_count, 3);
+        let usd = list_exchange_rates(&pool, 2, Some("usd")).await.unwrap();
+        assert_eq!(usd.len(), 1);
+        assert_eq!(usd[0].rate, 149.0);
+    }
+
+    #[tokio::test]
+    async fn transaction_currency_follows_real_accounts() {
+        let pool = setup().await;
+
+        assert_eq!(transaction_currency(&pool, 2, "USBANK", "NONE").await.unwrap(), "USD");
+        assert_eq!(transaction_currency(&pool, 2, "NONE", "NONE").await.unwrap(), "JPY");
+        let err = transaction_currency(&pool, 2, "BANK", "USBANK").await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+
+        set_base_currency(&pool, 2, "usd").await.unwrap();
+        assert_eq!(transaction_currency(&pool, 2, "NONE", "NONE").await.unwrap(), "USD");
+        assert!(set_base_currency(&pool, 2, "XYZ").await.is_err());
+    }
+}
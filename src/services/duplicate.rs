@@ -0,0 +1,431 @@
+//! Duplicate-transaction report.
+//!
+//! The usual cause is one receipt keyed in twice, so candidates are confirmed
+//! headers on the same category and accounts whose dates and totals are
+//! close. SQL narrows the ledger to those pairs; each pair is then scored on
+//! amount, date distance and how alike the detail item names are, and pairs
+//! above the threshold are joined into groups (A~B and B~C make one group).
+//!
+//! Dismissing a group stores each of its pairs in DUPLICATE_DISMISSALS, and
+//! dismissed pairs never come back out of the SQL. A later entry that looks
+//! like a member of a dismissed group still forms a new pair and is reported.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::sql_queries;
+
+const AMOUNT_WEIGHT: f64 = 0.4;
+const DATE_WEIGHT: f64 = 0.3;
+const ITEM_WEIGHT: f64 = 0.3;
+/// Item score when either side has no detail lines: a quick header-only entry
+/// is a common half of a double entry, so it neither helps nor hurts.
+const NO_ITEMS_SCORE: f64 = 0.5;
+const MAX_DAYS_APART_LIMIT: i64 = 31;
+const MAX_TOLERANCE_PERCENT: i64 = 50;
+const ITEM_NAME_SEPARATOR: char = '\u{1f}';
+
+/// Knobs for [`find_duplicate_groups`]. Missing fields take the defaults.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DuplicateScanOptions {
+    /// Largest gap in calendar days between two entries of one receipt.
+    pub max_days_apart: i64,
+    /// Largest difference between totals, as a percentage of the larger.
+    pub amount_tolerance_percent: i64,
+    /// Pairs scoring below this (0.0-1.0) are not reported.
+    pub min_score: f64,
+}
+
+impl Default for DuplicateScanOptions {
+    fn default() -> Self {
+        Self {
+            max_days_apart: 3,
+            amount_tolerance_percent: 1,
+            min_score: 0.7,
+        }
+    }
+}
+
+impl DuplicateScanOptions {
+    fn validate(&self) -> Result<(), ApiError> {
+        if !(0..=MAX_DAYS_APART_LIMIT).contains(&self.max_days_apart) {
+            return Err(ApiError::validation(format!(
+                "Days apart must be between 0 and {}",
+                MAX_DAYS_APART_LIMIT
+            )));
+        }
+        if !(0..=MAX_TOLERANCE_PERCENT).contains(&self.amount_tolerance_percent) {
+            return Err(ApiError::validation(format!(
+                "Amount tolerance must be between 0 and {} percent",
+                MAX_TOLERANCE_PERCENT
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.min_score) {
+            return Err(ApiError::validation("Minimum score must be between 0 and 1"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateCandidate {
+    pub transaction_id: i64,
+    pub transaction_date: String,
+    pub category1_code: String,
+    pub from_account_code: String,
+    pub to_account_code: String,
+    pub total_amount: i64,
+    pub shop_name: Option<String>,
+    pub item_names: Vec<String>,
+}
+
+/// Transactions that look like entries of the same receipt. `score` is the
+/// best pair score inside the group.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateGroup {
+    pub score: f64,
+    /// Oldest first.
+    pub transactions: Vec<DuplicateCandidate>,
+}
+
+#[derive(FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+struct CandidatePairRow {
+    transaction_id_low: i64,
+    transaction_id_high: i64,
+}
+
+#[derive(FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+struct CandidateRow {
+    transaction_id: i64,
+    transaction_date: String,
+    category1_code: String,
+    from_account_code: String,
+    to_account_code: String,
+    total_amount: i64,
+    shop_name: Option<String>,
+    item_names: Option<String>,
+}
+
+impl From<CandidateRow> for DuplicateCandidate {
+    fn from(row: CandidateRow) -> Self {
+        let mut item_names: Vec<String> = row
+            .item_names
+            .map(|s| s.split(ITEM_NAME_SEPARATOR).map(str::to_string).collect())
+            .unwrap_or_default();
+        item_names.sort();
+        DuplicateCandidate {
+            transaction_id: row.transaction_id,
+            transaction_date: row.transaction_date,
+            category1_code: row.category1_code,
+            from_account_code: row.from_account_code,
+            to_account_code: row.to_account_code,
+            total_amount: row.total_amount,
+            shop_name: row.shop_name,
+            item_names,
+        }
+    }
+}
+
+/// Scan the user's confirmed transactions for likely double entries, best
+/// matches first.
+pub async fn find_duplicate_groups(
+    pool: &SqlitePool,
+    user_id: i64,
+    options: &DuplicateScanOptions,
+) -> Result<Vec<DuplicateGroup>, ApiError> {
+    options.validate()?;
+
+    let pairs = sqlx::query_as::<_, CandidatePairRow>(sql_queries::DUPLICATE_CANDIDATE_PAIRS)
+        .bind(options.max_days_apart)
+        .bind(options.amount_tolerance_percent)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    let mut candidates: HashMap<i64, DuplicateCandidate> = HashMap::new();
+    for pair in &pairs {
+        for id in [pair.transaction_id_low, pair.transaction_id_high] {
+            if let Entry::Vacant(slot) = candidates.entry(id) {
+                let row = sqlx::query_as::<_, CandidateRow>(sql_queries::DUPLICATE_CANDIDATE_GET)
+                    .bind(id)
+                    .bind(user_id)
+                    .fetch_one(pool)
+                    .await?;
+                slot.insert(row.into());
+            }
+        }
+    }
+
+    // Union-find over the pairs that clear the threshold
+    let mut parent: HashMap<i64, i64> = HashMap::new();
+    let mut pair_scores: HashMap<(i64, i64), f64> = HashMap::new();
+    for pair in &pairs {
+        let (low, high) = (pair.transaction_id_low, pair.transaction_id_high);
+        let score = pair_score(&candidates[&low], &candidates[&high], options);
+        if score < options.min_score {
+            continue;
+        }
+        let root_low = find_root(&mut parent, low);
+        let root_high = find_root(&mut parent, high);
+        if root_low != root_high {
+            parent.insert(root_high.max(root_low), root_high.min(root_low));
+        }
+        pair_scores.insert((low, high), score);
+    }
+
+    let mut members: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+    for id in parent.keys().copied().collect::<Vec<_>>() {
+        let root = find_root(&mut parent, id);
+        members.entry(root).or_default().push(id);
+    }
+    let mut group_score: HashMap<i64, f64> = HashMap::new();
+    for (&(low, _), &score) in &pair_scores {
+        let root = find_root(&mut parent, low);
+        let entry = group_score.entry(root).or_insert(0.0);
+        *entry = entry.max(score);
+    }
+
+    let mut groups: Vec<DuplicateGroup> = members
+        .into_iter()
+        .map(|(root, ids)| {
+            let mut transactions: Vec<DuplicateCandidate> =
+                ids.into_iter().map(|id| candidates[&id].clone()).collect();
+            transactions.sort_by(|a, b| {
+                a.transaction_date
+                    .cmp(&b.transaction_date)
+                    .then(a.transaction_id.cmp(&b.transaction_id))
+            });
+            DuplicateGroup {
+                score: group_score[&root],
+                transactions,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.transactions[0].transaction_date.cmp(&b.transactions[0].transaction_date))
+    });
+    Ok(groups)
+}
+
+/// Mark every pair within `transaction_ids` as not a duplicate so the group
+/// stops appearing in the report.
+pub async fn dismiss_duplicate_group(
+    pool: &SqlitePool,
+    user_id: i64,
+    transaction_ids: &[i64],
+) -> Result<(), ApiError> {
+    let mut ids = transaction_ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() < 2 {
+        return Err(ApiError::validation("A duplicate group needs at least two transactions"));
+    }
+    for &id in &ids {
+        let header: Option<i64> = sqlx::query_scalar(sql_queries::TRANSACTION_HEADER_EXISTS_FOR_USER)
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+        if header.is_none() {
+            return Err(ApiError::not_found("Transaction"));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    for (i, &low) in ids.iter().enumerate() {
+        for &high in &ids[i + 1..] {
+            sqlx::query(sql_queries::DUPLICATE_DISMISSAL_INSERT)
+                .bind(user_id)
+                .bind(low)
+                .bind(high)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+fn find_root(parent: &mut HashMap<i64, i64>, id: i64) -> i64 {
+    let mut root = *parent.entry(id).or_insert(id);
+    while root != parent[&root] {
+        root = parent[&root];
+    }
+    parent.insert(id, root);
+    root
+}
+
+/// Weighted similarity in 0.0-1.0. The SQL has already enforced the date and
+/// amount limits, so those parts only measure how far inside them a pair is.
+fn pair_score(a: &DuplicateCandidate, b: &DuplicateCandidate, options: &DuplicateScanOptions) -> f64 {
+    let larger = a.total_amount.abs().max(b.total_amount.abs());
+    let diff = (a.total_amount - b.total_amount).abs();
+    let amount_score = if diff == 0 {
+        1.0
+    } else {
+        let allowed = larger as f64 * options.amount_tolerance_percent as f64 / 100.0;
+        (1.0 - diff as f64 / allowed).max(0.0)
+    };
+
+    let days = days_apart(&a.transaction_date, &b.transaction_date).unwrap_or(options.max_days_apart);
+    let date_score = 1.0 - days as f64 / (options.max_days_apart + 1) as f64;
+
+    let item_score = if a.item_names.is_empty() || b.item_names.is_empty() {
+        NO_ITEMS_SCORE
+    } else {
+        bigram_similarity(&a.item_names.join(" "), &b.item_names.join(" "))
+    };
+
+    AMOUNT_WEIGHT * amount_score + DATE_WEIGHT * date_score + ITEM_WEIGHT * item_score
+}
+
+fn days_apart(a: &str, b: &str) -> Option<i64> {
+    let parse = |s: &str| chrono::NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok();
+    Some((parse(a)? - parse(b)?).num_days().abs())
+}
+
+/// Dice coefficient over character bigrams, ignoring case and whitespace.
+/// Works on characters rather than words so Japanese names compare sensibly.
+fn bigram_similarity(a: &str, b: &str) -> f64 {
+    let bigrams = |s: &str| -> HashMap<(char, char), usize> {
+        let chars: Vec<char> = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect();
+        let mut counts = HashMap::new();
+        if chars.len() == 1 {
+            *counts.entry((chars[0], ' ')).or_insert(0) += 1;
+        }
+        for w in chars.windows(2) {
+            *counts.entry((w[0], w[1])).or_insert(0) += 1;
+        }
+        counts
+    };
+    let (left, right) = (bigrams(a), bigrams(b));
+    let total: usize = left.values().sum::<usize>() + right.values().sum::<usize>();
+    if total == 0 {
+        return 0.0;
+    }
+    let shared: usize = left
+        .iter()
+        .map(|(k, &n)| n.min(right.get(k).copied().unwrap_or(0)))
+        .sum();
+    2.0 * shared as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    /// User 2 with two card accounts:
+    /// - 1, 2, 3: the same ¥1,980 receipt keyed three times over two days
+    /// - 4: same card and day, unrelated ¥2,000 purchase
+    /// - 5: same amount as 1, but paid from the other card
+    /// - 6: ¥1,980 a week after 1
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'dups', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'CARD_A', 'Card A', 'NONE')",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'CARD_B', 'Card B', 'NONE')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (1, 2, 'EXPENSE', 'CARD_A', 'NONE', '2026-08-01 10:00:00', 1980)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 2, 'EXPENSE', 'CARD_A', 'NONE', '2026-08-01 18:00:00', 1980)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (3, 2, 'EXPENSE', 'CARD_A', 'NONE', '2026-08-02 09:00:00', 1980)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (4, 2, 'EXPENSE', 'CARD_A', 'NONE', '2026-08-01 12:00:00', 2000)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (5, 2, 'EXPENSE', 'CARD_B', 'NONE', '2026-08-01 10:00:00', 1980)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (6, 2, 'EXPENSE', 'CARD_A', 'NONE', '2026-08-08 10:00:00', 1980)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (1, 1, 2, 'EXPENSE', '牛乳', 280)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (2, 1, 2, 'EXPENSE', 'お米 5kg', 1700)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (3, 2, 2, 'EXPENSE', 'お米5kg', 1700)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (4, 2, 2, 'EXPENSE', '牛乳', 280)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (5, 4, 2, 'EXPENSE', 'Batteries', 2000)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    fn group_ids(groups: &[DuplicateGroup]) -> Vec<Vec<i64>> {
+        groups
+            .iter()
+            .map(|g| g.transactions.iter().map(|t| t.transaction_id).collect())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn groups_near_entries_on_the_same_account() {
+        let pool = setup().await;
+
+        let groups = find_duplicate_groups(&pool, 2, &DuplicateScanOptions::default()).await.unwrap();
+        // 4 is on the same day but ¥20 off (over 1%); 5 is another card;
+        // 6 is outside the three-day window
+        assert_eq!(group_ids(&groups), vec![vec![1, 2, 3]]);
+        // 1 and 2 share the day, the amount and (ignoring spaces) the items
+        assert!((groups[0].score - 1.0).abs() < 1e-9);
+        assert_eq!(groups[0].transactions[0].item_names, vec!["お米 5kg", "牛乳"]);
+
+        // Widening the tolerance brings in 4, but its items differ, so it only
+        // passes with a lower threshold
+        let wide = DuplicateScanOptions { amount_tolerance_percent: 2, ..Default::default() };
+        assert_eq!(group_ids(&find_duplicate_groups(&pool, 2, &wide).await.unwrap()), vec![vec![1, 2, 3]]);
+        let loose = DuplicateScanOptions { amount_tolerance_percent: 2, min_score: 0.3, ..Default::default() };
+        assert_eq!(
+            group_ids(&find_duplicate_groups(&pool, 2, &loose).await.unwrap()),
+            vec![vec![1, 4, 2, 3]]
+        );
+
+        let bad = DuplicateScanOptions { max_days_apart: -1, ..Default::default() };
+        assert_eq!(
+            find_duplicate_groups(&pool, 2, &bad).await.unwrap_err().code,
+            ApiError::CODE_VALIDATION
+        );
+    }
+
+    #[tokio::test]
+    async fn dismissed_groups_stay_hidden_until_a_new_match_appears() {
+        let pool = setup().await;
+
+        dismiss_duplicate_group(&pool, 2, &[3, 1, 2, 1]).await.unwrap();
+        assert!(find_duplicate_groups(&pool, 2, &DuplicateScanOptions::default()).await.unwrap().is_empty());
+        // Dismissing again is harmless
+        dismiss_duplicate_group(&pool, 2, &[1, 2]).await.unwrap();
+
+        sqlx::query("INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (7, 2, 'EXPENSE', 'CARD_A', 'NONE', '2026-08-02 20:00:00', 1980)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let groups = find_duplicate_groups(&pool, 2, &DuplicateScanOptions::default()).await.unwrap();
+        assert_eq!(group_ids(&groups), vec![vec![1, 2, 3, 7]]);
+
+        // Deleting a header drops its dismissals with it
+        sqlx::query("DELETE FROM TRANSACTIONS_DETAIL WHERE TRANSACTION_ID = 1").execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM TRANSACTIONS_HEADER WHERE TRANSACTION_ID = 1").execute(&pool).await.unwrap();
+        let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM DUPLICATE_DISMISSALS")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(left, 1);
+
+        assert_eq!(
+            dismiss_duplicate_group(&pool, 2, &[2]).await.unwrap_err().code,
+            ApiError::CODE_VALIDATION
+        );
+        assert_eq!(
+            dismiss_duplicate_group(&pool, 2, &[2, 999]).await.unwrap_err().code,
+            ApiError::CODE_NOT_FOUND
+        );
+    }
+}
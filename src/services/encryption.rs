@@ -144,8 +144,13 @@ impl EncryptionService {
     ) -> Result<(), EncryptionError> {
         // Get all encrypted fields
         let encrypted_fields = self.get_encrypted_fields().await?;
-        
-        if encrypted_fields.is_empty() {
+        let encrypted_attachments: Vec<(i64, Vec<u8>)> =
+            sqlx::query_as(sql_queries::ATTACHMENT_LIST_ENCRYPTED_CONTENT)
+                .bind(user_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+        if encrypted_fields.is_empty() && encrypted_attachments.is_empty() {
             return Ok(());
         }
 
@@ -260,6 +265,20 @@ impl EncryptionService {
             }
         }
 
+        // Attachment content is raw `nonce || ciphertext` bytes rather than
+        // Base64 text, so it is re-keyed here instead of via ENCRYPTED_FIELDS.
+        for (attachment_id, content) in encrypted_attachments {
+            let decrypted = old_crypto.decrypt_bytes(&content)
+                .map_err(|e| EncryptionError::DecryptionFailed(e.to_string()))?;
+            let re_encrypted = new_crypto.encrypt_bytes(&decrypted)
+                .map_err(|e| EncryptionError::EncryptionFailed(e.to_string()))?;
+            sqlx::query(sql_queries::ATTACHMENT_UPDATE_CONTENT)
+                .bind(re_encrypted)
+                .bind(attachment_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
         // Commit transaction
         tx.commit().await?;
 
@@ -382,6 +401,12 @@ mod tests {
         // — the encryption paths now fetch salt from USERS.ENCRYPTION_SALT).
         seed_user_with_salt(&pool, 1).await;
 
+        // Password changes also re-key encrypted attachments
+        sqlx::query(sql_queries::CREATE_ATTACHMENTS_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
+
         // Create a test table with encrypted fields
         sqlx::query(
             r#"
@@ -571,6 +596,10 @@ mod tests {
 
         // USERS row with a per-user random salt (Fable-5 review #15).
         seed_user_with_salt(&pool, 1).await;
+        sqlx::query(sql_queries::CREATE_ATTACHMENTS_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
 
         let service = EncryptionService::new(pool.clone());
         service
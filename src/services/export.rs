@@ -0,0 +1,463 @@
+//! Ledger export
+//!
+//! Walks TRANSACTIONS_HEADER / TRANSACTIONS_DETAIL for one user over a date
+//! range and renders the result as CSV or JSON with every code resolved to
+//! its display name, so the file can be read without the app (e.g. handed
+//! to an accountant).
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::sql_queries;
+
+/// Output format of `export_ledger`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Flat row returned by `LEDGER_EXPORT_ROWS`: one per detail, header columns
+/// repeated. `detail_id` is `None` for a header without details.
+#[derive(Debug, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+struct LedgerExportRow {
+    transaction_id: i64,
+    transaction_date: String,
+    category1_code: String,
+    category1_name: Option<String>,
+    from_account_code: String,
+    from_account_name: Option<String>,
+    to_account_code: String,
+    to_account_name: Option<String>,
+    shop_name: Option<String>,
+    total_amount: i64,
+    tax_rounding_type: Option<i64>,
+    tax_included_type: i64,
+    is_scheduled: i64,
+    header_memo: Option<String>,
+    detail_id: Option<i64>,
+    category2_code: Option<String>,
+    category2_name: Option<String>,
+    category3_code: Option<String>,
+    category3_name: Option<String>,
+    item_name: Option<String>,
+    amount: Option<i64>,
+    tax_rate: Option<i64>,
+    tax_amount: Option<i64>,
+    amount_including_tax: Option<i64>,
+    product_name: Option<String>,
+    manufacturer_name: Option<String>,
+    detail_memo: Option<String>,
+}
+
+/// Detail line in the JSON export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedDetail {
+    pub detail_id: i64,
+    pub category2_code: Option<String>,
+    pub category2_name: Option<String>,
+    pub category3_code: Option<String>,
+    pub category3_name: Option<String>,
+    pub item_name: String,
+    pub amount: i64,
+    pub tax_rate: i64,
+    pub tax_amount: i64,
+    pub amount_including_tax: Option<i64>,
+    pub product_name: Option<String>,
+    pub manufacturer_name: Option<String>,
+    pub memo: Option<String>,
+}
+
+/// Transaction (header plus its details) in the JSON export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedTransaction {
+    pub transaction_id: i64,
+    pub transaction_date: String,
+    pub category1_code: String,
+    pub category1_name: Option<String>,
+    pub from_account_code: String,
+    pub from_account_name: Option<String>,
+    pub to_account_code: String,
+    pub to_account_name: Option<String>,
+    pub shop_name: Option<String>,
+    pub total_amount: i64,
+    pub tax_rounding_type: Option<i64>,
+    pub tax_included_type: i64,
+    pub is_scheduled: bool,
+    pub memo: Option<String>,
+    pub details: Vec<ExportedDetail>,
+}
+
+/// Rendered export handed back to the frontend, which writes `content` to
+/// the file the user picks (suggesting `file_name`).
+#[derive(Debug, Serialize)]
+pub struct LedgerExport {
+    pub format: ExportFormat,
+    pub file_name: String,
+    pub content: String,
+    pub transaction_count: usize,
+    pub detail_count: usize,
+}
+
+/// CSV column order. Header columns are repeated on every detail line so
+/// each line stands on its own in a spreadsheet filter.
+const CSV_COLUMNS: [&str; 25] = [
+    "transaction_id",
+    "transaction_date",
+    "category1_code",
+    "category1_name",
+    "from_account_code",
+    "from_account_name",
+    "to_account_code",
+    "to_account_name",
+    "shop_name",
+    "total_amount",
+    "tax_rounding_type",
+    "tax_included_type",
+    "is_scheduled",
+    "header_memo",
+    "detail_id",
+    "category2_name",
+    "category3_name",
+    "item_name",
+    "amount",
+    "tax_rate",
+    "tax_amount",
+    "amount_including_tax",
+    "product_name",
+    "manufacturer_name",
+    "detail_memo",
+];
+
+/// Validate an export bound (`YYYY-MM-DD` or `YYYY-MM-DD HH:MM:SS`) and
+/// return it in the form TRANSACTION_DATE compares against. A bare end date
+/// is anchored to 23:59:59 so the boundary day is included, as in
+/// `TransactionService::get_transactions`.
+fn normalize_bound(value: Option<&str>, is_end: bool) -> Result<Option<String>, ApiError> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+
+    if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() && value.len() == 10 {
+        return Ok(Some(if is_end {
+            format!("{} 23:59:59", value)
+        } else {
+            format!("{} 00:00:00", value)
+        }));
+    }
+    if chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").is_ok() {
+        return Ok(Some(value.to_string()));
+    }
+
+    Err(ApiError::validation(format!(
+        "Invalid date: {}. Use YYYY-MM-DD",
+        value
+    )))
+}
+
+/// Quote a CSV field when it contains the delimiter, a quote or a line
+/// break. Text fields that a spreadsheet would evaluate as a formula
+/// (leading `=`, `+`, `-`, `@`) are prefixed with `'` so a memo such as
+/// `=HYPERLINK(...)` stays inert when the accountant opens the file.
+fn csv_text(value: Option<&str>) -> String {
+    let value = value.unwrap_or("");
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn csv_number(value: Option<i64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Render rows as CSV: UTF-8 with BOM and CRLF line endings, which is what
+/// Excel needs to open Japanese text correctly.
+fn render_csv(rows: &[LedgerExportRow]) -> String {
+    let mut out = String::from("\u{feff}");
+    out.push_str(&CSV_COLUMNS.join(","));
+    out.push_str("\r\n");
+
+    for r in rows {
+        let fields = [
+            r.transaction_id.to_string(),
+            r.transaction_date.clone(),
+            csv_text(Some(&r.category1_code)),
+            csv_text(r.category1_name.as_deref()),
+            csv_text(Some(&r.from_account_code)),
+            csv_text(r.from_account_name.as_deref()),
+            csv_text(Some(&r.to_account_code)),
+            csv_text(r.to_account_name.as_deref()),
+            csv_text(r.shop_name.as_deref()),
+            r.total_amount.to_string(),
+            csv_number(r.tax_rounding_type),
+            r.tax_included_type.to_string(),
+            r.is_scheduled.to_string(),
+            csv_text(r.header_memo.as_deref()),
+            csv_number(r.detail_id),
+            csv_text(r.category2_name.as_deref()),
+            csv_text(r.category3_name.as_deref()),
+            csv_text(r.item_name.as_deref()),
+            csv_number(r.amount),
+            csv_number(r.tax_rate),
+            csv_number(r.tax_amount),
+            csv_number(r.amount_including_tax),
+            csv_text(r.product_name.as_deref()),
+            csv_text(r.manufacturer_name.as_deref()),
+            csv_text(r.detail_memo.as_deref()),
+        ];
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Fold the flat, ordered rows back into one entry per header.
+fn group_transactions(rows: Vec<LedgerExportRow>) -> Vec<ExportedTransaction> {
+    let mut transactions: Vec<ExportedTransaction> = Vec::new();
+
+    for r in rows {
+        let detail = r.detail_id.map(|detail_id| ExportedDetail {
+            detail_id,
+            category2_code: r.category2_code,
+            category2_name: r.category2_name,
+            category3_code: r.category3_code,
+            category3_name: r.category3_name,
+            item_name: r.item_name.unwrap_or_default(),
+            amount: r.amount.unwrap_or(0),
+            tax_rate: r.tax_rate.unwrap_or(0),
+            tax_amount: r.tax_amount.unwrap_or(0),
+            amount_including_tax: r.amount_including_tax,
+            product_name: r.product_name,
+            manufacturer_name: r.manufacturer_name,
+            memo: r.detail_memo,
+        });
+
+        match transactions.last_mut() {
+            Some(last) if last.transaction_id == r.transaction_id => {
+                last.details.extend(detail);
+            }
+            _ => transactions.push(ExportedTransaction {
+                transaction_id: r.transaction_id,
+                transaction_date: r.transaction_date,
+                category1_code: r.category1_code,
+                category1_name: r.category1_name,
+                from_account_code: r.from_account_code,
+                from_account_name: r.from_account_name,
+                to_account_code: r.to_account_code,
+                to_account_name: r.to_account_name,
+                shop_name: r.shop_name,
+                total_amount: r.total_amount,
+                tax_rounding_type: r.tax_rounding_type,
+                tax_included_type: r.tax_included_type,
+                is_scheduled: r.is_scheduled != 0,
+                memo: r.header_memo,
+                details: detail.into_iter().collect(),
+            }),
+        }
+    }
+
+    transactions
+}
+
+/// Export `user_id`'s ledger between `start_date` and `end_date` (both
+/// inclusive, either may be omitted) in `format`. Category names are
+/// resolved for `lang`. Scheduled rows are left out unless
+/// `include_scheduled` is set, the same default as the transaction list.
+pub async fn export_ledger(
+    pool: &SqlitePool,
+    user_id: i64,
+    lang: &str,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+    include_scheduled: bool,
+    format: ExportFormat,
+) -> Result<LedgerExport, ApiError> {
+    let start = normalize_bound(start_date, false)?;
+    let end = normalize_bound(end_date, true)?;
+    if let (Some(s), Some(e)) = (&start, &end) {
+        if s > e {
+            return Err(ApiError::validation(
+                "Start date must be on or before end date",
+            ));
+        }
+    }
+
+    let rows = sqlx::query_as::<_, LedgerExportRow>(sql_queries::LEDGER_EXPORT_ROWS)
+        .bind(lang)
+        .bind(lang)
+        .bind(lang)
+        .bind(user_id)
+        .bind(&start)
+        .bind(&start)
+        .bind(&end)
+        .bind(&end)
+        .bind(include_scheduled as i64)
+        .fetch_all(pool)
+        .await?;
+
+    let detail_count = rows.iter().filter(|r| r.detail_id.is_some()).count();
+    let file_name = format!(
+        "kakeibon_ledger_{}_{}.{}",
+        start.as_deref().map_or("begin".to_string(), |s| s[..10].replace('-', "")),
+        end.as_deref().map_or("end".to_string(), |e| e[..10].replace('-', "")),
+        format.extension()
+    );
+
+    let (content, transaction_count) = match format {
+        ExportFormat::Csv => {
+            let transaction_count = {
+                let mut ids: Vec<i64> = rows.iter().map(|r| r.transaction_id).collect();
+                ids.dedup();
+                ids.len()
+            };
+            (render_csv(&rows), transaction_count)
+        }
+        ExportFormat::Json => {
+            let transactions = group_transactions(rows);
+            let count = transactions.len();
+            let json = serde_json::to_string_pretty(&transactions)
+                .map_err(|e| ApiError::database(format!("Failed to serialize export: {}", e)))?;
+            (json, count)
+        }
+    };
+
+    Ok(LedgerExport {
+        format,
+        file_name,
+        content,
+        transaction_count,
+        detail_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    /// User 2 with one fully-populated expense (shop, memo, product with
+    /// manufacturer, English category names), one header without details,
+    /// and one scheduled occurrence.
+    async fn setup_export_db() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'export', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO CATEGORY1_I18N (USER_ID, CATEGORY1_CODE, LANG_CODE, CATEGORY1_NAME_I18N, ENTRY_DT) VALUES (2, 'EXPENSE', 'en', 'Expense', datetime('now'))",
+            "INSERT INTO CATEGORY2 (USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, DISPLAY_ORDER, CATEGORY2_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 'FOOD', 1, '食費', datetime('now'))",
+            "INSERT INTO CATEGORY2_I18N (USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, LANG_CODE, CATEGORY2_NAME_I18N, ENTRY_DT) VALUES (2, 'EXPENSE', 'FOOD', 'en', 'Food', datetime('now'))",
+            "INSERT INTO CATEGORY3 (USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE, DISPLAY_ORDER, CATEGORY3_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 'FOOD', 'GROCERY', 1, '食料品', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'CASH', 'Wallet', 'CASH')",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO SHOPS (SHOP_ID, USER_ID, SHOP_NAME) VALUES (1, 2, 'Corner Store')",
+            "INSERT INTO MANUFACTURERS (MANUFACTURER_ID, USER_ID, MANUFACTURER_NAME) VALUES (1, 2, 'Acme')",
+            "INSERT INTO PRODUCTS (PRODUCT_ID, USER_ID, PRODUCT_NAME, MANUFACTURER_ID) VALUES (1, 2, 'Rice 5kg', 1)",
+            "INSERT INTO MEMOS (MEMO_ID, USER_ID, MEMO_TEXT) VALUES (1, 2, 'weekly, \"big\" shop')",
+            "INSERT INTO MEMOS (MEMO_ID, USER_ID, MEMO_TEXT) VALUES (2, 2, '=1+1')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, SHOP_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, TAX_INCLUDED_TYPE, MEMO_ID, IS_SCHEDULED) VALUES (10, 2, 1, 'EXPENSE', 'CASH', 'NONE', '2024-03-10 12:00:00', 2160, 1, 1, 0)",
+            "INSERT INTO TRANSACTIONS_DETAIL (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE, ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, PRODUCT_ID, MEMO_ID) VALUES (10, 2, 'EXPENSE', 'FOOD', 'GROCERY', 'Rice', 1500, 120, 8, 1, 2)",
+            "INSERT INTO TRANSACTIONS_DETAIL (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE, ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE) VALUES (10, 2, 'EXPENSE', 'FOOD', NULL, 'Eggs', 500, 40, 8)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, IS_SCHEDULED) VALUES (11, 2, 'EXPENSE', 'CASH', 'NONE', '2024-03-31 23:00:00', 300, 0)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, IS_SCHEDULED) VALUES (12, 2, 'EXPENSE', 'CASH', 'NONE', '2024-03-20 00:00:00', 9000, 1)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, IS_SCHEDULED) VALUES (13, 2, 'EXPENSE', 'CASH', 'NONE', '2024-04-01 00:00:00', 700, 0)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_export_json_resolves_names_and_groups_details() {
+        let pool = setup_export_db().await;
+
+        let export = export_ledger(&pool, 2, "en", Some("2024-03-01"), Some("2024-03-31"), false, ExportFormat::Json)
+            .await
+            .unwrap();
+        assert_eq!(export.file_name, "kakeibon_ledger_20240301_20240331.json");
+        assert_eq!(export.transaction_count, 2);
+        assert_eq!(export.detail_count, 2);
+
+        let transactions: Vec<ExportedTransaction> = serde_json::from_str(&export.content).unwrap();
+        assert_eq!(transactions.iter().map(|t| t.transaction_id).collect::<Vec<_>>(), vec![10, 11]);
+
+        let t = &transactions[0];
+        assert_eq!(t.category1_name.as_deref(), Some("Expense"));
+        assert_eq!(t.from_account_name.as_deref(), Some("Wallet"));
+        assert_eq!(t.shop_name.as_deref(), Some("Corner Store"));
+        assert_eq!(t.memo.as_deref(), Some("weekly, \"big\" shop"));
+        assert_eq!(t.details.len(), 2);
+        assert_eq!(t.details[0].category2_name.as_deref(), Some("Food"));
+        // No CATEGORY3_I18N row: falls back to the base name.
+        assert_eq!(t.details[0].category3_name.as_deref(), Some("食料品"));
+        assert_eq!(t.details[0].product_name.as_deref(), Some("Rice 5kg"));
+        assert_eq!(t.details[0].manufacturer_name.as_deref(), Some("Acme"));
+        assert_eq!(t.details[0].memo.as_deref(), Some("=1+1"));
+        assert!(transactions[1].details.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_honours_include_scheduled() {
+        let pool = setup_export_db().await;
+
+        let without = export_ledger(&pool, 2, "en", Some("2024-03-01"), Some("2024-03-31"), false, ExportFormat::Json)
+            .await
+            .unwrap();
+        let with = export_ledger(&pool, 2, "en", Some("2024-03-01"), Some("2024-03-31"), true, ExportFormat::Json)
+            .await
+            .unwrap();
+        assert_eq!(without.transaction_count, 2);
+        assert_eq!(with.transaction_count, 3);
+
+        let all = export_ledger(&pool, 2, "en", None, None, false, ExportFormat::Json).await.unwrap();
+        assert_eq!(all.transaction_count, 3);
+        assert_eq!(all.file_name, "kakeibon_ledger_begin_end.json");
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_quotes_and_neutralises_formulas() {
+        let pool = setup_export_db().await;
+
+        let export = export_ledger(&pool, 2, "ja", Some("2024-03-10"), Some("2024-03-10"), false, ExportFormat::Csv)
+            .await
+            .unwrap();
+        assert_eq!(export.transaction_count, 1);
+
+        let body = export.content.strip_prefix('\u{feff}').expect("CSV starts with a BOM");
+        let lines: Vec<&str> = body.split("\r\n").filter(|l| !l.is_empty()).collect();
+        assert_eq!(lines.len(), 3, "column titles + two detail lines");
+        assert!(lines[0].starts_with("transaction_id,transaction_date,"));
+        assert!(lines[1].contains(",支出,"), "ja has no i18n row, base name is used");
+        assert!(lines[1].contains("\"weekly, \"\"big\"\" shop\""));
+        assert!(lines[1].ends_with(",'=1+1"));
+    }
+
+    #[tokio::test]
+    async fn test_export_rejects_bad_ranges() {
+        let pool = setup_export_db().await;
+
+        let reversed = export_ledger(&pool, 2, "en", Some("2024-04-01"), Some("2024-03-01"), false, ExportFormat::Csv).await;
+        assert_eq!(reversed.unwrap_err().code, ApiError::CODE_VALIDATION);
+
+        let garbage = export_ledger(&pool, 2, "en", Some("2024/04/01"), None, false, ExportFormat::Csv).await;
+        assert_eq!(garbage.unwrap_err().code, ApiError::CODE_VALIDATION);
+    }
+}
@@ -0,0 +1,742 @@
+//! Change journal: undo / redo for the transaction, category, account,
+//! master-data, tag, attachment, card-statement and recurring-rule commands.
+//!
+//! A command runs between [`begin_operation`] and [`end_operation`]. While
+//! an operation is active, the `TRG_JOURNAL_*` triggers copy the before and
+//! after image of every row changed in `CHANGE_JOURNAL_TABLES` into
+//! CHANGE_JOURNAL. Because the triggers sit on the tables themselves, rows
+//! changed by FK actions (details deleted with their header, PRODUCT_ID set
+//! to NULL when a product is deleted) are journaled like any other row.
+//!
+//! Undo replays an operation's images newest-first and redo oldest-first,
+//! in one transaction with FK checks deferred to the commit. Each row must
+//! still look exactly as the operation left it (or, for redo, as undo left
+//! it); otherwise the whole undo / redo is refused and nothing changes.
+//!
+//! The journal lives for one login: it is cleared when the user logs in and
+//! at startup. Only one operation can be active at a time, and the triggers
+//! cannot tell which connection wrote a row, so callers must serialise
+//! [`begin_operation`]..[`end_operation`], undo / redo and every other write
+//! to a journaled table behind one lock. [`ActiveOperation`] ties an
+//! operation to that lock and ends it even if the command never returns.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::query::Query;
+use sqlx::sqlite::{Sqlite, SqliteArguments};
+use sqlx::{FromRow, Row, SqliteConnection, SqlitePool};
+use tokio::sync::OwnedMutexGuard;
+
+use crate::api_error::ApiError;
+use crate::services::audit::{self, AuditKey};
+use crate::sql_queries;
+
+/// Most operations undone or redone by one call.
+pub const MAX_REPLAY_COUNT: i64 = 50;
+
+const STATUS_DONE: &str = "done";
+const STATUS_UNDONE: &str = "undone";
+
+/// One journaled command, newest first in [`list_operations`].
+#[derive(Debug, Serialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ChangeOperation {
+    pub operation_id: i64,
+    /// `<entity>.<action>` key, e.g. `transaction.delete`.
+    pub label: String,
+    /// `done` or `undone`.
+    pub status: String,
+    /// Number of rows the operation changed, including FK cascades.
+    pub change_count: i64,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+pub(crate) struct JournalColumn {
+    pub(crate) name: String,
+    pub(crate) is_blob: bool,
+    /// 1-based position in the primary key, 0 if not part of it.
+    pub(crate) pk: i64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+pub(crate) async fn table_columns(
+    conn: &mut SqliteConnection,
+    table: &str,
+) -> Result<Vec<JournalColumn>, sqlx::Error> {
+    let rows = sqlx::query(sql_queries::CHANGE_JOURNAL_TABLE_COLUMNS)
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| JournalColumn {
+            name: row.get("name"),
+            is_blob: row.get::<String, _>("type").to_uppercase().contains("BLOB"),
+            pk: row.get("pk"),
+        })
+        .collect())
+}
+
+/// `json_object(...)` over every column of a row. JSON cannot hold a BLOB,
+/// so BLOB columns go in hex-encoded and come back out through `unhex()`.
+pub(crate) fn row_image_sql(columns: &[JournalColumn], prefix: &str) -> String {
+    let pairs: Vec<String> = columns
+        .iter()
+        .map(|c| {
+            if c.is_blob {
+                format!("'{0}', hex({1}{0})", c.name, prefix)
+            } else {
+                format!("'{0}', {1}{0}", c.name, prefix)
+            }
+        })
+        .collect();
+    format!("json_object({})", pairs.join(", "))
+}
+
+/// (Re)create the journal triggers from the tables' current columns. Run on
+/// every startup after the schema migrations so added columns are covered.
+pub(crate) async fn install_triggers(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    let existing: Vec<String> = sqlx::query_scalar(sql_queries::CHANGE_JOURNAL_LIST_TRIGGERS)
+        .fetch_all(&mut *conn)
+        .await?;
+    for name in existing {
+        sqlx::query(&format!("DROP TRIGGER IF EXISTS {}", name))
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    for table in sql_queries::CHANGE_JOURNAL_TABLES {
+        let columns = table_columns(conn, table).await?;
+        if columns.is_empty() {
+            // Table not created in this database (older schema in tests)
+            continue;
+        }
+        let old_image = row_image_sql(&columns, "OLD.");
+        let new_image = row_image_sql(&columns, "NEW.");
+        for (action, before, after) in [
+            ("INSERT", "NULL", new_image.as_str()),
+            ("UPDATE", old_image.as_str(), new_image.as_str()),
+            ("DELETE", old_image.as_str(), "NULL"),
+        ] {
+            let ddl = format!(
+                "CREATE TRIGGER TRG_JOURNAL_{table}_{action}
+AFTER {action} ON {table}
+WHEN (SELECT ACTIVE_OPERATION_ID FROM CHANGE_JOURNAL_CONTROL WHERE CONTROL_ID = 1) IS NOT NULL
+BEGIN
+    INSERT INTO CHANGE_JOURNAL (OPERATION_ID, TABLE_NAME, ACTION, BEFORE_IMAGE, AFTER_IMAGE)
+    SELECT ACTIVE_OPERATION_ID, '{table}', '{action}', {before}, {after}
+    FROM CHANGE_JOURNAL_CONTROL WHERE CONTROL_ID = 1;
+END"
+            );
+            sqlx::query(&ddl).execute(&mut *conn).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Start journaling a new operation for `user_id`. Starting an operation
+/// discards the user's undone operations, as with any undo stack.
+pub async fn begin_operation(
+    pool: &SqlitePool,
+    user_id: i64,
+    label: &str,
+) -> Result<i64, ApiError> {
+    let mut tx = pool.begin().await?;
+    let active: Option<i64> = sqlx::query_scalar(sql_queries::CHANGE_JOURNAL_GET_ACTIVE)
+        .fetch_one(&mut *tx)
+        .await?;
+    if active.is_some() {
+        return Err(ApiError::validation("Another operation is still being recorded"));
+    }
+    sqlx::query(sql_queries::CHANGE_OPERATION_DELETE_UNDONE)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    let operation_id = sqlx::query(sql_queries::CHANGE_OPERATION_INSERT)
+        .bind(user_id)
+        .bind(label)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+    sqlx::query(sql_queries::CHANGE_JOURNAL_SET_ACTIVE)
+        .bind(operation_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(operation_id)
+}
+
+/// Stop journaling. An operation that changed no row (e.g. the command
+/// failed validation) is dropped so it never shows up as undoable.
+pub async fn end_operation(pool: &SqlitePool, operation_id: i64) -> Result<(), ApiError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(sql_queries::CHANGE_JOURNAL_SET_ACTIVE)
+        .bind(None::<i64>)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(sql_queries::CHANGE_OPERATION_DELETE_IF_EMPTY)
+        .bind(operation_id)
+        .bind(operation_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// An operation begun under the journal lock. [`ActiveOperation::finish`]
+/// ends it and releases the lock. If it is dropped instead — the command
+/// panicked or its future was cancelled — a spawned task ends the operation
+/// and keeps the lock until it has, so no later write is recorded into it.
+pub struct ActiveOperation {
+    pool: SqlitePool,
+    operation_id: i64,
+    lock: Option<OwnedMutexGuard<()>>,
+}
+
+impl ActiveOperation {
+    pub async fn begin(
+        pool: &SqlitePool,
+        user_id: i64,
+        label: &str,
+        lock: OwnedMutexGuard<()>,
+    ) -> Result<Self, ApiError> {
+        let operation_id = begin_operation(pool, user_id, label).await?;
+        Ok(Self {
+            pool: pool.clone(),
+            operation_id,
+            lock: Some(lock),
+        })
+    }
+
+    pub async fn finish(mut self) -> Result<(), ApiError> {
+        end_operation(&self.pool, self.operation_id).await?;
+        self.lock = None;
+        Ok(())
+    }
+}
+
+impl Drop for ActiveOperation {
+    fn drop(&mut self) {
+        let Some(lock) = self.lock.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            // Runtime shutting down: startup clears the active operation
+            return;
+        };
+        let pool = self.pool.clone();
+        let operation_id = self.operation_id;
+        runtime.spawn(async move {
+            if let Err(e) = end_operation(&pool, operation_id).await {
+                log::error!("Failed to end journal operation {}: {}", operation_id, e);
+            }
+            drop(lock);
+        });
+    }
+}
+
+pub async fn list_operations(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<ChangeOperation>, ApiError> {
+    Ok(sqlx::query_as::<_, ChangeOperation>(sql_queries::CHANGE_OPERATION_LIST)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?)
+}
+
+pub async fn clear_journal(pool: &SqlitePool, user_id: i64) -> Result<(), ApiError> {
+    sqlx::query(sql_queries::CHANGE_OPERATION_CLEAR_USER)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Undo the user's `count` most recent operations that are not undone yet.
+/// All of them are undone or none is. Returns the undone operation IDs.
+pub async fn undo_last(pool: &SqlitePool, user_id: i64, count: i64) -> Result<Vec<i64>, ApiError> {
+    validate_count(count)?;
+    let ids: Vec<i64> = sqlx::query_scalar(sql_queries::CHANGE_OPERATION_LATEST_IDS)
+        .bind(user_id)
+        .bind(STATUS_DONE)
+        .bind(count)
+        .fetch_all(pool)
+        .await?;
+    replay(pool, user_id, &ids, Direction::Undo).await?;
+    Ok(ids)
+}
+
+/// Undo one operation, which need not be the most recent one as long as
+/// no later operation changed the same rows.
+pub async fn undo_operation(pool: &SqlitePool, user_id: i64, operation_id: i64) -> Result<(), ApiError> {
+    check_status(pool, user_id, operation_id, STATUS_DONE).await?;
+    replay(pool, user_id, &[operation_id], Direction::Undo).await
+}
+
+/// Redo the user's `count` undone operations, oldest first, so repeated
+/// undo-then-redo walks back through the same sequence. Returns the
+/// redone operation IDs.
+pub async fn redo_last(pool: &SqlitePool, user_id: i64, count: i64) -> Result<Vec<i64>, ApiError> {
+    validate_count(count)?;
+    let ids: Vec<i64> = sqlx::query_scalar(sql_queries::CHANGE_OPERATION_EARLIEST_IDS)
+        .bind(user_id)
+        .bind(STATUS_UNDONE)
+        .bind(count)
+        .fetch_all(pool)
+        .await?;
+    replay(pool, user_id, &ids, Direction::Redo).await?;
+    Ok(ids)
+}
+
+pub async fn redo_operation(pool: &SqlitePool, user_id: i64, operation_id: i64) -> Result<(), ApiError> {
+    check_status(pool, user_id, operation_id, STATUS_UNDONE).await?;
+    replay(pool, user_id, &[operation_id], Direction::Redo).await
+}
+
+fn validate_count(count: i64) -> Result<(), ApiError> {
+    if !(1..=MAX_REPLAY_COUNT).contains(&count) {
+        return Err(ApiError::validation(format!(
+            "Count must be between 1 and {}",
+            MAX_REPLAY_COUNT
+        )));
+    }
+    Ok(())
+}
+
+async fn check_status(
+    pool: &SqlitePool,
+    user_id: i64,
+    operation_id: i64,
+    expected: &str,
+) -> Result<(), ApiError> {
+    let status: Option<String> = sqlx::query_scalar(sql_queries::CHANGE_OPERATION_GET_STATUS)
+        .bind(operation_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+    match status {
+        None => Err(ApiError::not_found("Operation")),
+        Some(s) if s == expected => Ok(()),
+        Some(_) if expected == STATUS_DONE => {
+            Err(ApiError::validation("Operation has already been undone"))
+        }
+        Some(_) => Err(ApiError::validation("Operation has not been undone")),
+    }
+}
+
+/// Apply the journal of `operation_ids` (in the given order) in one
+/// transaction. The triggers stay quiet because no operation is active.
+async fn replay(
+    pool: &SqlitePool,
+    user_id: i64,
+    operation_ids: &[i64],
+    direction: Direction,
+) -> Result<(), ApiError> {
+    if operation_ids.is_empty() {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    // Restoring a header and its details happens one row at a time, so the
+    // FK constraints only have to hold once everything is back.
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+
+    let (new_status, order_desc) = match direction {
+        Direction::Undo => (STATUS_UNDONE, true),
+        Direction::Redo => (STATUS_DONE, false),
+    };
+    for &operation_id in operation_ids {
+        let mut changes = sqlx::query(sql_queries::CHANGE_JOURNAL_GET_CHANGES)
+            .bind(operation_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        if order_desc {
+            changes.reverse();
+        }
+        for change in &changes {
+            let table: String = change.get("TABLE_NAME");
+            let before: Option<String> = change.get("BEFORE_IMAGE");
+            let after: Option<String> = change.get("AFTER_IMAGE");
+            let (expected, target) = match direction {
+                Direction::Undo => (after, before),
+                Direction::Redo => (before, after),
+            };
+            apply_change(&mut tx, user_id, &table, expected.as_deref(), target.as_deref())
+                .await
+                .map_err(|e| conflict_or_database(e, &table))?;
+        }
+        sqlx::query(sql_queries::CHANGE_OPERATION_SET_STATUS)
+            .bind(new_status)
+            .bind(operation_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    // Deferred FK violations surface here, e.g. restoring a product whose
+    // manufacturer has been deleted since
+    tx.commit().await.map_err(|e| conflict_or_database(e, "A related row"))?;
+    Ok(())
+}
+
+/// Outcome of one row replay that is not a plain database error.
+enum ReplayError {
+    Conflict,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for ReplayError {
+    fn from(e: sqlx::Error) -> Self {
+        ReplayError::Database(e)
+    }
+}
+
+/// A constraint failure while restoring means the surrounding data moved
+/// on (a name was reused, a parent row is gone), which is a conflict too.
+fn conflict_or_database(e: impl Into<ReplayError>, table: &str) -> ApiError {
+    match e.into() {
+        ReplayError::Conflict => ApiError::journal_conflict(table),
+        ReplayError::Database(sqlx::Error::Database(db))
+            if db.is_unique_violation() || db.is_foreign_key_violation() =>
+        {
+            ApiError::journal_conflict(table)
+        }
+        ReplayError::Database(e) => ApiError::from(e),
+    }
+}
+
+/// Move one row from `expected` to `target`. `None` on either side means
+/// the row does not exist in that state. The row is audited as a change by
+/// `user_id`, like the command being undone or redone.
+async fn apply_change(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    table: &str,
+    expected: Option<&str>,
+    target: Option<&str>,
+) -> Result<(), ReplayError> {
+    let columns = table_columns(conn, table).await?;
+    let expected_row = expected.map(parse_image).transpose()?;
+    let target_row = target.map(parse_image).transpose()?;
+
+    // The row is looked up by the key it has now: the expected image when
+    // it should exist, otherwise the key it is about to be given.
+    let key_row = match (&expected_row, &target_row) {
+        (Some(row), _) | (None, Some(row)) => row,
+        (None, None) => return Ok(()),
+    };
+    let key_columns: Vec<&JournalColumn> = {
+        let mut keys: Vec<&JournalColumn> = columns.iter().filter(|c| c.pk > 0).collect();
+        keys.sort_by_key(|c| c.pk);
+        keys
+    };
+    let key_clause = key_columns
+        .iter()
+        .map(|c| format!("{} = ?", c.name))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    let current_sql = format!(
+        "SELECT {} FROM {} WHERE {}",
+        row_image_sql(&columns, ""),
+        table,
+        key_clause
+    );
+    let mut current_query = sqlx::query(&current_sql);
+    for c in &key_columns {
+        current_query = bind_json(current_query, key_row.get(&c.name));
+    }
+    let current: Option<String> = current_query
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|row| row.get(0));
+
+    let unchanged = match (expected, current.as_deref()) {
+        (Some(image), Some(now)) => image == now,
+        (None, None) => true,
+        _ => false,
+    };
+    if !unchanged {
+        return Err(ReplayError::Conflict);
+    }
+
+    let (sql, values) = match &target_row {
+        None => (format!("DELETE FROM {} WHERE {}", table, key_clause), Vec::new()),
+        Some(row) if expected_row.is_none() => {
+            let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+            let placeholders: Vec<&str> = columns
+                .iter()
+                .map(|c| if c.is_blob { "unhex(?)" } else { "?" })
+                .collect();
+            (
+                format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    table,
+                    names.join(", "),
+                    placeholders.join(", ")
+                ),
+                columns.iter().map(|c| row.get(&c.name)).collect(),
+            )
+        }
+        Some(row) => {
+            let assignments: Vec<String> = columns
+                .iter()
+                .map(|c| {
+                    if c.is_blob {
+                        format!("{} = unhex(?)", c.name)
+                    } else {
+                        format!("{} = ?", c.name)
+                    }
+                })
+                .collect();
+            (
+                format!("UPDATE {} SET {} WHERE {}", table, assignments.join(", "), key_clause),
+                columns.iter().map(|c| row.get(&c.name)).collect(),
+            )
+        }
+    };
+
+    let mut audit_key: Option<AuditKey> = None;
+    for c in &key_columns {
+        let value = key_row.get(&c.name).cloned().unwrap_or(Value::Null);
+        audit_key = Some(match audit_key {
+            None => AuditKey::new(table, &c.name, value),
+            Some(key) => key.and(&c.name, value),
+        });
+    }
+    let audit_before = match &audit_key {
+        Some(key) => audit::snapshot(conn, key).await?,
+        None => None,
+    };
+
+    let mut query = sqlx::query(&sql);
+    for value in values {
+        query = bind_json(query, value);
+    }
+    if expected_row.is_some() {
+        for c in &key_columns {
+            query = bind_json(query, key_row.get(&c.name));
+        }
+    }
+    query.execute(&mut *conn).await?;
+
+    if let Some(key) = &audit_key {
+        audit::record(conn, user_id, key, audit_before).await?;
+    }
+    Ok(())
+}
+
+fn parse_image(image: &str) -> Result<Map<String, Value>, ReplayError> {
+    match serde_json::from_str::<Value>(image) {
+        Ok(Value::Object(map)) => Ok(map),
+        _ => Err(ReplayError::Conflict),
+    }
+}
+
+pub(crate) fn bind_json<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: Option<&Value>,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        None | Some(Value::Null) => query.bind(None::<String>),
+        Some(Value::Bool(b)) => query.bind(*b),
+        Some(Value::Number(n)) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Some(Value::String(s)) => query.bind(s.clone()),
+        Some(other) => query.bind(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::product;
+    use crate::services::transaction::TransactionService;
+    use crate::test_helpers::database::setup_migrated_test_db;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    /// User 2 with header 1 (lines 1 and 2, line 2 bought as product 1),
+    /// a tag on the header and a receipt attachment.
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'journal', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO PRODUCTS (PRODUCT_ID, USER_ID, PRODUCT_NAME) VALUES (1, 2, 'Coffee beans')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (1, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-01 10:00:00', 3000)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (1, 1, 2, 'EXPENSE', 'Milk', 1000)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT, PRODUCT_ID) VALUES (2, 1, 2, 'EXPENSE', 'Coffee', 2000, 1)",
+            "INSERT INTO TAGS (TAG_ID, USER_ID, TAG_NAME) VALUES (1, 2, 'weekly')",
+            "INSERT INTO TRANSACTION_TAGS (USER_ID, TAG_ID, TRANSACTION_ID) VALUES (2, 1, 1)",
+            "INSERT INTO ATTACHMENTS (USER_ID, TRANSACTION_ID, FILE_NAME, MIME_TYPE, BYTE_SIZE, SHA256, CONTENT) VALUES (2, 1, 'r.png', 'image/png', 3, 'x', X'00FF10')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    /// Every row hanging off header 1, as one comparable string.
+    async fn snapshot(pool: &SqlitePool) -> String {
+        sqlx::query_scalar(
+            "SELECT json_array(
+                (SELECT json_group_array(json_object('id', TRANSACTION_ID, 'total', TOTAL_AMOUNT)) FROM TRANSACTIONS_HEADER),
+                (SELECT json_group_array(json_object('id', DETAIL_ID, 'product', PRODUCT_ID)) FROM TRANSACTIONS_DETAIL),
+                (SELECT json_group_array(TAG_ID) FROM TRANSACTION_TAGS),
+                (SELECT json_group_array(hex(CONTENT)) FROM ATTACHMENTS),
+                (SELECT json_group_array(PRODUCT_NAME) FROM PRODUCTS))",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn undo_restores_cascaded_rows_and_redo_deletes_them_again() {
+        let pool = setup().await;
+        let original = snapshot(&pool).await;
+
+        let op = begin_operation(&pool, 2, "transaction.delete").await.unwrap();
+        TransactionService::new(pool.clone()).delete_transaction(2, 1).await.unwrap();
+        end_operation(&pool, op).await.unwrap();
+        let deleted = snapshot(&pool).await;
+        assert_eq!(deleted, r#"[[],[],[],[],["Coffee beans"]]"#);
+
+        // Header, both lines, the tag link and the attachment
+        let ops = list_operations(&pool, 2).await.unwrap();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].change_count, 5);
+
+        assert_eq!(undo_last(&pool, 2, 1).await.unwrap(), vec![op]);
+        assert_eq!(snapshot(&pool).await, original);
+        assert_eq!(
+            undo_operation(&pool, 2, op).await.unwrap_err().code,
+            ApiError::CODE_VALIDATION
+        );
+
+        assert_eq!(redo_last(&pool, 2, 5).await.unwrap(), vec![op]);
+        assert_eq!(snapshot(&pool).await, deleted);
+
+        // A new operation drops what was undone
+        undo_last(&pool, 2, 1).await.unwrap();
+        let next = begin_operation(&pool, 2, "transaction.update").await.unwrap();
+        end_operation(&pool, next).await.unwrap();
+        assert!(list_operations(&pool, 2).await.unwrap().is_empty());
+        assert!(redo_last(&pool, 2, 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn undo_product_delete_restores_set_null_links() {
+        let pool = setup().await;
+        let original = snapshot(&pool).await;
+
+        let op = begin_operation(&pool, 2, "product.delete").await.unwrap();
+        product::delete_product(&pool, 2, 1).await.unwrap();
+        end_operation(&pool, op).await.unwrap();
+        undo_operation(&pool, 2, op).await.unwrap();
+        assert_eq!(snapshot(&pool).await, original);
+        let disabled: i64 = sqlx::query_scalar("SELECT IS_DISABLED FROM PRODUCTS WHERE PRODUCT_ID = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(disabled, 0);
+
+        // delete_product only disables the row; a physical delete must
+        // also bring back the PRODUCT_ID its FK action cleared
+        let op = begin_operation(&pool, 2, "product.delete").await.unwrap();
+        sqlx::query("DELETE FROM PRODUCTS WHERE PRODUCT_ID = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        end_operation(&pool, op).await.unwrap();
+        let product_id: Option<i64> =
+            sqlx::query_scalar("SELECT PRODUCT_ID FROM TRANSACTIONS_DETAIL WHERE DETAIL_ID = 2")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(product_id, None);
+
+        undo_operation(&pool, 2, op).await.unwrap();
+        assert_eq!(snapshot(&pool).await, original);
+    }
+
+    #[tokio::test]
+    async fn undo_refuses_rows_changed_since_and_changes_nothing() {
+        let pool = setup().await;
+
+        let op = begin_operation(&pool, 2, "transaction.update").await.unwrap();
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET TOTAL_AMOUNT = 3500 WHERE TRANSACTION_ID = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM TRANSACTIONS_DETAIL WHERE DETAIL_ID = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        end_operation(&pool, op).await.unwrap();
+
+        // Changed outside any journaled operation
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET TOTAL_AMOUNT = 4000 WHERE TRANSACTION_ID = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let before_undo = snapshot(&pool).await;
+
+        let err = undo_last(&pool, 2, 1).await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_JOURNAL_CONFLICT);
+        assert_eq!(snapshot(&pool).await, before_undo);
+        assert_eq!(list_operations(&pool, 2).await.unwrap()[0].status, STATUS_DONE);
+
+        // Nothing journaled for another user, and bad counts are refused
+        assert!(list_operations(&pool, 1).await.unwrap().is_empty());
+        assert_eq!(undo_last(&pool, 2, 0).await.unwrap_err().code, ApiError::CODE_VALIDATION);
+    }
+
+    #[tokio::test]
+    async fn operation_of_a_panicked_command_is_still_ended() {
+        let pool = setup().await;
+        let journal = Arc::new(Mutex::new(()));
+
+        let lock = journal.clone().lock_owned().await;
+        let task_pool = pool.clone();
+        let result = tokio::spawn(async move {
+            let _operation = ActiveOperation::begin(&task_pool, 2, "transaction.update", lock)
+                .await
+                .unwrap();
+            sqlx::query("UPDATE TRANSACTIONS_HEADER SET TOTAL_AMOUNT = 3500 WHERE TRANSACTION_ID = 1")
+                .execute(&task_pool)
+                .await
+                .unwrap();
+            panic!("command panicked mid-operation");
+        })
+        .await;
+        assert!(result.unwrap_err().is_panic());
+
+        // The lock comes back only after the operation has ended, so this
+        // write is not recorded into it
+        let _journal = journal.lock().await;
+        let active: Option<i64> = sqlx::query_scalar(sql_queries::CHANGE_JOURNAL_GET_ACTIVE)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(active, None);
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET TOTAL_AMOUNT = 4000 WHERE TRANSACTION_ID = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let ops = list_operations(&pool, 2).await.unwrap();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].change_count, 1);
+    }
+}
@@ -23,6 +23,7 @@
 use sqlx::{sqlite::SqliteRow, FromRow, SqlitePool};
 
 use crate::api_error::ApiError;
+use crate::services::audit::{self, AuditKey};
 
 /// Static description of one master service's SQL surface + user-facing
 /// labels. Kept as `&'static str` fields so it can live in a `const` at
@@ -30,6 +31,8 @@ use crate::api_error::ApiError;
 /// no allocation, no lifetime plumbing.
 ///
 /// Fields:
+/// - `table` / `id_column` — the master table and its primary key, used to
+///   key the audit log entries written by [`run_delete_expect_one`].
 /// - `entity_label`      — capitalised English name for `ApiError` (`"Shop"`,
 ///   `"Manufacturer"`, ...). Serialised via `ApiError::not_found` /
 ///   `duplicate_name` and lowercased by those constructors before hitting
@@ -44,6 +47,8 @@ use crate::api_error::ApiError;
 /// - `delete_logical_sql` — bound with `(user_id, id)`. Must use logical
 ///   delete semantics that update at most one row.
 pub struct MasterCrudSpec {
+    pub table: &'static str,
+    pub id_column: &'static str,
     pub entity_label: &'static str,
     pub name_label: &'static str,
     pub check_duplicate_for_add_sql: &'static str,
@@ -175,21 +180,28 @@ pub async fn check_duplicate_for_update(
 /// deleted between the check and the execute, and the caller would still
 /// report success. Threading the count back and mapping `0 → NotFound`
 /// eliminates both.
+///
+/// The delete and its audit log entry share one transaction.
 pub async fn run_delete_expect_one(
     spec: &MasterCrudSpec,
     pool: &SqlitePool,
     user_id: i64,
     id: i64,
 ) -> Result<(), ApiError> {
+    let mut tx = pool.begin().await?;
+    let key = AuditKey::new(spec.table, spec.id_column, id);
+    let before = audit::snapshot(&mut tx, &key).await?;
     let affected = sqlx::query(spec.delete_logical_sql)
         .bind(user_id)
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?
         .rows_affected();
     if affected == 0 {
         return Err(ApiError::not_found(spec.entity_label));
     }
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
     Ok(())
 }
 
@@ -217,6 +229,8 @@ mod tests {
     // Every string is nonsense on purpose — these tests do not touch the
     // database, they only pin the two `expect_one` mappers.
     const TEST_SPEC: MasterCrudSpec = MasterCrudSpec {
+        table: "SHOPS",
+        id_column: "SHOP_ID",
         entity_label: "Shop",
         name_label: "Shop name",
         check_duplicate_for_add_sql: "SELECT 1",
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, FromRow};
 use crate::api_error::ApiError;
+use crate::services::audit::{self, AuditKey};
 use crate::services::master_data::{self, MasterCrudSpec};
 use crate::sql_queries;
 use crate::validation;
@@ -8,6 +9,8 @@ use crate::validation;
 /// Full description of the Product master's SQL surface + labels.
 /// Fable-5 review #26.
 const SPEC: MasterCrudSpec = MasterCrudSpec {
+    table: "PRODUCTS",
+    id_column: "PRODUCT_ID",
     entity_label: "Product",
     name_label: "Product name",
     check_duplicate_for_add_sql: sql_queries::PRODUCT_CHECK_DUPLICATE_FOR_ADD,
@@ -131,15 +134,20 @@ pub async fn add_product(
 
     let is_disabled = request.is_disabled.unwrap_or(0);
 
-    sqlx::query(sql_queries::PRODUCT_INSERT)
+    let mut tx = pool.begin().await?;
+    let product_id = sqlx::query(sql_queries::PRODUCT_INSERT)
         .bind(user_id)
         .bind(&request.product_name)
         .bind(&request.manufacturer_id)
         .bind(&request.memo)
         .bind(display_order)
         .bind(is_disabled)
-        .execute(pool)
-        .await?;
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+    let key = AuditKey::new(SPEC.table, SPEC.id_column, product_id);
+    audit::record(&mut tx, user_id, &key, None).await?;
+    tx.commit().await?;
 
     Ok("Product added successfully".to_string())
 }
@@ -196,6 +204,9 @@ pub async fn update_product(
     // Pre-check + not_found is now derived from `rows_affected` on the
     // UPDATE itself (Fable-5 review #26). See shop.rs::update_shop for
     // rationale.
+    let mut tx = pool.begin().await?;
+    let key = AuditKey::new(SPEC.table, SPEC.id_column, product_id);
+    let before = audit::snapshot(&mut tx, &key).await?;
     let affected = sqlx::query(sql_queries::PRODUCT_UPDATE)
         .bind(&request.product_name)
         .bind(&request.manufacturer_id)
@@ -204,10 +215,12 @@ pub async fn update_product(
         .bind(request.is_disabled)
         .bind(user_id)
         .bind(product_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?
         .rows_affected();
     master_data::ensure_update_affected_one(&SPEC, affected)?;
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
 
     Ok("Product updated successfully".to_string())
 }
@@ -232,6 +245,11 @@ mod tests {
     async fn setup_test_db() -> SqlitePool {
         let pool = init_db(TEST_DB_URL).await.unwrap();
 
+        sqlx::query(sql_queries::CREATE_AUDIT_LOG_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
+
         // Create USERS table
         sqlx::query(sql_queries::TEST_CREATE_USERS_TABLE)
             .execute(&pool)
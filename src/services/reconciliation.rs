@@ -0,0 +1,418 @@
+//! Account reconciliation against bank / card statements.
+//!
+//! A checkpoint records the balance a statement reports for an account on a
+//! date. Listing checkpoints recomputes the ledger balance for that date with
+//! `account::get_account_balances_as_of` and reports the difference. Rows are
+//! ticked off as cleared while the user compares them with the statement, and
+//! reconciling a checkpoint with no difference locks every cleared row up to
+//! the statement date (RECONCILE_STATUS = 2). Until it is unlocked here, a
+//! locked row is refused with `TransactionError::Locked` by every
+//! `TransactionService` write to it or its detail lines: header edits and
+//! total updates, deletion, detail add/update/delete and bulk changes. The
+//! all-headers total recalculation and restore skip locked rows.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::consts::{
+    RECONCILE_STATUS_CLEARED, RECONCILE_STATUS_RECONCILED, RECONCILE_STATUS_UNCLEARED,
+};
+use crate::services::account;
+use crate::services::audit;
+use crate::services::transaction::header_audit_key;
+use crate::sql_queries;
+use crate::validation;
+
+const ENTITY_LABEL: &str = "Checkpoint";
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct StatementCheckpoint {
+    pub checkpoint_id: i64,
+    pub user_id: i64,
+    pub account_code: String,
+    pub statement_date: String,
+    pub statement_balance: i64,
+    pub memo: Option<String>,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+/// Checkpoint with the ledger side recomputed at read time
+#[derive(Debug, Serialize, Clone)]
+pub struct CheckpointStatus {
+    #[serde(flatten)]
+    pub checkpoint: StatementCheckpoint,
+    /// Ledger balance of the account at the end of `statement_date`
+    pub computed_balance: i64,
+    /// `statement_balance - computed_balance`; 0 means the ledger agrees
+    pub difference: i64,
+    /// Actualised rows up to the statement date not yet cleared
+    pub uncleared_count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddCheckpointRequest {
+    pub account_code: String,
+    /// `YYYY-MM-DD`
+    pub statement_date: String,
+    pub statement_balance: i64,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconcileSummary {
+    pub checkpoint_id: i64,
+    /// Rows newly locked as reconciled
+    pub reconciled_count: u64,
+}
+
+async fn fetch_checkpoint(
+    pool: &SqlitePool,
+    user_id: i64,
+    checkpoint_id: i64,
+) -> Result<StatementCheckpoint, ApiError> {
+    sqlx::query_as::<_, StatementCheckpoint>(sql_queries::RECONCILIATION_CHECKPOINT_GET_BY_ID)
+        .bind(user_id)
+        .bind(checkpoint_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(ENTITY_LABEL))
+}
+
+async fn checkpoint_status(
+    pool: &SqlitePool,
+    checkpoint: StatementCheckpoint,
+) -> Result<CheckpointStatus, ApiError> {
+    let computed_balance = account::get_account_balances_as_of(
+        pool,
+        checkpoint.user_id,
+        &checkpoint.statement_date,
+    )
+    .await?
+    .find(&checkpoint.account_code)
+    .map(|b| b.balance)
+    .ok_or_else(|| ApiError::not_found("Account"))?;
+
+    let uncleared_count: i64 = sqlx::query_scalar(sql_queries::RECONCILIATION_COUNT_UNCLEARED)
+        .bind(checkpoint.user_id)
+        .bind(&checkpoint.account_code)
+        .bind(&checkpoint.account_code)
+        .bind(&checkpoint.statement_date)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(CheckpointStatus {
+        difference: checkpoint.statement_balance - computed_balance,
+        computed_balance,
+        uncleared_count,
+        checkpoint,
+    })
+}
+
+/// Record a statement balance for one of the user's active accounts
+pub async fn add_checkpoint(
+    pool: &SqlitePool,
+    user_id: i64,
+    request: AddCheckpointRequest,
+) -> Result<CheckpointStatus, ApiError> {
+    let account_code = request.account_code.trim().to_uppercase();
+    let statement_date = request.statement_date.trim();
+    if statement_date.len() != 10
+        || chrono::NaiveDate::parse_from_str(statement_date, "%Y-%m-%d").is_err()
+    {
+        return Err(ApiError::validation(
+            "Invalid statement date. Use YYYY-MM-DD",
+        ));
+    }
+    validation::validate_memo("Memo", request.memo.as_ref()).map_err(ApiError::validation)?;
+
+    match account::get_account_by_code(pool, user_id, &account_code).await? {
+        Some(a) if a.is_disabled == 0 => {}
+        _ => return Err(ApiError::not_found("Account")),
+    }
+
+    let checkpoint_id = sqlx::query(sql_queries::RECONCILIATION_CHECKPOINT_INSERT)
+        .bind(user_id)
+        .bind(&account_code)
+        .bind(statement_date)
+        .bind(request.statement_balance)
+        .bind(&request.memo)
+        .execute(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                ApiError::validation("A checkpoint already exists for this account and date")
+            }
+            other => other.into(),
+        })?
+        .last_insert_rowid();
+
+    let checkpoint = fetch_checkpoint(pool, user_id, checkpoint_id).await?;
+    checkpoint_status(pool, checkpoint).await
+}
+
+/// Checkpoints of one account, newest statement first
+pub async fn list_checkpoints(
+    pool: &SqlitePool,
+    user_id: i64,
+    account_code: &str,
+) -> Result<Vec<CheckpointStatus>, ApiError> {
+    let checkpoints =
+        sqlx::query_as::<_, StatementCheckpoint>(sql_queries::RECONCILIATION_CHECKPOINT_LIST_BY_ACCOUNT)
+            .bind(user_id)
+            .bind(account_code.trim().to_uppercase())
+            .fetch_all(pool)
+            .await?;
+
+    let mut statuses = Vec::with_capacity(checkpoints.len());
+    for checkpoint in checkpoints {
+        statuses.push(checkpoint_status(pool, checkpoint).await?);
+    }
+    Ok(statuses)
+}
+
+/// Delete a checkpoint. Rows it reconciled stay locked.
+pub async fn delete_checkpoint(
+    pool: &SqlitePool,
+    user_id: i64,
+    checkpoint_id: i64,
+) -> Result<(), ApiError> {
+    let affected = sqlx::query(sql_queries::RECONCILIATION_CHECKPOINT_DELETE)
+        .bind(user_id)
+        .bind(checkpoint_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    Ok(())
+}
+
+/// Move each listed header from `from_status` to `to_status` in one DB
+/// transaction. Rows in any other state are left alone and not counted.
+async fn transition_status(
+    pool: &SqlitePool,
+    user_id: i64,
+    transaction_ids: &[i64],
+    from_status: i64,
+    to_status: i64,
+) -> Result<u64, ApiError> {
+    let mut tx = pool.begin().await?;
+    let mut changed = 0;
+    for &transaction_id in transaction_ids {
+        let key = header_audit_key(transaction_id);
+        let before = audit::snapshot(&mut tx, &key).await?;
+        changed += sqlx::query(sql_queries::RECONCILIATION_SET_STATUS)
+            .bind(to_status)
+            .bind(user_id)
+            .bind(transaction_id)
+            .bind(from_status)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        audit::record(&mut tx, user_id, &key, before).await?;
+    }
+    tx.commit().await?;
+    Ok(changed)
+}
+
+/// Tick (or untick) transactions as cleared. Reconciled rows are skipped;
+/// they have to be unlocked first. Returns the number of rows changed.
+pub async fn set_cleared(
+    pool: &SqlitePool,
+    user_id: i64,
+    transaction_ids: &[i64],
+    cleared: bool,
+) -> Result<u64, ApiError> {
+    let (from, to) = if cleared {
+        (RECONCILE_STATUS_UNCLEARED, RECONCILE_STATUS_CLEARED)
+    } else {
+        (RECONCILE_STATUS_CLEARED, RECONCILE_STATUS_UNCLEARED)
+    };
+    transition_status(pool, user_id, transaction_ids, from, to).await
+}
+
+/// Unlock reconciled transactions so they can be edited again. They drop
+/// back to cleared; reconciling a checkpoint locks them again.
+pub async fn unlock_transactions(
+    pool: &SqlitePool,
+    user_id: i64,
+    transaction_ids: &[i64],
+) -> Result<u64, ApiError> {
+    transition_status(
+        pool,
+        user_id,
+        transaction_ids,
+        RECONCILE_STATUS_RECONCILED,
+        RECONCILE_STATUS_CLEARED,
+    )
+    .await
+}
+
+/// Finish a reconciliation: when the ledger matches the statement, lock
+/// every cleared row of the account up to the statement date.
+pub async fn reconcile_checkpoint(
+    pool: &SqlitePool,
+    user_id: i64,
+    checkpoint_id: i64,
+) -> Result<ReconcileSummary, ApiError> {
+    let checkpoint = fetch_checkpoint(pool, user_id, checkpoint_id).await?;
+    let status = checkpoint_status(pool, checkpoint).await?;
+    if status.difference != 0 {
+        return Err(ApiError::validation(format!(
+            "Statement balance differs from the ledger by {}",
+            status.difference
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    let ids: Vec<i64> = sqlx::query_scalar(sql_queries::RECONCILIATION_CLEARED_IDS_TO_RECONCILE)
+        .bind(user_id)
+        .bind(&status.checkpoint.account_code)
+        .bind(&status.checkpoint.account_code)
+        .bind(&status.checkpoint.statement_date)
+        .fetch_all(&mut *tx)
+        .await?;
+    let mut snapshots = Vec::with_capacity(ids.len());
+    for &id in &ids {
+        let key = header_audit_key(id);
+        let before = audit::snapshot(&mut tx, &key).await?;
+        snapshots.push((key, before));
+    }
+    let reconciled_count = sqlx::query(sql_queries::RECONCILIATION_MARK_CLEARED_AS_RECONCILED)
+        .bind(user_id)
+        .bind(&status.checkpoint.account_code)
+        .bind(&status.checkpoint.account_code)
+        .bind(&status.checkpoint.statement_date)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    for (key, before) in snapshots {
+        audit::record(&mut tx, user_id, &key, before).await?;
+    }
+    tx.commit().await?;
+
+    Ok(ReconcileSummary {
+        checkpoint_id,
+        reconciled_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    async fn setup_reconcile_db() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'rec', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'INCOME', 2, '収入', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, INITIAL_BALANCE) VALUES (2, 'BANK', 'Bank', 'BANK', 10000)",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (1, 2, 'INCOME', 'NONE', 'BANK', '2024-06-05 09:00:00', 5000)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 2, 'EXPENSE', 'BANK', 'NONE', '2024-06-20 09:00:00', 3000)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (3, 2, 'EXPENSE', 'BANK', 'NONE', '2024-07-02 09:00:00', 700)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    async fn status_of(pool: &SqlitePool, transaction_id: i64) -> i64 {
+        sqlx::query_scalar(sql_queries::TRANSACTION_HEADER_GET_RECONCILE_STATUS)
+            .bind(transaction_id)
+            .bind(2)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn checkpoint(date: &str, balance: i64) -> AddCheckpointRequest {
+        AddCheckpointRequest {
+            account_code: "bank".to_string(),
+            statement_date: date.to_string(),
+            statement_balance: balance,
+            memo: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_reports_difference_from_computed_balance() {
+        let pool = setup_reconcile_db().await;
+
+        // 10000 + 5000 - 3000 = 12000 at the end of June; the bank says 11500.
+        let status = add_checkpoint(&pool, 2, checkpoint("2024-06-30", 11500)).await.unwrap();
+        assert_eq!(status.checkpoint.account_code, "BANK");
+        assert_eq!(status.computed_balance, 12000);
+        assert_eq!(status.difference, -500);
+        assert_eq!(status.uncleared_count, 2);
+
+        let dup = add_checkpoint(&pool, 2, checkpoint("2024-06-30", 1)).await.unwrap_err();
+        assert_eq!(dup.code, ApiError::CODE_VALIDATION);
+        let mut unknown = checkpoint("2024-06-30", 1);
+        unknown.account_code = "NOPE".into();
+        assert_eq!(
+            add_checkpoint(&pool, 2, unknown).await.unwrap_err().code,
+            ApiError::CODE_NOT_FOUND
+        );
+
+        // A missing 500 expense shows up and the difference closes.
+        sqlx::query("INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 'EXPENSE', 'BANK', 'NONE', '2024-06-28 12:00:00', 500)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let listed = list_checkpoints(&pool, 2, "BANK").await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].difference, 0);
+
+        delete_checkpoint(&pool, 2, listed[0].checkpoint.checkpoint_id).await.unwrap();
+        assert!(list_checkpoints(&pool, 2, "BANK").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_locks_cleared_rows_up_to_statement_date() {
+        let pool = setup_reconcile_db().await;
+
+        assert_eq!(set_cleared(&pool, 2, &[1, 2, 3], true).await.unwrap(), 3);
+        assert_eq!(set_cleared(&pool, 2, &[3], false).await.unwrap(), 1);
+
+        let mismatched = add_checkpoint(&pool, 2, checkpoint("2024-06-30", 99)).await.unwrap();
+        let err = reconcile_checkpoint(&pool, 2, mismatched.checkpoint.checkpoint_id)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+        assert_eq!(status_of(&pool, 1).await, RECONCILE_STATUS_CLEARED);
+        delete_checkpoint(&pool, 2, mismatched.checkpoint.checkpoint_id).await.unwrap();
+
+        let ok = add_checkpoint(&pool, 2, checkpoint("2024-06-30", 12000)).await.unwrap();
+        let summary = reconcile_checkpoint(&pool, 2, ok.checkpoint.checkpoint_id).await.unwrap();
+        assert_eq!(summary.reconciled_count, 2);
+        assert_eq!(status_of(&pool, 1).await, RECONCILE_STATUS_RECONCILED);
+        assert_eq!(status_of(&pool, 2).await, RECONCILE_STATUS_RECONCILED);
+        assert_eq!(status_of(&pool, 3).await, RECONCILE_STATUS_UNCLEARED);
+
+        // Clearing cannot touch a reconciled row; unlocking can.
+        assert_eq!(set_cleared(&pool, 2, &[1], false).await.unwrap(), 0);
+        assert_eq!(unlock_transactions(&pool, 2, &[1]).await.unwrap(), 1);
+        assert_eq!(status_of(&pool, 1).await, RECONCILE_STATUS_CLEARED);
+
+        // Every status change is audited against its header; the skipped
+        // set_cleared call above wrote nothing.
+        let audited: Vec<(String, String)> = sqlx::query_as(
+            "SELECT ENTITY_KEY, CHANGED_COLUMNS FROM AUDIT_LOG WHERE USER_ID = 2 ORDER BY AUDIT_ID",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(audited.len(), 7);
+        assert!(audited
+            .iter()
+            .all(|(_, changed)| changed.contains("RECONCILE_STATUS")));
+        assert_eq!(audited[6].0, r#"{"TRANSACTION_ID":1}"#);
+    }
+}
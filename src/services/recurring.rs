@@ -1,14 +1,20 @@
 //! 繰り返し予定入出金（v2.1.0）の周期計算と DB アクセス。
 //! 周期計算は純粋関数として切り出し、DB / Tauri コマンド層から独立してテスト可能にしている。
 
-use chrono::{Datelike, Days, NaiveDate, Weekday};
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
-use std::collections::HashSet;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::{HashMap, HashSet};
 
 use crate::api_error::ApiError;
+use crate::services::audit;
+use crate::services::currency;
 use crate::services::holiday::{shift_for_holidays, HolidayShift};
 use crate::services::period::end_of_month;
+use crate::services::transaction::{
+    calculate_recommended_total_with_settings, header_audit_key, DetailForRecalc,
+};
 use crate::{sql_queries, consts, validation};
 
 const ENTITY_LABEL: &str = "Recurring rule";
@@ -117,6 +123,37 @@ pub fn generate_dates(
     shifted
 }
 
+/// 個別の発生日に対する例外（RECURRING_RULE_EXCEPTIONS の 1 行）。
+/// キーはルールの周期が生成する日付（休日シフト後）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OccurrenceException {
+    /// その回だけ発生させない
+    Skip,
+    /// その回だけ別の日付に移す
+    MoveTo(NaiveDate),
+}
+
+/// `generate_dates` の結果に例外を適用する。Skip の日付は除き、MoveTo の日付は
+/// 移動先に置き換える。移動で順序が変わり得るため再ソートする（重複は除去しない）。
+pub fn apply_exceptions(
+    dates: Vec<NaiveDate>,
+    exceptions: &HashMap<NaiveDate, OccurrenceException>,
+) -> Vec<NaiveDate> {
+    if exceptions.is_empty() {
+        return dates;
+    }
+    let mut applied: Vec<NaiveDate> = dates
+        .into_iter()
+        .filter_map(|d| match exceptions.get(&d) {
+            None => Some(d),
+            Some(OccurrenceException::Skip) => None,
+            Some(OccurrenceException::MoveTo(to)) => Some(*to),
+        })
+        .collect();
+    applied.sort();
+    applied
+}
+
 fn generate_daily(
     interval: u32,
     anchor: NaiveDate,
@@ -540,6 +577,8 @@ pub enum RecurringError {
     Database(sqlx::Error),
     Validation(String),
     NotFound,
+    /// The occurrence is reconciled and must not change.
+    Locked,
 }
 
 impl std::fmt::Display for RecurringError {
@@ -548,6 +587,7 @@ impl std::fmt::Display for RecurringError {
             RecurringError::Database(e) => write!(f, "Database error: {}", e),
             RecurringError::Validation(msg) => write!(f, "Validation error: {}", msg),
             RecurringError::NotFound => write!(f, "Recurring rule not found"),
+            RecurringError::Locked => write!(f, "Occurrence is reconciled"),
         }
     }
 }
@@ -573,12 +613,14 @@ impl From<sqlx::Error> for RecurringError {
 ///                         screen can still dispatch bounded-field errors to
 ///                         the right inline input)
 ///   - `Database(e)`     → `database`
+///   - `Locked`          → `reconciled_locked`
 impl From<RecurringError> for ApiError {
     fn from(err: RecurringError) -> Self {
         match err {
             RecurringError::NotFound => ApiError::not_found(ENTITY_LABEL),
             RecurringError::Validation(msg) => ApiError::validation(msg),
             RecurringError::Database(e) => ApiError::database(e.to_string()),
+            RecurringError::Locked => ApiError::reconciled_locked(),
         }
     }
 }
@@ -596,9 +638,10 @@ pub struct SaveRecurringRuleRequest {
     pub week_of_month: Option<u32>,
     pub month_of_year: Option<u32>,
     pub holiday_shift_type: i32,
-    // Period (YYYY-MM-DD)
+    // Period (YYYY-MM-DD); no end_date makes the rule open-ended
     pub start_date: String,
-    pub end_date: String,
+    #[serde(default)]
+    pub end_date: Option<String>,
     // HEADER template
     pub shop_id: Option<i64>,
     pub category1_code: String,
@@ -608,8 +651,11 @@ pub struct SaveRecurringRuleRequest {
     pub tax_rounding_type: i64,
     pub tax_included_type: i64,
     pub header_memo: Option<String>,
-    // DETAIL template (1:1)
-    pub detail: SaveRecurringRuleDetailRequest,
+    // TOTAL_AMOUNT is an estimate; the actual amount is given on confirm
+    #[serde(default)]
+    pub is_estimated: bool,
+    // DETAIL templates, copied in this order into every occurrence
+    pub details: Vec<SaveRecurringRuleDetailRequest>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -634,6 +680,17 @@ pub struct CreateRecurringRuleResult {
     pub first_transaction_id: Option<i64>,
 }
 
+/// Outcome of `update_rule` for the rule's scheduled occurrences dated today
+/// or later: rows inserted for newly generated dates, rows deleted because
+/// their date is no longer generated, and rows rewritten in place.
+#[derive(Debug, Serialize)]
+pub struct UpdateRecurringRuleResult {
+    pub rule_id: i64,
+    pub added_count: usize,
+    pub removed_count: usize,
+    pub kept_count: usize,
+}
+
 #[derive(Debug, Serialize)]
 pub struct RecurringRuleSummary {
     pub rule_id: i64,
@@ -641,12 +698,496 @@ pub struct RecurringRuleSummary {
     pub period_unit: String,
     pub period_interval: i64,
     pub start_date: String,
-    pub end_date: String,
+    pub end_date: Option<String>,
     pub total_amount: i64,
     pub holiday_shift_type: i32,
+    pub is_estimated: bool,
     pub occurrence_count: i64,
 }
 
+/// Estimate for a variable-amount rule: the average TOTAL_AMOUNT of its
+/// `sample_count` most recent confirmed occurrences, rounded half up.
+/// `estimated_amount` is `None` until an occurrence has been confirmed.
+#[derive(Debug, Serialize)]
+pub struct RecurringEstimateSuggestion {
+    pub rule_id: i64,
+    pub sample_count: usize,
+    pub estimated_amount: Option<i64>,
+}
+
+/// Skip or move one occurrence of a rule. `occurrence_date` is a date the
+/// rule's cycle generates; `moved_to_date` is given for MOVE only.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SaveRecurringExceptionRequest {
+    pub occurrence_date: String,
+    pub exception_type: String,
+    #[serde(default)]
+    pub moved_to_date: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecurringExceptionSummary {
+    pub occurrence_date: String,
+    pub exception_type: String,
+    pub moved_to_date: Option<String>,
+}
+
+/// A validated `SaveRecurringRuleRequest`: its cycle columns, the spec they
+/// describe and the parsed period (`end` is `None` for an open-ended rule).
+struct ParsedRule {
+    columns: CycleColumns,
+    spec: CyclicSpec,
+    start: NaiveDate,
+    end: Option<NaiveDate>,
+}
+
+impl ParsedRule {
+    /// Last date occurrences are generated for as of `today`: the end date,
+    /// or the rolling horizon of an open-ended rule.
+    fn window_end(&self, today: NaiveDate) -> NaiveDate {
+        self.end.unwrap_or_else(|| open_ended_horizon(today))
+    }
+}
+
+/// How far ahead of `today` an open-ended rule is materialized.
+fn open_ended_horizon(today: NaiveDate) -> NaiveDate {
+    today
+        .checked_add_months(Months::new(consts::RECURRING_OPEN_ENDED_HORIZON_MONTHS))
+        .unwrap_or(NaiveDate::MAX)
+}
+
+/// Validation shared by rule creation and rule edits. Runs before any DB I/O.
+fn parse_rule_request(request: &SaveRecurringRuleRequest) -> Result<ParsedRule, RecurringError> {
+    let start = NaiveDate::parse_from_str(&request.start_date, "%Y-%m-%d")
+        .map_err(|_| RecurringError::Validation(
+            format!("Invalid start_date: {}", request.start_date)
+        ))?;
+    let end = match &request.end_date {
+        Some(s) => Some(
+            NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| {
+                RecurringError::Validation(format!("Invalid end_date: {}", s))
+            })?,
+        ),
+        None => None,
+    };
+    if end.is_some_and(|end| start > end) {
+        return Err(RecurringError::Validation(
+            "start_date must be on or before end_date".to_string(),
+        ));
+    }
+    if request.total_amount < 0 || request.total_amount > 999_999_999 {
+        return Err(RecurringError::Validation(
+            "TOTAL_AMOUNT must be between 0 and 999,999,999".to_string(),
+        ));
+    }
+    if request.details.is_empty() {
+        return Err(RecurringError::Validation(
+            "At least one DETAIL line is required".to_string(),
+        ));
+    }
+    if request.details.iter().any(|d| d.item_name.trim().is_empty()) {
+        return Err(RecurringError::Validation(
+            "DETAIL.item_name must not be empty".to_string(),
+        ));
+    }
+
+    // Bounded-field length checks (Issue #37 Phase 2-3, character count).
+    validation::validate_optional_max_chars(
+        "Rule name",
+        request.rule_name.as_ref(),
+        consts::MAX_RULE_NAME_LEN,
+    )
+    .map_err(RecurringError::Validation)?;
+    for detail in &request.details {
+        validation::validate_max_chars(
+            "Item name",
+            &detail.item_name,
+            consts::MAX_ITEM_NAME_LEN,
+        )
+        .map_err(RecurringError::Validation)?;
+    }
+    validation::validate_memo("Header memo", request.header_memo.as_ref())
+        .map_err(RecurringError::Validation)?;
+    for detail in &request.details {
+        validation::validate_memo("Detail memo", detail.detail_memo.as_ref())
+            .map_err(RecurringError::Validation)?;
+    }
+
+    let anchor_date = match &request.anchor_date {
+        Some(s) => Some(
+            NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| {
+                RecurringError::Validation(format!("Invalid anchor_date: {}", s))
+            })?,
+        ),
+        None => None,
+    };
+    let columns = CycleColumns {
+        period_unit: request.period_unit.clone(),
+        period_interval: request.period_interval,
+        anchor_date,
+        day_of_week: request.day_of_week,
+        month_day_rule_type: request.month_day_rule_type.clone(),
+        day_of_month: request.day_of_month,
+        week_of_month: request.week_of_month,
+        month_of_year: request.month_of_year,
+        holiday_shift_type: request.holiday_shift_type,
+    };
+    let spec = columns_to_cyclic_spec(&columns).map_err(RecurringError::Validation)?;
+
+    Ok(ParsedRule { columns, spec, start, end })
+}
+
+/// Reject a TOTAL_AMOUNT that differs from what the DETAIL lines add up to
+/// under the rule's tax settings, the same total a hand-entered transaction
+/// would be given. Checked when a rule is saved, not when stored rules are
+/// extended.
+fn validate_rule_total(request: &SaveRecurringRuleRequest) -> Result<(), RecurringError> {
+    let details: Vec<DetailForRecalc> = request
+        .details
+        .iter()
+        .map(|d| DetailForRecalc {
+            amount: d.amount,
+            amount_including_tax: d.amount_including_tax,
+            tax_rate: d.tax_rate as i64,
+            leg: 0,
+        })
+        .collect();
+    let expected = calculate_recommended_total_with_settings(
+        &details,
+        request.tax_rounding_type,
+        request.tax_included_type,
+    );
+    if expected != request.total_amount {
+        return Err(RecurringError::Validation(format!(
+            "TOTAL_AMOUNT {} does not match the DETAIL lines (expected {})",
+            request.total_amount, expected
+        )));
+    }
+    Ok(())
+}
+
+/// Insert a fresh MEMOS row for a non-blank memo text.
+async fn insert_memo(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    text: Option<&String>,
+) -> Result<Option<i64>, RecurringError> {
+    match text {
+        Some(text) if !text.trim().is_empty() => {
+            let r = sqlx::query(sql_queries::MEMO_INSERT)
+                .bind(user_id)
+                .bind(text)
+                .execute(&mut *conn)
+                .await?;
+            Ok(Some(r.last_insert_rowid()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// One memo per DETAIL line, in `request.details` order.
+async fn insert_detail_memos(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    request: &SaveRecurringRuleRequest,
+) -> Result<Vec<Option<i64>>, RecurringError> {
+    let mut memo_ids = Vec::with_capacity(request.details.len());
+    for detail in &request.details {
+        memo_ids.push(insert_memo(conn, user_id, detail.detail_memo.as_ref()).await?);
+    }
+    Ok(memo_ids)
+}
+
+/// Store the DETAIL templates of a rule.
+async fn insert_rule_details(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    rule_id: i64,
+    request: &SaveRecurringRuleRequest,
+    detail_memo_ids: &[Option<i64>],
+) -> Result<(), RecurringError> {
+    for (detail, memo_id) in request.details.iter().zip(detail_memo_ids) {
+        sqlx::query(sql_queries::RECURRING_RULE_DETAILS_INSERT)
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(&detail.category1_code)
+            .bind(&detail.category2_code)
+            .bind(&detail.category3_code)
+            .bind(&detail.item_name)
+            .bind(detail.amount)
+            .bind(detail.tax_amount)
+            .bind(detail.tax_rate)
+            .bind(detail.amount_including_tax)
+            .bind(detail.product_id)
+            .bind(*memo_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+fn parse_occurrence_date(value: Option<String>) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.as_deref()?, "%Y-%m-%d").ok()
+}
+
+fn parse_request_date(field: &str, value: &str) -> Result<NaiveDate, RecurringError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| RecurringError::Validation(format!("Invalid {}: {}", field, value)))
+}
+
+/// The exceptions recorded for a rule, keyed by occurrence date.
+async fn load_exceptions(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    rule_id: i64,
+) -> Result<HashMap<NaiveDate, OccurrenceException>, RecurringError> {
+    use sqlx::Row;
+
+    let rows = sqlx::query(sql_queries::RECURRING_RULE_EXCEPTIONS_BY_RULE)
+        .bind(rule_id)
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let date = parse_occurrence_date(row.get("OCCURRENCE_DATE"))?;
+            let exception = match row.get::<String, _>("EXCEPTION_TYPE").as_str() {
+                consts::RECURRING_EXCEPTION_SKIP => OccurrenceException::Skip,
+                consts::RECURRING_EXCEPTION_MOVE => {
+                    OccurrenceException::MoveTo(parse_occurrence_date(row.get("MOVED_TO_DATE"))?)
+                }
+                _ => return None,
+            };
+            Some((date, exception))
+        })
+        .collect())
+}
+
+/// TRANSACTION_ID of the rule's scheduled occurrence on `date`, if any.
+/// A confirmed occurrence there is an error: exceptions only change what
+/// is still scheduled. So is a reconciled one, which must not change.
+async fn scheduled_occurrence_on(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    rule_id: i64,
+    date: NaiveDate,
+) -> Result<Option<i64>, RecurringError> {
+    use sqlx::Row;
+
+    let row = sqlx::query(sql_queries::TRANSACTIONS_HEADER_BY_RULE_ON_DATE)
+        .bind(rule_id)
+        .bind(user_id)
+        .bind(date.format("%Y-%m-%d").to_string())
+        .fetch_optional(&mut *conn)
+        .await?;
+    match row {
+        None => Ok(None),
+        Some(row) if row.get::<i64, _>("RECONCILE_STATUS") == consts::RECONCILE_STATUS_RECONCILED => {
+            Err(RecurringError::Locked)
+        }
+        Some(row) if row.get::<i64, _>("IS_SCHEDULED") == 1 => Ok(Some(row.get("TRANSACTION_ID"))),
+        Some(_) => Err(RecurringError::Validation(format!(
+            "The occurrence on {} is already confirmed",
+            date
+        ))),
+    }
+}
+
+async fn set_occurrence_date(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    transaction_id: i64,
+    date: NaiveDate,
+) -> Result<(), RecurringError> {
+    sqlx::query(sql_queries::TRANSACTIONS_HEADER_SET_OCCURRENCE_DATE)
+        .bind(occurrence_datetime(date))
+        .bind(transaction_id)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// HEADER + DETAIL values written to every occurrence of one rule.
+/// `detail_memo_ids` runs parallel to `request.details`.
+struct OccurrenceTemplate<'a> {
+    user_id: i64,
+    rule_id: i64,
+    request: &'a SaveRecurringRuleRequest,
+    header_memo_id: Option<i64>,
+    detail_memo_ids: Vec<Option<i64>>,
+    currency_code: &'a str,
+}
+
+impl OccurrenceTemplate<'_> {
+    /// Insert a scheduled occurrence on `date` and return its TRANSACTION_ID.
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        date: NaiveDate,
+    ) -> Result<i64, RecurringError> {
+        let request = self.request;
+        let header_result = sqlx::query(sql_queries::TRANSACTIONS_HEADER_INSERT_FOR_RECURRING)
+            .bind(self.user_id)
+            .bind(request.shop_id)
+            .bind(occurrence_datetime(date))
+            .bind(&request.category1_code)
+            .bind(&request.from_account_code)
+            .bind(&request.to_account_code)
+            .bind(request.total_amount)
+            .bind(request.tax_rounding_type)
+            .bind(request.tax_included_type)
+            .bind(self.header_memo_id)
+            .bind(self.rule_id)
+            .bind(self.currency_code)
+            .execute(&mut *conn)
+            .await?;
+        let header_id = header_result.last_insert_rowid();
+        self.insert_details(conn, header_id).await?;
+        Ok(header_id)
+    }
+
+    /// Overwrite an existing scheduled occurrence with the template, keeping
+    /// its TRANSACTION_ID (and so its tags and attachments).
+    async fn rewrite(
+        &self,
+        conn: &mut SqliteConnection,
+        transaction_id: i64,
+        date: NaiveDate,
+    ) -> Result<(), RecurringError> {
+        let request = self.request;
+        sqlx::query(sql_queries::TRANSACTION_HEADER_UPDATE)
+            .bind(request.shop_id)
+            .bind(occurrence_datetime(date))
+            .bind(&request.category1_code)
+            .bind(&request.from_account_code)
+            .bind(&request.to_account_code)
+            .bind(request.total_amount)
+            .bind(request.tax_rounding_type)
+            .bind(request.tax_included_type)
+            .bind(self.header_memo_id)
+            .bind(self.currency_code)
+            .bind(transaction_id)
+            .bind(self.user_id)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(sql_queries::TRANSACTIONS_DETAIL_DELETE_BY_TRANSACTION)
+            .bind(transaction_id)
+            .bind(self.user_id)
+            .execute(&mut *conn)
+            .await?;
+        self.insert_details(conn, transaction_id).await
+    }
+
+    async fn insert_details(
+        &self,
+        conn: &mut SqliteConnection,
+        transaction_id: i64,
+    ) -> Result<(), RecurringError> {
+        for (detail, memo_id) in self.request.details.iter().zip(&self.detail_memo_ids) {
+            sqlx::query(sql_queries::TRANSACTION_DETAIL_INSERT_FULL)
+                .bind(transaction_id)
+                .bind(self.user_id)
+                .bind(&detail.category1_code)
+                .bind(&detail.category2_code)
+                .bind(&detail.category3_code)
+                .bind(&detail.item_name)
+                .bind(detail.amount)
+                .bind(detail.tax_amount)
+                .bind(detail.tax_rate)
+                .bind(detail.amount_including_tax)
+                .bind(detail.product_id)
+                .bind(*memo_id)
+                .bind(None::<String>)
+                .bind(None::<String>)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild the request a stored rule was saved from, as read by
+/// `RECURRING_RULES_OPEN_ENDED_DUE` or `RECURRING_RULE_GET_BY_ID` and
+/// `RECURRING_RULE_DETAILS_BY_RULE`. Memo texts are left out; occurrences
+/// reuse the rule's MEMO_IDs.
+fn rule_request_from_rows(row: &SqliteRow, detail_rows: &[SqliteRow]) -> SaveRecurringRuleRequest {
+    use sqlx::Row;
+
+    let to_u32 = |v: Option<i64>| v.map(|v| v as u32);
+    SaveRecurringRuleRequest {
+        rule_name: row.get("RULE_NAME"),
+        period_unit: row.get("PERIOD_UNIT"),
+        period_interval: row.get::<i64, _>("PERIOD_INTERVAL") as u32,
+        anchor_date: row.get("ANCHOR_DATE"),
+        day_of_week: to_u32(row.get("DAY_OF_WEEK")),
+        month_day_rule_type: row.get("MONTH_DAY_RULE_TYPE"),
+        day_of_month: to_u32(row.get("DAY_OF_MONTH")),
+        week_of_month: to_u32(row.get("WEEK_OF_MONTH")),
+        month_of_year: to_u32(row.get("MONTH_OF_YEAR")),
+        holiday_shift_type: row.get("HOLIDAY_SHIFT_TYPE"),
+        start_date: row.get("START_DATE"),
+        end_date: row.get("END_DATE"),
+        shop_id: row.get("SHOP_ID"),
+        category1_code: row.get("CATEGORY1_CODE"),
+        from_account_code: row.get("FROM_ACCOUNT_CODE"),
+        to_account_code: row.get("TO_ACCOUNT_CODE"),
+        total_amount: row.get("TOTAL_AMOUNT"),
+        tax_rounding_type: row.get("TAX_ROUNDING_TYPE"),
+        tax_included_type: row.get("TAX_INCLUDED_TYPE"),
+        header_memo: None,
+        is_estimated: row.get::<i64, _>("IS_ESTIMATED") != 0,
+        details: detail_rows
+            .iter()
+            .map(|d| SaveRecurringRuleDetailRequest {
+                category1_code: d.get("CATEGORY1_CODE"),
+                category2_code: d.get("CATEGORY2_CODE"),
+                category3_code: d.get("CATEGORY3_CODE"),
+                item_name: d.get("ITEM_NAME"),
+                amount: d.get("AMOUNT"),
+                tax_amount: d.get("TAX_AMOUNT"),
+                tax_rate: d.get("TAX_RATE"),
+                amount_including_tax: d.get("AMOUNT_INCLUDING_TAX"),
+                product_id: d.get("PRODUCT_ID"),
+                detail_memo: None,
+            })
+            .collect(),
+    }
+}
+
+fn occurrence_datetime(date: NaiveDate) -> String {
+    format!("{} 00:00:00", date.format("%Y-%m-%d"))
+}
+
+/// A rule read back from the database, with the memo ids its occurrences
+/// use and the last date occurrences have been generated up to.
+struct StoredRule {
+    request: SaveRecurringRuleRequest,
+    header_memo_id: Option<i64>,
+    detail_memo_ids: Vec<Option<i64>>,
+    materialized_until: Option<NaiveDate>,
+}
+
+impl StoredRule {
+    fn template<'a>(&'a self, user_id: i64, rule_id: i64, currency_code: &'a str) -> OccurrenceTemplate<'a> {
+        OccurrenceTemplate {
+            user_id,
+            rule_id,
+            request: &self.request,
+            header_memo_id: self.header_memo_id,
+            detail_memo_ids: self.detail_memo_ids.clone(),
+            currency_code,
+        }
+    }
+
+    /// Whether occurrences on `date` have been generated already, so that
+    /// one restored or moved there needs a row of its own.
+    fn is_materialized(&self, date: NaiveDate) -> bool {
+        self.materialized_until.is_some_and(|until| date <= until)
+    }
+}
+
 pub struct RecurringService {
     pool: SqlitePool,
 }
@@ -663,113 +1204,162 @@ impl RecurringService {
     /// Returns the new RULE_ID, the number of occurrences generated, and the
     /// first generated TRANSACTION_ID (if any) for callers that want to surface
     /// it in a result message.
+    ///
+    /// A rule without an end date only gets occurrences up to
+    /// `RECURRING_OPEN_ENDED_HORIZON_MONTHS` ahead; `extend_open_ended_rules`
+    /// adds the rest as time passes.
     pub async fn create_rule_with_instances(
         &self,
         user_id: i64,
         request: SaveRecurringRuleRequest,
     ) -> Result<CreateRecurringRuleResult, RecurringError> {
-        // ----- Parse + validate inputs -----
-        let start = NaiveDate::parse_from_str(&request.start_date, "%Y-%m-%d")
-            .map_err(|_| RecurringError::Validation(
-                format!("Invalid start_date: {}", request.start_date)
-            ))?;
-        let end = NaiveDate::parse_from_str(&request.end_date, "%Y-%m-%d")
-            .map_err(|_| RecurringError::Validation(
-                format!("Invalid end_date: {}", request.end_date)
-            ))?;
-        if start > end {
-            return Err(RecurringError::Validation(
-                "start_date must be on or before end_date".to_string(),
-            ));
-        }
-        if request.total_amount < 0 || request.total_amount > 999_999_999 {
-            return Err(RecurringError::Validation(
-                "TOTAL_AMOUNT must be between 0 and 999,999,999".to_string(),
-            ));
-        }
-        if request.detail.item_name.trim().is_empty() {
-            return Err(RecurringError::Validation(
-                "DETAIL.item_name must not be empty".to_string(),
-            ));
-        }
-
-        // Bounded-field length checks (Issue #37 Phase 2-3, character count).
-        validation::validate_optional_max_chars(
-            "Rule name",
-            request.rule_name.as_ref(),
-            consts::MAX_RULE_NAME_LEN,
-        )
-        .map_err(RecurringError::Validation)?;
-        validation::validate_max_chars(
-            "Item name",
-            &request.detail.item_name,
-            consts::MAX_ITEM_NAME_LEN,
-        )
-        .map_err(RecurringError::Validation)?;
-        validation::validate_memo("Header memo", request.header_memo.as_ref())
-            .map_err(RecurringError::Validation)?;
-        validation::validate_memo("Detail memo", request.detail.detail_memo.as_ref())
-            .map_err(RecurringError::Validation)?;
-
-        let anchor_date = match &request.anchor_date {
-            Some(s) => Some(
-                NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| {
-                    RecurringError::Validation(format!("Invalid anchor_date: {}", s))
-                })?,
-            ),
-            None => None,
-        };
-        let columns = CycleColumns {
-            period_unit: request.period_unit.clone(),
-            period_interval: request.period_interval,
-            anchor_date,
-            day_of_week: request.day_of_week,
-            month_day_rule_type: request.month_day_rule_type.clone(),
-            day_of_month: request.day_of_month,
-            week_of_month: request.week_of_month,
-            month_of_year: request.month_of_year,
-            holiday_shift_type: request.holiday_shift_type,
-        };
-        let spec = columns_to_cyclic_spec(&columns).map_err(RecurringError::Validation)?;
+        let parsed = parse_rule_request(&request)?;
+        validate_rule_total(&request)?;
+        let window_end = parsed.window_end(chrono::Local::now().date_naive());
+        let ParsedRule { columns, spec, start, end } = parsed;
 
         // ----- Fetch holidays (only when shift may apply) -----
         let holidays = if matches!(spec.holiday_shift, HolidayShift::None) {
             HashSet::new()
         } else {
-            self.fetch_holidays_for(user_id, start, end).await?
+            self.fetch_holidays_for(user_id, start, window_end).await?
         };
 
-        let dates = generate_dates(&spec, start, end, &holidays);
+        let dates = generate_dates(&spec, start, window_end, &holidays);
+
+        let currency_code = self.currency_for(user_id, &request).await?;
 
         // ----- Persist rule + instances atomically -----
         let mut tx = self.pool.begin().await?;
 
-        let header_memo_id = match &request.header_memo {
-            Some(text) if !text.trim().is_empty() => {
-                let r = sqlx::query(sql_queries::MEMO_INSERT)
-                    .bind(user_id)
-                    .bind(text)
-                    .execute(&mut *tx)
-                    .await?;
-                Some(r.last_insert_rowid())
-            }
-            _ => None,
+        let header_memo_id = insert_memo(&mut tx, user_id, request.header_memo.as_ref()).await?;
+        let detail_memo_ids = insert_detail_memos(&mut tx, user_id, &request).await?;
+
+        let rule_result = sqlx::query(sql_queries::RECURRING_RULES_INSERT)
+            .bind(user_id)
+            .bind(&request.rule_name)
+            .bind(&columns.period_unit)
+            .bind(columns.period_interval as i64)
+            .bind(columns.anchor_date.map(|d| d.format("%Y-%m-%d").to_string()))
+            .bind(columns.day_of_week.map(|v| v as i64))
+            .bind(&columns.month_day_rule_type)
+            .bind(columns.day_of_month.map(|v| v as i64))
+            .bind(columns.week_of_month.map(|v| v as i64))
+            .bind(columns.month_of_year.map(|v| v as i64))
+            .bind(columns.holiday_shift_type)
+            .bind(start.format("%Y-%m-%d").to_string())
+            .bind(end.map(|d| d.format("%Y-%m-%d").to_string()))
+            .bind(window_end.format("%Y-%m-%d").to_string())
+            .bind(request.shop_id)
+            .bind(&request.category1_code)
+            .bind(&request.from_account_code)
+            .bind(&request.to_account_code)
+            .bind(request.total_amount)
+            .bind(request.tax_rounding_type)
+            .bind(request.tax_included_type)
+            .bind(header_memo_id)
+            .bind(request.is_estimated as i64)
+            .execute(&mut *tx)
+            .await?;
+        let rule_id = rule_result.last_insert_rowid();
+
+        insert_rule_details(&mut tx, user_id, rule_id, &request, &detail_memo_ids).await?;
+
+        let template = OccurrenceTemplate {
+            user_id,
+            rule_id,
+            request: &request,
+            header_memo_id,
+            detail_memo_ids,
+            currency_code: &currency_code,
         };
+        let mut first_id: Option<i64> = None;
 
-        let detail_memo_id = match &request.detail.detail_memo {
-            Some(text) if !text.trim().is_empty() => {
-                let r = sqlx::query(sql_queries::MEMO_INSERT)
-                    .bind(user_id)
-                    .bind(text)
-                    .execute(&mut *tx)
-                    .await?;
-                Some(r.last_insert_rowid())
+        for date in &dates {
+            let header_id = template.insert(&mut tx, *date).await?;
+            audit::record(&mut tx, user_id, &header_audit_key(header_id), None).await?;
+            if first_id.is_none() {
+                first_id = Some(header_id);
             }
-            _ => None,
+        }
+
+        tx.commit().await?;
+
+        Ok(CreateRecurringRuleResult {
+            rule_id,
+            generated_count: dates.len(),
+            first_transaction_id: first_id,
+        })
+    }
+
+    /// Rewrite a recurring rule and its DETAIL templates, then bring the
+    /// rule's still-scheduled occurrences dated today or later in line with
+    /// it. See [`RecurringService::update_rule_as_of`].
+    pub async fn update_rule(
+        &self,
+        user_id: i64,
+        rule_id: i64,
+        request: SaveRecurringRuleRequest,
+    ) -> Result<UpdateRecurringRuleResult, RecurringError> {
+        let today = chrono::Local::now().date_naive();
+        self.update_rule_as_of(user_id, rule_id, request, today).await
+    }
+
+    /// `update_rule` with an explicit "today".
+    ///
+    /// Dates are regenerated over the rule's whole period (up to the rolling
+    /// horizon for an open-ended rule) so the cycle keeps its phase, and only
+    /// those on or after `today` are materialized. The rule's skip/move
+    /// exceptions are applied to the generated dates first.
+    /// An IS_SCHEDULED=1 occurrence of the rule on or after `today` is kept
+    /// (and rewritten to the new template) when its date is still generated,
+    /// and deleted otherwise. Generated dates without such a row get a new
+    /// one, unless the rule already has a confirmed occurrence on that date.
+    /// Confirmed occurrences, scheduled ones dated before `today` and
+    /// reconciled ones are left as they are; a reconciled occurrence counts
+    /// as kept and still holds its date. Each rewritten or deleted header is
+    /// audited. Memos the edit leaves unreferenced are removed.
+    pub async fn update_rule_as_of(
+        &self,
+        user_id: i64,
+        rule_id: i64,
+        request: SaveRecurringRuleRequest,
+        today: NaiveDate,
+    ) -> Result<UpdateRecurringRuleResult, RecurringError> {
+        use sqlx::Row;
+
+        let parsed = parse_rule_request(&request)?;
+        validate_rule_total(&request)?;
+        let window_end = parsed.window_end(today);
+        let ParsedRule { columns, spec, start, end } = parsed;
+
+        let holidays = if matches!(spec.holiday_shift, HolidayShift::None) {
+            HashSet::new()
+        } else {
+            self.fetch_holidays_for(user_id, start, window_end).await?
         };
 
-        let rule_result = sqlx::query(sql_queries::RECURRING_RULES_INSERT)
+        let currency_code = self.currency_for(user_id, &request).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let old_memos = sqlx::query(sql_queries::RECURRING_RULE_GET_MEMO_IDS)
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(rule_id)
             .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        if old_memos.is_empty() {
+            return Err(RecurringError::NotFound);
+        }
+        let old_memo_ids: Vec<Option<i64>> =
+            old_memos.iter().map(|row| row.get("MEMO_ID")).collect();
+
+        let header_memo_id = insert_memo(&mut tx, user_id, request.header_memo.as_ref()).await?;
+        let detail_memo_ids = insert_detail_memos(&mut tx, user_id, &request).await?;
+
+        sqlx::query(sql_queries::RECURRING_RULES_UPDATE)
             .bind(&request.rule_name)
             .bind(&columns.period_unit)
             .bind(columns.period_interval as i64)
@@ -781,7 +1371,8 @@ impl RecurringService {
             .bind(columns.month_of_year.map(|v| v as i64))
             .bind(columns.holiday_shift_type)
             .bind(start.format("%Y-%m-%d").to_string())
-            .bind(end.format("%Y-%m-%d").to_string())
+            .bind(end.map(|d| d.format("%Y-%m-%d").to_string()))
+            .bind(window_end.format("%Y-%m-%d").to_string())
             .bind(request.shop_id)
             .bind(&request.category1_code)
             .bind(&request.from_account_code)
@@ -790,106 +1381,537 @@ impl RecurringService {
             .bind(request.tax_rounding_type)
             .bind(request.tax_included_type)
             .bind(header_memo_id)
+            .bind(request.is_estimated as i64)
+            .bind(rule_id)
+            .bind(user_id)
             .execute(&mut *tx)
             .await?;
-        let rule_id = rule_result.last_insert_rowid();
 
-        sqlx::query(sql_queries::RECURRING_RULE_DETAILS_INSERT)
+        sqlx::query(sql_queries::RECURRING_RULE_DETAILS_DELETE_BY_RULE)
             .bind(rule_id)
             .bind(user_id)
-            .bind(&request.detail.category1_code)
-            .bind(&request.detail.category2_code)
-            .bind(&request.detail.category3_code)
-            .bind(&request.detail.item_name)
-            .bind(request.detail.amount)
-            .bind(request.detail.tax_amount)
-            .bind(request.detail.tax_rate)
-            .bind(request.detail.amount_including_tax)
-            .bind(detail_memo_id)
             .execute(&mut *tx)
             .await?;
+        insert_rule_details(&mut tx, user_id, rule_id, &request, &detail_memo_ids).await?;
 
-        let mut first_id: Option<i64> = None;
+        let confirmed: HashSet<NaiveDate> =
+            sqlx::query(sql_queries::TRANSACTIONS_HEADER_CONFIRMED_DATES_BY_RULE)
+                .bind(rule_id)
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?
+                .iter()
+                .filter_map(|row| parse_occurrence_date(row.get("OCCURRENCE_DATE")))
+                .collect();
+
+        let exceptions = load_exceptions(&mut tx, user_id, rule_id).await?;
+
+        // Dates still to be covered by a scheduled occurrence; each kept row
+        // claims its date so a second row on the same date is removed.
+        let generated = generate_dates(&spec, start, window_end, &holidays);
+        let mut pending: Vec<NaiveDate> = apply_exceptions(generated, &exceptions)
+            .into_iter()
+            .filter(|d| *d >= today && !confirmed.contains(d))
+            .collect();
 
-        for date in &dates {
-            let datetime_str = format!("{} 00:00:00", date.format("%Y-%m-%d"));
-            let header_result =
-                sqlx::query(sql_queries::TRANSACTIONS_HEADER_INSERT_FOR_RECURRING)
-                    .bind(user_id)
-                    .bind(request.shop_id)
-                    .bind(&datetime_str)
-                    .bind(&request.category1_code)
-                    .bind(&request.from_account_code)
-                    .bind(&request.to_account_code)
-                    .bind(request.total_amount)
-                    .bind(request.tax_rounding_type)
-                    .bind(request.tax_included_type)
-                    .bind(header_memo_id)
-                    .bind(rule_id)
+        let scheduled = sqlx::query(sql_queries::TRANSACTIONS_HEADER_SCHEDULED_BY_RULE_FROM)
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(today.format("%Y-%m-%d").to_string())
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let template = OccurrenceTemplate {
+            user_id,
+            rule_id,
+            request: &request,
+            header_memo_id,
+            detail_memo_ids,
+            currency_code: &currency_code,
+        };
+        let mut kept_count = 0;
+        let mut removed_count = 0;
+
+        for row in &scheduled {
+            let transaction_id = row.get::<i64, _>("TRANSACTION_ID");
+            let date = parse_occurrence_date(row.get("OCCURRENCE_DATE"));
+            let slot = date.and_then(|d| pending.iter().position(|p| *p == d));
+            if row.get::<i64, _>("RECONCILE_STATUS") == consts::RECONCILE_STATUS_RECONCILED {
+                if let Some(index) = slot {
+                    pending.remove(index);
+                }
+                kept_count += 1;
+                continue;
+            }
+
+            let key = header_audit_key(transaction_id);
+            let before = audit::snapshot(&mut tx, &key).await?;
+            match slot {
+                Some(index) => {
+                    let date = pending.remove(index);
+                    template.rewrite(&mut tx, transaction_id, date).await?;
+                    kept_count += 1;
+                }
+                None => {
+                    // DETAILs go with the header via the existing FK.
+                    sqlx::query(sql_queries::TRANSACTION_HEADER_DELETE)
+                        .bind(transaction_id)
+                        .bind(user_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    removed_count += 1;
+                }
+            }
+            audit::record(&mut tx, user_id, &key, before).await?;
+        }
+
+        for date in &pending {
+            let header_id = template.insert(&mut tx, *date).await?;
+            audit::record(&mut tx, user_id, &header_audit_key(header_id), None).await?;
+        }
+
+        for memo_id in old_memo_ids.into_iter().flatten() {
+            if Some(memo_id) == header_memo_id || template.detail_memo_ids.contains(&Some(memo_id)) {
+                continue;
+            }
+            let usage: i64 = sqlx::query_scalar(sql_queries::MEMO_COUNT_USAGE)
+                .bind(memo_id)
+                .bind(memo_id)
+                .bind(memo_id)
+                .bind(memo_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            if usage == 0 {
+                sqlx::query(sql_queries::MEMO_DELETE)
+                    .bind(memo_id)
                     .execute(&mut *tx)
                     .await?;
-            let header_id = header_result.last_insert_rowid();
-            if first_id.is_none() {
-                first_id = Some(header_id);
             }
+        }
 
-            sqlx::query(sql_queries::TRANSACTION_DETAIL_INSERT_FULL)
-                .bind(header_id)
+        tx.commit().await?;
+
+        Ok(UpdateRecurringRuleResult {
+            rule_id,
+            added_count: pending.len(),
+            removed_count,
+            kept_count,
+        })
+    }
+
+    /// Generate the occurrences open-ended rules are missing up to the
+    /// rolling horizon. Run at login so the window follows the calendar.
+    /// See [`RecurringService::extend_open_ended_rules_as_of`].
+    pub async fn extend_open_ended_rules(&self, user_id: i64) -> Result<usize, RecurringError> {
+        let today = chrono::Local::now().date_naive();
+        self.extend_open_ended_rules_as_of(user_id, today).await
+    }
+
+    /// `extend_open_ended_rules` with an explicit "today". Returns the number
+    /// of occurrences added.
+    ///
+    /// For each enabled open-ended rule materialized short of the horizon,
+    /// dates are generated from START_DATE to the horizon (with the rule's
+    /// skip/move exceptions applied) and those the
+    /// previous window (up to MATERIALIZED_UNTIL) already produced are
+    /// dropped, so an occurrence the user deleted does not come back. Dates
+    /// that already have a row for the rule are skipped as well. Each rule
+    /// is extended and its MATERIALIZED_UNTIL advanced in one transaction; a
+    /// rule that fails is logged and left for the next run, and the others
+    /// are still extended.
+    pub async fn extend_open_ended_rules_as_of(
+        &self,
+        user_id: i64,
+        today: NaiveDate,
+    ) -> Result<usize, RecurringError> {
+        use sqlx::Row;
+
+        let horizon = open_ended_horizon(today);
+
+        let rules = sqlx::query(sql_queries::RECURRING_RULES_OPEN_ENDED_DUE)
+            .bind(user_id)
+            .bind(horizon.format("%Y-%m-%d").to_string())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut added_count = 0;
+        for row in &rules {
+            match self.extend_rule(user_id, row, horizon).await {
+                Ok(added) => added_count += added,
+                Err(e) => log::error!(
+                    "Failed to extend recurring rule {}: {}",
+                    row.get::<i64, _>("RULE_ID"),
+                    e
+                ),
+            }
+        }
+
+        Ok(added_count)
+    }
+
+    /// Extend one rule row of RECURRING_RULES_OPEN_ENDED_DUE up to `horizon`.
+    async fn extend_rule(
+        &self,
+        user_id: i64,
+        row: &sqlx::sqlite::SqliteRow,
+        horizon: NaiveDate,
+    ) -> Result<usize, RecurringError> {
+        use sqlx::Row;
+
+        let rule_id = row.get::<i64, _>("RULE_ID");
+        let detail_rows = sqlx::query(sql_queries::RECURRING_RULE_DETAILS_BY_RULE)
+            .bind(rule_id)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let request = rule_request_from_rows(row, &detail_rows);
+        let ParsedRule { spec, start, .. } = parse_rule_request(&request)?;
+        let materialized_until = parse_occurrence_date(row.get("MATERIALIZED_UNTIL"));
+
+        let holidays = if matches!(spec.holiday_shift, HolidayShift::None) {
+            HashSet::new()
+        } else {
+            self.fetch_holidays_for(user_id, start, horizon).await?
+        };
+        let currency_code = self.currency_for(user_id, &request).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let exceptions = load_exceptions(&mut tx, user_id, rule_id).await?;
+        let previous: HashSet<NaiveDate> = match materialized_until {
+            Some(until) => {
+                apply_exceptions(generate_dates(&spec, start, until, &holidays), &exceptions)
+                    .into_iter()
+                    .collect()
+            }
+            None => HashSet::new(),
+        };
+
+        let existing: HashSet<NaiveDate> =
+            sqlx::query(sql_queries::TRANSACTIONS_HEADER_DATES_BY_RULE)
+                .bind(rule_id)
                 .bind(user_id)
-                .bind(&request.detail.category1_code)
-                .bind(&request.detail.category2_code)
-                .bind(&request.detail.category3_code)
-                .bind(&request.detail.item_name)
-                .bind(request.detail.amount)
-                .bind(request.detail.tax_amount)
-                .bind(request.detail.tax_rate)
-                .bind(request.detail.amount_including_tax)
-                .bind(request.detail.product_id)
-                .bind(detail_memo_id)
-                .execute(&mut *tx)
-                .await?;
+                .fetch_all(&mut *tx)
+                .await?
+                .iter()
+                .filter_map(|r| parse_occurrence_date(r.get("OCCURRENCE_DATE")))
+                .collect();
+
+        let template = OccurrenceTemplate {
+            user_id,
+            rule_id,
+            request: &request,
+            header_memo_id: row.get("MEMO_ID"),
+            detail_memo_ids: detail_rows.iter().map(|d| d.get("MEMO_ID")).collect(),
+            currency_code: &currency_code,
+        };
+        let dates = apply_exceptions(generate_dates(&spec, start, horizon, &holidays), &exceptions);
+        let mut added_count = 0;
+        for date in dates {
+            if previous.contains(&date) || existing.contains(&date) {
+                continue;
+            }
+            let header_id = template.insert(&mut tx, date).await?;
+            audit::record(&mut tx, user_id, &header_audit_key(header_id), None).await?;
+            added_count += 1;
+        }
+
+        sqlx::query(sql_queries::RECURRING_RULES_SET_MATERIALIZED_UNTIL)
+            .bind(horizon.format("%Y-%m-%d").to_string())
+            .bind(rule_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(added_count)
+    }
+
+    /// List all active recurring rules for a user, with each rule's currently
+    /// materialized occurrence count. Used by the rule list UI to show what
+    /// the user has registered and how many TRANSACTIONS_HEADER rows each
+    /// rule currently owns.
+    pub async fn list_rules(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<RecurringRuleSummary>, RecurringError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(sql_queries::RECURRING_RULES_LIST_BY_USER)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let summaries = rows
+            .into_iter()
+            .map(|row| RecurringRuleSummary {
+                rule_id: row.get::<i64, _>("RULE_ID"),
+                rule_name: row.get::<Option<String>, _>("RULE_NAME"),
+                period_unit: row.get::<String, _>("PERIOD_UNIT"),
+                period_interval: row.get::<i64, _>("PERIOD_INTERVAL"),
+                start_date: row.get::<String, _>("START_DATE"),
+                end_date: row.get::<Option<String>, _>("END_DATE"),
+                total_amount: row.get::<i64, _>("TOTAL_AMOUNT"),
+                holiday_shift_type: row.get::<i32, _>("HOLIDAY_SHIFT_TYPE"),
+                is_estimated: row.get::<i64, _>("IS_ESTIMATED") != 0,
+                occurrence_count: row.get::<i64, _>("OCCURRENCE_COUNT"),
+            })
+            .collect();
+
+        Ok(summaries)
+    }
+
+    /// Suggest the estimate of a variable-amount rule from the average of
+    /// its last `sample_size` confirmed occurrences. Confirmed rows carry
+    /// the actual amounts entered at confirmation, so the suggestion
+    /// follows the real bills. Nothing is written; the caller decides
+    /// whether to save the suggestion as the rule's TOTAL_AMOUNT.
+    pub async fn suggest_estimate(
+        &self,
+        user_id: i64,
+        rule_id: i64,
+        sample_size: u32,
+    ) -> Result<RecurringEstimateSuggestion, RecurringError> {
+        if sample_size == 0 {
+            return Err(RecurringError::Validation(
+                "sample_size must be at least 1".to_string(),
+            ));
+        }
+
+        let exists: Option<i64> = sqlx::query_scalar(sql_queries::RECURRING_RULES_EXISTS_FOR_USER)
+            .bind(rule_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if exists.is_none() {
+            return Err(RecurringError::NotFound);
+        }
+
+        let totals: Vec<i64> = sqlx::query_scalar(sql_queries::RECURRING_RULE_RECENT_CONFIRMED_TOTALS)
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(sample_size as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let count = totals.len() as i64;
+        let estimated_amount = (count > 0).then(|| {
+            let sum: i64 = totals.iter().sum();
+            (sum * 2 + count).div_euclid(count * 2)
+        });
+
+        Ok(RecurringEstimateSuggestion {
+            rule_id,
+            sample_count: totals.len(),
+            estimated_amount,
+        })
+    }
+
+    /// List the skip/move exceptions recorded for a rule, by occurrence date.
+    pub async fn list_exceptions(
+        &self,
+        user_id: i64,
+        rule_id: i64,
+    ) -> Result<Vec<RecurringExceptionSummary>, RecurringError> {
+        use sqlx::Row;
+
+        let exists: Option<i64> = sqlx::query_scalar(sql_queries::RECURRING_RULES_EXISTS_FOR_USER)
+            .bind(rule_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if exists.is_none() {
+            return Err(RecurringError::NotFound);
+        }
+
+        let rows = sqlx::query(sql_queries::RECURRING_RULE_EXCEPTIONS_BY_RULE)
+            .bind(rule_id)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| RecurringExceptionSummary {
+                occurrence_date: row.get::<String, _>("OCCURRENCE_DATE"),
+                exception_type: row.get::<String, _>("EXCEPTION_TYPE"),
+                moved_to_date: row.get::<Option<String>, _>("MOVED_TO_DATE"),
+            })
+            .collect())
+    }
+
+    /// Skip one occurrence of a rule or move it to another date without
+    /// touching the rule or its other occurrences. `occurrence_date` must be
+    /// a date the rule's cycle generates; saving again for the same date
+    /// replaces the earlier exception.
+    ///
+    /// The occurrence's scheduled row is deleted (SKIP) or re-dated (MOVE).
+    /// When there is no row but the original date lies inside the window
+    /// already generated, MOVE inserts one on the new date. A confirmed or
+    /// reconciled occurrence cannot be given an exception. The deleted or
+    /// re-dated header is audited.
+    pub async fn save_exception(
+        &self,
+        user_id: i64,
+        rule_id: i64,
+        request: SaveRecurringExceptionRequest,
+    ) -> Result<(), RecurringError> {
+        let occurrence = parse_request_date("occurrence_date", &request.occurrence_date)?;
+        let exception = match request.exception_type.as_str() {
+            consts::RECURRING_EXCEPTION_SKIP => OccurrenceException::Skip,
+            consts::RECURRING_EXCEPTION_MOVE => {
+                let moved_to = request.moved_to_date.as_deref().ok_or_else(|| {
+                    RecurringError::Validation(
+                        "moved_to_date is required to move an occurrence".to_string(),
+                    )
+                })?;
+                let moved_to = parse_request_date("moved_to_date", moved_to)?;
+                if moved_to == occurrence {
+                    return Err(RecurringError::Validation(
+                        "moved_to_date must differ from occurrence_date".to_string(),
+                    ));
+                }
+                OccurrenceException::MoveTo(moved_to)
+            }
+            other => {
+                return Err(RecurringError::Validation(format!(
+                    "Invalid exception_type: {}",
+                    other
+                )))
+            }
+        };
+
+        let stored = self.load_rule(user_id, rule_id).await?;
+        if !self.generates(user_id, &stored.request, occurrence).await? {
+            return Err(RecurringError::Validation(format!(
+                "{} is not an occurrence of the rule",
+                occurrence
+            )));
+        }
+        let currency_code = self.currency_for(user_id, &stored.request).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // Where the occurrence sits now: nowhere if it is already skipped
+        let current_date = match load_exceptions(&mut tx, user_id, rule_id).await?.get(&occurrence) {
+            Some(OccurrenceException::Skip) => None,
+            Some(OccurrenceException::MoveTo(date)) => Some(*date),
+            None => Some(occurrence),
+        };
+        let transaction_id = match current_date {
+            Some(date) => scheduled_occurrence_on(&mut tx, user_id, rule_id, date).await?,
+            None => None,
+        };
+
+        match (exception, transaction_id) {
+            (OccurrenceException::Skip, Some(transaction_id)) => {
+                let key = header_audit_key(transaction_id);
+                let before = audit::snapshot(&mut tx, &key).await?;
+                sqlx::query(sql_queries::TRANSACTION_HEADER_DELETE)
+                    .bind(transaction_id)
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
+                audit::record(&mut tx, user_id, &key, before).await?;
+            }
+            (OccurrenceException::MoveTo(date), Some(transaction_id)) => {
+                let key = header_audit_key(transaction_id);
+                let before = audit::snapshot(&mut tx, &key).await?;
+                set_occurrence_date(&mut tx, user_id, transaction_id, date).await?;
+                audit::record(&mut tx, user_id, &key, before).await?;
+            }
+            (OccurrenceException::MoveTo(date), None) if stored.is_materialized(occurrence) => {
+                let header_id = stored
+                    .template(user_id, rule_id, &currency_code)
+                    .insert(&mut tx, date)
+                    .await?;
+                audit::record(&mut tx, user_id, &header_audit_key(header_id), None).await?;
+            }
+            _ => {}
         }
 
-        tx.commit().await?;
+        let moved_to_date = match exception {
+            OccurrenceException::MoveTo(date) => Some(date.format("%Y-%m-%d").to_string()),
+            OccurrenceException::Skip => None,
+        };
+        sqlx::query(sql_queries::RECURRING_RULE_EXCEPTIONS_UPSERT)
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(occurrence.format("%Y-%m-%d").to_string())
+            .bind(&request.exception_type)
+            .bind(moved_to_date)
+            .execute(&mut *tx)
+            .await?;
 
-        Ok(CreateRecurringRuleResult {
-            rule_id,
-            generated_count: dates.len(),
-            first_transaction_id: first_id,
-        })
+        tx.commit().await?;
+        Ok(())
     }
 
-    /// List all active recurring rules for a user, with each rule's currently
-    /// materialized occurrence count. Used by the rule list UI to show what
-    /// the user has registered and how many TRANSACTIONS_HEADER rows each
-    /// rule currently owns.
-    pub async fn list_rules(
+    /// Remove the exception recorded for `occurrence_date` and put the
+    /// occurrence back on its generated date: a moved row is re-dated, and a
+    /// skipped one is inserted again if the date lies inside the window
+    /// already generated. Fails if the moved occurrence has been confirmed
+    /// or reconciled. The re-dated header is audited.
+    pub async fn delete_exception(
         &self,
         user_id: i64,
-    ) -> Result<Vec<RecurringRuleSummary>, RecurringError> {
-        use sqlx::Row;
+        rule_id: i64,
+        occurrence_date: &str,
+    ) -> Result<(), RecurringError> {
+        let occurrence = parse_request_date("occurrence_date", occurrence_date)?;
+        let stored = self.load_rule(user_id, rule_id).await?;
+        let currency_code = self.currency_for(user_id, &stored.request).await?;
 
-        let rows = sqlx::query(sql_queries::RECURRING_RULES_LIST_BY_USER)
+        let mut tx = self.pool.begin().await?;
+
+        let exception = load_exceptions(&mut tx, user_id, rule_id)
+            .await?
+            .get(&occurrence)
+            .copied()
+            .ok_or_else(|| {
+                RecurringError::Validation(format!("No exception recorded for {}", occurrence))
+            })?;
+        let moved_id = match exception {
+            OccurrenceException::MoveTo(date) => {
+                scheduled_occurrence_on(&mut tx, user_id, rule_id, date).await?
+            }
+            OccurrenceException::Skip => None,
+        };
+
+        match moved_id {
+            Some(transaction_id) => {
+                let key = header_audit_key(transaction_id);
+                let before = audit::snapshot(&mut tx, &key).await?;
+                set_occurrence_date(&mut tx, user_id, transaction_id, occurrence).await?;
+                audit::record(&mut tx, user_id, &key, before).await?;
+            }
+            None if stored.is_materialized(occurrence) => {
+                let existing = sqlx::query(sql_queries::TRANSACTIONS_HEADER_BY_RULE_ON_DATE)
+                    .bind(rule_id)
+                    .bind(user_id)
+                    .bind(occurrence.format("%Y-%m-%d").to_string())
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                if existing.is_none() {
+                    let header_id = stored
+                        .template(user_id, rule_id, &currency_code)
+                        .insert(&mut tx, occurrence)
+                        .await?;
+                    audit::record(&mut tx, user_id, &header_audit_key(header_id), None).await?;
+                }
+            }
+            None => {}
+        }
+
+        sqlx::query(sql_queries::RECURRING_RULE_EXCEPTIONS_DELETE)
+            .bind(rule_id)
             .bind(user_id)
-            .fetch_all(&self.pool)
+            .bind(occurrence.format("%Y-%m-%d").to_string())
+            .execute(&mut *tx)
             .await?;
 
-        let summaries = rows
-            .into_iter()
-            .map(|row| RecurringRuleSummary {
-                rule_id: row.get::<i64, _>("RULE_ID"),
-                rule_name: row.get::<Option<String>, _>("RULE_NAME"),
-                period_unit: row.get::<String, _>("PERIOD_UNIT"),
-                period_interval: row.get::<i64, _>("PERIOD_INTERVAL"),
-                start_date: row.get::<String, _>("START_DATE"),
-                end_date: row.get::<String, _>("END_DATE"),
-                total_amount: row.get::<i64, _>("TOTAL_AMOUNT"),
-                holiday_shift_type: row.get::<i32, _>("HOLIDAY_SHIFT_TYPE"),
-                occurrence_count: row.get::<i64, _>("OCCURRENCE_COUNT"),
-            })
-            .collect();
-
-        Ok(summaries)
+        tx.commit().await?;
+        Ok(())
     }
 
     /// Delete a recurring rule. The user picks one of two semantics in the UI:
@@ -901,9 +1923,10 @@ impl RecurringService {
     ///   scheduled transactions; only their `RULE_ID` is cleared so they no
     ///   longer reference the now-deleted rule.
     ///
-    /// Either way the rule itself and its `RECURRING_RULE_DETAILS` row go away
+    /// Either way the rule itself and its `RECURRING_RULE_DETAILS` rows go away
     /// (the latter via `ON DELETE CASCADE`). All steps run inside one
-    /// transaction so a partial failure leaves nothing dangling.
+    /// transaction so a partial failure leaves nothing dangling. A cascade is
+    /// refused with `Locked` while any occurrence is reconciled.
     pub async fn delete_rule(
         &self,
         user_id: i64,
@@ -912,7 +1935,29 @@ impl RecurringService {
     ) -> Result<(), RecurringError> {
         let mut tx = self.pool.begin().await?;
 
+        let header_ids: Vec<i64> = sqlx::query_scalar(sql_queries::TRANSACTIONS_HEADER_IDS_BY_RULE)
+            .bind(rule_id)
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        let mut snapshots = Vec::with_capacity(header_ids.len());
+        for id in header_ids {
+            let key = header_audit_key(id);
+            let before = audit::snapshot(&mut tx, &key).await?;
+            snapshots.push((key, before));
+        }
+
         if cascade {
+            let reconciled: i64 =
+                sqlx::query_scalar(sql_queries::TRANSACTIONS_HEADER_COUNT_BY_RULE_AND_RECONCILE_STATUS)
+                    .bind(rule_id)
+                    .bind(user_id)
+                    .bind(consts::RECONCILE_STATUS_RECONCILED)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            if reconciled > 0 {
+                return Err(RecurringError::Locked);
+            }
             sqlx::query(sql_queries::TRANSACTIONS_HEADER_DELETE_BY_RULE)
                 .bind(rule_id)
                 .bind(user_id)
@@ -942,10 +1987,79 @@ impl RecurringService {
             return Err(RecurringError::NotFound);
         }
 
+        for (key, before) in snapshots {
+            audit::record(&mut tx, user_id, &key, before).await?;
+        }
+
         tx.commit().await?;
         Ok(())
     }
 
+    /// Currency of the occurrences, derived from the two accounts.
+    async fn currency_for(
+        &self,
+        user_id: i64,
+        request: &SaveRecurringRuleRequest,
+    ) -> Result<String, RecurringError> {
+        currency::transaction_currency(
+            &self.pool,
+            user_id,
+            &request.from_account_code,
+            &request.to_account_code,
+        )
+        .await
+        .map_err(|e| {
+            if e.code == ApiError::CODE_VALIDATION {
+                RecurringError::Validation(e.message)
+            } else {
+                RecurringError::Database(sqlx::Error::Protocol(e.message))
+            }
+        })
+    }
+
+    async fn load_rule(&self, user_id: i64, rule_id: i64) -> Result<StoredRule, RecurringError> {
+        use sqlx::Row;
+
+        let row = sqlx::query(sql_queries::RECURRING_RULE_GET_BY_ID)
+            .bind(rule_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(RecurringError::NotFound)?;
+        let detail_rows = sqlx::query(sql_queries::RECURRING_RULE_DETAILS_BY_RULE)
+            .bind(rule_id)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(StoredRule {
+            request: rule_request_from_rows(&row, &detail_rows),
+            header_memo_id: row.get("MEMO_ID"),
+            detail_memo_ids: detail_rows.iter().map(|d| d.get("MEMO_ID")).collect(),
+            materialized_until: parse_occurrence_date(row.get("MATERIALIZED_UNTIL")),
+        })
+    }
+
+    /// Whether the rule's cycle produces `date`. Generation runs a little past
+    /// `date` because a holiday shift can move a later date back onto it.
+    async fn generates(
+        &self,
+        user_id: i64,
+        request: &SaveRecurringRuleRequest,
+        date: NaiveDate,
+    ) -> Result<bool, RecurringError> {
+        let ParsedRule { spec, start, end, .. } = parse_rule_request(request)?;
+        let until = date + Days::new(14);
+        let until = end.map_or(until, |end| end.min(until));
+
+        let holidays = if matches!(spec.holiday_shift, HolidayShift::None) {
+            HashSet::new()
+        } else {
+            self.fetch_holidays_for(user_id, start, until).await?
+        };
+        Ok(generate_dates(&spec, start, until, &holidays).contains(&date))
+    }
+
     /// Fetch holidays applicable to this user within a window slightly wider than
     /// [start, end] — HolidayShift::Prev/Next can land outside the rule's period
     /// (e.g. Jan 1 holiday shifted back to Dec 31 of the previous year), so we
@@ -1527,6 +2641,26 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn case_exceptions_skip_and_move_resort() {
+        let dates = generate_dates(
+            &spec_monthly_dom(1, 25),
+            d(2026, 1, 1),
+            d(2026, 4, 30),
+            &no_holidays(),
+        );
+        let exceptions = HashMap::from([
+            (d(2026, 2, 25), OccurrenceException::Skip),
+            (d(2026, 3, 25), OccurrenceException::MoveTo(d(2026, 1, 30))),
+            // 生成されない日付の例外は無視される
+            (d(2026, 3, 24), OccurrenceException::Skip),
+        ]);
+        assert_eq!(
+            apply_exceptions(dates, &exceptions),
+            vec![d(2026, 1, 25), d(2026, 1, 30), d(2026, 4, 25)]
+        );
+    }
+
     // ========================================================================
     // CyclicSpec ↔ CycleColumns ラウンドトリップ
     // ========================================================================
@@ -1721,7 +2855,7 @@ mod tests {
             month_of_year: None,
             holiday_shift_type: 0,
             start_date: "2026-01-01".to_string(),
-            end_date: "2026-01-01".to_string(),
+            end_date: Some("2026-01-01".to_string()),
             shop_id: None,
             category1_code: "EXPENSE".to_string(),
             from_account_code: "BANK".to_string(),
@@ -1730,7 +2864,8 @@ mod tests {
             tax_rounding_type: 0,
             tax_included_type: 1,
             header_memo: None,
-            detail: SaveRecurringRuleDetailRequest {
+            is_estimated: false,
+            details: vec![SaveRecurringRuleDetailRequest {
                 category1_code: "EXPENSE".to_string(),
                 category2_code: None,
                 category3_code: None,
@@ -1741,7 +2876,7 @@ mod tests {
                 amount_including_tax: Some(100),
                 product_id: None,
                 detail_memo: None,
-            },
+            }],
         }
     }
 
@@ -1767,7 +2902,7 @@ mod tests {
         let service = RecurringService::new(empty_pool().await);
 
         let mut request = minimal_request();
-        request.detail.item_name = "あ".repeat(consts::MAX_ITEM_NAME_LEN + 1);
+        request.details[0].item_name = "あ".repeat(consts::MAX_ITEM_NAME_LEN + 1);
 
         let err = service.create_rule_with_instances(2, request).await.unwrap_err();
         let msg = err.to_string();
@@ -1793,7 +2928,7 @@ mod tests {
         let service = RecurringService::new(empty_pool().await);
 
         let mut request = minimal_request();
-        request.detail.detail_memo = Some("メ".repeat(consts::MAX_MEMO_LEN + 1));
+        request.details[0].detail_memo = Some("メ".repeat(consts::MAX_MEMO_LEN + 1));
 
         let err = service.create_rule_with_instances(2, request).await.unwrap_err();
         let msg = err.to_string();
@@ -1809,7 +2944,8 @@ mod tests {
     // (PR #83).
     #[tokio::test]
     async fn test_delete_rule_returns_not_found_for_missing() {
-        let pool = crate::test_helpers::database::setup_test_db().await;
+        // Migrated schema: the cascade checks RECONCILE_STATUS first
+        let pool = crate::test_helpers::database::setup_migrated_test_db().await;
         let service = RecurringService::new(pool);
 
         let result = service.delete_rule(2, 99999, false).await;
@@ -1821,6 +2957,732 @@ mod tests {
         assert!(matches!(result_cascade.unwrap_err(), RecurringError::NotFound));
     }
 
+    #[tokio::test]
+    async fn test_delete_rule_cascade_refuses_reconciled_occurrences() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let rule_id = service.create_rule_with_instances(2, rent_request()).await.unwrap().rule_id;
+        sqlx::query(
+            "UPDATE TRANSACTIONS_HEADER SET IS_SCHEDULED = 0, RECONCILE_STATUS = 2
+             WHERE RULE_ID = ? AND date(TRANSACTION_DATE) = '2026-01-25'",
+        )
+        .bind(rule_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let result = service.delete_rule(2, rule_id, true).await;
+        assert!(matches!(result.unwrap_err(), RecurringError::Locked));
+        assert_eq!(occurrences(&pool, rule_id).await.len(), 12);
+        assert_eq!(service.list_rules(2).await.unwrap().len(), 1);
+
+        // Detaching leaves every occurrence in place, so it is still allowed
+        service.delete_rule(2, rule_id, false).await.unwrap();
+        assert!(service.list_rules(2).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_undo_of_rule_creation_removes_rule_and_occurrences() {
+        use crate::services::journal;
+
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let op = journal::begin_operation(&pool, 2, "recurring_rule.add").await.unwrap();
+        let rule_id = service.create_rule_with_instances(2, rent_request()).await.unwrap().rule_id;
+        journal::end_operation(&pool, op).await.unwrap();
+
+        journal::undo_last(&pool, 2, 1).await.unwrap();
+        assert!(service.list_rules(2).await.unwrap().is_empty());
+        assert!(occurrences(&pool, rule_id).await.is_empty());
+
+        journal::redo_last(&pool, 2, 1).await.unwrap();
+        assert_eq!(service.list_rules(2).await.unwrap().len(), 1);
+        assert_eq!(occurrences(&pool, rule_id).await.len(), 12);
+    }
+
+    /// Audit actions written for user 2's headers, by action.
+    async fn header_audit_counts(pool: &SqlitePool) -> Vec<(String, i64)> {
+        sqlx::query_as(
+            "SELECT ACTION, COUNT(*) FROM AUDIT_LOG
+             WHERE USER_ID = 2 AND ENTITY = 'TRANSACTIONS_HEADER'
+             GROUP BY ACTION ORDER BY ACTION",
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_cascade_delete_audit_each_occurrence() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let rule_id = service.create_rule_with_instances(2, rent_request()).await.unwrap().rule_id;
+        assert_eq!(header_audit_counts(&pool).await, [("INSERT".to_string(), 12)]);
+
+        service.delete_rule(2, rule_id, true).await.unwrap();
+        assert_eq!(
+            header_audit_counts(&pool).await,
+            [("DELETE".to_string(), 12), ("INSERT".to_string(), 12)]
+        );
+    }
+
+    // ---- update_rule ----------------------------------------------------
+
+    /// User 2 with the EXPENSE category and the NONE account.
+    async fn setup_rule_db() -> SqlitePool {
+        let pool = crate::test_helpers::database::setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'recurring', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    /// Rent on the 25th of every month of 2026.
+    fn rent_request() -> SaveRecurringRuleRequest {
+        let mut request = minimal_request();
+        request.period_unit = "MONTH".to_string();
+        request.anchor_date = None;
+        request.month_day_rule_type = Some(consts::MONTH_DAY_RULE_TYPE_DAY.to_string());
+        request.day_of_month = Some(25);
+        request.start_date = "2026-01-01".to_string();
+        request.end_date = Some("2026-12-31".to_string());
+        request.from_account_code = "NONE".to_string();
+        request.to_account_code = "NONE".to_string();
+        request.total_amount = 1000;
+        request.details[0].amount = 1000;
+        request.details[0].amount_including_tax = Some(1000);
+        request.header_memo = Some("rent".to_string());
+        request
+    }
+
+    /// (date, scheduled, header total, detail amount) of every occurrence.
+    async fn occurrences(pool: &SqlitePool, rule_id: i64) -> Vec<(String, i64, i64, i64)> {
+        sqlx::query_as(
+            "SELECT date(h.TRANSACTION_DATE), h.IS_SCHEDULED, h.TOTAL_AMOUNT, d.AMOUNT
+             FROM TRANSACTIONS_HEADER h JOIN TRANSACTIONS_DETAIL d ON d.TRANSACTION_ID = h.TRANSACTION_ID
+             WHERE h.RULE_ID = ? ORDER BY h.TRANSACTION_DATE",
+        )
+        .bind(rule_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    /// Creates the rent rule, confirms January to March plus the July
+    /// occurrence, and returns the rule id.
+    async fn create_rent_with_history(service: &RecurringService, pool: &SqlitePool) -> i64 {
+        let created = service.create_rule_with_instances(2, rent_request()).await.unwrap();
+        assert_eq!(created.generated_count, 12);
+        sqlx::query(
+            "UPDATE TRANSACTIONS_HEADER SET IS_SCHEDULED = 0
+             WHERE RULE_ID = ? AND (TRANSACTION_DATE < '2026-04-01' OR date(TRANSACTION_DATE) = '2026-07-25')",
+        )
+        .bind(created.rule_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        created.rule_id
+    }
+
+    #[tokio::test]
+    async fn test_update_rule_rewrites_future_scheduled_and_keeps_history() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let rule_id = create_rent_with_history(&service, &pool).await;
+
+        let mut request = rent_request();
+        request.total_amount = 1200;
+        request.details[0].amount = 1200;
+        let result = service
+            .update_rule_as_of(2, rule_id, request, d(2026, 6, 10))
+            .await
+            .unwrap();
+        assert_eq!((result.added_count, result.removed_count, result.kept_count), (0, 0, 6));
+
+        let rows = occurrences(&pool, rule_id).await;
+        assert_eq!(rows.len(), 12);
+        for (date, scheduled, total, amount) in &rows {
+            // Confirmed months and the overdue April/May rows keep the old
+            // amount; June onward (except confirmed July) follows the edit.
+            let expected = if *scheduled == 1 && date.as_str() >= "2026-06-10" { 1200 } else { 1000 };
+            assert_eq!((*total, *amount), (expected, expected), "occurrence {}", date);
+        }
+
+        let rule_total: i64 = sqlx::query_scalar("SELECT TOTAL_AMOUNT FROM RECURRING_RULES WHERE RULE_ID = ?")
+            .bind(rule_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rule_total, 1200);
+    }
+
+    #[tokio::test]
+    async fn test_update_rule_cycle_change_replaces_future_dates() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let rule_id = create_rent_with_history(&service, &pool).await;
+
+        let mut request = rent_request();
+        request.day_of_month = Some(10);
+        request.end_date = Some("2026-09-30".to_string());
+        let result = service
+            .update_rule_as_of(2, rule_id, request, d(2026, 6, 10))
+            .await
+            .unwrap();
+        // The 25th of June and August to December goes, the 10th of June to
+        // September comes in; the confirmed July 25th stays.
+        assert_eq!((result.added_count, result.removed_count, result.kept_count), (4, 6, 0));
+
+        let dates: Vec<String> = occurrences(&pool, rule_id)
+            .await
+            .into_iter()
+            .map(|(date, ..)| date)
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                "2026-01-25", "2026-02-25", "2026-03-25", "2026-04-25", "2026-05-25",
+                "2026-06-10", "2026-07-10", "2026-07-25", "2026-08-10", "2026-09-10",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_rule_leaves_reconciled_occurrences_and_audits_the_rest() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let rule_id = create_rent_with_history(&service, &pool).await;
+        sqlx::query(
+            "UPDATE TRANSACTIONS_HEADER SET RECONCILE_STATUS = 2
+             WHERE RULE_ID = ? AND date(TRANSACTION_DATE) = '2026-08-25'",
+        )
+        .bind(rule_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut request = rent_request();
+        request.day_of_month = Some(10);
+        request.end_date = Some("2026-09-30".to_string());
+        let result = service
+            .update_rule_as_of(2, rule_id, request, d(2026, 6, 10))
+            .await
+            .unwrap();
+        // As in the cycle change above, but the reconciled August 25th stays
+        assert_eq!((result.added_count, result.removed_count, result.kept_count), (4, 5, 1));
+        let august: Vec<(String, i64, i64, i64)> = occurrences(&pool, rule_id)
+            .await
+            .into_iter()
+            .filter(|(date, ..)| date.starts_with("2026-08"))
+            .collect();
+        assert_eq!(
+            august,
+            [("2026-08-10".to_string(), 1, 1000, 1000), ("2026-08-25".to_string(), 1, 1000, 1000)]
+        );
+
+        // 12 from creation plus the 4 added here
+        assert_eq!(
+            header_audit_counts(&pool).await,
+            [("DELETE".to_string(), 5), ("INSERT".to_string(), 16)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_rule_removes_memo_no_longer_referenced() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let created = service.create_rule_with_instances(2, rent_request()).await.unwrap();
+
+        let mut request = rent_request();
+        request.header_memo = Some("rent (new landlord)".to_string());
+        service
+            .update_rule_as_of(2, created.rule_id, request, d(2026, 1, 1))
+            .await
+            .unwrap();
+
+        let memos: Vec<String> = sqlx::query_scalar("SELECT MEMO_TEXT FROM MEMOS WHERE USER_ID = 2")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(memos, ["rent (new landlord)"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_rule_returns_not_found_for_missing() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool);
+
+        let result = service.update_rule(2, 99999, rent_request()).await;
+        assert!(matches!(result.unwrap_err(), RecurringError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_update_rule_to_open_ended_fills_rolling_window() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let rule_id = create_rent_with_history(&service, &pool).await;
+
+        let mut request = rent_request();
+        request.end_date = None;
+        let result = service
+            .update_rule_as_of(2, rule_id, request, d(2026, 6, 10))
+            .await
+            .unwrap();
+        // 13 months ahead of 2026-06-10 reaches 2027-07-10: January to June 2027 are new
+        assert_eq!((result.added_count, result.removed_count, result.kept_count), (6, 0, 6));
+
+        let (end_date, materialized): (Option<String>, String) = sqlx::query_as(
+            "SELECT END_DATE, MATERIALIZED_UNTIL FROM RECURRING_RULES WHERE RULE_ID = ?",
+        )
+        .bind(rule_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((end_date, materialized.as_str()), (None, "2027-07-10"));
+    }
+
+    #[tokio::test]
+    async fn test_extend_open_ended_rules_adds_only_new_window_dates() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+
+        // Starts beyond the current window, so creation materializes nothing
+        let mut request = rent_request();
+        request.start_date = "2100-01-01".to_string();
+        request.end_date = None;
+        let created = service.create_rule_with_instances(2, request).await.unwrap();
+        assert_eq!(created.generated_count, 0);
+        let end_date: Option<String> =
+            sqlx::query_scalar("SELECT END_DATE FROM RECURRING_RULES WHERE RULE_ID = ?")
+                .bind(created.rule_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(end_date, None);
+
+        // Window up to 2101-01-01: every 25th of 2100
+        assert_eq!(service.extend_open_ended_rules_as_of(2, d(2099, 12, 1)).await.unwrap(), 12);
+        // Nothing due until the calendar moves on
+        assert_eq!(service.extend_open_ended_rules_as_of(2, d(2099, 12, 1)).await.unwrap(), 0);
+
+        // The user drops March 2100 and has already entered February 2101 by hand
+        sqlx::query("DELETE FROM TRANSACTIONS_HEADER WHERE RULE_ID = ? AND date(TRANSACTION_DATE) = '2100-03-25'")
+            .bind(created.rule_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO TRANSACTIONS_HEADER (USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, IS_SCHEDULED, RULE_ID)
+             VALUES (2, 'EXPENSE', 'NONE', 'NONE', '2101-02-25 00:00:00', 1000, 1, ?)",
+        )
+        .bind(created.rule_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Window up to 2101-04-01: January and March 2101 are added
+        assert_eq!(service.extend_open_ended_rules_as_of(2, d(2100, 3, 1)).await.unwrap(), 2);
+        let dates: Vec<String> = occurrences(&pool, created.rule_id)
+            .await
+            .into_iter()
+            .map(|(date, ..)| date)
+            .filter(|date| date.as_str() < "2100-05-01" || date.as_str() > "2100-12-31")
+            .collect();
+        assert_eq!(dates, ["2100-01-25", "2100-02-25", "2100-04-25", "2101-01-25", "2101-03-25"]);
+        assert_eq!(header_audit_counts(&pool).await, [("INSERT".to_string(), 14)]);
+    }
+
+    #[tokio::test]
+    async fn test_extend_open_ended_rules_continues_past_a_failing_rule() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+
+        let mut request = rent_request();
+        request.start_date = "2100-01-01".to_string();
+        request.end_date = None;
+        let broken = service.create_rule_with_instances(2, request.clone()).await.unwrap();
+        let healthy = service.create_rule_with_instances(2, request).await.unwrap();
+        // Stored before validation tightened, say: no longer parses
+        sqlx::query("UPDATE RECURRING_RULES SET TOTAL_AMOUNT = -1 WHERE RULE_ID = ?")
+            .bind(broken.rule_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(service.extend_open_ended_rules_as_of(2, d(2099, 12, 1)).await.unwrap(), 12);
+        assert!(occurrences(&pool, broken.rule_id).await.is_empty());
+        assert_eq!(occurrences(&pool, healthy.rule_id).await.len(), 12);
+    }
+
+    #[tokio::test]
+    async fn test_extend_open_ended_rules_ignores_rules_with_end_date() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        service.create_rule_with_instances(2, rent_request()).await.unwrap();
+
+        assert_eq!(service.extend_open_ended_rules_as_of(2, d(2026, 12, 1)).await.unwrap(), 0);
+    }
+
+    // ---- estimated amounts -----------------------------------------------
+
+    #[tokio::test]
+    async fn test_suggest_estimate_averages_recent_confirmed_actuals() {
+        use crate::services::transaction::{ConfirmScheduledAdjustment, TransactionService};
+
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let mut request = rent_request();
+        request.is_estimated = true;
+        let created = service.create_rule_with_instances(2, request).await.unwrap();
+
+        let suggestion = service.suggest_estimate(2, created.rule_id, 3).await.unwrap();
+        assert_eq!((suggestion.sample_count, suggestion.estimated_amount), (0, None));
+
+        // Confirm January to April with the actual bills
+        let transactions = TransactionService::new(pool.clone());
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT TRANSACTION_ID FROM TRANSACTIONS_HEADER WHERE RULE_ID = ? ORDER BY TRANSACTION_DATE LIMIT 4",
+        )
+        .bind(created.rule_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        for (transaction_id, actual) in ids.into_iter().zip([1100, 1300, 1200, 900]) {
+            let adjustment = ConfirmScheduledAdjustment { total_amount: actual, details: None };
+            transactions
+                .confirm_scheduled_transaction(2, transaction_id, Some(adjustment))
+                .await
+                .unwrap();
+        }
+
+        // February to April: (1300 + 1200 + 900) / 3 = 1133.3
+        let suggestion = service.suggest_estimate(2, created.rule_id, 3).await.unwrap();
+        assert_eq!((suggestion.sample_count, suggestion.estimated_amount), (3, Some(1133)));
+
+        let summary = service.list_rules(2).await.unwrap();
+        assert!(summary[0].is_estimated);
+
+        assert!(matches!(
+            service.suggest_estimate(2, created.rule_id, 0).await,
+            Err(RecurringError::Validation(_))
+        ));
+        assert!(matches!(
+            service.suggest_estimate(2, 99999, 3).await,
+            Err(RecurringError::NotFound)
+        ));
+    }
+
+    // ---- multi-line DETAIL templates -------------------------------------
+
+    /// Rent plus a tax-excluded 10% management fee with a product attached,
+    /// rounded down: 1000 + floor(300 * 1.10) = 1330.
+    fn rent_with_fee_request() -> SaveRecurringRuleRequest {
+        let mut request = rent_request();
+        let mut fee = request.details[0].clone();
+        fee.item_name = "management fee".to_string();
+        fee.amount = 300;
+        fee.tax_amount = 30;
+        fee.tax_rate = 10;
+        fee.amount_including_tax = Some(330);
+        fee.product_id = Some(1);
+        fee.detail_memo = Some("fee".to_string());
+        request.details.push(fee);
+        request.total_amount = 1330;
+        request
+    }
+
+    /// (item name, amount, product id) of each DETAIL of one occurrence.
+    async fn occurrence_lines(pool: &SqlitePool, transaction_id: i64) -> Vec<(String, i64, Option<i64>)> {
+        sqlx::query_as(
+            "SELECT ITEM_NAME, AMOUNT, PRODUCT_ID FROM TRANSACTIONS_DETAIL
+             WHERE TRANSACTION_ID = ? ORDER BY DETAIL_ID",
+        )
+        .bind(transaction_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_rule_copies_every_detail_template_into_occurrences() {
+        let pool = setup_rule_db().await;
+        sqlx::query("INSERT INTO PRODUCTS (PRODUCT_ID, USER_ID, PRODUCT_NAME) VALUES (1, 2, 'Management')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let service = RecurringService::new(pool.clone());
+
+        let created = service
+            .create_rule_with_instances(2, rent_with_fee_request())
+            .await
+            .unwrap();
+        assert_eq!(created.generated_count, 12);
+
+        let transaction_ids: Vec<i64> =
+            sqlx::query_scalar("SELECT TRANSACTION_ID FROM TRANSACTIONS_HEADER WHERE RULE_ID = ?")
+                .bind(created.rule_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        for transaction_id in transaction_ids {
+            assert_eq!(
+                occurrence_lines(&pool, transaction_id).await,
+                [
+                    ("test".to_string(), 1000, None),
+                    ("management fee".to_string(), 300, Some(1)),
+                ]
+            );
+        }
+
+        let templates: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM RECURRING_RULE_DETAILS WHERE RULE_ID = ?")
+                .bind(created.rule_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(templates, 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_rule_rejects_total_not_matching_detail_lines() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+
+        let mut request = rent_with_fee_request();
+        // Sum of the raw amounts, ignoring the fee's tax
+        request.total_amount = 1300;
+        let err = service.create_rule_with_instances(2, request).await.unwrap_err();
+        assert!(matches!(&err, RecurringError::Validation(msg) if msg.contains("expected 1330")), "{}", err);
+
+        let mut request = rent_request();
+        request.details.clear();
+        let err = service.create_rule_with_instances(2, request).await.unwrap_err();
+        assert!(matches!(err, RecurringError::Validation(_)));
+
+        let rules: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM RECURRING_RULES")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rules, 0);
+    }
+
+    #[tokio::test]
+    async fn test_update_rule_replaces_detail_templates_of_future_occurrences() {
+        let pool = setup_rule_db().await;
+        sqlx::query("INSERT INTO PRODUCTS (PRODUCT_ID, USER_ID, PRODUCT_NAME) VALUES (1, 2, 'Management')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let service = RecurringService::new(pool.clone());
+        let created = service
+            .create_rule_with_instances(2, rent_with_fee_request())
+            .await
+            .unwrap();
+
+        // Drop the fee line from July on
+        service
+            .update_rule_as_of(2, created.rule_id, rent_request(), d(2026, 7, 1))
+            .await
+            .unwrap();
+
+        let headers: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT TRANSACTION_ID, date(TRANSACTION_DATE) FROM TRANSACTIONS_HEADER
+             WHERE RULE_ID = ? ORDER BY TRANSACTION_DATE",
+        )
+        .bind(created.rule_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(headers.len(), 12);
+        for (transaction_id, date) in headers {
+            let expected = if date.as_str() >= "2026-07-01" { 1 } else { 2 };
+            assert_eq!(occurrence_lines(&pool, transaction_id).await.len(), expected, "occurrence {}", date);
+        }
+
+        // January to June still point at the fee memo, so it is kept
+        let memos: Vec<String> = sqlx::query_scalar("SELECT MEMO_TEXT FROM MEMOS WHERE USER_ID = 2 ORDER BY MEMO_ID")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(memos, ["rent", "fee", "rent"]);
+    }
+
+    // ---- occurrence exceptions ------------------------------------------
+
+    fn exception(occurrence_date: &str, exception_type: &str, moved_to_date: Option<&str>) -> SaveRecurringExceptionRequest {
+        SaveRecurringExceptionRequest {
+            occurrence_date: occurrence_date.to_string(),
+            exception_type: exception_type.to_string(),
+            moved_to_date: moved_to_date.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skip_exception_survives_update_and_delete_restores_it() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let created = service.create_rule_with_instances(2, rent_request()).await.unwrap();
+        let rule_id = created.rule_id;
+
+        service
+            .save_exception(2, rule_id, exception("2026-08-25", consts::RECURRING_EXCEPTION_SKIP, None))
+            .await
+            .unwrap();
+        assert_eq!(occurrences(&pool, rule_id).await.len(), 11);
+
+        // Regenerating the future occurrences does not bring August back
+        let mut request = rent_request();
+        request.total_amount = 1200;
+        request.details[0].amount = 1200;
+        let result = service
+            .update_rule_as_of(2, rule_id, request, d(2026, 6, 10))
+            .await
+            .unwrap();
+        assert_eq!((result.added_count, result.removed_count, result.kept_count), (0, 0, 6));
+        assert!(occurrences(&pool, rule_id)
+            .await
+            .iter()
+            .all(|(date, ..)| date != "2026-08-25"));
+
+        let listed = service.list_exceptions(2, rule_id).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(
+            (listed[0].occurrence_date.as_str(), listed[0].exception_type.as_str(), listed[0].moved_to_date.as_deref()),
+            ("2026-08-25", consts::RECURRING_EXCEPTION_SKIP, None)
+        );
+
+        service.delete_exception(2, rule_id, "2026-08-25").await.unwrap();
+        assert!(service.list_exceptions(2, rule_id).await.unwrap().is_empty());
+        let august: Vec<(String, i64, i64, i64)> = occurrences(&pool, rule_id)
+            .await
+            .into_iter()
+            .filter(|(date, ..)| date == "2026-08-25")
+            .collect();
+        assert_eq!(august, [("2026-08-25".to_string(), 1, 1200, 1200)]);
+    }
+
+    #[tokio::test]
+    async fn test_move_exception_redates_occurrence_and_extend_respects_it() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let mut request = rent_request();
+        request.start_date = "2100-01-01".to_string();
+        request.end_date = None;
+        let rule_id = service.create_rule_with_instances(2, request).await.unwrap().rule_id;
+        assert_eq!(service.extend_open_ended_rules_as_of(2, d(2099, 12, 1)).await.unwrap(), 12);
+
+        // One materialized occurrence and one beyond MATERIALIZED_UNTIL
+        service
+            .save_exception(2, rule_id, exception("2100-03-25", consts::RECURRING_EXCEPTION_MOVE, Some("2100-03-31")))
+            .await
+            .unwrap();
+        service
+            .save_exception(2, rule_id, exception("2101-02-25", consts::RECURRING_EXCEPTION_MOVE, Some("2101-02-27")))
+            .await
+            .unwrap();
+
+        assert_eq!(service.extend_open_ended_rules_as_of(2, d(2100, 3, 1)).await.unwrap(), 3);
+        let dates = |rows: Vec<(String, i64, i64, i64)>| -> Vec<String> {
+            rows.into_iter()
+                .map(|(date, ..)| date)
+                .filter(|date| date.as_str() < "2100-05-01" || date.as_str() > "2100-12-31")
+                .collect()
+        };
+        assert_eq!(
+            dates(occurrences(&pool, rule_id).await),
+            ["2100-01-25", "2100-02-25", "2100-03-31", "2100-04-25", "2101-01-25", "2101-02-27", "2101-03-25"]
+        );
+
+        service.delete_exception(2, rule_id, "2100-03-25").await.unwrap();
+        assert_eq!(
+            dates(occurrences(&pool, rule_id).await),
+            ["2100-01-25", "2100-02-25", "2100-03-25", "2100-04-25", "2101-01-25", "2101-02-27", "2101-03-25"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_exception_rejects_confirmed_or_non_generated_dates() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let rule_id = create_rent_with_history(&service, &pool).await;
+
+        for request in [
+            exception("2026-07-25", consts::RECURRING_EXCEPTION_SKIP, None),
+            exception("2026-07-26", consts::RECURRING_EXCEPTION_SKIP, None),
+            exception("2026-08-25", consts::RECURRING_EXCEPTION_MOVE, None),
+            exception("2026-08-25", "POSTPONE", None),
+        ] {
+            let result = service.save_exception(2, rule_id, request.clone()).await;
+            assert!(
+                matches!(result, Err(RecurringError::Validation(_))),
+                "{:?} should be rejected",
+                request
+            );
+        }
+        assert!(service.list_exceptions(2, rule_id).await.unwrap().is_empty());
+        assert_eq!(occurrences(&pool, rule_id).await.len(), 12);
+
+        let result = service
+            .save_exception(2, 99999, exception("2026-08-25", consts::RECURRING_EXCEPTION_SKIP, None))
+            .await;
+        assert!(matches!(result.unwrap_err(), RecurringError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_save_exception_refuses_reconciled_and_audits_the_change() {
+        let pool = setup_rule_db().await;
+        let service = RecurringService::new(pool.clone());
+        let rule_id = service.create_rule_with_instances(2, rent_request()).await.unwrap().rule_id;
+        sqlx::query(
+            "UPDATE TRANSACTIONS_HEADER SET RECONCILE_STATUS = 2
+             WHERE RULE_ID = ? AND date(TRANSACTION_DATE) = '2026-08-25'",
+        )
+        .bind(rule_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let result = service
+            .save_exception(2, rule_id, exception("2026-08-25", consts::RECURRING_EXCEPTION_SKIP, None))
+            .await;
+        assert!(matches!(result.unwrap_err(), RecurringError::Locked));
+        assert!(service.list_exceptions(2, rule_id).await.unwrap().is_empty());
+
+        // Only the exception writes below are of interest
+        sqlx::query("DELETE FROM AUDIT_LOG").execute(&pool).await.unwrap();
+        service
+            .save_exception(2, rule_id, exception("2026-09-25", consts::RECURRING_EXCEPTION_SKIP, None))
+            .await
+            .unwrap();
+        service
+            .save_exception(2, rule_id, exception("2026-10-25", consts::RECURRING_EXCEPTION_MOVE, Some("2026-10-27")))
+            .await
+            .unwrap();
+        service.delete_exception(2, rule_id, "2026-10-25").await.unwrap();
+        // Moving a skipped occurrence and restoring it both insert a fresh row
+        service
+            .save_exception(2, rule_id, exception("2026-09-25", consts::RECURRING_EXCEPTION_MOVE, Some("2026-09-28")))
+            .await
+            .unwrap();
+        service.delete_exception(2, rule_id, "2026-09-25").await.unwrap();
+        service
+            .save_exception(2, rule_id, exception("2026-11-25", consts::RECURRING_EXCEPTION_SKIP, None))
+            .await
+            .unwrap();
+        service.delete_exception(2, rule_id, "2026-11-25").await.unwrap();
+        let actions: Vec<String> = sqlx::query_scalar(
+            "SELECT ACTION FROM AUDIT_LOG WHERE ENTITY = 'TRANSACTIONS_HEADER' ORDER BY AUDIT_ID",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(actions, ["DELETE", "UPDATE", "UPDATE", "INSERT", "UPDATE", "DELETE", "INSERT"]);
+    }
+
     // ---- From<RecurringError> for ApiError ------------------------------
     // These tests pin the wire codes that the frontend classifier
     // (`res/js/recurring-rule.js` — `err.code` branching) matches on.
@@ -1847,6 +3709,12 @@ mod tests {
         assert!(err.entity.is_none());
     }
 
+    #[test]
+    fn locked_maps_to_reconciled_locked_code() {
+        let err: ApiError = RecurringError::Locked.into();
+        assert_eq!(err.code, ApiError::CODE_RECONCILED_LOCKED);
+    }
+
     #[test]
     fn database_error_maps_to_database_code() {
         let err: ApiError = RecurringError::Database(sqlx::Error::RowNotFound).into();
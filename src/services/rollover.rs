@@ -0,0 +1,115 @@
+//! Budget carry-forward between consecutive month periods.
+//!
+//! Pure calculation only; `services::budget` loads the inputs and stores
+//! each result in BUDGET_CARRY_HISTORY so a closed period keeps its carry
+//! even after the category's rule is changed later.
+
+/// How a category's leftover budget moves into the next period.
+/// Stored as INTEGER 0..=3 in `BUDGET_ROLLOVER_RULES.ROLLOVER_MODE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RolloverMode {
+    /// 0: every period starts from its own planned amount
+    Reset,
+    /// 1: unspent budget is added to the next period
+    CarrySurplus,
+    /// 2: overspending is taken from the next period
+    CarryDeficit,
+    /// 3: both surplus and deficit carry over
+    CarryBoth,
+}
+
+impl RolloverMode {
+    /// DB value (0..=3) to enum. Out-of-range values yield `None`.
+    pub fn from_db_value(value: i64) -> Option<RolloverMode> {
+        match value {
+            0 => Some(RolloverMode::Reset),
+            1 => Some(RolloverMode::CarrySurplus),
+            2 => Some(RolloverMode::CarryDeficit),
+            3 => Some(RolloverMode::CarryBoth),
+            _ => None,
+        }
+    }
+
+    pub fn to_db_value(self) -> i64 {
+        match self {
+            RolloverMode::Reset => 0,
+            RolloverMode::CarrySurplus => 1,
+            RolloverMode::CarryDeficit => 2,
+            RolloverMode::CarryBoth => 3,
+        }
+    }
+}
+
+/// Amount carried into the next period. `carried_in` is what this period
+/// itself received from the previous one, so carries accumulate across a
+/// run of periods. Positive = surplus, negative = deficit.
+pub fn carry_forward(planned: i64, carried_in: i64, actual: i64, mode: RolloverMode) -> i64 {
+    let remaining = planned + carried_in - actual;
+    match mode {
+        RolloverMode::Reset => 0,
+        RolloverMode::CarrySurplus => remaining.max(0),
+        RolloverMode::CarryDeficit => remaining.min(0),
+        RolloverMode::CarryBoth => remaining,
+    }
+}
+
+/// (year, month) of the period before `month`.
+pub fn previous_period(year: i32, month: u32) -> (i32, u32) {
+    if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_value_round_trip() {
+        for v in 0..=3 {
+            assert_eq!(RolloverMode::from_db_value(v).unwrap().to_db_value(), v);
+        }
+        assert_eq!(RolloverMode::from_db_value(4), None);
+        assert_eq!(RolloverMode::from_db_value(-1), None);
+    }
+
+    #[test]
+    fn reset_never_carries() {
+        assert_eq!(carry_forward(10000, 500, 3000, RolloverMode::Reset), 0);
+        assert_eq!(carry_forward(10000, 0, 15000, RolloverMode::Reset), 0);
+    }
+
+    #[test]
+    fn surplus_only_carries_unspent_amount() {
+        assert_eq!(carry_forward(10000, 0, 7000, RolloverMode::CarrySurplus), 3000);
+        assert_eq!(carry_forward(10000, 0, 12000, RolloverMode::CarrySurplus), 0);
+    }
+
+    #[test]
+    fn deficit_only_carries_overspending() {
+        assert_eq!(carry_forward(10000, 0, 7000, RolloverMode::CarryDeficit), 0);
+        assert_eq!(carry_forward(10000, 0, 12000, RolloverMode::CarryDeficit), -2000);
+    }
+
+    #[test]
+    fn both_carries_signed_remaining() {
+        assert_eq!(carry_forward(10000, 0, 7000, RolloverMode::CarryBoth), 3000);
+        assert_eq!(carry_forward(10000, 0, 12000, RolloverMode::CarryBoth), -2000);
+    }
+
+    #[test]
+    fn carried_in_accumulates() {
+        // 3000 left last period + 1000 unspent now = 4000 forward
+        assert_eq!(carry_forward(10000, 3000, 9000, RolloverMode::CarrySurplus), 4000);
+        // A deficit carried in can eat a surplus mode's carry entirely
+        assert_eq!(carry_forward(10000, -4000, 8000, RolloverMode::CarrySurplus), 0);
+    }
+
+    #[test]
+    fn previous_period_wraps_year() {
+        assert_eq!(previous_period(2025, 1), (2024, 12));
+        assert_eq!(previous_period(2024, 5), (2024, 4));
+    }
+}
@@ -0,0 +1,256 @@
+//! Saved searches for the transaction list.
+//!
+//! A saved filter is a name plus the JSON form of a [`TransactionQuery`],
+//! sort order included. Executing one runs it through
+//! `TransactionService::get_transactions`, the same path as the live list,
+//! so the two can never disagree about which rows match.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::services::transaction::{TransactionListResponse, TransactionQuery, TransactionService};
+use crate::sql_queries;
+use crate::validation;
+
+const ENTITY_LABEL: &str = "Saved filter";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedFilter {
+    pub saved_filter_id: i64,
+    pub filter_name: String,
+    pub filter: TransactionQuery,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+#[derive(FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+struct SavedFilterRow {
+    saved_filter_id: i64,
+    filter_name: String,
+    filter_spec: String,
+    entry_dt: String,
+    update_dt: Option<String>,
+}
+
+impl TryFrom<SavedFilterRow> for SavedFilter {
+    type Error = ApiError;
+
+    fn try_from(row: SavedFilterRow) -> Result<Self, ApiError> {
+        let filter = serde_json::from_str(&row.filter_spec).map_err(|e| {
+            ApiError::database(format!(
+                "Saved filter {} has an unreadable spec: {}",
+                row.saved_filter_id, e
+            ))
+        })?;
+        Ok(SavedFilter {
+            saved_filter_id: row.saved_filter_id,
+            filter_name: row.filter_name,
+            filter,
+            entry_dt: row.entry_dt,
+            update_dt: row.update_dt,
+        })
+    }
+}
+
+fn normalize_filter_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    validation::validate_master_name("Filter name", name).map_err(ApiError::validation)?;
+    Ok(name.to_string())
+}
+
+async fn ensure_name_free(
+    pool: &SqlitePool,
+    user_id: i64,
+    name: &str,
+    exclude_saved_filter_id: i64,
+) -> Result<(), ApiError> {
+    let taken: i64 = sqlx::query_scalar(sql_queries::SAVED_FILTER_NAME_EXISTS)
+        .bind(user_id)
+        .bind(name)
+        .bind(exclude_saved_filter_id)
+        .fetch_one(pool)
+        .await?;
+    if taken > 0 {
+        return Err(ApiError::duplicate_name(ENTITY_LABEL));
+    }
+    Ok(())
+}
+
+pub async fn list_saved_filters(pool: &SqlitePool, user_id: i64) -> Result<Vec<SavedFilter>, ApiError> {
+    sqlx::query_as::<_, SavedFilterRow>(sql_queries::SAVED_FILTER_LIST)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(SavedFilter::try_from)
+        .collect()
+}
+
+pub async fn get_saved_filter(
+    pool: &SqlitePool,
+    user_id: i64,
+    saved_filter_id: i64,
+) -> Result<SavedFilter, ApiError> {
+    sqlx::query_as::<_, SavedFilterRow>(sql_queries::SAVED_FILTER_GET_BY_ID)
+        .bind(saved_filter_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(ENTITY_LABEL))?
+        .try_into()
+}
+
+/// Save `filter` under a new name. Names are compared without regard to case.
+pub async fn save_filter(
+    pool: &SqlitePool,
+    user_id: i64,
+    filter_name: &str,
+    filter: &TransactionQuery,
+) -> Result<SavedFilter, ApiError> {
+    let name = normalize_filter_name(filter_name)?;
+    ensure_name_free(pool, user_id, &name, 0).await?;
+    let spec = serde_json::to_string(filter)
+        .map_err(|e| ApiError::validation(format!("Invalid filter: {}", e)))?;
+
+    let result = sqlx::query(sql_queries::SAVED_FILTER_INSERT)
+        .bind(user_id)
+        .bind(&name)
+        .bind(&spec)
+        .execute(pool)
+        .await?;
+    get_saved_filter(pool, user_id, result.last_insert_rowid()).await
+}
+
+pub async fn rename_saved_filter(
+    pool: &SqlitePool,
+    user_id: i64,
+    saved_filter_id: i64,
+    filter_name: &str,
+) -> Result<SavedFilter, ApiError> {
+    let name = normalize_filter_name(filter_name)?;
+    ensure_name_free(pool, user_id, &name, saved_filter_id).await?;
+
+    let result = sqlx::query(sql_queries::SAVED_FILTER_UPDATE_NAME)
+        .bind(&name)
+        .bind(saved_filter_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    get_saved_filter(pool, user_id, saved_filter_id).await
+}
+
+pub async fn delete_saved_filter(
+    pool: &SqlitePool,
+    user_id: i64,
+    saved_filter_id: i64,
+) -> Result<(), ApiError> {
+    let result = sqlx::query(sql_queries::SAVED_FILTER_DELETE)
+        .bind(saved_filter_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    Ok(())
+}
+
+/// Run a saved filter and return one page of the matching transactions.
+pub async fn execute_saved_filter(
+    pool: &SqlitePool,
+    user_id: i64,
+    saved_filter_id: i64,
+    page: i64,
+    per_page: i64,
+) -> Result<TransactionListResponse, ApiError> {
+    let saved = get_saved_filter(pool, user_id, saved_filter_id).await?;
+    let transactions = TransactionService::new(pool.clone());
+    Ok(transactions
+        .get_transactions(user_id, &saved.filter, page, per_page)
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::transaction::{TransactionSort, TransactionStatusFilter};
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'filters', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (1, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-01 10:00:00', 3000)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-31 10:00:00', 500)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (3, 2, 'EXPENSE', 'NONE', 'NONE', '2026-09-01 10:00:00', 800)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn saved_filter_crud_rejects_duplicate_names() {
+        let pool = setup().await;
+        let filter = TransactionQuery { min_amount: Some(1000), ..Default::default() };
+
+        let big = save_filter(&pool, 2, " Big spends ", &filter).await.unwrap();
+        assert_eq!(big.filter_name, "Big spends");
+        assert_eq!(big.filter, filter);
+        let august = save_filter(&pool, 2, "August", &TransactionQuery::default()).await.unwrap();
+
+        let err = save_filter(&pool, 2, "big SPENDS", &filter).await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_DUPLICATE_NAME);
+        let err = rename_saved_filter(&pool, 2, august.saved_filter_id, "Big spends").await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_DUPLICATE_NAME);
+        assert_eq!(save_filter(&pool, 2, "", &filter).await.unwrap_err().code, ApiError::CODE_VALIDATION);
+
+        let renamed = rename_saved_filter(&pool, 2, august.saved_filter_id, "August 2026").await.unwrap();
+        assert_eq!(renamed.filter_name, "August 2026");
+        let names: Vec<String> = list_saved_filters(&pool, 2).await.unwrap().into_iter().map(|f| f.filter_name).collect();
+        assert_eq!(names, vec!["August 2026", "Big spends"]);
+
+        // Another user's filter is invisible
+        assert_eq!(get_saved_filter(&pool, 1, big.saved_filter_id).await.unwrap_err().code, ApiError::CODE_NOT_FOUND);
+
+        delete_saved_filter(&pool, 2, big.saved_filter_id).await.unwrap();
+        assert_eq!(delete_saved_filter(&pool, 2, big.saved_filter_id).await.unwrap_err().code, ApiError::CODE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn execute_saved_filter_matches_live_list() {
+        let pool = setup().await;
+        let filter = TransactionQuery {
+            start_date: Some("2026-08-01".to_string()),
+            end_date: Some("2026-08-31".to_string()),
+            ..Default::default()
+        };
+        let saved = save_filter(&pool, 2, "August", &filter).await.unwrap();
+
+        let executed = execute_saved_filter(&pool, 2, saved.saved_filter_id, 1, 50).await.unwrap();
+        let live = TransactionService::new(pool.clone())
+            .get_transactions(2, &filter, 1, 50)
+            .await
+            .unwrap();
+
+        let ids = |r: &TransactionListResponse| r.transactions.iter().map(|t| t.transaction_id).collect::<Vec<_>>();
+        assert_eq!(executed.total_count, 2);
+        assert_eq!(ids(&executed), ids(&live));
+    }
+
+    #[test]
+    fn filter_spec_missing_fields_fall_back_to_defaults() {
+        let filter: TransactionQuery = serde_json::from_str(r#"{"keyword":"coffee"}"#).unwrap();
+        assert_eq!(filter.keyword.as_deref(), Some("coffee"));
+        assert!(filter.tag_ids.is_empty());
+        assert_eq!(filter.status, TransactionStatusFilter::Confirmed);
+        assert_eq!(filter.sort, TransactionSort::DateDesc);
+    }
+}
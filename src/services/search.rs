@@ -0,0 +1,369 @@
+//! Full-text search over transactions.
+//!
+//! The TRANSACTION_SEARCH FTS5 table holds one row per transaction header
+//! (rowid = TRANSACTION_ID) with header and line memos, line item names, the
+//! shop name and product/manufacturer names. Triggers created by
+//! `Database::migrate_transaction_search` keep it in step with the source
+//! tables, so nothing here writes to it.
+//!
+//! The table uses the trigram tokenizer, which needs no word boundaries and
+//! therefore works for Japanese. Its one blind spot is terms shorter than
+//! three characters, which no trigram can cover; when the query contains
+//! such a term the search falls back to a LIKE scan of the same table and
+//! orders by date instead of rank.
+//!
+//! Snippets are returned as plain-text segments rather than HTML so the
+//! frontend can escape the text and add its own markup.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::consts;
+use crate::services::transaction::escape_like_pattern;
+use crate::sql_queries;
+use crate::validation;
+
+/// Shortest term the trigram index can match.
+pub(crate) const MIN_MATCH_TERM_CHARS: usize = 3;
+/// Extra terms beyond this are ignored.
+const MAX_TERMS: usize = 8;
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+/// Characters kept before the first hit in a snippet.
+const SNIPPET_LEAD_CHARS: usize = 16;
+/// Characters kept from the first hit onwards in a snippet.
+const SNIPPET_TAIL_CHARS: usize = 48;
+const ELLIPSIS: &str = "…";
+
+/// Indexed column a snippet was taken from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Memo,
+    ItemName,
+    Shop,
+    Product,
+}
+
+/// A run of snippet text; `highlighted` runs are the matched terms.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SnippetSegment {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionSearchHit {
+    pub transaction_id: i64,
+    pub transaction_date: String,
+    pub category1_code: String,
+    pub total_amount: i64,
+    pub currency_code: String,
+    /// `None` when the index matched through case folding that the
+    /// snippet builder does not reproduce (non-ASCII letters).
+    pub matched_field: Option<SearchField>,
+    pub snippet: Vec<SnippetSegment>,
+}
+
+#[derive(FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+struct SearchRow {
+    transaction_id: i64,
+    transaction_date: String,
+    category1_code: String,
+    total_amount: i64,
+    currency_code: String,
+    memo_text: Option<String>,
+    item_names: Option<String>,
+    shop_name: Option<String>,
+    product_names: Option<String>,
+}
+
+/// Search the user's transactions for every whitespace-separated term in
+/// `query`. Results are ranked by relevance (bm25), or newest first when a
+/// term is too short for the index.
+pub async fn search_transactions(
+    pool: &SqlitePool,
+    user_id: i64,
+    query: &str,
+    limit: Option<i64>,
+) -> Result<Vec<TransactionSearchHit>, ApiError> {
+    let query = query.trim();
+    validation::validate_not_empty("Search text", query).map_err(ApiError::validation)?;
+    validation::validate_max_chars("Search text", query, consts::MAX_NAME_LEN)
+        .map_err(ApiError::validation)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let terms = split_terms(query);
+
+    let rows: Vec<SearchRow> = if terms.iter().all(|t| t.chars().count() >= MIN_MATCH_TERM_CHARS) {
+        sqlx::query_as(sql_queries::TRANSACTION_SEARCH_MATCH)
+            .bind(build_match_expression(&terms))
+            .bind(user_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?
+    } else {
+        let mut sql = String::from(sql_queries::TRANSACTION_SEARCH_LIKE_BASE);
+        for _ in &terms {
+            sql.push_str(sql_queries::TRANSACTION_SEARCH_LIKE_TERM);
+        }
+        sql.push_str(sql_queries::TRANSACTION_SEARCH_LIKE_ORDER);
+
+        let mut q = sqlx::query_as(&sql).bind(user_id);
+        for term in &terms {
+            let pattern = format!("%{}%", escape_like_pattern(term));
+            for _ in 0..4 {
+                q = q.bind(pattern.clone());
+            }
+        }
+        q.bind(limit).fetch_all(pool).await?
+    };
+
+    let lowered: Vec<String> = terms.iter().map(|t| t.to_ascii_lowercase()).collect();
+    Ok(rows.into_iter().map(|row| into_hit(row, &lowered)).collect())
+}
+
+/// Split on any whitespace (full-width spaces included), dropping duplicates.
+pub(crate) fn split_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split_whitespace() {
+        if terms.len() == MAX_TERMS {
+            break;
+        }
+        if !terms.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+            terms.push(term.to_string());
+        }
+    }
+    terms
+}
+
+/// Quote each term as an FTS5 phrase so operators and punctuation in user
+/// input are taken literally. Space-separated phrases are ANDed.
+pub(crate) fn build_match_expression(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn into_hit(row: SearchRow, terms: &[String]) -> TransactionSearchHit {
+    let fields = [
+        (SearchField::Memo, row.memo_text),
+        (SearchField::ItemName, row.item_names),
+        (SearchField::Shop, row.shop_name),
+        (SearchField::Product, row.product_names),
+    ];
+    let (matched_field, snippet) = fields
+        .into_iter()
+        .find_map(|(field, text)| {
+            text.and_then(|t| build_snippet(&t, terms)).map(|s| (Some(field), s))
+        })
+        .unwrap_or((None, Vec::new()));
+
+    TransactionSearchHit {
+        transaction_id: row.transaction_id,
+        transaction_date: row.transaction_date,
+        category1_code: row.category1_code,
+        total_amount: row.total_amount,
+        currency_code: row.currency_code,
+        matched_field,
+        snippet,
+    }
+}
+
+/// Cut a window around the first hit in `text` and split it into plain and
+/// highlighted segments. `terms` must already be ASCII-lowercased; ASCII
+/// lowering keeps byte offsets, so positions found in the lowered copy are
+/// valid in `text`.
+fn build_snippet(text: &str, terms: &[String]) -> Option<Vec<SnippetSegment>> {
+    let lowered = text.to_ascii_lowercase();
+    let mut hits: Vec<(usize, usize)> = terms
+        .iter()
+        .filter(|t| !t.is_empty())
+        .flat_map(|t| lowered.match_indices(t.as_str()).map(|(i, m)| (i, i + m.len())))
+        .collect();
+    if hits.is_empty() {
+        return None;
+    }
+    hits.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(hits.len());
+    for (s, e) in hits {
+        match merged.last_mut() {
+            Some(last) if s <= last.1 => last.1 = last.1.max(e),
+            _ => merged.push((s, e)),
+        }
+    }
+
+    let first = merged[0].0;
+    let lead: Vec<usize> = text[..first].char_indices().map(|(i, _)| i).collect();
+    let start = lead.len().checked_sub(SNIPPET_LEAD_CHARS).map_or(0, |n| lead[n]);
+    let mut end = text[first..]
+        .char_indices()
+        .nth(SNIPPET_TAIL_CHARS)
+        .map_or(text.len(), |(i, _)| first + i);
+    // Never cut through a highlight
+    if let Some(&(_, e)) = merged.iter().find(|&&(s, e)| s < end && e > end) {
+        end = e;
+    }
+
+    let mut segments: Vec<SnippetSegment> = Vec::new();
+    let mut push = |text: &str, highlighted: bool| {
+        if text.is_empty() {
+            return;
+        }
+        match segments.last_mut() {
+            Some(last) if last.highlighted == highlighted => last.text.push_str(text),
+            _ => segments.push(SnippetSegment { text: text.to_string(), highlighted }),
+        }
+    };
+    if start > 0 {
+        push(ELLIPSIS, false);
+    }
+    let mut cursor = start;
+    for &(s, e) in merged.iter().filter(|&&(s, _)| s < end) {
+        push(&text[cursor..s], false);
+        push(&text[s..e], true);
+        cursor = e;
+    }
+    push(&text[cursor..end], false);
+    if end < text.len() {
+        push(ELLIPSIS, false);
+    }
+    Some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    /// User 2 with a shop, a product and two EXPENSE headers with memos and
+    /// lines; user 3 owns one header that mentions the same words.
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'search', 'x', 1, datetime('now'))",
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (3, 'other', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (3, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (3, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO SHOPS (SHOP_ID, USER_ID, SHOP_NAME) VALUES (1, 2, 'スーパー山田')",
+            "INSERT INTO MEMOS (MEMO_ID, USER_ID, MEMO_TEXT) VALUES (1, 2, '北海道旅行のお土産を購入')",
+            "INSERT INTO MEMOS (MEMO_ID, USER_ID, MEMO_TEXT) VALUES (2, 3, '北海道旅行')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, MEMO_ID, SHOP_ID) VALUES (1, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-01 10:00:00', 3000, 1, 1)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-02 10:00:00', 500)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT, MEMO_ID) VALUES (3, 3, 'EXPENSE', 'NONE', 'NONE', '2026-08-03 10:00:00', 800, 2)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (1, 1, 2, 'EXPENSE', '白い恋人', 2000)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (2, 2, 2, 'EXPENSE', 'Coffee beans', 500)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    fn ids(hits: &[TransactionSearchHit]) -> Vec<i64> {
+        hits.iter().map(|h| h.transaction_id).collect()
+    }
+
+    #[tokio::test]
+    async fn japanese_terms_match_by_trigram_and_are_scoped_to_user() {
+        let pool = setup().await;
+
+        let hits = search_transactions(&pool, 2, "北海道　お土産", None).await.unwrap();
+        assert_eq!(ids(&hits), vec![1]);
+        assert_eq!(hits[0].matched_field, Some(SearchField::Memo));
+        let marked: Vec<&str> = hits[0]
+            .snippet
+            .iter()
+            .filter(|s| s.highlighted)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(marked, vec!["北海道", "お土産"]);
+
+        // Item names and shop names are indexed too
+        assert_eq!(ids(&search_transactions(&pool, 2, "白い恋人", None).await.unwrap()), vec![1]);
+        let shop = search_transactions(&pool, 2, "スーパー", None).await.unwrap();
+        assert_eq!(shop[0].matched_field, Some(SearchField::Shop));
+
+        assert_eq!(ids(&search_transactions(&pool, 3, "北海道", None).await.unwrap()), vec![3]);
+        assert_eq!(
+            search_transactions(&pool, 2, "   ", None).await.unwrap_err().code,
+            ApiError::CODE_VALIDATION
+        );
+    }
+
+    #[tokio::test]
+    async fn short_terms_fall_back_to_like_and_ascii_is_case_insensitive() {
+        let pool = setup().await;
+
+        // Two characters: below the trigram minimum
+        assert_eq!(ids(&search_transactions(&pool, 2, "恋人", None).await.unwrap()), vec![1]);
+
+        let hits = search_transactions(&pool, 2, "COFFEE", None).await.unwrap();
+        assert_eq!(ids(&hits), vec![2]);
+        assert_eq!(hits[0].matched_field, Some(SearchField::ItemName));
+        assert_eq!(
+            hits[0].snippet,
+            vec![
+                SnippetSegment { text: "Coffee".to_string(), highlighted: true },
+                SnippetSegment { text: " beans".to_string(), highlighted: false },
+            ]
+        );
+
+        // LIKE metacharacters are literal
+        assert!(search_transactions(&pool, 2, "%", None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn index_follows_inserts_updates_and_deletes() {
+        let pool = setup().await;
+
+        sqlx::query("UPDATE MEMOS SET MEMO_TEXT = '沖縄旅行' WHERE MEMO_ID = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(search_transactions(&pool, 2, "北海道", None).await.unwrap().is_empty());
+        assert_eq!(ids(&search_transactions(&pool, 2, "沖縄旅行", None).await.unwrap()), vec![1]);
+
+        sqlx::query("UPDATE SHOPS SET SHOP_NAME = 'コンビニ' WHERE SHOP_ID = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(ids(&search_transactions(&pool, 2, "コンビニ", None).await.unwrap()), vec![1]);
+
+        sqlx::query(
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (3, 2, 2, 'EXPENSE', 'Espresso cups', 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(ids(&search_transactions(&pool, 2, "espresso", None).await.unwrap()), vec![2]);
+
+        sqlx::query("DELETE FROM TRANSACTIONS_DETAIL WHERE DETAIL_ID = 3").execute(&pool).await.unwrap();
+        assert!(search_transactions(&pool, 2, "espresso", None).await.unwrap().is_empty());
+
+        sqlx::query("DELETE FROM TRANSACTIONS_DETAIL WHERE TRANSACTION_ID = 2").execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM TRANSACTIONS_HEADER WHERE TRANSACTION_ID = 2").execute(&pool).await.unwrap();
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM TRANSACTION_SEARCH WHERE rowid = 2")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    #[test]
+    fn snippet_windows_long_text_and_merges_adjacent_hits() {
+        let text = format!("{}needle{}", "a".repeat(40), "b".repeat(80));
+        let snippet = build_snippet(&text, &["needle".to_string(), "dle".to_string()]).unwrap();
+        assert_eq!(snippet.len(), 3);
+        assert_eq!(snippet[0].text, format!("…{}", "a".repeat(SNIPPET_LEAD_CHARS)));
+        assert_eq!(snippet[1], SnippetSegment { text: "needle".to_string(), highlighted: true });
+        assert_eq!(snippet[2].text, format!("{}…", "b".repeat(SNIPPET_TAIL_CHARS - 6)));
+
+        assert!(build_snippet("nothing here", &["needle".to_string()]).is_none());
+        assert_eq!(build_match_expression(&["a\"b".to_string(), "OR".to_string()]), "\"a\"\"b\" \"OR\"");
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, FromRow};
 use crate::api_error::ApiError;
+use crate::services::audit::{self, AuditKey};
 use crate::services::master_data::{self, MasterCrudSpec};
 use crate::sql_queries;
 use crate::validation;
@@ -9,6 +10,8 @@ use crate::validation;
 /// the generic `master_data` helpers so the shared prelude (duplicate check
 /// + not-found mapping) stays out of this module. Fable-5 review #26.
 const SPEC: MasterCrudSpec = MasterCrudSpec {
+    table: "SHOPS",
+    id_column: "SHOP_ID",
     entity_label: "Shop",
     name_label: "Shop name",
     check_duplicate_for_add_sql: sql_queries::SHOP_CHECK_DUPLICATE_FOR_ADD,
@@ -84,13 +87,18 @@ pub async fn add_shop(
     )
     .await?;
 
-    sqlx::query(sql_queries::SHOP_INSERT)
+    let mut tx = pool.begin().await?;
+    let shop_id = sqlx::query(sql_queries::SHOP_INSERT)
         .bind(user_id)
         .bind(&request.shop_name)
         .bind(&request.memo)
         .bind(display_order)
-        .execute(pool)
-        .await?;
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+    let key = AuditKey::new(SPEC.table, SPEC.id_column, shop_id);
+    audit::record(&mut tx, user_id, &key, None).await?;
+    tx.commit().await?;
 
     Ok("Shop added successfully".to_string())
 }
@@ -115,16 +123,21 @@ pub async fn update_shop(
     // same thing in one round-trip instead of two, and it closes the
     // TOCTOU window where the row could vanish between the pre-check
     // and the update. See master_data::ensure_update_affected_one.
+    let mut tx = pool.begin().await?;
+    let key = AuditKey::new(SPEC.table, SPEC.id_column, shop_id);
+    let before = audit::snapshot(&mut tx, &key).await?;
     let affected = sqlx::query(sql_queries::SHOP_UPDATE)
         .bind(&request.shop_name)
         .bind(&request.memo)
         .bind(request.display_order)
         .bind(user_id)
         .bind(shop_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?
         .rows_affected();
     master_data::ensure_update_affected_one(&SPEC, affected)?;
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
 
     Ok("Shop updated successfully".to_string())
 }
@@ -148,6 +161,11 @@ mod tests {
     async fn setup_test_db() -> SqlitePool {
         let pool = init_db(TEST_DB_URL).await.unwrap();
 
+        sqlx::query(sql_queries::CREATE_AUDIT_LOG_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
+
         sqlx::query(sql_queries::TEST_CREATE_USERS_TABLE)
             .execute(&pool)
             .await
@@ -0,0 +1,356 @@
+//! Free-form tags on transactions.
+//!
+//! Categories form a strict three-level tree; tags such as "vacation-2026"
+//! or "reimbursable" cut across it. A tag can sit on a whole header or on
+//! individual detail lines. A tag on the header covers every line, so the
+//! aggregation by tag counts a line once even when both it and its header
+//! carry the same tag.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::api_error::ApiError;
+use crate::services::audit::{self, AuditKey};
+use crate::sql_queries;
+use crate::validation;
+
+const ENTITY_LABEL: &str = "Tag";
+
+fn tag_audit_key(tag_id: i64) -> AuditKey {
+    AuditKey::new("TAGS", "TAG_ID", tag_id)
+}
+
+fn tag_link_audit_key(tag_link_id: i64) -> AuditKey {
+    AuditKey::new("TRANSACTION_TAGS", "TAG_LINK_ID", tag_link_id)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct Tag {
+    pub tag_id: i64,
+    pub tag_name: String,
+    /// Headers carrying the tag on themselves or on any of their lines
+    pub transaction_count: i64,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+/// A tag as attached to a transaction; `detail_id` is `None` for a tag on
+/// the header itself.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct TransactionTag {
+    pub tag_id: i64,
+    pub tag_name: String,
+    pub detail_id: Option<i64>,
+}
+
+fn normalize_tag_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    validation::validate_master_name("Tag name", name).map_err(ApiError::validation)?;
+    Ok(name.to_string())
+}
+
+async fn ensure_name_free(
+    pool: &SqlitePool,
+    user_id: i64,
+    name: &str,
+    exclude_tag_id: i64,
+) -> Result<(), ApiError> {
+    let taken: i64 = sqlx::query_scalar(sql_queries::TAG_NAME_EXISTS)
+        .bind(user_id)
+        .bind(name)
+        .bind(exclude_tag_id)
+        .fetch_one(pool)
+        .await?;
+    if taken > 0 {
+        return Err(ApiError::duplicate_name(ENTITY_LABEL));
+    }
+    Ok(())
+}
+
+pub async fn list_tags(pool: &SqlitePool, user_id: i64) -> Result<Vec<Tag>, ApiError> {
+    let tags = sqlx::query_as::<_, Tag>(sql_queries::TAG_LIST)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(tags)
+}
+
+async fn get_tag(pool: &SqlitePool, user_id: i64, tag_id: i64) -> Result<Tag, ApiError> {
+    sqlx::query_as::<_, Tag>(sql_queries::TAG_GET_BY_ID)
+        .bind(tag_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(ENTITY_LABEL))
+}
+
+/// Create a tag. Names are compared without regard to case.
+pub async fn add_tag(pool: &SqlitePool, user_id: i64, tag_name: &str) -> Result<Tag, ApiError> {
+    let name = normalize_tag_name(tag_name)?;
+    ensure_name_free(pool, user_id, &name, 0).await?;
+
+    let mut tx = pool.begin().await?;
+    let tag_id = sqlx::query(sql_queries::TAG_INSERT)
+        .bind(user_id)
+        .bind(&name)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+    audit::record(&mut tx, user_id, &tag_audit_key(tag_id), None).await?;
+    tx.commit().await?;
+    get_tag(pool, user_id, tag_id).await
+}
+
+pub async fn rename_tag(
+    pool: &SqlitePool,
+    user_id: i64,
+    tag_id: i64,
+    tag_name: &str,
+) -> Result<Tag, ApiError> {
+    let name = normalize_tag_name(tag_name)?;
+    ensure_name_free(pool, user_id, &name, tag_id).await?;
+
+    let mut tx = pool.begin().await?;
+    let key = tag_audit_key(tag_id);
+    let before = audit::snapshot(&mut tx, &key).await?;
+    let result = sqlx::query(sql_queries::TAG_UPDATE_NAME)
+        .bind(&name)
+        .bind(tag_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
+    get_tag(pool, user_id, tag_id).await
+}
+
+/// Delete a tag; it disappears from every transaction it was on.
+pub async fn delete_tag(pool: &SqlitePool, user_id: i64, tag_id: i64) -> Result<(), ApiError> {
+    let mut tx = pool.begin().await?;
+    let key = tag_audit_key(tag_id);
+    let before = audit::snapshot(&mut tx, &key).await?;
+    let result = sqlx::query(sql_queries::TAG_DELETE)
+        .bind(tag_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found(ENTITY_LABEL));
+    }
+    audit::record(&mut tx, user_id, &key, before).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn get_transaction_tags(
+    pool: &SqlitePool,
+    user_id: i64,
+    transaction_id: i64,
+) -> Result<Vec<TransactionTag>, ApiError> {
+    let tags = sqlx::query_as::<_, TransactionTag>(sql_queries::TRANSACTION_TAG_LIST)
+        .bind(user_id)
+        .bind(transaction_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(tags)
+}
+
+/// Replace the tags on a header (`detail_id` = `None`) or on one of its
+/// lines with `tag_ids`. Tags at the other level are left alone.
+pub async fn set_transaction_tags(
+    pool: &SqlitePool,
+    user_id: i64,
+    transaction_id: i64,
+    detail_id: Option<i64>,
+    tag_ids: &[i64],
+) -> Result<Vec<TransactionTag>, ApiError> {
+    let header: Option<i64> = sqlx::query_scalar(sql_queries::TRANSACTION_HEADER_EXISTS_FOR_USER)
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+    if header.is_none() {
+        return Err(ApiError::not_found("Transaction"));
+    }
+    if let Some(detail_id) = detail_id {
+        let belongs: i64 = sqlx::query_scalar(sql_queries::TRANSACTION_DETAIL_BELONGS_TO_HEADER)
+            .bind(detail_id)
+            .bind(transaction_id)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+        if belongs == 0 {
+            return Err(ApiError::validation(format!(
+                "Detail {} is not part of transaction {}",
+                detail_id, transaction_id
+            )));
+        }
+    }
+    for &tag_id in tag_ids {
+        get_tag(pool, user_id, tag_id).await?;
+    }
+
+    let mut tx = pool.begin().await?;
+    let cleared: Vec<i64> = sqlx::query_scalar(sql_queries::TRANSACTION_TAG_LINK_IDS_TO_CLEAR)
+        .bind(user_id)
+        .bind(transaction_id)
+        .bind(detail_id)
+        .bind(detail_id)
+        .fetch_all(&mut *tx)
+        .await?;
+    let mut befores = Vec::with_capacity(cleared.len());
+    for &tag_link_id in &cleared {
+        befores.push(audit::snapshot(&mut tx, &tag_link_audit_key(tag_link_id)).await?);
+    }
+    sqlx::query(sql_queries::TRANSACTION_TAG_CLEAR)
+        .bind(user_id)
+        .bind(transaction_id)
+        .bind(detail_id)
+        .bind(detail_id)
+        .execute(&mut *tx)
+        .await?;
+    for (tag_link_id, before) in cleared.into_iter().zip(befores) {
+        audit::record(&mut tx, user_id, &tag_link_audit_key(tag_link_id), before).await?;
+    }
+    for &tag_id in tag_ids {
+        let result = sqlx::query(sql_queries::TRANSACTION_TAG_INSERT)
+            .bind(user_id)
+            .bind(tag_id)
+            .bind(transaction_id)
+            .bind(detail_id)
+            .execute(&mut *tx)
+            .await?;
+        // OR IGNORE: a tag listed twice is linked once
+        if result.rows_affected() == 1 {
+            let key = tag_link_audit_key(result.last_insert_rowid());
+            audit::record(&mut tx, user_id, &key, None).await?;
+        }
+    }
+    tx.commit().await?;
+
+    get_transaction_tags(pool, user_id, transaction_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::database::setup_migrated_test_db;
+
+    /// User 2 with two EXPENSE headers; header 1 has lines 1 and 2.
+    async fn setup() -> SqlitePool {
+        let pool = setup_migrated_test_db().await;
+        for sql in [
+            "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'tags', 'x', 1, datetime('now'))",
+            "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, DISPLAY_ORDER, CATEGORY1_NAME, ENTRY_DT) VALUES (2, 'EXPENSE', 1, '支出', datetime('now'))",
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (1, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-01 10:00:00', 3000)",
+            "INSERT INTO TRANSACTIONS_HEADER (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, TRANSACTION_DATE, TOTAL_AMOUNT) VALUES (2, 2, 'EXPENSE', 'NONE', 'NONE', '2026-08-02 10:00:00', 500)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (1, 1, 2, 'EXPENSE', 'Hotel', 2000)",
+            "INSERT INTO TRANSACTIONS_DETAIL (DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT) VALUES (2, 1, 2, 'EXPENSE', 'Taxi', 1000)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn tag_crud_rejects_duplicate_names_case_insensitively() {
+        let pool = setup().await;
+
+        let vacation = add_tag(&pool, 2, "  vacation-2026 ").await.unwrap();
+        assert_eq!(vacation.tag_name, "vacation-2026");
+        assert_eq!(vacation.transaction_count, 0);
+        let reimbursable = add_tag(&pool, 2, "reimbursable").await.unwrap();
+
+        let err = add_tag(&pool, 2, "Vacation-2026").await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_DUPLICATE_NAME);
+        let err = rename_tag(&pool, 2, reimbursable.tag_id, "VACATION-2026").await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_DUPLICATE_NAME);
+        assert_eq!(add_tag(&pool, 2, "   ").await.unwrap_err().code, ApiError::CODE_VALIDATION);
+
+        // Renaming to a different case of its own name is allowed
+        let renamed = rename_tag(&pool, 2, vacation.tag_id, "Vacation-2026").await.unwrap();
+        assert_eq!(renamed.tag_name, "Vacation-2026");
+
+        let names: Vec<String> = list_tags(&pool, 2).await.unwrap().into_iter().map(|t| t.tag_name).collect();
+        assert_eq!(names, vec!["reimbursable", "Vacation-2026"]);
+
+        delete_tag(&pool, 2, reimbursable.tag_id).await.unwrap();
+        assert_eq!(delete_tag(&pool, 2, reimbursable.tag_id).await.unwrap_err().code, ApiError::CODE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn set_transaction_tags_replaces_one_level_and_cascades() {
+        let pool = setup().await;
+        let vacation = add_tag(&pool, 2, "vacation-2026").await.unwrap().tag_id;
+        let reimbursable = add_tag(&pool, 2, "reimbursable").await.unwrap().tag_id;
+
+        set_transaction_tags(&pool, 2, 1, None, &[vacation]).await.unwrap();
+        let tags = set_transaction_tags(&pool, 2, 1, Some(2), &[reimbursable, vacation, reimbursable])
+            .await
+            .unwrap();
+        assert_eq!(
+            tags.iter().map(|t| (t.tag_name.as_str(), t.detail_id)).collect::<Vec<_>>(),
+            vec![("vacation-2026", None), ("reimbursable", Some(2)), ("vacation-2026", Some(2))]
+        );
+        // Header and line both carry the tag, but it is one transaction
+        let listed = list_tags(&pool, 2).await.unwrap();
+        assert!(listed.iter().all(|t| t.transaction_count == 1));
+
+        // Replacing the line's tags leaves the header's alone
+        let tags = set_transaction_tags(&pool, 2, 1, Some(2), &[]).await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].detail_id, None);
+
+        // A line of another header, another user's header, an unknown tag
+        let err = set_transaction_tags(&pool, 2, 2, Some(1), &[vacation]).await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_VALIDATION);
+        let err = set_transaction_tags(&pool, 3, 1, None, &[vacation]).await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_NOT_FOUND);
+        let err = set_transaction_tags(&pool, 2, 2, None, &[999]).await.unwrap_err();
+        assert_eq!(err.code, ApiError::CODE_NOT_FOUND);
+
+        set_transaction_tags(&pool, 2, 1, Some(1), &[reimbursable]).await.unwrap();
+        sqlx::query(sql_queries::TRANSACTION_DETAIL_DELETE_BY_ID)
+            .bind(1)
+            .bind(2)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(get_transaction_tags(&pool, 2, 1).await.unwrap().len(), 1);
+
+        delete_tag(&pool, 2, vacation).await.unwrap();
+        assert!(get_transaction_tags(&pool, 2, 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn tag_and_link_changes_are_audited() {
+        let pool = setup().await;
+        let tag_id = add_tag(&pool, 2, "vacation").await.unwrap().tag_id;
+        rename_tag(&pool, 2, tag_id, "holiday").await.unwrap();
+        set_transaction_tags(&pool, 2, 1, None, &[tag_id, tag_id]).await.unwrap();
+        set_transaction_tags(&pool, 2, 1, None, &[]).await.unwrap();
+        delete_tag(&pool, 2, tag_id).await.unwrap();
+
+        let audited: Vec<(String, String)> =
+            sqlx::query_as("SELECT ENTITY, ACTION FROM AUDIT_LOG WHERE USER_ID = 2 ORDER BY AUDIT_ID")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            audited,
+            [
+                ("TAGS".into(), "INSERT".into()),
+                ("TAGS".into(), "UPDATE".into()),
+                ("TRANSACTION_TAGS".into(), "INSERT".into()),
+                ("TRANSACTION_TAGS".into(), "DELETE".into()),
+                ("TAGS".into(), "DELETE".into()),
+            ]
+        );
+    }
+}
@@ -1,10 +1,20 @@
-use sqlx::{SqlitePool, Row};
+use sqlx::{Connection, Row, SqliteConnection, SqlitePool};
 use serde::{Serialize, Deserialize};
 use crate::api_error::ApiError;
+use crate::services::audit::{self, AuditKey};
+use crate::services::{currency, search};
 use crate::{sql_queries, consts, validation};
 
 const ENTITY_LABEL: &str = "Transaction";
 
+pub(crate) fn header_audit_key(transaction_id: i64) -> AuditKey {
+    AuditKey::new("TRANSACTIONS_HEADER", "TRANSACTION_ID", transaction_id)
+}
+
+fn detail_audit_key(detail_id: i64) -> AuditKey {
+    AuditKey::new("TRANSACTIONS_DETAIL", "DETAIL_ID", detail_id)
+}
+
 /// Upper bound for page size in paginated transaction listings
 const MAX_PER_PAGE: i64 = 500;
 
@@ -87,6 +97,10 @@ pub struct TransactionDetail {
     pub product_id: Option<i64>,
     #[sqlx(rename = "MEMO_ID")]
     pub memo_id: Option<i64>,
+    #[sqlx(rename = "FROM_ACCOUNT_CODE")]
+    pub from_account_code: Option<String>,
+    #[sqlx(rename = "TO_ACCOUNT_CODE")]
+    pub to_account_code: Option<String>,
     #[sqlx(rename = "ENTRY_DT")]
     pub entry_dt: String,
     #[sqlx(rename = "UPDATE_DT")]
@@ -108,6 +122,24 @@ pub struct SaveTransactionDetailRequest {
     #[serde(default)]
     pub product_id: Option<i64>,
     pub memo: Option<String>,
+    /// Accounts of a split line. Both set, the line moves money between
+    /// them under its own `category1_code`; both empty, it follows the
+    /// header's accounts and category.
+    #[serde(default)]
+    pub from_account_code: Option<String>,
+    #[serde(default)]
+    pub to_account_code: Option<String>,
+}
+
+/// Actual figures entered when confirming a scheduled occurrence whose
+/// amount was only estimated.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConfirmScheduledAdjustment {
+    pub total_amount: i64,
+    /// Replacement detail lines. When omitted, a header with one line has
+    /// that line rebooked at `total_amount`, tax included.
+    #[serde(default)]
+    pub details: Option<Vec<SaveTransactionDetailRequest>>,
 }
 
 /// Transaction detail with related information for display
@@ -132,6 +164,8 @@ pub struct TransactionDetailWithInfo {
     pub manufacturer_name: Option<String>,
     pub memo_id: Option<i64>,
     pub memo_text: Option<String>,
+    pub from_account_code: Option<String>,
+    pub to_account_code: Option<String>,
     pub entry_dt: String,
     pub update_dt: Option<String>,
 }
@@ -150,6 +184,8 @@ pub struct TransactionHeaderWithInfo {
     pub from_account_name: Option<String>,
     pub to_account_name: Option<String>,
     pub total_amount: i64,
+    /// Currency TOTAL_AMOUNT is in (minor units)
+    pub currency_code: String,
     pub tax_rounding_type: i64,
     pub tax_included_type: i64,
     pub memo_id: Option<i64>,
@@ -177,12 +213,17 @@ pub struct Transaction {
     pub to_account_code: String,
     #[sqlx(rename = "TOTAL_AMOUNT")]
     pub total_amount: i64,
+    #[sqlx(rename = "CURRENCY_CODE")]
+    pub currency_code: String,
     #[sqlx(rename = "TAX_ROUNDING_TYPE")]
     pub tax_rounding_type: i64,
     #[sqlx(rename = "MEMO_ID")]
     pub memo_id: Option<i64>,
     #[sqlx(rename = "IS_SCHEDULED")]
     pub is_scheduled: i64,
+    /// 0 uncleared, 1 cleared, 2 reconciled (locked)
+    #[sqlx(rename = "RECONCILE_STATUS")]
+    pub reconcile_status: i64,
     #[sqlx(rename = "CATEGORY1_NAME")]
     pub category1_name: Option<String>,
     #[sqlx(rename = "FROM_ACCOUNT_NAME")]
@@ -191,6 +232,10 @@ pub struct Transaction {
     pub to_account_name: Option<String>,
     #[sqlx(rename = "MEMO_TEXT")]
     pub memo_text: Option<String>,
+    /// 1 when the occurrence comes from a recurring rule whose amount is an
+    /// estimate, so confirming it should ask for the actual amount
+    #[sqlx(rename = "IS_ESTIMATED")]
+    pub is_estimated: i64,
 }
 
 /// Transaction list response with pagination
@@ -203,6 +248,73 @@ pub struct TransactionListResponse {
     pub total_pages: i64,
 }
 
+/// Which headers the transaction list shows by IS_SCHEDULED
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatusFilter {
+    /// Confirmed transactions only (IS_SCHEDULED = 0)
+    #[default]
+    Confirmed,
+    /// Scheduled transactions only (IS_SCHEDULED = 1)
+    Scheduled,
+    /// Both
+    All,
+}
+
+/// Sort order of the transaction list. Ties fall back to newest first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionSort {
+    #[default]
+    DateDesc,
+    DateAsc,
+    AmountDesc,
+    AmountAsc,
+}
+
+impl TransactionSort {
+    fn order_by_sql(self) -> &'static str {
+        match self {
+            TransactionSort::DateDesc => sql_queries::TRANSACTION_LIST_ORDER,
+            TransactionSort::DateAsc => sql_queries::TRANSACTION_LIST_ORDER_DATE_ASC,
+            TransactionSort::AmountDesc => sql_queries::TRANSACTION_LIST_ORDER_AMOUNT_DESC,
+            TransactionSort::AmountAsc => sql_queries::TRANSACTION_LIST_ORDER_AMOUNT_ASC,
+        }
+    }
+}
+
+/// Conditions and sort order of the transaction list. Every field is
+/// optional; the default lists confirmed transactions newest first.
+/// Serialised as-is into SAVED_FILTERS, so fields only ever get added with
+/// a serde default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionQuery {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub category1_code: Option<String>,
+    pub category2_code: Option<String>,
+    pub category3_code: Option<String>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+    /// Substring of the header or any detail memo
+    pub keyword: Option<String>,
+    /// Every listed tag must be on the header or one of its lines
+    pub tag_ids: Vec<i64>,
+    pub shop_id: Option<i64>,
+    /// Account on either side of the header or of any split line
+    pub account_code: Option<String>,
+    pub product_id: Option<i64>,
+    /// Manufacturer of the product on any detail line
+    pub manufacturer_id: Option<i64>,
+    /// Substring of any detail line's item name
+    pub item_name: Option<String>,
+    /// Occurrences generated by this recurring rule
+    pub rule_id: Option<i64>,
+    pub status: TransactionStatusFilter,
+    pub sort: TransactionSort,
+}
+
 /// Transaction service for managing income/expense data
 pub struct TransactionService {
     pool: SqlitePool,
@@ -213,6 +325,8 @@ pub enum TransactionError {
     DatabaseError(String),
     ValidationError(String),
     NotFound,
+    /// Header is RECONCILE_STATUS_RECONCILED and must be unlocked first
+    Locked,
 }
 
 impl std::fmt::Display for TransactionError {
@@ -221,6 +335,7 @@ impl std::fmt::Display for TransactionError {
             TransactionError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             TransactionError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             TransactionError::NotFound => write!(f, "Transaction not found"),
+            TransactionError::Locked => write!(f, "Transaction is reconciled"),
         }
     }
 }
@@ -250,16 +365,171 @@ impl From<sqlx::Error> for TransactionError {
 ///                               `"Memo must be …"` — to the right inline
 ///                               input via `startsWith`)
 ///   - `DatabaseError(msg)`    → `database`
+///   - `Locked`                → `reconciled_locked`
 impl From<TransactionError> for ApiError {
     fn from(err: TransactionError) -> Self {
         match err {
             TransactionError::NotFound => ApiError::not_found(ENTITY_LABEL),
             TransactionError::ValidationError(msg) => ApiError::validation(msg),
             TransactionError::DatabaseError(msg) => ApiError::database(msg),
+            TransactionError::Locked => ApiError::reconciled_locked(),
         }
     }
 }
 
+/// Build the WHERE clause (table alias `t.`) and its string binds for the
+/// transaction list. Shared by the live list and saved filters so both
+/// select exactly the same rows. User input only ever reaches the binds.
+pub(crate) fn build_transaction_where(user_id: i64, query: &TransactionQuery) -> (String, Vec<String>) {
+    let mut where_clauses = vec!["t.USER_ID = ?".to_string()];
+    let mut params: Vec<String> = vec![user_id.to_string()];
+
+    // Exclude scheduled transactions by default. When the user opts in,
+    // every IS_SCHEDULED row is shown, including each occurrence of a
+    // recurring rule — the date filter (TRANSACTION_DATE BETWEEN ...)
+    // is what scopes the visible window. Group membership of recurring
+    // occurrences is preserved through RULE_ID, not through any
+    // representative-row trick that would have to be maintained on
+    // every confirm/delete.
+    match query.status {
+        TransactionStatusFilter::Confirmed => where_clauses.push("t.IS_SCHEDULED = 0".to_string()),
+        TransactionStatusFilter::Scheduled => where_clauses.push("t.IS_SCHEDULED = 1".to_string()),
+        TransactionStatusFilter::All => {}
+    }
+
+    if let Some(start) = query.start_date.as_deref() {
+        where_clauses.push("t.TRANSACTION_DATE >= ?".to_string());
+        params.push(start.to_string());
+    }
+
+    if let Some(end) = query.end_date.as_deref() {
+        where_clauses.push("t.TRANSACTION_DATE <= ?".to_string());
+        // TRANSACTION_DATE is stored as 'YYYY-MM-DD HH:MM:SS' but the UI's
+        // <input type="date"> sends bare 'YYYY-MM-DD', which under string
+        // comparison drops every same-day timestamp. Anchor to end-of-day
+        // so the boundary day is included.
+        let normalized = if end.len() == 10 {
+            format!("{} 23:59:59", end)
+        } else {
+            end.to_string()
+        };
+        params.push(normalized);
+    }
+
+    if let Some(cat1) = query.category1_code.as_deref() {
+        if !cat1.is_empty() {
+            where_clauses.push("t.CATEGORY1_CODE = ?".to_string());
+            params.push(cat1.to_string());
+        }
+    }
+
+    // CATEGORY2_CODE / CATEGORY3_CODE live on TRANSACTIONS_DETAIL, so filter
+    // via EXISTS to keep the header row count stable (no DISTINCT needed).
+    if let Some(cat2) = query.category2_code.as_deref() {
+        if !cat2.is_empty() {
+            where_clauses.push(
+                "EXISTS (SELECT 1 FROM TRANSACTIONS_DETAIL td \
+                 WHERE td.USER_ID = t.USER_ID \
+                   AND td.TRANSACTION_ID = t.TRANSACTION_ID \
+                   AND td.CATEGORY2_CODE = ?)"
+                    .to_string(),
+            );
+            params.push(cat2.to_string());
+        }
+    }
+
+    if let Some(cat3) = query.category3_code.as_deref() {
+        if !cat3.is_empty() {
+            where_clauses.push(
+                "EXISTS (SELECT 1 FROM TRANSACTIONS_DETAIL td \
+                 WHERE td.USER_ID = t.USER_ID \
+                   AND td.TRANSACTION_ID = t.TRANSACTION_ID \
+                   AND td.CATEGORY3_CODE = ?)"
+                    .to_string(),
+            );
+            params.push(cat3.to_string());
+        }
+    }
+
+    if let Some(min) = query.min_amount {
+        where_clauses.push("t.TOTAL_AMOUNT >= ?".to_string());
+        params.push(min.to_string());
+    }
+
+    if let Some(max) = query.max_amount {
+        where_clauses.push("t.TOTAL_AMOUNT <= ?".to_string());
+        params.push(max.to_string());
+    }
+
+    // Keyword: same matching as the search box — memo, item names, shop and
+    // product through TRANSACTION_SEARCH, falling back to LIKE per term when
+    // any term is too short for the trigram index.
+    if let Some(kw) = query.keyword.as_deref() {
+        let terms = search::split_terms(kw);
+        if !terms.is_empty() && terms.iter().all(|t| t.chars().count() >= search::MIN_MATCH_TERM_CHARS) {
+            where_clauses.push(sql_queries::TRANSACTION_KEYWORD_SEARCH_FILTER.to_string());
+            params.push(search::build_match_expression(&terms));
+            params.push(user_id.to_string());
+        } else {
+            for term in &terms {
+                let pattern = format!("%{}%", escape_like_pattern(term));
+                where_clauses.push(sql_queries::TRANSACTION_KEYWORD_LIKE_FILTER.to_string());
+                params.push(user_id.to_string());
+                for _ in 0..4 {
+                    params.push(pattern.clone());
+                }
+            }
+        }
+    }
+
+    // Tags: the header must carry each requested tag, on itself or on
+    // any of its lines. EXISTS keeps one row per header however many
+    // lines match.
+    for tag_id in &query.tag_ids {
+        where_clauses.push(sql_queries::TRANSACTION_TAG_FILTER.to_string());
+        params.push(tag_id.to_string());
+    }
+
+    if let Some(shop_id) = query.shop_id {
+        where_clauses.push("t.SHOP_ID = ?".to_string());
+        params.push(shop_id.to_string());
+    }
+
+    if let Some(account) = query.account_code.as_deref() {
+        if !account.is_empty() {
+            where_clauses.push(sql_queries::TRANSACTION_ACCOUNT_FILTER.to_string());
+            for _ in 0..4 {
+                params.push(account.to_string());
+            }
+        }
+    }
+
+    if let Some(product_id) = query.product_id {
+        where_clauses.push(sql_queries::TRANSACTION_PRODUCT_FILTER.to_string());
+        params.push(product_id.to_string());
+    }
+
+    if let Some(manufacturer_id) = query.manufacturer_id {
+        where_clauses.push(sql_queries::TRANSACTION_MANUFACTURER_FILTER.to_string());
+        params.push(manufacturer_id.to_string());
+    }
+
+    if let Some(item) = query.item_name.as_deref() {
+        let item = item.trim();
+        if !item.is_empty() {
+            where_clauses.push(sql_queries::TRANSACTION_ITEM_NAME_FILTER.to_string());
+            params.push(format!("%{}%", escape_like_pattern(item)));
+        }
+    }
+
+    if let Some(rule_id) = query.rule_id {
+        where_clauses.push("t.RULE_ID = ?".to_string());
+        params.push(rule_id.to_string());
+    }
+
+    (where_clauses.join(" AND "), params)
+}
+
 /// MEMOS.MEMO_TEXT length guard, shared by the header and detail paths.
 fn validate_memo_length(memo_text: &str) -> Result<(), TransactionError> {
     validation::validate_max_chars("Memo", memo_text, consts::MAX_MEMO_LEN)
@@ -272,10 +542,45 @@ fn validate_item_name_length(item_name: &str) -> Result<(), TransactionError> {
         .map_err(TransactionError::ValidationError)
 }
 
+/// Field checks shared by every path that writes a detail line.
+fn validate_detail_request(request: &SaveTransactionDetailRequest) -> Result<(), TransactionError> {
+    // Validate item name
+    if request.item_name.trim().is_empty() {
+        return Err(TransactionError::ValidationError(
+            "Item name is required".to_string(),
+        ));
+    }
+
+    validate_item_name_length(&request.item_name)?;
+
+    // Validate amount
+    if request.amount < 0 || request.amount > 999_999_999 {
+        return Err(TransactionError::ValidationError(
+            "Amount must be between 0 and 999,999,999".to_string(),
+        ));
+    }
+
+    // Validate tax rate
+    if request.tax_rate < 0 || request.tax_rate > 100 {
+        return Err(TransactionError::ValidationError(
+            "Tax rate must be between 0 and 100".to_string(),
+        ));
+    }
+
+    // Validate tax amount
+    if request.tax_amount < 0 {
+        return Err(TransactionError::ValidationError(
+            "Tax amount cannot be negative".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /// Escape SQL LIKE metacharacters so user-supplied text matches literally.
 /// Paired with `LIKE ? ESCAPE '\'` in the query. Backslash must be escaped
 /// first so we do not re-escape the escapes we just added.
-fn escape_like_pattern(s: &str) -> String {
+pub(crate) fn escape_like_pattern(s: &str) -> String {
     s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
 }
 
@@ -348,6 +653,10 @@ pub struct DetailForRecalc {
     pub amount: i64,
     pub amount_including_tax: Option<i64>,
     pub tax_rate: i64,
+    /// Which (category, from, to) leg of a split header the line belongs
+    /// to; every line of an ordinary header shares one leg. Each leg is
+    /// rounded on its own, the way TRANSACTION_MOVEMENTS books it.
+    pub leg: i64,
 }
 
 /// Compute the recommended `TOTAL_AMOUNT` for a transaction header from its
@@ -363,7 +672,9 @@ pub struct DetailForRecalc {
 ///    gross-up factor is applied — never the other way around — and the
 ///    rounding rule is applied exactly once per `(rate, rounding_type)` slice
 ///    to avoid the per-detail rounding error that v1.x carried.
-/// 3. The integer slices are summed to produce the header total.
+/// 3. The integer slices are summed to produce the header total. A split
+///    header is sliced per `(leg, rate)` instead, so the total equals the
+///    sum of the amounts its legs move.
 ///
 /// `tax_rounding_type` follows the existing constants:
 /// - `0` → floor (`TAX_ROUND_DOWN`)
@@ -376,14 +687,14 @@ pub fn calculate_recommended_total(
 ) -> i64 {
     use std::collections::HashMap;
 
-    // (already_included_sum, pretax_sum) keyed by tax_rate
-    let mut by_rate: HashMap<i64, (i64, i64)> = HashMap::new();
+    // (already_included_sum, pretax_sum) keyed by (leg, tax_rate)
+    let mut by_rate: HashMap<(i64, i64), (i64, i64)> = HashMap::new();
 
     for d in details {
         let is_already_included = d.tax_rate == 0
             || d.amount_including_tax.map_or(false, |inc| inc == d.amount);
 
-        let entry = by_rate.entry(d.tax_rate).or_insert((0, 0));
+        let entry = by_rate.entry((d.leg, d.tax_rate)).or_insert((0, 0));
         if is_already_included {
             entry.0 += d.amount;
         } else {
@@ -392,7 +703,7 @@ pub fn calculate_recommended_total(
     }
 
     let mut total: i64 = 0;
-    for (rate, (already, pretax)) in by_rate {
+    for ((_, rate), (already, pretax)) in by_rate {
         // pretax * (100 + rate) is the un-rounded grossed amount in 1/100ths
         // of a yen; rounding it back to whole yen depends on the chosen mode.
         let grossed = pretax * (100 + rate);
@@ -461,6 +772,684 @@ fn find_matching_pattern(details: &[DetailForRecalc], target_total: i64) -> Opti
     None
 }
 
+/// Column mapping used to turn one account's CSV statement into ledger rows.
+/// Column indexes are 0-based. Exactly one amount layout applies: a single
+/// signed `amount_column`, or the `withdrawal_column` / `deposit_column` pair
+/// that Japanese bank passbook exports use.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+#[sqlx(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ImportProfile {
+    pub profile_id: i64,
+    pub user_id: i64,
+    pub account_code: String,
+    pub profile_name: String,
+    pub delimiter: String,
+    /// Leading lines (column titles, account banners) to ignore.
+    pub skip_rows: i64,
+    pub date_column: i64,
+    /// chrono `strftime` pattern, e.g. `%Y/%m/%d`.
+    pub date_format: String,
+    pub amount_column: Option<i64>,
+    pub withdrawal_column: Option<i64>,
+    pub deposit_column: Option<i64>,
+    pub description_column: i64,
+    pub memo_column: Option<i64>,
+    /// 1 when the statement prints charges as positive numbers (most card
+    /// statements), so the signed amount has to be flipped.
+    pub negate_amount: i64,
+    /// Account booked on the other side of every imported row.
+    pub counter_account_code: String,
+    pub entry_dt: String,
+    pub update_dt: Option<String>,
+}
+
+/// Request structure for creating or replacing an account's import profile
+#[derive(Debug, Deserialize, Clone)]
+pub struct SaveImportProfileRequest {
+    pub account_code: String,
+    pub profile_name: String,
+    pub delimiter: Option<String>,
+    pub skip_rows: Option<i64>,
+    pub date_column: i64,
+    pub date_format: Option<String>,
+    pub amount_column: Option<i64>,
+    pub withdrawal_column: Option<i64>,
+    pub deposit_column: Option<i64>,
+    pub description_column: i64,
+    pub memo_column: Option<i64>,
+    pub negate_amount: Option<bool>,
+    pub counter_account_code: Option<String>,
+}
+
+/// One statement line mapped onto the header/detail it would create.
+/// `amount` is the unsigned TOTAL_AMOUNT; the direction is carried by
+/// `category1_code` and the FROM/TO pair.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct StatementRow {
+    pub transaction_date: String,
+    pub category1_code: String,
+    pub from_account_code: String,
+    pub to_account_code: String,
+    pub amount: i64,
+    pub item_name: String,
+    pub memo: Option<String>,
+}
+
+/// Dry-run outcome for a single CSV record. Exactly one of `row` / `error`
+/// is set. `line_number` is 1-based and points at the physical line the
+/// record starts on, so the user can find it in the original file.
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportPreviewRow {
+    pub line_number: usize,
+    pub row: Option<StatementRow>,
+    pub is_duplicate: bool,
+    pub error: Option<String>,
+}
+
+/// Result of `preview_csv_import`
+#[derive(Debug, Serialize)]
+pub struct ImportPreview {
+    pub account_code: String,
+    pub rows: Vec<ImportPreviewRow>,
+    pub new_count: usize,
+    pub duplicate_count: usize,
+    pub error_count: usize,
+}
+
+/// Result of `import_csv_statement`
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    pub imported_count: usize,
+    pub skipped_duplicate_count: usize,
+    pub skipped_error_count: usize,
+    pub transaction_ids: Vec<i64>,
+}
+
+/// Split CSV text into records. Handles RFC 4180 quoting (delimiters and
+/// line breaks inside quotes, `""` as an escaped quote), CRLF line endings
+/// and a leading UTF-8 BOM. Returns `(line_number, fields)` pairs where
+/// `line_number` is the 1-based line the record starts on.
+pub(crate) fn parse_csv_records(text: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' | '\n' => {
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut fields)));
+                line += 1;
+                record_line = line;
+            }
+            c if c == delimiter => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(format!("Unterminated quoted field starting on line {}", record_line));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+
+    Ok(records)
+}
+
+/// Parse a statement amount cell into a signed amount in minor units of a
+/// currency with `minor_units` digits. Blank cells yield `None` (the
+/// withdrawal/deposit layout leaves one side empty). Accepts the decorations
+/// bank exports actually carry: thousands separators, yen signs, a trailing
+/// `円`, `▲`/`△` or parentheses for negatives, and a zero fractional part
+/// such as `1200.00`.
+fn parse_statement_amount(raw: &str, minor_units: u32) -> Result<Option<i64>, String> {
+    let cleaned: String = raw
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ',' | '¥' | '￥' | '円' | ' ' | '\u{3000}'))
+        .collect();
+    if cleaned.is_empty() {
+        return Ok(None);
+    }
+
+    let (negative, digits) = if let Some(rest) = cleaned
+        .strip_prefix('-')
+        .or_else(|| cleaned.strip_prefix('−'))
+        .or_else(|| cleaned.strip_prefix('▲'))
+        .or_else(|| cleaned.strip_prefix('△'))
+    {
+        (true, rest)
+    } else if let Some(rest) = cleaned.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        (true, rest)
+    } else {
+        (false, cleaned.strip_prefix('+').unwrap_or(&cleaned))
+    };
+
+    let value = currency::parse_minor_amount(digits, minor_units).ok_or_else(|| {
+        match digits.split_once('.') {
+            Some((int, frac))
+                if !int.is_empty()
+                    && int.chars().all(|c| c.is_ascii_digit())
+                    && frac.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                format!("Fractional amount is not supported: {}", raw.trim())
+            }
+            _ => format!("Invalid amount: {}", raw.trim()),
+        }
+    })?;
+    Ok(Some(if negative { -value } else { value }))
+}
+
+/// Reject strftime patterns chrono cannot interpret, so a bad profile fails
+/// at save time rather than turning every row of the next import into an
+/// error.
+fn validate_date_format(format: &str) -> Result<(), TransactionError> {
+    use chrono::format::{Item, StrftimeItems};
+
+    if format.trim().is_empty() || StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(TransactionError::ValidationError(format!(
+            "Invalid date format: {}",
+            format
+        )));
+    }
+    Ok(())
+}
+
+/// Map one CSV record onto a `StatementRow` using `profile`. The statement
+/// account is debited for negative amounts (EXPENSE, account → counter) and
+/// credited for positive ones (INCOME, counter → account), matching the sign
+/// rules in `ACCOUNT_BALANCES_AS_OF`.
+fn map_statement_record(
+    profile: &ImportProfile,
+    fields: &[String],
+    minor_units: u32,
+) -> Result<StatementRow, String> {
+    let cell = |index: i64| -> Result<&str, String> {
+        fields
+            .get(index as usize)
+            .map(|s| s.trim())
+            .ok_or_else(|| format!("Column {} is missing", index + 1))
+    };
+
+    let date_cell = cell(profile.date_column)?;
+    let date = chrono::NaiveDate::parse_from_str(date_cell, &profile.date_format)
+        .map_err(|_| format!("Invalid date: {}", date_cell))?;
+
+    let signed = match profile.amount_column {
+        Some(column) => parse_statement_amount(cell(column)?, minor_units)?,
+        None => {
+            let withdrawal = match profile.withdrawal_column {
+                Some(column) => parse_statement_amount(cell(column)?, minor_units)?,
+                None => None,
+            };
+            let deposit = match profile.deposit_column {
+                Some(column) => parse_statement_amount(cell(column)?, minor_units)?,
+                None => None,
+            };
+            match (withdrawal, deposit) {
+                (None, None) => None,
+                (w, d) => Some(d.unwrap_or(0) - w.unwrap_or(0)),
+            }
+        }
+    };
+    let signed = signed.ok_or_else(|| "Amount is empty".to_string())?;
+    let signed = if profile.negate_amount != 0 { -signed } else { signed };
+    if signed == 0 {
+        return Err("Amount is zero".to_string());
+    }
+    let amount = signed.abs();
+    if amount > 999_999_999 {
+        return Err("Amount must be between 0 and 999,999,999".to_string());
+    }
+
+    let item_name = cell(profile.description_column)?.to_string();
+    if item_name.is_empty() {
+        return Err("Item name is required".to_string());
+    }
+    validation::validate_max_chars("Item name", &item_name, consts::MAX_ITEM_NAME_LEN)?;
+
+    let memo = match profile.memo_column {
+        Some(column) => {
+            let text = cell(column)?;
+            if text.is_empty() {
+                None
+            } else {
+                validation::validate_max_chars("Memo", text, consts::MAX_MEMO_LEN)?;
+                Some(text.to_string())
+            }
+        }
+        None => None,
+    };
+
+    let (category1_code, from_account_code, to_account_code) = if signed < 0 {
+        (consts::CATEGORY1_EXPENSE, &profile.account_code, &profile.counter_account_code)
+    } else {
+        (consts::CATEGORY1_INCOME, &profile.counter_account_code, &profile.account_code)
+    };
+
+    Ok(StatementRow {
+        transaction_date: format!("{} 00:00:00", date.format("%Y-%m-%d")),
+        category1_code: category1_code.to_string(),
+        from_account_code: from_account_code.clone(),
+        to_account_code: to_account_code.clone(),
+        amount,
+        item_name,
+        memo,
+    })
+}
+
+/// Parse a whole statement with `profile`. A malformed file (unterminated
+/// quote) fails as a whole; per-record problems are reported on the row so
+/// the preview can show them next to the good lines. Blank lines are
+/// dropped. Amounts are read in minor units of the statement account's
+/// currency.
+fn parse_statement(
+    profile: &ImportProfile,
+    csv_text: &str,
+    minor_units: u32,
+) -> Result<Vec<ImportPreviewRow>, TransactionError> {
+    let delimiter = profile.delimiter.chars().next().unwrap_or(',');
+    let records = parse_csv_records(csv_text, delimiter).map_err(TransactionError::ValidationError)?;
+
+    Ok(records
+        .into_iter()
+        .skip(profile.skip_rows.max(0) as usize)
+        .filter(|(_, fields)| fields.iter().any(|f| !f.trim().is_empty()))
+        .map(|(line_number, fields)| match map_statement_record(profile, &fields, minor_units) {
+            Ok(row) => ImportPreviewRow { line_number, row: Some(row), is_duplicate: false, error: None },
+            Err(e) => ImportPreviewRow { line_number, row: None, is_duplicate: false, error: Some(e) },
+        })
+        .collect())
+}
+
+/// Flag rows that are already booked on `account_code`: same calendar date
+/// and same TOTAL_AMOUNT. Matching is count-aware — if the ledger holds one
+/// ¥500 coffee on a day and the statement lists two, only the first
+/// statement row is flagged, so legitimately repeated purchases survive.
+async fn mark_import_duplicates(
+    conn: &mut sqlx::SqliteConnection,
+    user_id: i64,
+    account_code: &str,
+    rows: &mut [ImportPreviewRow],
+) -> Result<(), sqlx::Error> {
+    use std::collections::HashMap;
+
+    let mut remaining: HashMap<(String, i64), i64> = HashMap::new();
+    for preview in rows.iter_mut() {
+        let Some(row) = &preview.row else { continue };
+        let key = (row.transaction_date[..10].to_string(), row.amount);
+        let left = match remaining.get_mut(&key) {
+            Some(left) => left,
+            None => {
+                let count: i64 = sqlx::query_scalar(sql_queries::IMPORT_COUNT_EXISTING_MATCHES)
+                    .bind(user_id)
+                    .bind(&key.0)
+                    .bind(key.1)
+                    .bind(account_code)
+                    .bind(account_code)
+                    .fetch_one(&mut *conn)
+                    .await?;
+                remaining.entry(key).or_insert(count)
+            }
+        };
+        if *left > 0 {
+            *left -= 1;
+            preview.is_duplicate = true;
+        }
+    }
+    Ok(())
+}
+
+/// Currency for a header between the two accounts. Mixing currencies is the
+/// caller's mistake and stays a validation error.
+async fn header_currency(
+    pool: &SqlitePool,
+    user_id: i64,
+    from_account_code: &str,
+    to_account_code: &str,
+) -> Result<String, TransactionError> {
+    currency::transaction_currency(pool, user_id, from_account_code, to_account_code)
+        .await
+        .map_err(|e| {
+            if e.code == ApiError::CODE_VALIDATION {
+                TransactionError::ValidationError(e.message)
+            } else {
+                TransactionError::DatabaseError(e.message)
+            }
+        })
+}
+
+/// Normalise and check the accounts of a split detail line. Returns
+/// `(None, None)` for an ordinary line that follows its header. A split line
+/// needs both accounts, both must belong to the user, and they must be in
+/// the currency the header is booked in so its legs stay comparable.
+async fn split_line_accounts(
+    pool: &SqlitePool,
+    user_id: i64,
+    transaction_id: i64,
+    from_account_code: Option<&str>,
+    to_account_code: Option<&str>,
+) -> Result<(Option<String>, Option<String>), TransactionError> {
+    let normalize = |code: Option<&str>| {
+        code.map(|c| c.trim().to_uppercase()).filter(|c| !c.is_empty())
+    };
+    let (from, to) = match (normalize(from_account_code), normalize(to_account_code)) {
+        (None, None) => return Ok((None, None)),
+        (Some(from), Some(to)) => (from, to),
+        _ => {
+            return Err(TransactionError::ValidationError(
+                "A split line needs both a from and a to account".to_string(),
+            ))
+        }
+    };
+
+    let known: Vec<String> = sqlx::query_scalar(sql_queries::TRANSACTION_DETAIL_LINE_ACCOUNTS)
+        .bind(user_id)
+        .bind(&from)
+        .bind(&to)
+        .fetch_all(pool)
+        .await?;
+    for code in [&from, &to] {
+        if !known.contains(code) {
+            return Err(TransactionError::ValidationError(format!(
+                "Account {} does not exist",
+                code
+            )));
+        }
+    }
+
+    let booked_in: String = sqlx::query_scalar(sql_queries::TRANSACTION_HEADER_GET_CURRENCY)
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(TransactionError::NotFound)?;
+    let line_currency = header_currency(pool, user_id, &from, &to).await?;
+    if line_currency != booked_in {
+        return Err(TransactionError::ValidationError(format!(
+            "Split line accounts use {} but the transaction is booked in {}",
+            line_currency, booked_in
+        )));
+    }
+
+    Ok((Some(from), Some(to)))
+}
+
+/// Most transactions one bulk command may touch.
+const MAX_BULK_TRANSACTIONS: usize = 1000;
+
+/// A change `bulk_update_transactions` applies to every selected transaction.
+/// The frontend sends it as `{ "action": "set_shop", "shop_id": 3 }` etc.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BulkTransactionChange {
+    /// `None` clears the shop.
+    SetShop { shop_id: Option<i64> },
+    /// Set (or with `None`, clear) the sub-categories of every detail line.
+    SetCategories {
+        category2_code: Option<String>,
+        category3_code: Option<String>,
+    },
+    /// Replace the header's from and/or to account. Split lines keep their
+    /// own accounts. The new accounts must be in the currency each header is
+    /// already booked in.
+    MoveAccount {
+        from_account_code: Option<String>,
+        to_account_code: Option<String>,
+    },
+    /// `None` or blank clears the memo.
+    SetMemo { memo: Option<String> },
+    Delete,
+    ConfirmScheduled,
+}
+
+/// Outcome for one transaction of a bulk command; `error` is `None` when the
+/// change was applied.
+#[derive(Debug, Serialize, Clone)]
+pub struct BulkTransactionResult {
+    pub transaction_id: i64,
+    pub error: Option<ApiError>,
+}
+
+/// Refuse a write to a header that a reconciled checkpoint has locked. Runs
+/// on the write's own transaction so the check and the change see the same
+/// row; a missing header is left to the caller's NotFound handling.
+async fn ensure_not_reconciled(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    transaction_id: i64,
+) -> Result<(), TransactionError> {
+    let reconcile_status: Option<i64> =
+        sqlx::query_scalar(sql_queries::TRANSACTION_HEADER_GET_RECONCILE_STATUS)
+            .bind(transaction_id)
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if reconcile_status == Some(consts::RECONCILE_STATUS_RECONCILED) {
+        return Err(TransactionError::Locked);
+    }
+    Ok(())
+}
+
+/// Apply one bulk change to one header on `conn`. Everything runs on the
+/// caller's connection so it can be rolled back to a savepoint on failure.
+async fn apply_bulk_change(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    transaction_id: i64,
+    change: &BulkTransactionChange,
+) -> Result<(), TransactionError> {
+    let (from_account_code, to_account_code, currency_code, reconcile_status, old_memo_id):
+        (String, String, String, i64, Option<i64>) =
+        sqlx::query_as(sql_queries::TRANSACTION_BULK_GET_HEADER)
+            .bind(transaction_id)
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or(TransactionError::NotFound)?;
+    if reconcile_status == consts::RECONCILE_STATUS_RECONCILED
+        && !matches!(change, BulkTransactionChange::ConfirmScheduled)
+    {
+        return Err(TransactionError::Locked);
+    }
+
+    // Category changes land on the detail lines, everything else on the header
+    let mut keys = vec![header_audit_key(transaction_id)];
+    if matches!(change, BulkTransactionChange::SetCategories { .. }) {
+        let detail_ids: Vec<i64> = sqlx::query_scalar(sql_queries::TRANSACTION_BULK_DETAIL_IDS)
+            .bind(transaction_id)
+            .bind(user_id)
+            .fetch_all(&mut *conn)
+            .await?;
+        keys.extend(detail_ids.into_iter().map(detail_audit_key));
+    }
+    let mut befores = Vec::with_capacity(keys.len());
+    for key in &keys {
+        befores.push(audit::snapshot(conn, key).await?);
+    }
+
+    let result = match change {
+        BulkTransactionChange::SetShop { shop_id } => {
+            sqlx::query(sql_queries::TRANSACTION_BULK_SET_SHOP)
+                .bind(shop_id)
+                .bind(transaction_id)
+                .bind(user_id)
+                .execute(&mut *conn)
+                .await?
+        }
+        BulkTransactionChange::SetCategories { category2_code, category3_code } => {
+            let category1_codes: Vec<String> =
+                sqlx::query_scalar(sql_queries::TRANSACTION_BULK_DETAIL_CATEGORY1_CODES)
+                    .bind(transaction_id)
+                    .bind(user_id)
+                    .fetch_all(&mut *conn)
+                    .await?;
+            if category1_codes.is_empty() {
+                return Err(TransactionError::ValidationError(
+                    "Transaction has no detail lines".to_string(),
+                ));
+            }
+            for category1_code in &category1_codes {
+                if let Some(category2_code) = category2_code {
+                    let found: i64 = sqlx::query_scalar(sql_queries::CATEGORY2_CHECK_DUPLICATE_CODE)
+                        .bind(user_id)
+                        .bind(category1_code)
+                        .bind(category2_code)
+                        .fetch_one(&mut *conn)
+                        .await?;
+                    if found == 0 {
+                        return Err(TransactionError::ValidationError(format!(
+                            "Category {} does not exist under {}",
+                            category2_code, category1_code
+                        )));
+                    }
+                }
+                if let (Some(category2_code), Some(category3_code)) = (category2_code, category3_code) {
+                    let found: i64 = sqlx::query_scalar(sql_queries::CATEGORY3_CHECK_DUPLICATE_CODE)
+                        .bind(user_id)
+                        .bind(category1_code)
+                        .bind(category2_code)
+                        .bind(category3_code)
+                        .fetch_one(&mut *conn)
+                        .await?;
+                    if found == 0 {
+                        return Err(TransactionError::ValidationError(format!(
+                            "Category {} does not exist under {}/{}",
+                            category3_code, category1_code, category2_code
+                        )));
+                    }
+                }
+            }
+            sqlx::query(sql_queries::TRANSACTION_BULK_SET_DETAIL_CATEGORIES)
+                .bind(category2_code)
+                .bind(category3_code)
+                .bind(transaction_id)
+                .bind(user_id)
+                .execute(&mut *conn)
+                .await?
+        }
+        BulkTransactionChange::MoveAccount { from_account_code: new_from, to_account_code: new_to } => {
+            let from = new_from.as_deref().unwrap_or(&from_account_code);
+            let to = new_to.as_deref().unwrap_or(&to_account_code);
+            // Moving must not change the currency the amounts are booked in
+            let currencies: Vec<(String, String)> = sqlx::query_as(sql_queries::ACCOUNT_CURRENCIES_FOR_CODES)
+                .bind(user_id)
+                .bind(from)
+                .bind(to)
+                .fetch_all(&mut *conn)
+                .await?;
+            if let Some((code, currency)) = currencies.iter().find(|(_, c)| *c != currency_code) {
+                return Err(TransactionError::ValidationError(format!(
+                    "Account {} uses {} but the transaction is booked in {}",
+                    code, currency, currency_code
+                )));
+            }
+            sqlx::query(sql_queries::TRANSACTION_BULK_SET_ACCOUNTS)
+                .bind(from)
+                .bind(to)
+                .bind(transaction_id)
+                .bind(user_id)
+                .execute(&mut *conn)
+                .await?
+        }
+        BulkTransactionChange::SetMemo { memo } => {
+            // Memos are shared by text, so every header ends up on one row
+            let memo_id: Option<i64> = match memo {
+                Some(text) => {
+                    let existing: Option<i64> = sqlx::query_scalar(sql_queries::MEMO_FIND_BY_TEXT)
+                        .bind(user_id)
+                        .bind(text)
+                        .fetch_optional(&mut *conn)
+                        .await?;
+                    match existing {
+                        Some(id) => Some(id),
+                        None => Some(
+                            sqlx::query(sql_queries::MEMO_INSERT)
+                                .bind(user_id)
+                                .bind(text)
+                                .execute(&mut *conn)
+                                .await?
+                                .last_insert_rowid(),
+                        ),
+                    }
+                }
+                None => None,
+            };
+            let result = sqlx::query(sql_queries::TRANSACTION_BULK_SET_MEMO)
+                .bind(memo_id)
+                .bind(transaction_id)
+                .bind(user_id)
+                .execute(&mut *conn)
+                .await?;
+            // Drop the memo this header let go of once nothing else uses it,
+            // as the single-row header update does
+            if let Some(old_memo_id) = old_memo_id.filter(|id| Some(*id) != memo_id) {
+                if TransactionService::memo_usage_count(conn, old_memo_id).await? == 0 {
+                    sqlx::query(sql_queries::MEMO_DELETE)
+                        .bind(old_memo_id)
+                        .execute(&mut *conn)
+                        .await?;
+                }
+            }
+            result
+        }
+        BulkTransactionChange::Delete => {
+            sqlx::query(sql_queries::TRANSACTION_DELETE)
+                .bind(user_id)
+                .bind(transaction_id)
+                .execute(&mut *conn)
+                .await?
+        }
+        BulkTransactionChange::ConfirmScheduled => {
+            let result = sqlx::query(sql_queries::TRANSACTION_HEADER_CONFIRM_SCHEDULED)
+                .bind(transaction_id)
+                .bind(user_id)
+                .execute(&mut *conn)
+                .await?;
+            if result.rows_affected() == 0 {
+                return Err(TransactionError::ValidationError(
+                    "Transaction is not scheduled".to_string(),
+                ));
+            }
+            result
+        }
+    };
+
+    if result.rows_affected() == 0 {
+        return Err(TransactionError::NotFound);
+    }
+    for (key, before) in keys.iter().zip(befores) {
+        audit::record(conn, user_id, key, before).await?;
+    }
+    Ok(())
+}
+
 impl TransactionService {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
@@ -496,6 +1485,16 @@ impl TransactionService {
             ));
         }
 
+        let currency_code = header_currency(
+            &self.pool,
+            user_id,
+            &request.from_account_code,
+            &request.to_account_code,
+        )
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+
         // Save memo if provided
         let memo_id = if let Some(text) = &request.memo {
             if !text.trim().is_empty() {
@@ -503,7 +1502,7 @@ impl TransactionService {
                 let result = sqlx::query(sql_queries::MEMO_INSERT)
                     .bind(user_id)
                     .bind(text)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
                 Some(result.last_insert_rowid())
             } else {
@@ -526,10 +1525,14 @@ impl TransactionService {
             .bind(request.tax_included_type)
             .bind(memo_id)
             .bind(request.is_scheduled.unwrap_or(0))
-            .execute(&self.pool)
+            .bind(&currency_code)
+            .execute(&mut *tx)
             .await?;
+        let transaction_id = result.last_insert_rowid();
+        audit::record(&mut tx, user_id, &header_audit_key(transaction_id), None).await?;
+        tx.commit().await?;
 
-        Ok(result.last_insert_rowid())
+        Ok(transaction_id)
     }
 
     /// Get transaction header by ID
@@ -584,120 +1587,20 @@ impl TransactionService {
         }
     }
 
-    /// Get transactions with filters and pagination
+    /// Get one page of the transactions matching `query`
     pub async fn get_transactions(
-        &self,
-        user_id: i64,
-        start_date: Option<&str>,
-        end_date: Option<&str>,
-        category1_code: Option<&str>,
-        category2_code: Option<&str>,
-        category3_code: Option<&str>,
-        min_amount: Option<i64>,
-        max_amount: Option<i64>,
-        keyword: Option<&str>,
-        include_scheduled: bool,
-        page: i64,
-        per_page: i64,
-    ) -> Result<TransactionListResponse, TransactionError> {
-        // Clamp pagination input: per_page = 0 would divide by zero below and
-        // negative values would produce a negative OFFSET.
-        let page = page.max(1);
-        let per_page = per_page.clamp(1, MAX_PER_PAGE);
-
-        // Build WHERE clauses (with table alias 't.')
-        let mut where_clauses = vec!["t.USER_ID = ?".to_string()];
-        let mut params: Vec<String> = vec![user_id.to_string()];
-
-        // Exclude scheduled transactions by default. When the user opts in,
-        // every IS_SCHEDULED row is shown, including each occurrence of a
-        // recurring rule — the date filter (TRANSACTION_DATE BETWEEN ...)
-        // is what scopes the visible window. Group membership of recurring
-        // occurrences is preserved through RULE_ID, not through any
-        // representative-row trick that would have to be maintained on
-        // every confirm/delete.
-        if !include_scheduled {
-            where_clauses.push("t.IS_SCHEDULED = 0".to_string());
-        }
-
-        if let Some(start) = start_date {
-            where_clauses.push("t.TRANSACTION_DATE >= ?".to_string());
-            params.push(start.to_string());
-        }
-
-        if let Some(end) = end_date {
-            where_clauses.push("t.TRANSACTION_DATE <= ?".to_string());
-            // TRANSACTION_DATE is stored as 'YYYY-MM-DD HH:MM:SS' but the UI's
-            // <input type="date"> sends bare 'YYYY-MM-DD', which under string
-            // comparison drops every same-day timestamp. Anchor to end-of-day
-            // so the boundary day is included.
-            let normalized = if end.len() == 10 {
-                format!("{} 23:59:59", end)
-            } else {
-                end.to_string()
-            };
-            params.push(normalized);
-        }
-
-        if let Some(cat1) = category1_code {
-            if !cat1.is_empty() {
-                where_clauses.push("t.CATEGORY1_CODE = ?".to_string());
-                params.push(cat1.to_string());
-            }
-        }
-
-        // CATEGORY2_CODE / CATEGORY3_CODE live on TRANSACTIONS_DETAIL, so filter
-        // via EXISTS to keep the header row count stable (no DISTINCT needed).
-        if let Some(cat2) = category2_code {
-            if !cat2.is_empty() {
-                where_clauses.push(
-                    "EXISTS (SELECT 1 FROM TRANSACTIONS_DETAIL td \
-                     WHERE td.USER_ID = t.USER_ID \
-                       AND td.TRANSACTION_ID = t.TRANSACTION_ID \
-                       AND td.CATEGORY2_CODE = ?)"
-                        .to_string(),
-                );
-                params.push(cat2.to_string());
-            }
-        }
-
-        if let Some(cat3) = category3_code {
-            if !cat3.is_empty() {
-                where_clauses.push(
-                    "EXISTS (SELECT 1 FROM TRANSACTIONS_DETAIL td \
-                     WHERE td.USER_ID = t.USER_ID \
-                       AND td.TRANSACTION_ID = t.TRANSACTION_ID \
-                       AND td.CATEGORY3_CODE = ?)"
-                        .to_string(),
-                );
-                params.push(cat3.to_string());
-            }
-        }
-
-        if let Some(min) = min_amount {
-            where_clauses.push("t.TOTAL_AMOUNT >= ?".to_string());
-            params.push(min.to_string());
-        }
-
-        if let Some(max) = max_amount {
-            where_clauses.push("t.TOTAL_AMOUNT <= ?".to_string());
-            params.push(max.to_string());
-        }
-
-        // Keyword: substring match against memo text on the header row and on
-        // any detail row of the same header. MEMO_TEXT is user-supplied so we
-        // escape LIKE metacharacters and bind the same pattern twice.
-        if let Some(kw) = keyword {
-            let kw = kw.trim();
-            if !kw.is_empty() {
-                let pattern = format!("%{}%", escape_like_pattern(kw));
-                where_clauses.push(sql_queries::TRANSACTION_KEYWORD_MEMO_FILTER.to_string());
-                params.push(pattern.clone());
-                params.push(pattern);
-            }
-        }
+        &self,
+        user_id: i64,
+        query: &TransactionQuery,
+        page: i64,
+        per_page: i64,
+    ) -> Result<TransactionListResponse, TransactionError> {
+        // Clamp pagination input: per_page = 0 would divide by zero below and
+        // negative values would produce a negative OFFSET.
+        let page = page.max(1);
+        let per_page = per_page.clamp(1, MAX_PER_PAGE);
 
-        let where_clause = where_clauses.join(" AND ");
+        let (where_clause, params) = build_transaction_where(user_id, query);
 
         // Get total count
         let count_query = format!("{}{}", sql_queries::TRANSACTION_COUNT_BASE, where_clause);
@@ -715,14 +1618,14 @@ impl TransactionService {
         let total_pages = (total_count + per_page - 1) / per_page;
 
         // Get transactions
-        let query = format!(
+        let list_query = format!(
             "{}{}{} LIMIT ? OFFSET ?",
             sql_queries::TRANSACTION_LIST_BASE,
             where_clause,
-            sql_queries::TRANSACTION_LIST_ORDER
+            query.sort.order_by_sql()
         );
         
-        let mut stmt = sqlx::query_as::<_, Transaction>(&query);
+        let mut stmt = sqlx::query_as::<_, Transaction>(&list_query);
         for param in &params {
             stmt = stmt.bind(param);
         }
@@ -745,42 +1648,220 @@ impl TransactionService {
         user_id: i64,
         transaction_id: i64,
     ) -> Result<(), TransactionError> {
+        let mut tx = self.pool.begin().await?;
+        ensure_not_reconciled(&mut tx, user_id, transaction_id).await?;
+        let key = header_audit_key(transaction_id);
+        let before = audit::snapshot(&mut tx, &key).await?;
         let result = sqlx::query(sql_queries::TRANSACTION_DELETE)
             .bind(user_id)
             .bind(transaction_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(TransactionError::NotFound);
         }
 
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
         Ok(())
     }
 
-    /// Confirm a scheduled transaction (set IS_SCHEDULED from 1 to 0)
+    /// Confirm a scheduled transaction (set IS_SCHEDULED from 1 to 0).
+    ///
+    /// With an `adjustment` the estimate is replaced by the actual amount in
+    /// the same SQL transaction: TOTAL_AMOUNT is set, the detail lines are
+    /// replaced (or the single line rebooked) and the lines must add up to
+    /// the new total under the header's tax settings.
     pub async fn confirm_scheduled_transaction(
         &self,
         user_id: i64,
         transaction_id: i64,
+        adjustment: Option<ConfirmScheduledAdjustment>,
     ) -> Result<(), TransactionError> {
+        // Replacement lines are checked, and their split accounts resolved,
+        // before any write, as `add_transaction_detail` does.
+        let mut replacement_lines = None;
+        if let Some(adjustment) = &adjustment {
+            if adjustment.total_amount < 0 || adjustment.total_amount > 999_999_999 {
+                return Err(TransactionError::ValidationError(
+                    "Amount must be between 0 and 999,999,999".to_string(),
+                ));
+            }
+            if let Some(details) = &adjustment.details {
+                if details.is_empty() {
+                    return Err(TransactionError::ValidationError(
+                        "At least one detail line is required".to_string(),
+                    ));
+                }
+                let mut lines = Vec::with_capacity(details.len());
+                for detail in details {
+                    validate_detail_request(detail)?;
+                    if let Some(memo) = &detail.memo {
+                        validate_memo_length(memo.trim())?;
+                    }
+                    let accounts = split_line_accounts(
+                        &self.pool,
+                        user_id,
+                        transaction_id,
+                        detail.from_account_code.as_deref(),
+                        detail.to_account_code.as_deref(),
+                    )
+                    .await?;
+                    lines.push((detail, accounts));
+                }
+                replacement_lines = Some(lines);
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let key = header_audit_key(transaction_id);
+        let before = audit::snapshot(&mut tx, &key).await?;
+
+        if let Some(adjustment) = &adjustment {
+            let header = sqlx::query(sql_queries::TRANSACTION_HEADER_GET_SCHEDULED_FOR_CONFIRM)
+                .bind(transaction_id)
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or(TransactionError::NotFound)?;
+            if header.get::<i64, _>("RECONCILE_STATUS") == consts::RECONCILE_STATUS_RECONCILED {
+                return Err(TransactionError::Locked);
+            }
+
+            let existing = sqlx::query(sql_queries::TRANSACTION_DETAIL_LINES_BY_TRANSACTION)
+                .bind(transaction_id)
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+            match &replacement_lines {
+                Some(lines) => {
+                    for row in &existing {
+                        let detail_id: i64 = row.get("DETAIL_ID");
+                        let detail_key = detail_audit_key(detail_id);
+                        let detail_before = audit::snapshot(&mut tx, &detail_key).await?;
+                        sqlx::query(sql_queries::TRANSACTION_DETAIL_DELETE_BY_ID)
+                            .bind(detail_id)
+                            .bind(user_id)
+                            .execute(&mut *tx)
+                            .await?;
+                        if let Some(memo_id) = row.get::<Option<i64>, _>("MEMO_ID") {
+                            if Self::memo_usage_count(&mut tx, memo_id).await? == 0 {
+                                sqlx::query(sql_queries::MEMO_DELETE)
+                                    .bind(memo_id)
+                                    .execute(&mut *tx)
+                                    .await?;
+                            }
+                        }
+                        audit::record(&mut tx, user_id, &detail_key, detail_before).await?;
+                    }
+                    for (detail, (from_account_code, to_account_code)) in lines {
+                        let memo_id =
+                            Self::get_or_create_memo_id(&mut tx, user_id, detail.memo.as_deref()).await?;
+                        let result = sqlx::query(sql_queries::TRANSACTION_DETAIL_INSERT_FULL)
+                            .bind(transaction_id)
+                            .bind(user_id)
+                            .bind(&detail.category1_code)
+                            .bind(&detail.category2_code)
+                            .bind(&detail.category3_code)
+                            .bind(&detail.item_name)
+                            .bind(detail.amount)
+                            .bind(detail.tax_amount)
+                            .bind(detail.tax_rate)
+                            .bind(detail.amount_including_tax)
+                            .bind(detail.product_id)
+                            .bind(memo_id)
+                            .bind(from_account_code)
+                            .bind(to_account_code)
+                            .execute(&mut *tx)
+                            .await?;
+                        let detail_key = detail_audit_key(result.last_insert_rowid());
+                        audit::record(&mut tx, user_id, &detail_key, None).await?;
+                    }
+                }
+                None => match existing.as_slice() {
+                    [] => {}
+                    [line] => {
+                        let detail_id: i64 = line.get("DETAIL_ID");
+                        let tax_rate: i64 = line.get("TAX_RATE");
+                        let total = adjustment.total_amount;
+                        let tax_amount = total - total * 100 / (100 + tax_rate);
+                        let detail_key = detail_audit_key(detail_id);
+                        let detail_before = audit::snapshot(&mut tx, &detail_key).await?;
+                        sqlx::query(sql_queries::TRANSACTION_DETAIL_SET_INCLUDED_AMOUNT)
+                            .bind(total)
+                            .bind(total)
+                            .bind(tax_amount)
+                            .bind(detail_id)
+                            .bind(user_id)
+                            .execute(&mut *tx)
+                            .await?;
+                        audit::record(&mut tx, user_id, &detail_key, detail_before).await?;
+                    }
+                    _ => {
+                        return Err(TransactionError::ValidationError(
+                            "Detail lines are required to confirm a transaction with several lines at its actual amount".to_string(),
+                        ));
+                    }
+                },
+            }
+
+            sqlx::query(sql_queries::TRANSACTION_HEADER_SET_TOTAL_AMOUNT)
+                .bind(adjustment.total_amount)
+                .bind(transaction_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+
+            let details: Vec<DetailForRecalc> =
+                sqlx::query(sql_queries::TRANSACTION_DETAIL_GET_FOR_RECALC)
+                    .bind(transaction_id)
+                    .bind(user_id)
+                    .fetch_all(&mut *tx)
+                    .await?
+                    .iter()
+                    .map(|r| DetailForRecalc {
+                        amount: r.get("AMOUNT"),
+                        amount_including_tax: r.get("AMOUNT_INCLUDING_TAX"),
+                        tax_rate: r.get("TAX_RATE"),
+                        leg: r.get("LEG"),
+                    })
+                    .collect();
+            if !details.is_empty() {
+                let expected = calculate_recommended_total_with_settings(
+                    &details,
+                    header.get("TAX_ROUNDING_TYPE"),
+                    header.get("TAX_INCLUDED_TYPE"),
+                );
+                if expected != adjustment.total_amount {
+                    return Err(TransactionError::ValidationError(format!(
+                        "Actual amount {} does not match the detail lines (expected {})",
+                        adjustment.total_amount, expected
+                    )));
+                }
+            }
+        }
+
         let result = sqlx::query(sql_queries::TRANSACTION_HEADER_CONFIRM_SCHEDULED)
             .bind(transaction_id)
             .bind(user_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(TransactionError::NotFound);
         }
 
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
         Ok(())
     }
 
     /// Helper function to get or create memo_id for memo text
     /// Returns memo_id if memo text is provided, None if empty
     async fn get_or_create_memo_id(
-        &self,
+        conn: &mut SqliteConnection,
         user_id: i64,
         memo_text: Option<&str>,
     ) -> Result<Option<i64>, TransactionError> {
@@ -800,7 +1881,7 @@ impl TransactionService {
         let existing_memo = sqlx::query(sql_queries::MEMO_FIND_BY_TEXT)
             .bind(user_id)
             .bind(memo_text)
-            .fetch_optional(&self.pool)
+            .fetch_optional(&mut *conn)
             .await?;
 
         if let Some(row) = existing_memo {
@@ -810,7 +1891,7 @@ impl TransactionService {
             let result = sqlx::query(sql_queries::MEMO_INSERT)
                 .bind(user_id)
                 .bind(memo_text)
-                .execute(&self.pool)
+                .execute(&mut *conn)
                 .await?;
             Ok(Some(result.last_insert_rowid()))
         }
@@ -821,20 +1902,20 @@ impl TransactionService {
     /// A memo is considered shared when this exceeds the caller's own
     /// reference (typically shared > 1 for updates, or leftover > 0 after
     /// the caller already released its reference).
-    async fn memo_usage_count(&self, memo_id: i64) -> Result<i64, TransactionError> {
+    async fn memo_usage_count(conn: &mut SqliteConnection, memo_id: i64) -> Result<i64, TransactionError> {
         let count: i64 = sqlx::query_scalar(sql_queries::MEMO_COUNT_USAGE)
             .bind(memo_id)
             .bind(memo_id)
             .bind(memo_id)
             .bind(memo_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await?;
         Ok(count)
     }
 
     /// Helper function to get memo_id for update (handles shared memo_id case)
     async fn get_memo_id_for_update(
-        &self,
+        conn: &mut SqliteConnection,
         user_id: i64,
         memo_text: Option<&str>,
         current_memo_id: Option<i64>,
@@ -853,7 +1934,7 @@ impl TransactionService {
 
         // Check if current memo_id is shared with other transactions
         let is_shared = if let Some(memo_id) = current_memo_id {
-            self.memo_usage_count(memo_id).await? > 1
+            Self::memo_usage_count(conn, memo_id).await? > 1
         } else {
             false
         };
@@ -864,7 +1945,7 @@ impl TransactionService {
             let existing_memo = sqlx::query(sql_queries::MEMO_FIND_BY_TEXT)
                 .bind(user_id)
                 .bind(memo_text)
-                .fetch_optional(&self.pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
             if let Some(row) = existing_memo {
@@ -874,13 +1955,13 @@ impl TransactionService {
                 let result = sqlx::query(sql_queries::MEMO_INSERT)
                     .bind(user_id)
                     .bind(memo_text)
-                    .execute(&self.pool)
+                    .execute(&mut *conn)
                     .await?;
                 Ok(Some(result.last_insert_rowid()))
             }
         } else {
             // Current memo_id is not shared - can reuse or create new
-            self.get_or_create_memo_id(user_id, Some(memo_text)).await
+            Self::get_or_create_memo_id(conn, user_id, Some(memo_text)).await
         }
     }
 
@@ -918,10 +1999,27 @@ impl TransactionService {
         // Get current transaction header to check current memo_id
         let current_header = self.get_transaction_header(user_id, transaction_id).await?;
 
+        let currency_code = header_currency(
+            &self.pool,
+            user_id,
+            &request.from_account_code,
+            &request.to_account_code,
+        )
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        ensure_not_reconciled(&mut tx, user_id, transaction_id).await?;
+        let key = header_audit_key(transaction_id);
+        let before = audit::snapshot(&mut tx, &key).await?;
+
         // Get or create memo_id (handles shared memo_id case)
-        let memo_id = self
-            .get_memo_id_for_update(user_id, request.memo.as_deref(), current_header.memo_id)
-            .await?;
+        let memo_id = Self::get_memo_id_for_update(
+            &mut tx,
+            user_id,
+            request.memo.as_deref(),
+            current_header.memo_id,
+        )
+        .await?;
 
         // Update transaction header
         let result = sqlx::query(sql_queries::TRANSACTION_HEADER_UPDATE)
@@ -934,15 +2032,18 @@ impl TransactionService {
             .bind(request.tax_rounding_type)
             .bind(request.tax_included_type)
             .bind(memo_id)
+            .bind(&currency_code)
             .bind(transaction_id)
             .bind(user_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(TransactionError::NotFound);
         }
 
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -959,6 +2060,121 @@ impl TransactionService {
         Ok(())
     }
 
+    /// Check and normalise the parts of a bulk change that do not depend on
+    /// the individual transaction.
+    async fn prepare_bulk_change(
+        &self,
+        user_id: i64,
+        change: &BulkTransactionChange,
+    ) -> Result<BulkTransactionChange, TransactionError> {
+        let normalize = |code: &Option<String>, upper: bool| {
+            code.as_deref()
+                .map(|c| if upper { c.trim().to_uppercase() } else { c.trim().to_string() })
+                .filter(|c| !c.is_empty())
+        };
+        Ok(match change {
+            BulkTransactionChange::SetShop { shop_id: Some(shop_id) } => {
+                let shop = sqlx::query(sql_queries::SHOP_GET_BY_ID)
+                    .bind(user_id)
+                    .bind(shop_id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+                if shop.is_none() {
+                    return Err(TransactionError::ValidationError(format!("Shop {} does not exist", shop_id)));
+                }
+                change.clone()
+            }
+            BulkTransactionChange::SetCategories { category2_code, category3_code } => {
+                let category2_code = normalize(category2_code, false);
+                let category3_code = normalize(category3_code, false);
+                if category2_code.is_none() && category3_code.is_some() {
+                    return Err(TransactionError::ValidationError(
+                        "A sub-category needs its parent category".to_string(),
+                    ));
+                }
+                BulkTransactionChange::SetCategories { category2_code, category3_code }
+            }
+            BulkTransactionChange::MoveAccount { from_account_code, to_account_code } => {
+                let from = normalize(from_account_code, true);
+                let to = normalize(to_account_code, true);
+                if from.is_none() && to.is_none() {
+                    return Err(TransactionError::ValidationError(
+                        "Choose an account to move to".to_string(),
+                    ));
+                }
+                let known: Vec<String> = sqlx::query_scalar(sql_queries::TRANSACTION_DETAIL_LINE_ACCOUNTS)
+                    .bind(user_id)
+                    .bind(&from)
+                    .bind(&to)
+                    .fetch_all(&self.pool)
+                    .await?;
+                for code in from.iter().chain(to.iter()) {
+                    if !known.contains(code) {
+                        return Err(TransactionError::ValidationError(format!("Account {} does not exist", code)));
+                    }
+                }
+                BulkTransactionChange::MoveAccount { from_account_code: from, to_account_code: to }
+            }
+            BulkTransactionChange::SetMemo { memo } => {
+                let memo = normalize(memo, false);
+                if let Some(text) = &memo {
+                    validate_memo_length(text)?;
+                }
+                BulkTransactionChange::SetMemo { memo }
+            }
+            _ => change.clone(),
+        })
+    }
+
+    /// Apply `change` to every transaction in `transaction_ids` inside one
+    /// database transaction. Each row runs under its own savepoint, so a row
+    /// that fails (not found, reconciled, invalid for that row) is rolled
+    /// back alone and reported in its result while the others are kept.
+    /// Problems with the change itself (unknown shop or account, memo too
+    /// long) fail the whole call before anything is written.
+    pub async fn bulk_update_transactions(
+        &self,
+        user_id: i64,
+        transaction_ids: &[i64],
+        change: &BulkTransactionChange,
+    ) -> Result<Vec<BulkTransactionResult>, TransactionError> {
+        let mut ids: Vec<i64> = Vec::with_capacity(transaction_ids.len());
+        for &id in transaction_ids {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        if ids.is_empty() {
+            return Err(TransactionError::ValidationError("Select at least one transaction".to_string()));
+        }
+        if ids.len() > MAX_BULK_TRANSACTIONS {
+            return Err(TransactionError::ValidationError(format!(
+                "At most {} transactions can be changed at once",
+                MAX_BULK_TRANSACTIONS
+            )));
+        }
+        let change = self.prepare_bulk_change(user_id, change).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(ids.len());
+        for transaction_id in ids {
+            let mut savepoint = tx.begin().await?;
+            let error = match apply_bulk_change(&mut savepoint, user_id, transaction_id, &change).await {
+                Ok(()) => {
+                    savepoint.commit().await?;
+                    None
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    Some(ApiError::from(e))
+                }
+            };
+            results.push(BulkTransactionResult { transaction_id, error });
+        }
+        tx.commit().await?;
+        Ok(results)
+    }
+
     /// Get transaction header with related information
     pub async fn get_transaction_header_with_info(
         &self,
@@ -984,6 +2200,7 @@ impl TransactionService {
                 from_account_name: row.get("FROM_ACCOUNT_NAME"),
                 to_account_name: row.get("TO_ACCOUNT_NAME"),
                 total_amount: row.get("TOTAL_AMOUNT"),
+                currency_code: row.get("CURRENCY_CODE"),
                 tax_rounding_type: row.get("TAX_ROUNDING_TYPE"),
                 tax_included_type: row.get("TAX_INCLUDED_TYPE"),
                 memo_id: row.get("MEMO_ID"),
@@ -1031,6 +2248,8 @@ impl TransactionService {
                 manufacturer_name: row.get("MANUFACTURER_NAME"),
                 memo_id: row.get("MEMO_ID"),
                 memo_text: row.get("MEMO_TEXT"),
+                from_account_code: row.get("FROM_ACCOUNT_CODE"),
+                to_account_code: row.get("TO_ACCOUNT_CODE"),
                 entry_dt: row.get("ENTRY_DT"),
                 update_dt: row.get("UPDATE_DT"),
             })
@@ -1046,35 +2265,7 @@ impl TransactionService {
         transaction_id: i64,
         request: SaveTransactionDetailRequest,
     ) -> Result<i64, TransactionError> {
-        // Validate item name
-        if request.item_name.trim().is_empty() {
-            return Err(TransactionError::ValidationError(
-                "Item name is required".to_string(),
-            ));
-        }
-
-        validate_item_name_length(&request.item_name)?;
-
-        // Validate amount
-        if request.amount < 0 || request.amount > 999_999_999 {
-            return Err(TransactionError::ValidationError(
-                "Amount must be between 0 and 999,999,999".to_string(),
-            ));
-        }
-
-        // Validate tax rate
-        if request.tax_rate < 0 || request.tax_rate > 100 {
-            return Err(TransactionError::ValidationError(
-                "Tax rate must be between 0 and 100".to_string(),
-            ));
-        }
-
-        // Validate tax amount
-        if request.tax_amount < 0 {
-            return Err(TransactionError::ValidationError(
-                "Tax amount cannot be negative".to_string(),
-            ));
-        }
+        validate_detail_request(&request)?;
 
         // Verify the parent header exists AND belongs to this user. The FK
         // on TRANSACTIONS_DETAIL.TRANSACTION_ID only checks that some header
@@ -1097,6 +2288,18 @@ impl TransactionService {
             return Err(TransactionError::NotFound);
         }
 
+        let (from_account_code, to_account_code) = split_line_accounts(
+            &self.pool,
+            user_id,
+            transaction_id,
+            request.from_account_code.as_deref(),
+            request.to_account_code.as_deref(),
+        )
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        ensure_not_reconciled(&mut tx, user_id, transaction_id).await?;
+
         // Save memo if provided
         let memo_id = if let Some(text) = &request.memo {
             if !text.trim().is_empty() {
@@ -1104,7 +2307,7 @@ impl TransactionService {
                 let result = sqlx::query(sql_queries::MEMO_INSERT)
                     .bind(user_id)
                     .bind(text)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
                 Some(result.last_insert_rowid())
             } else {
@@ -1128,10 +2331,15 @@ impl TransactionService {
             .bind(request.amount_including_tax)
             .bind(request.product_id)
             .bind(memo_id)
-            .execute(&self.pool)
+            .bind(&from_account_code)
+            .bind(&to_account_code)
+            .execute(&mut *tx)
             .await?;
+        let detail_id = result.last_insert_rowid();
+        audit::record(&mut tx, user_id, &detail_audit_key(detail_id), None).await?;
+        tx.commit().await?;
 
-        Ok(result.last_insert_rowid())
+        Ok(detail_id)
     }
 
     /// Update a transaction detail
@@ -1141,35 +2349,7 @@ impl TransactionService {
         detail_id: i64,
         request: SaveTransactionDetailRequest,
     ) -> Result<(), TransactionError> {
-        // Validate item name
-        if request.item_name.trim().is_empty() {
-            return Err(TransactionError::ValidationError(
-                "Item name is required".to_string(),
-            ));
-        }
-
-        validate_item_name_length(&request.item_name)?;
-
-        // Validate amount
-        if request.amount < 0 || request.amount > 999_999_999 {
-            return Err(TransactionError::ValidationError(
-                "Amount must be between 0 and 999,999,999".to_string(),
-            ));
-        }
-
-        // Validate tax rate
-        if request.tax_rate < 0 || request.tax_rate > 100 {
-            return Err(TransactionError::ValidationError(
-                "Tax rate must be between 0 and 100".to_string(),
-            ));
-        }
-
-        // Validate tax amount
-        if request.tax_amount < 0 {
-            return Err(TransactionError::ValidationError(
-                "Tax amount cannot be negative".to_string(),
-            ));
-        }
+        validate_detail_request(&request)?;
 
         // Get existing detail to check memo_id
         let existing: Option<TransactionDetail> = sqlx::query_as(
@@ -1182,6 +2362,20 @@ impl TransactionService {
 
         let existing_detail = existing.ok_or(TransactionError::NotFound)?;
 
+        let (from_account_code, to_account_code) = split_line_accounts(
+            &self.pool,
+            user_id,
+            existing_detail.transaction_id,
+            request.from_account_code.as_deref(),
+            request.to_account_code.as_deref(),
+        )
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        ensure_not_reconciled(&mut tx, user_id, existing_detail.transaction_id).await?;
+        let key = detail_audit_key(detail_id);
+        let before = audit::snapshot(&mut tx, &key).await?;
+
         // Handle memo update. The old code mutated / deleted the referenced
         // memo row directly, which corrupts any header or sibling detail that
         // shared the same MEMO_ID via MEMO_FIND_BY_TEXT reuse. Gate every
@@ -1197,24 +2391,29 @@ impl TransactionService {
                 validate_memo_length(text)?;
 
                 let resolved = if let Some(old_memo_id) = existing_detail.memo_id {
-                    let shared = self.memo_usage_count(old_memo_id).await? > 1;
+                    let shared = Self::memo_usage_count(&mut tx, old_memo_id).await? > 1;
                     if shared {
                         // Old memo has other references — must not mutate it.
                         // Point this detail at a fresh/reused memo instead.
-                        self.get_or_create_memo_id(user_id, Some(text)).await?
+                        Self::get_or_create_memo_id(&mut tx, user_id, Some(text)).await?
                     } else {
                         // Only this detail uses the old memo — safe in place.
+                        // The detail row itself does not change, so the
+                        // memo gets its own audit entry.
+                        let memo_key = AuditKey::new("MEMOS", "MEMO_ID", old_memo_id);
+                        let memo_before = audit::snapshot(&mut tx, &memo_key).await?;
                         sqlx::query(sql_queries::MEMO_UPDATE)
                             .bind(text)
                             .bind(old_memo_id)
-                            .execute(&self.pool)
+                            .execute(&mut *tx)
                             .await?;
+                        audit::record(&mut tx, user_id, &memo_key, memo_before).await?;
                         Some(old_memo_id)
                     }
                 } else {
                     // No prior memo — find-or-create to avoid duplicate rows
                     // when the same text already lives in MEMOS.
-                    self.get_or_create_memo_id(user_id, Some(text)).await?
+                    Self::get_or_create_memo_id(&mut tx, user_id, Some(text)).await?
                 };
                 (resolved, None)
             } else {
@@ -1238,9 +2437,11 @@ impl TransactionService {
             .bind(request.amount_including_tax)
             .bind(request.product_id)
             .bind(memo_id)
+            .bind(&from_account_code)
+            .bind(&to_account_code)
             .bind(detail_id)
             .bind(user_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
@@ -1251,14 +2452,16 @@ impl TransactionService {
         // clean up the old memo row if the user cleared the memo text and
         // nothing else still points at it.
         if let Some(old_memo_id) = deferred_memo_delete {
-            if self.memo_usage_count(old_memo_id).await? == 0 {
+            if Self::memo_usage_count(&mut tx, old_memo_id).await? == 0 {
                 sqlx::query(sql_queries::MEMO_DELETE)
                     .bind(old_memo_id)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
             }
         }
 
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -1268,25 +2471,31 @@ impl TransactionService {
         user_id: i64,
         detail_id: i64,
     ) -> Result<(), TransactionError> {
+        let mut tx = self.pool.begin().await?;
+
         // Get detail to check if it exists and get memo_id
         let detail: Option<TransactionDetail> = sqlx::query_as(
             sql_queries::TRANSACTION_DETAIL_GET_BY_ID
         )
         .bind(detail_id)
         .bind(user_id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
         let detail = detail.ok_or(TransactionError::NotFound)?;
+        ensure_not_reconciled(&mut tx, user_id, detail.transaction_id).await?;
 
         // Save memo_id for later deletion
         let memo_id = detail.memo_id;
 
+        let key = detail_audit_key(detail_id);
+        let before = audit::snapshot(&mut tx, &key).await?;
+
         // Delete detail first (to release foreign key constraint)
         let result = sqlx::query(sql_queries::TRANSACTION_DETAIL_DELETE_BY_ID)
             .bind(detail_id)
             .bind(user_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
@@ -1298,14 +2507,16 @@ impl TransactionService {
         // MEMO_ID (via MEMO_FIND_BY_TEXT reuse); dropping it unconditionally
         // would leave those references dangling.
         if let Some(memo_id) = memo_id {
-            if self.memo_usage_count(memo_id).await? == 0 {
+            if Self::memo_usage_count(&mut tx, memo_id).await? == 0 {
                 sqlx::query(sql_queries::MEMO_DELETE)
                     .bind(memo_id)
-                    .execute(&self.pool)
+                    .execute(&mut *tx)
                     .await?;
             }
         }
 
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -1338,6 +2549,7 @@ impl TransactionService {
                 amount: r.get("AMOUNT"),
                 amount_including_tax: r.get("AMOUNT_INCLUDING_TAX"),
                 tax_rate: r.get("TAX_RATE"),
+                leg: r.get("LEG"),
             })
             .collect();
 
@@ -1381,9 +2593,11 @@ impl TransactionService {
 
         // Load every header for this user up front. Holding them in memory
         // keeps the hot loop below from interleaving SELECT cursors with
-        // UPDATE statements on the same transaction.
+        // UPDATE statements on the same transaction. Reconciled headers are
+        // locked; they are left alone and counted as skipped.
         let header_rows = sqlx::query(
-            "SELECT TRANSACTION_ID, TRANSACTION_DATE, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, TOTAL_AMOUNT \
+            "SELECT TRANSACTION_ID, TRANSACTION_DATE, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, TOTAL_AMOUNT, \
+                    RECONCILE_STATUS \
              FROM TRANSACTIONS_HEADER WHERE USER_ID = ? \
              ORDER BY TRANSACTION_DATE, TRANSACTION_ID",
         )
@@ -1411,6 +2625,7 @@ impl TransactionService {
                 amount: row.get("AMOUNT"),
                 amount_including_tax: row.get("AMOUNT_INCLUDING_TAX"),
                 tax_rate: row.get("TAX_RATE"),
+                leg: row.get("LEG"),
             });
         }
 
@@ -1427,6 +2642,12 @@ impl TransactionService {
             let included_before: i64 = header_row.get("TAX_INCLUDED_TYPE");
             let total_before: i64 = header_row.get("TOTAL_AMOUNT");
 
+            if header_row.get::<i64, _>("RECONCILE_STATUS") == consts::RECONCILE_STATUS_RECONCILED {
+                details_by_txn.remove(&txn_id);
+                skipped += 1;
+                continue;
+            }
+
             // Take the pre-loaded details for this header (removing to
             // release memory as we go); a header with no detail rows
             // yields an empty Vec, matching the prior fetch behaviour.
@@ -1519,6 +2740,7 @@ impl TransactionService {
     /// deliberately touch *only* `TOTAL_AMOUNT` — leaving details, memos and
     /// the rest of the schema untouched — so a rollback cannot accidentally
     /// erase any data the user has entered since the recalculation ran.
+    /// Headers reconciled in the meantime are locked and keep their total.
     pub async fn restore_totals_from_backup(
         &self,
         user_id: i64,
@@ -1571,61 +2793,357 @@ impl TransactionService {
         );
         sqlx::query(&attach_sql).execute(&mut *conn).await?;
 
-        let result = sqlx::query(
-            "UPDATE TRANSACTIONS_HEADER \
-             SET TOTAL_AMOUNT = ( \
-                 SELECT b.TOTAL_AMOUNT FROM recalc_backup.TRANSACTIONS_HEADER b \
-                 WHERE b.TRANSACTION_ID = TRANSACTIONS_HEADER.TRANSACTION_ID \
-                   AND b.USER_ID = TRANSACTIONS_HEADER.USER_ID \
-             ), UPDATE_DT = datetime('now') \
-             WHERE USER_ID = ? \
-               AND EXISTS ( \
-                   SELECT 1 FROM recalc_backup.TRANSACTIONS_HEADER b \
-                   WHERE b.TRANSACTION_ID = TRANSACTIONS_HEADER.TRANSACTION_ID \
-                     AND b.USER_ID = TRANSACTIONS_HEADER.USER_ID \
-               )",
-        )
-        .bind(user_id)
-        .execute(&mut *conn)
-        .await?;
+        let result = sqlx::query(
+            "UPDATE TRANSACTIONS_HEADER \
+             SET TOTAL_AMOUNT = ( \
+                 SELECT b.TOTAL_AMOUNT FROM recalc_backup.TRANSACTIONS_HEADER b \
+                 WHERE b.TRANSACTION_ID = TRANSACTIONS_HEADER.TRANSACTION_ID \
+                   AND b.USER_ID = TRANSACTIONS_HEADER.USER_ID \
+             ), UPDATE_DT = datetime('now') \
+             WHERE USER_ID = ? AND RECONCILE_STATUS != ? \
+               AND EXISTS ( \
+                   SELECT 1 FROM recalc_backup.TRANSACTIONS_HEADER b \
+                   WHERE b.TRANSACTION_ID = TRANSACTIONS_HEADER.TRANSACTION_ID \
+                     AND b.USER_ID = TRANSACTIONS_HEADER.USER_ID \
+               )",
+        )
+        .bind(user_id)
+        .bind(consts::RECONCILE_STATUS_RECONCILED)
+        .execute(&mut *conn)
+        .await?;
+
+        let restored = result.rows_affected() as i64;
+
+        sqlx::query("DETACH DATABASE recalc_backup")
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(RestoreSummary { restored })
+    }
+
+    /// Set `TOTAL_AMOUNT` on a transaction header without touching any other
+    /// field. The frontend reaches for this after the user confirms the
+    /// "header total drifted from details" prompt.
+    pub async fn update_transaction_header_total(
+        &self,
+        user_id: i64,
+        transaction_id: i64,
+        new_total: i64,
+    ) -> Result<(), TransactionError> {
+        if new_total < 0 || new_total > 999_999_999 {
+            return Err(TransactionError::ValidationError(
+                "Amount must be between 0 and 999,999,999".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        ensure_not_reconciled(&mut tx, user_id, transaction_id).await?;
+        let key = header_audit_key(transaction_id);
+        let before = audit::snapshot(&mut tx, &key).await?;
+        let result = sqlx::query(sql_queries::TRANSACTION_HEADER_UPDATE_TOTAL_ONLY)
+            .bind(new_total)
+            .bind(transaction_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(TransactionError::NotFound);
+        }
+
+        audit::record(&mut tx, user_id, &key, before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Create or replace the CSV import profile for one of the user's
+    /// accounts. Both the statement account and the counter account must be
+    /// active accounts owned by the user.
+    pub async fn save_import_profile(
+        &self,
+        user_id: i64,
+        request: SaveImportProfileRequest,
+    ) -> Result<ImportProfile, TransactionError> {
+        let account_code = request.account_code.trim().to_uppercase();
+        let counter_account_code = request
+            .counter_account_code
+            .as_deref()
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| consts::ACCOUNT_CODE_NONE.to_string());
+        let profile_name = request.profile_name.trim();
+        let delimiter = request.delimiter.unwrap_or_else(|| ",".to_string());
+        let date_format = request.date_format.unwrap_or_else(|| "%Y/%m/%d".to_string());
+
+        if profile_name.is_empty() {
+            return Err(TransactionError::ValidationError(
+                "Profile name is required".to_string(),
+            ));
+        }
+        validation::validate_max_chars("Profile name", profile_name, consts::MAX_NAME_LEN)
+            .map_err(TransactionError::ValidationError)?;
+
+        if delimiter.chars().count() != 1 || delimiter == "\"" || delimiter == "\n" || delimiter == "\r" {
+            return Err(TransactionError::ValidationError(
+                "Delimiter must be a single character".to_string(),
+            ));
+        }
+        validate_date_format(&date_format)?;
+
+        if request.amount_column.is_none()
+            && request.withdrawal_column.is_none()
+            && request.deposit_column.is_none()
+        {
+            return Err(TransactionError::ValidationError(
+                "Either an amount column or a withdrawal/deposit column is required".to_string(),
+            ));
+        }
+        if request.amount_column.is_some()
+            && (request.withdrawal_column.is_some() || request.deposit_column.is_some())
+        {
+            return Err(TransactionError::ValidationError(
+                "Amount column cannot be combined with withdrawal/deposit columns".to_string(),
+            ));
+        }
+        let columns = [
+            Some(request.date_column),
+            Some(request.description_column),
+            request.amount_column,
+            request.withdrawal_column,
+            request.deposit_column,
+            request.memo_column,
+        ];
+        if columns.iter().flatten().any(|c| *c < 0) {
+            return Err(TransactionError::ValidationError(
+                "Column index cannot be negative".to_string(),
+            ));
+        }
+        if request.skip_rows.unwrap_or(1) < 0 {
+            return Err(TransactionError::ValidationError(
+                "Skip rows cannot be negative".to_string(),
+            ));
+        }
+
+        if counter_account_code == account_code {
+            return Err(TransactionError::ValidationError(
+                "Counter account must differ from the statement account".to_string(),
+            ));
+        }
+        for code in [&account_code, &counter_account_code] {
+            let exists: Option<i64> = sqlx::query_scalar(sql_queries::IMPORT_ACCOUNT_EXISTS_FOR_USER)
+                .bind(user_id)
+                .bind(code)
+                .fetch_optional(&self.pool)
+                .await?;
+            if exists.is_none() {
+                return Err(TransactionError::ValidationError(format!(
+                    "Account not found: {}",
+                    code
+                )));
+            }
+        }
+
+        sqlx::query(sql_queries::IMPORT_PROFILE_UPSERT)
+            .bind(user_id)
+            .bind(&account_code)
+            .bind(profile_name)
+            .bind(&delimiter)
+            .bind(request.skip_rows.unwrap_or(1))
+            .bind(request.date_column)
+            .bind(&date_format)
+            .bind(request.amount_column)
+            .bind(request.withdrawal_column)
+            .bind(request.deposit_column)
+            .bind(request.description_column)
+            .bind(request.memo_column)
+            .bind(request.negate_amount.unwrap_or(false) as i64)
+            .bind(&counter_account_code)
+            .execute(&self.pool)
+            .await?;
 
-        let restored = result.rows_affected() as i64;
+        self.get_import_profile(user_id, &account_code).await
+    }
 
-        sqlx::query("DETACH DATABASE recalc_backup")
-            .execute(&mut *conn)
+    /// Get the CSV import profile registered for `account_code`
+    pub async fn get_import_profile(
+        &self,
+        user_id: i64,
+        account_code: &str,
+    ) -> Result<ImportProfile, TransactionError> {
+        let profile = sqlx::query_as::<_, ImportProfile>(sql_queries::IMPORT_PROFILE_GET_BY_ACCOUNT)
+            .bind(user_id)
+            .bind(account_code.trim().to_uppercase())
+            .fetch_optional(&self.pool)
             .await?;
 
-        Ok(RestoreSummary { restored })
+        profile.ok_or(TransactionError::NotFound)
     }
 
-    /// Set `TOTAL_AMOUNT` on a transaction header without touching any other
-    /// field. The frontend reaches for this after the user confirms the
-    /// "header total drifted from details" prompt.
-    pub async fn update_transaction_header_total(
+    /// List every CSV import profile of the user, in account display order
+    pub async fn list_import_profiles(
         &self,
         user_id: i64,
-        transaction_id: i64,
-        new_total: i64,
-    ) -> Result<(), TransactionError> {
-        if new_total < 0 || new_total > 999_999_999 {
-            return Err(TransactionError::ValidationError(
-                "Amount must be between 0 and 999,999,999".to_string(),
-            ));
-        }
+    ) -> Result<Vec<ImportProfile>, TransactionError> {
+        let profiles = sqlx::query_as::<_, ImportProfile>(sql_queries::IMPORT_PROFILE_LIST_BY_USER)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(profiles)
+    }
 
-        let result = sqlx::query(sql_queries::TRANSACTION_HEADER_UPDATE_TOTAL_ONLY)
-            .bind(new_total)
-            .bind(transaction_id)
+    /// Delete the CSV import profile registered for `account_code`
+    pub async fn delete_import_profile(
+        &self,
+        user_id: i64,
+        account_code: &str,
+    ) -> Result<(), TransactionError> {
+        let result = sqlx::query(sql_queries::IMPORT_PROFILE_DELETE)
             .bind(user_id)
+            .bind(account_code.trim().to_uppercase())
             .execute(&self.pool)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(TransactionError::NotFound);
         }
-
         Ok(())
     }
+
+    /// Dry run of `import_csv_statement`: parse `csv_text` with the
+    /// account's profile and report what each record would become, which
+    /// records are already in the ledger, and which cannot be imported.
+    /// Nothing is written.
+    pub async fn preview_csv_import(
+        &self,
+        user_id: i64,
+        account_code: &str,
+        csv_text: &str,
+    ) -> Result<ImportPreview, TransactionError> {
+        let profile = self.get_import_profile(user_id, account_code).await?;
+        let currency_code =
+            header_currency(&self.pool, user_id, &profile.account_code, &profile.counter_account_code).await?;
+        let mut rows = parse_statement(&profile, csv_text, currency::minor_units(&currency_code))?;
+
+        let mut conn = self.pool.acquire().await?;
+        mark_import_duplicates(&mut conn, user_id, &profile.account_code, &mut rows).await?;
+
+        let error_count = rows.iter().filter(|r| r.error.is_some()).count();
+        let duplicate_count = rows.iter().filter(|r| r.is_duplicate).count();
+        Ok(ImportPreview {
+            account_code: profile.account_code,
+            new_count: rows.len() - error_count - duplicate_count,
+            duplicate_count,
+            error_count,
+            rows,
+        })
+    }
+
+    /// Book a CSV statement: each importable record becomes one header plus
+    /// one detail (tax-included, TAX_RATE 0, so the header total equals the
+    /// statement amount). Records flagged as duplicates are skipped unless
+    /// `include_duplicates` is set; records with errors are always skipped.
+    /// Duplicate detection and every insert share one SQL transaction, so
+    /// the import is all-or-nothing and cannot race a second import of the
+    /// same file.
+    pub async fn import_csv_statement(
+        &self,
+        user_id: i64,
+        account_code: &str,
+        csv_text: &str,
+        include_duplicates: bool,
+    ) -> Result<ImportSummary, TransactionError> {
+        let profile = self.get_import_profile(user_id, account_code).await?;
+        let currency_code =
+            header_currency(&self.pool, user_id, &profile.account_code, &profile.counter_account_code).await?;
+        let mut rows = parse_statement(&profile, csv_text, currency::minor_units(&currency_code))?;
+
+        let mut tx = self.pool.begin().await?;
+        mark_import_duplicates(&mut tx, user_id, &profile.account_code, &mut rows).await?;
+
+        let mut summary = ImportSummary {
+            imported_count: 0,
+            skipped_duplicate_count: 0,
+            skipped_error_count: 0,
+            transaction_ids: Vec::new(),
+        };
+
+        for preview in &rows {
+            let Some(row) = &preview.row else {
+                summary.skipped_error_count += 1;
+                continue;
+            };
+            if preview.is_duplicate && !include_duplicates {
+                summary.skipped_duplicate_count += 1;
+                continue;
+            }
+
+            let memo_id: Option<i64> = match &row.memo {
+                Some(text) => {
+                    let existing: Option<i64> = sqlx::query_scalar(sql_queries::MEMO_FIND_BY_TEXT)
+                        .bind(user_id)
+                        .bind(text)
+                        .fetch_optional(&mut *tx)
+                        .await?;
+                    match existing {
+                        Some(id) => Some(id),
+                        None => Some(
+                            sqlx::query(sql_queries::MEMO_INSERT)
+                                .bind(user_id)
+                                .bind(text)
+                                .execute(&mut *tx)
+                                .await?
+                                .last_insert_rowid(),
+                        ),
+                    }
+                }
+                None => None,
+            };
+
+            let transaction_id = sqlx::query(sql_queries::TRANSACTION_HEADER_INSERT)
+                .bind(user_id)
+                .bind(None::<i64>)
+                .bind(&row.transaction_date)
+                .bind(&row.category1_code)
+                .bind(&row.from_account_code)
+                .bind(&row.to_account_code)
+                .bind(row.amount)
+                .bind(consts::TAX_ROUND_DOWN)
+                .bind(consts::TAX_INCLUDED)
+                .bind(memo_id)
+                .bind(0_i64)
+                .bind(&currency_code)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_rowid();
+
+            let detail_id = sqlx::query(sql_queries::TRANSACTION_DETAIL_INSERT_FULL)
+                .bind(transaction_id)
+                .bind(user_id)
+                .bind(&row.category1_code)
+                .bind(None::<String>)
+                .bind(None::<String>)
+                .bind(&row.item_name)
+                .bind(row.amount)
+                .bind(0_i64)
+                .bind(0_i32)
+                .bind(Some(row.amount))
+                .bind(None::<i64>)
+                .bind(None::<i64>)
+                .bind(None::<String>)
+                .bind(None::<String>)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_rowid();
+            audit::record(&mut tx, user_id, &header_audit_key(transaction_id), None).await?;
+            audit::record(&mut tx, user_id, &detail_audit_key(detail_id), None).await?;
+
+            summary.imported_count += 1;
+            summary.transaction_ids.push(transaction_id);
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
 }
 
 #[cfg(test)]
@@ -1648,6 +3166,7 @@ mod tests {
             amount,
             amount_including_tax: including,
             tax_rate: rate,
+            leg: 1,
         }
     }
 
@@ -1684,6 +3203,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_calculate_recommended_total_rounds_each_leg_separately() {
+        // One leg: 10 × 1.10 = 11. Two legs of a split header: 5.5 floors
+        // to 5 on each side, which is what the balances actually move.
+        let mut split = detail(5, None, 10);
+        split.leg = 2;
+        assert_eq!(
+            calculate_recommended_total(&[detail(5, None, 10), detail(5, None, 10)], consts::TAX_ROUND_DOWN),
+            11
+        );
+        assert_eq!(
+            calculate_recommended_total(&[detail(5, None, 10), split], consts::TAX_ROUND_DOWN),
+            10
+        );
+    }
+
     #[test]
     fn test_calculate_recommended_total_tax_included_detail_passes_through() {
         // amount == amount_including_tax with non-zero rate is tax-included
@@ -1759,6 +3294,11 @@ mod tests {
             .await
             .expect("Failed to create test database");
 
+        sqlx::query(sql_queries::CREATE_AUDIT_LOG_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
+
         // Create USERS table
         sqlx::query(sql_queries::TEST_TRANSACTION_CREATE_USERS_TABLE)
             .execute(&pool)
@@ -1873,6 +3413,20 @@ mod tests {
             .await
             .unwrap();
 
+        // Full-text index behind the keyword filter, kept current by the
+        // same triggers production installs.
+        sqlx::query(sql_queries::CREATE_TRANSACTION_SEARCH_SOURCE_VIEW)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(sql_queries::CREATE_TRANSACTION_SEARCH_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for ddl in sql_queries::CREATE_TRANSACTION_SEARCH_TRIGGERS {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+
         pool
     }
 
@@ -1920,6 +3474,8 @@ mod tests {
             amount_including_tax: Some(540),
             product_id: None,
             memo: None,
+            from_account_code: None,
+            to_account_code: None,
         }
     }
 
@@ -2100,6 +3656,8 @@ mod tests {
             amount_including_tax: Some(216),
             product_id: None,
             memo: Some("Test memo".to_string()),
+            from_account_code: None,
+            to_account_code: None,
         };
         let detail_id = service.add_transaction_detail(2, transaction_id, request).await.unwrap();
 
@@ -2133,6 +3691,8 @@ mod tests {
             amount_including_tax: None,
             product_id: None,
             memo: None,
+            from_account_code: None,
+            to_account_code: None,
         };
         let detail_id = service.add_transaction_detail(2, transaction_id, request).await.unwrap();
 
@@ -2191,6 +3751,8 @@ mod tests {
             amount_including_tax: Some(660),
             product_id: None,
             memo: Some("Updated memo".to_string()),
+            from_account_code: None,
+            to_account_code: None,
         };
         let result = service.update_transaction_detail(2, detail_id, update_request).await;
         assert!(result.is_ok(), "update_transaction_detail failed: {:?}", result.err());
@@ -2484,6 +4046,8 @@ mod tests {
             amount_including_tax: None,
             product_id: None,
             memo: None,
+            from_account_code: None,
+            to_account_code: None,
         };
         let detail_id = service.add_transaction_detail(2, transaction_id, request).await.unwrap();
 
@@ -2515,6 +4079,8 @@ mod tests {
             amount_including_tax: Some(880),
             product_id: None,
             memo: None,
+            from_account_code: None,
+            to_account_code: None,
         };
         let detail_id = service.add_transaction_detail(2, transaction_id, request).await.unwrap();
 
@@ -3005,6 +4571,84 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_update_header_refused_while_reconciled() {
+        let pool = setup_test_db().await;
+        let service = TransactionService::new(pool.clone());
+        let transaction_id = create_test_header(&service).await;
+
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET RECONCILE_STATUS = ? WHERE TRANSACTION_ID = ?")
+            .bind(consts::RECONCILE_STATUS_RECONCILED)
+            .bind(transaction_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let request = || SaveTransactionRequest {
+            shop_id: None,
+            category1_code: "EXPENSE".to_string(),
+            from_account_code: "CASH".to_string(),
+            to_account_code: "BANK".to_string(),
+            transaction_date: "2024-01-01 10:00:00".to_string(),
+            total_amount: 4321,
+            tax_rounding_type: consts::TAX_ROUND_DOWN,
+            tax_included_type: consts::TAX_EXCLUDED,
+            memo: None,
+            is_scheduled: None,
+        };
+        let result = service.update_transaction_header(2, transaction_id, request()).await;
+        assert!(matches!(result, Err(TransactionError::Locked)));
+        let api: ApiError = result.unwrap_err().into();
+        assert_eq!(api.code, ApiError::CODE_RECONCILED_LOCKED);
+
+        // Once unlocked back to cleared the edit goes through.
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET RECONCILE_STATUS = ? WHERE TRANSACTION_ID = ?")
+            .bind(consts::RECONCILE_STATUS_CLEARED)
+            .bind(transaction_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        service
+            .update_transaction_header(2, transaction_id, request())
+            .await
+            .unwrap();
+        let header = service.get_transaction_header(2, transaction_id).await.unwrap();
+        assert_eq!(header.total_amount, 4321);
+    }
+
+    #[tokio::test]
+    async fn test_detail_total_and_delete_refused_while_reconciled() {
+        let pool = setup_test_db().await;
+        let service = TransactionService::new(pool.clone());
+        let transaction_id = create_test_header(&service).await;
+        let detail_id = service
+            .add_transaction_detail(2, transaction_id, basic_detail_request())
+            .await
+            .unwrap();
+
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET RECONCILE_STATUS = ? WHERE TRANSACTION_ID = ?")
+            .bind(consts::RECONCILE_STATUS_RECONCILED)
+            .bind(transaction_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let results = [
+            service.add_transaction_detail(2, transaction_id, basic_detail_request()).await.map(|_| ()),
+            service.update_transaction_detail(2, detail_id, basic_detail_request()).await,
+            service.delete_transaction_detail(2, detail_id).await,
+            service.update_transaction_header_total(2, transaction_id, 1).await,
+            service.delete_transaction(2, transaction_id).await,
+        ];
+        for (index, result) in results.into_iter().enumerate() {
+            assert!(matches!(result, Err(TransactionError::Locked)), "write #{} was not refused", index);
+        }
+
+        let details = service.get_transaction_details(2, transaction_id).await.unwrap();
+        assert_eq!(details.len(), 1);
+        assert!(service.get_transaction_header(2, transaction_id).await.is_ok());
+    }
+
     // ========================================================================
     // IS_SCHEDULED Tests
     // ========================================================================
@@ -3076,7 +4720,7 @@ mod tests {
         let transaction_id = service.save_transaction_header(2, request).await.unwrap();
 
         // Confirm it
-        service.confirm_scheduled_transaction(2, transaction_id).await.unwrap();
+        service.confirm_scheduled_transaction(2, transaction_id, None).await.unwrap();
 
         // Verify it's now actual
         let header = service.get_transaction_header(2, transaction_id).await.unwrap();
@@ -3084,16 +4728,270 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_confirm_already_actual_transaction_fails() {
+    async fn test_confirm_already_actual_transaction_fails() {
+        let pool = setup_test_db().await;
+        let service = TransactionService::new(pool);
+
+        // Create a normal (actual) transaction
+        let transaction_id = create_test_header(&service).await;
+
+        // Trying to confirm should fail (it's already actual, IS_SCHEDULED = 0)
+        let result = service.confirm_scheduled_transaction(2, transaction_id, None).await;
+        assert!(result.is_err());
+    }
+
+    /// Scheduled header estimated at 540 with one 500 + 8% line.
+    async fn create_estimated_scheduled(service: &TransactionService) -> i64 {
+        let request = SaveTransactionRequest {
+            shop_id: None,
+            category1_code: "EXPENSE".to_string(),
+            from_account_code: "CASH".to_string(),
+            to_account_code: "BANK".to_string(),
+            transaction_date: "2024-02-01 10:00:00".to_string(),
+            total_amount: 540,
+            tax_rounding_type: consts::TAX_ROUND_DOWN,
+            tax_included_type: consts::TAX_EXCLUDED,
+            memo: None,
+            is_scheduled: Some(1),
+        };
+        let transaction_id = service.save_transaction_header(2, request).await.unwrap();
+        service
+            .add_transaction_detail(2, transaction_id, basic_detail_request())
+            .await
+            .unwrap();
+        transaction_id
+    }
+
+    /// (item name, amount, amount including tax, tax amount) per line.
+    async fn detail_lines(pool: &SqlitePool, transaction_id: i64) -> Vec<(String, i64, Option<i64>, i64)> {
+        sqlx::query_as(
+            "SELECT ITEM_NAME, AMOUNT, AMOUNT_INCLUDING_TAX, TAX_AMOUNT FROM TRANSACTIONS_DETAIL
+             WHERE TRANSACTION_ID = ? ORDER BY DETAIL_ID",
+        )
+        .bind(transaction_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_confirm_scheduled_with_actual_amount_rebooks_single_line() {
+        let pool = setup_test_db().await;
+        let service = TransactionService::new(pool.clone());
+        let transaction_id = create_estimated_scheduled(&service).await;
+
+        let adjustment = ConfirmScheduledAdjustment { total_amount: 648, details: None };
+        service
+            .confirm_scheduled_transaction(2, transaction_id, Some(adjustment))
+            .await
+            .unwrap();
+
+        let header = service.get_transaction_header(2, transaction_id).await.unwrap();
+        assert_eq!((header.is_scheduled, header.total_amount), (0, 648));
+        assert_eq!(
+            detail_lines(&pool, transaction_id).await,
+            [("Rice".to_string(), 648, Some(648), 48)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_confirm_scheduled_with_details_checks_total_atomically() {
+        let pool = setup_test_db().await;
+        let service = TransactionService::new(pool.clone());
+        let transaction_id = create_estimated_scheduled(&service).await;
+
+        let mut bread = basic_detail_request();
+        bread.item_name = "Bread".to_string();
+        bread.amount = 200;
+        bread.tax_rate = 0;
+        bread.tax_amount = 0;
+        bread.amount_including_tax = Some(200);
+        let lines = vec![basic_detail_request(), bread];
+
+        // 540 + 200 = 740; a mismatching total changes nothing
+        let adjustment = ConfirmScheduledAdjustment { total_amount: 700, details: Some(lines.clone()) };
+        let err = service
+            .confirm_scheduled_transaction(2, transaction_id, Some(adjustment))
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, TransactionError::ValidationError(msg) if msg.contains("expected 740")), "{}", err);
+        let header = service.get_transaction_header(2, transaction_id).await.unwrap();
+        assert_eq!((header.is_scheduled, header.total_amount), (1, 540));
+        assert_eq!(detail_lines(&pool, transaction_id).await.len(), 1);
+
+        let adjustment = ConfirmScheduledAdjustment { total_amount: 740, details: Some(lines) };
+        service
+            .confirm_scheduled_transaction(2, transaction_id, Some(adjustment))
+            .await
+            .unwrap();
+        let header = service.get_transaction_header(2, transaction_id).await.unwrap();
+        assert_eq!((header.is_scheduled, header.total_amount), (0, 740));
+        let names: Vec<String> = detail_lines(&pool, transaction_id)
+            .await
+            .into_iter()
+            .map(|(name, ..)| name)
+            .collect();
+        assert_eq!(names, ["Rice", "Bread"]);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_scheduled_multi_line_needs_details_for_actual_amount() {
+        let pool = setup_test_db().await;
+        let service = TransactionService::new(pool);
+        let transaction_id = create_estimated_scheduled(&service).await;
+        service
+            .add_transaction_detail(2, transaction_id, basic_detail_request())
+            .await
+            .unwrap();
+
+        let adjustment = ConfirmScheduledAdjustment { total_amount: 1200, details: None };
+        let result = service
+            .confirm_scheduled_transaction(2, transaction_id, Some(adjustment))
+            .await;
+        assert!(matches!(result, Err(TransactionError::ValidationError(_))));
+
+        let header = service.get_transaction_header(2, transaction_id).await.unwrap();
+        assert_eq!(header.is_scheduled, 1);
+    }
+
+    fn bulk_error_codes(results: &[BulkTransactionResult]) -> Vec<(i64, Option<&'static str>)> {
+        results
+            .iter()
+            .map(|r| {
+                let code = r.error.as_ref().map(|e| match e.code.as_str() {
+                    ApiError::CODE_NOT_FOUND => ApiError::CODE_NOT_FOUND,
+                    ApiError::CODE_VALIDATION => ApiError::CODE_VALIDATION,
+                    ApiError::CODE_RECONCILED_LOCKED => ApiError::CODE_RECONCILED_LOCKED,
+                    _ => "other",
+                });
+                (r.transaction_id, code)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_bulk_update_reports_each_row_and_keeps_the_rest() {
+        let pool = setup_test_db().await;
+        let service = TransactionService::new(pool.clone());
+        let with_lines = create_test_header(&service).await;
+        service.add_transaction_detail(2, with_lines, basic_detail_request()).await.unwrap();
+        let no_lines = create_test_header(&service).await;
+        let reconciled = create_test_header(&service).await;
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET RECONCILE_STATUS = ? WHERE TRANSACTION_ID = ?")
+            .bind(consts::RECONCILE_STATUS_RECONCILED)
+            .bind(reconciled)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let change = BulkTransactionChange::SetCategories {
+            category2_code: Some(" FOOD ".to_string()),
+            category3_code: None,
+        };
+        let results = service
+            .bulk_update_transactions(2, &[with_lines, no_lines, reconciled, 999, with_lines], &change)
+            .await
+            .unwrap();
+        assert_eq!(
+            bulk_error_codes(&results),
+            vec![
+                (with_lines, None),
+                (no_lines, Some(ApiError::CODE_VALIDATION)),
+                (reconciled, Some(ApiError::CODE_RECONCILED_LOCKED)),
+                (999, Some(ApiError::CODE_NOT_FOUND)),
+            ]
+        );
+        let details = service.get_transaction_details(2, with_lines).await.unwrap();
+        assert_eq!(details[0].category2_code.as_deref(), Some("FOOD"));
+        assert_eq!(details[0].category3_code, None);
+
+        // An unknown sub-category fails that row only
+        let change = BulkTransactionChange::SetCategories {
+            category2_code: Some("FOOD".to_string()),
+            category3_code: Some("NO_SUCH".to_string()),
+        };
+        let results = service.bulk_update_transactions(2, &[with_lines], &change).await.unwrap();
+        assert_eq!(bulk_error_codes(&results), vec![(with_lines, Some(ApiError::CODE_VALIDATION))]);
+
+        // Both headers end up on one shared memo row; blank clears it again
+        let set_memo = |memo: &str| BulkTransactionChange::SetMemo { memo: Some(memo.to_string()) };
+        service.bulk_update_transactions(2, &[with_lines, no_lines], &set_memo("  Trip  ")).await.unwrap();
+        let a = service.get_transaction_header_with_memo(2, with_lines).await.unwrap();
+        let b = service.get_transaction_header_with_memo(2, no_lines).await.unwrap();
+        assert_eq!(a.1.as_deref(), Some("Trip"));
+        assert_eq!(a.0.memo_id, b.0.memo_id);
+        service.bulk_update_transactions(2, &[with_lines], &set_memo(" ")).await.unwrap();
+        assert_eq!(service.get_transaction_header(2, with_lines).await.unwrap().memo_id, None);
+
+        // The memo row goes once its last header lets go of it
+        async fn memo_count(pool: &SqlitePool) -> i64 {
+            sqlx::query_scalar("SELECT COUNT(*) FROM MEMOS WHERE USER_ID = 2")
+                .fetch_one(pool)
+                .await
+                .unwrap()
+        }
+        let with_trip = memo_count(&pool).await;
+        service.bulk_update_transactions(2, &[no_lines], &set_memo("Hotel")).await.unwrap();
+        assert_eq!(memo_count(&pool).await, with_trip);
+        service.bulk_update_transactions(2, &[no_lines], &set_memo(" ")).await.unwrap();
+        assert_eq!(memo_count(&pool).await, with_trip - 1);
+
+        // Problems with the change itself reject the whole call
+        let result = service
+            .bulk_update_transactions(2, &[with_lines], &BulkTransactionChange::SetShop { shop_id: Some(42) })
+            .await;
+        assert!(matches!(result, Err(TransactionError::ValidationError(_))));
+        let result = service.bulk_update_transactions(2, &[], &BulkTransactionChange::Delete).await;
+        assert!(matches!(result, Err(TransactionError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_move_account_confirm_and_delete() {
         let pool = setup_test_db().await;
-        let service = TransactionService::new(pool);
+        let service = TransactionService::new(pool.clone());
+        sqlx::query(
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, CURRENCY_CODE) VALUES (2, 'USD_CARD', 'USD card', 'BANK', 'USD')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let first = create_test_header(&service).await;
+        let second = create_test_header(&service).await;
 
-        // Create a normal (actual) transaction
-        let transaction_id = create_test_header(&service).await;
+        let move_from = |code: &str| BulkTransactionChange::MoveAccount {
+            from_account_code: Some(code.to_string()),
+            to_account_code: None,
+        };
+        let results = service.bulk_update_transactions(2, &[first, second], &move_from("bank")).await.unwrap();
+        assert!(results.iter().all(|r| r.error.is_none()));
+        let header = service.get_transaction_header(2, first).await.unwrap();
+        assert_eq!((header.from_account_code.as_str(), header.to_account_code.as_str()), ("BANK", "BANK"));
+
+        // Switching currency is refused per row and leaves the header alone
+        let results = service.bulk_update_transactions(2, &[first], &move_from("USD_CARD")).await.unwrap();
+        assert_eq!(bulk_error_codes(&results), vec![(first, Some(ApiError::CODE_VALIDATION))]);
+        assert_eq!(service.get_transaction_header(2, first).await.unwrap().from_account_code, "BANK");
+        let result = service.bulk_update_transactions(2, &[first], &move_from("NOPE")).await;
+        assert!(matches!(result, Err(TransactionError::ValidationError(_))));
+
+        sqlx::query("UPDATE TRANSACTIONS_HEADER SET IS_SCHEDULED = 1 WHERE TRANSACTION_ID = ?")
+            .bind(second)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let results = service
+            .bulk_update_transactions(2, &[first, second], &BulkTransactionChange::ConfirmScheduled)
+            .await
+            .unwrap();
+        assert_eq!(bulk_error_codes(&results), vec![(first, Some(ApiError::CODE_VALIDATION)), (second, None)]);
+        assert_eq!(service.get_transaction_header(2, second).await.unwrap().is_scheduled, 0);
 
-        // Trying to confirm should fail (it's already actual, IS_SCHEDULED = 0)
-        let result = service.confirm_scheduled_transaction(2, transaction_id).await;
-        assert!(result.is_err());
+        let results = service
+            .bulk_update_transactions(2, &[second, first], &BulkTransactionChange::Delete)
+            .await
+            .unwrap();
+        assert!(results.iter().all(|r| r.error.is_none()));
+        assert!(matches!(service.get_transaction_header(2, first).await, Err(TransactionError::NotFound)));
     }
 
     /// Regression test: filtering by category2_code/category3_code used to be
@@ -3146,6 +5044,8 @@ mod tests {
                     amount_including_tax: Some(216),
                     product_id: None,
                     memo: None,
+                    from_account_code: None,
+                    to_account_code: None,
                 },
             )
             .await
@@ -3153,14 +5053,23 @@ mod tests {
 
         // No filter: both transactions returned.
         let all = service
-            .get_transactions(2, None, None, None, None, None, None, None, None, false, 1, 50)
+            .get_transactions(2, &TransactionQuery::default(), 1, 50)
             .await
             .unwrap();
         assert_eq!(all.total_count, 2);
 
         // CATEGORY2 = FOOD: only header A.
         let food = service
-            .get_transactions(2, None, None, Some("EXPENSE"), Some("FOOD"), None, None, None, None, false, 1, 50)
+            .get_transactions(
+                2,
+                &TransactionQuery {
+                    category1_code: Some("EXPENSE".to_string()),
+                    category2_code: Some("FOOD".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
+            )
             .await
             .unwrap();
         assert_eq!(food.total_count, 1);
@@ -3168,7 +5077,16 @@ mod tests {
 
         // CATEGORY2 = OTHER: only header B.
         let other = service
-            .get_transactions(2, None, None, Some("EXPENSE"), Some("OTHER"), None, None, None, None, false, 1, 50)
+            .get_transactions(
+                2,
+                &TransactionQuery {
+                    category1_code: Some("EXPENSE".to_string()),
+                    category2_code: Some("OTHER".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
+            )
             .await
             .unwrap();
         assert_eq!(other.total_count, 1);
@@ -3178,15 +5096,12 @@ mod tests {
         let grocery = service
             .get_transactions(
                 2,
-                None,
-                None,
-                Some("EXPENSE"),
-                Some("FOOD"),
-                Some("GROCERY"),
-                None,
-                None,
-                None,
-                false,
+                &TransactionQuery {
+                    category1_code: Some("EXPENSE".to_string()),
+                    category2_code: Some("FOOD".to_string()),
+                    category3_code: Some("GROCERY".to_string()),
+                    ..Default::default()
+                },
                 1,
                 50,
             )
@@ -3197,7 +5112,17 @@ mod tests {
 
         // Empty-string filter must be treated as "no filter".
         let empty = service
-            .get_transactions(2, None, None, Some(""), Some(""), Some(""), None, None, None, false, 1, 50)
+            .get_transactions(
+                2,
+                &TransactionQuery {
+                    category1_code: Some("".to_string()),
+                    category2_code: Some("".to_string()),
+                    category3_code: Some("".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
+            )
             .await
             .unwrap();
         assert_eq!(empty.total_count, 2);
@@ -3238,10 +5163,13 @@ mod tests {
         let result = service
             .get_transactions(
                 2,
-                Some("2024-05-10"),
-                Some("2024-05-15"),
-                None, None, None, None, None, None,
-                false, 1, 50,
+                &TransactionQuery {
+                    start_date: Some("2024-05-10".to_string()),
+                    end_date: Some("2024-05-15".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
             )
             .await
             .unwrap();
@@ -3251,10 +5179,13 @@ mod tests {
         let single_day = service
             .get_transactions(
                 2,
-                Some("2024-05-15"),
-                Some("2024-05-15"),
-                None, None, None, None, None, None,
-                false, 1, 50,
+                &TransactionQuery {
+                    start_date: Some("2024-05-15".to_string()),
+                    end_date: Some("2024-05-15".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
             )
             .await
             .unwrap();
@@ -3264,10 +5195,12 @@ mod tests {
         let datetime_end = service
             .get_transactions(
                 2,
-                None,
-                Some("2024-05-15 12:00:00"),
-                None, None, None, None, None, None,
-                false, 1, 50,
+                &TransactionQuery {
+                    end_date: Some("2024-05-15 12:00:00".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
             )
             .await
             .unwrap();
@@ -3275,8 +5208,10 @@ mod tests {
     }
 
     /// Regression: the keyword parameter used to be discarded (`let _ = keyword`),
-    /// leaving the search box a silent no-op. Keyword must substring-match
-    /// against memos on the header row and on any detail row of the header.
+    /// leaving the search box a silent no-op. Keyword must match the same
+    /// fields as the search box: memos on the header and detail rows, item
+    /// names, shop and product — through the index for terms of 3+ chars and
+    /// by LIKE for shorter ones.
     #[tokio::test]
     async fn test_get_transactions_keyword_matches_header_and_detail_memo() {
         let pool = setup_test_db().await;
@@ -3333,8 +5268,13 @@ mod tests {
         // Keyword hits header memo only.
         let hit_header = service
             .get_transactions(
-                2, None, None, None, None, None, None, None,
-                Some("牛乳"), false, 1, 50,
+                2,
+                &TransactionQuery {
+                    keyword: Some("牛乳".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
             )
             .await
             .unwrap();
@@ -3344,19 +5284,75 @@ mod tests {
         // Keyword hits detail memo only.
         let hit_detail = service
             .get_transactions(
-                2, None, None, None, None, None, None, None,
-                Some("特売"), false, 1, 50,
+                2,
+                &TransactionQuery {
+                    keyword: Some("特売".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
             )
             .await
             .unwrap();
         assert_eq!(hit_detail.total_count, 1);
         assert_eq!(hit_detail.transactions[0].transaction_id, id_b);
 
+        // Longer terms go through the index: header memo, and item name.
+        let indexed_memo = service
+            .get_transactions(
+                2,
+                &TransactionQuery {
+                    keyword: Some("駅前スーパー".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
+            )
+            .await
+            .unwrap();
+        assert_eq!(indexed_memo.total_count, 1);
+        assert_eq!(indexed_memo.transactions[0].transaction_id, id_a);
+
+        let indexed_item = service
+            .get_transactions(
+                2,
+                &TransactionQuery {
+                    keyword: Some("rice".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
+            )
+            .await
+            .unwrap();
+        assert_eq!(indexed_item.total_count, 1);
+        assert_eq!(indexed_item.transactions[0].transaction_id, id_b);
+
+        // Every term must match, mixing index-length and short terms.
+        let mixed_miss = service
+            .get_transactions(
+                2,
+                &TransactionQuery {
+                    keyword: Some("Rice 牛乳".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
+            )
+            .await
+            .unwrap();
+        assert_eq!(mixed_miss.total_count, 0);
+
         // No match returns no rows.
         let miss = service
             .get_transactions(
-                2, None, None, None, None, None, None, None,
-                Some("ダミー"), false, 1, 50,
+                2,
+                &TransactionQuery {
+                    keyword: Some("ダミー".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
             )
             .await
             .unwrap();
@@ -3365,8 +5361,13 @@ mod tests {
         // Whitespace-only keyword is treated as no filter.
         let whitespace = service
             .get_transactions(
-                2, None, None, None, None, None, None, None,
-                Some("   "), false, 1, 50,
+                2,
+                &TransactionQuery {
+                    keyword: Some("   ".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
             )
             .await
             .unwrap();
@@ -3376,8 +5377,13 @@ mod tests {
         // not as a wildcard.
         let percent_miss = service
             .get_transactions(
-                2, None, None, None, None, None, None, None,
-                Some("%"), false, 1, 50,
+                2,
+                &TransactionQuery {
+                    keyword: Some("%".to_string()),
+                    ..Default::default()
+                },
+                1,
+                50,
             )
             .await
             .unwrap();
@@ -3420,16 +5426,104 @@ mod tests {
         service.save_transaction_header(2, request_scheduled).await.unwrap();
 
         // Default: exclude scheduled
-        let result = service.get_transactions(
-            2, None, None, None, None, None, None, None, None, false, 1, 50
-        ).await.unwrap();
+        let result = service.get_transactions(2, &TransactionQuery::default(), 1, 50).await.unwrap();
         assert_eq!(result.total_count, 1);
 
         // Include scheduled
         let result = service.get_transactions(
-            2, None, None, None, None, None, None, None, None, true, 1, 50
+            2,
+            &TransactionQuery {
+                status: TransactionStatusFilter::All,
+                ..Default::default()
+            },
+            1,
+            50,
         ).await.unwrap();
         assert_eq!(result.total_count, 2);
+
+        // Scheduled only
+        let result = service.get_transactions(
+            2,
+            &TransactionQuery {
+                status: TransactionStatusFilter::Scheduled,
+                ..Default::default()
+            },
+            1,
+            50,
+        ).await.unwrap();
+        assert_eq!(result.total_count, 1);
+        assert_eq!(result.transactions[0].is_scheduled, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_transactions_structured_filters_and_sort() {
+        let pool = setup_test_db().await;
+        for sql in [
+            "INSERT INTO SHOPS (SHOP_ID, USER_ID, SHOP_NAME) VALUES (1, 2, 'Corner Store')",
+            "INSERT INTO MANUFACTURERS (MANUFACTURER_ID, USER_ID, MANUFACTURER_NAME) VALUES (1, 2, 'Meiji')",
+            "INSERT INTO PRODUCTS (PRODUCT_ID, USER_ID, PRODUCT_NAME, MANUFACTURER_ID) VALUES (1, 2, 'Milk', 1)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        let service = TransactionService::new(pool.clone());
+
+        // A: shop 1, product Milk, 300 yen. B: rule 7, item "Rice", 900 yen.
+        // C: paid from BANK instead of CASH, 600 yen.
+        let a = create_test_header(&service).await;
+        let mut milk = basic_detail_request();
+        milk.item_name = "Milk 1L".to_string();
+        milk.product_id = Some(1);
+        service.add_transaction_detail(2, a, milk).await.unwrap();
+        let b = create_test_header(&service).await;
+        service.add_transaction_detail(2, b, basic_detail_request()).await.unwrap();
+        let c = create_test_header(&service).await;
+        for (sql, id) in [
+            ("UPDATE TRANSACTIONS_HEADER SET SHOP_ID = 1, TOTAL_AMOUNT = 300 WHERE TRANSACTION_ID = ?", a),
+            ("UPDATE TRANSACTIONS_HEADER SET RULE_ID = 7, TOTAL_AMOUNT = 900 WHERE TRANSACTION_ID = ?", b),
+            ("UPDATE TRANSACTIONS_HEADER SET FROM_ACCOUNT_CODE = 'BANK', TOTAL_AMOUNT = 600 WHERE TRANSACTION_ID = ?", c),
+        ] {
+            sqlx::query(sql).bind(id).execute(&pool).await.unwrap();
+        }
+
+        let ids = |query: TransactionQuery| {
+            let service = &service;
+            async move {
+                service
+                    .get_transactions(2, &query, 1, 50)
+                    .await
+                    .unwrap()
+                    .transactions
+                    .into_iter()
+                    .map(|t| t.transaction_id)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(ids(TransactionQuery { shop_id: Some(1), ..Default::default() }).await, vec![a]);
+        assert_eq!(ids(TransactionQuery { product_id: Some(1), ..Default::default() }).await, vec![a]);
+        assert_eq!(ids(TransactionQuery { manufacturer_id: Some(1), ..Default::default() }).await, vec![a]);
+        assert_eq!(ids(TransactionQuery { rule_id: Some(7), ..Default::default() }).await, vec![b]);
+        assert_eq!(
+            ids(TransactionQuery { item_name: Some("rice".to_string()), ..Default::default() }).await,
+            vec![b]
+        );
+        // LIKE metacharacters in the item name match literally
+        assert!(ids(TransactionQuery { item_name: Some("%".to_string()), ..Default::default() }).await.is_empty());
+        // CASH is on A and B's FROM side; BANK is on every TO side
+        assert_eq!(
+            ids(TransactionQuery { account_code: Some("CASH".to_string()), ..Default::default() }).await,
+            vec![b, a]
+        );
+        assert_eq!(
+            ids(TransactionQuery { account_code: Some("BANK".to_string()), ..Default::default() }).await.len(),
+            3
+        );
+
+        // Same date everywhere, so the date sorts fall back to the ID
+        assert_eq!(ids(TransactionQuery::default()).await, vec![c, b, a]);
+        assert_eq!(ids(TransactionQuery { sort: TransactionSort::DateAsc, ..Default::default() }).await, vec![a, b, c]);
+        assert_eq!(ids(TransactionQuery { sort: TransactionSort::AmountDesc, ..Default::default() }).await, vec![b, c, a]);
+        assert_eq!(ids(TransactionQuery { sort: TransactionSort::AmountAsc, ..Default::default() }).await, vec![a, c, b]);
     }
 
     // Issue #37 — bounded-field length checks must count characters, not bytes.
@@ -4051,5 +6145,383 @@ mod tests {
             );
         }
     }
-}
 
+    #[tokio::test]
+    async fn test_save_transaction_header_takes_currency_from_accounts() {
+        let pool = setup_test_db().await;
+        for sql in [
+            sql_queries::TEST_TRANSACTION_INSERT_ACCOUNT_NONE,
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, CURRENCY_CODE) VALUES (2, 'USD_BANK', 'US bank', 'BANK', 'USD')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        let service = TransactionService::new(pool);
+        let request = |from: &str, to: &str| SaveTransactionRequest {
+            shop_id: None,
+            category1_code: "TRANSFER".to_string(),
+            from_account_code: from.to_string(),
+            to_account_code: to.to_string(),
+            transaction_date: "2024-01-01 10:00:00".to_string(),
+            total_amount: 1250,
+            tax_rounding_type: consts::TAX_ROUND_DOWN,
+            tax_included_type: consts::TAX_INCLUDED,
+            memo: None,
+            is_scheduled: None,
+        };
+
+        let usd = service.save_transaction_header(2, request("NONE", "USD_BANK")).await.unwrap();
+        let header = service.get_transaction_header_with_info(2, usd).await.unwrap();
+        assert_eq!(header.currency_code, "USD");
+
+        let none_only = service.save_transaction_header(2, request("NONE", "NONE")).await.unwrap();
+        let header = service.get_transaction_header_with_info(2, none_only).await.unwrap();
+        assert_eq!(header.currency_code, "JPY");
+
+        assert!(matches!(
+            service.save_transaction_header(2, request("CASH", "USD_BANK")).await,
+            Err(TransactionError::ValidationError(msg)) if msg.contains("different currencies")
+        ));
+        assert!(matches!(
+            service.update_transaction_header(2, usd, request("CASH", "USD_BANK")).await,
+            Err(TransactionError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_split_detail_lines_validate_accounts_and_round_per_leg() {
+        let pool = setup_test_db().await;
+        for sql in [
+            sql_queries::TEST_TRANSACTION_INSERT_ACCOUNT_NONE,
+            "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, CURRENCY_CODE) VALUES (2, 'USD_BANK', 'US bank', 'BANK', 'USD')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        let service = TransactionService::new(pool);
+        let txn_id = create_test_header(&service).await;
+        let line = |from: Option<&str>, to: Option<&str>| SaveTransactionDetailRequest {
+            category1_code: "TRANSFER".to_string(),
+            category2_code: None,
+            category3_code: None,
+            amount: 5,
+            tax_rate: 10,
+            tax_amount: 0,
+            amount_including_tax: None,
+            from_account_code: from.map(str::to_string),
+            to_account_code: to.map(str::to_string),
+            ..basic_detail_request()
+        };
+
+        for (from, to, expected) in [
+            (Some("CASH"), None, "needs both"),
+            (Some("CASH"), Some("NOWHERE"), "does not exist"),
+            (Some("NONE"), Some("USD_BANK"), "booked in JPY"),
+        ] {
+            match service.add_transaction_detail(2, txn_id, line(from, to)).await {
+                Err(TransactionError::ValidationError(msg)) => {
+                    assert!(msg.contains(expected), "unexpected error: {}", msg)
+                }
+                other => panic!("expected validation error, got {:?}", other),
+            }
+        }
+
+        service
+            .add_transaction_detail(2, txn_id, line(None, None))
+            .await
+            .unwrap();
+        let split_id = service
+            .add_transaction_detail(2, txn_id, line(Some(" cash "), Some("bank")))
+            .await
+            .unwrap();
+
+        let details = service.get_transaction_details(2, txn_id).await.unwrap();
+        let split = details.iter().find(|d| d.detail_id == split_id).unwrap();
+        assert_eq!(split.from_account_code.as_deref(), Some("CASH"));
+        assert_eq!(split.to_account_code.as_deref(), Some("BANK"));
+        // Each leg grosses up and floors 5 × 1.10 on its own
+        assert_eq!(service.compute_recommended_total(2, txn_id).await.unwrap(), 10);
+
+        // Clearing the accounts folds the line back into the header's leg
+        service
+            .update_transaction_detail(2, split_id, line(None, None))
+            .await
+            .unwrap();
+        assert_eq!(service.compute_recommended_total(2, txn_id).await.unwrap(), 11);
+    }
+
+    // ========================================================================
+    // CSV Statement Import Tests
+    // ========================================================================
+
+    /// `setup_test_db` plus the IMPORT_PROFILES table, the INCOME category
+    /// and the NONE counter account every default profile books against.
+    async fn setup_import_test_db() -> SqlitePool {
+        let pool = setup_test_db().await;
+        for sql in [
+            sql_queries::CREATE_IMPORT_PROFILES_TABLE,
+            sql_queries::TEST_TRANSACTION_INSERT_ACCOUNT_NONE,
+            sql_queries::TEST_TRANSACTION_INSERT_CATEGORY1_INCOME,
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    /// Bank passbook layout: date, description, withdrawal, deposit, balance.
+    fn bank_profile_request() -> SaveImportProfileRequest {
+        SaveImportProfileRequest {
+            account_code: "bank".to_string(),
+            profile_name: "Passbook".to_string(),
+            delimiter: None,
+            skip_rows: None,
+            date_column: 0,
+            date_format: None,
+            amount_column: None,
+            withdrawal_column: Some(2),
+            deposit_column: Some(3),
+            description_column: 1,
+            memo_column: None,
+            negate_amount: None,
+            counter_account_code: None,
+        }
+    }
+
+    const BANK_CSV: &str = "日付,摘要,お引出し,お預入れ,残高\r\n\
+        2024/04/01,\"ATM, Shinjuku\",\"10,000\",,90000\r\n\
+        2024/04/25,SALARY,,250000,340000\r\n\
+        2024/04/26,Coffee,500,,339500\r\n\
+        2024/04/26,Coffee,500,,339000\r\n";
+
+    #[test]
+    fn test_parse_csv_records_handles_quotes_bom_and_crlf() {
+        let text = "\u{feff}a,\"b,c\",\"say \"\"hi\"\"\"\r\n\"multi\nline\",x\n\nlast";
+        let records = parse_csv_records(text, ',').unwrap();
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0], (1, vec!["a".to_string(), "b,c".to_string(), "say \"hi\"".to_string()]));
+        assert_eq!(records[1], (2, vec!["multi\nline".to_string(), "x".to_string()]));
+        assert_eq!(records[2], (4, vec![String::new()]));
+        assert_eq!(records[3], (5, vec!["last".to_string()]));
+
+        assert!(parse_csv_records("a,\"open", ',').is_err());
+        let tsv = parse_csv_records("a\tb", '\t').unwrap();
+        assert_eq!(tsv[0].1, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_statement_amount_accepts_bank_decorations() {
+        assert_eq!(parse_statement_amount("1,234", 0).unwrap(), Some(1234));
+        assert_eq!(parse_statement_amount("￥1,234円", 0).unwrap(), Some(1234));
+        assert_eq!(parse_statement_amount("-500", 0).unwrap(), Some(-500));
+        assert_eq!(parse_statement_amount("▲500", 0).unwrap(), Some(-500));
+        assert_eq!(parse_statement_amount("(500)", 0).unwrap(), Some(-500));
+        assert_eq!(parse_statement_amount("1200.00", 0).unwrap(), Some(1200));
+        assert_eq!(parse_statement_amount("  ", 0).unwrap(), None);
+        assert!(parse_statement_amount("12.50", 0).is_err());
+        assert!(parse_statement_amount("abc", 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_statement_amount_scales_to_minor_units() {
+        assert_eq!(parse_statement_amount("12.50", 2).unwrap(), Some(1250));
+        assert_eq!(parse_statement_amount("1,234.5", 2).unwrap(), Some(123450));
+        assert_eq!(parse_statement_amount("-3", 2).unwrap(), Some(-300));
+        assert!(parse_statement_amount("0.125", 2).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_import_profile_validates_mapping() {
+        let pool = setup_import_test_db().await;
+        let service = TransactionService::new(pool);
+
+        let mut no_amount = bank_profile_request();
+        no_amount.withdrawal_column = None;
+        no_amount.deposit_column = None;
+        assert!(matches!(
+            service.save_import_profile(2, no_amount).await,
+            Err(TransactionError::ValidationError(_))
+        ));
+
+        let mut unknown_account = bank_profile_request();
+        unknown_account.account_code = "WALLET".to_string();
+        assert!(matches!(
+            service.save_import_profile(2, unknown_account).await,
+            Err(TransactionError::ValidationError(_))
+        ));
+
+        let mut bad_format = bank_profile_request();
+        bad_format.date_format = Some("%Q".to_string());
+        assert!(matches!(
+            service.save_import_profile(2, bad_format).await,
+            Err(TransactionError::ValidationError(_))
+        ));
+
+        let mut same_counter = bank_profile_request();
+        same_counter.counter_account_code = Some("BANK".to_string());
+        assert!(matches!(
+            service.save_import_profile(2, same_counter).await,
+            Err(TransactionError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_save_import_profile_upserts_per_account() {
+        let pool = setup_import_test_db().await;
+        let service = TransactionService::new(pool);
+
+        let saved = service.save_import_profile(2, bank_profile_request()).await.unwrap();
+        assert_eq!(saved.account_code, "BANK");
+        assert_eq!(saved.counter_account_code, "NONE");
+        assert_eq!(saved.skip_rows, 1);
+
+        let mut renamed = bank_profile_request();
+        renamed.profile_name = "Passbook v2".to_string();
+        renamed.skip_rows = Some(2);
+        let updated = service.save_import_profile(2, renamed).await.unwrap();
+        assert_eq!(updated.profile_id, saved.profile_id);
+        assert_eq!(updated.profile_name, "Passbook v2");
+        assert_eq!(updated.skip_rows, 2);
+
+        assert_eq!(service.list_import_profiles(2).await.unwrap().len(), 1);
+        service.delete_import_profile(2, "BANK").await.unwrap();
+        assert!(matches!(
+            service.get_import_profile(2, "BANK").await,
+            Err(TransactionError::NotFound)
+        ));
+        assert!(matches!(
+            service.delete_import_profile(2, "BANK").await,
+            Err(TransactionError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_preview_csv_import_maps_direction_and_reports_errors() {
+        let pool = setup_import_test_db().await;
+        let service = TransactionService::new(pool);
+        service.save_import_profile(2, bank_profile_request()).await.unwrap();
+
+        let csv = format!("{}2024/13/01,Broken,100,,0\r\n", BANK_CSV);
+        let preview = service.preview_csv_import(2, "BANK", &csv).await.unwrap();
+
+        assert_eq!(preview.rows.len(), 5);
+        assert_eq!(preview.new_count, 4);
+        assert_eq!(preview.duplicate_count, 0);
+        assert_eq!(preview.error_count, 1);
+
+        let atm = preview.rows[0].row.as_ref().unwrap();
+        assert_eq!(preview.rows[0].line_number, 2);
+        assert_eq!(atm.transaction_date, "2024-04-01 00:00:00");
+        assert_eq!(atm.category1_code, "EXPENSE");
+        assert_eq!(atm.from_account_code, "BANK");
+        assert_eq!(atm.to_account_code, "NONE");
+        assert_eq!(atm.amount, 10000);
+        assert_eq!(atm.item_name, "ATM, Shinjuku");
+
+        let salary = preview.rows[1].row.as_ref().unwrap();
+        assert_eq!(salary.category1_code, "INCOME");
+        assert_eq!(salary.from_account_code, "NONE");
+        assert_eq!(salary.to_account_code, "BANK");
+        assert_eq!(salary.amount, 250000);
+
+        assert_eq!(preview.rows[4].line_number, 6);
+        assert!(preview.rows[4].error.as_deref().unwrap().starts_with("Invalid date"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_csv_import_negates_card_statement_amounts() {
+        let pool = setup_import_test_db().await;
+        let service = TransactionService::new(pool);
+        let request = SaveImportProfileRequest {
+            account_code: "CASH".to_string(),
+            profile_name: "Card".to_string(),
+            delimiter: None,
+            skip_rows: Some(0),
+            date_column: 0,
+            date_format: Some("%Y-%m-%d".to_string()),
+            amount_column: Some(2),
+            withdrawal_column: None,
+            deposit_column: None,
+            description_column: 1,
+            memo_column: Some(3),
+            negate_amount: Some(true),
+            counter_account_code: Some("BANK".to_string()),
+        };
+        service.save_import_profile(2, request).await.unwrap();
+
+        let preview = service
+            .preview_csv_import(2, "CASH", "2024-05-02,Books,3200,gift\n2024-05-03,Refund,-800,\n")
+            .await
+            .unwrap();
+
+        let charge = preview.rows[0].row.as_ref().unwrap();
+        assert_eq!(charge.category1_code, "EXPENSE");
+        assert_eq!(charge.from_account_code, "CASH");
+        assert_eq!(charge.to_account_code, "BANK");
+        assert_eq!(charge.memo.as_deref(), Some("gift"));
+        let refund = preview.rows[1].row.as_ref().unwrap();
+        assert_eq!(refund.category1_code, "INCOME");
+        assert_eq!(refund.amount, 800);
+        assert_eq!(refund.memo, None);
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_statement_books_rows_and_skips_duplicates() {
+        let pool = setup_import_test_db().await;
+        let service = TransactionService::new(pool.clone());
+        service.save_import_profile(2, bank_profile_request()).await.unwrap();
+
+        // One of the two ¥500 coffees on 04/26 is already in the ledger.
+        let existing = SaveTransactionRequest {
+            shop_id: None,
+            category1_code: "EXPENSE".to_string(),
+            from_account_code: "BANK".to_string(),
+            to_account_code: "NONE".to_string(),
+            transaction_date: "2024-04-26 08:15:00".to_string(),
+            total_amount: 500,
+            tax_rounding_type: consts::TAX_ROUND_DOWN,
+            tax_included_type: consts::TAX_INCLUDED,
+            memo: None,
+            is_scheduled: None,
+        };
+        service.save_transaction_header(2, existing).await.unwrap();
+
+        let preview = service.preview_csv_import(2, "BANK", BANK_CSV).await.unwrap();
+        let flags: Vec<bool> = preview.rows.iter().map(|r| r.is_duplicate).collect();
+        assert_eq!(flags, vec![false, false, true, false]);
+
+        let summary = service.import_csv_statement(2, "BANK", BANK_CSV, false).await.unwrap();
+        assert_eq!(summary.imported_count, 3);
+        assert_eq!(summary.skipped_duplicate_count, 1);
+        assert_eq!(summary.skipped_error_count, 0);
+
+        let atm_id = summary.transaction_ids[0];
+        let header = service.get_transaction_header(2, atm_id).await.unwrap();
+        assert_eq!(header.total_amount, 10000);
+        assert_eq!(header.tax_included_type, consts::TAX_INCLUDED);
+        assert_eq!(header.is_scheduled, 0);
+        let details = service.get_transaction_details(2, atm_id).await.unwrap();
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].item_name, "ATM, Shinjuku");
+        assert_eq!(details[0].amount, 10000);
+        assert_eq!(service.compute_recommended_total(2, atm_id).await.unwrap(), 10000);
+
+        // Re-importing the same file finds every row already booked.
+        let again = service.import_csv_statement(2, "BANK", BANK_CSV, false).await.unwrap();
+        assert_eq!(again.imported_count, 0);
+        assert_eq!(again.skipped_duplicate_count, 4);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM TRANSACTIONS_HEADER WHERE USER_ID = 2")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 4);
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_statement_requires_profile() {
+        let pool = setup_import_test_db().await;
+        let service = TransactionService::new(pool);
+
+        let result = service.import_csv_statement(2, "BANK", BANK_CSV, false).await;
+        assert!(matches!(result, Err(TransactionError::NotFound)));
+    }
+}
@@ -485,25 +485,34 @@ WHERE USER_ID = ? AND CATEGORY1_CODE = ? AND CATEGORY2_CODE = ? AND IS_DISABLED
 
 // Account Templates
 pub const ACCOUNT_TEMPLATE_LIST: &str = r#"
-SELECT TEMPLATE_ID, TEMPLATE_CODE, TEMPLATE_NAME_JA, TEMPLATE_NAME_EN, DISPLAY_ORDER, ENTRY_DT
+SELECT TEMPLATE_ID, TEMPLATE_CODE, TEMPLATE_NAME_JA, TEMPLATE_NAME_EN, DISPLAY_ORDER,
+       ACCOUNT_CLASS, ENTRY_DT
 FROM ACCOUNT_TEMPLATES
 ORDER BY DISPLAY_ORDER
 "#;
 
 // User Accounts
+// ACCOUNT_CLASS comes from the template (0 asset, 1 liability); the
+// LEFT JOIN keeps accounts readable even if their template row is gone.
+// INITIAL_BALANCE and every amount booked on the account are in minor units
+// of CURRENCY_CODE.
 pub const ACCOUNT_LIST_BY_USER: &str = r#"
-SELECT ACCOUNT_ID, USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, 
-       INITIAL_BALANCE, DISPLAY_ORDER, IS_DISABLED, ENTRY_DT, UPDATE_DT
-FROM ACCOUNTS
-WHERE USER_ID = ? AND IS_DISABLED = 0
-ORDER BY DISPLAY_ORDER, ACCOUNT_CODE
+SELECT a.ACCOUNT_ID, a.USER_ID, a.ACCOUNT_CODE, a.ACCOUNT_NAME, a.TEMPLATE_CODE,
+       a.INITIAL_BALANCE, a.DISPLAY_ORDER, a.IS_DISABLED, a.ENTRY_DT, a.UPDATE_DT,
+       COALESCE(t.ACCOUNT_CLASS, 0) AS ACCOUNT_CLASS, a.CURRENCY_CODE
+FROM ACCOUNTS a
+LEFT JOIN ACCOUNT_TEMPLATES t ON t.TEMPLATE_CODE = a.TEMPLATE_CODE
+WHERE a.USER_ID = ? AND a.IS_DISABLED = 0
+ORDER BY a.DISPLAY_ORDER, a.ACCOUNT_CODE
 "#;
 
 pub const ACCOUNT_GET_BY_CODE: &str = r#"
-SELECT ACCOUNT_ID, USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE, 
-       INITIAL_BALANCE, DISPLAY_ORDER, IS_DISABLED, ENTRY_DT, UPDATE_DT
-FROM ACCOUNTS
-WHERE USER_ID = ? AND ACCOUNT_CODE = ?
+SELECT a.ACCOUNT_ID, a.USER_ID, a.ACCOUNT_CODE, a.ACCOUNT_NAME, a.TEMPLATE_CODE,
+       a.INITIAL_BALANCE, a.DISPLAY_ORDER, a.IS_DISABLED, a.ENTRY_DT, a.UPDATE_DT,
+       COALESCE(t.ACCOUNT_CLASS, 0) AS ACCOUNT_CLASS, a.CURRENCY_CODE
+FROM ACCOUNTS a
+LEFT JOIN ACCOUNT_TEMPLATES t ON t.TEMPLATE_CODE = a.TEMPLATE_CODE
+WHERE a.USER_ID = ? AND a.ACCOUNT_CODE = ?
 "#;
 
 pub const ACCOUNT_CHECK_DUPLICATE_CODE: &str = r#"
@@ -539,6 +548,19 @@ SET ACCOUNT_NAME = ?, TEMPLATE_CODE = ?, INITIAL_BALANCE = ?,
 WHERE USER_ID = ? AND ACCOUNT_CODE = ?
 "#;
 
+pub const ACCOUNT_UPDATE_CURRENCY: &str = r#"
+UPDATE ACCOUNTS
+SET CURRENCY_CODE = ?, UPDATE_DT = datetime('now')
+WHERE USER_ID = ? AND ACCOUNT_CODE = ?
+"#;
+
+// Headers booked on an account, including disabled and scheduled ones
+pub const ACCOUNT_COUNT_TRANSACTIONS: &str = r#"
+SELECT COUNT(*)
+FROM TRANSACTIONS_HEADER
+WHERE USER_ID = ? AND (FROM_ACCOUNT_CODE = ? OR TO_ACCOUNT_CODE = ?)
+"#;
+
 pub const ACCOUNT_DELETE_LOGICAL: &str = r#"
 UPDATE ACCOUNTS 
 SET IS_DISABLED = 1, UPDATE_DT = datetime('now')
@@ -569,16 +591,19 @@ WHERE USER_ID = ? AND HOLIDAY_DATE BETWEEN ? AND ?
 "#;
 
 pub const ACCOUNT_TEMPLATE_GET_NONE: &str = r#"
-SELECT TEMPLATE_ID, TEMPLATE_CODE, TEMPLATE_NAME_JA, TEMPLATE_NAME_EN, DISPLAY_ORDER, ENTRY_DT
+SELECT TEMPLATE_ID, TEMPLATE_CODE, TEMPLATE_NAME_JA, TEMPLATE_NAME_EN, DISPLAY_ORDER,
+       ACCOUNT_CLASS, ENTRY_DT
 FROM ACCOUNT_TEMPLATES
 WHERE TEMPLATE_CODE = 'NONE'
 "#;
 
 pub const ACCOUNT_LIST_ALL: &str = r#"
-SELECT ACCOUNT_ID, USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE,
-       INITIAL_BALANCE, DISPLAY_ORDER, IS_DISABLED, ENTRY_DT, UPDATE_DT
-FROM ACCOUNTS
-ORDER BY USER_ID, DISPLAY_ORDER
+SELECT a.ACCOUNT_ID, a.USER_ID, a.ACCOUNT_CODE, a.ACCOUNT_NAME, a.TEMPLATE_CODE,
+       a.INITIAL_BALANCE, a.DISPLAY_ORDER, a.IS_DISABLED, a.ENTRY_DT, a.UPDATE_DT,
+       COALESCE(t.ACCOUNT_CLASS, 0) AS ACCOUNT_CLASS, a.CURRENCY_CODE
+FROM ACCOUNTS a
+LEFT JOIN ACCOUNT_TEMPLATES t ON t.TEMPLATE_CODE = a.TEMPLATE_CODE
+ORDER BY a.USER_ID, a.DISPLAY_ORDER
 "#;
 
 /// Running balance of every active account for `user_id`, counted up to and
@@ -590,14 +615,23 @@ ORDER BY USER_ID, DISPLAY_ORDER
 ///   - TRANSFER with FROM_ACCOUNT = a.ACCOUNT_CODE
 ///
 /// The single-pass CASE keeps a transfer visible to both source and
-/// destination accounts with sign flipped per side. Bindings: (as_of_date,
-/// user_id).
+/// destination accounts with sign flipped per side. For liability
+/// accounts (ACCOUNT_CLASS = 1) INITIAL_BALANCE is the amount owed and
+/// the result is flipped, so BALANCE is the outstanding debt: card
+/// spending raises it and a TRANSFER paying the bill lowers it.
+/// BALANCE is in the account's own currency; headers always share the
+/// currency of the accounts they touch, so no conversion happens here.
+/// Reads TRANSACTION_MOVEMENTS so each leg of a split header lands on its
+/// own accounts. Bindings: (as_of_date, user_id).
 pub const ACCOUNT_BALANCES_AS_OF: &str = r#"
 SELECT
     a.ACCOUNT_CODE,
     a.ACCOUNT_NAME,
+    COALESCE(t.ACCOUNT_CLASS, 0) AS ACCOUNT_CLASS,
+    a.CURRENCY_CODE,
     a.INITIAL_BALANCE
-        + COALESCE(SUM(CASE
+        + CASE WHEN COALESCE(t.ACCOUNT_CLASS, 0) = 1 THEN -1 ELSE 1 END
+        * COALESCE(SUM(CASE
             WHEN th.CATEGORY1_CODE = 'INCOME'
                  AND th.TO_ACCOUNT_CODE = a.ACCOUNT_CODE
                 THEN th.TOTAL_AMOUNT
@@ -614,17 +648,105 @@ SELECT
         END), 0) AS BALANCE,
     a.DISPLAY_ORDER
 FROM ACCOUNTS a
-LEFT JOIN TRANSACTIONS_HEADER th
+LEFT JOIN ACCOUNT_TEMPLATES t ON t.TEMPLATE_CODE = a.TEMPLATE_CODE
+LEFT JOIN TRANSACTION_MOVEMENTS th
     ON th.USER_ID = a.USER_ID
    AND th.IS_SCHEDULED = 0
    AND DATE(th.TRANSACTION_DATE) <= DATE(?)
    AND ( th.FROM_ACCOUNT_CODE = a.ACCOUNT_CODE
       OR th.TO_ACCOUNT_CODE   = a.ACCOUNT_CODE )
 WHERE a.USER_ID = ? AND a.IS_DISABLED = 0
-GROUP BY a.ACCOUNT_CODE, a.ACCOUNT_NAME, a.INITIAL_BALANCE, a.DISPLAY_ORDER
+GROUP BY a.ACCOUNT_CODE, a.ACCOUNT_NAME, t.ACCOUNT_CLASS, a.CURRENCY_CODE, a.INITIAL_BALANCE, a.DISPLAY_ORDER
 ORDER BY a.DISPLAY_ORDER
 "#;
 
+// Every actualised movement touching one account, oldest first, for the
+// account ledger. A split header contributes one row per leg on the
+// account. The running balance and signed amount are computed in
+// `services::account::signed_amount` with the same rules as
+// ACCOUNT_BALANCES_AS_OF; rows before the requested start date are still
+// returned so the opening balance can be folded in the same pass.
+// Bindings: (account_code, account_code, user_id, account_code,
+// account_code, end_date, end_date).
+pub const ACCOUNT_LEDGER_ROWS: &str = r#"
+SELECT
+    mv.TRANSACTION_ID,
+    mv.TRANSACTION_DATE,
+    mv.CATEGORY1_CODE,
+    mv.FROM_ACCOUNT_CODE,
+    mv.TO_ACCOUNT_CODE,
+    CASE WHEN mv.FROM_ACCOUNT_CODE = ? THEN mv.TO_ACCOUNT_CODE
+         ELSE mv.FROM_ACCOUNT_CODE END AS COUNTERPART_ACCOUNT_CODE,
+    ca.ACCOUNT_NAME AS COUNTERPART_ACCOUNT_NAME,
+    mv.TOTAL_AMOUNT,
+    h.RECONCILE_STATUS,
+    s.SHOP_NAME,
+    m.MEMO_TEXT
+FROM TRANSACTION_MOVEMENTS mv
+JOIN TRANSACTIONS_HEADER h
+    ON h.USER_ID = mv.USER_ID AND h.TRANSACTION_ID = mv.TRANSACTION_ID
+LEFT JOIN ACCOUNTS ca
+    ON ca.USER_ID = mv.USER_ID
+   AND ca.ACCOUNT_CODE = CASE WHEN mv.FROM_ACCOUNT_CODE = ? THEN mv.TO_ACCOUNT_CODE
+                              ELSE mv.FROM_ACCOUNT_CODE END
+LEFT JOIN SHOPS s ON s.SHOP_ID = h.SHOP_ID
+LEFT JOIN MEMOS m ON m.MEMO_ID = h.MEMO_ID
+WHERE mv.USER_ID = ?
+  AND mv.IS_SCHEDULED = 0
+  AND (mv.FROM_ACCOUNT_CODE = ? OR mv.TO_ACCOUNT_CODE = ?)
+  AND (? IS NULL OR DATE(mv.TRANSACTION_DATE) <= DATE(?))
+ORDER BY mv.TRANSACTION_DATE, mv.TRANSACTION_ID, mv.CATEGORY1_CODE,
+         mv.FROM_ACCOUNT_CODE, mv.TO_ACCOUNT_CODE
+"#;
+
+/// Net change per active account and calendar day, up to and including
+/// `?` (end_date). Same CASE as ACCOUNT_BALANCES_AS_OF so the balance
+/// history series agrees with the as-of balances on every point; the caller
+/// adds INITIAL_BALANCE and accumulates the deltas in one sweep.
+/// Bindings: (end_date, user_id).
+pub const ACCOUNT_BALANCE_DAILY_DELTAS: &str = r#"
+SELECT
+    a.ACCOUNT_CODE,
+    DATE(th.TRANSACTION_DATE) AS DAY,
+    SUM(CASE
+        WHEN th.CATEGORY1_CODE = 'INCOME'
+             AND th.TO_ACCOUNT_CODE = a.ACCOUNT_CODE
+            THEN th.TOTAL_AMOUNT
+        WHEN th.CATEGORY1_CODE = 'EXPENSE'
+             AND th.FROM_ACCOUNT_CODE = a.ACCOUNT_CODE
+            THEN -th.TOTAL_AMOUNT
+        WHEN th.CATEGORY1_CODE = 'TRANSFER'
+             AND th.TO_ACCOUNT_CODE = a.ACCOUNT_CODE
+            THEN th.TOTAL_AMOUNT
+        WHEN th.CATEGORY1_CODE = 'TRANSFER'
+             AND th.FROM_ACCOUNT_CODE = a.ACCOUNT_CODE
+            THEN -th.TOTAL_AMOUNT
+        ELSE 0
+    END) AS DELTA
+FROM ACCOUNTS a
+JOIN TRANSACTION_MOVEMENTS th
+    ON th.USER_ID = a.USER_ID
+   AND th.IS_SCHEDULED = 0
+   AND DATE(th.TRANSACTION_DATE) <= DATE(?)
+   AND ( th.FROM_ACCOUNT_CODE = a.ACCOUNT_CODE
+      OR th.TO_ACCOUNT_CODE   = a.ACCOUNT_CODE )
+WHERE a.USER_ID = ? AND a.IS_DISABLED = 0
+GROUP BY a.ACCOUNT_CODE, DATE(th.TRANSACTION_DATE)
+ORDER BY DAY
+"#;
+
+// Liability templates. The dbaccess.sql seed predates account classes and
+// inserts every template as an asset, so the class is (re)applied here on
+// every startup.
+pub const ACCOUNT_TEMPLATE_INSERT_LOAN: &str = r#"
+INSERT OR IGNORE INTO ACCOUNT_TEMPLATES
+    (TEMPLATE_CODE, TEMPLATE_NAME_JA, TEMPLATE_NAME_EN, DISPLAY_ORDER, ACCOUNT_CLASS)
+VALUES ('LOAN', 'ローン', 'Loan', 6, 1)
+"#;
+
+pub const ACCOUNT_TEMPLATE_MARK_LIABILITIES: &str =
+    "UPDATE ACCOUNT_TEMPLATES SET ACCOUNT_CLASS = 1 WHERE TEMPLATE_CODE IN ('CREDIT', 'LOAN')";
+
 // ============================================================================
 // Database Service Queries
 // ============================================================================
@@ -649,6 +771,7 @@ CREATE TABLE USERS (
     NAME TEXT NOT NULL,
     PAW TEXT NOT NULL,
     ROLE INTEGER NOT NULL,
+    BASE_CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY',
     ENTRY_DT DATETIME NOT NULL,
     UPDATE_DT DATETIME
 )
@@ -671,6 +794,7 @@ CREATE TABLE ACCOUNT_TEMPLATES (
     TEMPLATE_NAME_JA TEXT NOT NULL,
     TEMPLATE_NAME_EN TEXT NOT NULL,
     DISPLAY_ORDER INTEGER,
+    ACCOUNT_CLASS INTEGER NOT NULL DEFAULT 0,
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now'))
 )
 "#;
@@ -685,6 +809,7 @@ CREATE TABLE ACCOUNTS (
     INITIAL_BALANCE INTEGER DEFAULT 0,
     DISPLAY_ORDER INTEGER,
     IS_DISABLED INTEGER DEFAULT 0,
+    CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY',
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
     FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID),
@@ -700,6 +825,11 @@ VALUES
 ('BANK', '銀行口座', 'Bank Account', 2)
 "#;
 
+pub const TEST_ACCOUNT_INSERT_CREDIT_TEMPLATE: &str = r#"
+INSERT INTO ACCOUNT_TEMPLATES (TEMPLATE_CODE, TEMPLATE_NAME_JA, TEMPLATE_NAME_EN, DISPLAY_ORDER, ACCOUNT_CLASS)
+VALUES ('CREDIT', 'クレジットカード', 'Credit Card', 3, 1)
+"#;
+
 pub const TEST_INSERT_USER_ADMIN: &str = "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (1, 'admin', 'dummy', 0, datetime('now'))";
 
 pub const TEST_INSERT_USER_GENERAL: &str = "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'testuser', 'dummy', 1, datetime('now'))";
@@ -847,32 +977,67 @@ SELECT
     t.TRANSACTION_ID, t.USER_ID, t.SHOP_ID, t.TRANSACTION_DATE,
     t.CATEGORY1_CODE, t.FROM_ACCOUNT_CODE, t.TO_ACCOUNT_CODE,
     t.TOTAL_AMOUNT, t.TAX_ROUNDING_TYPE, t.MEMO_ID, t.IS_SCHEDULED,
+    t.RECONCILE_STATUS, t.CURRENCY_CODE,
     c1.CATEGORY1_NAME,
     a1.ACCOUNT_NAME as FROM_ACCOUNT_NAME,
     a2.ACCOUNT_NAME as TO_ACCOUNT_NAME,
-    m.MEMO_TEXT
+    m.MEMO_TEXT,
+    COALESCE(rr.IS_ESTIMATED, 0) AS IS_ESTIMATED
 FROM TRANSACTIONS_HEADER t
 LEFT JOIN CATEGORY1 c1 ON t.USER_ID = c1.USER_ID AND t.CATEGORY1_CODE = c1.CATEGORY1_CODE
 LEFT JOIN ACCOUNTS a1 ON t.USER_ID = a1.USER_ID AND t.FROM_ACCOUNT_CODE = a1.ACCOUNT_CODE
 LEFT JOIN ACCOUNTS a2 ON t.USER_ID = a2.USER_ID AND t.TO_ACCOUNT_CODE = a2.ACCOUNT_CODE
 LEFT JOIN MEMOS m ON t.MEMO_ID = m.MEMO_ID
+LEFT JOIN RECURRING_RULES rr ON rr.RULE_ID = t.RULE_ID
 WHERE 
 "#;
 
 pub const TRANSACTION_LIST_ORDER: &str = " ORDER BY t.TRANSACTION_DATE DESC, t.TRANSACTION_ID DESC";
-
-// Keyword filter: substring match against memo text on the header row and
-// on any detail row of the header. Two `?` placeholders — bind the same
-// escaped LIKE pattern twice. Paired with `ESCAPE '\'` so caller-supplied
-// LIKE metacharacters (`%`, `_`, `\`) match literally.
-pub const TRANSACTION_KEYWORD_MEMO_FILTER: &str = r"(EXISTS (SELECT 1 FROM MEMOS mh
-    WHERE mh.MEMO_ID = t.MEMO_ID
-      AND mh.MEMO_TEXT LIKE ? ESCAPE '\')
+pub const TRANSACTION_LIST_ORDER_DATE_ASC: &str = " ORDER BY t.TRANSACTION_DATE ASC, t.TRANSACTION_ID ASC";
+pub const TRANSACTION_LIST_ORDER_AMOUNT_DESC: &str =
+    " ORDER BY t.TOTAL_AMOUNT DESC, t.TRANSACTION_DATE DESC, t.TRANSACTION_ID DESC";
+pub const TRANSACTION_LIST_ORDER_AMOUNT_ASC: &str =
+    " ORDER BY t.TOTAL_AMOUNT ASC, t.TRANSACTION_DATE DESC, t.TRANSACTION_ID DESC";
+
+// Keyword filter through the full-text index. Bindings: (match expression,
+// user_id). The list query binds every parameter as text, so the user id is
+// cast back to the integer stored in the index.
+pub const TRANSACTION_KEYWORD_SEARCH_FILTER: &str = "t.TRANSACTION_ID IN (SELECT rowid FROM TRANSACTION_SEARCH
+    WHERE TRANSACTION_SEARCH MATCH ? AND USER_ID = CAST(? AS INTEGER))";
+
+// Keyword fallback for terms too short for the trigram index: one filter per
+// term. Bindings: (user_id, 4 escaped LIKE patterns).
+pub const TRANSACTION_KEYWORD_LIKE_FILTER: &str = r"t.TRANSACTION_ID IN (SELECT ts.rowid FROM TRANSACTION_SEARCH ts
+    WHERE ts.USER_ID = CAST(? AS INTEGER)
+      AND (ts.MEMO_TEXT LIKE ? ESCAPE '\'
+        OR ts.ITEM_NAMES LIKE ? ESCAPE '\'
+        OR ts.SHOP_NAME LIKE ? ESCAPE '\'
+        OR ts.PRODUCT_NAMES LIKE ? ESCAPE '\'))";
+
+// Account filter: either side of the header, or either side of any split
+// detail line. Bind the account code four times.
+pub const TRANSACTION_ACCOUNT_FILTER: &str = r"(t.FROM_ACCOUNT_CODE = ? OR t.TO_ACCOUNT_CODE = ?
    OR EXISTS (SELECT 1 FROM TRANSACTIONS_DETAIL td
-    JOIN MEMOS md ON md.MEMO_ID = td.MEMO_ID
     WHERE td.USER_ID = t.USER_ID
       AND td.TRANSACTION_ID = t.TRANSACTION_ID
-      AND md.MEMO_TEXT LIKE ? ESCAPE '\'))";
+      AND (td.FROM_ACCOUNT_CODE = ? OR td.TO_ACCOUNT_CODE = ?)))";
+
+pub const TRANSACTION_PRODUCT_FILTER: &str = r"EXISTS (SELECT 1 FROM TRANSACTIONS_DETAIL td
+    WHERE td.USER_ID = t.USER_ID
+      AND td.TRANSACTION_ID = t.TRANSACTION_ID
+      AND td.PRODUCT_ID = ?)";
+
+pub const TRANSACTION_MANUFACTURER_FILTER: &str = r"EXISTS (SELECT 1 FROM TRANSACTIONS_DETAIL td
+    JOIN PRODUCTS p ON p.USER_ID = td.USER_ID AND p.PRODUCT_ID = td.PRODUCT_ID
+    WHERE td.USER_ID = t.USER_ID
+      AND td.TRANSACTION_ID = t.TRANSACTION_ID
+      AND p.MANUFACTURER_ID = ?)";
+
+// Item-name filter: bind an escaped LIKE pattern, as for the keyword filter.
+pub const TRANSACTION_ITEM_NAME_FILTER: &str = r"EXISTS (SELECT 1 FROM TRANSACTIONS_DETAIL td
+    WHERE td.USER_ID = t.USER_ID
+      AND td.TRANSACTION_ID = t.TRANSACTION_ID
+      AND td.ITEM_NAME LIKE ? ESCAPE '\')";
 
 
 // ============================================================================
@@ -931,6 +1096,8 @@ CREATE TABLE IF NOT EXISTS TRANSACTIONS_DETAIL (
     AMOUNT_INCLUDING_TAX INTEGER,
     PRODUCT_ID INTEGER,
     MEMO_ID INTEGER,
+    FROM_ACCOUNT_CODE VARCHAR(50),
+    TO_ACCOUNT_CODE VARCHAR(50),
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
     FOREIGN KEY (TRANSACTION_ID) REFERENCES TRANSACTIONS_HEADER(TRANSACTION_ID) ON DELETE CASCADE,
@@ -1127,6 +1294,10 @@ FROM TRANSACTIONS_DETAIL
 // supersedes the older IS_END_OF_MONTH flag (which lacked DayOfMonthOrEnd).
 // Group membership of generated occurrences is established by the RULE_ID
 // foreign key on each TRANSACTIONS_HEADER row (no linked-list bookkeeping).
+// END_DATE is NULL for an open-ended rule, whose occurrences are generated
+// a rolling window ahead; MATERIALIZED_UNTIL is where generation stopped.
+// IS_ESTIMATED = 1 marks TOTAL_AMOUNT as an estimate (utility bills, card
+// statements); the actual amount is entered when an occurrence is confirmed.
 pub const CREATE_RECURRING_RULES_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS RECURRING_RULES (
     RULE_ID INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -1142,7 +1313,46 @@ CREATE TABLE IF NOT EXISTS RECURRING_RULES (
     MONTH_OF_YEAR INTEGER,
     HOLIDAY_SHIFT_TYPE INTEGER DEFAULT 0,
     START_DATE DATE NOT NULL,
-    END_DATE DATE NOT NULL,
+    END_DATE DATE,
+    MATERIALIZED_UNTIL DATE,
+    SHOP_ID INTEGER,
+    CATEGORY1_CODE VARCHAR(50) NOT NULL,
+    FROM_ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    TO_ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    TOTAL_AMOUNT INTEGER NOT NULL,
+    TAX_ROUNDING_TYPE INTEGER DEFAULT 0,
+    TAX_INCLUDED_TYPE INTEGER DEFAULT 1 NOT NULL,
+    MEMO_ID INTEGER,
+    IS_ESTIMATED INTEGER NOT NULL DEFAULT 0,
+    IS_DISABLED INTEGER DEFAULT 0,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now', 'localtime')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    FOREIGN KEY (USER_ID, CATEGORY1_CODE) REFERENCES CATEGORY1(USER_ID, CATEGORY1_CODE),
+    FOREIGN KEY (USER_ID, FROM_ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE),
+    FOREIGN KEY (USER_ID, TO_ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE)
+)
+"#;
+
+// Rebuild of RECURRING_RULES for databases created before open-ended rules:
+// END_DATE loses its NOT NULL and MATERIALIZED_UNTIL is added.
+pub const MIGRATE_RECURRING_RULES_CREATE_NEW: &str = r#"
+CREATE TABLE RECURRING_RULES_NEW (
+    RULE_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    RULE_NAME TEXT,
+    PERIOD_UNIT TEXT NOT NULL,
+    PERIOD_INTERVAL INTEGER NOT NULL,
+    ANCHOR_DATE DATE,
+    DAY_OF_WEEK INTEGER,
+    MONTH_DAY_RULE_TYPE TEXT,
+    DAY_OF_MONTH INTEGER,
+    WEEK_OF_MONTH INTEGER,
+    MONTH_OF_YEAR INTEGER,
+    HOLIDAY_SHIFT_TYPE INTEGER DEFAULT 0,
+    START_DATE DATE NOT NULL,
+    END_DATE DATE,
+    MATERIALIZED_UNTIL DATE,
     SHOP_ID INTEGER,
     CATEGORY1_CODE VARCHAR(50) NOT NULL,
     FROM_ACCOUNT_CODE VARCHAR(50) NOT NULL,
@@ -1161,12 +1371,79 @@ CREATE TABLE IF NOT EXISTS RECURRING_RULES (
 )
 "#;
 
-// 1:1 with RECURRING_RULES enforced by RULE_ID UNIQUE (DB-level prong of the
-// triple defense; frontend + backend validation are the other two).
+// Rules created before open-ended support were materialized up to END_DATE
+// at creation time.
+pub const MIGRATE_RECURRING_RULES_COPY_DATA: &str = r#"
+INSERT INTO RECURRING_RULES_NEW (
+    RULE_ID, USER_ID, RULE_NAME,
+    PERIOD_UNIT, PERIOD_INTERVAL,
+    ANCHOR_DATE, DAY_OF_WEEK, MONTH_DAY_RULE_TYPE,
+    DAY_OF_MONTH, WEEK_OF_MONTH, MONTH_OF_YEAR,
+    HOLIDAY_SHIFT_TYPE,
+    START_DATE, END_DATE, MATERIALIZED_UNTIL,
+    SHOP_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE,
+    TOTAL_AMOUNT, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, MEMO_ID,
+    IS_DISABLED, ENTRY_DT, UPDATE_DT
+)
+SELECT
+    RULE_ID, USER_ID, RULE_NAME,
+    PERIOD_UNIT, PERIOD_INTERVAL,
+    ANCHOR_DATE, DAY_OF_WEEK, MONTH_DAY_RULE_TYPE,
+    DAY_OF_MONTH, WEEK_OF_MONTH, MONTH_OF_YEAR,
+    HOLIDAY_SHIFT_TYPE,
+    START_DATE, END_DATE, END_DATE,
+    SHOP_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE,
+    TOTAL_AMOUNT, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, MEMO_ID,
+    IS_DISABLED, ENTRY_DT, UPDATE_DT
+FROM RECURRING_RULES
+"#;
+
+pub const MIGRATE_RECURRING_RULES_DROP_OLD: &str = "DROP TABLE RECURRING_RULES";
+
+pub const MIGRATE_RECURRING_RULES_RENAME_NEW: &str = "ALTER TABLE RECURRING_RULES_NEW RENAME TO RECURRING_RULES";
+
+// dbaccess.sql creates this index; the rebuild above drops it with the old table.
+pub const CREATE_RECURRING_RULES_USER_INDEX: &str =
+    "CREATE INDEX IF NOT EXISTS idx_recurring_rules_user ON RECURRING_RULES(USER_ID, IS_DISABLED)";
+
+pub const MIGRATE_RECURRING_RULES_ADD_IS_ESTIMATED: &str =
+    "ALTER TABLE RECURRING_RULES ADD COLUMN IS_ESTIMATED INTEGER NOT NULL DEFAULT 0";
+
+// DETAIL templates of a rule, copied in RULE_DETAIL_ID order into every
+// generated occurrence.
 pub const CREATE_RECURRING_RULE_DETAILS_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS RECURRING_RULE_DETAILS (
     RULE_DETAIL_ID INTEGER PRIMARY KEY AUTOINCREMENT,
-    RULE_ID INTEGER NOT NULL UNIQUE,
+    RULE_ID INTEGER NOT NULL,
+    USER_ID INTEGER NOT NULL,
+    CATEGORY1_CODE VARCHAR(50) NOT NULL,
+    CATEGORY2_CODE VARCHAR(50),
+    CATEGORY3_CODE VARCHAR(50),
+    ITEM_NAME TEXT NOT NULL,
+    AMOUNT INTEGER NOT NULL,
+    TAX_AMOUNT INTEGER DEFAULT 0,
+    TAX_RATE INTEGER DEFAULT 8,
+    AMOUNT_INCLUDING_TAX INTEGER,
+    PRODUCT_ID INTEGER,
+    MEMO_ID INTEGER,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (RULE_ID) REFERENCES RECURRING_RULES(RULE_ID) ON DELETE CASCADE,
+    FOREIGN KEY (PRODUCT_ID) REFERENCES PRODUCTS(PRODUCT_ID) ON DELETE SET NULL,
+    FOREIGN KEY (MEMO_ID) REFERENCES MEMOS(MEMO_ID),
+    CHECK (ITEM_NAME != '')
+)
+"#;
+
+pub const CREATE_RECURRING_RULE_DETAILS_INDEX: &str =
+    "CREATE INDEX IF NOT EXISTS idx_recurring_rule_details_rule ON RECURRING_RULE_DETAILS(RULE_ID)";
+
+// Rebuild of RECURRING_RULE_DETAILS for databases created while rules had a
+// single DETAIL template: drops the UNIQUE on RULE_ID and adds PRODUCT_ID.
+pub const MIGRATE_RECURRING_RULE_DETAILS_CREATE_NEW: &str = r#"
+CREATE TABLE RECURRING_RULE_DETAILS_NEW (
+    RULE_DETAIL_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    RULE_ID INTEGER NOT NULL,
     USER_ID INTEGER NOT NULL,
     CATEGORY1_CODE VARCHAR(50) NOT NULL,
     CATEGORY2_CODE VARCHAR(50),
@@ -1176,15 +1453,54 @@ CREATE TABLE IF NOT EXISTS RECURRING_RULE_DETAILS (
     TAX_AMOUNT INTEGER DEFAULT 0,
     TAX_RATE INTEGER DEFAULT 8,
     AMOUNT_INCLUDING_TAX INTEGER,
+    PRODUCT_ID INTEGER,
     MEMO_ID INTEGER,
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
     FOREIGN KEY (RULE_ID) REFERENCES RECURRING_RULES(RULE_ID) ON DELETE CASCADE,
+    FOREIGN KEY (PRODUCT_ID) REFERENCES PRODUCTS(PRODUCT_ID) ON DELETE SET NULL,
     FOREIGN KEY (MEMO_ID) REFERENCES MEMOS(MEMO_ID),
     CHECK (ITEM_NAME != '')
 )
 "#;
 
+pub const MIGRATE_RECURRING_RULE_DETAILS_COPY_DATA: &str = r#"
+INSERT INTO RECURRING_RULE_DETAILS_NEW (
+    RULE_DETAIL_ID, RULE_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE,
+    ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX, MEMO_ID,
+    ENTRY_DT, UPDATE_DT
+)
+SELECT
+    RULE_DETAIL_ID, RULE_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE,
+    ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX, MEMO_ID,
+    ENTRY_DT, UPDATE_DT
+FROM RECURRING_RULE_DETAILS
+"#;
+
+pub const MIGRATE_RECURRING_RULE_DETAILS_DROP_OLD: &str = "DROP TABLE RECURRING_RULE_DETAILS";
+
+pub const MIGRATE_RECURRING_RULE_DETAILS_RENAME_NEW: &str =
+    "ALTER TABLE RECURRING_RULE_DETAILS_NEW RENAME TO RECURRING_RULE_DETAILS";
+
+// Per-occurrence exceptions of a rule, keyed by the date its cycle
+// generates: SKIP drops that occurrence, MOVE puts it on MOVED_TO_DATE.
+pub const CREATE_RECURRING_RULE_EXCEPTIONS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS RECURRING_RULE_EXCEPTIONS (
+    EXCEPTION_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    RULE_ID INTEGER NOT NULL,
+    USER_ID INTEGER NOT NULL,
+    OCCURRENCE_DATE DATE NOT NULL,
+    EXCEPTION_TYPE TEXT NOT NULL,
+    MOVED_TO_DATE DATE,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (RULE_ID) REFERENCES RECURRING_RULES(RULE_ID) ON DELETE CASCADE,
+    UNIQUE (RULE_ID, OCCURRENCE_DATE),
+    CHECK ((EXCEPTION_TYPE = 'SKIP' AND MOVED_TO_DATE IS NULL)
+        OR (EXCEPTION_TYPE = 'MOVE' AND MOVED_TO_DATE IS NOT NULL))
+)
+"#;
+
 // System-defined holidays (e.g. transcribed from the jpholiday crate).
 pub const CREATE_HOLIDAYS_STANDARD_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS HOLIDAYS_STANDARD (
@@ -1221,17 +1537,27 @@ INSERT INTO RECURRING_RULES (
     ANCHOR_DATE, DAY_OF_WEEK, MONTH_DAY_RULE_TYPE,
     DAY_OF_MONTH, WEEK_OF_MONTH, MONTH_OF_YEAR,
     HOLIDAY_SHIFT_TYPE,
-    START_DATE, END_DATE,
+    START_DATE, END_DATE, MATERIALIZED_UNTIL,
     SHOP_ID, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE,
-    TOTAL_AMOUNT, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, MEMO_ID
-) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    TOTAL_AMOUNT, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, MEMO_ID, IS_ESTIMATED
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
 "#;
 
 pub const RECURRING_RULE_DETAILS_INSERT: &str = r#"
 INSERT INTO RECURRING_RULE_DETAILS (
     RULE_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE,
-    ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX, MEMO_ID
-) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX, PRODUCT_ID, MEMO_ID
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const RECURRING_RULE_DETAILS_BY_RULE: &str = r#"
+SELECT
+    CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE,
+    ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX,
+    PRODUCT_ID, MEMO_ID
+FROM RECURRING_RULE_DETAILS
+WHERE RULE_ID = ? AND USER_ID = ?
+ORDER BY RULE_DETAIL_ID
 "#;
 
 // HEADER insert for a generated occurrence: IS_SCHEDULED is fixed to 1 here so
@@ -1242,18 +1568,32 @@ INSERT INTO TRANSACTIONS_HEADER (
     USER_ID, SHOP_ID, TRANSACTION_DATE, CATEGORY1_CODE,
     FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE,
     TOTAL_AMOUNT, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, MEMO_ID,
-    IS_SCHEDULED, RULE_ID, ENTRY_DT
-) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?, datetime('now', 'localtime'))
+    IS_SCHEDULED, RULE_ID, CURRENCY_CODE, ENTRY_DT
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?, ?, datetime('now', 'localtime'))
 "#;
 
 // Cascade-delete path for a recurring rule: drop every generated HEADER first
 // (their DETAILs cascade via the existing FK). Caller still has to DELETE the
 // RECURRING_RULES row afterwards (which cascades RECURRING_RULE_DETAILS).
+// Bindings: (rule_id, user_id). The rows a cascade delete or detach touches.
+pub const TRANSACTIONS_HEADER_IDS_BY_RULE: &str = r#"
+SELECT TRANSACTION_ID FROM TRANSACTIONS_HEADER
+WHERE RULE_ID = ? AND USER_ID = ?
+ORDER BY TRANSACTION_ID
+"#;
+
 pub const TRANSACTIONS_HEADER_DELETE_BY_RULE: &str = r#"
 DELETE FROM TRANSACTIONS_HEADER
 WHERE RULE_ID = ? AND USER_ID = ?
 "#;
 
+// Occurrences of a rule in the given RECONCILE_STATUS. A cascade delete is
+// refused while any of them is reconciled.
+pub const TRANSACTIONS_HEADER_COUNT_BY_RULE_AND_RECONCILE_STATUS: &str = r#"
+SELECT COUNT(*) FROM TRANSACTIONS_HEADER
+WHERE RULE_ID = ? AND USER_ID = ? AND RECONCILE_STATUS = ?
+"#;
+
 // Detach-mode path: orphan generated HEADERs from the rule before the rule
 // goes away. Used when the user wants to remove the rule template but keep
 // the already-generated occurrences as standalone scheduled transactions.
@@ -1270,6 +1610,100 @@ DELETE FROM RECURRING_RULES
 WHERE RULE_ID = ? AND USER_ID = ?
 "#;
 
+// Memo references of a rule and its DETAIL templates, read before an edit
+// so memos the edit leaves unreferenced can be removed afterwards. The rule
+// itself always yields a row (MEMO_ID may be NULL), so no rows at all means
+// the rule does not exist.
+pub const RECURRING_RULE_GET_MEMO_IDS: &str = r#"
+SELECT r.MEMO_ID
+FROM RECURRING_RULES r
+WHERE r.RULE_ID = ? AND r.USER_ID = ?
+UNION ALL
+SELECT d.MEMO_ID
+FROM RECURRING_RULE_DETAILS d
+WHERE d.RULE_ID = ? AND d.USER_ID = ? AND d.MEMO_ID IS NOT NULL
+"#;
+
+// Bind order matches RECURRING_RULES_INSERT minus USER_ID, followed by
+// RULE_ID and USER_ID.
+pub const RECURRING_RULES_UPDATE: &str = r#"
+UPDATE RECURRING_RULES SET
+    RULE_NAME = ?,
+    PERIOD_UNIT = ?, PERIOD_INTERVAL = ?,
+    ANCHOR_DATE = ?, DAY_OF_WEEK = ?, MONTH_DAY_RULE_TYPE = ?,
+    DAY_OF_MONTH = ?, WEEK_OF_MONTH = ?, MONTH_OF_YEAR = ?,
+    HOLIDAY_SHIFT_TYPE = ?,
+    START_DATE = ?, END_DATE = ?, MATERIALIZED_UNTIL = ?,
+    SHOP_ID = ?, CATEGORY1_CODE = ?, FROM_ACCOUNT_CODE = ?, TO_ACCOUNT_CODE = ?,
+    TOTAL_AMOUNT = ?, TAX_ROUNDING_TYPE = ?, TAX_INCLUDED_TYPE = ?, MEMO_ID = ?,
+    IS_ESTIMATED = ?,
+    UPDATE_DT = datetime('now', 'localtime')
+WHERE RULE_ID = ? AND USER_ID = ?
+"#;
+
+// A rule edit replaces the DETAIL templates wholesale.
+pub const RECURRING_RULE_DETAILS_DELETE_BY_RULE: &str = r#"
+DELETE FROM RECURRING_RULE_DETAILS
+WHERE RULE_ID = ? AND USER_ID = ?
+"#;
+
+// Dates of the occurrences of a rule that have already been confirmed. A rule
+// edit never regenerates these, so their dates are not generated again.
+pub const TRANSACTIONS_HEADER_CONFIRMED_DATES_BY_RULE: &str = r#"
+SELECT DISTINCT date(TRANSACTION_DATE) AS OCCURRENCE_DATE
+FROM TRANSACTIONS_HEADER
+WHERE RULE_ID = ? AND USER_ID = ? AND IS_SCHEDULED = 0
+"#;
+
+// Still-scheduled occurrences of a rule dated on or after the given day
+// (YYYY-MM-DD). These are the rows a rule edit may rewrite or remove.
+pub const TRANSACTIONS_HEADER_SCHEDULED_BY_RULE_FROM: &str = r#"
+SELECT TRANSACTION_ID, date(TRANSACTION_DATE) AS OCCURRENCE_DATE, RECONCILE_STATUS
+FROM TRANSACTIONS_HEADER
+WHERE RULE_ID = ? AND USER_ID = ? AND IS_SCHEDULED = 1
+  AND date(TRANSACTION_DATE) >= ?
+ORDER BY TRANSACTION_DATE, TRANSACTION_ID
+"#;
+
+// Open-ended rules of a user whose occurrences stop short of the given
+// horizon (YYYY-MM-DD).
+pub const RECURRING_RULES_OPEN_ENDED_DUE: &str = r#"
+SELECT
+    r.RULE_ID, r.RULE_NAME,
+    r.PERIOD_UNIT, r.PERIOD_INTERVAL,
+    r.ANCHOR_DATE, r.DAY_OF_WEEK, r.MONTH_DAY_RULE_TYPE,
+    r.DAY_OF_MONTH, r.WEEK_OF_MONTH, r.MONTH_OF_YEAR,
+    r.HOLIDAY_SHIFT_TYPE,
+    r.START_DATE, r.MATERIALIZED_UNTIL,
+    r.SHOP_ID, r.CATEGORY1_CODE, r.FROM_ACCOUNT_CODE, r.TO_ACCOUNT_CODE,
+    r.TOTAL_AMOUNT, r.TAX_ROUNDING_TYPE, r.TAX_INCLUDED_TYPE, r.MEMO_ID,
+    r.IS_ESTIMATED, r.END_DATE
+FROM RECURRING_RULES r
+WHERE r.USER_ID = ? AND r.END_DATE IS NULL AND COALESCE(r.IS_DISABLED, 0) = 0
+  AND (r.MATERIALIZED_UNTIL IS NULL OR r.MATERIALIZED_UNTIL < ?)
+ORDER BY r.RULE_ID
+"#;
+
+pub const RECURRING_RULES_SET_MATERIALIZED_UNTIL: &str = r#"
+UPDATE RECURRING_RULES
+SET MATERIALIZED_UNTIL = ?
+WHERE RULE_ID = ? AND USER_ID = ?
+"#;
+
+// Dates that already have an occurrence of the rule, scheduled or confirmed.
+pub const TRANSACTIONS_HEADER_DATES_BY_RULE: &str = r#"
+SELECT DISTINCT date(TRANSACTION_DATE) AS OCCURRENCE_DATE
+FROM TRANSACTIONS_HEADER
+WHERE RULE_ID = ? AND USER_ID = ?
+"#;
+
+// Clears the lines of a kept occurrence before the new DETAIL template is
+// written to it.
+pub const TRANSACTIONS_DETAIL_DELETE_BY_TRANSACTION: &str = r#"
+DELETE FROM TRANSACTIONS_DETAIL
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
 // List active recurring rules for a user, with the number of occurrences
 // each one has currently materialized in TRANSACTIONS_HEADER. The LEFT JOIN
 // keeps rules that have not generated any rows yet (count = 0).
@@ -1281,8 +1715,10 @@ SELECT
     r.PERIOD_INTERVAL,
     r.START_DATE,
     r.END_DATE,
+    r.MATERIALIZED_UNTIL,
     r.TOTAL_AMOUNT,
     r.HOLIDAY_SHIFT_TYPE,
+    r.IS_ESTIMATED,
     COUNT(h.TRANSACTION_ID) AS OCCURRENCE_COUNT
 FROM RECURRING_RULES r
 LEFT JOIN TRANSACTIONS_HEADER h
@@ -1292,6 +1728,74 @@ GROUP BY r.RULE_ID
 ORDER BY r.RULE_ID DESC
 "#;
 
+pub const RECURRING_RULES_EXISTS_FOR_USER: &str =
+    "SELECT 1 FROM RECURRING_RULES WHERE RULE_ID = ? AND USER_ID = ?";
+
+// One stored rule, same columns as RECURRING_RULES_OPEN_ENDED_DUE.
+pub const RECURRING_RULE_GET_BY_ID: &str = r#"
+SELECT
+    r.RULE_ID, r.RULE_NAME,
+    r.PERIOD_UNIT, r.PERIOD_INTERVAL,
+    r.ANCHOR_DATE, r.DAY_OF_WEEK, r.MONTH_DAY_RULE_TYPE,
+    r.DAY_OF_MONTH, r.WEEK_OF_MONTH, r.MONTH_OF_YEAR,
+    r.HOLIDAY_SHIFT_TYPE,
+    r.START_DATE, r.MATERIALIZED_UNTIL,
+    r.SHOP_ID, r.CATEGORY1_CODE, r.FROM_ACCOUNT_CODE, r.TO_ACCOUNT_CODE,
+    r.TOTAL_AMOUNT, r.TAX_ROUNDING_TYPE, r.TAX_INCLUDED_TYPE, r.MEMO_ID,
+    r.IS_ESTIMATED, r.END_DATE
+FROM RECURRING_RULES r
+WHERE r.RULE_ID = ? AND r.USER_ID = ?
+"#;
+
+pub const RECURRING_RULE_EXCEPTIONS_BY_RULE: &str = r#"
+SELECT OCCURRENCE_DATE, EXCEPTION_TYPE, MOVED_TO_DATE
+FROM RECURRING_RULE_EXCEPTIONS
+WHERE RULE_ID = ? AND USER_ID = ?
+ORDER BY OCCURRENCE_DATE
+"#;
+
+// Bind order: RULE_ID, USER_ID, OCCURRENCE_DATE, EXCEPTION_TYPE, MOVED_TO_DATE.
+pub const RECURRING_RULE_EXCEPTIONS_UPSERT: &str = r#"
+INSERT INTO RECURRING_RULE_EXCEPTIONS (
+    RULE_ID, USER_ID, OCCURRENCE_DATE, EXCEPTION_TYPE, MOVED_TO_DATE
+) VALUES (?, ?, ?, ?, ?)
+ON CONFLICT (RULE_ID, OCCURRENCE_DATE) DO UPDATE SET
+    EXCEPTION_TYPE = excluded.EXCEPTION_TYPE,
+    MOVED_TO_DATE = excluded.MOVED_TO_DATE,
+    UPDATE_DT = datetime('now')
+"#;
+
+pub const RECURRING_RULE_EXCEPTIONS_DELETE: &str = r#"
+DELETE FROM RECURRING_RULE_EXCEPTIONS
+WHERE RULE_ID = ? AND USER_ID = ? AND OCCURRENCE_DATE = ?
+"#;
+
+// The rule's occurrence on a date, a scheduled row first when both exist.
+pub const TRANSACTIONS_HEADER_BY_RULE_ON_DATE: &str = r#"
+SELECT TRANSACTION_ID, IS_SCHEDULED, RECONCILE_STATUS
+FROM TRANSACTIONS_HEADER
+WHERE RULE_ID = ? AND USER_ID = ? AND date(TRANSACTION_DATE) = ?
+ORDER BY IS_SCHEDULED DESC, TRANSACTION_ID
+LIMIT 1
+"#;
+
+// Moves a scheduled occurrence to another date.
+pub const TRANSACTIONS_HEADER_SET_OCCURRENCE_DATE: &str = r#"
+UPDATE TRANSACTIONS_HEADER
+SET TRANSACTION_DATE = ?, UPDATE_DT = datetime('now')
+WHERE TRANSACTION_ID = ? AND USER_ID = ? AND IS_SCHEDULED = 1
+"#;
+
+// Totals of the rule's most recent confirmed occurrences, newest first,
+// LIMIT bound last. Feeds the estimate suggested for variable-amount rules.
+pub const RECURRING_RULE_RECENT_CONFIRMED_TOTALS: &str = r#"
+SELECT TOTAL_AMOUNT
+FROM TRANSACTIONS_HEADER
+WHERE RULE_ID = ? AND USER_ID = ? AND IS_SCHEDULED = 0
+ORDER BY TRANSACTION_DATE DESC, TRANSACTION_ID DESC
+LIMIT ?
+"#;
+
 // ============================================================================
 // Unspecified Master Data Insertion
 // ============================================================================
@@ -1322,8 +1826,8 @@ WHERE USER_ID = ?
 pub const TRANSACTION_HEADER_INSERT: &str = r#"
 INSERT INTO TRANSACTIONS_HEADER (
     USER_ID, SHOP_ID, TRANSACTION_DATE, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE,
-    TOTAL_AMOUNT, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, MEMO_ID, IS_SCHEDULED, ENTRY_DT
-) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'))
+    TOTAL_AMOUNT, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE, MEMO_ID, IS_SCHEDULED, CURRENCY_CODE, ENTRY_DT
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'))
 "#;
 
 pub const TRANSACTION_HEADER_GET_BY_ID: &str = r#"
@@ -1349,7 +1853,7 @@ pub const TRANSACTION_HEADER_UPDATE: &str = r#"
 UPDATE TRANSACTIONS_HEADER
 SET SHOP_ID = ?, TRANSACTION_DATE = ?, CATEGORY1_CODE = ?, FROM_ACCOUNT_CODE = ?,
     TO_ACCOUNT_CODE = ?, TOTAL_AMOUNT = ?, TAX_ROUNDING_TYPE = ?, TAX_INCLUDED_TYPE = ?,
-    MEMO_ID = ?, UPDATE_DT = datetime('now')
+    MEMO_ID = ?, CURRENCY_CODE = ?, UPDATE_DT = datetime('now')
 WHERE TRANSACTION_ID = ? AND USER_ID = ?
 "#;
 
@@ -1406,6 +1910,36 @@ SET IS_SCHEDULED = 0, UPDATE_DT = datetime('now')
 WHERE TRANSACTION_ID = ? AND USER_ID = ? AND IS_SCHEDULED = 1
 "#;
 
+// Scheduled header about to be confirmed with its actual amount:
+// RECONCILE_STATUS, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE.
+pub const TRANSACTION_HEADER_GET_SCHEDULED_FOR_CONFIRM: &str = r#"
+SELECT RECONCILE_STATUS, TAX_ROUNDING_TYPE, TAX_INCLUDED_TYPE
+FROM TRANSACTIONS_HEADER
+WHERE TRANSACTION_ID = ? AND USER_ID = ? AND IS_SCHEDULED = 1
+"#;
+
+pub const TRANSACTION_HEADER_SET_TOTAL_AMOUNT: &str = r#"
+UPDATE TRANSACTIONS_HEADER
+SET TOTAL_AMOUNT = ?, UPDATE_DT = datetime('now')
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+// DETAIL_ID, TAX_RATE, MEMO_ID of every line of a header, in line order.
+pub const TRANSACTION_DETAIL_LINES_BY_TRANSACTION: &str = r#"
+SELECT DETAIL_ID, TAX_RATE, MEMO_ID
+FROM TRANSACTIONS_DETAIL
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+ORDER BY DETAIL_ID
+"#;
+
+// Books a tax-included amount on one line; AMOUNT = AMOUNT_INCLUDING_TAX
+// makes the total calculation take it as is.
+pub const TRANSACTION_DETAIL_SET_INCLUDED_AMOUNT: &str = r#"
+UPDATE TRANSACTIONS_DETAIL
+SET AMOUNT = ?, AMOUNT_INCLUDING_TAX = ?, TAX_AMOUNT = ?, UPDATE_DT = datetime('now')
+WHERE DETAIL_ID = ? AND USER_ID = ?
+"#;
+
 // ============================================================================
 // Transaction Detail Queries
 // ============================================================================
@@ -1426,12 +1960,22 @@ ORDER BY DETAIL_ID
 "#;
 
 /// Read the minimal set of columns that drive `calculate_recommended_total`:
-/// pre-tax AMOUNT, the optional tax-included counterpart, and the per-detail
-/// tax rate. Scoped by USER_ID so callers can rely on session isolation.
+/// pre-tax AMOUNT, the optional tax-included counterpart, the per-detail
+/// tax rate, and LEG — the 1-based index of the (category, from, to) leg
+/// the line moves money on, resolved the same way as TRANSACTION_MOVEMENTS.
+/// Scoped by USER_ID so callers can rely on session isolation.
 pub const TRANSACTION_DETAIL_GET_FOR_RECALC: &str = r#"
-SELECT AMOUNT, AMOUNT_INCLUDING_TAX, TAX_RATE
-FROM TRANSACTIONS_DETAIL
-WHERE TRANSACTION_ID = ? AND USER_ID = ?
+SELECT d.AMOUNT, d.AMOUNT_INCLUDING_TAX, d.TAX_RATE,
+       DENSE_RANK() OVER (
+           PARTITION BY d.TRANSACTION_ID
+           ORDER BY CASE WHEN d.FROM_ACCOUNT_CODE IS NULL THEN h.CATEGORY1_CODE ELSE d.CATEGORY1_CODE END,
+                    COALESCE(d.FROM_ACCOUNT_CODE, h.FROM_ACCOUNT_CODE),
+                    COALESCE(d.TO_ACCOUNT_CODE, h.TO_ACCOUNT_CODE)
+       ) AS LEG
+FROM TRANSACTIONS_DETAIL d
+JOIN TRANSACTIONS_HEADER h
+    ON h.USER_ID = d.USER_ID AND h.TRANSACTION_ID = d.TRANSACTION_ID
+WHERE d.TRANSACTION_ID = ? AND d.USER_ID = ?
 "#;
 
 /// PR12 (Fable-5 #32): every detail row for a user in one SELECT,
@@ -1442,10 +1986,18 @@ WHERE TRANSACTION_ID = ? AND USER_ID = ?
 /// BY is inherited from the schema so callers get the same in-detail
 /// order as the older per-transaction query.
 pub const TRANSACTION_DETAIL_GET_ALL_FOR_USER_RECALC: &str = r#"
-SELECT TRANSACTION_ID, AMOUNT, AMOUNT_INCLUDING_TAX, TAX_RATE
-FROM TRANSACTIONS_DETAIL
-WHERE USER_ID = ?
-ORDER BY TRANSACTION_ID, DETAIL_ID
+SELECT d.TRANSACTION_ID, d.AMOUNT, d.AMOUNT_INCLUDING_TAX, d.TAX_RATE,
+       DENSE_RANK() OVER (
+           PARTITION BY d.TRANSACTION_ID
+           ORDER BY CASE WHEN d.FROM_ACCOUNT_CODE IS NULL THEN h.CATEGORY1_CODE ELSE d.CATEGORY1_CODE END,
+                    COALESCE(d.FROM_ACCOUNT_CODE, h.FROM_ACCOUNT_CODE),
+                    COALESCE(d.TO_ACCOUNT_CODE, h.TO_ACCOUNT_CODE)
+       ) AS LEG
+FROM TRANSACTIONS_DETAIL d
+JOIN TRANSACTIONS_HEADER h
+    ON h.USER_ID = d.USER_ID AND h.TRANSACTION_ID = d.TRANSACTION_ID
+WHERE d.USER_ID = ?
+ORDER BY d.TRANSACTION_ID, d.DETAIL_ID
 "#;
 
 pub const TRANSACTION_DETAIL_UPDATE: &str = r#"
@@ -1798,6 +2350,7 @@ SELECT
     fa.ACCOUNT_NAME as FROM_ACCOUNT_NAME,
     ta.ACCOUNT_NAME as TO_ACCOUNT_NAME,
     h.TOTAL_AMOUNT,
+    h.CURRENCY_CODE,
     h.TAX_ROUNDING_TYPE,
     h.TAX_INCLUDED_TYPE,
     h.MEMO_ID,
@@ -1835,6 +2388,8 @@ SELECT
     mf.MANUFACTURER_NAME,
     d.MEMO_ID,
     m.MEMO_TEXT,
+    d.FROM_ACCOUNT_CODE,
+    d.TO_ACCOUNT_CODE,
     d.ENTRY_DT,
     d.UPDATE_DT
 FROM TRANSACTIONS_DETAIL d
@@ -1854,8 +2409,9 @@ ORDER BY d.DETAIL_ID
 pub const TRANSACTION_DETAIL_INSERT_FULL: &str = r#"
 INSERT INTO TRANSACTIONS_DETAIL (
     TRANSACTION_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE,
-    ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX, PRODUCT_ID, MEMO_ID
-) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX, PRODUCT_ID, MEMO_ID,
+    FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
 "#;
 
 pub const TRANSACTION_DETAIL_UPDATE_FULL: &str = r#"
@@ -1870,13 +2426,16 @@ UPDATE TRANSACTIONS_DETAIL SET
     AMOUNT_INCLUDING_TAX = ?,
     PRODUCT_ID = ?,
     MEMO_ID = ?,
+    FROM_ACCOUNT_CODE = ?,
+    TO_ACCOUNT_CODE = ?,
     UPDATE_DT = datetime('now')
 WHERE DETAIL_ID = ? AND USER_ID = ?
 "#;
 
 pub const TRANSACTION_DETAIL_GET_BY_ID: &str = r#"
 SELECT DETAIL_ID, TRANSACTION_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE,
-       ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX, PRODUCT_ID, MEMO_ID, ENTRY_DT, UPDATE_DT
+       ITEM_NAME, AMOUNT, TAX_AMOUNT, TAX_RATE, AMOUNT_INCLUDING_TAX, PRODUCT_ID, MEMO_ID,
+       FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, ENTRY_DT, UPDATE_DT
 FROM TRANSACTIONS_DETAIL
 WHERE DETAIL_ID = ? AND USER_ID = ?
 "#;
@@ -1887,37 +2446,1647 @@ WHERE DETAIL_ID = ? AND USER_ID = ?
 "#;
 
 // ============================================================================
-// Transaction Service Test Queries
+// CSV Statement Import Queries
 // ============================================================================
 
-pub const TEST_TRANSACTION_CREATE_USERS_TABLE: &str = r#"
-CREATE TABLE USERS (
-    USER_ID INTEGER PRIMARY KEY,
-    NAME TEXT NOT NULL,
-    PAW TEXT NOT NULL,
-    ROLE INTEGER NOT NULL,
-    ENTRY_DT DATETIME NOT NULL,
-    UPDATE_DT DATETIME
-)
-"#;
-
-pub const TEST_TRANSACTION_INSERT_USER: &str = "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'testuser', 'hash', 1, datetime('now'))";
-
-pub const TEST_TRANSACTION_CREATE_MEMOS_TABLE: &str = r#"
-CREATE TABLE MEMOS (
-    MEMO_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+// One column-mapping profile per (user, account). Column indexes are 0-based
+// positions in the statement CSV. Either AMOUNT_COLUMN (single signed amount)
+// or the WITHDRAWAL_COLUMN / DEPOSIT_COLUMN pair (bank passbook layout) must
+// be set; the service rejects profiles that define neither.
+pub const CREATE_IMPORT_PROFILES_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS IMPORT_PROFILES (
+    PROFILE_ID INTEGER PRIMARY KEY AUTOINCREMENT,
     USER_ID INTEGER NOT NULL,
-    MEMO_TEXT TEXT NOT NULL,
+    ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    PROFILE_NAME TEXT NOT NULL,
+    DELIMITER TEXT NOT NULL DEFAULT ',',
+    SKIP_ROWS INTEGER NOT NULL DEFAULT 1,
+    DATE_COLUMN INTEGER NOT NULL,
+    DATE_FORMAT TEXT NOT NULL DEFAULT '%Y/%m/%d',
+    AMOUNT_COLUMN INTEGER,
+    WITHDRAWAL_COLUMN INTEGER,
+    DEPOSIT_COLUMN INTEGER,
+    DESCRIPTION_COLUMN INTEGER NOT NULL,
+    MEMO_COLUMN INTEGER,
+    NEGATE_AMOUNT INTEGER NOT NULL DEFAULT 0,
+    COUNTER_ACCOUNT_CODE VARCHAR(50) NOT NULL DEFAULT 'NONE',
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
-    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID)
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    FOREIGN KEY (USER_ID, ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE),
+    UNIQUE(USER_ID, ACCOUNT_CODE)
 )
 "#;
 
-pub const TEST_TRANSACTION_CREATE_CATEGORY1_TABLE: &str = r#"
-CREATE TABLE CATEGORY1 (
-    USER_ID INTEGER NOT NULL,
-    CATEGORY1_CODE VARCHAR(50) NOT NULL,
+pub const IMPORT_PROFILE_GET_BY_ACCOUNT: &str = r#"
+SELECT PROFILE_ID, USER_ID, ACCOUNT_CODE, PROFILE_NAME, DELIMITER, SKIP_ROWS,
+       DATE_COLUMN, DATE_FORMAT, AMOUNT_COLUMN, WITHDRAWAL_COLUMN, DEPOSIT_COLUMN,
+       DESCRIPTION_COLUMN, MEMO_COLUMN, NEGATE_AMOUNT, COUNTER_ACCOUNT_CODE,
+       ENTRY_DT, UPDATE_DT
+FROM IMPORT_PROFILES
+WHERE USER_ID = ? AND ACCOUNT_CODE = ?
+"#;
+
+pub const IMPORT_PROFILE_LIST_BY_USER: &str = r#"
+SELECT p.PROFILE_ID, p.USER_ID, p.ACCOUNT_CODE, p.PROFILE_NAME, p.DELIMITER, p.SKIP_ROWS,
+       p.DATE_COLUMN, p.DATE_FORMAT, p.AMOUNT_COLUMN, p.WITHDRAWAL_COLUMN, p.DEPOSIT_COLUMN,
+       p.DESCRIPTION_COLUMN, p.MEMO_COLUMN, p.NEGATE_AMOUNT, p.COUNTER_ACCOUNT_CODE,
+       p.ENTRY_DT, p.UPDATE_DT
+FROM IMPORT_PROFILES p
+LEFT JOIN ACCOUNTS a ON a.USER_ID = p.USER_ID AND a.ACCOUNT_CODE = p.ACCOUNT_CODE
+WHERE p.USER_ID = ?
+ORDER BY a.DISPLAY_ORDER, p.ACCOUNT_CODE
+"#;
+
+// Upsert keyed on UNIQUE(USER_ID, ACCOUNT_CODE) so "save" both creates the
+// first profile for an account and overwrites the mapping afterwards.
+// Bindings: (user_id, account_code, profile_name, delimiter, skip_rows,
+// date_column, date_format, amount_column, withdrawal_column, deposit_column,
+// description_column, memo_column, negate_amount, counter_account_code).
+pub const IMPORT_PROFILE_UPSERT: &str = r#"
+INSERT INTO IMPORT_PROFILES (
+    USER_ID, ACCOUNT_CODE, PROFILE_NAME, DELIMITER, SKIP_ROWS,
+    DATE_COLUMN, DATE_FORMAT, AMOUNT_COLUMN, WITHDRAWAL_COLUMN, DEPOSIT_COLUMN,
+    DESCRIPTION_COLUMN, MEMO_COLUMN, NEGATE_AMOUNT, COUNTER_ACCOUNT_CODE
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+ON CONFLICT(USER_ID, ACCOUNT_CODE) DO UPDATE SET
+    PROFILE_NAME = excluded.PROFILE_NAME,
+    DELIMITER = excluded.DELIMITER,
+    SKIP_ROWS = excluded.SKIP_ROWS,
+    DATE_COLUMN = excluded.DATE_COLUMN,
+    DATE_FORMAT = excluded.DATE_FORMAT,
+    AMOUNT_COLUMN = excluded.AMOUNT_COLUMN,
+    WITHDRAWAL_COLUMN = excluded.WITHDRAWAL_COLUMN,
+    DEPOSIT_COLUMN = excluded.DEPOSIT_COLUMN,
+    DESCRIPTION_COLUMN = excluded.DESCRIPTION_COLUMN,
+    MEMO_COLUMN = excluded.MEMO_COLUMN,
+    NEGATE_AMOUNT = excluded.NEGATE_AMOUNT,
+    COUNTER_ACCOUNT_CODE = excluded.COUNTER_ACCOUNT_CODE,
+    UPDATE_DT = datetime('now')
+"#;
+
+pub const IMPORT_PROFILE_DELETE: &str = r#"
+DELETE FROM IMPORT_PROFILES
+WHERE USER_ID = ? AND ACCOUNT_CODE = ?
+"#;
+
+pub const IMPORT_ACCOUNT_EXISTS_FOR_USER: &str = r#"
+SELECT 1 FROM ACCOUNTS
+WHERE USER_ID = ? AND ACCOUNT_CODE = ? AND IS_DISABLED = 0
+"#;
+
+// Number of headers already booked on the statement account for one
+// (date, amount) pair. The statement account may sit on either side of the
+// header (FROM for withdrawals, TO for deposits), so both are matched.
+// Scheduled rows are included: a materialised recurring occurrence for the
+// same day and amount is exactly what the statement line would duplicate.
+// Bindings: (user_id, date `YYYY-MM-DD`, total_amount, account_code, account_code).
+pub const IMPORT_COUNT_EXISTING_MATCHES: &str = r#"
+SELECT COUNT(*) FROM TRANSACTIONS_HEADER
+WHERE USER_ID = ?
+  AND DATE(TRANSACTION_DATE) = DATE(?)
+  AND TOTAL_AMOUNT = ?
+  AND (FROM_ACCOUNT_CODE = ? OR TO_ACCOUNT_CODE = ?)
+"#;
+
+// ============================================================================
+// Ledger Export Queries
+// ============================================================================
+
+// One row per detail line, with the parent header columns repeated and every
+// code resolved to its display name. LEFT JOIN on TRANSACTIONS_DETAIL keeps
+// headers that have no detail yet (DETAIL_ID comes back NULL). Category names
+// prefer the CATEGORY*_I18N row for the requested language and fall back to
+// the base name, matching CATEGORY1_LIST.
+// Bindings: (lang, lang, lang, user_id, start, start, end, end, include_scheduled).
+// start / end may be NULL for an open bound; include_scheduled is 0 or 1.
+pub const LEDGER_EXPORT_ROWS: &str = r#"
+SELECT
+    h.TRANSACTION_ID,
+    h.TRANSACTION_DATE,
+    h.CATEGORY1_CODE,
+    COALESCE(c1i.CATEGORY1_NAME_I18N, c1.CATEGORY1_NAME) AS CATEGORY1_NAME,
+    h.FROM_ACCOUNT_CODE,
+    fa.ACCOUNT_NAME AS FROM_ACCOUNT_NAME,
+    h.TO_ACCOUNT_CODE,
+    ta.ACCOUNT_NAME AS TO_ACCOUNT_NAME,
+    s.SHOP_NAME,
+    h.TOTAL_AMOUNT,
+    h.TAX_ROUNDING_TYPE,
+    h.TAX_INCLUDED_TYPE,
+    h.IS_SCHEDULED,
+    hm.MEMO_TEXT AS HEADER_MEMO,
+    d.DETAIL_ID,
+    d.CATEGORY2_CODE,
+    COALESCE(c2i.CATEGORY2_NAME_I18N, c2.CATEGORY2_NAME) AS CATEGORY2_NAME,
+    d.CATEGORY3_CODE,
+    COALESCE(c3i.CATEGORY3_NAME_I18N, c3.CATEGORY3_NAME) AS CATEGORY3_NAME,
+    d.ITEM_NAME,
+    d.AMOUNT,
+    d.TAX_RATE,
+    d.TAX_AMOUNT,
+    d.AMOUNT_INCLUDING_TAX,
+    p.PRODUCT_NAME,
+    mf.MANUFACTURER_NAME,
+    dm.MEMO_TEXT AS DETAIL_MEMO
+FROM TRANSACTIONS_HEADER h
+LEFT JOIN CATEGORY1 c1
+    ON c1.USER_ID = h.USER_ID AND c1.CATEGORY1_CODE = h.CATEGORY1_CODE
+LEFT JOIN CATEGORY1_I18N c1i
+    ON c1i.USER_ID = h.USER_ID AND c1i.CATEGORY1_CODE = h.CATEGORY1_CODE
+   AND c1i.LANG_CODE = ?
+LEFT JOIN ACCOUNTS fa ON fa.USER_ID = h.USER_ID AND fa.ACCOUNT_CODE = h.FROM_ACCOUNT_CODE
+LEFT JOIN ACCOUNTS ta ON ta.USER_ID = h.USER_ID AND ta.ACCOUNT_CODE = h.TO_ACCOUNT_CODE
+LEFT JOIN SHOPS s ON s.SHOP_ID = h.SHOP_ID AND s.USER_ID = h.USER_ID
+LEFT JOIN MEMOS hm ON hm.MEMO_ID = h.MEMO_ID
+LEFT JOIN TRANSACTIONS_DETAIL d
+    ON d.TRANSACTION_ID = h.TRANSACTION_ID AND d.USER_ID = h.USER_ID
+LEFT JOIN CATEGORY2 c2
+    ON c2.USER_ID = d.USER_ID AND c2.CATEGORY1_CODE = d.CATEGORY1_CODE
+   AND c2.CATEGORY2_CODE = d.CATEGORY2_CODE
+LEFT JOIN CATEGORY2_I18N c2i
+    ON c2i.USER_ID = d.USER_ID AND c2i.CATEGORY1_CODE = d.CATEGORY1_CODE
+   AND c2i.CATEGORY2_CODE = d.CATEGORY2_CODE AND c2i.LANG_CODE = ?
+LEFT JOIN CATEGORY3 c3
+    ON c3.USER_ID = d.USER_ID AND c3.CATEGORY1_CODE = d.CATEGORY1_CODE
+   AND c3.CATEGORY2_CODE = d.CATEGORY2_CODE AND c3.CATEGORY3_CODE = d.CATEGORY3_CODE
+LEFT JOIN CATEGORY3_I18N c3i
+    ON c3i.USER_ID = d.USER_ID AND c3i.CATEGORY1_CODE = d.CATEGORY1_CODE
+   AND c3i.CATEGORY2_CODE = d.CATEGORY2_CODE AND c3i.CATEGORY3_CODE = d.CATEGORY3_CODE
+   AND c3i.LANG_CODE = ?
+LEFT JOIN PRODUCTS p ON p.PRODUCT_ID = d.PRODUCT_ID AND p.USER_ID = d.USER_ID
+LEFT JOIN MANUFACTURERS mf ON mf.MANUFACTURER_ID = p.MANUFACTURER_ID AND mf.USER_ID = d.USER_ID
+LEFT JOIN MEMOS dm ON dm.MEMO_ID = d.MEMO_ID
+WHERE h.USER_ID = ?
+  AND (? IS NULL OR h.TRANSACTION_DATE >= ?)
+  AND (? IS NULL OR h.TRANSACTION_DATE <= ?)
+  AND (? = 1 OR h.IS_SCHEDULED = 0)
+ORDER BY h.TRANSACTION_DATE, h.TRANSACTION_ID, d.DETAIL_ID
+"#;
+
+// ============================================================================
+// Budget Queries
+// ============================================================================
+
+// One planned amount per (user, month, category node). CATEGORY2_CODE /
+// CATEGORY3_CODE are NULL for a budget set on a higher level, so a budget on
+// EXPENSE and one on EXPENSE/FOOD can coexist for the same month.
+pub const CREATE_BUDGETS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS BUDGETS (
+    BUDGET_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    PERIOD_YEAR INTEGER NOT NULL,
+    PERIOD_MONTH INTEGER NOT NULL CHECK (PERIOD_MONTH BETWEEN 1 AND 12),
+    CATEGORY1_CODE VARCHAR(64) NOT NULL,
+    CATEGORY2_CODE VARCHAR(64),
+    CATEGORY3_CODE VARCHAR(64),
+    AMOUNT INTEGER NOT NULL CHECK (AMOUNT >= 0),
+    MEMO TEXT,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE
+)
+"#;
+
+// UNIQUE over nullable columns treats NULLs as distinct, so the key goes
+// through IFNULL in an expression index instead of a table constraint.
+pub const CREATE_BUDGETS_KEY_INDEX: &str = r#"
+CREATE UNIQUE INDEX IF NOT EXISTS IDX_BUDGETS_KEY ON BUDGETS (
+    USER_ID, PERIOD_YEAR, PERIOD_MONTH, CATEGORY1_CODE,
+    IFNULL(CATEGORY2_CODE, ''), IFNULL(CATEGORY3_CODE, '')
+)
+"#;
+
+// Budgets of one month with the deepest category level's localized name.
+// Bindings: (lang, lang, lang, user_id, period_year, period_month).
+pub const BUDGET_LIST_BY_PERIOD: &str = r#"
+SELECT
+    b.BUDGET_ID,
+    b.USER_ID,
+    b.PERIOD_YEAR,
+    b.PERIOD_MONTH,
+    b.CATEGORY1_CODE,
+    b.CATEGORY2_CODE,
+    b.CATEGORY3_CODE,
+    CASE
+        WHEN b.CATEGORY3_CODE IS NOT NULL THEN COALESCE(c3i.CATEGORY3_NAME_I18N, c3.CATEGORY3_NAME)
+        WHEN b.CATEGORY2_CODE IS NOT NULL THEN COALESCE(c2i.CATEGORY2_NAME_I18N, c2.CATEGORY2_NAME)
+        ELSE COALESCE(c1i.CATEGORY1_NAME_I18N, c1.CATEGORY1_NAME)
+    END AS CATEGORY_NAME,
+    b.AMOUNT,
+    b.MEMO,
+    b.ENTRY_DT,
+    b.UPDATE_DT
+FROM BUDGETS b
+LEFT JOIN CATEGORY1 c1
+    ON c1.USER_ID = b.USER_ID AND c1.CATEGORY1_CODE = b.CATEGORY1_CODE
+LEFT JOIN CATEGORY1_I18N c1i
+    ON c1i.USER_ID = b.USER_ID AND c1i.CATEGORY1_CODE = b.CATEGORY1_CODE
+    AND c1i.LANG_CODE = ?
+LEFT JOIN CATEGORY2 c2
+    ON c2.USER_ID = b.USER_ID AND c2.CATEGORY1_CODE = b.CATEGORY1_CODE
+    AND c2.CATEGORY2_CODE = b.CATEGORY2_CODE
+LEFT JOIN CATEGORY2_I18N c2i
+    ON c2i.USER_ID = b.USER_ID AND c2i.CATEGORY1_CODE = b.CATEGORY1_CODE
+    AND c2i.CATEGORY2_CODE = b.CATEGORY2_CODE AND c2i.LANG_CODE = ?
+LEFT JOIN CATEGORY3 c3
+    ON c3.USER_ID = b.USER_ID AND c3.CATEGORY1_CODE = b.CATEGORY1_CODE
+    AND c3.CATEGORY2_CODE = b.CATEGORY2_CODE AND c3.CATEGORY3_CODE = b.CATEGORY3_CODE
+LEFT JOIN CATEGORY3_I18N c3i
+    ON c3i.USER_ID = b.USER_ID AND c3i.CATEGORY1_CODE = b.CATEGORY1_CODE
+    AND c3i.CATEGORY2_CODE = b.CATEGORY2_CODE AND c3i.CATEGORY3_CODE = b.CATEGORY3_CODE
+    AND c3i.LANG_CODE = ?
+WHERE b.USER_ID = ? AND b.PERIOD_YEAR = ? AND b.PERIOD_MONTH = ?
+ORDER BY c1.DISPLAY_ORDER, b.CATEGORY1_CODE,
+         c2.DISPLAY_ORDER, IFNULL(b.CATEGORY2_CODE, ''),
+         c3.DISPLAY_ORDER, IFNULL(b.CATEGORY3_CODE, '')
+"#;
+
+// Bindings: (user_id, period_year, period_month, category1, category2, category3).
+pub const BUDGET_CHECK_DUPLICATE: &str = r#"
+SELECT COUNT(*) FROM BUDGETS
+WHERE USER_ID = ? AND PERIOD_YEAR = ? AND PERIOD_MONTH = ?
+  AND CATEGORY1_CODE = ?
+  AND IFNULL(CATEGORY2_CODE, '') = IFNULL(?, '')
+  AND IFNULL(CATEGORY3_CODE, '') = IFNULL(?, '')
+"#;
+
+// 1 when the category node a budget points at exists for the user, 0 otherwise.
+// Bindings: (category2, category3, user_id, category1, category2, category3).
+pub const BUDGET_CATEGORY_EXISTS: &str = r#"
+SELECT COUNT(*) FROM CATEGORY1 c1
+LEFT JOIN CATEGORY2 c2
+    ON c2.USER_ID = c1.USER_ID AND c2.CATEGORY1_CODE = c1.CATEGORY1_CODE
+    AND c2.CATEGORY2_CODE = ?
+LEFT JOIN CATEGORY3 c3
+    ON c3.USER_ID = c2.USER_ID AND c3.CATEGORY1_CODE = c2.CATEGORY1_CODE
+    AND c3.CATEGORY2_CODE = c2.CATEGORY2_CODE AND c3.CATEGORY3_CODE = ?
+WHERE c1.USER_ID = ? AND c1.CATEGORY1_CODE = ?
+  AND (? IS NULL OR c2.CATEGORY2_CODE IS NOT NULL)
+  AND (? IS NULL OR c3.CATEGORY3_CODE IS NOT NULL)
+"#;
+
+pub const BUDGET_INSERT: &str = r#"
+INSERT INTO BUDGETS (
+    USER_ID, PERIOD_YEAR, PERIOD_MONTH,
+    CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE, AMOUNT, MEMO
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const BUDGET_UPDATE: &str = r#"
+UPDATE BUDGETS
+SET AMOUNT = ?, MEMO = ?, UPDATE_DT = datetime('now')
+WHERE USER_ID = ? AND BUDGET_ID = ?
+"#;
+
+pub const BUDGET_DELETE: &str = r#"
+DELETE FROM BUDGETS
+WHERE USER_ID = ? AND BUDGET_ID = ?
+"#;
+
+// ============================================================================
+// Budget Rollover Queries
+// ============================================================================
+
+// Carry-forward mode per category node (see services::rollover::RolloverMode).
+// A category without a row uses Reset (0).
+pub const CREATE_BUDGET_ROLLOVER_RULES_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS BUDGET_ROLLOVER_RULES (
+    RULE_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    CATEGORY1_CODE VARCHAR(64) NOT NULL,
+    CATEGORY2_CODE VARCHAR(64),
+    CATEGORY3_CODE VARCHAR(64),
+    ROLLOVER_MODE INTEGER NOT NULL DEFAULT 0 CHECK (ROLLOVER_MODE BETWEEN 0 AND 3),
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE
+)
+"#;
+
+pub const CREATE_BUDGET_ROLLOVER_RULES_KEY_INDEX: &str = r#"
+CREATE UNIQUE INDEX IF NOT EXISTS IDX_BUDGET_ROLLOVER_RULES_KEY ON BUDGET_ROLLOVER_RULES (
+    USER_ID, CATEGORY1_CODE, IFNULL(CATEGORY2_CODE, ''), IFNULL(CATEGORY3_CODE, '')
+)
+"#;
+
+// One row per budget of a closed period, snapshotting every input of the
+// carry (mode, planned, carried in, actual, bounds) next to the result.
+// PERIOD_YEAR / PERIOD_MONTH is the period the amount was carried *out of*.
+pub const CREATE_BUDGET_CARRY_HISTORY_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS BUDGET_CARRY_HISTORY (
+    CARRY_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    PERIOD_YEAR INTEGER NOT NULL,
+    PERIOD_MONTH INTEGER NOT NULL CHECK (PERIOD_MONTH BETWEEN 1 AND 12),
+    CATEGORY1_CODE VARCHAR(64) NOT NULL,
+    CATEGORY2_CODE VARCHAR(64),
+    CATEGORY3_CODE VARCHAR(64),
+    ROLLOVER_MODE INTEGER NOT NULL,
+    PLANNED INTEGER NOT NULL,
+    CARRIED_IN INTEGER NOT NULL,
+    ACTUAL INTEGER NOT NULL,
+    CARRIED_OUT INTEGER NOT NULL,
+    PERIOD_START TEXT NOT NULL,
+    PERIOD_END TEXT NOT NULL,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE
+)
+"#;
+
+pub const CREATE_BUDGET_CARRY_HISTORY_KEY_INDEX: &str = r#"
+CREATE UNIQUE INDEX IF NOT EXISTS IDX_BUDGET_CARRY_HISTORY_KEY ON BUDGET_CARRY_HISTORY (
+    USER_ID, PERIOD_YEAR, PERIOD_MONTH, CATEGORY1_CODE,
+    IFNULL(CATEGORY2_CODE, ''), IFNULL(CATEGORY3_CODE, '')
+)
+"#;
+
+pub const BUDGET_ROLLOVER_RULE_LIST: &str = r#"
+SELECT RULE_ID, USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE,
+       ROLLOVER_MODE, ENTRY_DT, UPDATE_DT
+FROM BUDGET_ROLLOVER_RULES
+WHERE USER_ID = ?
+ORDER BY CATEGORY1_CODE, IFNULL(CATEGORY2_CODE, ''), IFNULL(CATEGORY3_CODE, '')
+"#;
+
+// Bindings: (rollover_mode, user_id, category1, category2, category3).
+pub const BUDGET_ROLLOVER_RULE_UPDATE: &str = r#"
+UPDATE BUDGET_ROLLOVER_RULES
+SET ROLLOVER_MODE = ?, UPDATE_DT = datetime('now')
+WHERE USER_ID = ? AND CATEGORY1_CODE = ?
+  AND IFNULL(CATEGORY2_CODE, '') = IFNULL(?, '')
+  AND IFNULL(CATEGORY3_CODE, '') = IFNULL(?, '')
+"#;
+
+pub const BUDGET_ROLLOVER_RULE_INSERT: &str = r#"
+INSERT INTO BUDGET_ROLLOVER_RULES (
+    USER_ID, CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE, ROLLOVER_MODE
+) VALUES (?, ?, ?, ?, ?)
+"#;
+
+pub const BUDGET_CARRY_HISTORY_LIST_BY_PERIOD: &str = r#"
+SELECT CARRY_ID, USER_ID, PERIOD_YEAR, PERIOD_MONTH,
+       CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE, ROLLOVER_MODE,
+       PLANNED, CARRIED_IN, ACTUAL, CARRIED_OUT, PERIOD_START, PERIOD_END, ENTRY_DT
+FROM BUDGET_CARRY_HISTORY
+WHERE USER_ID = ? AND PERIOD_YEAR = ? AND PERIOD_MONTH = ?
+ORDER BY CATEGORY1_CODE, IFNULL(CATEGORY2_CODE, ''), IFNULL(CATEGORY3_CODE, '')
+"#;
+
+pub const BUDGET_CARRY_HISTORY_DELETE_BY_PERIOD: &str = r#"
+DELETE FROM BUDGET_CARRY_HISTORY
+WHERE USER_ID = ? AND PERIOD_YEAR = ? AND PERIOD_MONTH = ?
+"#;
+
+pub const BUDGET_CARRY_HISTORY_INSERT: &str = r#"
+INSERT INTO BUDGET_CARRY_HISTORY (
+    USER_ID, PERIOD_YEAR, PERIOD_MONTH,
+    CATEGORY1_CODE, CATEGORY2_CODE, CATEGORY3_CODE, ROLLOVER_MODE,
+    PLANNED, CARRIED_IN, ACTUAL, CARRIED_OUT, PERIOD_START, PERIOD_END
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+// ============================================================================
+// Reconciliation Queries
+// ============================================================================
+
+// "The bank says BALANCE on STATEMENT_DATE" for one account. The computed
+// side is not stored: it is recalculated with ACCOUNT_BALANCES_AS_OF every
+// time so back-dated edits show up as a new difference.
+pub const CREATE_RECONCILIATION_CHECKPOINTS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS RECONCILIATION_CHECKPOINTS (
+    CHECKPOINT_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    STATEMENT_DATE TEXT NOT NULL,
+    STATEMENT_BALANCE INTEGER NOT NULL,
+    MEMO TEXT,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    FOREIGN KEY (USER_ID, ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE),
+    UNIQUE(USER_ID, ACCOUNT_CODE, STATEMENT_DATE)
+)
+"#;
+
+pub const RECONCILIATION_CHECKPOINT_LIST_BY_ACCOUNT: &str = r#"
+SELECT CHECKPOINT_ID, USER_ID, ACCOUNT_CODE, STATEMENT_DATE, STATEMENT_BALANCE,
+       MEMO, ENTRY_DT, UPDATE_DT
+FROM RECONCILIATION_CHECKPOINTS
+WHERE USER_ID = ? AND ACCOUNT_CODE = ?
+ORDER BY STATEMENT_DATE DESC
+"#;
+
+pub const RECONCILIATION_CHECKPOINT_GET_BY_ID: &str = r#"
+SELECT CHECKPOINT_ID, USER_ID, ACCOUNT_CODE, STATEMENT_DATE, STATEMENT_BALANCE,
+       MEMO, ENTRY_DT, UPDATE_DT
+FROM RECONCILIATION_CHECKPOINTS
+WHERE USER_ID = ? AND CHECKPOINT_ID = ?
+"#;
+
+pub const RECONCILIATION_CHECKPOINT_INSERT: &str = r#"
+INSERT INTO RECONCILIATION_CHECKPOINTS (
+    USER_ID, ACCOUNT_CODE, STATEMENT_DATE, STATEMENT_BALANCE, MEMO
+) VALUES (?, ?, ?, ?, ?)
+"#;
+
+pub const RECONCILIATION_CHECKPOINT_DELETE: &str = r#"
+DELETE FROM RECONCILIATION_CHECKPOINTS
+WHERE USER_ID = ? AND CHECKPOINT_ID = ?
+"#;
+
+// Actualised headers touching the account on or before the statement date
+// that are not cleared yet. A split header touches every account one of its
+// legs moves. Bindings: (user_id, account_code, account_code, statement_date).
+pub const RECONCILIATION_COUNT_UNCLEARED: &str = r#"
+SELECT COUNT(*) FROM TRANSACTIONS_HEADER h
+WHERE h.USER_ID = ?
+  AND EXISTS (
+      SELECT 1 FROM TRANSACTION_MOVEMENTS mv
+      WHERE mv.USER_ID = h.USER_ID AND mv.TRANSACTION_ID = h.TRANSACTION_ID
+        AND (mv.FROM_ACCOUNT_CODE = ? OR mv.TO_ACCOUNT_CODE = ?)
+  )
+  AND h.IS_SCHEDULED = 0
+  AND DATE(h.TRANSACTION_DATE) <= DATE(?)
+  AND h.RECONCILE_STATUS = 0
+"#;
+
+// Lock every cleared header of the account up to the statement date.
+// Bindings: (user_id, account_code, account_code, statement_date).
+pub const RECONCILIATION_MARK_CLEARED_AS_RECONCILED: &str = r#"
+UPDATE TRANSACTIONS_HEADER
+SET RECONCILE_STATUS = 2, UPDATE_DT = datetime('now')
+WHERE USER_ID = ?
+  AND TRANSACTION_ID IN (
+      SELECT mv.TRANSACTION_ID FROM TRANSACTION_MOVEMENTS mv
+      WHERE mv.USER_ID = TRANSACTIONS_HEADER.USER_ID
+        AND (mv.FROM_ACCOUNT_CODE = ? OR mv.TO_ACCOUNT_CODE = ?)
+  )
+  AND IS_SCHEDULED = 0
+  AND DATE(TRANSACTION_DATE) <= DATE(?)
+  AND RECONCILE_STATUS = 1
+"#;
+
+// Same bindings and row set as RECONCILIATION_MARK_CLEARED_AS_RECONCILED.
+pub const RECONCILIATION_CLEARED_IDS_TO_RECONCILE: &str = r#"
+SELECT TRANSACTION_ID FROM TRANSACTIONS_HEADER
+WHERE USER_ID = ?
+  AND TRANSACTION_ID IN (
+      SELECT mv.TRANSACTION_ID FROM TRANSACTION_MOVEMENTS mv
+      WHERE mv.USER_ID = TRANSACTIONS_HEADER.USER_ID
+        AND (mv.FROM_ACCOUNT_CODE = ? OR mv.TO_ACCOUNT_CODE = ?)
+  )
+  AND IS_SCHEDULED = 0
+  AND DATE(TRANSACTION_DATE) <= DATE(?)
+  AND RECONCILE_STATUS = 1
+ORDER BY TRANSACTION_ID
+"#;
+
+// Bindings: (new_status, user_id, transaction_id, expected_status).
+// Guarded by the current status so clearing never touches a reconciled
+// row and unlocking only ever downgrades a reconciled one.
+pub const RECONCILIATION_SET_STATUS: &str = r#"
+UPDATE TRANSACTIONS_HEADER
+SET RECONCILE_STATUS = ?, UPDATE_DT = datetime('now')
+WHERE USER_ID = ? AND TRANSACTION_ID = ? AND RECONCILE_STATUS = ?
+"#;
+
+pub const TRANSACTION_HEADER_GET_RECONCILE_STATUS: &str = r#"
+SELECT RECONCILE_STATUS FROM TRANSACTIONS_HEADER
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+// ============================================================================
+// Credit Card Billing Queries
+// ============================================================================
+
+// Per-card billing cycle. CLOSING_DAY / PAYMENT_DAY are 1..=31 (a day past
+// the end of a month resolves to its last day); PAYMENT_MONTH_OFFSET is how
+// many months after the closing month the bill is paid (1 = 翌月, 2 = 翌々月).
+pub const CREATE_CARD_BILLING_SETTINGS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS CARD_BILLING_SETTINGS (
+    SETTING_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    PAYMENT_ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    CLOSING_DAY INTEGER NOT NULL CHECK (CLOSING_DAY BETWEEN 1 AND 31),
+    PAYMENT_DAY INTEGER NOT NULL CHECK (PAYMENT_DAY BETWEEN 1 AND 31),
+    PAYMENT_MONTH_OFFSET INTEGER NOT NULL DEFAULT 1 CHECK (PAYMENT_MONTH_OFFSET BETWEEN 1 AND 2),
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    FOREIGN KEY (USER_ID, ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE),
+    FOREIGN KEY (USER_ID, PAYMENT_ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE),
+    UNIQUE(USER_ID, ACCOUNT_CODE)
+)
+"#;
+
+// One row per issued statement. TRANSACTION_ID is the scheduled TRANSFER
+// paying it; deleting that row from the ledger leaves the statement with
+// NULL so it can be issued again.
+pub const CREATE_CARD_STATEMENTS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS CARD_STATEMENTS (
+    STATEMENT_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    PERIOD_START TEXT NOT NULL,
+    CLOSING_DATE TEXT NOT NULL,
+    PAYMENT_DATE TEXT NOT NULL,
+    TOTAL_AMOUNT INTEGER NOT NULL,
+    CHARGE_COUNT INTEGER NOT NULL,
+    TRANSACTION_ID INTEGER,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    FOREIGN KEY (USER_ID, ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE),
+    FOREIGN KEY (TRANSACTION_ID) REFERENCES TRANSACTIONS_HEADER(TRANSACTION_ID) ON DELETE SET NULL,
+    UNIQUE(USER_ID, ACCOUNT_CODE, CLOSING_DATE)
+)
+"#;
+
+pub const CARD_BILLING_SETTINGS_LIST: &str = r#"
+SELECT ACCOUNT_CODE, PAYMENT_ACCOUNT_CODE, CLOSING_DAY, PAYMENT_DAY,
+       PAYMENT_MONTH_OFFSET, ENTRY_DT, UPDATE_DT
+FROM CARD_BILLING_SETTINGS
+WHERE USER_ID = ?
+ORDER BY ACCOUNT_CODE
+"#;
+
+pub const CARD_BILLING_SETTINGS_GET: &str = r#"
+SELECT ACCOUNT_CODE, PAYMENT_ACCOUNT_CODE, CLOSING_DAY, PAYMENT_DAY,
+       PAYMENT_MONTH_OFFSET, ENTRY_DT, UPDATE_DT
+FROM CARD_BILLING_SETTINGS
+WHERE USER_ID = ? AND ACCOUNT_CODE = ?
+"#;
+
+pub const CARD_BILLING_SETTINGS_UPDATE: &str = r#"
+UPDATE CARD_BILLING_SETTINGS
+SET PAYMENT_ACCOUNT_CODE = ?, CLOSING_DAY = ?, PAYMENT_DAY = ?,
+    PAYMENT_MONTH_OFFSET = ?, UPDATE_DT = datetime('now')
+WHERE USER_ID = ? AND ACCOUNT_CODE = ?
+"#;
+
+pub const CARD_BILLING_SETTINGS_INSERT: &str = r#"
+INSERT INTO CARD_BILLING_SETTINGS (
+    USER_ID, ACCOUNT_CODE, PAYMENT_ACCOUNT_CODE, CLOSING_DAY, PAYMENT_DAY, PAYMENT_MONTH_OFFSET
+) VALUES (?, ?, ?, ?, ?, ?)
+"#;
+
+pub const CARD_BILLING_SETTINGS_DELETE: &str = r#"
+DELETE FROM CARD_BILLING_SETTINGS
+WHERE USER_ID = ? AND ACCOUNT_CODE = ?
+"#;
+
+// Charges on the card within one cycle: EXPENSE or TRANSFER paid from the
+// card count up, INCOME into it (refunds) counts down. Payments into the
+// card are TRANSFERs *to* it and are not part of the statement. Only the
+// card's legs of a split header count, but the header is one charge.
+// Bindings: (card, card, card, user_id, card, card, period_start, closing_date).
+pub const CARD_STATEMENT_TOTAL: &str = r#"
+SELECT
+    COALESCE(SUM(CASE
+        WHEN CATEGORY1_CODE IN ('EXPENSE', 'TRANSFER') AND FROM_ACCOUNT_CODE = ?
+            THEN TOTAL_AMOUNT
+        WHEN CATEGORY1_CODE = 'INCOME' AND TO_ACCOUNT_CODE = ?
+            THEN -TOTAL_AMOUNT
+        ELSE 0
+    END), 0) AS TOTAL_AMOUNT,
+    COUNT(DISTINCT CASE
+        WHEN CATEGORY1_CODE IN ('EXPENSE', 'TRANSFER') AND FROM_ACCOUNT_CODE = ?
+            THEN TRANSACTION_ID
+    END) AS CHARGE_COUNT
+FROM TRANSACTION_MOVEMENTS
+WHERE USER_ID = ?
+  AND IS_SCHEDULED = 0
+  AND (FROM_ACCOUNT_CODE = ? OR TO_ACCOUNT_CODE = ?)
+  AND DATE(TRANSACTION_DATE) BETWEEN DATE(?) AND DATE(?)
+"#;
+
+// PAYMENT_ACCOUNT_CODE / IS_SCHEDULED come from the payment row, so they
+// are NULL once that row has been deleted.
+pub const CARD_STATEMENT_LIST_BY_ACCOUNT: &str = r#"
+SELECT s.STATEMENT_ID, s.ACCOUNT_CODE, s.PERIOD_START, s.CLOSING_DATE, s.PAYMENT_DATE,
+       s.TOTAL_AMOUNT, s.CHARGE_COUNT, s.TRANSACTION_ID,
+       h.FROM_ACCOUNT_CODE AS PAYMENT_ACCOUNT_CODE, h.IS_SCHEDULED
+FROM CARD_STATEMENTS s
+LEFT JOIN TRANSACTIONS_HEADER h ON h.TRANSACTION_ID = s.TRANSACTION_ID
+WHERE s.USER_ID = ? AND s.ACCOUNT_CODE = ?
+ORDER BY s.CLOSING_DATE DESC
+"#;
+
+pub const CARD_STATEMENT_GET: &str = r#"
+SELECT s.STATEMENT_ID, s.ACCOUNT_CODE, s.PERIOD_START, s.CLOSING_DATE, s.PAYMENT_DATE,
+       s.TOTAL_AMOUNT, s.CHARGE_COUNT, s.TRANSACTION_ID,
+       h.FROM_ACCOUNT_CODE AS PAYMENT_ACCOUNT_CODE, h.IS_SCHEDULED
+FROM CARD_STATEMENTS s
+LEFT JOIN TRANSACTIONS_HEADER h ON h.TRANSACTION_ID = s.TRANSACTION_ID
+WHERE s.USER_ID = ? AND s.ACCOUNT_CODE = ? AND s.CLOSING_DATE = ?
+"#;
+
+pub const CARD_STATEMENT_INSERT: &str = r#"
+INSERT INTO CARD_STATEMENTS (
+    USER_ID, ACCOUNT_CODE, PERIOD_START, CLOSING_DATE, PAYMENT_DATE,
+    TOTAL_AMOUNT, CHARGE_COUNT, TRANSACTION_ID
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+pub const CARD_STATEMENT_UPDATE: &str = r#"
+UPDATE CARD_STATEMENTS
+SET PERIOD_START = ?, PAYMENT_DATE = ?, TOTAL_AMOUNT = ?, CHARGE_COUNT = ?,
+    TRANSACTION_ID = ?, UPDATE_DT = datetime('now')
+WHERE STATEMENT_ID = ?
+"#;
+
+// Scheduled payment of a statement: a TRANSFER from the bank to the card
+pub const CARD_PAYMENT_INSERT: &str = r#"
+INSERT INTO TRANSACTIONS_HEADER (
+    USER_ID, TRANSACTION_DATE, CATEGORY1_CODE, FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE,
+    TOTAL_AMOUNT, CURRENCY_CODE, IS_SCHEDULED, ENTRY_DT
+) VALUES (?, ?, 'TRANSFER', ?, ?, ?, ?, 1, datetime('now', 'localtime'))
+"#;
+
+pub const CARD_PAYMENT_UPDATE: &str = r#"
+UPDATE TRANSACTIONS_HEADER
+SET TRANSACTION_DATE = ?, FROM_ACCOUNT_CODE = ?, TOTAL_AMOUNT = ?,
+    UPDATE_DT = datetime('now', 'localtime')
+WHERE TRANSACTION_ID = ? AND USER_ID = ? AND IS_SCHEDULED = 1
+"#;
+
+pub const CARD_PAYMENT_DELETE: &str = r#"
+DELETE FROM TRANSACTIONS_HEADER
+WHERE TRANSACTION_ID = ? AND USER_ID = ? AND IS_SCHEDULED = 1
+"#;
+
+// ============================================================================
+// Currency and Exchange Rate Queries
+// ============================================================================
+
+pub const USER_GET_BASE_CURRENCY: &str = r#"
+SELECT COALESCE(BASE_CURRENCY_CODE, 'JPY') FROM USERS WHERE USER_ID = ?
+"#;
+
+pub const USER_SET_BASE_CURRENCY: &str = r#"
+UPDATE USERS SET BASE_CURRENCY_CODE = ?, UPDATE_DT = datetime('now') WHERE USER_ID = ?
+"#;
+
+// Currencies of the real accounts on either side of a header; NONE carries
+// no currency of its own. Bindings: (user_id, from_account, to_account).
+pub const ACCOUNT_CURRENCIES_FOR_CODES: &str = r#"
+SELECT ACCOUNT_CODE, CURRENCY_CODE
+FROM ACCOUNTS
+WHERE USER_ID = ? AND ACCOUNT_CODE IN (?, ?) AND ACCOUNT_CODE != 'NONE'
+ORDER BY ACCOUNT_CODE
+"#;
+
+// RATE is base units per one unit of CURRENCY_CODE as the user typed it;
+// MINOR_RATE is the same rate between minor units (yen per cent for
+// USD → JPY), which is what conversions multiply by. A row applies from
+// RATE_DATE until the next row for the same pair.
+pub const CREATE_EXCHANGE_RATES_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS EXCHANGE_RATES (
+    RATE_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    CURRENCY_CODE TEXT NOT NULL,
+    BASE_CURRENCY_CODE TEXT NOT NULL,
+    RATE_DATE TEXT NOT NULL,
+    RATE REAL NOT NULL CHECK (RATE > 0),
+    MINOR_RATE REAL NOT NULL CHECK (MINOR_RATE > 0),
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    UNIQUE(USER_ID, CURRENCY_CODE, BASE_CURRENCY_CODE, RATE_DATE)
+)
+"#;
+
+// Rates against the user's current base currency, optionally for one
+// currency. Bindings: (user_id, currency_code, currency_code).
+pub const EXCHANGE_RATE_LIST: &str = r#"
+SELECT r.RATE_ID, r.CURRENCY_CODE, r.BASE_CURRENCY_CODE, r.RATE_DATE, r.RATE,
+       r.ENTRY_DT, r.UPDATE_DT
+FROM EXCHANGE_RATES r
+JOIN USERS u ON u.USER_ID = r.USER_ID AND u.BASE_CURRENCY_CODE = r.BASE_CURRENCY_CODE
+WHERE r.USER_ID = ? AND (? IS NULL OR r.CURRENCY_CODE = ?)
+ORDER BY r.CURRENCY_CODE, r.RATE_DATE DESC
+"#;
+
+pub const EXCHANGE_RATE_LIST_FOR_CONVERSION: &str = r#"
+SELECT CURRENCY_CODE, RATE_DATE, MINOR_RATE
+FROM EXCHANGE_RATES
+WHERE USER_ID = ? AND BASE_CURRENCY_CODE = ?
+ORDER BY CURRENCY_CODE, RATE_DATE
+"#;
+
+// Bindings: (user_id, currency_code, base_currency_code, rate_date, rate,
+// minor_rate).
+pub const EXCHANGE_RATE_UPSERT: &str = r#"
+INSERT INTO EXCHANGE_RATES
+    (USER_ID, CURRENCY_CODE, BASE_CURRENCY_CODE, RATE_DATE, RATE, MINOR_RATE, ENTRY_DT)
+VALUES (?, ?, ?, ?, ?, ?, datetime('now'))
+ON CONFLICT(USER_ID, CURRENCY_CODE, BASE_CURRENCY_CODE, RATE_DATE)
+DO UPDATE SET
+    RATE = excluded.RATE,
+    MINOR_RATE = excluded.MINOR_RATE,
+    UPDATE_DT = datetime('now')
+"#;
+
+pub const EXCHANGE_RATE_DELETE: &str = r#"
+DELETE FROM EXCHANGE_RATES WHERE RATE_ID = ? AND USER_ID = ?
+"#;
+
+// ============================================================================
+// Split Transaction Queries
+// ============================================================================
+
+/// Money movements per header. An ordinary header is one movement. A header
+/// with split lines (detail rows carrying their own FROM/TO accounts) is one
+/// movement per (category, from, to) leg: split lines use their own
+/// CATEGORY1_CODE and accounts, the remaining lines the header's. A leg's
+/// amount is its lines after tax under the header's TAX_INCLUDED_TYPE and
+/// TAX_ROUNDING_TYPE, rounded once per tax rate exactly like
+/// `calculate_recommended_total_with_settings`.
+pub const CREATE_TRANSACTION_MOVEMENTS_VIEW: &str = r#"
+CREATE VIEW IF NOT EXISTS TRANSACTION_MOVEMENTS AS
+SELECT
+    h.TRANSACTION_ID, h.USER_ID, h.SHOP_ID, h.TRANSACTION_DATE, h.CATEGORY1_CODE,
+    h.FROM_ACCOUNT_CODE, h.TO_ACCOUNT_CODE, h.TOTAL_AMOUNT, h.CURRENCY_CODE,
+    h.IS_SCHEDULED
+FROM TRANSACTIONS_HEADER h
+WHERE NOT EXISTS (
+    SELECT 1 FROM TRANSACTIONS_DETAIL d
+    WHERE d.USER_ID = h.USER_ID AND d.TRANSACTION_ID = h.TRANSACTION_ID
+      AND d.FROM_ACCOUNT_CODE IS NOT NULL
+)
+UNION ALL
+SELECT
+    s.TRANSACTION_ID, s.USER_ID, s.SHOP_ID, s.TRANSACTION_DATE, s.LEG_CATEGORY1_CODE,
+    s.LEG_FROM_ACCOUNT_CODE, s.LEG_TO_ACCOUNT_CODE,
+    SUM(CASE
+        WHEN s.TAX_INCLUDED_TYPE = 0 THEN s.ALREADY_INCLUDED_SUM + s.PRETAX_SUM
+        ELSE s.ALREADY_INCLUDED_SUM + CASE s.TAX_ROUNDING_TYPE
+            WHEN 1 THEN CAST(ROUND(s.PRETAX_SUM * (100.0 + s.TAX_RATE) / 100.0) AS INTEGER)
+            WHEN 2 THEN (s.PRETAX_SUM * (100 + s.TAX_RATE) + 99) / 100
+            ELSE s.PRETAX_SUM * (100 + s.TAX_RATE) / 100
+        END
+    END),
+    s.CURRENCY_CODE, s.IS_SCHEDULED
+FROM (
+    SELECT
+        h.TRANSACTION_ID, h.USER_ID, h.SHOP_ID, h.TRANSACTION_DATE, h.CURRENCY_CODE,
+        h.IS_SCHEDULED, h.TAX_INCLUDED_TYPE, h.TAX_ROUNDING_TYPE, d.TAX_RATE,
+        CASE WHEN d.FROM_ACCOUNT_CODE IS NULL THEN h.CATEGORY1_CODE
+             ELSE d.CATEGORY1_CODE END AS LEG_CATEGORY1_CODE,
+        CASE WHEN d.FROM_ACCOUNT_CODE IS NULL THEN h.FROM_ACCOUNT_CODE
+             ELSE d.FROM_ACCOUNT_CODE END AS LEG_FROM_ACCOUNT_CODE,
+        CASE WHEN d.FROM_ACCOUNT_CODE IS NULL THEN h.TO_ACCOUNT_CODE
+             ELSE d.TO_ACCOUNT_CODE END AS LEG_TO_ACCOUNT_CODE,
+        SUM(CASE
+            WHEN d.TAX_RATE = 0
+              OR (d.AMOUNT_INCLUDING_TAX IS NOT NULL AND d.AMOUNT = d.AMOUNT_INCLUDING_TAX)
+            THEN d.AMOUNT ELSE 0
+        END) AS ALREADY_INCLUDED_SUM,
+        SUM(CASE
+            WHEN d.TAX_RATE > 0
+              AND (d.AMOUNT_INCLUDING_TAX IS NULL OR d.AMOUNT != d.AMOUNT_INCLUDING_TAX)
+            THEN d.AMOUNT ELSE 0
+        END) AS PRETAX_SUM
+    FROM TRANSACTIONS_HEADER h
+    JOIN TRANSACTIONS_DETAIL d
+        ON d.USER_ID = h.USER_ID AND d.TRANSACTION_ID = h.TRANSACTION_ID
+    WHERE EXISTS (
+        SELECT 1 FROM TRANSACTIONS_DETAIL x
+        WHERE x.USER_ID = h.USER_ID AND x.TRANSACTION_ID = h.TRANSACTION_ID
+          AND x.FROM_ACCOUNT_CODE IS NOT NULL
+    )
+    GROUP BY h.TRANSACTION_ID, LEG_CATEGORY1_CODE, LEG_FROM_ACCOUNT_CODE,
+             LEG_TO_ACCOUNT_CODE, d.TAX_RATE
+) s
+GROUP BY s.TRANSACTION_ID, s.LEG_CATEGORY1_CODE, s.LEG_FROM_ACCOUNT_CODE,
+         s.LEG_TO_ACCOUNT_CODE
+"#;
+
+// Split-line accounts must be real accounts of the user ('NONE' included).
+// Bindings: (user_id, from_account_code, to_account_code).
+pub const TRANSACTION_DETAIL_LINE_ACCOUNTS: &str = r#"
+SELECT ACCOUNT_CODE FROM ACCOUNTS
+WHERE USER_ID = ? AND ACCOUNT_CODE IN (?, ?)
+"#;
+
+pub const TRANSACTION_HEADER_GET_CURRENCY: &str = r#"
+SELECT CURRENCY_CODE
+FROM TRANSACTIONS_HEADER
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+// ============================================================================
+// Attachment Queries
+// ============================================================================
+
+// Receipts and documents kept with a header or one of its detail lines.
+// CONTENT holds the file itself, or `nonce || ciphertext` when IS_ENCRYPTED;
+// SHA256 is always the hex digest of the plain file. Both foreign keys
+// cascade, so deleting the header or the detail drops its attachments.
+pub const CREATE_ATTACHMENTS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS ATTACHMENTS (
+    ATTACHMENT_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    TRANSACTION_ID INTEGER NOT NULL,
+    DETAIL_ID INTEGER,
+    FILE_NAME TEXT NOT NULL,
+    MIME_TYPE TEXT NOT NULL,
+    BYTE_SIZE INTEGER NOT NULL,
+    SHA256 TEXT NOT NULL,
+    IS_ENCRYPTED INTEGER NOT NULL DEFAULT 0 CHECK (IS_ENCRYPTED IN (0, 1)),
+    CONTENT BLOB NOT NULL,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (TRANSACTION_ID) REFERENCES TRANSACTIONS_HEADER(TRANSACTION_ID) ON DELETE CASCADE,
+    FOREIGN KEY (DETAIL_ID) REFERENCES TRANSACTIONS_DETAIL(DETAIL_ID) ON DELETE CASCADE,
+    CHECK (FILE_NAME != '')
+)
+"#;
+
+pub const CREATE_ATTACHMENTS_INDEX: &str = r#"
+CREATE INDEX IF NOT EXISTS IDX_ATTACHMENTS_TRANSACTION
+ON ATTACHMENTS(USER_ID, TRANSACTION_ID)
+"#;
+
+// Bindings: (user_id, transaction_id, detail_id, file_name, mime_type,
+// byte_size, sha256, is_encrypted, content).
+pub const ATTACHMENT_INSERT: &str = r#"
+INSERT INTO ATTACHMENTS (USER_ID, TRANSACTION_ID, DETAIL_ID, FILE_NAME, MIME_TYPE,
+                         BYTE_SIZE, SHA256, IS_ENCRYPTED, CONTENT)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+
+// A header's attachments, optionally only those of one detail line.
+// Bindings: (user_id, transaction_id, detail_id, detail_id).
+pub const ATTACHMENT_LIST: &str = r#"
+SELECT ATTACHMENT_ID, TRANSACTION_ID, DETAIL_ID, FILE_NAME, MIME_TYPE,
+       BYTE_SIZE, SHA256, IS_ENCRYPTED, ENTRY_DT
+FROM ATTACHMENTS
+WHERE USER_ID = ? AND TRANSACTION_ID = ? AND (? IS NULL OR DETAIL_ID = ?)
+ORDER BY ATTACHMENT_ID
+"#;
+
+pub const ATTACHMENT_GET_BY_ID: &str = r#"
+SELECT ATTACHMENT_ID, TRANSACTION_ID, DETAIL_ID, FILE_NAME, MIME_TYPE,
+       BYTE_SIZE, SHA256, IS_ENCRYPTED, ENTRY_DT
+FROM ATTACHMENTS
+WHERE ATTACHMENT_ID = ? AND USER_ID = ?
+"#;
+
+pub const ATTACHMENT_GET_CONTENT: &str = r#"
+SELECT CONTENT FROM ATTACHMENTS WHERE ATTACHMENT_ID = ? AND USER_ID = ?
+"#;
+
+pub const ATTACHMENT_DELETE: &str = r#"
+DELETE FROM ATTACHMENTS WHERE ATTACHMENT_ID = ? AND USER_ID = ?
+"#;
+
+// Whether a detail line belongs to the given header of the user; used
+// before linking attachments or tags to the line.
+// Bindings: (detail_id, transaction_id, user_id).
+pub const TRANSACTION_DETAIL_BELONGS_TO_HEADER: &str = r#"
+SELECT COUNT(*) FROM TRANSACTIONS_DETAIL
+WHERE DETAIL_ID = ? AND TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+// Encrypted attachments are re-keyed together with ENCRYPTED_FIELDS when
+// the user changes their password.
+pub const ATTACHMENT_LIST_ENCRYPTED_CONTENT: &str = r#"
+SELECT ATTACHMENT_ID, CONTENT FROM ATTACHMENTS WHERE USER_ID = ? AND IS_ENCRYPTED = 1
+"#;
+
+pub const ATTACHMENT_UPDATE_CONTENT: &str = r#"
+UPDATE ATTACHMENTS SET CONTENT = ? WHERE ATTACHMENT_ID = ?
+"#;
+
+// ============================================================================
+// Tag Queries
+// ============================================================================
+
+// Free-form labels that cut across the category tree. Names are unique per
+// user regardless of case.
+pub const CREATE_TAGS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS TAGS (
+    TAG_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    TAG_NAME TEXT NOT NULL COLLATE NOCASE,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    UNIQUE(USER_ID, TAG_NAME),
+    CHECK (TAG_NAME != '')
+)
+"#;
+
+// A tag on a whole header (DETAIL_ID NULL) or on one of its detail lines.
+// Every foreign key cascades, so dropping a tag, header or line drops the
+// link with it.
+pub const CREATE_TRANSACTION_TAGS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS TRANSACTION_TAGS (
+    TAG_LINK_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    TAG_ID INTEGER NOT NULL,
+    TRANSACTION_ID INTEGER NOT NULL,
+    DETAIL_ID INTEGER,
+    FOREIGN KEY (TAG_ID) REFERENCES TAGS(TAG_ID) ON DELETE CASCADE,
+    FOREIGN KEY (TRANSACTION_ID) REFERENCES TRANSACTIONS_HEADER(TRANSACTION_ID) ON DELETE CASCADE,
+    FOREIGN KEY (DETAIL_ID) REFERENCES TRANSACTIONS_DETAIL(DETAIL_ID) ON DELETE CASCADE
+)
+"#;
+
+// NULL DETAIL_IDs never collide in a plain UNIQUE constraint, so header-level
+// links are keyed on COALESCE(DETAIL_ID, 0) instead.
+pub const CREATE_TRANSACTION_TAGS_INDEXES: [&str; 2] = [
+    "CREATE UNIQUE INDEX IF NOT EXISTS UX_TRANSACTION_TAGS_LINK \
+     ON TRANSACTION_TAGS(TAG_ID, TRANSACTION_ID, COALESCE(DETAIL_ID, 0))",
+    "CREATE INDEX IF NOT EXISTS IDX_TRANSACTION_TAGS_TRANSACTION \
+     ON TRANSACTION_TAGS(USER_ID, TRANSACTION_ID)",
+];
+
+pub const TAG_LIST: &str = r#"
+SELECT t.TAG_ID, t.TAG_NAME,
+       (SELECT COUNT(DISTINCT tt.TRANSACTION_ID) FROM TRANSACTION_TAGS tt
+        WHERE tt.TAG_ID = t.TAG_ID) AS TRANSACTION_COUNT,
+       t.ENTRY_DT, t.UPDATE_DT
+FROM TAGS t
+WHERE t.USER_ID = ?
+ORDER BY t.TAG_NAME
+"#;
+
+pub const TAG_GET_BY_ID: &str = r#"
+SELECT t.TAG_ID, t.TAG_NAME,
+       (SELECT COUNT(DISTINCT tt.TRANSACTION_ID) FROM TRANSACTION_TAGS tt
+        WHERE tt.TAG_ID = t.TAG_ID) AS TRANSACTION_COUNT,
+       t.ENTRY_DT, t.UPDATE_DT
+FROM TAGS t
+WHERE t.TAG_ID = ? AND t.USER_ID = ?
+"#;
+
+// Bindings: (user_id, tag_name, exclude_tag_id). Pass 0 to exclude nothing.
+pub const TAG_NAME_EXISTS: &str = r#"
+SELECT COUNT(*) FROM TAGS WHERE USER_ID = ? AND TAG_NAME = ? AND TAG_ID != ?
+"#;
+
+pub const TAG_INSERT: &str = r#"
+INSERT INTO TAGS (USER_ID, TAG_NAME) VALUES (?, ?)
+"#;
+
+pub const TAG_UPDATE_NAME: &str = r#"
+UPDATE TAGS SET TAG_NAME = ?, UPDATE_DT = datetime('now') WHERE TAG_ID = ? AND USER_ID = ?
+"#;
+
+pub const TAG_DELETE: &str = r#"
+DELETE FROM TAGS WHERE TAG_ID = ? AND USER_ID = ?
+"#;
+
+// Tags on a header and on each of its lines. Bindings: (user_id, transaction_id).
+pub const TRANSACTION_TAG_LIST: &str = r#"
+SELECT tt.TAG_ID, t.TAG_NAME, tt.DETAIL_ID
+FROM TRANSACTION_TAGS tt
+INNER JOIN TAGS t ON t.TAG_ID = tt.TAG_ID
+WHERE tt.USER_ID = ? AND tt.TRANSACTION_ID = ?
+ORDER BY tt.DETAIL_ID IS NOT NULL, tt.DETAIL_ID, t.TAG_NAME
+"#;
+
+// Clear the tags at one level: the header itself (detail_id NULL) or one
+// line. Bindings: (user_id, transaction_id, detail_id, detail_id).
+// Links TRANSACTION_TAG_CLEAR removes, same bindings; each is audited.
+pub const TRANSACTION_TAG_LINK_IDS_TO_CLEAR: &str = r#"
+SELECT TAG_LINK_ID FROM TRANSACTION_TAGS
+WHERE USER_ID = ? AND TRANSACTION_ID = ?
+  AND ((? IS NULL AND DETAIL_ID IS NULL) OR DETAIL_ID = ?)
+"#;
+
+pub const TRANSACTION_TAG_CLEAR: &str = r#"
+DELETE FROM TRANSACTION_TAGS
+WHERE USER_ID = ? AND TRANSACTION_ID = ?
+  AND ((? IS NULL AND DETAIL_ID IS NULL) OR DETAIL_ID = ?)
+"#;
+
+pub const TRANSACTION_TAG_INSERT: &str = r#"
+INSERT OR IGNORE INTO TRANSACTION_TAGS (USER_ID, TAG_ID, TRANSACTION_ID, DETAIL_ID)
+VALUES (?, ?, ?, ?)
+"#;
+
+// Header matches a tag when the header or any of its lines carries it, so a
+// header is listed once however many of its lines are tagged.
+pub const TRANSACTION_TAG_FILTER: &str = "EXISTS (SELECT 1 FROM TRANSACTION_TAGS ttf \
+     WHERE ttf.USER_ID = t.USER_ID AND ttf.TRANSACTION_ID = t.TRANSACTION_ID AND ttf.TAG_ID = ?)";
+
+// ============================================================================
+// Saved Filter Queries
+// ============================================================================
+
+// Named transaction-list filters. FILTER_SPEC holds the JSON form of
+// services::transaction::TransactionFilter.
+pub const CREATE_SAVED_FILTERS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS SAVED_FILTERS (
+    SAVED_FILTER_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    FILTER_NAME TEXT NOT NULL COLLATE NOCASE,
+    FILTER_SPEC TEXT NOT NULL,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    UNIQUE(USER_ID, FILTER_NAME),
+    CHECK (FILTER_NAME != '')
+)
+"#;
+
+pub const SAVED_FILTER_LIST: &str = r#"
+SELECT SAVED_FILTER_ID, FILTER_NAME, FILTER_SPEC, ENTRY_DT, UPDATE_DT
+FROM SAVED_FILTERS
+WHERE USER_ID = ?
+ORDER BY FILTER_NAME
+"#;
+
+pub const SAVED_FILTER_GET_BY_ID: &str = r#"
+SELECT SAVED_FILTER_ID, FILTER_NAME, FILTER_SPEC, ENTRY_DT, UPDATE_DT
+FROM SAVED_FILTERS
+WHERE SAVED_FILTER_ID = ? AND USER_ID = ?
+"#;
+
+// Bindings: (user_id, filter_name, exclude_saved_filter_id). Pass 0 to exclude nothing.
+pub const SAVED_FILTER_NAME_EXISTS: &str = r#"
+SELECT COUNT(*) FROM SAVED_FILTERS WHERE USER_ID = ? AND FILTER_NAME = ? AND SAVED_FILTER_ID != ?
+"#;
+
+pub const SAVED_FILTER_INSERT: &str = r#"
+INSERT INTO SAVED_FILTERS (USER_ID, FILTER_NAME, FILTER_SPEC) VALUES (?, ?, ?)
+"#;
+
+pub const SAVED_FILTER_UPDATE_NAME: &str = r#"
+UPDATE SAVED_FILTERS SET FILTER_NAME = ?, UPDATE_DT = datetime('now')
+WHERE SAVED_FILTER_ID = ? AND USER_ID = ?
+"#;
+
+pub const SAVED_FILTER_DELETE: &str = r#"
+DELETE FROM SAVED_FILTERS WHERE SAVED_FILTER_ID = ? AND USER_ID = ?
+"#;
+
+// ============================================================================
+// Duplicate Detection Queries
+// ============================================================================
+
+// A pair of headers the user has marked as "not a duplicate". Stored with the
+// lower id first so each pair has one row; deleting either header drops it.
+pub const CREATE_DUPLICATE_DISMISSALS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS DUPLICATE_DISMISSALS (
+    USER_ID INTEGER NOT NULL,
+    TRANSACTION_ID_LOW INTEGER NOT NULL,
+    TRANSACTION_ID_HIGH INTEGER NOT NULL,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (TRANSACTION_ID_LOW, TRANSACTION_ID_HIGH),
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    FOREIGN KEY (TRANSACTION_ID_LOW) REFERENCES TRANSACTIONS_HEADER(TRANSACTION_ID) ON DELETE CASCADE,
+    FOREIGN KEY (TRANSACTION_ID_HIGH) REFERENCES TRANSACTIONS_HEADER(TRANSACTION_ID) ON DELETE CASCADE,
+    CHECK (TRANSACTION_ID_LOW < TRANSACTION_ID_HIGH)
+)
+"#;
+
+// Confirmed header pairs on the same accounts and category whose dates are at
+// most N days apart and whose totals differ by at most P percent of the
+// larger one. Dismissed pairs are left out.
+// Bindings: (max_days_apart, amount_tolerance_percent, user_id)
+pub const DUPLICATE_CANDIDATE_PAIRS: &str = r#"
+SELECT
+    a.TRANSACTION_ID AS TRANSACTION_ID_LOW,
+    b.TRANSACTION_ID AS TRANSACTION_ID_HIGH
+FROM TRANSACTIONS_HEADER a
+INNER JOIN TRANSACTIONS_HEADER b
+    ON b.USER_ID = a.USER_ID
+   AND b.TRANSACTION_ID > a.TRANSACTION_ID
+   AND b.CATEGORY1_CODE = a.CATEGORY1_CODE
+   AND b.FROM_ACCOUNT_CODE = a.FROM_ACCOUNT_CODE
+   AND b.TO_ACCOUNT_CODE = a.TO_ACCOUNT_CODE
+   AND b.IS_SCHEDULED = 0
+   AND ABS(julianday(date(b.TRANSACTION_DATE)) - julianday(date(a.TRANSACTION_DATE))) <= ?
+   AND ABS(b.TOTAL_AMOUNT - a.TOTAL_AMOUNT) * 100
+       <= ? * MAX(ABS(a.TOTAL_AMOUNT), ABS(b.TOTAL_AMOUNT))
+WHERE a.USER_ID = ?
+  AND a.IS_SCHEDULED = 0
+  AND NOT EXISTS (
+      SELECT 1 FROM DUPLICATE_DISMISSALS d
+      WHERE d.TRANSACTION_ID_LOW = a.TRANSACTION_ID
+        AND d.TRANSACTION_ID_HIGH = b.TRANSACTION_ID
+  )
+ORDER BY a.TRANSACTION_ID, b.TRANSACTION_ID
+"#;
+
+// Item names are joined with the ASCII unit separator, which cannot be typed
+// into an item name.
+pub const DUPLICATE_CANDIDATE_GET: &str = r#"
+SELECT
+    th.TRANSACTION_ID, th.TRANSACTION_DATE, th.CATEGORY1_CODE,
+    th.FROM_ACCOUNT_CODE, th.TO_ACCOUNT_CODE, th.TOTAL_AMOUNT,
+    s.SHOP_NAME,
+    (SELECT GROUP_CONCAT(td.ITEM_NAME, char(31))
+     FROM TRANSACTIONS_DETAIL td
+     WHERE td.TRANSACTION_ID = th.TRANSACTION_ID) AS ITEM_NAMES
+FROM TRANSACTIONS_HEADER th
+LEFT JOIN SHOPS s ON s.SHOP_ID = th.SHOP_ID
+WHERE th.TRANSACTION_ID = ? AND th.USER_ID = ?
+"#;
+
+pub const DUPLICATE_DISMISSAL_INSERT: &str = r#"
+INSERT OR IGNORE INTO DUPLICATE_DISMISSALS (USER_ID, TRANSACTION_ID_LOW, TRANSACTION_ID_HIGH)
+VALUES (?, ?, ?)
+"#;
+
+// ============================================================================
+// Bulk Transaction Edit Queries
+// ============================================================================
+
+pub const TRANSACTION_BULK_GET_HEADER: &str = r#"
+SELECT FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE, CURRENCY_CODE, RECONCILE_STATUS, MEMO_ID
+FROM TRANSACTIONS_HEADER
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+pub const TRANSACTION_BULK_SET_SHOP: &str = r#"
+UPDATE TRANSACTIONS_HEADER
+SET SHOP_ID = ?, UPDATE_DT = datetime('now')
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+pub const TRANSACTION_BULK_SET_ACCOUNTS: &str = r#"
+UPDATE TRANSACTIONS_HEADER
+SET FROM_ACCOUNT_CODE = ?, TO_ACCOUNT_CODE = ?, UPDATE_DT = datetime('now')
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+pub const TRANSACTION_BULK_SET_MEMO: &str = r#"
+UPDATE TRANSACTIONS_HEADER
+SET MEMO_ID = ?, UPDATE_DT = datetime('now')
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+pub const TRANSACTION_BULK_DETAIL_CATEGORY1_CODES: &str = r#"
+SELECT DISTINCT CATEGORY1_CODE
+FROM TRANSACTIONS_DETAIL
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+ORDER BY CATEGORY1_CODE
+"#;
+
+pub const TRANSACTION_BULK_DETAIL_IDS: &str = r#"
+SELECT DETAIL_ID FROM TRANSACTIONS_DETAIL
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+ORDER BY DETAIL_ID
+"#;
+
+pub const TRANSACTION_BULK_SET_DETAIL_CATEGORIES: &str = r#"
+UPDATE TRANSACTIONS_DETAIL
+SET CATEGORY2_CODE = ?, CATEGORY3_CODE = ?, UPDATE_DT = datetime('now')
+WHERE TRANSACTION_ID = ? AND USER_ID = ?
+"#;
+
+// ============================================================================
+// Change Journal Queries
+// ============================================================================
+
+// One undoable user action (a command call). STATUS flips between 'done'
+// and 'undone'; a new operation drops the user's undone ones (redo stack).
+pub const CREATE_CHANGE_OPERATIONS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS CHANGE_OPERATIONS (
+    OPERATION_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    LABEL TEXT NOT NULL,
+    STATUS TEXT NOT NULL DEFAULT 'done' CHECK (STATUS IN ('done', 'undone')),
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE
+)
+"#;
+
+// Row images written by the TRG_JOURNAL_* triggers. Images are JSON objects
+// keyed by column name; BLOB columns are stored hex-encoded.
+pub const CREATE_CHANGE_JOURNAL_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS CHANGE_JOURNAL (
+    CHANGE_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    OPERATION_ID INTEGER NOT NULL,
+    TABLE_NAME TEXT NOT NULL,
+    ACTION TEXT NOT NULL CHECK (ACTION IN ('INSERT', 'UPDATE', 'DELETE')),
+    BEFORE_IMAGE TEXT,
+    AFTER_IMAGE TEXT,
+    FOREIGN KEY (OPERATION_ID) REFERENCES CHANGE_OPERATIONS(OPERATION_ID) ON DELETE CASCADE
+)
+"#;
+
+pub const CREATE_CHANGE_JOURNAL_INDEX: &str = r#"
+CREATE INDEX IF NOT EXISTS idx_change_journal_operation ON CHANGE_JOURNAL(OPERATION_ID, CHANGE_ID)
+"#;
+
+// Single-row switch the triggers read: rows are journaled only while
+// ACTIVE_OPERATION_ID is set.
+pub const CREATE_CHANGE_JOURNAL_CONTROL_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS CHANGE_JOURNAL_CONTROL (
+    CONTROL_ID INTEGER PRIMARY KEY CHECK (CONTROL_ID = 1),
+    ACTIVE_OPERATION_ID INTEGER
+)
+"#;
+
+pub const CHANGE_JOURNAL_CONTROL_INIT: &str = r#"
+INSERT OR IGNORE INTO CHANGE_JOURNAL_CONTROL (CONTROL_ID, ACTIVE_OPERATION_ID) VALUES (1, NULL)
+"#;
+
+// The journal only covers one run of the app; anything left from the last
+// run (including an operation interrupted mid-way) is dropped at startup.
+pub const CHANGE_JOURNAL_RESET: [&str; 2] = [
+    "UPDATE CHANGE_JOURNAL_CONTROL SET ACTIVE_OPERATION_ID = NULL",
+    "DELETE FROM CHANGE_OPERATIONS",
+];
+
+// Tables touched by the transaction, category, account and master-data
+// services, plus the tables their FK actions cascade into.
+pub const CHANGE_JOURNAL_TABLES: [&str; 21] = [
+    "TRANSACTIONS_HEADER",
+    "TRANSACTIONS_DETAIL",
+    "MEMOS",
+    "TRANSACTION_TAGS",
+    "ATTACHMENTS",
+    "DUPLICATE_DISMISSALS",
+    "CARD_STATEMENTS",
+    "CATEGORY1",
+    "CATEGORY2",
+    "CATEGORY3",
+    "CATEGORY1_I18N",
+    "CATEGORY2_I18N",
+    "CATEGORY3_I18N",
+    "ACCOUNTS",
+    "SHOPS",
+    "MANUFACTURERS",
+    "PRODUCTS",
+    "TAGS",
+    "RECURRING_RULES",
+    "RECURRING_RULE_DETAILS",
+    "RECURRING_RULE_EXCEPTIONS",
+];
+
+pub const CHANGE_JOURNAL_TABLE_COLUMNS: &str = r#"
+SELECT name, type, pk FROM pragma_table_info(?) ORDER BY cid
+"#;
+
+pub const CHANGE_JOURNAL_LIST_TRIGGERS: &str = r#"
+SELECT name FROM sqlite_master WHERE type = 'trigger' AND name LIKE 'TRG_JOURNAL_%'
+"#;
+
+pub const CHANGE_JOURNAL_GET_ACTIVE: &str = r#"
+SELECT ACTIVE_OPERATION_ID FROM CHANGE_JOURNAL_CONTROL WHERE CONTROL_ID = 1
+"#;
+
+pub const CHANGE_JOURNAL_SET_ACTIVE: &str = r#"
+UPDATE CHANGE_JOURNAL_CONTROL SET ACTIVE_OPERATION_ID = ? WHERE CONTROL_ID = 1
+"#;
+
+pub const CHANGE_OPERATION_DELETE_UNDONE: &str = r#"
+DELETE FROM CHANGE_OPERATIONS WHERE USER_ID = ? AND STATUS = 'undone'
+"#;
+
+pub const CHANGE_OPERATION_INSERT: &str = r#"
+INSERT INTO CHANGE_OPERATIONS (USER_ID, LABEL) VALUES (?, ?)
+"#;
+
+// Operations that ended up touching no row are not worth an undo entry.
+pub const CHANGE_OPERATION_DELETE_IF_EMPTY: &str = r#"
+DELETE FROM CHANGE_OPERATIONS
+WHERE OPERATION_ID = ?
+  AND NOT EXISTS (SELECT 1 FROM CHANGE_JOURNAL WHERE OPERATION_ID = ?)
+"#;
+
+pub const CHANGE_OPERATION_LIST: &str = r#"
+SELECT o.OPERATION_ID, o.LABEL, o.STATUS, o.ENTRY_DT, o.UPDATE_DT,
+       (SELECT COUNT(*) FROM CHANGE_JOURNAL j WHERE j.OPERATION_ID = o.OPERATION_ID) AS CHANGE_COUNT
+FROM CHANGE_OPERATIONS o
+WHERE o.USER_ID = ?
+ORDER BY o.OPERATION_ID DESC
+"#;
+
+pub const CHANGE_OPERATION_GET_STATUS: &str = r#"
+SELECT STATUS FROM CHANGE_OPERATIONS WHERE OPERATION_ID = ? AND USER_ID = ?
+"#;
+
+// Bindings: (user_id, status, limit)
+pub const CHANGE_OPERATION_LATEST_IDS: &str = r#"
+SELECT OPERATION_ID FROM CHANGE_OPERATIONS
+WHERE USER_ID = ? AND STATUS = ?
+ORDER BY OPERATION_ID DESC
+LIMIT ?
+"#;
+
+// Bindings: (user_id, status, limit)
+pub const CHANGE_OPERATION_EARLIEST_IDS: &str = r#"
+SELECT OPERATION_ID FROM CHANGE_OPERATIONS
+WHERE USER_ID = ? AND STATUS = ?
+ORDER BY OPERATION_ID ASC
+LIMIT ?
+"#;
+
+pub const CHANGE_OPERATION_SET_STATUS: &str = r#"
+UPDATE CHANGE_OPERATIONS SET STATUS = ?, UPDATE_DT = datetime('now')
+WHERE OPERATION_ID = ? AND USER_ID = ?
+"#;
+
+pub const CHANGE_OPERATION_CLEAR_USER: &str = r#"
+DELETE FROM CHANGE_OPERATIONS WHERE USER_ID = ?
+"#;
+
+pub const CHANGE_JOURNAL_GET_CHANGES: &str = r#"
+SELECT TABLE_NAME, ACTION, BEFORE_IMAGE, AFTER_IMAGE
+FROM CHANGE_JOURNAL
+WHERE OPERATION_ID = ?
+ORDER BY CHANGE_ID
+"#;
+
+// ============================================================================
+// Audit Log Queries
+// ============================================================================
+
+// Who created, changed or deleted which row. ENTITY is the table name and
+// ENTITY_KEY a JSON object of its primary-key values. No FK to USERS: the
+// history must outlive the user who made it.
+pub const CREATE_AUDIT_LOG_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS AUDIT_LOG (
+    AUDIT_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    ENTITY TEXT NOT NULL,
+    ENTITY_KEY TEXT NOT NULL,
+    ACTION TEXT NOT NULL CHECK (ACTION IN ('INSERT', 'UPDATE', 'DELETE')),
+    CHANGED_COLUMNS TEXT NOT NULL,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now'))
+)
+"#;
+
+pub const CREATE_AUDIT_LOG_INDEXES: [&str; 2] = [
+    "CREATE INDEX IF NOT EXISTS idx_audit_log_user ON AUDIT_LOG(USER_ID, ENTRY_DT)",
+    "CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON AUDIT_LOG(ENTITY, ENTRY_DT)",
+];
+
+// Bindings: (user_id, entity, entity_key, action, changed_columns)
+pub const AUDIT_LOG_INSERT: &str = r#"
+INSERT INTO AUDIT_LOG (USER_ID, ENTITY, ENTITY_KEY, ACTION, CHANGED_COLUMNS)
+VALUES (?, ?, ?, ?, ?)
+"#;
+
+// Bindings: (user_id x2, entity x2, action x2, start_date x2, end_date x2,
+// limit, offset). Dates are inclusive calendar days.
+pub const AUDIT_LOG_LIST: &str = r#"
+SELECT a.AUDIT_ID, a.USER_ID, u.NAME AS USER_NAME, a.ENTITY, a.ENTITY_KEY,
+       a.ACTION, a.CHANGED_COLUMNS, a.ENTRY_DT
+FROM AUDIT_LOG a
+LEFT JOIN USERS u ON u.USER_ID = a.USER_ID
+WHERE (? IS NULL OR a.USER_ID = ?)
+  AND (? IS NULL OR a.ENTITY = ?)
+  AND (? IS NULL OR a.ACTION = ?)
+  AND (? IS NULL OR DATE(a.ENTRY_DT) >= DATE(?))
+  AND (? IS NULL OR DATE(a.ENTRY_DT) <= DATE(?))
+ORDER BY a.AUDIT_ID DESC
+LIMIT ? OFFSET ?
+"#;
+
+// ============================================================================
+// Full-text Search Queries
+// ============================================================================
+
+// Searchable text of one header: its own and its lines' memos, item names,
+// shop name, and product names with their manufacturers.
+pub const CREATE_TRANSACTION_SEARCH_SOURCE_VIEW: &str = r#"
+CREATE VIEW IF NOT EXISTS TRANSACTION_SEARCH_SOURCE AS
+SELECT
+    th.TRANSACTION_ID,
+    th.USER_ID,
+    TRIM(COALESCE(mh.MEMO_TEXT, '') || ' ' || COALESCE((
+        SELECT GROUP_CONCAT(md.MEMO_TEXT, ' ')
+        FROM TRANSACTIONS_DETAIL td
+        INNER JOIN MEMOS md ON md.MEMO_ID = td.MEMO_ID
+        WHERE td.TRANSACTION_ID = th.TRANSACTION_ID
+    ), '')) AS MEMO_TEXT,
+    COALESCE((
+        SELECT GROUP_CONCAT(td.ITEM_NAME, ' ')
+        FROM TRANSACTIONS_DETAIL td
+        WHERE td.TRANSACTION_ID = th.TRANSACTION_ID
+    ), '') AS ITEM_NAMES,
+    COALESCE(s.SHOP_NAME, '') AS SHOP_NAME,
+    COALESCE((
+        SELECT GROUP_CONCAT(p.PRODUCT_NAME || COALESCE(' ' || m.MANUFACTURER_NAME, ''), ' ')
+        FROM TRANSACTIONS_DETAIL td
+        INNER JOIN PRODUCTS p ON p.PRODUCT_ID = td.PRODUCT_ID
+        LEFT JOIN MANUFACTURERS m ON m.MANUFACTURER_ID = p.MANUFACTURER_ID
+        WHERE td.TRANSACTION_ID = th.TRANSACTION_ID
+    ), '') AS PRODUCT_NAMES
+FROM TRANSACTIONS_HEADER th
+LEFT JOIN MEMOS mh ON mh.MEMO_ID = th.MEMO_ID
+LEFT JOIN SHOPS s ON s.SHOP_ID = th.SHOP_ID
+"#;
+
+// One row per header, rowid = TRANSACTION_ID. The trigram tokenizer matches
+// any substring of three or more characters, which works for Japanese text
+// without a word segmenter.
+pub const CREATE_TRANSACTION_SEARCH_TABLE: &str = r#"
+CREATE VIRTUAL TABLE IF NOT EXISTS TRANSACTION_SEARCH USING fts5(
+    USER_ID UNINDEXED,
+    MEMO_TEXT,
+    ITEM_NAMES,
+    SHOP_NAME,
+    PRODUCT_NAMES,
+    tokenize = 'trigram'
+)
+"#;
+
+pub const TRANSACTION_SEARCH_TABLE_EXISTS: &str =
+    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'TRANSACTION_SEARCH'";
+
+pub const TRANSACTION_SEARCH_REBUILD: [&str; 2] = [
+    "DELETE FROM TRANSACTION_SEARCH",
+    r#"
+INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+FROM TRANSACTION_SEARCH_SOURCE
+"#,
+];
+
+// Keep TRANSACTION_SEARCH in step with every table feeding the source view.
+// Each trigger re-derives the affected headers' rows from the view.
+pub const CREATE_TRANSACTION_SEARCH_TRIGGERS: [&str; 10] = [
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_HEADER_INSERT
+AFTER INSERT ON TRANSACTIONS_HEADER
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (NEW.TRANSACTION_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (NEW.TRANSACTION_ID);
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_HEADER_UPDATE
+AFTER UPDATE OF MEMO_ID, SHOP_ID ON TRANSACTIONS_HEADER
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (NEW.TRANSACTION_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (NEW.TRANSACTION_ID);
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_HEADER_DELETE
+AFTER DELETE ON TRANSACTIONS_HEADER
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid = OLD.TRANSACTION_ID;
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_DETAIL_INSERT
+AFTER INSERT ON TRANSACTIONS_DETAIL
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (NEW.TRANSACTION_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (NEW.TRANSACTION_ID);
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_DETAIL_UPDATE
+AFTER UPDATE OF TRANSACTION_ID, ITEM_NAME, MEMO_ID, PRODUCT_ID ON TRANSACTIONS_DETAIL
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (OLD.TRANSACTION_ID, NEW.TRANSACTION_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (OLD.TRANSACTION_ID, NEW.TRANSACTION_ID);
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_DETAIL_DELETE
+AFTER DELETE ON TRANSACTIONS_DETAIL
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (OLD.TRANSACTION_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (OLD.TRANSACTION_ID);
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_MEMO_UPDATE
+AFTER UPDATE OF MEMO_TEXT ON MEMOS
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (
+        SELECT TRANSACTION_ID FROM TRANSACTIONS_HEADER WHERE MEMO_ID = NEW.MEMO_ID
+        UNION SELECT TRANSACTION_ID FROM TRANSACTIONS_DETAIL WHERE MEMO_ID = NEW.MEMO_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (
+        SELECT TRANSACTION_ID FROM TRANSACTIONS_HEADER WHERE MEMO_ID = NEW.MEMO_ID
+        UNION SELECT TRANSACTION_ID FROM TRANSACTIONS_DETAIL WHERE MEMO_ID = NEW.MEMO_ID);
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_SHOP_UPDATE
+AFTER UPDATE OF SHOP_NAME ON SHOPS
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (
+        SELECT TRANSACTION_ID FROM TRANSACTIONS_HEADER WHERE SHOP_ID = NEW.SHOP_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (
+        SELECT TRANSACTION_ID FROM TRANSACTIONS_HEADER WHERE SHOP_ID = NEW.SHOP_ID);
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_PRODUCT_UPDATE
+AFTER UPDATE OF PRODUCT_NAME, MANUFACTURER_ID ON PRODUCTS
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (
+        SELECT TRANSACTION_ID FROM TRANSACTIONS_DETAIL WHERE PRODUCT_ID = NEW.PRODUCT_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (
+        SELECT TRANSACTION_ID FROM TRANSACTIONS_DETAIL WHERE PRODUCT_ID = NEW.PRODUCT_ID);
+END
+"#,
+    r#"
+CREATE TRIGGER IF NOT EXISTS TRG_TRANSACTION_SEARCH_MANUFACTURER_UPDATE
+AFTER UPDATE OF MANUFACTURER_NAME ON MANUFACTURERS
+BEGIN
+    DELETE FROM TRANSACTION_SEARCH WHERE rowid IN (
+        SELECT td.TRANSACTION_ID FROM TRANSACTIONS_DETAIL td
+        INNER JOIN PRODUCTS p ON p.PRODUCT_ID = td.PRODUCT_ID
+        WHERE p.MANUFACTURER_ID = NEW.MANUFACTURER_ID);
+    INSERT INTO TRANSACTION_SEARCH (rowid, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES)
+    SELECT TRANSACTION_ID, USER_ID, MEMO_TEXT, ITEM_NAMES, SHOP_NAME, PRODUCT_NAMES
+    FROM TRANSACTION_SEARCH_SOURCE WHERE TRANSACTION_ID IN (
+        SELECT td.TRANSACTION_ID FROM TRANSACTIONS_DETAIL td
+        INNER JOIN PRODUCTS p ON p.PRODUCT_ID = td.PRODUCT_ID
+        WHERE p.MANUFACTURER_ID = NEW.MANUFACTURER_ID);
+END
+"#,
+];
+
+// Ranked search. Bindings: (fts5 match expression, user_id, limit).
+pub const TRANSACTION_SEARCH_MATCH: &str = r#"
+SELECT
+    t.TRANSACTION_ID, t.TRANSACTION_DATE, t.CATEGORY1_CODE, t.TOTAL_AMOUNT, t.CURRENCY_CODE,
+    ts.MEMO_TEXT, ts.ITEM_NAMES, ts.SHOP_NAME, ts.PRODUCT_NAMES
+FROM TRANSACTION_SEARCH ts
+INNER JOIN TRANSACTIONS_HEADER t ON t.TRANSACTION_ID = ts.rowid
+WHERE TRANSACTION_SEARCH MATCH ? AND ts.USER_ID = ?
+ORDER BY ts.rank, t.TRANSACTION_DATE DESC
+LIMIT ?
+"#;
+
+// Search for terms shorter than a trigram, which MATCH cannot index. The
+// caller appends one TRANSACTION_SEARCH_LIKE_TERM per term, then
+// TRANSACTION_SEARCH_LIKE_ORDER. Bindings: (user_id, 4 escaped patterns per
+// term, limit).
+pub const TRANSACTION_SEARCH_LIKE_BASE: &str = r#"
+SELECT
+    t.TRANSACTION_ID, t.TRANSACTION_DATE, t.CATEGORY1_CODE, t.TOTAL_AMOUNT, t.CURRENCY_CODE,
+    ts.MEMO_TEXT, ts.ITEM_NAMES, ts.SHOP_NAME, ts.PRODUCT_NAMES
+FROM TRANSACTION_SEARCH ts
+INNER JOIN TRANSACTIONS_HEADER t ON t.TRANSACTION_ID = ts.rowid
+WHERE ts.USER_ID = ?
+"#;
+
+pub const TRANSACTION_SEARCH_LIKE_TERM: &str = r" AND (ts.MEMO_TEXT LIKE ? ESCAPE '\'
+    OR ts.ITEM_NAMES LIKE ? ESCAPE '\'
+    OR ts.SHOP_NAME LIKE ? ESCAPE '\'
+    OR ts.PRODUCT_NAMES LIKE ? ESCAPE '\')";
+
+pub const TRANSACTION_SEARCH_LIKE_ORDER: &str =
+    " ORDER BY t.TRANSACTION_DATE DESC, t.TRANSACTION_ID DESC LIMIT ?";
+
+// ============================================================================
+// Transaction Service Test Queries
+// ============================================================================
+
+pub const TEST_TRANSACTION_CREATE_USERS_TABLE: &str = r#"
+CREATE TABLE USERS (
+    USER_ID INTEGER PRIMARY KEY,
+    NAME TEXT NOT NULL,
+    PAW TEXT NOT NULL,
+    ROLE INTEGER NOT NULL,
+    BASE_CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY',
+    ENTRY_DT DATETIME NOT NULL,
+    UPDATE_DT DATETIME
+)
+"#;
+
+pub const TEST_TRANSACTION_INSERT_USER: &str = "INSERT INTO USERS (USER_ID, NAME, PAW, ROLE, ENTRY_DT) VALUES (2, 'testuser', 'hash', 1, datetime('now'))";
+
+pub const TEST_TRANSACTION_CREATE_MEMOS_TABLE: &str = r#"
+CREATE TABLE MEMOS (
+    MEMO_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    MEMO_TEXT TEXT NOT NULL,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID)
+)
+"#;
+
+pub const TEST_TRANSACTION_CREATE_CATEGORY1_TABLE: &str = r#"
+CREATE TABLE CATEGORY1 (
+    USER_ID INTEGER NOT NULL,
+    CATEGORY1_CODE VARCHAR(50) NOT NULL,
     CATEGORY1_NAME TEXT NOT NULL,
     DISPLAY_ORDER INTEGER DEFAULT 0,
     IS_DISABLED INTEGER DEFAULT 0,
@@ -1939,6 +4108,7 @@ CREATE TABLE ACCOUNTS (
     INITIAL_BALANCE INTEGER DEFAULT 0,
     DISPLAY_ORDER INTEGER DEFAULT 0,
     IS_DISABLED INTEGER DEFAULT 0,
+    CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY',
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
     UNIQUE(USER_ID, ACCOUNT_CODE)
@@ -1949,6 +4119,10 @@ pub const TEST_TRANSACTION_INSERT_ACCOUNT_CASH: &str = "INSERT INTO ACCOUNTS (US
 
 pub const TEST_TRANSACTION_INSERT_ACCOUNT_BANK: &str = "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'BANK', '銀行', 'BANK')";
 
+pub const TEST_TRANSACTION_INSERT_ACCOUNT_NONE: &str = "INSERT INTO ACCOUNTS (USER_ID, ACCOUNT_CODE, ACCOUNT_NAME, TEMPLATE_CODE) VALUES (2, 'NONE', '指定なし', 'NONE')";
+
+pub const TEST_TRANSACTION_INSERT_CATEGORY1_INCOME: &str = "INSERT INTO CATEGORY1 (USER_ID, CATEGORY1_CODE, CATEGORY1_NAME) VALUES (2, 'INCOME', '収入')";
+
 pub const TEST_TRANSACTION_CREATE_HEADER_TABLE: &str = r#"
 CREATE TABLE TRANSACTIONS_HEADER (
     TRANSACTION_ID INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -1964,6 +4138,9 @@ CREATE TABLE TRANSACTIONS_HEADER (
     MEMO_ID INTEGER,
     IS_DISABLED INTEGER DEFAULT 0,
     IS_SCHEDULED INTEGER DEFAULT 0,
+    RULE_ID INTEGER,
+    RECONCILE_STATUS INTEGER NOT NULL DEFAULT 0,
+    CURRENCY_CODE TEXT NOT NULL DEFAULT 'JPY',
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME
 )
@@ -2028,6 +4205,8 @@ CREATE TABLE TRANSACTIONS_DETAIL (
     AMOUNT_INCLUDING_TAX INTEGER,
     PRODUCT_ID INTEGER,
     MEMO_ID INTEGER,
+    FROM_ACCOUNT_CODE VARCHAR(50),
+    TO_ACCOUNT_CODE VARCHAR(50),
     ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
     UPDATE_DT DATETIME,
     FOREIGN KEY (TRANSACTION_ID) REFERENCES TRANSACTIONS_HEADER(TRANSACTION_ID) ON DELETE CASCADE,
@@ -2099,12 +4278,75 @@ CREATE TABLE TRANSACTIONS_HEADER (
 )
 "#;
 
+// Pre-open-ended RECURRING_RULES schema: END_DATE NOT NULL, no
+// MATERIALIZED_UNTIL.
+pub const TEST_DB_CREATE_LEGACY_RECURRING_RULES_TABLE: &str = r#"
+CREATE TABLE RECURRING_RULES (
+    RULE_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    USER_ID INTEGER NOT NULL,
+    RULE_NAME TEXT,
+    PERIOD_UNIT TEXT NOT NULL,
+    PERIOD_INTERVAL INTEGER NOT NULL,
+    ANCHOR_DATE DATE,
+    DAY_OF_WEEK INTEGER,
+    MONTH_DAY_RULE_TYPE TEXT,
+    DAY_OF_MONTH INTEGER,
+    WEEK_OF_MONTH INTEGER,
+    MONTH_OF_YEAR INTEGER,
+    HOLIDAY_SHIFT_TYPE INTEGER DEFAULT 0,
+    START_DATE DATE NOT NULL,
+    END_DATE DATE NOT NULL,
+    SHOP_ID INTEGER,
+    CATEGORY1_CODE VARCHAR(50) NOT NULL,
+    FROM_ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    TO_ACCOUNT_CODE VARCHAR(50) NOT NULL,
+    TOTAL_AMOUNT INTEGER NOT NULL,
+    TAX_ROUNDING_TYPE INTEGER DEFAULT 0,
+    TAX_INCLUDED_TYPE INTEGER DEFAULT 1 NOT NULL,
+    MEMO_ID INTEGER,
+    IS_DISABLED INTEGER DEFAULT 0,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now', 'localtime')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (USER_ID) REFERENCES USERS(USER_ID) ON DELETE CASCADE,
+    FOREIGN KEY (USER_ID, CATEGORY1_CODE) REFERENCES CATEGORY1(USER_ID, CATEGORY1_CODE),
+    FOREIGN KEY (USER_ID, FROM_ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE),
+    FOREIGN KEY (USER_ID, TO_ACCOUNT_CODE) REFERENCES ACCOUNTS(USER_ID, ACCOUNT_CODE)
+)
+"#;
+
+// Single-template RECURRING_RULE_DETAILS schema: RULE_ID UNIQUE, no
+// PRODUCT_ID.
+pub const TEST_DB_CREATE_LEGACY_RECURRING_RULE_DETAILS_TABLE: &str = r#"
+CREATE TABLE RECURRING_RULE_DETAILS (
+    RULE_DETAIL_ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    RULE_ID INTEGER NOT NULL UNIQUE,
+    USER_ID INTEGER NOT NULL,
+    CATEGORY1_CODE VARCHAR(50) NOT NULL,
+    CATEGORY2_CODE VARCHAR(50),
+    CATEGORY3_CODE VARCHAR(50),
+    ITEM_NAME TEXT NOT NULL,
+    AMOUNT INTEGER NOT NULL,
+    TAX_AMOUNT INTEGER DEFAULT 0,
+    TAX_RATE INTEGER DEFAULT 8,
+    AMOUNT_INCLUDING_TAX INTEGER,
+    MEMO_ID INTEGER,
+    ENTRY_DT DATETIME NOT NULL DEFAULT (datetime('now')),
+    UPDATE_DT DATETIME,
+    FOREIGN KEY (RULE_ID) REFERENCES RECURRING_RULES(RULE_ID) ON DELETE CASCADE,
+    FOREIGN KEY (MEMO_ID) REFERENCES MEMOS(MEMO_ID),
+    CHECK (ITEM_NAME != '')
+)
+"#;
+
 pub const TEST_DB_COUNT_TABLE_COLUMN: &str =
     "SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?";
 
 pub const TEST_DB_COUNT_TABLE: &str =
     "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?";
 
+pub const TEST_DB_COUNT_VIEW: &str =
+    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'view' AND name = ?";
+
 pub const TEST_DB_COUNT_STANDARD_HOLIDAYS: &str =
     "SELECT COUNT(*) FROM HOLIDAYS_STANDARD WHERE LOCALE = 'JP'";
 
@@ -2119,6 +4361,16 @@ INSERT INTO TRANSACTIONS_HEADER
 VALUES (?, ?, ?, ?, ?, ?, ?)
 "#;
 
+// Tax-free detail line on the most recently inserted header.
+// Bindings: (category1, item_name, amount, from_account, to_account).
+pub const TEST_ACCOUNT_INSERT_DETAIL_LINE: &str = r#"
+INSERT INTO TRANSACTIONS_DETAIL
+    (TRANSACTION_ID, USER_ID, CATEGORY1_CODE, ITEM_NAME, AMOUNT, TAX_RATE,
+     FROM_ACCOUNT_CODE, TO_ACCOUNT_CODE)
+SELECT MAX(TRANSACTION_ID), 2, ?, ?, ?, 0, ?, ?
+FROM TRANSACTIONS_HEADER
+"#;
+
 pub const TEST_ACCOUNT_GET_IS_DISABLED: &str =
     "SELECT IS_DISABLED FROM ACCOUNTS WHERE USER_ID = ? AND ACCOUNT_CODE = ?";
 
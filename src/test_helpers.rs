@@ -146,10 +146,47 @@ pub mod database {
                 current_statement.clear();
             }
         }
+
+        // Registering a user creates its NONE account, which is audited
+        sqlx::query(crate::sql_queries::CREATE_AUDIT_LOG_TABLE)
+            .execute(&pool)
+            .await
+            .unwrap();
         
         pool
     }
 
+    /// Setup an in-memory test database with the schema the app actually
+    /// runs on: dbaccess.sql followed by every migration `lib.rs` applies at
+    /// startup. Use this instead of `setup_test_db` when a test touches
+    /// columns that only exist after migration (e.g. TRANSACTIONS_DETAIL.USER_ID).
+    pub async fn setup_migrated_test_db() -> SqlitePool {
+        let db = crate::db::Database::from_pool(connect_db(TEST_DB_URL).await.unwrap());
+        db.initialize().await.expect("initialize");
+        db.migrate_transactions().await.expect("migrate_transactions");
+        db.migrate_recurring().await.expect("migrate_recurring");
+        db.migrate_period_customization().await.expect("migrate_period_customization");
+        db.migrate_period_holiday_shift().await.expect("migrate_period_holiday_shift");
+        db.migrate_encryption_salt().await.expect("migrate_encryption_salt");
+        db.migrate_shops_unique().await.expect("migrate_shops_unique");
+        db.migrate_csv_import().await.expect("migrate_csv_import");
+        db.migrate_budgets().await.expect("migrate_budgets");
+        db.migrate_budget_rollover().await.expect("migrate_budget_rollover");
+        db.migrate_reconciliation().await.expect("migrate_reconciliation");
+        db.migrate_account_class().await.expect("migrate_account_class");
+        db.migrate_card_billing().await.expect("migrate_card_billing");
+        db.migrate_currency().await.expect("migrate_currency");
+        db.migrate_split_transactions().await.expect("migrate_split_transactions");
+        db.migrate_attachments().await.expect("migrate_attachments");
+        db.migrate_tags().await.expect("migrate_tags");
+        db.migrate_saved_filters().await.expect("migrate_saved_filters");
+        db.migrate_duplicate_dismissals().await.expect("migrate_duplicate_dismissals");
+        db.migrate_audit_log().await.expect("migrate_audit_log");
+        db.migrate_transaction_search().await.expect("migrate_transaction_search");
+        db.migrate_change_journal().await.expect("migrate_change_journal");
+        db.pool().clone()
+    }
+
     /// Create a test admin user in the database
     pub async fn create_test_admin(pool: &SqlitePool, username: &str, password: &str) -> i64 {
         let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();